@@ -0,0 +1,34 @@
+# Import the results of the cwe_checker as tags into Binary Ninja.
+#
+# Usage:
+# - Run the cwe_checker on a binary and save the annotations for Binary Ninja as a json file, e.g. with
+#   "cwe_checker BINARY --annotate-for binja --out annotations.json"
+# - Open the binary in Binary Ninja and run this file as a script from the Python console,
+#   e.g. with "exec(open('cwe_checker_binja_plugin.py').read())".
+#   Select the generated json file when prompted.
+
+import json
+
+from binaryninja.interaction import get_open_filename_input
+
+TAG_ICON = '⚠'
+
+
+def get_cwe_checker_annotations():
+    file_name = get_open_filename_input('Select the Binary Ninja annotations of the cwe_checker', '*.json')
+    with open(file_name) as json_file:
+        return json.load(json_file)
+
+
+def main():
+    """
+    Annotate cwe_checker results in Binary Ninja as tags at the corresponding addresses.
+    """
+    annotations = get_cwe_checker_annotations()
+    for tag_type in annotations['tag_types']:
+        if tag_type not in bv.tag_types:
+            bv.create_tag_type(tag_type, TAG_ICON)
+    for tag in annotations['tags']:
+        bv.add_tag(tag['address'], tag['tag_type'], tag['data'])
+
+main()
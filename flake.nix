@@ -58,6 +58,7 @@
       mkdir -p $out
       cp $src/config.json $out
       cp $src/lkm_config.json $out
+      cp $src/function_hashes.json $out
       ln -s ${cwe-ghidra-json} $out/ghidra.json
       '';
       # Target bin for 'nix run'.
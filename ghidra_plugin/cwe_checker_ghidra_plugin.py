@@ -4,19 +4,21 @@
 # - Copy this file into the Ghidra scripts folder
 # - Run the cwe_checker on a binary and save its output as a json file, e.g. with
 #   "cwe_checker BINARY --json --out output.json"
+#   Alternatively, save bookmarks and comments for the CWE warnings and the recovered strings with
+#   "cwe_checker BINARY --ghidra-markers markers.json"
 # - Open the binary in Ghidra and run this file as a script. Select the generated json file when prompted.
 
 import json
 
 
-def bookmark_cwe(ghidra_address, text):
+def bookmark_cwe(ghidra_address, text, category='[cwe_checker]'):
     previous_bookmarks = getBookmarks(ghidra_address)
     for bookmark in previous_bookmarks:
-        if '[cwe_checker]' == bookmark.getCategory():
+        if category == bookmark.getCategory():
             if text not in bookmark.getComment():
-                createBookmark(ghidra_address, '[cwe_checker]', bookmark.getComment() + '\n' + text)
+                createBookmark(ghidra_address, category, bookmark.getComment() + '\n' + text)
             return
-    createBookmark(ghidra_address, '[cwe_checker]', text)
+    createBookmark(ghidra_address, category, text)
     return
 
 
@@ -42,12 +44,35 @@ def get_cwe_checker_output():
         return json.load(json_file)
 
 
+def get_ghidra_address(address_string):
+    if address_string is None:
+        return currentProgram.getMinAddress().add(0)
+    return currentProgram.getAddressFactory().getAddress(address_string)
+
+
+def annotate_markers(markers):
+    """
+    Import the bookmarks and comments generated with the --ghidra-markers command line option.
+    """
+    for bookmark in markers['bookmarks']:
+        bookmark_cwe(get_ghidra_address(bookmark['address']), bookmark['text'], bookmark['category'])
+    for comment in markers['comments']:
+        ghidra_address = get_ghidra_address(comment['address'])
+        if comment['kind'] == 'Eol':
+            comment_cwe_eol(ghidra_address, comment['text'])
+        else:
+            comment_cwe_pre(ghidra_address, comment['text'])
+
+
 def main():
     """
     Annotate cwe_checker results in Ghidra as end-of-line
     comments and bookmarks to the corresponding addresses.
     """
     warnings = get_cwe_checker_output()
+    if isinstance(warnings, dict):
+        annotate_markers(warnings)
+        return
     for warning in warnings:
         if len(warning['addresses']) == 0:
             cwe_text =  '[' + warning['name'] + '] ' + warning['description']
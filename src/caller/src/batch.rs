@@ -0,0 +1,459 @@
+//! Batch analysis of many binaries, e.g. of an unpacked firmware image.
+//!
+//! The binaries are either all files in a directory or the files listed in the manifest of an unpacker,
+//! see [`InputAdapter`].
+//! They are lifted and analyzed concurrently by a fixed number of workers.
+//! Each worker runs at most one headless Ghidra instance at a time,
+//! so that the number of workers bounds the number of concurrent Ghidra instances and the memory usage.
+//! A Ghidra instance lifts a chunk of binaries at once,
+//! so that the startup cost of Ghidra is not paid for every binary.
+//! The CWE warnings of each binary are written to a result file of their own.
+//! Warnings in functions that are marked as safe by their names are not reported,
+//! see [`Allowlist`].
+//! Each warning contains the hash of the function containing it,
+//! so that warnings in code shared between the binaries can be grouped by the `aggregate` subcommand.
+//! Warnings in functions of the bundled hash database are labelled as being in statically linked third-party code,
+//! see [`HashDatabase`].
+//! Binaries that cannot be lifted or analyzed do not abort the batch run,
+//! even if the analysis panics.
+//! Instead the error is written to an error file.
+//! The findings and the hardening reports of all binaries are aggregated in the file `firmware_report.json`,
+//! see [`FirmwareReport`].
+
+use super::{filter_modules_for_partial_run, normalize_project, with_analysis_results, BatchArgs};
+use anyhow::{anyhow, Context, Error};
+use cwe_checker_lib::analysis::function_hashing::{
+    add_function_hashes, label_third_party_code, HashDatabase,
+};
+use cwe_checker_lib::checkers::{schedule_analyses, Checker};
+use cwe_checker_lib::intermediate_representation::Project;
+use cwe_checker_lib::pipeline::{
+    disassemble_binaries, DirectoryInput, FirmwareReport, InputAdapter, InputBinary, ManifestInput,
+};
+use cwe_checker_lib::utils::allowlist::Allowlist;
+use cwe_checker_lib::utils::debug;
+use cwe_checker_lib::utils::debug_info::DebugInfo;
+use cwe_checker_lib::utils::hardening::HardeningReport;
+use cwe_checker_lib::utils::log::{format_cwe_warnings, CweWarning, OutputFormat, WithLogs};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Analyze all binaries of the input directory or manifest.
+///
+/// The result file of a binary has the same relative path in the output directory
+/// as the binary in the original filesystem, with an extension depending on the output format appended.
+/// A summary with the number of warnings or the error of each binary is printed to stdout.
+pub fn run_batch(args: &BatchArgs) -> Result<(), Error> {
+    let mut modules = cwe_checker_lib::checkers::get_modules();
+    if let Some(partial_module_list) = &args.partial {
+        filter_modules_for_partial_run(&mut modules, partial_module_list);
+    }
+    let config: Option<serde_json::Value> = match &args.config {
+        Some(config_path) => {
+            let file = std::io::BufReader::new(std::fs::File::open(config_path)?);
+            Some(
+                serde_json::from_reader(file)
+                    .context("Parsing of the configuration file failed")?,
+            )
+        }
+        None => None,
+    };
+    let input: Box<dyn InputAdapter> = match (&args.directory, &args.manifest) {
+        (_, Some(manifest)) => Box::new(ManifestInput {
+            manifest: PathBuf::from(manifest),
+        }),
+        (Some(directory), None) => Box::new(DirectoryInput {
+            root: PathBuf::from(directory),
+        }),
+        (None, None) => return Err(anyhow!("Either a directory or a manifest is required.")),
+    };
+    let binaries = input.get_binaries()?;
+    let format: OutputFormat = args.format.into();
+    let verbosity = if args.verbose {
+        debug::Verbosity::Verbose
+    } else {
+        debug::Verbosity::default()
+    };
+    let debug_settings = debug::SettingsBuilder::default()
+        .set_verbosity(verbosity)
+        .build();
+
+    let hash_database = match HashDatabase::load_bundled() {
+        Ok(hash_database) => Some(hash_database),
+        Err(error) => {
+            println!(
+                "[cwe_checker] No hash database of library functions loaded: {:#}",
+                Error::from(error)
+            );
+            None
+        }
+    };
+
+    let mut report = analyze_binaries(
+        &binaries,
+        Path::new(&args.out_dir),
+        format,
+        args.jobs,
+        args.binaries_per_ghidra,
+        |binary_paths| match disassemble_binaries(binary_paths, &debug_settings) {
+            Ok(lifted_binaries) => lifted_binaries
+                .into_iter()
+                .map(|lifted_binary| lifted_binary.map_err(Error::from))
+                .collect(),
+            Err(error) => {
+                let error = format!("{:#}", Error::from(error));
+                binary_paths
+                    .iter()
+                    .map(|_| Err(anyhow!("{error}")))
+                    .collect()
+            }
+        },
+        |(binary, project)| {
+            analyze_binary(
+                binary,
+                project,
+                &modules,
+                config.as_ref(),
+                args.partial.is_some(),
+                hash_database.as_ref(),
+                &debug_settings,
+            )
+        },
+    );
+    report.sort();
+    let failures = report
+        .binaries
+        .iter()
+        .filter(|result| result.error.is_some())
+        .count();
+    write_result(
+        &Path::new(&args.out_dir).join("firmware_report"),
+        "json",
+        &serde_json::to_string_pretty(&report)?,
+    )?;
+    println!(
+        "[cwe_checker] Analyzed {} of {} binaries successfully.",
+        binaries.len() - failures,
+        binaries.len()
+    );
+
+    Ok(())
+}
+
+/// Analyze the binaries with `jobs` concurrent workers and write the result file of each binary.
+///
+/// Each worker lifts chunks of at most `chunk_size` binaries at once with `lift`,
+/// which returns the lifted binaries in the order of the given paths.
+/// Then it analyzes each lifted binary with `analyze`.
+/// The result file of a binary has the same relative path in the output directory
+/// as the binary in the original filesystem.
+/// If the lifting or analysis of a binary fails or panics,
+/// the error is written to a result file with the extension `error` instead.
+/// Returns the aggregated results of all binaries in no particular order.
+fn analyze_binaries<L>(
+    binaries: &[InputBinary],
+    out_dir: &Path,
+    format: OutputFormat,
+    jobs: usize,
+    chunk_size: usize,
+    lift: impl Fn(&[&Path]) -> Vec<Result<L, Error>> + Sync,
+    analyze: impl Fn(L) -> Result<(Vec<CweWarning>, Option<HardeningReport>), Error> + Sync,
+) -> FirmwareReport {
+    let chunks = get_chunks(binaries, chunk_size);
+    let next_chunk = AtomicUsize::new(0);
+    let report = Mutex::new(FirmwareReport::default());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.max(1) {
+            scope.spawn(|| {
+                while let Some(chunk) = chunks.get(next_chunk.fetch_add(1, Ordering::Relaxed)) {
+                    let binary_paths: Vec<&Path> =
+                        chunk.iter().map(|binary| binary.path.as_path()).collect();
+                    let lifted_binaries = match catch_panic(|| lift(&binary_paths)) {
+                        Ok(lifted_binaries) => lifted_binaries,
+                        Err(error) => {
+                            let error = format!("{error:#}");
+                            chunk.iter().map(|_| Err(anyhow!("{error}"))).collect()
+                        }
+                    };
+                    for (binary, lifted_binary) in chunk.iter().zip(lifted_binaries) {
+                        let relative_path = binary.original_path.trim_start_matches('/');
+                        let output_path = out_dir.join(relative_path);
+                        let result = lifted_binary
+                            .and_then(|lifted_binary| catch_panic(|| analyze(lifted_binary))?)
+                            .and_then(|(warnings, hardening)| {
+                                let warning_refs: Vec<&CweWarning> = warnings.iter().collect();
+                                write_result(
+                                    &output_path,
+                                    get_file_extension(format),
+                                    &format_cwe_warnings(&warning_refs, format),
+                                )?;
+                                Ok((warnings, hardening))
+                            });
+                        let mut report = report.lock().unwrap();
+                        match result {
+                            Ok((warnings, hardening)) => {
+                                println!(
+                                    "[cwe_checker] {}: {} warnings",
+                                    binary.original_path,
+                                    warnings.len()
+                                );
+                                report.add_warnings((*binary).clone(), &warnings, hardening);
+                            }
+                            Err(error) => {
+                                let _ =
+                                    write_result(&output_path, "error", &format!("{error:#}\n"));
+                                println!(
+                                    "[cwe_checker] {}: failed: {error:#}",
+                                    binary.original_path
+                                );
+                                report.add_error((*binary).clone(), format!("{error:#}"));
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    report.into_inner().unwrap()
+}
+
+/// Split the binaries into chunks of at most `chunk_size` binaries with distinct file names.
+///
+/// The binaries of a chunk are lifted by the same Ghidra instance,
+/// which names the imported programs after their file names.
+fn get_chunks(binaries: &[InputBinary], chunk_size: usize) -> Vec<Vec<&InputBinary>> {
+    let mut chunks: Vec<Vec<&InputBinary>> = Vec::new();
+    for binary in binaries {
+        let file_name = binary.path.file_name();
+        let chunk = chunks.iter_mut().find(|chunk| {
+            chunk.len() < chunk_size.max(1)
+                && chunk
+                    .iter()
+                    .all(|other_binary| other_binary.path.file_name() != file_name)
+        });
+        match chunk {
+            Some(chunk) => chunk.push(binary),
+            None => chunks.push(vec![binary]),
+        }
+    }
+
+    chunks
+}
+
+/// Run the closure and return an error if it panics,
+/// so that a bug triggered by one binary does not abort the batch run.
+fn catch_panic<T>(closure: impl FnOnce() -> T) -> Result<T, Error> {
+    std::panic::catch_unwind(AssertUnwindSafe(closure)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        anyhow!("The analysis panicked: {message}")
+    })
+}
+
+/// Analyze a single lifted binary with the given checkers
+/// and return the CWE warnings together with the hardening report of the binary.
+///
+/// If no configuration is given, the standard configuration file is used.
+/// For a `partial` run all given checkers are executed,
+/// otherwise the checkers for the kind of binary (standard or kernel module) are selected like in a normal run.
+fn analyze_binary(
+    binary: Vec<u8>,
+    mut project: WithLogs<Project>,
+    modules: &[&'static dyn Checker],
+    config: Option<&serde_json::Value>,
+    partial: bool,
+    hash_database: Option<&HashDatabase>,
+    debug_settings: &debug::Settings,
+) -> Result<(Vec<CweWarning>, Option<HardeningReport>), Error> {
+    let config = normalize_project(&mut project, config, debug_settings)?;
+    let is_lkm = project.runtime_memory_image.is_lkm;
+    let modules: Vec<&dyn Checker> = modules
+        .iter()
+        .copied()
+        .filter(|module| {
+            partial
+                || if is_lkm {
+                    cwe_checker_lib::checkers::MODULES_LKM.contains(&module.name())
+                } else {
+                    module.name() != "CWE78"
+                }
+        })
+        .collect();
+
+    let cwe_warnings: Vec<CweWarning> = with_analysis_results(
+        &binary,
+        &project,
+        &config,
+        &schedule_analyses(&modules, &[]),
+        |analysis_results| {
+            Ok(modules
+                .iter()
+                .flat_map(|module| {
+                    let _span = tracing::info_span!("checker", name = module.name()).entered();
+                    module
+                        .run(analysis_results, &config[module.name()], debug_settings)
+                        .into_object()
+                })
+                .collect())
+        },
+    )?;
+    // Only functions marked as safe by their names are known for all binaries of the batch.
+    let mut cwe_warnings = Allowlist::new(&project)
+        .filter_cwe_warnings(cwe_warnings)
+        .into_object();
+    add_function_hashes(&project, cwe_warnings.iter_mut());
+    if let Some(hash_database) = hash_database {
+        label_third_party_code(&project, hash_database, cwe_warnings.iter_mut());
+    }
+    let debug_info = DebugInfo::parse(&binary).ok().flatten();
+    for cwe_warning in cwe_warnings.iter_mut() {
+        cwe_warning.demangle_symbols();
+        if let Some(debug_info) = &debug_info {
+            debug_info.add_source_location(cwe_warning, project.program.term.address_base_offset);
+        }
+    }
+
+    Ok((cwe_warnings, HardeningReport::parse(&binary).ok()))
+}
+
+/// Get the extension of the result files for the given output format.
+fn get_file_extension(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Text => "txt",
+        OutputFormat::Json => "json",
+        OutputFormat::Csv => "csv",
+        OutputFormat::Junit => "xml",
+    }
+}
+
+/// Write the content to the path with the extension appended, creating missing parent directories.
+fn write_result(path: &Path, extension: &str, content: &str) -> Result<(), Error> {
+    let mut file_name = path.as_os_str().to_os_string();
+    file_name.push(format!(".{extension}"));
+    let path = PathBuf::from(file_name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, content).context(format!("Writing to {} failed", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn binary(original_path: &str) -> InputBinary {
+        InputBinary {
+            path: PathBuf::from(original_path.trim_start_matches('/')),
+            original_path: original_path.to_string(),
+            permissions: None,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn result_file_per_binary() {
+        let out_dir =
+            std::env::temp_dir().join(format!("cwe_checker_batch_{}", std::process::id()));
+        let binaries = [
+            binary("/usr/sbin/httpd"),
+            binary("/bin/ping"),
+            binary("/lib/libbroken.so"),
+            binary("/lib/libpanic.so"),
+        ];
+        let mut report = analyze_binaries(
+            &binaries,
+            &out_dir,
+            OutputFormat::Json,
+            2,
+            3,
+            |paths| {
+                paths
+                    .iter()
+                    .map(|path| {
+                        if path.ends_with("libbroken.so") {
+                            return Err(anyhow!("Lifting failed"));
+                        }
+                        Ok(path.to_path_buf())
+                    })
+                    .collect()
+            },
+            |path| {
+                if path.ends_with("libpanic.so") {
+                    panic!("Bug in a checker");
+                }
+                let warning = CweWarning::new("CWE676", "0.1", path.display());
+                Ok((vec![warning.clone(), warning], None))
+            },
+        );
+        report.sort();
+
+        let read_result = |path: &str| std::fs::read_to_string(out_dir.join(path));
+        let warnings: serde_json::Value =
+            serde_json::from_str(&read_result("usr/sbin/httpd.json").unwrap()).unwrap();
+        assert_eq!(warnings.as_array().unwrap().len(), 2);
+        assert_eq!(warnings[0]["description"], "usr/sbin/httpd");
+        let warnings: serde_json::Value =
+            serde_json::from_str(&read_result("bin/ping.json").unwrap()).unwrap();
+        assert_eq!(warnings[0]["description"], "bin/ping");
+        // A failing binary gets an error file instead of a result file.
+        assert_eq!(
+            read_result("lib/libbroken.so.error").unwrap(),
+            "Lifting failed\n"
+        );
+        assert!(read_result("lib/libbroken.so.json").is_err());
+        // A panic during the analysis is reported like other errors.
+        assert_eq!(
+            read_result("lib/libpanic.so.error").unwrap(),
+            "The analysis panicked: Bug in a checker\n"
+        );
+        std::fs::remove_dir_all(&out_dir).unwrap();
+
+        // The failing binaries do not abort the analysis of the other binaries.
+        assert_eq!(report.binaries.len(), 4);
+        assert_eq!(report.binaries[0].binary.original_path, "/bin/ping");
+        assert_eq!(report.binaries[0].warning_counts["CWE676"], 2);
+        assert_eq!(report.binaries[1].binary.original_path, "/lib/libbroken.so");
+        assert_eq!(report.binaries[1].error, Some("Lifting failed".to_string()));
+        assert!(report.binaries[1].warning_counts.is_empty());
+        assert_eq!(
+            report.binaries[2].error,
+            Some("The analysis panicked: Bug in a checker".to_string())
+        );
+        assert_eq!(report.binaries[3].error, None);
+    }
+
+    #[test]
+    fn chunks_with_distinct_file_names() {
+        let binaries = [
+            binary("/bin/busybox"),
+            binary("/bin/ping"),
+            binary("/usr/bin/busybox"),
+            binary("/usr/bin/httpd"),
+            binary("/sbin/busybox"),
+        ];
+        let chunks: Vec<Vec<&str>> = get_chunks(&binaries, 2)
+            .into_iter()
+            .map(|chunk| {
+                chunk
+                    .into_iter()
+                    .map(|binary| binary.original_path.as_str())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            chunks,
+            vec![
+                vec!["/bin/busybox", "/bin/ping"],
+                vec!["/usr/bin/busybox", "/usr/bin/httpd"],
+                vec!["/sbin/busybox"],
+            ]
+        );
+    }
+}
@@ -5,24 +5,58 @@
 
 extern crate cwe_checker_lib; // Needed for the docstring-link to work
 
+use anyhow::anyhow;
 use anyhow::Context;
 use anyhow::Error;
-use clap::{Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
+use cwe_checker_lib::abstract_domain::operation_counters;
+use cwe_checker_lib::analysis::function_hashing::{
+    add_function_hashes, label_third_party_code, HashDatabase,
+};
 use cwe_checker_lib::analysis::graph;
-use cwe_checker_lib::checkers::CweModule;
-use cwe_checker_lib::pipeline::{disassemble_binary, AnalysisResults};
+use cwe_checker_lib::analysis::library_summaries::{
+    Config as LibrarySummariesConfig, LibrarySummaries,
+};
+use cwe_checker_lib::analysis::slicing::{get_terms_at_address, DependencyGraph};
+use cwe_checker_lib::analysis::string_abstraction::report::get_call_site_strings;
+use cwe_checker_lib::analysis::symbolic_execution::confirm_cwe_warnings;
+use cwe_checker_lib::checkers::{schedule_analyses, Analysis, Checker};
+use cwe_checker_lib::intermediate_representation::{
+    dump_pseudo_assembly, NormalizationConfig, Project, Tid, TidAddress,
+};
+use cwe_checker_lib::pipeline::{
+    disassemble_binary, disassemble_binary_with_ir_cache, AnalysisResults, PrecisionReport,
+    ProjectReport, RunStatistics,
+};
+use cwe_checker_lib::utils::allowlist::Allowlist;
+use cwe_checker_lib::utils::annotations::{format_annotations, AnnotationTarget};
 use cwe_checker_lib::utils::binary::BareMetalConfig;
 use cwe_checker_lib::utils::debug;
-use cwe_checker_lib::utils::log::{print_all_messages, CweWarning, LogLevel, LogMessage};
+use cwe_checker_lib::utils::debug_info::DebugInfo;
+use cwe_checker_lib::utils::ghidra_markers::GhidraMarkers;
+use cwe_checker_lib::utils::hardening::HardeningReport;
+use cwe_checker_lib::utils::log::{
+    group_cwe_warnings, print_all_messages, print_logs_and_output, CweWarning, LogLevel,
+    LogMessage, OutputFormat, WithLogs,
+};
+use cwe_checker_lib::utils::log_layer::LogMessageLayer;
 use cwe_checker_lib::utils::read_config_file;
+use cwe_checker_lib::utils::state_diff::diff_states;
 
-use std::collections::{BTreeSet, HashSet};
+use std::collections::HashSet;
 use std::convert::From;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::prelude::*;
 
+mod batch;
 mod cfg_stats;
+mod server;
+
+use server::Server;
 
 #[derive(ValueEnum, Clone, Debug, Copy)]
 /// Selects which kind of debug output is displayed.
@@ -67,6 +101,9 @@ pub enum CliDebugMode {
     IrStackPointerAlignmentSubstituted,
     /// The final IR.
     IrOptimized,
+    /// After temporary registers have been propagated into their uses.
+    /// Only generated if the normalization is enabled in the configuration.
+    IrTemporariesPropagated,
     /// Whole-program call graph.
     Cg,
     /// Whole-program control flow graph.
@@ -105,6 +142,7 @@ impl From<&CliDebugMode> for debug::Stage {
                 debug::Stage::Ir(debug::IrForm::StackPointerAlignmentSubstituted)
             }
             IrOptimized => debug::Stage::Ir(debug::IrForm::Optimized),
+            IrTemporariesPropagated => debug::Stage::Ir(debug::IrForm::TemporariesPropagated),
             Cg => debug::Stage::CallGraph,
             Cfg => debug::Stage::ControlFlowGraph,
             Pi => debug::Stage::Pi,
@@ -113,11 +151,19 @@ impl From<&CliDebugMode> for debug::Stage {
 }
 
 #[derive(Debug, Parser)]
-#[command(version, about)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 /// Find vulnerable patterns in binary executables
 struct CmdlineArgs {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The path to the binary.
-    #[arg(required_unless_present("module_versions"), value_parser = check_file_existence)]
+    #[arg(required_unless_present_any(["module_versions", "list_checkers"]), value_parser = check_file_existence)]
     binary: Option<String>,
 
     /// Path to a custom configuration file to use instead of the standard one.
@@ -135,10 +181,24 @@ struct CmdlineArgs {
     #[arg(long, short)]
     partial: Option<String>,
 
-    /// Generate JSON output.
-    #[arg(long, short)]
+    /// Generate JSON output. Shorthand for "--format json".
+    #[arg(long, short, conflicts_with("format"))]
     json: bool,
 
+    /// The output format of the CWE warnings.
+    #[arg(long, value_enum, default_value_t = CliOutputFormat::Text)]
+    format: CliOutputFormat,
+
+    /// Output annotations of the CWE warnings for the given reverse engineering tool
+    /// instead of the CWE warnings themselves.
+    ///
+    /// For Binary Ninja a JSON file of tags is generated,
+    /// which can be imported with the script `binja_plugin/cwe_checker_binja_plugin.py`.
+    /// For radare2 a script with comments and flags is generated,
+    /// which can be loaded with `r2 -i SCRIPT BINARY`.
+    #[arg(long, value_enum, conflicts_with_all(["json", "format"]))]
+    annotate_for: Option<CliAnnotationTarget>,
+
     /// Do not print log messages. This prevents polluting stdout for json output.
     #[arg(long, short)]
     quiet: bool,
@@ -147,6 +207,79 @@ struct CmdlineArgs {
     #[arg(long, short, conflicts_with("quiet"))]
     verbose: bool,
 
+    /// Record the events of the instrumented analyses and checkers up to the given level,
+    /// e.g. the runtime of each analysis and checker and summaries of the fixpoint computations.
+    ///
+    /// The events are printed together with the other log messages.
+    /// The "trace" level also records events for each function during the fixpoint computations.
+    #[arg(long, value_enum, conflicts_with("quiet"))]
+    log_level: Option<CliLogLevel>,
+
+    /// Print the events of the instrumented analyses and checkers as JSON lines to stderr
+    /// instead of printing them together with the other log messages.
+    ///
+    /// Uses the "info" level if no "--log-level" is given.
+    #[arg(long)]
+    log_json: bool,
+
+    /// Group CWE warnings generated by different checks at the same address.
+    ///
+    /// For each address only one warning is reported, the other warnings are listed as related findings.
+    #[arg(long)]
+    group_warnings: bool,
+
+    /// Try to confirm the warnings of selected checks by bounded symbolic execution.
+    ///
+    /// Warnings for which a feasible path through their function was found are marked as confirmed.
+    /// The checks and the solver are configured in the "WitnessValidation" section of the configuration.
+    #[arg(long)]
+    confirm_findings: bool,
+
+    /// Explain why warnings were generated.
+    ///
+    /// Warnings of supporting checks list the abstract values that triggered them
+    /// and the merges at which these values lost precision during the Pointer Inference.
+    /// Recording the merges slows down the Pointer Inference.
+    #[arg(long)]
+    explain: bool,
+
+    /// Write a report of the program points at which register values become Top
+    /// as JSON to the given file.
+    ///
+    /// The report ranks the merges and the called functions by the number of values that became Top through them,
+    /// e.g. to find extern functions for which a library summary would improve the analysis.
+    /// Recording the program points slows down the Pointer Inference.
+    #[arg(long)]
+    precision_report: Option<String>,
+
+    /// Path to a JSON file that maps the entry addresses of functions
+    /// to the CWEs that the functions were verified to be safe for,
+    /// e.g. {"0x401000": ["CWE-134"]}.
+    ///
+    /// Warnings whose addresses are all contained in such functions are not reported.
+    /// Functions whose names end with "_cwe_safe_" (optionally followed by CWE numbers like "_cwe_safe_134_416")
+    /// are treated as safe without an annotation file.
+    #[arg(long, value_parser = check_file_existence)]
+    safe_annotations: Option<String>,
+
+    /// Add the position-independent hash of the function containing each warning to the warning.
+    ///
+    /// Warnings in functions with the same hash in different binaries, e.g. in statically linked library code,
+    /// are grouped by the `aggregate` subcommand.
+    #[arg(long)]
+    function_hashes: bool,
+
+    /// Path to a hash database of library functions to use instead of the bundled one.
+    ///
+    /// Warnings in functions whose hashes are contained in the database are labelled as
+    /// being in statically linked third-party code. Databases are generated with the `hash-library` subcommand.
+    #[arg(long, value_parser = check_file_existence)]
+    hash_database: Option<String>,
+
+    /// Do not report warnings in statically linked third-party code, see `--hash-database`.
+    #[arg(long)]
+    skip_third_party_code: bool,
+
     /// Include various statistics in the log messages.
     /// This can be helpful for assessing the analysis quality for the input binary.
     #[arg(long, conflicts_with("quiet"))]
@@ -158,10 +291,52 @@ struct CmdlineArgs {
     #[arg(long, value_parser = check_file_existence)]
     bare_metal_config: Option<String>,
 
+    /// Path to a shared library that the binary imports functions from.
+    /// Can be given multiple times.
+    ///
+    /// The functions exported by the libraries are summarized,
+    /// so that calls to them are not treated as calls to opaque extern functions.
+    /// E.g. the CWE78 check then also checks calls to library functions that pass a parameter on to `system`.
+    #[arg(long, value_parser = check_file_existence)]
+    library: Vec<String>,
+
+    /// Path to a file with summaries of shared library functions
+    /// generated by `cwe_checker summarize`. Can be given multiple times.
+    ///
+    /// The summaries are used like the summaries computed for the `--library` option.
+    #[arg(long, value_parser = check_file_existence)]
+    summaries: Vec<String>,
+
+    /// Write metadata and statistics about the run as JSON to the given file,
+    /// e.g. the runtimes of the analyses and the number of warnings per checker.
+    #[arg(long)]
+    stats: Option<String>,
+
+    /// Write bookmarks and comments for the CWE warnings and for the recovered strings
+    /// as JSON to the given file.
+    ///
+    /// The file can be imported into Ghidra with the script `ghidra_plugin/cwe_checker_ghidra_plugin.py`.
+    /// The strings are recovered by the string abstraction analysis,
+    /// which is computed even if no enabled check depends on it.
+    #[arg(long)]
+    ghidra_markers: Option<String>,
+
+    /// Path to a file for caching the lifted IR of the binary.
+    ///
+    /// If the file contains the IR of the binary then it is used instead of lifting the binary again.
+    /// Otherwise the binary is lifted and the IR is saved to the file.
+    #[arg(long)]
+    ir_cache: Option<String>,
+
     /// Prints out the version numbers of all known modules.
     #[arg(long)]
     module_versions: bool,
 
+    /// Prints out all known checkers together with the CWEs they cover
+    /// and the analyses they depend on.
+    #[arg(long)]
+    list_checkers: bool,
+
     /// Output for debugging purposes.
     /// The current behavior of this flag is unstable and subject to change.
     #[arg(long, hide(true))]
@@ -175,6 +350,384 @@ struct CmdlineArgs {
     /// Print some statistics and metrics of the IR-program's CFG and exit.
     #[arg(long, hide(true))]
     cfg_stats: bool,
+
+    /// Count the merge, normalization and widening operations of the abstract domains
+    /// and print the counters to stderr after the analysis.
+    #[arg(long, hide(true))]
+    bench_mode: bool,
+
+    /// Print the interprocedural backward slice of the instruction at the given
+    /// (hexadecimal) address and exit.
+    #[arg(long, hide(true))]
+    slice: Option<String>,
+
+    /// Print the forward slice instead of the backward slice for the `--slice` option.
+    #[arg(long, hide(true), requires("slice"))]
+    slice_forward: bool,
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy)]
+/// Selects the output format of the CWE warnings.
+pub enum CliOutputFormat {
+    /// Human-readable text.
+    Text,
+    /// JSON.
+    Json,
+    /// Comma-separated values, e.g. for spreadsheets.
+    Csv,
+    /// JUnit XML, e.g. for test report viewers of CI systems.
+    Junit,
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy)]
+/// Selects the analysis whose abstract state is inspected.
+pub enum CliInspectedAnalysis {
+    /// The Pointer Inference, i.e. the values of registers and the content of memory objects.
+    #[value(alias("pi"))]
+    PointerInference,
+    /// The string abstraction, i.e. the pointers to strings and the abstract values of the strings.
+    String,
+}
+
+impl From<CliOutputFormat> for OutputFormat {
+    fn from(format: CliOutputFormat) -> Self {
+        match format {
+            CliOutputFormat::Text => OutputFormat::Text,
+            CliOutputFormat::Json => OutputFormat::Json,
+            CliOutputFormat::Csv => OutputFormat::Csv,
+            CliOutputFormat::Junit => OutputFormat::Junit,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq)]
+/// Selects the most verbose level of the recorded events.
+pub enum CliLogLevel {
+    /// Errors.
+    Error,
+    /// Warnings.
+    Warn,
+    /// Informational events, e.g. the runtimes of analyses and checkers.
+    Info,
+    /// Debug events, e.g. summaries of fixpoint computations.
+    Debug,
+    /// Events for each function during fixpoint computations.
+    Trace,
+}
+
+impl From<CliLogLevel> for tracing::Level {
+    fn from(level: CliLogLevel) -> Self {
+        match level {
+            CliLogLevel::Error => tracing::Level::ERROR,
+            CliLogLevel::Warn => tracing::Level::WARN,
+            CliLogLevel::Info => tracing::Level::INFO,
+            CliLogLevel::Debug => tracing::Level::DEBUG,
+            CliLogLevel::Trace => tracing::Level::TRACE,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy)]
+/// Selects the reverse engineering tool for which the CWE warnings are annotated.
+pub enum CliAnnotationTarget {
+    /// Binary Ninja.
+    Binja,
+    /// radare2.
+    R2,
+}
+
+impl From<CliAnnotationTarget> for AnnotationTarget {
+    fn from(target: CliAnnotationTarget) -> Self {
+        match target {
+            CliAnnotationTarget::Binja => AnnotationTarget::BinaryNinja,
+            CliAnnotationTarget::R2 => AnnotationTarget::Radare2,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Inspect the intermediate representation (IR) that a binary is lifted to.
+    Ir {
+        #[command(subcommand)]
+        command: IrCommand,
+    },
+    /// Print the abstract string arguments of all calls to string functions.
+    ///
+    /// The string arguments are computed by the string abstraction analysis.
+    Strings(StringsArgs),
+    /// Print the abstract state that an analysis computed directly before the instructions at an address.
+    ///
+    /// Useful for finding out why a check did or did not generate a warning at an instruction.
+    /// With `--diff-states` the changes of the state between two addresses are printed instead,
+    /// e.g. for finding out where a string value was lost.
+    Inspect(InspectArgs),
+    /// Analyze a binary once and answer queries about it as a JSON-RPC server over stdio.
+    ///
+    /// The server reads one JSON-RPC request per line from stdin and writes one response per line to stdout.
+    /// Supported methods are `list_checkers`, `run_checker`, `get_strings`, `get_state`, `get_slice` and `shutdown`.
+    Serve(ServeArgs),
+    /// Analyze all binaries in a directory and write one result file per binary.
+    ///
+    /// Several binaries are lifted and analyzed concurrently, e.g. for scanning the files of a firmware image.
+    Batch(BatchArgs),
+    /// Aggregate the JSON result files of many binaries of one firmware image or project into a single report.
+    ///
+    /// The report contains the number of warnings of each check in each binary
+    /// and groups warnings in code that is shared between binaries (see the `--function-hashes` option).
+    Aggregate(AggregateArgs),
+    /// Add the position-independent hashes of the functions of a binary to a hash database of library functions.
+    ///
+    /// The binary should statically link the library, e.g. a small test program.
+    /// Warnings in functions contained in the database are labelled as being in statically linked third-party code.
+    HashLibrary(HashLibraryArgs),
+    /// Compute summaries of the functions exported by a shared library and save them as JSON.
+    ///
+    /// The summaries can be loaded with the `--summaries` option when analyzing binaries linking the library,
+    /// so that the library does not have to be analyzed again for each binary.
+    Summarize(SummarizeArgs),
+    /// Print the compile-time exploit mitigations of a binary, e.g. RELRO, NX, PIE and stack canaries.
+    ///
+    /// The mitigations are read from the headers of the binary, so the binary is not lifted with Ghidra.
+    Hardening(HardeningArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum IrCommand {
+    /// Print the lifted IR of a binary as readable pseudo-assembly.
+    Dump(IrDumpArgs),
+}
+
+#[derive(Debug, Args)]
+struct StringsArgs {
+    /// The path to the binary.
+    #[arg(value_parser = check_file_existence)]
+    binary: String,
+
+    /// Only print calls to the extern functions with the given name. Can be used multiple times.
+    ///
+    /// Defaults to the string functions in the "StringAbstraction" section of the configuration file.
+    #[arg(long, short)]
+    function: Vec<String>,
+
+    /// Path to a custom configuration file to use instead of the standard one.
+    #[arg(long, short, value_parser = check_file_existence)]
+    config: Option<String>,
+
+    /// Path to a configuration file for analysis of bare metal binaries.
+    #[arg(long, value_parser = check_file_existence)]
+    bare_metal_config: Option<String>,
+
+    /// Path to a file for caching the lifted IR of the binary.
+    #[arg(long)]
+    ir_cache: Option<String>,
+
+    /// Generate JSON output.
+    #[arg(long, short)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct InspectArgs {
+    /// The path to the binary.
+    #[arg(value_parser = check_file_existence)]
+    binary: String,
+
+    /// The address of the inspected instructions, e.g. `0x1234`.
+    #[arg(long, required_unless_present("diff_states"))]
+    at: Option<String>,
+
+    /// Print the changes of the state between the first instructions at two addresses instead,
+    /// e.g. the registers and memory cells whose values changed along a path between the addresses.
+    #[arg(long, num_args = 2, value_names = ["ADDR1", "ADDR2"], conflicts_with("at"))]
+    diff_states: Vec<String>,
+
+    /// The analysis whose state is printed.
+    #[arg(long, short, value_enum, default_value_t = CliInspectedAnalysis::PointerInference)]
+    analysis: CliInspectedAnalysis,
+
+    /// Path to a custom configuration file to use instead of the standard one.
+    #[arg(long, short, value_parser = check_file_existence)]
+    config: Option<String>,
+
+    /// Path to a configuration file for analysis of bare metal binaries.
+    #[arg(long, value_parser = check_file_existence)]
+    bare_metal_config: Option<String>,
+
+    /// Path to a file for caching the lifted IR of the binary.
+    #[arg(long)]
+    ir_cache: Option<String>,
+
+    /// Generate JSON output.
+    #[arg(long, short)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct ServeArgs {
+    /// The path to the binary.
+    #[arg(value_parser = check_file_existence)]
+    binary: String,
+
+    /// Path to a custom configuration file to use instead of the standard one.
+    #[arg(long, short, value_parser = check_file_existence)]
+    config: Option<String>,
+
+    /// Path to a configuration file for analysis of bare metal binaries.
+    #[arg(long, value_parser = check_file_existence)]
+    bare_metal_config: Option<String>,
+
+    /// Path to a file for caching the lifted IR of the binary.
+    #[arg(long)]
+    ir_cache: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct SummarizeArgs {
+    /// The path to the shared library.
+    #[arg(value_parser = check_file_existence)]
+    library: String,
+
+    /// Path to a custom configuration file to use instead of the standard one.
+    ///
+    /// The sink and allocation functions are taken from its "LibrarySummaries" section.
+    #[arg(long, short, value_parser = check_file_existence)]
+    config: Option<String>,
+
+    /// Write the summaries to a file instead of stdout.
+    #[arg(long, short)]
+    out: Option<String>,
+
+    /// Path to a file for caching the lifted IR of the library.
+    #[arg(long)]
+    ir_cache: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct BatchArgs {
+    /// The directory containing the binaries. Subdirectories are searched as well.
+    ///
+    /// The directory is treated as the root directory of the filesystem of the analyzed firmware.
+    #[arg(value_parser = check_dir_existence, required_unless_present("manifest"))]
+    directory: Option<String>,
+
+    /// A JSON manifest of the binaries to analyze, e.g. written by a firmware unpacker,
+    /// to use instead of a directory.
+    ///
+    /// The manifest is an array of objects with the fields `path` (the path of the extracted binary),
+    /// `original_path` (the path in the firmware filesystem) and the optional fields
+    /// `permissions` (the Unix permission bits) and `metadata` (a map of strings).
+    #[arg(long, short, value_parser = check_file_existence, conflicts_with("directory"))]
+    manifest: Option<String>,
+
+    /// The directory to which the result files are written.
+    ///
+    /// The result file of a binary has the same relative path as the binary in the input directory.
+    /// If the analysis of a binary fails, the error is written to a file with the extension `.error` instead.
+    /// The aggregated findings of all binaries are written to `firmware_report.json`.
+    #[arg(long, short)]
+    out_dir: String,
+
+    /// The maximal number of headless Ghidra instances running at the same time.
+    ///
+    /// Each Ghidra instance needs several GB of RAM.
+    #[arg(long, short, default_value_t = 2)]
+    jobs: usize,
+
+    /// The maximal number of binaries that are lifted by the same Ghidra instance.
+    ///
+    /// Starting Ghidra takes a lot of time, so lifting several binaries per instance is faster.
+    /// The lifted binaries are kept in memory until all of them are analyzed.
+    #[arg(long, default_value_t = 8)]
+    binaries_per_ghidra: usize,
+
+    /// Path to a custom configuration file to use instead of the standard one.
+    #[arg(long, short, value_parser = check_file_existence)]
+    config: Option<String>,
+
+    /// Specify a specific set of checks to be run as a comma separated list, e.g. 'CWE332,CWE476,CWE782'.
+    #[arg(long, short)]
+    partial: Option<String>,
+
+    /// The output format of the result files.
+    #[arg(long, value_enum, default_value_t = CliOutputFormat::Json)]
+    format: CliOutputFormat,
+
+    /// Include the output of Ghidra in the error files if lifting a binary fails.
+    #[arg(long, short)]
+    verbose: bool,
+}
+
+#[derive(Debug, Args)]
+struct AggregateArgs {
+    /// The JSON result files or directories containing them, e.g. the output directory of a batch run.
+    ///
+    /// Directories are searched recursively for files with the extension `.json`.
+    #[arg(required = true)]
+    results: Vec<String>,
+
+    /// Write the report to the given file instead of stdout.
+    #[arg(long, short)]
+    out: Option<String>,
+
+    /// Generate JSON output.
+    #[arg(long, short)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct HashLibraryArgs {
+    /// The path to the binary statically linking the library.
+    #[arg(value_parser = check_file_existence)]
+    binary: String,
+
+    /// The name and version of the library, e.g. "zlib 1.3.1".
+    #[arg(long, short)]
+    name: String,
+
+    /// The hash database to add the hashes to. It is created if it does not exist.
+    #[arg(long, short)]
+    database: String,
+
+    /// Path to a custom configuration file to use instead of the standard one.
+    ///
+    /// The binary is normalized according to its "Normalization" section.
+    #[arg(long, short, value_parser = check_file_existence)]
+    config: Option<String>,
+
+    /// Path to a file for caching the lifted IR of the binary.
+    #[arg(long)]
+    ir_cache: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct HardeningArgs {
+    /// The path to the binary.
+    #[arg(value_parser = check_file_existence)]
+    binary: String,
+
+    /// Generate JSON output.
+    #[arg(long, short)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct IrDumpArgs {
+    /// The path to the binary.
+    #[arg(value_parser = check_file_existence)]
+    binary: String,
+
+    /// Only print the functions with the given name. Can be used multiple times.
+    #[arg(long, short)]
+    function: Vec<String>,
+
+    /// Path to a configuration file for analysis of bare metal binaries.
+    #[arg(long, value_parser = check_file_existence)]
+    bare_metal_config: Option<String>,
+
+    /// Path to a file for caching the lifted IR of the binary.
+    #[arg(long)]
+    ir_cache: Option<String>,
 }
 
 impl From<&CmdlineArgs> for debug::Settings {
@@ -207,7 +760,20 @@ impl From<&CmdlineArgs> for debug::Settings {
 fn main() -> Result<(), Error> {
     let cmdline_args = CmdlineArgs::parse();
 
-    run_with_ghidra(&cmdline_args)
+    match &cmdline_args.command {
+        Some(Command::Ir {
+            command: IrCommand::Dump(dump_args),
+        }) => run_ir_dump(dump_args),
+        Some(Command::Strings(strings_args)) => run_strings(strings_args),
+        Some(Command::Inspect(inspect_args)) => run_inspect(inspect_args),
+        Some(Command::Serve(serve_args)) => run_server(serve_args),
+        Some(Command::Batch(batch_args)) => batch::run_batch(batch_args),
+        Some(Command::Aggregate(aggregate_args)) => run_aggregate(aggregate_args),
+        Some(Command::HashLibrary(hash_library_args)) => run_hash_library(hash_library_args),
+        Some(Command::Summarize(summarize_args)) => run_summarize(summarize_args),
+        Some(Command::Hardening(hardening_args)) => run_hardening(hardening_args),
+        None => run_with_ghidra(&cmdline_args),
+    }
 }
 
 /// Return `Ok(file_path)` only if `file_path` points to an existing file.
@@ -222,6 +788,408 @@ fn check_file_existence(file_path: &str) -> Result<String, String> {
     }
 }
 
+/// Return `Ok(dir_path)` only if `dir_path` points to an existing directory.
+fn check_dir_existence(dir_path: &str) -> Result<String, String> {
+    if std::fs::metadata(dir_path)
+        .map_err(|err| format!("{err}"))?
+        .is_dir()
+    {
+        Ok(dir_path.to_string())
+    } else {
+        Err(format!("{dir_path} is not a directory."))
+    }
+}
+
+/// Lift the binary to the IR, reusing the IR cache file if one is given.
+fn lift_binary(
+    binary_file_path: &Path,
+    bare_metal_config_path: Option<&str>,
+    ir_cache_path: Option<&str>,
+    debug_settings: &debug::Settings,
+) -> Result<(Vec<u8>, WithLogs<Project>), Error> {
+    // Get the bare metal configuration file if it is provided
+    let bare_metal_config_opt: Option<BareMetalConfig> =
+        bare_metal_config_path.map(|config_path| {
+            let file = std::io::BufReader::new(std::fs::File::open(config_path).unwrap());
+            serde_json::from_reader(file)
+                .expect("Parsing of the bare metal configuration file failed")
+        });
+
+    let lifted_binary = if let Some(ir_cache_path) = ir_cache_path {
+        disassemble_binary_with_ir_cache(
+            binary_file_path,
+            bare_metal_config_opt,
+            debug_settings,
+            Path::new(ir_cache_path),
+        )?
+    } else {
+        disassemble_binary(binary_file_path, bare_metal_config_opt, debug_settings)?
+    };
+
+    Ok(lifted_binary)
+}
+
+/// Read a custom configuration file.
+fn read_custom_config(config_path: &str) -> Result<serde_json::Value, Error> {
+    let file = std::io::BufReader::new(std::fs::File::open(config_path)?);
+    serde_json::from_reader(file).context("Parsing of the configuration file failed")
+}
+
+/// Lift the binary and normalize the project like in a normal run of the checks.
+///
+/// If no configuration is given, the standard configuration file for the type of the binary is used.
+/// Returns the binary, the normalized project and the configuration.
+fn prepare_project(
+    binary_file_path: &Path,
+    bare_metal_config_path: Option<&str>,
+    ir_cache_path: Option<&str>,
+    config: Option<&serde_json::Value>,
+    debug_settings: &debug::Settings,
+) -> Result<(Vec<u8>, WithLogs<Project>, serde_json::Value), Error> {
+    let (binary, mut project) = lift_binary(
+        binary_file_path,
+        bare_metal_config_path,
+        ir_cache_path,
+        debug_settings,
+    )?;
+    let config = normalize_project(&mut project, config, debug_settings)?;
+
+    Ok((binary, project, config))
+}
+
+/// Normalize the lifted project like in a normal run of the checks.
+///
+/// If no configuration is given, the standard configuration file for the type of the binary is used.
+/// Returns the configuration.
+fn normalize_project(
+    project: &mut WithLogs<Project>,
+    config: Option<&serde_json::Value>,
+    debug_settings: &debug::Settings,
+) -> Result<serde_json::Value, Error> {
+    let config = match config {
+        Some(config) => config.clone(),
+        None if project.runtime_memory_image.is_lkm => read_config_file("lkm_config.json")?,
+        None => read_config_file("config.json")?,
+    };
+    let normalization_config: NormalizationConfig =
+        serde_json::from_value(config["Normalization"].clone()).unwrap_or_default();
+    project.normalize(&normalization_config, debug_settings);
+
+    Ok(config)
+}
+
+/// Compute the given analyses and their dependencies for the normalized project
+/// and pass their results to `analyze`.
+///
+/// The analyses are configured by their sections of the configuration like in a normal run of the checks,
+/// so that all subcommands see the same analysis results.
+fn with_analysis_results<T>(
+    binary: &[u8],
+    project: &Project,
+    config: &serde_json::Value,
+    analyses: &[Analysis],
+    analyze: impl FnOnce(&AnalysisResults) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let analysis_schedule = schedule_analyses(&[], analyses);
+    let control_flow_graph = graph::get_program_cfg_with_logs(&project.program);
+    let analysis_results = AnalysisResults::new(binary, &control_flow_graph, project);
+    let function_signatures = analysis_schedule
+        .contains(&Analysis::FunctionSignatures)
+        .then(|| analysis_results.compute_function_signatures());
+    let analysis_results =
+        analysis_results.with_function_signatures(function_signatures.as_deref());
+    let pointer_inference = analysis_schedule
+        .contains(&Analysis::PointerInference)
+        .then(|| analysis_results.compute_pointer_inference(&config["Memory"], false));
+    let analysis_results = analysis_results.with_pointer_inference(pointer_inference.as_ref());
+    let string_abstraction = analysis_schedule
+        .contains(&Analysis::StringAbstraction)
+        .then(|| {
+            analysis_results.compute_string_abstraction(
+                &config["StringAbstraction"],
+                pointer_inference.as_ref(),
+            )
+        })
+        .transpose()?;
+    let analysis_results = analysis_results.with_string_abstraction(string_abstraction.as_ref());
+
+    analyze(&analysis_results)
+}
+
+/// Print the lifted IR of the binary as pseudo-assembly.
+fn run_ir_dump(args: &IrDumpArgs) -> Result<(), Error> {
+    let debug_settings = debug::SettingsBuilder::default().build();
+    let (_, project) = lift_binary(
+        Path::new(&args.binary),
+        args.bare_metal_config.as_deref(),
+        args.ir_cache.as_deref(),
+        &debug_settings,
+    )?;
+    print!(
+        "{}",
+        dump_pseudo_assembly(&project.program.term, &args.function)
+    );
+
+    Ok(())
+}
+
+/// Print the abstract string arguments of the calls to string functions.
+fn run_strings(args: &StringsArgs) -> Result<(), Error> {
+    let debug_settings = debug::SettingsBuilder::default().build();
+    let config = args.config.as_deref().map(read_custom_config).transpose()?;
+    let (binary, project, config) = prepare_project(
+        Path::new(&args.binary),
+        args.bare_metal_config.as_deref(),
+        args.ir_cache.as_deref(),
+        config.as_ref(),
+        &debug_settings,
+    )?;
+    let symbol_names: Vec<String> = if args.function.is_empty() {
+        serde_json::from_value(config["StringAbstraction"]["string_symbols"].clone())
+            .context("The configuration file contains no string functions")?
+    } else {
+        args.function.clone()
+    };
+
+    let call_sites = with_analysis_results(
+        &binary,
+        &project,
+        &config,
+        &[Analysis::StringAbstraction],
+        |analysis_results| {
+            Ok(get_call_site_strings(
+                analysis_results.string_abstraction.unwrap(),
+                analysis_results.pointer_inference.unwrap(),
+                &symbol_names,
+            ))
+        },
+    )?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&call_sites)?);
+    } else {
+        for call_site in call_sites {
+            print!("{call_site}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the abstract states of the chosen analysis directly before the instructions at the given address
+/// or the changes of the state between two addresses.
+///
+/// The project is normalized and the analyses are configured as for a normal run of the checks,
+/// so that the printed states are the states that the checks see.
+fn run_inspect(args: &InspectArgs) -> Result<(), Error> {
+    let debug_settings = debug::SettingsBuilder::default().build();
+    let config = args.config.as_deref().map(read_custom_config).transpose()?;
+    let (binary, project, config) = prepare_project(
+        Path::new(&args.binary),
+        args.bare_metal_config.as_deref(),
+        args.ir_cache.as_deref(),
+        config.as_ref(),
+        &debug_settings,
+    )?;
+    let addresses: Vec<&String> = args.at.iter().chain(args.diff_states.iter()).collect();
+    let mut tids_at_addresses = Vec::new();
+    for address in addresses.iter() {
+        let tids = get_terms_at_address(&project, TidAddress::from(address));
+        if tids.is_empty() {
+            return Err(anyhow!("No instruction found at address {address}."));
+        }
+        tids_at_addresses.push(tids);
+    }
+
+    let analysis = match args.analysis {
+        CliInspectedAnalysis::PointerInference => Analysis::PointerInference,
+        CliInspectedAnalysis::String => Analysis::StringAbstraction,
+    };
+    with_analysis_results(
+        &binary,
+        &project,
+        &config,
+        &[analysis],
+        |analysis_results| {
+            let get_state = |tid: &Tid| match analysis_results.string_abstraction {
+                Some(string_abstraction) => string_abstraction
+                    .get_state_at_tid(tid)
+                    .map(|state| state.to_json_compact()),
+                None => analysis_results
+                    .pointer_inference
+                    .unwrap()
+                    .get_state_at_tid(tid)
+                    .map(|state| state.to_json_compact()),
+            };
+            print_inspected_states(args, &project, &addresses, &tids_at_addresses, get_state)
+        },
+    )
+}
+
+/// Print the states directly before the terms at the inspected addresses
+/// or the changes of the state between the two addresses given by `--diff-states`.
+fn print_inspected_states(
+    args: &InspectArgs,
+    project: &Project,
+    addresses: &[&String],
+    tids_at_addresses: &[Vec<Tid>],
+    get_state: impl Fn(&Tid) -> Option<serde_json::Value>,
+) -> Result<(), Error> {
+    if !args.diff_states.is_empty() {
+        let mut states = Vec::new();
+        for (address, tids) in addresses.iter().zip(tids_at_addresses.iter()) {
+            let (tid, state) = tids
+                .iter()
+                .find_map(|tid| Some((tid, get_state(tid)?)))
+                .ok_or_else(|| anyhow!("No state computed at address {address}."))?;
+            states.push((tid, state));
+        }
+        let changes = diff_states(&states[0].1, &states[1].1);
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&changes)?);
+        } else {
+            println!("State changes from {} to {}:", states[0].0, states[1].0);
+            for change in changes {
+                println!("{change}");
+            }
+        }
+        return Ok(());
+    }
+
+    let mut inspected_terms = Vec::new();
+    for tid in tids_at_addresses.iter().flatten() {
+        let (function, term) = find_term(project, tid).unwrap_or_default();
+        let state = get_state(tid);
+        if args.json {
+            inspected_terms.push(serde_json::json!({
+                "function": function,
+                "tid": tid.to_string(),
+                "term": term,
+                "state": state,
+            }));
+        } else {
+            println!("{function} {tid}: {term}");
+            match state {
+                Some(state) => println!("{}", serde_json::to_string_pretty(&state)?),
+                None => println!("No state computed. The instruction may be unreachable."),
+            }
+        }
+    }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&inspected_terms)?);
+    }
+
+    Ok(())
+}
+
+/// Find the `Def` or `Jmp` term with the given TID.
+/// Returns the name of the containing function and the term as pseudo-assembly.
+fn find_term(project: &Project, tid: &Tid) -> Option<(String, String)> {
+    for sub in project.program.term.subs.values() {
+        for block in sub.term.blocks.iter() {
+            let defs = block
+                .term
+                .defs
+                .iter()
+                .map(|def| (&def.tid, def.term.to_string()));
+            let jmps = block
+                .term
+                .jmps
+                .iter()
+                .map(|jmp| (&jmp.tid, jmp.term.to_string()));
+            if let Some((_, term)) = defs.chain(jmps).find(|(term_tid, _)| *term_tid == tid) {
+                return Some((sub.term.name.clone(), term));
+            }
+        }
+    }
+    None
+}
+
+/// Aggregate the result files of many binaries and print or save the report.
+fn run_aggregate(args: &AggregateArgs) -> Result<(), Error> {
+    let paths: Vec<PathBuf> = args.results.iter().map(PathBuf::from).collect();
+    let report = ProjectReport::from_result_files(&paths)?;
+    let output = if args.json {
+        serde_json::to_string_pretty(&report)? + "\n"
+    } else {
+        report.to_string()
+    };
+    match &args.out {
+        Some(out_path) => {
+            std::fs::write(out_path, output).context(format!("Writing to {out_path} failed"))?
+        }
+        None => print!("{output}"),
+    }
+
+    Ok(())
+}
+
+/// Lift the binary and add the hashes of its functions to the hash database.
+fn run_hash_library(args: &HashLibraryArgs) -> Result<(), Error> {
+    let debug_settings = debug::SettingsBuilder::default().build();
+    let config = args.config.as_deref().map(read_custom_config).transpose()?;
+    let (_, project, _) = prepare_project(
+        Path::new(&args.binary),
+        None,
+        args.ir_cache.as_deref(),
+        config.as_ref(),
+        &debug_settings,
+    )?;
+    let database_path = Path::new(&args.database);
+    let mut database = if database_path.exists() {
+        HashDatabase::load(database_path)?
+    } else {
+        HashDatabase::default()
+    };
+    let num_hashes = database.add_library(&args.name, &project);
+    database.save(database_path)?;
+    println!(
+        "Added {num_hashes} function hashes of {} ({}) to {}.",
+        args.name, project.cpu_architecture, args.database
+    );
+
+    Ok(())
+}
+
+/// Print the hardening report of the binary.
+fn run_hardening(args: &HardeningArgs) -> Result<(), Error> {
+    let binary = std::fs::read(&args.binary)?;
+    let report = HardeningReport::parse(&binary)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{report}");
+    }
+
+    Ok(())
+}
+
+/// Analyze the binary and answer queries about it on stdio until the server is shut down.
+///
+/// All analyses are computed at startup, so that each checker can be run without recomputing them.
+fn run_server(args: &ServeArgs) -> Result<(), Error> {
+    let debug_settings = debug::SettingsBuilder::default().build();
+    let config = args.config.as_deref().map(read_custom_config).transpose()?;
+    let (binary, project, config) = prepare_project(
+        Path::new(&args.binary),
+        args.bare_metal_config.as_deref(),
+        args.ir_cache.as_deref(),
+        config.as_ref(),
+        &debug_settings,
+    )?;
+
+    with_analysis_results(
+        &binary,
+        &project,
+        &config,
+        &[Analysis::StringAbstraction],
+        |analysis_results| {
+            let server = Server::new(analysis_results, &config);
+            server.serve(std::io::stdin().lock(), std::io::stdout().lock())?;
+            Ok(())
+        },
+    )
+}
+
 /// Run the cwe_checker with Ghidra as its backend.
 fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
     let debug_settings = args.into();
@@ -234,19 +1202,29 @@ fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
         }
         return Ok(());
     }
+    if args.list_checkers {
+        // Only print the checker metadata and then quit.
+        print_checker_list(&modules);
+        return Ok(());
+    }
 
-    // Get the bare metal configuration file if it is provided
-    let bare_metal_config_opt: Option<BareMetalConfig> =
-        args.bare_metal_config.as_ref().map(|config_path| {
-            let file = std::io::BufReader::new(std::fs::File::open(config_path).unwrap());
-            serde_json::from_reader(file)
-                .expect("Parsing of the bare metal configuration file failed")
-        });
+    if args.bench_mode {
+        operation_counters::enable_counting();
+    }
 
-    let binary_file_path = PathBuf::from(args.binary.clone().unwrap());
+    let log_layer = init_tracing(args);
 
-    let (binary, project) =
-        disassemble_binary(&binary_file_path, bare_metal_config_opt, &debug_settings)?;
+    let binary_file_path = PathBuf::from(args.binary.clone().unwrap());
+    let mut stats = RunStatistics::new(&binary_file_path);
+    let (binary, mut project) = stats.time("Lifting", || {
+        lift_binary(
+            &binary_file_path,
+            args.bare_metal_config.as_deref(),
+            args.ir_cache.as_deref(),
+            &debug_settings,
+        )
+    })?;
+    stats.add_project_statistics(&project);
 
     if debug_settings.should_debug(debug::Stage::CallGraph) {
         // TODO: Move once call graph is used somewhere else.
@@ -260,19 +1238,24 @@ fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
         return Ok(());
     }
 
+    if let Some(address) = &args.slice {
+        print_slice(&project, address, args.slice_forward);
+        return Ok(());
+    }
+
     // Filter the modules to be executed.
     if let Some(ref partial_module_list) = args.partial {
         filter_modules_for_partial_run(&mut modules, partial_module_list);
     } else if project.runtime_memory_image.is_lkm {
-        modules.retain(|module| cwe_checker_lib::checkers::MODULES_LKM.contains(&module.name));
+        modules.retain(|module| cwe_checker_lib::checkers::MODULES_LKM.contains(&module.name()));
     } else {
         // TODO: CWE78 is disabled on a standard run for now,
         // because it uses up huge amounts of RAM and computation time on some binaries.
-        modules.retain(|module| module.name != "CWE78");
+        modules.retain(|module| module.name() != "CWE78");
     }
 
     // Get the configuration file.
-    let config: serde_json::Value = if let Some(ref config_path) = args.config {
+    let mut config: serde_json::Value = if let Some(ref config_path) = args.config {
         let file = std::io::BufReader::new(std::fs::File::open(config_path).unwrap());
         serde_json::from_reader(file).context("Parsing of the configuration file failed")?
     } else if project.runtime_memory_image.is_lkm {
@@ -281,29 +1264,73 @@ fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
         read_config_file("config.json")?
     };
 
-    // Generate the control flow graph of the program
-    let control_flow_graph = graph::get_program_cfg_with_logs(&project.program);
-    debug_settings.print_compact_json(control_flow_graph.deref(), debug::Stage::ControlFlowGraph);
+    if args.explain || args.precision_report.is_some() {
+        config["Memory"]["record_precision_losses"] = serde_json::Value::Bool(true);
+    }
 
-    let analysis_results = AnalysisResults::new(&binary, &control_flow_graph, &project);
+    // Run the optional normalization passes on the IR.
+    let normalization_config: NormalizationConfig =
+        serde_json::from_value(config["Normalization"].clone()).unwrap_or_default();
+    stats.time("Normalization", || {
+        project.normalize(&normalization_config, &debug_settings)
+    });
 
-    let modules_depending_on_string_abstraction = BTreeSet::from_iter(["CWE78"]);
-    let modules_depending_on_pointer_inference = BTreeSet::from_iter([
-        "CWE119", "CWE134", "CWE190", "CWE252", "CWE337", "CWE416", "CWE476", "CWE789", "Memory",
-    ]);
+    // Summarize the functions of the shared libraries and resolve the imports of the binary to them.
+    let library_summaries = if args.library.is_empty() && args.summaries.is_empty() {
+        None
+    } else {
+        let mut library_summaries = LibrarySummaries::default();
+        for summaries_path in args.summaries.iter() {
+            let file = std::io::BufReader::new(std::fs::File::open(summaries_path)?);
+            library_summaries.extend(
+                serde_json::from_reader(file)
+                    .context(format!("Parsing of the summaries {summaries_path} failed"))?,
+            );
+        }
+        for library_path in args.library.iter() {
+            library_summaries.extend(stats.time("Library Summaries", || {
+                summarize_library(Path::new(library_path), &config, None, &debug_settings)
+            })?);
+        }
+        let num_resolved = library_summaries.resolve_imports(&mut project);
+        project.add_log_msg(LogMessage::new_info(format!(
+            "Resolved {num_resolved} imported symbols to functions of {} shared libraries.",
+            args.library.len() + args.summaries.len()
+        )));
+        Some(library_summaries)
+    };
 
-    let string_abstraction_needed = modules
-        .iter()
-        .any(|module| modules_depending_on_string_abstraction.contains(&module.name));
+    // Generate the control flow graph of the program
+    let control_flow_graph = stats.time("Control Flow Graph", || {
+        graph::get_program_cfg_with_logs(&project.program)
+    });
+    debug_settings.print_compact_json(control_flow_graph.deref(), debug::Stage::ControlFlowGraph);
 
-    let pi_analysis_needed = string_abstraction_needed
-        || modules
-            .iter()
-            .any(|module| modules_depending_on_pointer_inference.contains(&module.name));
+    let analysis_results = AnalysisResults::new(&binary, &control_flow_graph, &project)
+        .with_library_summaries(library_summaries.as_ref());
+
+    // Only compute the analyses that the enabled checkers depend on.
+    let mut additional_analyses = if debug_settings.should_debug(debug::Stage::Pi) {
+        vec![Analysis::PointerInference]
+    } else {
+        Vec::new()
+    };
+    if args.ghidra_markers.is_some() {
+        additional_analyses.push(Analysis::StringAbstraction);
+    }
+    if args.precision_report.is_some() {
+        additional_analyses.push(Analysis::PointerInference);
+    }
+    let analysis_schedule = schedule_analyses(&modules, &additional_analyses);
+    let string_abstraction_needed = analysis_schedule.contains(&Analysis::StringAbstraction);
+    let pi_analysis_needed = analysis_schedule.contains(&Analysis::PointerInference);
+    let function_signatures_needed = analysis_schedule.contains(&Analysis::FunctionSignatures);
 
     // Compute function signatures if required
-    let function_signatures = if pi_analysis_needed {
-        let function_signatures = analysis_results.compute_function_signatures();
+    let function_signatures = if function_signatures_needed {
+        let function_signatures = stats.time("Function Signatures", || {
+            analysis_results.compute_function_signatures()
+        });
 
         Some(function_signatures)
     } else {
@@ -313,21 +1340,31 @@ fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
         analysis_results.with_function_signatures(function_signatures.as_deref());
     // Compute pointer inference if required
     let pi_analysis_results = if pi_analysis_needed {
-        Some(analysis_results.compute_pointer_inference(&config["Memory"], args.statistics))
+        let pointer_inference = stats.time("Pointer Inference", || {
+            analysis_results.compute_pointer_inference(&config["Memory"], args.statistics)
+        });
+        stats.add_pointer_inference_statistics(&pointer_inference);
+        Some(pointer_inference)
     } else {
         None
     };
     let analysis_results = analysis_results.with_pointer_inference(pi_analysis_results.as_ref());
     // Compute string abstraction analysis if required
-    let string_abstraction_results =
-        if string_abstraction_needed {
-            Some(analysis_results.compute_string_abstraction(
+    let string_abstraction_results = if string_abstraction_needed {
+        let string_abstraction = stats.time("String Abstraction", || {
+            analysis_results.compute_string_abstraction(
                 &config["StringAbstraction"],
                 pi_analysis_results.as_ref(),
-            ))
-        } else {
-            None
-        };
+            )
+        })?;
+        stats.add_fixpoint_steps(
+            "String Abstraction",
+            string_abstraction.get_computation().get_number_of_steps(),
+        );
+        Some(string_abstraction)
+    } else {
+        None
+    };
     let analysis_results =
         analysis_results.with_string_abstraction(string_abstraction_results.as_ref());
 
@@ -337,7 +1374,7 @@ fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
     if debug_settings.should_debug(debug::Stage::Pi) {
         cwe_checker_lib::analysis::pointer_inference::run(
             &analysis_results,
-            serde_json::from_value(config["Memory"].clone()).unwrap(),
+            analysis_results.get_pointer_inference_config(&config["Memory"]),
             true,
             false,
         );
@@ -347,11 +1384,118 @@ fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
     // Execute the modules and collect their logs and CWE-warnings.
     let mut all_cwe_warnings = Vec::new();
     for module in modules {
-        let cwe_warnings = (module.run)(&analysis_results, &config[&module.name], &debug_settings);
+        let _span = tracing::info_span!("checker", name = module.name()).entered();
+        let cwe_warnings = stats.time(module.name(), || {
+            module.run(&analysis_results, &config[module.name()], &debug_settings)
+        });
+        stats.add_warning_count(module.name(), cwe_warnings.len());
 
         all_cwe_warnings.push(cwe_warnings);
     }
 
+    // Remove the CWE warnings in functions that are verified to be safe.
+    let mut allowlist = Allowlist::new(&project);
+    let allowlist_logs = match &args.safe_annotations {
+        Some(annotations_path) => allowlist.add_annotation_file(Path::new(annotations_path))?,
+        None => Vec::new(),
+    };
+    let mut all_cwe_warnings: Vec<WithLogs<Vec<CweWarning>>> = all_cwe_warnings
+        .into_iter()
+        .map(|cwe_warnings| {
+            let (cwe_warnings, mut logs) = cwe_warnings.into_object_logs();
+            let cwe_warnings = allowlist
+                .filter_cwe_warnings(cwe_warnings)
+                .move_logs_to(&mut logs)
+                .into_object();
+            WithLogs::new(cwe_warnings, logs)
+        })
+        .collect();
+
+    // Label the CWE warnings in statically linked third-party code.
+    let mut third_party_logs = Vec::new();
+    let hash_database = match &args.hash_database {
+        Some(database_path) => Some(HashDatabase::load(Path::new(database_path))?),
+        None => HashDatabase::load_bundled()
+            .map_err(|error| {
+                third_party_logs.push(LogMessage::new_debug(format!(
+                    "No hash database of library functions loaded: {error:#}"
+                )))
+            })
+            .ok(),
+    };
+    if let Some(hash_database) = &hash_database {
+        let num_labelled = label_third_party_code(
+            &project,
+            hash_database,
+            all_cwe_warnings.iter_mut().flat_map(|x| x.iter_mut()),
+        );
+        if args.skip_third_party_code && num_labelled > 0 {
+            for cwe_warnings in all_cwe_warnings.iter_mut() {
+                cwe_warnings.retain(|cwe_warning| cwe_warning.third_party_library.is_none());
+            }
+            third_party_logs.push(LogMessage::new_info(format!(
+                "Skipped {num_labelled} CWE warnings in statically linked third-party code"
+            )));
+        }
+    }
+
+    // Try to confirm the CWE warnings by symbolic execution.
+    let witness_validation_logs = if args.confirm_findings {
+        let witness_validation_config = match config.get("WitnessValidation") {
+            Some(section) => serde_json::from_value(section.clone())
+                .context("Parsing of the WitnessValidation configuration failed")?,
+            None => Default::default(),
+        };
+        stats.time("Witness Validation", || {
+            confirm_cwe_warnings(
+                &project,
+                all_cwe_warnings.iter_mut().flat_map(|x| x.iter_mut()),
+                &witness_validation_config,
+            )
+        })
+    } else {
+        Vec::new()
+    };
+
+    // Identify the functions containing the CWE warnings across binaries.
+    if args.function_hashes {
+        add_function_hashes(
+            &project,
+            all_cwe_warnings.iter_mut().flat_map(|x| x.iter_mut()),
+        );
+    }
+
+    // Demangle C++ and Rust symbol names to make the CWE warnings readable.
+    for cwe_warning in all_cwe_warnings.iter_mut().flat_map(|x| x.iter_mut()) {
+        cwe_warning.demangle_symbols();
+    }
+
+    // Add the source code locations to the CWE warnings if the binary contains debug information.
+    let mut debug_info_logs = Vec::new();
+    match DebugInfo::parse(&binary) {
+        Ok(Some(debug_info)) => {
+            for cwe_warning in all_cwe_warnings.iter_mut().flat_map(|x| x.iter_mut()) {
+                debug_info
+                    .add_source_location(cwe_warning, project.program.term.address_base_offset);
+            }
+        }
+        Ok(None) => (),
+        Err(error) => debug_info_logs.push(LogMessage::new_error(format!(
+            "Parsing of the debug information failed: {error}"
+        ))),
+    }
+
+    // The events recorded by the instrumented analyses and checkers.
+    let tracing_logs = log_layer
+        .map(|log_layer| log_layer.take_messages())
+        .unwrap_or_default();
+
+    // Functions skipped by the string abstraction because their analysis failed.
+    let string_abstraction_logs = string_abstraction_results
+        .as_ref()
+        .map(|string_abstraction| string_abstraction.get_logs())
+        .unwrap_or_default();
+
     // Print the results of the modules.
     let all_logs: Vec<&LogMessage> = if args.quiet {
         Vec::new() // Suppress all log messages since the `--quiet` flag is set.
@@ -360,10 +1504,16 @@ fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
 
         // Aggregate the logs of all objects that come with logs.
         all_logs.extend(project.logs().iter());
+        all_logs.extend(debug_info_logs.iter());
+        all_logs.extend(allowlist_logs.iter());
+        all_logs.extend(third_party_logs.iter());
+        all_logs.extend(witness_validation_logs.iter());
         all_logs.extend(control_flow_graph.logs().iter());
         if let Some(function_signatures) = &function_signatures {
             all_logs.extend(function_signatures.logs().iter());
         }
+        all_logs.extend(string_abstraction_logs.iter());
+        all_logs.extend(tracing_logs.iter());
         for cwe_warnings in all_cwe_warnings.iter() {
             all_logs.extend(cwe_warnings.logs().iter());
         }
@@ -373,27 +1523,166 @@ fn run_with_ghidra(args: &CmdlineArgs) -> Result<(), Error> {
             //cwe_checker_lib::utils::log::add_debug_log_statistics(&mut all_logs);
             todo!()
         }
-        if !args.verbose {
+        let debug_events_requested = matches!(
+            args.log_level,
+            Some(CliLogLevel::Debug | CliLogLevel::Trace)
+        );
+        if !args.verbose && !debug_events_requested {
             all_logs.retain(|log_msg| log_msg.level != LogLevel::Debug);
         }
 
         all_logs
     };
-    let all_cwes: Vec<&CweWarning> = all_cwe_warnings.iter().flat_map(|x| x.iter()).collect();
+    let grouped_cwes;
+    let all_cwes: Vec<&CweWarning> = if args.group_warnings {
+        grouped_cwes = group_cwe_warnings(
+            all_cwe_warnings
+                .iter()
+                .flat_map(|x| x.iter())
+                .cloned()
+                .collect(),
+        );
+        grouped_cwes.iter().collect()
+    } else {
+        all_cwe_warnings.iter().flat_map(|x| x.iter()).collect()
+    };
+
+    let output_format = if args.json {
+        OutputFormat::Json
+    } else {
+        args.format.into()
+    };
+    if let Some(markers_path) = &args.ghidra_markers {
+        let mut markers = GhidraMarkers::default();
+        markers.add_cwe_warnings(&all_cwes);
+        if let (Some(string_abstraction), Some(pointer_inference)) =
+            (&string_abstraction_results, &pi_analysis_results)
+        {
+            let symbol_names: Vec<String> =
+                serde_json::from_value(config["StringAbstraction"]["string_symbols"].clone())
+                    .unwrap_or_default();
+            markers.add_string_annotations(&get_call_site_strings(
+                string_abstraction,
+                pointer_inference,
+                &symbol_names,
+            ));
+        }
+        std::fs::write(markers_path, serde_json::to_string_pretty(&markers)?)
+            .context("Writing the Ghidra markers failed")?;
+    }
+    if let Some(target) = args.annotate_for {
+        let annotations = format_annotations(
+            &all_cwes,
+            target.into(),
+            project.program.term.address_base_offset,
+        );
+        print_logs_and_output(all_logs, &annotations, args.out.as_deref());
+    } else {
+        print_all_messages(all_logs, all_cwes, args.out.as_deref(), output_format);
+    }
 
-    print_all_messages(all_logs, all_cwes, args.out.as_deref(), args.json);
+    if let Some(report_path) = &args.precision_report {
+        if let Some(report) = analysis_results
+            .pointer_inference
+            .and_then(PrecisionReport::new)
+        {
+            report.save(Path::new(report_path))?;
+        }
+    }
+    if let Some(stats_path) = &args.stats {
+        stats.add_memory_statistics();
+        stats.save(Path::new(stats_path))?;
+    }
+    if args.bench_mode {
+        print_operation_counts();
+    }
 
     Ok(())
 }
 
+/// Print the counters of abstract domain operations to stderr.
+fn print_operation_counts() {
+    for count in operation_counters::get_operation_counts() {
+        eprintln!(
+            "[bench] {:?} {:?}: {}",
+            count.domain, count.operation, count.count
+        );
+    }
+}
+
+/// Compute the summaries of the functions exported by a shared library and save them as JSON.
+fn run_summarize(args: &SummarizeArgs) -> Result<(), Error> {
+    let debug_settings = debug::SettingsBuilder::default().build();
+    let config: serde_json::Value = if let Some(config_path) = &args.config {
+        let file = std::io::BufReader::new(std::fs::File::open(config_path)?);
+        serde_json::from_reader(file).context("Parsing of the configuration file failed")?
+    } else {
+        read_config_file("config.json")?
+    };
+    let summaries = summarize_library(
+        Path::new(&args.library),
+        &config,
+        args.ir_cache.as_deref(),
+        &debug_settings,
+    )?;
+    let summaries = serde_json::to_string_pretty(&summaries)?;
+    if let Some(out_path) = &args.out {
+        std::fs::write(out_path, summaries).context("Writing the summaries failed")?;
+    } else {
+        println!("{summaries}");
+    }
+
+    Ok(())
+}
+
+/// Lift and analyze a shared library and summarize the functions that it exports.
+///
+/// The sink and allocation functions tracked by the summaries are taken from
+/// the "LibrarySummaries" section of the configuration.
+fn summarize_library(
+    library_path: &Path,
+    config: &serde_json::Value,
+    ir_cache_path: Option<&str>,
+    debug_settings: &debug::Settings,
+) -> Result<LibrarySummaries, Error> {
+    let summaries_config: LibrarySummariesConfig =
+        serde_json::from_value(config["LibrarySummaries"].clone()).unwrap_or_default();
+    let (binary, project, config) = prepare_project(
+        library_path,
+        None,
+        ir_cache_path,
+        Some(config),
+        debug_settings,
+    )
+    .context(format!(
+        "Analysis of library {} failed",
+        library_path.display()
+    ))?;
+
+    with_analysis_results(
+        &binary,
+        &project,
+        &config,
+        &[Analysis::PointerInference],
+        |analysis_results| {
+            Ok(LibrarySummaries::compute(
+                &project,
+                analysis_results.function_signatures.unwrap(),
+                analysis_results.pointer_inference.unwrap(),
+                &summaries_config,
+            ))
+        },
+    )
+}
+
 /// Only keep the modules specified by the `--partial` parameter in the `modules` list.
 /// The parameter is a comma-separated list of module names, e.g. 'CWE332,CWE476,CWE782'.
-fn filter_modules_for_partial_run(modules: &mut Vec<&CweModule>, partial_param: &str) {
+fn filter_modules_for_partial_run(modules: &mut Vec<&dyn Checker>, partial_param: &str) {
     let module_names: HashSet<&str> = partial_param.split(',').collect();
     *modules = module_names
         .into_iter()
         .filter_map(|module_name| {
-            if let Some(module) = modules.iter().find(|module| module.name == module_name) {
+            if let Some(module) = modules.iter().find(|module| module.name() == module_name) {
                 Some(*module)
             } else if module_name.is_empty() {
                 None
@@ -403,3 +1692,98 @@ fn filter_modules_for_partial_run(modules: &mut Vec<&CweModule>, partial_param:
         })
         .collect();
 }
+
+/// Install a `tracing` subscriber for the events of the instrumented analyses and checkers
+/// according to the `--log-level` and `--log-json` command line options.
+///
+/// Returns the layer that records the events as log messages
+/// unless the events are printed as JSON or no events are requested.
+fn init_tracing(args: &CmdlineArgs) -> Option<LogMessageLayer> {
+    let level: tracing::Level = match (args.log_level, args.log_json) {
+        (Some(level), _) => level.into(),
+        (None, true) => tracing::Level::INFO,
+        (None, false) => return None,
+    };
+    if args.log_json {
+        tracing_subscriber::fmt()
+            .json()
+            .with_max_level(level)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .init();
+        None
+    } else {
+        let log_layer = LogMessageLayer::new();
+        tracing_subscriber::registry()
+            .with(
+                log_layer
+                    .clone()
+                    .with_filter(LevelFilter::from_level(level)),
+            )
+            .init();
+        Some(log_layer)
+    }
+}
+
+/// Print the name, version, covered CWEs and required analyses of all given checkers.
+fn print_checker_list(modules: &[&dyn Checker]) {
+    println!("[cwe_checker] checkers:");
+    for module in modules {
+        let cwes: Vec<String> = module
+            .cwes()
+            .iter()
+            .map(|cwe| format!("CWE-{cwe}"))
+            .collect();
+        let analyses: Vec<String> = module
+            .required_analyses()
+            .iter()
+            .map(|analysis| analysis.to_string())
+            .collect();
+        println!(
+            "{} (version {}): covers [{}], requires [{}]",
+            module.name(),
+            module.version(),
+            cwes.join(", "),
+            analyses.join(", ")
+        );
+    }
+}
+
+/// Print the slice of the instruction at the given address, ordered by functions and blocks.
+fn print_slice(project: &Project, address: &str, forward: bool) {
+    let criteria = get_terms_at_address(project, TidAddress::from(address));
+    if criteria.is_empty() {
+        println!("No instruction found at address {address}.");
+        return;
+    }
+    let dependency_graph = DependencyGraph::new(project, true);
+    let slice = if forward {
+        dependency_graph.forward_slice(&criteria)
+    } else {
+        dependency_graph.backward_slice(&criteria)
+    };
+    for sub in project.program.term.subs.values() {
+        let mut sub_header_printed = false;
+        for block in sub.term.blocks.iter() {
+            let defs = block
+                .term
+                .defs
+                .iter()
+                .filter(|def| slice.contains(&def.tid))
+                .map(|def| format!("{}: {}", def.tid, def.term));
+            let jmps = block
+                .term
+                .jmps
+                .iter()
+                .filter(|jmp| slice.contains(&jmp.tid))
+                .map(|jmp| format!("{}: {}", jmp.tid, jmp.term));
+            for line in defs.chain(jmps) {
+                if !sub_header_printed {
+                    println!("{} ({}):", sub.term.name, sub.tid);
+                    sub_header_printed = true;
+                }
+                println!("    {line}");
+            }
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! A long-running server that answers queries about a single binary.
+//!
+//! The server speaks [JSON-RPC 2.0](https://www.jsonrpc.org/specification) over stdio,
+//! with one JSON message per line.
+//! The binary is lifted and analyzed once at startup,
+//! so that interactive frontends do not pay the startup cost for every query.
+//!
+//! The following methods are supported:
+//! - `list_checkers`: Get the names of all checkers.
+//! - `run_checker` with parameter `name`: Run the checker and get its CWE warnings.
+//! - `get_strings` with optional parameter `address`:
+//!   Get the abstract string arguments of the calls to string functions (at the given address).
+//! - `get_state` with parameter `tid`:
+//!   Get the Pointer Inference state directly before the term with the given TID.
+//! - `get_slice` with parameters `address` and optional `forward`:
+//!   Get the backward (or forward) slice of the instructions at the given address.
+//! - `shutdown`: Stop the server.
+
+use cwe_checker_lib::analysis::slicing::{get_terms_at_address, DependencyGraph};
+use cwe_checker_lib::analysis::string_abstraction::report::get_call_site_strings;
+use cwe_checker_lib::checkers::Checker;
+use cwe_checker_lib::intermediate_representation::{Project, Tid, TidAddress};
+use cwe_checker_lib::pipeline::AnalysisResults;
+use cwe_checker_lib::utils::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+/// The error code for messages that are not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// The error code for valid JSON messages that are not valid requests.
+const INVALID_REQUEST: i64 = -32600;
+/// The error code for unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// The error code for missing or invalid parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// A JSON-RPC request.
+#[derive(Deserialize, Debug)]
+struct Request {
+    /// The ID of the request or `None` for notifications, which get no response.
+    #[serde(default)]
+    id: Option<Value>,
+    /// The name of the method.
+    method: String,
+    /// The parameters of the method.
+    #[serde(default)]
+    params: Value,
+}
+
+/// A JSON-RPC response.
+#[derive(Serialize, Debug)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ResponseError>,
+}
+
+/// The error object of a JSON-RPC response.
+#[derive(Serialize, Debug)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl Response {
+    /// Create a response from the result of a method.
+    fn new(id: Value, result: Result<Value, ResponseError>) -> Response {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Response {
+            jsonrpc: "2.0",
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+/// Create an error for missing or invalid parameters.
+fn invalid_params(message: impl Into<String>) -> ResponseError {
+    ResponseError {
+        code: INVALID_PARAMS,
+        message: message.into(),
+    }
+}
+
+/// The server answering queries about the analysis results of a binary.
+pub struct Server<'a, 'b> {
+    /// The results of all analyses.
+    analysis_results: &'a AnalysisResults<'b>,
+    /// The configuration of the checkers and analyses.
+    config: &'a Value,
+    /// All known checkers.
+    modules: Vec<&'static dyn Checker>,
+    /// The names of the string functions whose arguments are reported by `get_strings`.
+    string_symbols: Vec<String>,
+}
+
+impl<'a, 'b> Server<'a, 'b> {
+    /// Create a new server.
+    ///
+    /// The analysis results should contain the results of all analyses that the checkers depend on.
+    pub fn new(analysis_results: &'a AnalysisResults<'b>, config: &'a Value) -> Server<'a, 'b> {
+        Server {
+            analysis_results,
+            config,
+            modules: cwe_checker_lib::checkers::get_modules(),
+            string_symbols: serde_json::from_value(
+                config["StringAbstraction"]["string_symbols"].clone(),
+            )
+            .unwrap_or_default(),
+        }
+    }
+
+    /// Answer the requests read from `input` until a `shutdown` request is received
+    /// or the end of the input is reached.
+    pub fn serve(&self, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let request = serde_json::from_str::<Value>(&line)
+                .map_err(|error| (Value::Null, PARSE_ERROR, error))
+                .and_then(|message| {
+                    Request::deserialize(&message).map_err(|error| {
+                        let id = message.get("id").cloned().unwrap_or(Value::Null);
+                        (id, INVALID_REQUEST, error)
+                    })
+                });
+            let (id, result, shutdown) = match request {
+                Ok(request) => {
+                    let result = self.handle_request(&request.method, &request.params);
+                    let Some(id) = request.id else {
+                        // Notifications get no response.
+                        if request.method == "shutdown" {
+                            break;
+                        }
+                        continue;
+                    };
+                    (id, result, request.method == "shutdown")
+                }
+                Err((id, code, error)) => (
+                    id,
+                    Err(ResponseError {
+                        code,
+                        message: error.to_string(),
+                    }),
+                    false,
+                ),
+            };
+            let response = serde_json::to_string(&Response::new(id, result))?;
+            writeln!(output, "{response}")?;
+            output.flush()?;
+            if shutdown {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the result of the given method.
+    fn handle_request(&self, method: &str, params: &Value) -> Result<Value, ResponseError> {
+        match method {
+            "list_checkers" => Ok(json!(self
+                .modules
+                .iter()
+                .map(|module| module.name())
+                .collect::<Vec<_>>())),
+            "run_checker" => self.run_checker(params),
+            "get_strings" => self.get_strings(params),
+            "get_state" => self.get_state(params),
+            "get_slice" => self.get_slice(params),
+            "shutdown" => Ok(Value::Null),
+            _ => Err(ResponseError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Unknown method: {method}"),
+            }),
+        }
+    }
+
+    /// Run the checker given by the `name` parameter and return its CWE warnings.
+    fn run_checker(&self, params: &Value) -> Result<Value, ResponseError> {
+        let name = get_str_param(params, "name")?;
+        let module = self
+            .modules
+            .iter()
+            .find(|module| module.name() == name)
+            .ok_or_else(|| invalid_params(format!("{name} is not a valid checker name.")))?;
+        let debug_settings = debug::SettingsBuilder::default().build();
+        let cwe_warnings = module.run(self.analysis_results, &self.config[name], &debug_settings);
+        Ok(json!(cwe_warnings.into_object()))
+    }
+
+    /// Get the abstract string arguments of the calls to string functions.
+    ///
+    /// If the `address` parameter is given, only the calls at this address are returned.
+    fn get_strings(&self, params: &Value) -> Result<Value, ResponseError> {
+        let (Some(string_abstraction), Some(pointer_inference)) = (
+            self.analysis_results.string_abstraction,
+            self.analysis_results.pointer_inference,
+        ) else {
+            return Err(invalid_params("The string abstraction was not computed."));
+        };
+        let address = params["address"].as_str().map(TidAddress::from);
+        let call_sites: Vec<_> =
+            get_call_site_strings(string_abstraction, pointer_inference, &self.string_symbols)
+                .into_iter()
+                .filter(|call_site| {
+                    address.is_none_or(|address| call_site.call.address() == address)
+                })
+                .collect();
+        Ok(json!(call_sites))
+    }
+
+    /// Get the Pointer Inference state directly before the term with the TID given by the `tid` parameter.
+    fn get_state(&self, params: &Value) -> Result<Value, ResponseError> {
+        let tid = get_str_param(params, "tid")?;
+        let pointer_inference = self
+            .analysis_results
+            .pointer_inference
+            .ok_or_else(|| invalid_params("The Pointer Inference was not computed."))?;
+        let tid = find_tid(self.analysis_results.project, tid)
+            .ok_or_else(|| invalid_params(format!("No term with TID {tid} found.")))?;
+        Ok(pointer_inference
+            .get_state_at_tid(tid)
+            .map(|state| state.to_json_compact())
+            .unwrap_or(Value::Null))
+    }
+
+    /// Get the slice of the instructions at the address given by the `address` parameter.
+    ///
+    /// The slice is a backward slice unless the `forward` parameter is `true`.
+    /// It is returned as a list of terms together with the names of the functions containing them.
+    fn get_slice(&self, params: &Value) -> Result<Value, ResponseError> {
+        let address = get_str_param(params, "address")?;
+        let forward = params["forward"].as_bool().unwrap_or(false);
+        let project = self.analysis_results.project;
+        let criteria = get_terms_at_address(project, TidAddress::from(address));
+        if criteria.is_empty() {
+            return Err(invalid_params(format!(
+                "No instruction found at address {address}."
+            )));
+        }
+        let dependency_graph = DependencyGraph::new(project, true);
+        let slice = if forward {
+            dependency_graph.forward_slice(&criteria)
+        } else {
+            dependency_graph.backward_slice(&criteria)
+        };
+        let mut terms = Vec::new();
+        for sub in project.program.term.subs.values() {
+            for block in sub.term.blocks.iter() {
+                let defs = block
+                    .term
+                    .defs
+                    .iter()
+                    .filter(|def| slice.contains(&def.tid))
+                    .map(|def| (&def.tid, def.term.to_string()));
+                let jmps = block
+                    .term
+                    .jmps
+                    .iter()
+                    .filter(|jmp| slice.contains(&jmp.tid))
+                    .map(|jmp| (&jmp.tid, jmp.term.to_string()));
+                for (tid, term) in defs.chain(jmps) {
+                    terms.push(json!({
+                        "function": sub.term.name,
+                        "tid": tid.to_string(),
+                        "address": tid.address().to_string(),
+                        "term": term,
+                    }));
+                }
+            }
+        }
+        Ok(Value::Array(terms))
+    }
+}
+
+/// Get the string parameter with the given name.
+fn get_str_param<'b>(params: &'b Value, name: &str) -> Result<&'b str, ResponseError> {
+    params[name]
+        .as_str()
+        .ok_or_else(|| invalid_params(format!("Missing string parameter: {name}")))
+}
+
+/// Find the TID of the instruction or jump with the given ID in the program.
+fn find_tid<'b>(project: &'b Project, id: &str) -> Option<&'b Tid> {
+    project
+        .program
+        .term
+        .subs
+        .values()
+        .flat_map(|sub| sub.term.blocks.iter())
+        .flat_map(|block| {
+            let defs = block.term.defs.iter().map(|def| &def.tid);
+            defs.chain(block.term.jmps.iter().map(|jmp| &jmp.tid))
+        })
+        .find(|tid| tid.to_string() == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cwe_checker_lib::analysis::graph;
+
+    /// A project without functions and memory for an x86-64 binary.
+    fn mock_project() -> Project {
+        serde_json::from_value(json!({
+            "program": {
+                "tid": {"id": "program_tid", "address": null},
+                "term": {
+                    "subs": {},
+                    "extern_symbols": {},
+                    "entry_points": [],
+                    "address_base_offset": 0
+                }
+            },
+            "cpu_architecture": "x86_64",
+            "stack_pointer_register": {"name": "RSP", "size": 8, "is_temp": false},
+            "calling_conventions": {},
+            "register_set": [],
+            "datatype_properties": {
+                "char_size": 1,
+                "double_size": 8,
+                "float_size": 4,
+                "integer_size": 4,
+                "long_double_size": 16,
+                "long_long_size": 8,
+                "long_size": 8,
+                "pointer_size": 8,
+                "short_size": 2
+            },
+            "runtime_memory_image": {
+                "memory_segments": [],
+                "is_little_endian": true,
+                "is_lkm": false
+            }
+        }))
+        .unwrap()
+    }
+
+    /// Send the requests to a server for the mock project and return its responses.
+    fn round_trip(requests: &[&str]) -> Vec<Value> {
+        let project = mock_project();
+        let control_flow_graph = graph::get_program_cfg(&project.program);
+        let analysis_results = AnalysisResults::new(&[], &control_flow_graph, &project);
+        let config = json!({"CWE676": {"symbols": ["strcpy"]}});
+        let server = Server::new(&analysis_results, &config);
+
+        let input = requests.join("\n");
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn request_response_round_trip() {
+        let responses = round_trip(&[
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "list_checkers"}"#,
+            r#"{"jsonrpc": "2.0", "id": "run", "method": "run_checker", "params": {"name": "CWE676"}}"#,
+            r#"{"jsonrpc": "2.0", "method": "list_checkers"}"#,
+            "",
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "shutdown"}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "list_checkers"}"#,
+        ]);
+        // Notifications and requests after the shutdown get no response.
+        assert_eq!(responses.len(), 3);
+
+        assert_eq!(responses[0]["jsonrpc"], "2.0");
+        assert_eq!(responses[0]["id"], 1);
+        let checkers = responses[0]["result"].as_array().unwrap();
+        assert!(checkers.contains(&json!("CWE676")));
+        assert!(responses[0].get("error").is_none());
+
+        assert_eq!(responses[1]["id"], "run");
+        assert_eq!(responses[1]["result"], json!([]));
+
+        assert_eq!(
+            responses[2],
+            json!({"jsonrpc": "2.0", "id": 2, "result": null})
+        );
+    }
+
+    #[test]
+    fn error_responses() {
+        let responses = round_trip(&[
+            "not json",
+            r#"{"jsonrpc": "2.0", "id": 0, "params": {}}"#,
+            "[1, 2]",
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "unknown"}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "run_checker", "params": {}}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "run_checker", "params": {"name": "CWE0"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 4, "method": "get_state", "params": {"tid": "instr_0x1000_0"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 5, "method": "get_slice", "params": {"address": "0x1000"}}"#,
+        ]);
+        let error_codes: Vec<(Value, i64)> = responses
+            .iter()
+            .map(|response| {
+                assert!(response.get("result").is_none());
+                (
+                    response["id"].clone(),
+                    response["error"]["code"].as_i64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            error_codes,
+            vec![
+                (Value::Null, PARSE_ERROR),
+                (json!(0), INVALID_REQUEST),
+                (Value::Null, INVALID_REQUEST),
+                (json!(1), METHOD_NOT_FOUND),
+                (json!(2), INVALID_PARAMS),
+                (json!(3), INVALID_PARAMS),
+                (json!(4), INVALID_PARAMS),
+                (json!(5), INVALID_PARAMS),
+            ]
+        );
+        assert_eq!(
+            responses[4]["error"]["message"],
+            "Missing string parameter: name"
+        );
+    }
+}
@@ -4,11 +4,15 @@
 //! Currently there are benchmarks for the following steps:
 //!
 //! - interprocedural CFG construction,
+//! - worklist orders of the fixpoint algorithm,
 //! - individual IR passes,
 //! - function signatures analysis,
 //! - pointer inference,
 //! - string abstractions,
 //! - individual checkers.
+//!
+//! Benchmarks for the operations of individual abstract domains are contained
+//! in `domains.rs`.
 // TODO:
 // - call graph
 //!
@@ -249,7 +253,7 @@ mod checkers {
     use super::*;
     use cwe_checker_lib::checkers::*;
 
-    fn helper_bench_checker<T>(checker: &CweModule, group: BenchmarkGroup<T>, is_lkm: bool)
+    fn helper_bench_checker<T>(checker: &dyn Checker, group: BenchmarkGroup<T>, is_lkm: bool)
     where
         T: Measurement,
     {
@@ -283,9 +287,9 @@ mod checkers {
                         &analysis_results,
                         |b, analysis_results| {
                             b.iter_with_large_drop(|| {
-                                (checker.run)(
+                                checker.run(
                                     analysis_results,
-                                    &config[&checker.name],
+                                    &config[checker.name()],
                                     &debug_settings,
                                 )
                             })
@@ -341,6 +345,11 @@ mod checkers {
         time = 20
     );
     bench_checker!(cwe_134);
+    bench_checker!(
+        name = cwe_170;
+        samples = 10;
+        time = 20
+    );
     bench_checker!(cwe_190);
     bench_checker!(cwe_215);
     bench_checker!(cwe_243);
@@ -349,6 +358,22 @@ mod checkers {
         samples = 10;
         time = 20
     );
+    bench_checker!(cwe_306);
+    bench_checker!(
+        name = cwe_311;
+        samples = 10;
+        time = 20
+    );
+    bench_checker!(
+        name = cwe_319;
+        samples = 10;
+        time = 20
+    );
+    bench_checker!(
+        name = cwe_330;
+        samples = 10;
+        time = 20
+    );
     bench_checker!(
         name = cwe_332;
         samples = 1000;
@@ -360,6 +385,8 @@ mod checkers {
         time = 10
     );
     bench_checker!(cwe_367);
+    bench_checker!(cwe_377);
+    bench_checker!(cwe_401);
     bench_checker!(
         name = cwe_416;
         samples = 10;
@@ -367,6 +394,7 @@ mod checkers {
     );
     bench_checker!(cwe_426);
     bench_checker!(cwe_467);
+    bench_checker!(cwe_468);
     bench_checker!(
         name = cwe_476;
         samples = 10;
@@ -377,9 +405,26 @@ mod checkers {
         samples = 1000;
         time = 10
     );
+    bench_checker!(cwe_590);
+    bench_checker!(
+        name = cwe_672;
+        samples = 10;
+        time = 20
+    );
     bench_checker!(cwe_676);
+    bench_checker!(
+        name = cwe_696;
+        samples = 10;
+        time = 20
+    );
     bench_checker!(cwe_782);
     bench_checker!(cwe_789);
+    bench_checker!(cwe_806);
+    bench_checker!(
+        name = cwe_839;
+        samples = 10;
+        time = 20
+    );
 }
 
 mod core_analyses {
@@ -503,10 +548,12 @@ mod core_analyses {
                         BenchmarkId::new("string_abstractions", pcode_project_json),
                         |b| {
                             b.iter_with_large_drop(|| {
-                                analysis_results.compute_string_abstraction(
-                                    &config["StringAbstraction"],
-                                    black_box(Some(&pi_result)),
-                                )
+                                analysis_results
+                                    .compute_string_abstraction(
+                                        &config["StringAbstraction"],
+                                        black_box(Some(&pi_result)),
+                                    )
+                                    .unwrap()
                             })
                         },
                     );
@@ -830,6 +877,115 @@ mod cg {
     }
 }
 
+mod fixpoint {
+    //! Benchmarks for the worklist order of the fixpoint algorithm.
+    //!
+    //! Besides the runtime, the number of fixpoint steps needed with each
+    //! worklist order is printed for every input.
+
+    use super::helpers::*;
+    use super::inputs::*;
+    use super::*;
+    use cwe_checker_lib::analysis::fixpoint::{
+        get_reverse_post_order_priorities, Computation, Context,
+    };
+    use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
+
+    /// Computes the minimal number of edges between the function entry points
+    /// and every node of the CFG.
+    struct DistanceContext<'a> {
+        graph: &'a graph::Graph<'a>,
+    }
+
+    impl<'a> Context for DistanceContext<'a> {
+        type EdgeLabel = graph::Edge<'a>;
+        type NodeLabel = graph::Node<'a>;
+        type NodeValue = u64;
+
+        fn get_graph(&self) -> &DiGraph<graph::Node<'a>, graph::Edge<'a>> {
+            self.graph
+        }
+
+        fn merge(&self, val1: &u64, val2: &u64) -> u64 {
+            std::cmp::min(*val1, *val2)
+        }
+
+        fn update_edge(&self, value: &u64, _edge: EdgeIndex) -> Option<u64> {
+            Some(value + 1)
+        }
+    }
+
+    /// Computes the distances in the CFG with the given worklist order and
+    /// returns the number of fixpoint steps.
+    fn compute_distances(cfg: &graph::Graph, priority_sorted_nodes: Vec<NodeIndex>) -> u64 {
+        let mut computation = Computation::from_node_priority_list(
+            DistanceContext { graph: cfg },
+            None,
+            priority_sorted_nodes,
+        );
+        for entry_node in graph::get_entry_nodes_of_subs(cfg).into_values() {
+            computation.set_node_value(entry_node, 0);
+        }
+        computation.compute();
+
+        computation.get_number_of_steps()
+    }
+
+    pub fn bench_worklist_order(c: &mut Criterion) {
+        let bench_with_input_loop =
+            |pcode_projects: &[&str], binaries: &[&str], mut group: BenchmarkGroup<WallTime>| {
+                for (pcode_project_json, binary) in iter::zip(pcode_projects, binaries) {
+                    let project = into_optimized_project(get_project(pcode_project_json, binary));
+                    let cfg = graph::get_program_cfg(&project.program);
+                    let orders = [
+                        (
+                            "scc",
+                            petgraph::algo::kosaraju_scc(&cfg)
+                                .into_iter()
+                                .flatten()
+                                .collect::<Vec<_>>(),
+                        ),
+                        (
+                            "reverse_post_order",
+                            get_reverse_post_order_priorities(&cfg),
+                        ),
+                    ];
+
+                    group.throughput(Throughput::Elements(cfg.edge_count() as u64));
+                    for (order_name, priority_sorted_nodes) in orders {
+                        println!(
+                            "{}: {} fixpoint steps with {} worklist order",
+                            pcode_project_json,
+                            compute_distances(&cfg, priority_sorted_nodes.clone()),
+                            order_name
+                        );
+                        group.bench_with_input(
+                            BenchmarkId::new(order_name, pcode_project_json),
+                            &priority_sorted_nodes,
+                            |b, priority_sorted_nodes| {
+                                b.iter_batched(
+                                    || priority_sorted_nodes.clone(),
+                                    |priority_sorted_nodes| {
+                                        compute_distances(&cfg, priority_sorted_nodes)
+                                    },
+                                    BatchSize::LargeInput,
+                                );
+                            },
+                        );
+                    }
+                }
+
+                group.finish();
+            };
+
+        let group_ls = c.benchmark_group("ls_worklist_order");
+        bench_with_input_loop(&LS_PCODE_PROJECTS, &LS_BINARIES, group_ls);
+
+        let group_netfs = c.benchmark_group("netfs_worklist_order");
+        bench_with_input_loop(&NETFS_PCODE_PROJECTS, &NETFS_BINARIES, group_netfs);
+    }
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default()
@@ -838,6 +994,7 @@ criterion_group!(
         .measurement_time(time::Duration::new(10, 0));
     targets = cfg::bench_cfg_construction,
         cg::bench_cg_construction,
+        fixpoint::bench_worklist_order,
         optimization::bench_optimize_intraprocedural_dead_block_elim,
         optimization::bench_optimize_input_expression_propagation,
         optimization::bench_optimize_trivial_expression_substitution,
@@ -849,20 +1006,33 @@ criterion_group!(
         core_analyses::bench_string_abstractions,
         checkers::bench_cwe_119,
         checkers::bench_cwe_134,
+        checkers::bench_cwe_170,
         checkers::bench_cwe_190,
         checkers::bench_cwe_215,
         checkers::bench_cwe_243,
         checkers::bench_cwe_252,
+        checkers::bench_cwe_306,
+        checkers::bench_cwe_311,
+        checkers::bench_cwe_319,
+        checkers::bench_cwe_330,
         checkers::bench_cwe_332,
         checkers::bench_cwe_337,
         checkers::bench_cwe_367,
+        checkers::bench_cwe_377,
+        checkers::bench_cwe_401,
         checkers::bench_cwe_416,
         checkers::bench_cwe_426,
         checkers::bench_cwe_467,
+        checkers::bench_cwe_468,
         checkers::bench_cwe_476,
         checkers::bench_cwe_560,
+        checkers::bench_cwe_590,
+        checkers::bench_cwe_672,
         checkers::bench_cwe_676,
+        checkers::bench_cwe_696,
         checkers::bench_cwe_782,
         checkers::bench_cwe_789,
+        checkers::bench_cwe_806,
+        checkers::bench_cwe_839,
 );
 criterion_main!(benches);
@@ -0,0 +1,231 @@
+//! Microbenchmarks for the operations of abstract domains.
+//!
+//! In contrast to the benchmarks in `benchmarks.rs` these benchmarks do not
+//! need any inputs, since they operate on representative values of the
+//! following domains:
+//!
+//! - Bricks domain (merge, normalization, widening, memoized merge),
+//! - Character Inclusion domain (merge),
+//! - Interval domain (merge with and without widening).
+//!
+//! The benchmarks are run and compared to a baseline like the other
+//! benchmarks, e.g.:
+//!
+//! ```
+//! $ cargo bench --bench "domains" -- --save-baseline master
+//! $ git checkout my_awesome_fix
+//! $ cargo bench --bench "domains" -- --verbose --baseline master
+//! ```
+//!
+//! To find out how often these operations are executed during an analysis run,
+//! use the hidden `--bench-mode` command line flag of the `cwe_checker`.
+
+use std::time;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use cwe_checker_lib::abstract_domain::{
+    AbstractDomain, BrickDomain, BricksDomain, BricksMemoScope, CharacterInclusionDomain,
+    DomainInsertion, IntervalDomain,
+};
+use cwe_checker_lib::intermediate_representation::Bitvector;
+
+mod inputs {
+    //! Representative values of the benchmarked domains.
+
+    use super::*;
+
+    /// Command strings as they are typically built with `sprintf` and `strcat`.
+    pub const COMMANDS: [&str; 6] = [
+        "/bin/sh -c ",
+        "/usr/bin/wget ",
+        "rm -rf /tmp/",
+        "cat /etc/passwd",
+        "ping -c 1 ",
+        "echo ",
+    ];
+
+    /// Returns pairs of brick lists that are merged at loop heads,
+    /// i.e. a command prefix followed by different arguments.
+    pub fn get_bricks_pairs() -> Vec<(BricksDomain, BricksDomain)> {
+        COMMANDS
+            .iter()
+            .zip(COMMANDS.iter().rev())
+            .map(|(first, second)| {
+                let prefix = BricksDomain::from(first.to_string());
+                let with_argument = prefix
+                    .append_string_domain(&BricksDomain::from(second.to_string()))
+                    .append_string_domain(&BricksDomain::create_integer_domain());
+                let with_unknown_suffix =
+                    prefix.append_string_domain(&BricksDomain::create_top_value_domain());
+                (with_argument, with_unknown_suffix)
+            })
+            .collect()
+    }
+
+    /// Returns brick lists that are not normalized,
+    /// e.g. successive bricks that occur exactly once.
+    pub fn get_unnormalized_bricks() -> Vec<BricksDomain> {
+        COMMANDS
+            .iter()
+            .map(|command| {
+                BricksDomain::Value(
+                    command
+                        .split(' ')
+                        .map(|part| BrickDomain::new(part.to_string()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns pairs of character inclusion domains of different commands.
+    pub fn get_character_inclusion_pairs(
+    ) -> Vec<(CharacterInclusionDomain, CharacterInclusionDomain)> {
+        COMMANDS
+            .iter()
+            .zip(COMMANDS.iter().rev())
+            .map(|(first, second)| {
+                (
+                    CharacterInclusionDomain::from(first.to_string()),
+                    CharacterInclusionDomain::from(second.to_string()),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns pairs of intervals of a loop counter in successive loop iterations.
+    /// If `with_widening_bounds` is set, the intervals have an upper widening bound.
+    pub fn get_interval_pairs(with_widening_bounds: bool) -> Vec<(IntervalDomain, IntervalDomain)> {
+        (0..64)
+            .map(|iteration| {
+                let mut first =
+                    IntervalDomain::new(Bitvector::from_i64(0), Bitvector::from_i64(iteration));
+                let second =
+                    IntervalDomain::new(Bitvector::from_i64(0), Bitvector::from_i64(iteration + 1));
+                if with_widening_bounds {
+                    first.update_widening_upper_bound(&Some(Bitvector::from_i64(256)));
+                }
+                (first, second)
+            })
+            .collect()
+    }
+}
+
+mod bricks {
+    //! Benchmarks for the Bricks domain.
+
+    use super::inputs::*;
+    use super::*;
+
+    pub fn bench_bricks_merge(c: &mut Criterion) {
+        let pairs = get_bricks_pairs();
+        c.bench_function("bricks_merge", |b| {
+            b.iter(|| {
+                for (first, second) in pairs.iter() {
+                    black_box(first.merge(second));
+                }
+            })
+        });
+    }
+
+    pub fn bench_bricks_memoized_merge(c: &mut Criterion) {
+        let pairs = get_bricks_pairs();
+        let _memo_scope = BricksMemoScope::new();
+        c.bench_function("bricks_memoized_merge", |b| {
+            b.iter(|| {
+                for (first, second) in pairs.iter() {
+                    black_box(first.merge(second));
+                }
+            })
+        });
+    }
+
+    pub fn bench_bricks_widen(c: &mut Criterion) {
+        let pairs = get_bricks_pairs();
+        c.bench_function("bricks_widen", |b| {
+            b.iter(|| {
+                for (first, second) in pairs.iter() {
+                    black_box(first.widen(second));
+                }
+            })
+        });
+    }
+
+    pub fn bench_bricks_normalize(c: &mut Criterion) {
+        let values = get_unnormalized_bricks();
+        c.bench_function("bricks_normalize", |b| {
+            b.iter_batched(
+                || values.clone(),
+                |values| {
+                    for value in values.iter() {
+                        black_box(value.normalize(256));
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+mod character_inclusion {
+    //! Benchmarks for the Character Inclusion domain.
+
+    use super::inputs::*;
+    use super::*;
+
+    pub fn bench_character_inclusion_merge(c: &mut Criterion) {
+        let pairs = get_character_inclusion_pairs();
+        c.bench_function("character_inclusion_merge", |b| {
+            b.iter(|| {
+                for (first, second) in pairs.iter() {
+                    black_box(first.merge(second));
+                }
+            })
+        });
+    }
+}
+
+mod interval {
+    //! Benchmarks for the Interval domain.
+
+    use super::inputs::*;
+    use super::*;
+
+    pub fn bench_interval_merge(c: &mut Criterion) {
+        let pairs = get_interval_pairs(false);
+        c.bench_function("interval_merge", |b| {
+            b.iter(|| {
+                for (first, second) in pairs.iter() {
+                    black_box(first.merge(second));
+                }
+            })
+        });
+    }
+
+    pub fn bench_interval_widen(c: &mut Criterion) {
+        let pairs = get_interval_pairs(true);
+        c.bench_function("interval_widen", |b| {
+            b.iter(|| {
+                for (first, second) in pairs.iter() {
+                    black_box(first.signed_merge_and_widen(second));
+                }
+            })
+        });
+    }
+}
+
+criterion_group!(
+    name = domains;
+    config = Criterion::default()
+        .warm_up_time(time::Duration::new(1, 0))
+        .measurement_time(time::Duration::new(3, 0));
+    targets = bricks::bench_bricks_merge,
+        bricks::bench_bricks_memoized_merge,
+        bricks::bench_bricks_widen,
+        bricks::bench_bricks_normalize,
+        character_inclusion::bench_character_inclusion_merge,
+        interval::bench_interval_merge,
+        interval::bench_interval_widen,
+);
+criterion_main!(domains);
@@ -1,5 +1,7 @@
 use super::Interval;
-use super::{AbstractDomain, HasTop, RegisterDomain, SizedDomain, TryToBitvec, TryToInterval};
+use super::{
+    AbstractDomain, DomainOrder, HasTop, RegisterDomain, SizedDomain, TryToBitvec, TryToInterval,
+};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 
@@ -30,6 +32,13 @@ impl AbstractDomain for BitvectorDomain {
     }
 }
 
+impl DomainOrder for BitvectorDomain {
+    /// Every value is less than or equal to *Top* and to itself.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        other.is_top() || self == other
+    }
+}
+
 impl HasTop for BitvectorDomain {
     /// Return a *Top* value with the same bytesize as `self`.
     fn top(&self) -> BitvectorDomain {
@@ -188,6 +197,15 @@ pub mod tests {
         bitvec!(format!("{}:8", value)).into()
     }
 
+    #[test]
+    fn bitvector_domain_order() {
+        let top = BitvectorDomain::new_top(ByteSize::new(8));
+        assert!(bv(8).less_or_equal(&bv(8)));
+        assert!(bv(8).less_or_equal(&top));
+        assert!(!bv(8).less_or_equal(&bv(16)));
+        assert!(!top.less_or_equal(&bv(8)));
+    }
+
     #[test]
     fn bitvector_domain_as_value_domain() {
         use BinOpType::*;
@@ -25,21 +25,31 @@
 //!    sequences in a brick by forcing a *Top* value.
 //!  - The *length threshold* which  overapproximates the number of bricks in
 //!    the BricksDomain and forces a *Top* value.
+//!
+//! Results of merges and normalizations are memoized while a [`BricksMemoScope`] is active,
+//! since fixpoint computations repeatedly merge the same brick lists.
 
 use std::{collections::BTreeSet, fmt};
 
-use super::{AbstractDomain, DomainInsertion, HasTop};
+use super::operation_counters::{self, CountedDomain, DomainOperation};
+use super::{AbstractDomain, DomainInsertion, DomainOrder, HasTop, TryToConcrete};
 use crate::prelude::*;
 use std::fmt::Debug;
 
 mod brick;
 use brick::Brick;
 
+mod format_string;
+pub use format_string::{FieldSize, FormatSpecifier, FormatString};
+
+mod memo;
+pub use memo::BricksMemoScope;
+
 mod widening;
 
 /// The BricksDomain contains a sorted list of single normalized BrickDomains.
 /// It represents the composition of a string through sub sequences.
-#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
 pub enum BricksDomain {
     /// The *Top* value represents an invalid sequence.
     Top,
@@ -70,20 +80,32 @@ impl BricksDomain {
     ///
     /// Since normalization is rather expensive w.r.t. runtime and since it could entail a precision loss,
     /// it is only computed after a merge or widening operation.
-    pub fn normalize(&self) -> Self {
-        let mut normalized = self.unwrap_value();
+    ///
+    /// Steps 2, 3 and 5 may create bricks with exponentially many string sequences.
+    /// If a resulting brick would contain more than `max_cardinality` sequences,
+    /// it is replaced by a *Top* brick instead.
+    pub fn normalize(&self, max_cardinality: usize) -> Self {
+        memo::memoized_normalization(self, max_cardinality, || {
+            operation_counters::count(CountedDomain::Bricks, DomainOperation::Normalize);
+            self.normalize_without_memoization(max_cardinality)
+        })
+    }
+
+    /// Normalize the bricks, see [`BricksDomain::normalize`].
+    fn normalize_without_memoization(&self, max_cardinality: usize) -> Self {
+        let BricksDomain::Value(bricks) = self else {
+            return BricksDomain::Top;
+        };
+        let mut normalized = bricks.clone();
         // A second vector to do lookups and to iterate over the values.
-        let mut lookup = self.unwrap_value();
+        let mut lookup = bricks.clone();
         let mut unchanged = false;
         while !unchanged {
             for (index, brick_domain) in lookup.iter().enumerate() {
                 // Ignore Top value bricks.
-                if brick_domain.is_top() {
+                let BrickDomain::Value(current_brick) = brick_domain else {
                     continue;
-                }
-
-                // Get the current brick for checks .
-                let current_brick = brick_domain.unwrap_value();
+                };
 
                 // --Step 1-- Check whether the brick contains the empty string only.
                 // If so, remove the brick from the list.
@@ -96,9 +118,12 @@ impl BricksDomain {
                 // If so, create all permutations of the size of min=max and set them to 1.
                 if current_brick.get_min() == current_brick.get_max() && current_brick.get_min() > 1
                 {
-                    let transformed_brick = current_brick
-                        .transform_brick_with_min_max_equal(current_brick.get_min() as usize);
-                    normalized[index] = BrickDomain::Value(transformed_brick);
+                    normalized[index] = current_brick
+                        .transform_brick_with_min_max_equal(
+                            current_brick.get_min() as usize,
+                            max_cardinality,
+                        )
+                        .map_or(BrickDomain::Top, BrickDomain::Value);
                     break;
                 }
 
@@ -106,41 +131,42 @@ impl BricksDomain {
                 // If so, break the brick into simpler bricks.
                 if current_brick.get_min() >= 1 && current_brick.get_max() > current_brick.get_min()
                 {
-                    let (new_brick1, new_brick2) =
-                        current_brick.break_single_brick_into_simpler_bricks();
-                    normalized[index] = BrickDomain::Value(new_brick1);
-                    normalized.insert(index + 1, BrickDomain::Value(new_brick2));
+                    if let Some((new_brick1, new_brick2)) =
+                        current_brick.break_single_brick_into_simpler_bricks(max_cardinality)
+                    {
+                        normalized[index] = BrickDomain::Value(new_brick1);
+                        normalized.insert(index + 1, BrickDomain::Value(new_brick2));
+                    } else {
+                        normalized[index] = BrickDomain::Top;
+                    }
                     break;
                 }
 
                 // Check whether bricks can be merged.
-                if let Some(next_brick_domain) = lookup.get(index + 1) {
-                    if !next_brick_domain.is_top() {
-                        let next_brick = next_brick_domain.unwrap_value();
-                        // --Step 2-- Check whether two successive bricks are bound by one in min and max.
-                        // If so, merge them by taking the cartesian product of the sequences.
-                        if (
-                            current_brick.get_min(),
-                            current_brick.get_max(),
-                            next_brick.get_min(),
-                            next_brick.get_max(),
-                        ) == (1, 1, 1, 1)
-                        {
-                            let merged_brick =
-                                current_brick.merge_bricks_with_bound_one(next_brick);
-                            normalized[index] = BrickDomain::Value(merged_brick);
-                            normalized.remove(index + 1);
-                            break;
-                        }
-                        // --Step 4-- Check whether two successive bricks have equal content.
-                        // If so, merge them with the same content and add their min and max values together.
-                        else if current_brick.get_sequence() == next_brick.get_sequence() {
-                            let merged_brick =
-                                current_brick.merge_bricks_with_equal_content(next_brick);
-                            normalized[index] = BrickDomain::Value(merged_brick);
-                            normalized.remove(index + 1);
-                            break;
-                        }
+                if let Some(BrickDomain::Value(next_brick)) = lookup.get(index + 1) {
+                    // --Step 2-- Check whether two successive bricks are bound by one in min and max.
+                    // If so, merge them by taking the cartesian product of the sequences.
+                    if (
+                        current_brick.get_min(),
+                        current_brick.get_max(),
+                        next_brick.get_min(),
+                        next_brick.get_max(),
+                    ) == (1, 1, 1, 1)
+                    {
+                        normalized[index] = current_brick
+                            .merge_bricks_with_bound_one(next_brick.clone(), max_cardinality)
+                            .map_or(BrickDomain::Top, BrickDomain::Value);
+                        normalized.remove(index + 1);
+                        break;
+                    }
+                    // --Step 4-- Check whether two successive bricks have equal content.
+                    // If so, merge them with the same content and add their min and max values together.
+                    else if current_brick.get_sequence() == next_brick.get_sequence() {
+                        let merged_brick =
+                            current_brick.merge_bricks_with_equal_content(next_brick.clone());
+                        normalized[index] = BrickDomain::Value(merged_brick);
+                        normalized.remove(index + 1);
+                        break;
                     }
                 }
             }
@@ -160,8 +186,11 @@ impl BricksDomain {
     /// correspondence, empty string bricks will be added in a way that
     /// equal bricks have the same indices in both lists.
     fn pad_list(&self, other: &BricksDomain) -> Self {
-        let mut short_list = self.unwrap_value();
-        let long_list = other.unwrap_value();
+        let (BricksDomain::Value(short_list), BricksDomain::Value(long_list)) = (self, other)
+        else {
+            return self.clone();
+        };
+        let mut short_list = short_list.clone();
         let mut new_list: Vec<BrickDomain> = Vec::new();
         let len_diff = long_list.len() - short_list.len();
 
@@ -171,7 +200,7 @@ impl BricksDomain {
             if empty_bricks_added >= len_diff {
                 new_list.push(short_list[0].clone());
                 short_list.remove(0);
-            } else if short_list.is_empty() || &short_list[0] != long_list.get(i).unwrap() {
+            } else if short_list.is_empty() || long_list.get(i) != Some(&short_list[0]) {
                 new_list.push(BrickDomain::get_empty_brick_domain());
                 empty_bricks_added += 1;
             } else {
@@ -183,11 +212,63 @@ impl BricksDomain {
         BricksDomain::Value(new_list)
     }
 
-    /// Unwraps a list of BrickDomains and panic if it's *Top*
-    fn unwrap_value(&self) -> Vec<BrickDomain> {
+    /// Returns the maximal length of the strings represented by the given bricks
+    /// or `None` if it is unbounded.
+    fn get_max_length_of_bricks(bricks: &[BrickDomain]) -> Option<usize> {
+        bricks.iter().try_fold(0, |length, brick| match brick {
+            BrickDomain::Top => None,
+            BrickDomain::Value(brick) => {
+                let max_string_length = brick
+                    .get_sequence()
+                    .iter()
+                    .map(|string| string.len())
+                    .max()
+                    .unwrap_or(0);
+                Some(length + max_string_length * brick.get_max() as usize)
+            }
+        })
+    }
+
+    /// Returns the set of represented strings if it consists of a single brick
+    /// that occurs exactly once.
+    fn get_finite_strings(&self) -> Option<BTreeSet<String>> {
         match self {
-            BricksDomain::Value(bricks) => bricks.clone(),
-            _ => panic!("Unexpected Brick Domain type."),
+            BricksDomain::Value(bricks) => match bricks.as_slice() {
+                [] => Some(BTreeSet::from([String::new()])),
+                [BrickDomain::Value(brick)] if (brick.get_min(), brick.get_max()) == (1, 1) => {
+                    Some(brick.get_sequence().clone())
+                }
+                _ => None,
+            },
+            BricksDomain::Top => None,
+        }
+    }
+
+    /// Checks whether the given string is represented by the domain.
+    pub(crate) fn contains_string(&self, string: &str) -> bool {
+        let BricksDomain::Value(bricks) = self else {
+            return true;
+        };
+        let chars: Vec<char> = string.chars().collect();
+        // The set of positions in the string up to which the string is matched by the bricks.
+        let mut positions = BTreeSet::from([0]);
+        for brick in bricks {
+            positions = match brick {
+                BrickDomain::Top => match positions.first() {
+                    Some(&first) => (first..=chars.len()).collect(),
+                    None => return false,
+                },
+                BrickDomain::Value(brick) => brick.get_end_positions(&chars, &positions),
+            };
+        }
+        positions.contains(&chars.len())
+    }
+
+    /// Returns the list of bricks or `None` if the domain is *Top*.
+    pub fn as_value(&self) -> Option<&[BrickDomain]> {
+        match self {
+            BricksDomain::Value(bricks) => Some(bricks),
+            BricksDomain::Top => None,
         }
     }
 }
@@ -265,23 +346,186 @@ impl DomainInsertion for BricksDomain {
     fn create_empty_string_domain() -> Self {
         BricksDomain::from("".to_string())
     }
-}
 
-impl AbstractDomain for BricksDomain {
-    /// Takes care of merging lists of bricks
-    fn merge(&self, other: &Self) -> Self {
+    /// Truncate the bricks to the given length in bytes.
+    ///
+    /// Bricks are kept as long as their length is exactly known and fits into the given length.
+    /// The first brick that does not fit is truncated if it consists of fixed strings
+    /// that are all at least as long as the remaining length
+    /// and that are not cut in the middle of a multi-byte character.
+    /// Otherwise the remaining bricks are only kept if they fit in any case,
+    /// else they are replaced by a *Top* brick.
+    fn truncate(&self, length: usize) -> Self {
+        let BricksDomain::Value(bricks) = self else {
+            return BricksDomain::Top;
+        };
+        let mut remaining = length;
+        let mut truncated = Vec::new();
+        for (index, brick) in bricks.iter().enumerate() {
+            if remaining == 0 {
+                break;
+            }
+            if let BrickDomain::Value(brick) = brick {
+                let lengths: BTreeSet<usize> = brick
+                    .get_sequence()
+                    .iter()
+                    .map(|string| string.len())
+                    .collect();
+                if let (Some(&min_length), Some(&max_length), 1, 1) = (
+                    lengths.first(),
+                    lengths.last(),
+                    brick.get_min(),
+                    brick.get_max(),
+                ) {
+                    if min_length == max_length && max_length <= remaining {
+                        truncated.push(BrickDomain::Value(brick.clone()));
+                        remaining -= max_length;
+                        continue;
+                    } else if min_length >= remaining
+                        && brick
+                            .get_sequence()
+                            .iter()
+                            .all(|string| string.is_char_boundary(remaining))
+                    {
+                        let mut truncated_brick = brick.clone();
+                        truncated_brick.set_sequence(
+                            brick
+                                .get_sequence()
+                                .iter()
+                                .map(|string| string[..remaining].to_string())
+                                .collect(),
+                        );
+                        truncated.push(BrickDomain::Value(truncated_brick));
+                        break;
+                    }
+                }
+            }
+            match BricksDomain::get_max_length_of_bricks(&bricks[index..]) {
+                Some(max_length) if max_length <= remaining => {
+                    truncated.extend(bricks[index..].iter().cloned())
+                }
+                _ => truncated.push(BrickDomain::Top),
+            }
+            break;
+        }
+        if truncated.is_empty() {
+            Self::create_empty_string_domain()
+        } else {
+            BricksDomain::Value(truncated)
+        }
+    }
+
+    /// Returns the sum of the minimal lengths of the bricks.
+    /// *Top* bricks may represent the empty string.
+    fn get_min_length(&self) -> usize {
+        let BricksDomain::Value(bricks) = self else {
+            return 0;
+        };
+        bricks
+            .iter()
+            .map(|brick| match brick {
+                BrickDomain::Top => 0,
+                BrickDomain::Value(brick) => {
+                    let min_string_length = brick
+                        .get_sequence()
+                        .iter()
+                        .map(|string| string.len())
+                        .min()
+                        .unwrap_or(0);
+                    min_string_length * brick.get_min() as usize
+                }
+            })
+            .sum()
+    }
+
+    /// Returns the sum of the maximal lengths of the bricks
+    /// or `None` if the domain contains *Top* bricks.
+    fn get_max_length(&self) -> Option<usize> {
+        match self {
+            BricksDomain::Value(bricks) => BricksDomain::get_max_length_of_bricks(bricks),
+            BricksDomain::Top => None,
+        }
+    }
+
+    /// Intersect two domains.
+    ///
+    /// If one of the domains represents a finite set of strings,
+    /// the strings that are not represented by the other domain are removed from it.
+    /// Otherwise the intersection is overapproximated by `self`.
+    fn intersect(&self, other: &Self) -> Result<Self, Error> {
+        if self.is_top() || self == other {
+            return Ok(other.clone());
+        } else if other.is_top() {
+            return Ok(self.clone());
+        }
+        let (strings, other) = match (self.get_finite_strings(), other.get_finite_strings()) {
+            (Some(strings), _) => (strings, other),
+            (None, Some(strings)) => (strings, self),
+            (None, None) => return Ok(self.clone()),
+        };
+        let strings: BTreeSet<String> = strings
+            .into_iter()
+            .filter(|string| other.contains_string(string))
+            .collect();
+        if strings.is_empty() {
+            return Err(anyhow!("Empty intersection of bricks domains."));
+        }
+        let mut brick = Brick::new();
+        brick.set_sequence(strings);
+        brick.set_min(1);
+        brick.set_max(1);
+
+        Ok(BricksDomain::Value(vec![BrickDomain::Value(brick)]))
+    }
+
+    /// Merge two lists of bricks by widening them and normalizing the result.
+    /// The result is memoized if a [`BricksMemoScope`] is active.
+    /// Bricks created during normalization that would contain more than `max_cardinality`
+    /// string sequences are replaced by *Top* bricks.
+    fn merge_with_cardinality_limit(&self, other: &Self, max_cardinality: usize) -> Self {
         if self.is_top() || other.is_top() {
             Self::Top
         } else if self == other {
             self.clone()
         } else {
-            let merged = self.widen(other);
-            if !merged.is_top() {
-                return merged.normalize();
-            }
+            memo::memoized_merge(self, other, max_cardinality, || {
+                operation_counters::count(CountedDomain::Bricks, DomainOperation::Merge);
+                let merged = self.widen(other);
+                if !merged.is_top() {
+                    return merged.normalize(max_cardinality);
+                }
+
+                merged
+            })
+        }
+    }
+}
 
-            merged
+impl TryToConcrete for BricksDomain {
+    /// Enumerate the strings represented by the bricks.
+    /// Fails if the domain contains *Top* bricks.
+    fn try_to_concrete(&self, max_count: usize) -> Result<BTreeSet<String>, Error> {
+        let BricksDomain::Value(bricks) = self else {
+            return Err(anyhow!("Bricks domain is Top."));
+        };
+        let mut strings = BTreeSet::from([String::new()]);
+        for brick in bricks {
+            match brick {
+                BrickDomain::Top => return Err(anyhow!("Bricks domain contains Top bricks.")),
+                BrickDomain::Value(brick) => {
+                    strings = brick.append_to_strings(&strings, max_count)?
+                }
+            }
         }
+        Ok(strings)
+    }
+}
+
+impl AbstractDomain for BricksDomain {
+    /// Takes care of merging lists of bricks.
+    /// The cardinality of the bricks created during normalization is not limited.
+    fn merge(&self, other: &Self) -> Self {
+        self.merge_with_cardinality_limit(other, usize::MAX)
     }
 
     /// Check if the value is *Top*.
@@ -290,6 +534,24 @@ impl AbstractDomain for BricksDomain {
     }
 }
 
+impl DomainOrder for BricksDomain {
+    /// Compare two lists of bricks of equal length brick by brick.
+    /// Lists of different lengths are only ordered if `other` is *Top*.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        match (self, other) {
+            (_, BricksDomain::Top) => true,
+            (BricksDomain::Top, BricksDomain::Value(_)) => false,
+            (BricksDomain::Value(self_bricks), BricksDomain::Value(other_bricks)) => {
+                self_bricks.len() == other_bricks.len()
+                    && self_bricks
+                        .iter()
+                        .zip(other_bricks.iter())
+                        .all(|(self_brick, other_brick)| self_brick.less_or_equal(other_brick))
+            }
+        }
+    }
+}
+
 impl HasTop for BricksDomain {
     /// Return a *Top* value
     fn top(&self) -> Self {
@@ -302,7 +564,7 @@ impl HasTop for BricksDomain {
 ///
 /// e.g. \[{"mo", "de"}\]^{1,2} represents the following set of strings:
 /// {mo, de, momo, dede, mode, demo}.
-#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
 pub enum BrickDomain {
     /// The *Top* value represents the powerset over the alphabet
     /// of allowed characters with a minimum of 0 and a maximum of positive infinity.
@@ -329,11 +591,11 @@ impl BrickDomain {
         BrickDomain::Value(Brick::new())
     }
 
-    /// Unwraps a brick value and panics if it's *Top*.
-    fn unwrap_value(&self) -> Brick {
+    /// Returns the brick or `None` if the domain is *Top*.
+    pub fn as_value(&self) -> Option<&Brick> {
         match self {
-            BrickDomain::Value(brick) => brick.clone(),
-            _ => panic!("Unexpected Brick Domain type."),
+            BrickDomain::Value(brick) => Some(brick),
+            BrickDomain::Top => None,
         }
     }
 }
@@ -363,6 +625,26 @@ impl AbstractDomain for BrickDomain {
     }
 }
 
+impl DomainOrder for BrickDomain {
+    /// `self` is less than or equal to `other` if its sequences are contained in the sequences of `other`
+    /// and its bounds are contained in the bounds of `other`.
+    ///
+    /// In contrast to [`BrickDomain::is_less_or_equal`] empty string bricks are not treated specially.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        match (self, other) {
+            (_, BrickDomain::Top) => true,
+            (BrickDomain::Top, BrickDomain::Value(_)) => false,
+            (BrickDomain::Value(self_brick), BrickDomain::Value(other_brick)) => {
+                self_brick
+                    .get_sequence()
+                    .is_subset(other_brick.get_sequence())
+                    && self_brick.get_min() >= other_brick.get_min()
+                    && self_brick.get_max() <= other_brick.get_max()
+            }
+        }
+    }
+}
+
 impl fmt::Display for BrickDomain {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -12,7 +12,7 @@ use crate::prelude::*;
 use itertools::Itertools;
 
 /// A single Brick with the set of strings, a minimum and maximum bound.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Brick {
     sequence: BTreeSet<String>,
     min: u32,
@@ -65,6 +65,78 @@ impl Brick {
         self.max
     }
 
+    /// Returns the positions in `chars` up to which a prefix can be matched by the brick,
+    /// if the brick starts matching at one of the given start positions.
+    pub fn get_end_positions(&self, chars: &[char], starts: &BTreeSet<usize>) -> BTreeSet<usize> {
+        let sequence: Vec<Vec<char>> = self
+            .sequence
+            .iter()
+            .map(|string| string.chars().collect())
+            .collect();
+        let mut end_positions = BTreeSet::new();
+        if self.min == 0 {
+            end_positions.extend(starts.iter().copied());
+        }
+        let mut current = starts.clone();
+        for occurrences in 1..=self.max {
+            let next: BTreeSet<usize> = current
+                .iter()
+                .flat_map(|&start| {
+                    sequence
+                        .iter()
+                        .filter(move |string| chars[start..].starts_with(string))
+                        .map(move |string| start + string.len())
+                })
+                .collect();
+            if next == current {
+                // Every further occurrence yields the same positions.
+                end_positions.extend(next);
+                break;
+            }
+            if occurrences >= self.min {
+                end_positions.extend(next.iter().copied());
+            }
+            if next.is_empty() {
+                break;
+            }
+            current = next;
+        }
+        end_positions
+    }
+
+    /// Appends the strings represented by the brick to each of the given prefixes.
+    ///
+    /// Returns an error if the result would contain more than `max_count` strings.
+    pub fn append_to_strings(
+        &self,
+        prefixes: &BTreeSet<String>,
+        max_count: usize,
+    ) -> Result<BTreeSet<String>, Error> {
+        let mut strings = BTreeSet::new();
+        if self.min == 0 {
+            strings.extend(prefixes.iter().cloned());
+        }
+        let mut current = prefixes.clone();
+        for occurrences in 1..=self.max {
+            let next: BTreeSet<String> = current
+                .iter()
+                .cartesian_product(self.sequence.iter())
+                .map(|(prefix, string)| format!("{prefix}{string}"))
+                .collect();
+            if occurrences >= self.min {
+                strings.extend(next.iter().cloned());
+            }
+            if next.len() > max_count || strings.len() > max_count {
+                return Err(anyhow!("Brick represents more than {} strings.", max_count));
+            }
+            if next.is_empty() || next == current {
+                break;
+            }
+            current = next;
+        }
+        Ok(strings)
+    }
+
     /// Checks whether a brick represents an empty string (Rule 1)
     pub fn is_empty_string(&self) -> bool {
         if self.sequence.is_empty() && self.min == 0 && self.max == 0 {
@@ -76,7 +148,16 @@ impl Brick {
     /// **merge** bricks with the same indices max = 1, min = 1, in a new single brick
     /// with the new string set being the concatenation of the former two. e.g. B0 = \[{a,cd}\]^{1,1}
     /// and B1 = \[{b,ef}\]^{1,1} become B_new = \[{ab, aef, cdb, cdef}\]^{1,1}.
-    pub fn merge_bricks_with_bound_one(&self, other: Brick) -> Self {
+    ///
+    /// Returns `None` if the new string set would contain more than `max_cardinality` strings.
+    pub fn merge_bricks_with_bound_one(
+        &self,
+        other: Brick,
+        max_cardinality: usize,
+    ) -> Option<Self> {
+        if self.sequence.len().saturating_mul(other.sequence.len()) > max_cardinality {
+            return None;
+        }
         let product = self
             .sequence
             .iter()
@@ -87,25 +168,37 @@ impl Brick {
             .map(|&(str1, str2)| str1.clone() + str2)
             .collect();
 
-        Brick {
+        Some(Brick {
             sequence,
             min: 1,
             max: 1,
-        }
+        })
     }
 
     /// **transform** a brick in which the number of applications is constant (min = max) into one in which
     /// min = max = 1. e.g. B = \[{a,b}\]^{2,2} => B_new = \[{aa, ab, ba, bb}\]^{1,1}.
-    pub fn transform_brick_with_min_max_equal(&self, length: usize) -> Self {
+    ///
+    /// Returns `None` if the new string set would contain more than `max_cardinality` strings.
+    pub fn transform_brick_with_min_max_equal(
+        &self,
+        length: usize,
+        max_cardinality: usize,
+    ) -> Option<Self> {
+        let cardinality = u32::try_from(length)
+            .ok()
+            .and_then(|length| self.sequence.len().checked_pow(length));
+        if cardinality.is_none_or(|cardinality| cardinality > max_cardinality) {
+            return None;
+        }
         let permutations: BTreeSet<String> =
             Self::generate_permutations_of_fixed_length(length, &self.sequence, Vec::new(), 1)
                 .into_iter()
                 .collect();
-        Brick {
+        Some(Brick {
             sequence: permutations,
             min: 1,
             max: 1,
-        }
+        })
     }
 
     /// **merge** two bricks in which the set of strings is the same. e.g. B1 = \[S\]^{m1, M1}
@@ -121,15 +214,21 @@ impl Brick {
     /// **break** a single brick with min >= 1 and max != min into two simpler bricks where B = \[S\]^{min,max} =>
     /// B1 = \[S^min\]^{1,1}, B2 = \[S\]^{0, max-min}.
     /// e.g. B = \[{a}\]^{2,5} => B1 = \[{aa}\]^{1,1}, B2 = \[{a}\]^{0,3}
-    pub fn break_single_brick_into_simpler_bricks(&self) -> (Self, Self) {
-        let brick_1 = self.transform_brick_with_min_max_equal(self.min as usize);
+    ///
+    /// Returns `None` if B1 would contain more than `max_cardinality` strings.
+    pub fn break_single_brick_into_simpler_bricks(
+        &self,
+        max_cardinality: usize,
+    ) -> Option<(Self, Self)> {
+        let brick_1 =
+            self.transform_brick_with_min_max_equal(self.min as usize, max_cardinality)?;
         let brick_2 = Brick {
             sequence: self.sequence.clone(),
             min: 0,
             max: self.max - self.min,
         };
 
-        (brick_1, brick_2)
+        Some((brick_1, brick_2))
     }
 
     /// Recursive function to generate sequence permutations of fixed length.
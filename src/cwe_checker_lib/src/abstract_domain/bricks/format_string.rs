@@ -0,0 +1,332 @@
+//! Parsing of `printf`-style format strings represented by the BricksDomain.
+//!
+//! A format string is split into its literal characters and its conversion
+//! specifications, e.g. `%-08.3lf`. For each specification the number and
+//! the data types of the consumed variadic arguments and a lower bound for the
+//! number of written characters can be computed.
+
+use super::{BricksDomain, TryToConcrete};
+use crate::intermediate_representation::Datatype;
+use crate::prelude::*;
+
+/// Flag characters that may follow the `%` of a conversion specification.
+const FLAGS: &str = "-+ #0'";
+
+/// Length modifiers, longest first so that e.g. `ll` is not parsed as `l`.
+const LENGTH_MODIFIERS: [&str; 9] = ["hh", "ll", "h", "l", "L", "j", "z", "t", "q"];
+
+/// The minimum number of characters written for infinite or NaN floating
+/// point values, i.e. for `inf` or `nan`.
+const MIN_NON_FINITE_LENGTH: u64 = 3;
+
+/// The width or precision of a conversion specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldSize {
+    /// The value is given in the format string.
+    Fixed(u64),
+    /// The value is given by an additional `int` argument (`*`).
+    Argument,
+}
+
+/// A single conversion specification of a format string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatSpecifier {
+    /// The flag characters, e.g. `-` or `0`.
+    pub flags: String,
+    /// The minimum field width.
+    pub width: Option<FieldSize>,
+    /// The precision.
+    pub precision: Option<FieldSize>,
+    /// The length modifier, e.g. `l` or `hh`.
+    pub length_modifier: String,
+    /// The conversion specifier character, e.g. `d` or `s`.
+    pub conversion: char,
+}
+
+impl FormatSpecifier {
+    /// Returns the data type of the argument converted by the specification.
+    ///
+    /// Returns `None` for specifications that do not convert an argument, i.e.
+    /// `%%` and `%m`.
+    pub fn get_datatype(&self) -> Option<Datatype> {
+        let datatype = match self.conversion {
+            'd' | 'i' | 'o' | 'u' | 'x' | 'X' => match self.length_modifier.as_str() {
+                "l" | "j" | "z" | "t" => Datatype::Long,
+                "ll" | "q" => Datatype::LongLong,
+                _ => Datatype::Integer,
+            },
+            'c' | 'C' => Datatype::Char,
+            's' | 'S' | 'p' | 'n' => Datatype::Pointer,
+            'e' | 'E' | 'f' | 'F' | 'g' | 'G' | 'a' | 'A' => {
+                if self.length_modifier == "L" {
+                    Datatype::LongDouble
+                } else {
+                    Datatype::Double
+                }
+            }
+            _ => return None,
+        };
+        Some(datatype)
+    }
+
+    /// Returns the data types of all arguments consumed by the specification.
+    ///
+    /// Widths and precisions given by `*` consume an `int` argument before the
+    /// converted argument.
+    pub fn get_argument_datatypes(&self) -> Vec<Datatype> {
+        [self.width, self.precision]
+            .into_iter()
+            .filter(|size| *size == Some(FieldSize::Argument))
+            .map(|_| Datatype::Integer)
+            .chain(self.get_datatype())
+            .collect()
+    }
+
+    /// Returns a lower bound for the number of characters written for the
+    /// specification.
+    pub fn get_min_output_length(&self) -> u64 {
+        let precision = match self.precision {
+            Some(FieldSize::Fixed(precision)) => Some(precision),
+            // A negative precision argument is taken as if the precision was
+            // omitted, so the smallest possible precision is zero.
+            Some(FieldSize::Argument) => Some(0),
+            None => None,
+        };
+        let sign = u64::from(self.flags.contains(['+', ' ']));
+        // Digits and the decimal point of the fractional part of a floating
+        // point number.
+        let fraction = |default: u64| match precision.unwrap_or(default) {
+            0 if self.flags.contains('#') => 1,
+            0 => 0,
+            precision => precision + 1,
+        };
+        let length = match self.conversion {
+            'd' | 'i' => sign + precision.unwrap_or(1),
+            'o' | 'u' | 'x' | 'X' => precision.unwrap_or(1),
+            'c' | 'C' | '%' => 1,
+            // "0x0", glibc prints "(nil)" for NULL.
+            'p' => 3,
+            // "0" and the fraction.
+            'f' | 'F' => sign + std::cmp::min(1 + fraction(6), MIN_NON_FINITE_LENGTH),
+            // "0", the fraction and the exponent "e+00".
+            'e' | 'E' => sign + std::cmp::min(5 + fraction(6), MIN_NON_FINITE_LENGTH),
+            // "0"
+            'g' | 'G' => sign + 1,
+            // "0x0", the fraction and the exponent "p+0".
+            'a' | 'A' => sign + std::cmp::min(6 + fraction(0), MIN_NON_FINITE_LENGTH),
+            _ => 0,
+        };
+        match self.width {
+            Some(FieldSize::Fixed(width)) => std::cmp::max(width, length),
+            _ => length,
+        }
+    }
+}
+
+/// A parsed `printf`-style format string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatString {
+    /// The conversion specifications in the order of their occurrence.
+    pub specifiers: Vec<FormatSpecifier>,
+    /// The number of characters that are not part of a conversion
+    /// specification.
+    pub literal_length: u64,
+}
+
+impl FormatString {
+    /// Parses the given format string.
+    ///
+    /// Returns an error for invalid conversion specifications and for
+    /// positional arguments like `%1$s`, which are not supported.
+    pub fn parse(format_string: &str) -> Result<Self, Error> {
+        let mut specifiers = Vec::new();
+        let mut literal_length = 0;
+        let mut chars = format_string.chars().peekable();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                literal_length += 1;
+                continue;
+            }
+            let mut flags = String::new();
+            while let Some(flag) = chars.next_if(|char| FLAGS.contains(*char)) {
+                flags.push(flag);
+            }
+            let width = parse_field_size(&mut chars);
+            let precision = if chars.next_if_eq(&'.').is_some() {
+                Some(parse_field_size(&mut chars).unwrap_or(FieldSize::Fixed(0)))
+            } else {
+                None
+            };
+            if chars.peek() == Some(&'$') {
+                return Err(anyhow!("Positional arguments are not supported."));
+            }
+            let mut length_modifier = String::new();
+            let rest: String = chars.clone().take(2).collect();
+            if let Some(modifier) = LENGTH_MODIFIERS
+                .iter()
+                .find(|modifier| rest.starts_with(*modifier))
+            {
+                length_modifier.push_str(modifier);
+                chars.nth(modifier.len() - 1);
+            }
+            let conversion = chars
+                .next()
+                .ok_or_else(|| anyhow!("Incomplete conversion specification."))?;
+            if !"diouxXeEfFgGaAcCsSpnm%".contains(conversion) {
+                return Err(anyhow!("Invalid conversion specifier '{}'.", conversion));
+            }
+            specifiers.push(FormatSpecifier {
+                flags,
+                width,
+                precision,
+                length_modifier,
+                conversion,
+            });
+        }
+
+        Ok(FormatString {
+            specifiers,
+            literal_length,
+        })
+    }
+
+    /// Returns the data types of all variadic arguments expected by the format
+    /// string in the order in which they are consumed.
+    pub fn get_argument_datatypes(&self) -> Vec<Datatype> {
+        self.specifiers
+            .iter()
+            .flat_map(FormatSpecifier::get_argument_datatypes)
+            .collect()
+    }
+
+    /// Returns a lower bound for the number of characters written for the
+    /// format string, excluding the terminating null byte.
+    pub fn get_min_output_length(&self) -> u64 {
+        self.literal_length
+            + self
+                .specifiers
+                .iter()
+                .map(FormatSpecifier::get_min_output_length)
+                .sum::<u64>()
+    }
+}
+
+/// Parses a width or precision at the current position of the iterator.
+fn parse_field_size(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<FieldSize> {
+    if chars.next_if_eq(&'*').is_some() {
+        return Some(FieldSize::Argument);
+    }
+    let mut size: Option<u64> = None;
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        size = Some(
+            size.unwrap_or(0)
+                .saturating_mul(10)
+                .saturating_add(u64::from(digit) - u64::from('0')),
+        );
+    }
+    size.map(FieldSize::Fixed)
+}
+
+impl BricksDomain {
+    /// Parses all format strings represented by the domain.
+    ///
+    /// Fails if the domain represents more than `max_count` strings, if it
+    /// contains *Top* bricks or if one of the strings is not a valid format
+    /// string.
+    pub fn try_to_format_strings(&self, max_count: usize) -> Result<Vec<FormatString>, Error> {
+        self.try_to_concrete(max_count)?
+            .iter()
+            .map(|string| FormatString::parse(string))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::BrickDomain;
+
+    #[test]
+    fn parse_format_string() {
+        let format_string = FormatString::parse("id=%-8d name=%.*s %%").unwrap();
+        assert_eq!(format_string.literal_length, 10);
+        assert_eq!(
+            format_string.specifiers[0],
+            FormatSpecifier {
+                flags: "-".to_string(),
+                width: Some(FieldSize::Fixed(8)),
+                precision: None,
+                length_modifier: String::new(),
+                conversion: 'd',
+            }
+        );
+        assert_eq!(
+            format_string.specifiers[1].precision,
+            Some(FieldSize::Argument)
+        );
+        assert_eq!(format_string.specifiers[2].conversion, '%');
+        assert_eq!(
+            format_string.get_argument_datatypes(),
+            vec![Datatype::Integer, Datatype::Integer, Datatype::Pointer]
+        );
+
+        let format_string = FormatString::parse("%lu %lld %hhx %Lf %c").unwrap();
+        assert_eq!(
+            format_string.get_argument_datatypes(),
+            vec![
+                Datatype::Long,
+                Datatype::LongLong,
+                Datatype::Integer,
+                Datatype::LongDouble,
+                Datatype::Char
+            ]
+        );
+
+        assert!(FormatString::parse("%1$s").is_err());
+        assert!(FormatString::parse("%y").is_err());
+        assert!(FormatString::parse("100%").is_err());
+    }
+
+    #[test]
+    fn min_output_length() {
+        let length = |format_string: &str| {
+            FormatString::parse(format_string)
+                .unwrap()
+                .get_min_output_length()
+        };
+        assert_eq!(length("abc"), 3);
+        assert_eq!(length("%d"), 1);
+        assert_eq!(length("%+d"), 2);
+        assert_eq!(length("%.4x"), 4);
+        assert_eq!(length("%08.3d"), 8);
+        assert_eq!(length("%*d"), 1);
+        assert_eq!(length("%s"), 0);
+        assert_eq!(length("%10s"), 10);
+        assert_eq!(length("%.1f"), 3);
+        assert_eq!(length("%.0f"), 1);
+        assert_eq!(length("%f"), 3);
+        assert_eq!(length("/tmp/file_%d.%s"), 12);
+    }
+
+    #[test]
+    fn format_strings_from_bricks() {
+        let mut brick = crate::abstract_domain::bricks::Brick::new();
+        brick.set_sequence(["%d".to_string(), "%02d".to_string()].into());
+        brick.set_min(1);
+        brick.set_max(1);
+        let domain = BricksDomain::Value(vec![
+            BrickDomain::new("id=".to_string()),
+            BrickDomain::Value(brick),
+        ]);
+        let mut lengths: Vec<u64> = domain
+            .try_to_format_strings(10)
+            .unwrap()
+            .iter()
+            .map(FormatString::get_min_output_length)
+            .collect();
+        lengths.sort();
+        assert_eq!(lengths, vec![4, 5]);
+
+        assert!(BricksDomain::Top.try_to_format_strings(10).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Memoization of merge and normalization results of the BricksDomain.
+//!
+//! During a fixpoint computation the same pairs of brick lists are merged over and over again,
+//! e.g. whenever a loop head is revisited without changes of the string states.
+//! Since merging includes the expensive normalization of the merged bricks,
+//! the results are stored in a memo table indexed by the structural hash of the operands.
+//!
+//! The memo table is thread-local and only active while a [`BricksMemoScope`] exists,
+//! so that its lifetime is bound to a single analysis run.
+//! Without an active scope all operations are computed without memoization.
+
+use super::BricksDomain;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The maximal number of entries of each memo table.
+/// If a table grows larger, it is cleared.
+const MAX_MEMO_TABLE_SIZE: usize = 1 << 16;
+
+/// The memo tables for merge and normalization results of the BricksDomain.
+#[derive(Default)]
+struct MemoTable {
+    /// The number of currently active scopes.
+    active_scopes: usize,
+    /// Maps the operands of a merge and the cardinality limit to the merge result.
+    merges: HashMap<(BricksDomain, BricksDomain, usize), BricksDomain>,
+    /// Maps the operand of a normalization and the cardinality limit to the normalized value.
+    normalizations: HashMap<(BricksDomain, usize), BricksDomain>,
+    /// The number of operations whose result was found in the memo tables.
+    hits: usize,
+    /// The number of operations whose result had to be computed.
+    misses: usize,
+}
+
+thread_local! {
+    static MEMO_TABLE: RefCell<MemoTable> = RefCell::new(MemoTable::default());
+}
+
+/// A guard that activates the memoization of BricksDomain operations on the current thread.
+///
+/// The memo tables are cleared when the last active scope is dropped.
+pub struct BricksMemoScope {
+    /// The guard must not be sent to other threads, since the memo table is thread-local.
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl BricksMemoScope {
+    /// Activate the memoization of BricksDomain operations until the returned guard is dropped.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> BricksMemoScope {
+        MEMO_TABLE.with_borrow_mut(|table| table.active_scopes += 1);
+        BricksMemoScope {
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the number of memo table hits and misses on the current thread
+    /// since the first currently active scope was created.
+    pub fn get_statistics(&self) -> (usize, usize) {
+        MEMO_TABLE.with_borrow(|table| (table.hits, table.misses))
+    }
+}
+
+impl Drop for BricksMemoScope {
+    fn drop(&mut self) {
+        MEMO_TABLE.with_borrow_mut(|table| {
+            table.active_scopes -= 1;
+            if table.active_scopes == 0 {
+                *table = MemoTable::default();
+            }
+        });
+    }
+}
+
+/// Get the memoized result of merging `first` and `second`
+/// or compute it with the given function and memoize it if a scope is active.
+pub fn memoized_merge(
+    first: &BricksDomain,
+    second: &BricksDomain,
+    max_cardinality: usize,
+    compute: impl FnOnce() -> BricksDomain,
+) -> BricksDomain {
+    if !is_active() {
+        return compute();
+    }
+    let key = (first.clone(), second.clone(), max_cardinality);
+    memoized(|table| &mut table.merges, key, compute)
+}
+
+/// Get the memoized normalization of `value`
+/// or compute it with the given function and memoize it if a scope is active.
+pub fn memoized_normalization(
+    value: &BricksDomain,
+    max_cardinality: usize,
+    compute: impl FnOnce() -> BricksDomain,
+) -> BricksDomain {
+    if !is_active() {
+        return compute();
+    }
+    let key = (value.clone(), max_cardinality);
+    memoized(|table| &mut table.normalizations, key, compute)
+}
+
+/// Check whether a memoization scope is active on the current thread.
+fn is_active() -> bool {
+    MEMO_TABLE.with_borrow(|table| table.active_scopes > 0)
+}
+
+/// Look up the key in the memo table selected by `select_table`.
+/// On a miss the result is computed and inserted into the table.
+///
+/// The table is not borrowed while the result is computed,
+/// since the computation may itself use memoized operations.
+fn memoized<K: std::hash::Hash + Eq>(
+    select_table: impl Fn(&mut MemoTable) -> &mut HashMap<K, BricksDomain>,
+    key: K,
+    compute: impl FnOnce() -> BricksDomain,
+) -> BricksDomain {
+    let cached = MEMO_TABLE.with_borrow_mut(|table| {
+        let cached = select_table(table).get(&key).cloned();
+        if cached.is_some() {
+            table.hits += 1;
+        } else {
+            table.misses += 1;
+        }
+        cached
+    });
+    if let Some(result) = cached {
+        return result;
+    }
+    let result = compute();
+    MEMO_TABLE.with_borrow_mut(|table| {
+        let memo = select_table(table);
+        if memo.len() >= MAX_MEMO_TABLE_SIZE {
+            memo.clear();
+        }
+        memo.insert(key, result.clone());
+    });
+    result
+}
@@ -283,9 +283,12 @@ fn test_brick_list_is_less_or_equal() {
 #[test]
 fn test_normalize() {
     let setup = Setup::new();
-    let to_normalize: BricksDomain =
-        BricksDomain::Value(vec![setup.brick5, setup.brick3, setup.brick4]); // ["a"]^{1,1}["a", "b"]^{2,3}["a", "b"]^{0,1}
-    let normalized = to_normalize.normalize();
+    let to_normalize: BricksDomain = BricksDomain::Value(vec![
+        setup.brick5.clone(),
+        setup.brick3,
+        setup.brick4.clone(),
+    ]); // ["a"]^{1,1}["a", "b"]^{2,3}["a", "b"]^{0,1}
+    let normalized = to_normalize.normalize(usize::MAX);
 
     let expected_brick1 = BrickDomain::Value(Brick::mock_brick(
         vec!["aaa", "aab", "aba", "abb"]
@@ -305,6 +308,10 @@ fn test_normalize() {
     let expected = BricksDomain::Value(vec![expected_brick1, expected_brick2]);
 
     assert_eq!(normalized, expected);
+
+    // Breaking ["a", "b"]^{2,3} would create a brick with 4 sequences.
+    let expected = BricksDomain::Value(vec![setup.brick5, BrickDomain::Top, setup.brick4]);
+    assert_eq!(to_normalize.normalize(3), expected);
 }
 
 #[test]
@@ -328,8 +335,10 @@ fn test_generate_permutations_of_fixed_length() {
 #[test]
 fn test_break_single_brick_into_simpler_bricks() {
     let setup = Setup::new();
-    let complex_brick = setup.brick3.unwrap_value(); // ["a", "b"]^{2,3}
-    let (result1, result2) = complex_brick.break_single_brick_into_simpler_bricks();
+    let complex_brick = setup.brick3.as_value().unwrap().clone(); // ["a", "b"]^{2,3}
+    let (result1, result2) = complex_brick
+        .break_single_brick_into_simpler_bricks(4)
+        .unwrap();
     let expected_brick1 = Brick::mock_brick(
         vec!["aa", "ba", "ab", "bb"]
             .iter()
@@ -347,16 +356,19 @@ fn test_break_single_brick_into_simpler_bricks() {
 
     assert_eq!(result1, expected_brick1);
     assert_eq!(result2, expected_brick2);
+    assert!(complex_brick
+        .break_single_brick_into_simpler_bricks(3)
+        .is_none());
 }
 
 #[test]
 fn test_merge_bricks_with_equal_content() {
     let setup = Setup::new();
-    let merge1 = setup.brick0.unwrap_value();
-    let merge2 = setup.brick4.unwrap_value();
+    let merge1 = setup.brick0.as_value().unwrap().clone();
+    let merge2 = setup.brick4.as_value().unwrap().clone();
 
     let result = merge1.merge_bricks_with_equal_content(merge2);
-    let expected = setup.brick3.unwrap_value();
+    let expected = setup.brick3.as_value().unwrap().clone();
 
     assert_eq!(result, expected);
 }
@@ -364,9 +376,9 @@ fn test_merge_bricks_with_equal_content() {
 #[test]
 fn test_transform_brick_with_min_max_equal() {
     let setup = Setup::new();
-    let not_normalized = setup.brick0.unwrap_value();
+    let not_normalized = setup.brick0.as_value().unwrap().clone();
     let result =
-        not_normalized.transform_brick_with_min_max_equal(not_normalized.get_min() as usize);
+        not_normalized.transform_brick_with_min_max_equal(not_normalized.get_min() as usize, 4);
     let expected_brick = Brick::mock_brick(
         vec!["aa", "ba", "ab", "bb"]
             .iter()
@@ -376,16 +388,19 @@ fn test_transform_brick_with_min_max_equal() {
         1,
     );
 
-    assert_eq!(result, expected_brick);
+    assert_eq!(result, Some(expected_brick));
+    assert!(not_normalized
+        .transform_brick_with_min_max_equal(not_normalized.get_min() as usize, 3)
+        .is_none());
 }
 
 #[test]
 fn test_merge_bricks_with_bound_one() {
     let setup = Setup::new();
-    let merge1 = setup.brick1.unwrap_value();
-    let merge2 = setup.brick2.unwrap_value();
+    let merge1 = setup.brick1.as_value().unwrap().clone();
+    let merge2 = setup.brick2.as_value().unwrap().clone();
 
-    let result = merge1.merge_bricks_with_bound_one(merge2);
+    let result = merge1.merge_bricks_with_bound_one(merge2.clone(), 4);
     let expected_brick = Brick::mock_brick(
         vec!["ab", "aef", "cdb", "cdef"]
             .iter()
@@ -395,14 +410,18 @@ fn test_merge_bricks_with_bound_one() {
         1,
     );
 
-    assert_eq!(result, expected_brick);
+    assert_eq!(result, Some(expected_brick));
+    assert!(merge1.merge_bricks_with_bound_one(merge2, 3).is_none());
 }
 
 #[test]
 fn test_empty_string() {
     let setup = Setup::new();
-    let brick = setup.brick5.unwrap_value();
-    let empty_brick = BrickDomain::get_empty_brick_domain().unwrap_value();
+    let brick = setup.brick5.as_value().unwrap().clone();
+    let empty_brick = BrickDomain::get_empty_brick_domain()
+        .as_value()
+        .unwrap()
+        .clone();
 
     assert!(!brick.is_empty_string());
     assert!(empty_brick.is_empty_string());
@@ -483,3 +502,178 @@ fn test_append_string_domain() {
         bricks_one.append_string_domain(&bricks_two)
     );
 }
+
+#[test]
+fn test_truncate() {
+    let bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["cat ".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(
+            vec!["bash.sh".to_string(), "run.sh".to_string()],
+            1,
+            1,
+        )),
+    ]);
+
+    assert_eq!(BricksDomain::Top, BricksDomain::Top.truncate(4));
+    assert_eq!(bricks, bricks.truncate(20));
+    assert_eq!(BricksDomain::from("".to_string()), bricks.truncate(0));
+    assert_eq!(BricksDomain::from("ca".to_string()), bricks.truncate(2));
+
+    let expected_bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["cat ".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(
+            vec!["bas".to_string(), "run".to_string()],
+            1,
+            1,
+        )),
+    ]);
+    assert_eq!(expected_bricks, bricks.truncate(7));
+
+    // The second brick may or may not fit into the remaining length.
+    let bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["cat ".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(
+            vec!["bash.sh".to_string(), "ls".to_string()],
+            1,
+            1,
+        )),
+    ]);
+    let expected_bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["cat ".to_string()], 1, 1)),
+        BrickDomain::Top,
+    ]);
+    assert_eq!(expected_bricks, bricks.truncate(10));
+    assert_eq!(bricks, bricks.truncate(11));
+
+    // Lengths are measured in bytes and multi-byte characters are not cut.
+    let bricks = BricksDomain::from("äb".to_string());
+    assert_eq!(BricksDomain::from("ä".to_string()), bricks.truncate(2));
+    assert_eq!(
+        BricksDomain::Value(vec![BrickDomain::Top]),
+        bricks.truncate(1)
+    );
+}
+
+#[test]
+fn test_intersect() {
+    let prefix = BricksDomain::from("/".to_string()).append_string_domain(&BricksDomain::Top);
+    let paths = BricksDomain::Value(vec![BrickDomain::Value(Brick::mock_brick(
+        vec!["/bin".to_string(), "usr".to_string()],
+        1,
+        1,
+    ))]);
+
+    assert_eq!(
+        BricksDomain::from("/bin".to_string()),
+        paths.intersect(&prefix).unwrap()
+    );
+    assert_eq!(prefix, BricksDomain::Top.intersect(&prefix).unwrap());
+    assert!(BricksDomain::from("usr".to_string())
+        .intersect(&prefix)
+        .is_err());
+
+    let repeated = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["ab".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(vec!["c".to_string()], 0, 3)),
+    ]);
+    assert_eq!(
+        BricksDomain::from("abcc".to_string()),
+        repeated
+            .intersect(&BricksDomain::from("abcc".to_string()))
+            .unwrap()
+    );
+    assert!(repeated
+        .intersect(&BricksDomain::from("abcccc".to_string()))
+        .is_err());
+    // Intersections of infinite sets are overapproximated.
+    assert_eq!(repeated, repeated.intersect(&prefix).unwrap());
+}
+
+#[test]
+fn test_try_to_concrete() {
+    let bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["ls ".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(
+            vec!["-l".to_string(), "-a".to_string()],
+            0,
+            1,
+        )),
+    ]);
+    let expected: BTreeSet<String> = ["ls ", "ls -l", "ls -a"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    assert_eq!(expected, bricks.try_to_concrete(3).unwrap());
+    assert!(bricks.try_to_concrete(2).is_err());
+    assert!(BricksDomain::Top.try_to_concrete(3).is_err());
+    assert!(bricks
+        .append_string_domain(&BricksDomain::Top)
+        .try_to_concrete(3)
+        .is_err());
+
+    let unbounded = BricksDomain::Value(vec![BrickDomain::Value(Brick::mock_brick(
+        vec!["a".to_string()],
+        0,
+        u32::MAX,
+    ))]);
+    assert!(unbounded.try_to_concrete(10).is_err());
+}
+
+#[test]
+fn test_top_values() {
+    let setup = Setup::new();
+    let bricks = BricksDomain::Value(vec![setup.brick0.clone(), BrickDomain::Top]);
+
+    assert_eq!(BricksDomain::Top.normalize(usize::MAX), BricksDomain::Top);
+    assert_eq!(BricksDomain::Top.pad_list(&bricks), BricksDomain::Top);
+    assert_eq!(bricks.widen(&BricksDomain::Top), BricksDomain::Top);
+    assert!(bricks.is_less_or_equal(&BricksDomain::Top));
+    assert!(!BricksDomain::Top.is_less_or_equal(&bricks));
+    assert_eq!(setup.brick0.widen(&BrickDomain::Top), BrickDomain::Top);
+    assert!(!BrickDomain::Top.is_less_or_equal(&setup.brick0));
+    assert_eq!(BricksDomain::Top.as_value(), None);
+    assert_eq!(bricks.as_value().unwrap().len(), 2);
+}
+
+#[test]
+fn test_domain_order() {
+    let setup = Setup::new();
+    let short_list = BricksDomain::Value(vec![setup.brick0.clone(), setup.brick1.clone()]);
+    let long_list = BricksDomain::Value(vec![setup.brick3.clone(), setup.brick1.clone()]);
+
+    assert!(setup.brick0.less_or_equal(&setup.brick3));
+    assert!(!setup.brick3.less_or_equal(&setup.brick0));
+    assert!(!BrickDomain::get_empty_brick_domain().less_or_equal(&setup.brick0));
+    assert!(short_list.less_or_equal(&long_list));
+    assert!(!long_list.less_or_equal(&short_list));
+    assert!(!short_list.less_or_equal(&BricksDomain::Value(vec![setup.brick3])));
+    assert!(short_list.less_or_equal(&BricksDomain::Top));
+    assert!(!BricksDomain::Top.less_or_equal(&short_list));
+}
+
+#[test]
+fn test_memoized_merge() {
+    let first = BricksDomain::Value(vec![
+        BrickDomain::new("a".to_string()),
+        BrickDomain::new("b".to_string()),
+    ]);
+    let second = BricksDomain::from("c".to_string());
+    let expected = first.merge(&second);
+
+    let scope = BricksMemoScope::new();
+    assert_eq!(first.merge(&second), expected);
+    let (hits, misses) = scope.get_statistics();
+    assert_eq!(hits, 0);
+    assert!(misses > 0);
+    assert_eq!(first.merge(&second), expected);
+    assert_eq!(scope.get_statistics(), (hits + 1, misses));
+    // A different cardinality limit is memoized separately.
+    assert_eq!(first.merge_with_cardinality_limit(&second, 1), expected);
+    assert_eq!(scope.get_statistics().0, hits + 1);
+
+    drop(scope);
+    let scope = BricksMemoScope::new();
+    assert_eq!(first.merge(&second), expected);
+    assert_eq!(scope.get_statistics().0, 0);
+}
@@ -21,9 +21,8 @@ use std::{
     collections::BTreeSet,
 };
 
-use crate::abstract_domain::AbstractDomain;
-
-use super::{brick::Brick, BrickDomain, BricksDomain};
+use super::{brick::Brick, AbstractDomain, BrickDomain, BricksDomain};
+use crate::abstract_domain::operation_counters::{self, CountedDomain, DomainOperation};
 
 pub const INTERVAL_THRESHOLD: usize = 8;
 pub const SEQUENCE_THRESHOLD: usize = 8;
@@ -38,8 +37,13 @@ impl BricksDomain {
     /// If after the widening all BrickDomain values are *Top*, return
     /// the *Top* value for the BricksDomain.
     pub fn widen(&self, other: &BricksDomain) -> Self {
-        let self_num_of_bricks = self.unwrap_value().len();
-        let other_num_of_bricks = other.unwrap_value().len();
+        operation_counters::count(CountedDomain::Bricks, DomainOperation::Widen);
+        let (BricksDomain::Value(self_bricks), BricksDomain::Value(other_bricks)) = (self, other)
+        else {
+            return BricksDomain::Top;
+        };
+        let self_num_of_bricks = self_bricks.len();
+        let other_num_of_bricks = other_bricks.len();
 
         let mut new_self = self.clone();
         let mut new_other = other.clone();
@@ -64,9 +68,10 @@ impl BricksDomain {
         let mut widened_brick_domain_list: Vec<BrickDomain> = Vec::new();
 
         for (self_brick, other_brick) in new_self
-            .unwrap_value()
+            .as_value()
+            .unwrap_or_default()
             .iter()
-            .zip(new_other.unwrap_value().iter())
+            .zip(new_other.as_value().unwrap_or_default().iter())
         {
             widened_brick_domain_list.push(self_brick.merge(other_brick));
         }
@@ -87,10 +92,14 @@ impl BricksDomain {
     /// Checks whether the current BricksDomain is less or equal than the other BricksDomain
     /// by definition of the partial order.
     pub fn is_less_or_equal(&self, other: &BricksDomain) -> bool {
-        self.unwrap_value()
-            .iter()
-            .zip(other.unwrap_value().iter())
-            .all(|(self_brick, other_brick)| self_brick.is_less_or_equal(other_brick))
+        match (self, other) {
+            (BricksDomain::Value(self_bricks), BricksDomain::Value(other_bricks)) => self_bricks
+                .iter()
+                .zip(other_bricks.iter())
+                .all(|(self_brick, other_brick)| self_brick.is_less_or_equal(other_brick)),
+            (BricksDomain::Top, BricksDomain::Value(_)) => false,
+            (_, BricksDomain::Top) => true,
+        }
     }
 }
 
@@ -104,8 +113,10 @@ impl BrickDomain {
     /// If so *min* is set to 0 and *max* is set to infinity (here Max value of 32 bits).
     /// Otherwise, their values are taken as new bounds for the merged domain.
     pub fn widen(&self, other: &BrickDomain) -> Self {
-        let self_brick = self.unwrap_value();
-        let other_brick = other.unwrap_value();
+        let (BrickDomain::Value(self_brick), BrickDomain::Value(other_brick)) = (self, other)
+        else {
+            return BrickDomain::Top;
+        };
         let merged_sequence = self_brick
             .get_sequence()
             .union(other_brick.get_sequence())
@@ -137,10 +148,8 @@ impl BrickDomain {
     /// by definition of the partial order.
     /// Empty strings are ignored for order comparisons.
     pub fn is_less_or_equal(&self, other: &BrickDomain) -> bool {
-        match (self.is_top(), other.is_top()) {
-            (false, false) => {
-                let self_brick = self.unwrap_value();
-                let other_brick = other.unwrap_value();
+        match (self, other) {
+            (BrickDomain::Value(self_brick), BrickDomain::Value(other_brick)) => {
                 if self_brick.is_empty_string() || other_brick.is_empty_string() {
                     return true;
                 }
@@ -155,8 +164,8 @@ impl BrickDomain {
 
                 false
             }
-            (true, false) => false,
-            (false, true) | (true, true) => true,
+            (BrickDomain::Top, BrickDomain::Value(_)) => false,
+            (_, BrickDomain::Top) => true,
         }
     }
 }
@@ -27,13 +27,18 @@
 //!  3. When two domains are merged, the intersection of the certain sets and
 //!     the union of possible sets are taken, e.g.
 //!     ({H,e,l,o,',',' '}, {H,e,l,o,',',' '}) v ({W,o,r,l,d}, {W,o,r,l,d}) => ({l,o}, {H,e,l,o,',',' ',W,o,r,d})
+//!
+//! The alphabet of allowed characters is described by a [`CharacterAlphabet`].
+//! It is also used to classify byte sequences as strings or as binary data,
+//! see [`CharacterAlphabet::is_probably_binary_data`].
 
 use std::{collections::BTreeSet, fmt};
 
 use crate::prelude::*;
 use std::fmt::Debug;
 
-use super::{AbstractDomain, DomainInsertion, HasTop};
+use super::operation_counters::{self, CountedDomain, DomainOperation};
+use super::{AbstractDomain, DomainInsertion, DomainOrder, HasTop, TryToConcrete};
 
 /// The `CharacterInclusionDomain` is a abstract domain describing the characters a string certainly has
 /// and the characters a string may have.
@@ -51,11 +56,41 @@ pub enum CharacterInclusionDomain {
 }
 
 impl CharacterInclusionDomain {
-    /// Unwraps the values from the Character Inclusion Domain
-    pub fn unwrap_value(&self) -> (CharacterSet, CharacterSet) {
+    /// Returns the sets of certainly and possibly contained characters
+    /// or `None` if the domain is *Top*.
+    pub fn as_value(&self) -> Option<(&CharacterSet, &CharacterSet)> {
+        match self {
+            CharacterInclusionDomain::Value((certain, possible)) => Some((certain, possible)),
+            CharacterInclusionDomain::Top => None,
+        }
+    }
+}
+
+impl CharacterInclusionDomain {
+    /// Restrict the characters of the domain to the characters of the given alphabet.
+    ///
+    /// For alphabets with a known set of characters the *Top* value is replaced by
+    /// the value whose possibly contained characters are the characters of the alphabet.
+    /// Characters outside of the alphabet are removed from both sets.
+    pub fn restrict_to_alphabet(&self, alphabet: CharacterAlphabet) -> Self {
+        let Some(alphabet_characters) = alphabet.get_characters() else {
+            return self.clone();
+        };
+        let (certain, possible) = self.get_sets();
+        let alphabet_set = CharacterSet::Value(alphabet_characters);
+        CharacterInclusionDomain::Value((
+            certain.intersection(alphabet_set.clone()),
+            possible.intersection(alphabet_set),
+        ))
+    }
+
+    /// Returns the set of certainly contained characters and the set of possibly contained characters.
+    fn get_sets(&self) -> (CharacterSet, CharacterSet) {
         match self {
+            CharacterInclusionDomain::Top => {
+                (CharacterSet::Value(BTreeSet::new()), CharacterSet::Top)
+            }
             CharacterInclusionDomain::Value(value) => value.clone(),
-            _ => panic!("Unexpected Character Inclusion type."),
         }
     }
 }
@@ -129,6 +164,67 @@ impl DomainInsertion for CharacterInclusionDomain {
     fn create_empty_string_domain() -> Self {
         CharacterInclusionDomain::from("".to_string())
     }
+
+    /// Truncate the strings to the given length in bytes.
+    /// Since the domain does not know the order of characters,
+    /// the truncated strings may not contain any of the certainly contained characters.
+    fn truncate(&self, length: usize) -> Self {
+        match self {
+            _ if length == 0 => Self::create_empty_string_domain(),
+            CharacterInclusionDomain::Top => CharacterInclusionDomain::Top,
+            CharacterInclusionDomain::Value((_, possible)) => CharacterInclusionDomain::Value((
+                CharacterSet::Value(BTreeSet::new()),
+                possible.clone(),
+            )),
+        }
+    }
+
+    /// Each certainly contained character occurs at least once in the strings.
+    fn get_min_length(&self) -> usize {
+        match self.get_sets() {
+            (CharacterSet::Value(certain), _) => certain.iter().map(|c| c.len_utf8()).sum(),
+            (CharacterSet::Top, _) => 0,
+        }
+    }
+
+    /// The domain does not bound the number of occurrences of a character.
+    /// Only the empty string has a known maximal length.
+    fn get_max_length(&self) -> Option<usize> {
+        match self.get_sets() {
+            (_, CharacterSet::Value(possible)) if possible.is_empty() => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Intersect two domains by taking the union of the certainly contained characters
+    /// and the intersection of the possibly contained characters.
+    /// The intersection is empty if a certainly contained character is not possibly contained.
+    fn intersect(&self, other: &Self) -> Result<Self, Error> {
+        let (self_certain, self_possible) = self.get_sets();
+        let (other_certain, other_possible) = other.get_sets();
+        let certain = self_certain.union(other_certain);
+        let possible = self_possible.intersection(other_possible);
+        if let (CharacterSet::Value(certain_chars), CharacterSet::Value(possible_chars)) =
+            (&certain, &possible)
+        {
+            if !certain_chars.is_subset(possible_chars) {
+                return Err(anyhow!(
+                    "Empty intersection of character inclusion domains."
+                ));
+            }
+        }
+        if certain == CharacterSet::Value(BTreeSet::new()) && possible.is_top() {
+            Ok(CharacterInclusionDomain::Top)
+        } else {
+            Ok(CharacterInclusionDomain::Value((certain, possible)))
+        }
+    }
+
+    /// The size of the domain is bounded by the number of characters,
+    /// so this is the same as a normal merge.
+    fn merge_with_cardinality_limit(&self, other: &Self, _max_cardinality: usize) -> Self {
+        self.merge(other)
+    }
 }
 
 impl From<String> for CharacterInclusionDomain {
@@ -141,22 +237,38 @@ impl From<String> for CharacterInclusionDomain {
     }
 }
 
+impl TryToConcrete for CharacterInclusionDomain {
+    /// Return the string consisting of the certainly contained characters as an example.
+    /// Since the order and multiplicity of characters is unknown,
+    /// at most one example is returned.
+    /// Fails if the set of possibly contained characters is not restricted.
+    fn try_to_concrete(&self, max_count: usize) -> Result<BTreeSet<String>, Error> {
+        match self {
+            CharacterInclusionDomain::Value((
+                CharacterSet::Value(certain),
+                CharacterSet::Value(_),
+            )) if max_count > 0 => Ok(BTreeSet::from([certain.iter().collect()])),
+            _ => Err(anyhow!("No example string for character inclusion domain.")),
+        }
+    }
+}
+
 impl AbstractDomain for CharacterInclusionDomain {
     /// Merge two values; Takes the intersection of the certainly contained characters
     /// and the union of the possibly contained characters.
     /// Returns *Top* if either Domain represents it.
     fn merge(&self, other: &Self) -> Self {
-        if self.is_top() || other.is_top() {
-            Self::Top
-        } else if self == other {
-            self.clone()
-        } else {
-            let (self_certain, self_possible) = self.unwrap_value();
-            let (other_certain, other_possible) = other.unwrap_value();
-            Self::Value((
-                self_certain.intersection(other_certain),
-                self_possible.union(other_possible),
-            ))
+        operation_counters::count(CountedDomain::CharacterInclusion, DomainOperation::Merge);
+        match (self, other) {
+            _ if self == other => self.clone(),
+            (
+                Self::Value((self_certain, self_possible)),
+                Self::Value((other_certain, other_possible)),
+            ) => Self::Value((
+                self_certain.intersection(other_certain.clone()),
+                self_possible.union(other_possible.clone()),
+            )),
+            _ => Self::Top,
         }
     }
 
@@ -166,6 +278,24 @@ impl AbstractDomain for CharacterInclusionDomain {
     }
 }
 
+impl DomainOrder for CharacterInclusionDomain {
+    /// `self` is less than or equal to `other` if it certainly contains at least the characters
+    /// certainly contained in `other` and possibly contains at most the characters possibly contained in `other`.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        match (self, other) {
+            (_, Self::Top) => true,
+            (Self::Top, Self::Value(_)) => false,
+            (
+                Self::Value((self_certain, self_possible)),
+                Self::Value((other_certain, other_possible)),
+            ) => {
+                other_certain.less_or_equal(self_certain)
+                    && self_possible.less_or_equal(other_possible)
+            }
+        }
+    }
+}
+
 impl HasTop for CharacterInclusionDomain {
     /// Return a *Top* value
     fn top(&self) -> Self {
@@ -194,44 +324,36 @@ pub enum CharacterSet {
 }
 
 impl CharacterSet {
-    /// Unwraps the values from the CharacterSet
-    pub fn unwrap_value(&self) -> BTreeSet<char> {
+    /// Returns the set of characters or `None` if the set is *Top*.
+    pub fn as_value(&self) -> Option<&BTreeSet<char>> {
         match self {
-            CharacterSet::Value(value) => value.clone(),
-            _ => panic!("Unexpected CharacterSet type."),
+            CharacterSet::Value(value) => Some(value),
+            CharacterSet::Top => None,
         }
     }
 
-    /// Takes the intersection of two character sets.
-    /// None of the sets should be *Top* since otherwise
-    /// the whole CharacterInclusionDomain would be *Top*
-    /// which is checked beforehand.
+    /// Takes the intersection of two character sets,
+    /// where *Top* represents the set of all allowed characters.
     pub fn intersection(&self, other: Self) -> Self {
-        if self.is_top() || other.is_top() {
-            panic!("Unexpected Top Value for CharacterSet intersection.")
+        match (self, other) {
+            (CharacterSet::Value(self_chars), CharacterSet::Value(other_chars)) => {
+                CharacterSet::Value(self_chars.intersection(&other_chars).cloned().collect())
+            }
+            (CharacterSet::Top, other) => other,
+            (_, CharacterSet::Top) => self.clone(),
         }
-        CharacterSet::Value(
-            self.unwrap_value()
-                .intersection(&other.unwrap_value())
-                .cloned()
-                .collect(),
-        )
     }
 
     /// Takes the union of two character sets.
     /// If either of them is *Top* the union is *Top*.
     /// Otherwise the standard set union is taken.
     pub fn union(&self, other: Self) -> Self {
-        if self.is_top() || other.is_top() {
-            return CharacterSet::Top;
+        match (self, other) {
+            (CharacterSet::Value(self_chars), CharacterSet::Value(other_chars)) => {
+                CharacterSet::Value(self_chars.union(&other_chars).cloned().collect())
+            }
+            _ => CharacterSet::Top,
         }
-
-        CharacterSet::Value(
-            self.unwrap_value()
-                .union(&other.unwrap_value())
-                .cloned()
-                .collect(),
-        )
     }
 
     /// Check if the value is *Top*.
@@ -258,6 +380,102 @@ impl fmt::Display for CharacterSet {
     }
 }
 
+/// The alphabet of characters that strings may consist of.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum CharacterAlphabet {
+    /// Printable ASCII characters and ASCII whitespace.
+    AsciiPrintable,
+    /// All characters that can be represented by a single non-NUL byte,
+    /// i.e. each byte is read as a Latin-1 character.
+    FullByteRange,
+    /// All Unicode characters except NUL, encoded as UTF-8.
+    #[default]
+    Utf8,
+}
+
+impl CharacterAlphabet {
+    /// Check whether the character is contained in the alphabet.
+    pub fn contains(&self, character: char) -> bool {
+        match self {
+            CharacterAlphabet::AsciiPrintable => {
+                character.is_ascii_graphic() || character.is_ascii_whitespace()
+            }
+            CharacterAlphabet::FullByteRange => ('\u{1}'..='\u{ff}').contains(&character),
+            CharacterAlphabet::Utf8 => character != '\0',
+        }
+    }
+
+    /// Returns the set of characters of the alphabet
+    /// or `None` if the alphabet is too large to be enumerated.
+    pub fn get_characters(&self) -> Option<BTreeSet<char>> {
+        match self {
+            CharacterAlphabet::AsciiPrintable | CharacterAlphabet::FullByteRange => Some(
+                ('\u{1}'..='\u{ff}')
+                    .filter(|character| self.contains(*character))
+                    .collect(),
+            ),
+            CharacterAlphabet::Utf8 => None,
+        }
+    }
+
+    /// Decode the bytes to a string of characters of the alphabet.
+    ///
+    /// Returns `None` if the bytes are no valid encoding of such a string.
+    pub fn decode(&self, bytes: &[u8]) -> Option<String> {
+        let string: String = match self {
+            CharacterAlphabet::AsciiPrintable | CharacterAlphabet::Utf8 => {
+                std::str::from_utf8(bytes).ok()?.to_string()
+            }
+            CharacterAlphabet::FullByteRange => {
+                bytes.iter().map(|byte| char::from(*byte)).collect()
+            }
+        };
+        string
+            .chars()
+            .all(|character| self.contains(character))
+            .then_some(string)
+    }
+
+    /// Check whether the bytes (without a terminating NUL byte) are probably binary data
+    /// instead of the characters of a string.
+    ///
+    /// This is the case if the bytes are no string of characters of the alphabet
+    /// or if more than a quarter of the characters are control characters other than whitespace.
+    pub fn is_probably_binary_data(&self, bytes: &[u8]) -> bool {
+        let Some(string) = self.decode(bytes) else {
+            return true;
+        };
+        let control_character_count = string
+            .chars()
+            .filter(|character| character.is_control() && !character.is_ascii_whitespace())
+            .count();
+        control_character_count * 4 > string.chars().count()
+    }
+}
+
+impl fmt::Display for CharacterAlphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharacterAlphabet::AsciiPrintable => write!(f, "ASCII printable"),
+            CharacterAlphabet::FullByteRange => write!(f, "full byte range"),
+            CharacterAlphabet::Utf8 => write!(f, "UTF-8"),
+        }
+    }
+}
+
+impl DomainOrder for CharacterSet {
+    /// Check whether `self` is a subset of `other`.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        match (self, other) {
+            (_, CharacterSet::Top) => true,
+            (CharacterSet::Top, CharacterSet::Value(_)) => false,
+            (CharacterSet::Value(self_chars), CharacterSet::Value(other_chars)) => {
+                self_chars.is_subset(other_chars)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -269,6 +487,17 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn ordering() {
+        let abc = CharacterInclusionDomain::ci("abc");
+        let merged = abc.merge(&CharacterInclusionDomain::ci("ab"));
+
+        assert!(abc.less_or_equal(&merged));
+        assert!(!merged.less_or_equal(&abc));
+        assert!(abc.less_or_equal(&CharacterInclusionDomain::Top));
+        assert!(!CharacterInclusionDomain::Top.less_or_equal(&abc));
+    }
+
     #[test]
     fn merging() {
         let first = CharacterInclusionDomain::ci("abc");
@@ -297,4 +526,130 @@ pub mod tests {
             CharacterInclusionDomain::Top
         );
     }
+
+    #[test]
+    fn truncating() {
+        let domain = CharacterInclusionDomain::ci("abc");
+
+        assert_eq!(
+            domain.truncate(2),
+            CharacterInclusionDomain::Value((
+                CharacterSet::Value(BTreeSet::new()),
+                CharacterSet::Value("abc".chars().collect())
+            ))
+        );
+        assert_eq!(domain.truncate(0), CharacterInclusionDomain::ci(""));
+        assert_eq!(
+            CharacterInclusionDomain::Top.truncate(2),
+            CharacterInclusionDomain::Top
+        );
+    }
+
+    #[test]
+    fn concretizing() {
+        let merged = CharacterInclusionDomain::ci("ab").merge(&CharacterInclusionDomain::ci("bc"));
+
+        assert_eq!(
+            merged.try_to_concrete(5).unwrap(),
+            BTreeSet::from(["b".to_string()])
+        );
+        assert!(CharacterInclusionDomain::Top.try_to_concrete(5).is_err());
+        assert!(CharacterInclusionDomain::ci("a")
+            .append_string_domain(&CharacterInclusionDomain::Top)
+            .try_to_concrete(5)
+            .is_err());
+    }
+
+    #[test]
+    fn intersecting() {
+        let prefix =
+            CharacterInclusionDomain::ci("/").append_string_domain(&CharacterInclusionDomain::Top);
+        let merged =
+            CharacterInclusionDomain::ci("/bin").merge(&CharacterInclusionDomain::ci("usr"));
+
+        assert_eq!(
+            merged.intersect(&prefix).unwrap(),
+            CharacterInclusionDomain::Value((
+                CharacterSet::Value("/".chars().collect()),
+                CharacterSet::Value("/binusr".chars().collect())
+            ))
+        );
+        assert_eq!(
+            CharacterInclusionDomain::Top.intersect(&prefix).unwrap(),
+            prefix
+        );
+        assert_eq!(
+            CharacterInclusionDomain::Top
+                .intersect(&CharacterInclusionDomain::Top)
+                .unwrap(),
+            CharacterInclusionDomain::Top
+        );
+        assert!(CharacterInclusionDomain::ci("usr")
+            .intersect(&prefix)
+            .is_err());
+    }
+
+    #[test]
+    fn alphabets() {
+        assert!(CharacterAlphabet::AsciiPrintable.contains('\n'));
+        assert!(!CharacterAlphabet::AsciiPrintable.contains('\u{7f}'));
+        assert!(CharacterAlphabet::FullByteRange.contains('\u{e9}'));
+        assert!(!CharacterAlphabet::FullByteRange.contains('\u{20ac}'));
+        assert!(CharacterAlphabet::Utf8.contains('\u{20ac}'));
+        assert_eq!(
+            CharacterAlphabet::AsciiPrintable
+                .get_characters()
+                .unwrap()
+                .len(),
+            99
+        );
+        assert!(CharacterAlphabet::Utf8.get_characters().is_none());
+
+        assert_eq!(
+            CharacterAlphabet::FullByteRange.decode(b"caf\xe9"),
+            Some("caf\u{e9}".to_string())
+        );
+        assert_eq!(CharacterAlphabet::Utf8.decode(b"caf\xe9"), None);
+        assert_eq!(
+            CharacterAlphabet::Utf8.decode("caf\u{e9}".as_bytes()),
+            Some("caf\u{e9}".to_string())
+        );
+        assert_eq!(
+            CharacterAlphabet::AsciiPrintable.decode("caf\u{e9}".as_bytes()),
+            None
+        );
+    }
+
+    #[test]
+    fn binary_data_classification() {
+        assert!(!CharacterAlphabet::AsciiPrintable.is_probably_binary_data(b"/bin/sh -c\n"));
+        assert!(!CharacterAlphabet::Utf8.is_probably_binary_data(b""));
+        assert!(!CharacterAlphabet::Utf8.is_probably_binary_data(b"\x1b[31mred"));
+        assert!(CharacterAlphabet::Utf8.is_probably_binary_data(b"\x01\x02A"));
+        assert!(CharacterAlphabet::Utf8.is_probably_binary_data(b"\xff\xfe"));
+        assert!(!CharacterAlphabet::FullByteRange.is_probably_binary_data(b"\xff\xfe"));
+        assert!(CharacterAlphabet::FullByteRange.is_probably_binary_data(b"\x90\x90\xcc"));
+        assert!(CharacterAlphabet::AsciiPrintable.is_probably_binary_data(b"AB\x1bC"));
+    }
+
+    #[test]
+    fn restricting_to_alphabets() {
+        let ascii_characters = CharacterAlphabet::AsciiPrintable.get_characters().unwrap();
+        assert_eq!(
+            CharacterInclusionDomain::Top.restrict_to_alphabet(CharacterAlphabet::AsciiPrintable),
+            CharacterInclusionDomain::Value((
+                CharacterSet::Value(BTreeSet::new()),
+                CharacterSet::Value(ascii_characters)
+            ))
+        );
+        assert_eq!(
+            CharacterInclusionDomain::ci("caf\u{e9}")
+                .restrict_to_alphabet(CharacterAlphabet::AsciiPrintable),
+            CharacterInclusionDomain::ci("caf")
+        );
+        assert_eq!(
+            CharacterInclusionDomain::Top.restrict_to_alphabet(CharacterAlphabet::Utf8),
+            CharacterInclusionDomain::Top
+        );
+    }
 }
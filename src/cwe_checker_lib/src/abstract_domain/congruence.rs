@@ -0,0 +1,498 @@
+//! This module contains the Congruence Domain.
+//!
+//! The domain represents all values `x` satisfying `x ≡ remainder mod modulus`,
+//! where the modulus is a power of two.
+//! Restricting the modulus to powers of two keeps the domain sound under the wrap-around semantics of bitvector arithmetic
+//! and allows exact transfer functions for bit masking operations.
+//! Thus the domain is well suited to track the alignment of pointers and the strides of array indices.
+//!
+//! In contrast to the stride of an [`Interval`] the congruence does not get lost
+//! if the bounds of a value are unknown (e.g. after widening to *Top*)
+//! or if the value is aligned through bit masking (e.g. `x & ~7`).
+//! Use [`CongruenceDomain::refine_interval`] to combine the information of both domains.
+
+use std::fmt::Display;
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+
+use super::{
+    AbstractDomain, DomainOrder, HasTop, Interval, RegisterDomain, SizedDomain, TryToBitvec,
+};
+
+/// An abstract domain representing the values `x` with `x ≡ remainder mod 2^modulus_bits`,
+/// i.e. the values whose `modulus_bits` least significant bits are known.
+///
+/// If all bits of a value are known, the domain represents exactly one value.
+/// For values larger than 8 bytes only the congruence of the 64 least significant bits is tracked.
+/// A modulus of `2^0 = 1` represents the *Top* value of the domain.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct CongruenceDomain {
+    /// The least significant bits of all represented values.
+    remainder: u64,
+    /// The number of known least significant bits, i.e. the base 2 logarithm of the modulus.
+    modulus_bits: u32,
+    /// The size of the represented values.
+    bytesize: ByteSize,
+}
+
+impl CongruenceDomain {
+    /// Create a new domain representing the values `x` with `x ≡ remainder mod 2^modulus_bits`.
+    ///
+    /// The number of modulus bits is truncated to the maximal number of bits trackable for the given byte size.
+    pub fn new(remainder: u64, modulus_bits: u32, bytesize: ByteSize) -> CongruenceDomain {
+        let modulus_bits = std::cmp::min(modulus_bits, max_modulus_bits(bytesize));
+        CongruenceDomain {
+            remainder: remainder & low_bits_mask(modulus_bits),
+            modulus_bits,
+            bytesize,
+        }
+    }
+
+    /// Get the known least significant bits of the represented values.
+    pub fn remainder(&self) -> u64 {
+        self.remainder
+    }
+
+    /// Get the base 2 logarithm of the modulus, i.e. the number of known least significant bits.
+    pub fn modulus_bits(&self) -> u32 {
+        self.modulus_bits
+    }
+
+    /// Returns true if all represented values are multiples of `alignment`.
+    /// The alignment has to be a power of two.
+    pub fn is_aligned_to(&self, alignment: u64) -> bool {
+        assert!(alignment.is_power_of_two());
+        alignment.trailing_zeros() <= self.modulus_bits && self.remainder & (alignment - 1) == 0
+    }
+
+    /// Restrict the given interval to the values also contained in `self`.
+    ///
+    /// Returns an error if no value of the interval is contained in `self`.
+    /// Intervals of values larger than 8 bytes are returned unchanged.
+    pub fn refine_interval(&self, interval: &Interval) -> Result<Interval, Error> {
+        if self.modulus_bits == 0 || interval.bytesize() > ByteSize::new(8) {
+            return Ok(interval.clone());
+        }
+        if let Ok(value) = self.try_to_bitvec() {
+            if interval.contains(&value) {
+                return Ok(value.into());
+            } else {
+                return Err(anyhow!("Empty interval"));
+            }
+        }
+        let congruence_interval = Interval::new_top(interval.bytesize())
+            .adjust_to_stride_and_remainder(1 << self.modulus_bits, self.remainder)?;
+        interval.signed_intersect(&congruence_interval)
+    }
+
+    /// Returns true if the domain represents exactly one value.
+    fn is_exact(&self) -> bool {
+        self.modulus_bits == self.bytesize.as_bit_length() as u32
+    }
+
+    /// Return a bitmask of the known bits of the represented values.
+    fn known_bits_mask(&self) -> u64 {
+        low_bits_mask(self.modulus_bits)
+    }
+
+    /// Create a new domain where the known bits are given by `known_bits`.
+    /// Only the contiguous known bits starting from the least significant bit are kept.
+    fn from_known_bits(known_bits: u64, value: u64, bytesize: ByteSize) -> CongruenceDomain {
+        CongruenceDomain::new(value, known_bits.trailing_ones(), bytesize)
+    }
+
+    /// Compute the result of a binary operation exactly if both inputs represent exactly one value.
+    /// Return *Top* otherwise.
+    fn bin_op_on_exact_values(&self, op: BinOpType, rhs: &Self) -> Self {
+        if let (Ok(lhs_value), Ok(rhs_value)) = (self.try_to_bitvec(), rhs.try_to_bitvec()) {
+            if let Ok(result) = lhs_value.bin_op(op, &rhs_value) {
+                return result.into();
+            }
+        }
+        CongruenceDomain::new_top(self.bin_op_bytesize(op, rhs))
+    }
+
+    /// Compute the result of a left or right shift by `rhs` bits.
+    /// The result is *Top* if the shift amount is not exactly known.
+    fn shift(&self, op: BinOpType, rhs: &Self) -> Self {
+        let Ok(shift_amount) = rhs
+            .try_to_bitvec()
+            .and_then(|value| Ok(value.try_to_u64()?))
+        else {
+            return CongruenceDomain::new_top(self.bytesize);
+        };
+        if self.is_exact() {
+            return self.bin_op_on_exact_values(op, rhs);
+        }
+        let shift_amount = std::cmp::min(shift_amount, 64) as u32;
+        match op {
+            BinOpType::IntLeft => CongruenceDomain::new(
+                self.remainder.checked_shl(shift_amount).unwrap_or(0),
+                self.modulus_bits + shift_amount,
+                self.bytesize,
+            ),
+            _ => CongruenceDomain::new(
+                self.remainder.checked_shr(shift_amount).unwrap_or(0),
+                self.modulus_bits.saturating_sub(shift_amount),
+                self.bytesize,
+            ),
+        }
+    }
+}
+
+/// Return the maximal number of bits of values with the given byte size that the congruence domain can track.
+fn max_modulus_bits(bytesize: ByteSize) -> u32 {
+    std::cmp::min(bytesize.as_bit_length(), 64) as u32
+}
+
+/// Return a bitmask where the `bits` least significant bits are set.
+fn low_bits_mask(bits: u32) -> u64 {
+    u64::MAX.checked_shr(64 - bits).unwrap_or(0)
+}
+
+impl AbstractDomain for CongruenceDomain {
+    /// Merge two congruences.
+    /// The result keeps the least significant bits that are known and equal in both inputs.
+    fn merge(&self, other: &Self) -> Self {
+        let modulus_bits = std::cmp::min(
+            std::cmp::min(self.modulus_bits, other.modulus_bits),
+            (self.remainder ^ other.remainder).trailing_zeros(),
+        );
+        CongruenceDomain::new(self.remainder, modulus_bits, self.bytesize)
+    }
+
+    /// Return true if no bit of the represented values is known.
+    fn is_top(&self) -> bool {
+        self.modulus_bits == 0
+    }
+}
+
+impl DomainOrder for CongruenceDomain {
+    /// `self` is less or equal to `other` if the known bits of `other` are also known and equal in `self`.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        other.modulus_bits <= self.modulus_bits
+            && (self.remainder ^ other.remainder) & other.known_bits_mask() == 0
+    }
+}
+
+impl SizedDomain for CongruenceDomain {
+    /// Return the size in bytes of the represented values.
+    fn bytesize(&self) -> ByteSize {
+        self.bytesize
+    }
+
+    /// Return a new *Top* value with the given bytesize.
+    fn new_top(bytesize: ByteSize) -> Self {
+        CongruenceDomain {
+            remainder: 0,
+            modulus_bits: 0,
+            bytesize,
+        }
+    }
+}
+
+impl HasTop for CongruenceDomain {
+    /// Return a *Top* value with the same bytesize as `self`.
+    fn top(&self) -> Self {
+        CongruenceDomain::new_top(self.bytesize)
+    }
+}
+
+impl RegisterDomain for CongruenceDomain {
+    /// Compute the congruence of the result of a binary operation.
+    ///
+    /// For binary operations that are not explicitly implemented
+    /// the result is only exact if both inputs represent exactly one value.
+    fn bin_op(&self, op: BinOpType, rhs: &Self) -> Self {
+        use BinOpType::*;
+        let bytesize = self.bytesize;
+        let known_bits = self.known_bits_mask() & rhs.known_bits_mask();
+        match op {
+            IntAdd => CongruenceDomain::from_known_bits(
+                known_bits,
+                self.remainder.wrapping_add(rhs.remainder),
+                bytesize,
+            ),
+            IntSub => CongruenceDomain::from_known_bits(
+                known_bits,
+                self.remainder.wrapping_sub(rhs.remainder),
+                bytesize,
+            ),
+            IntMult => {
+                // For x = a + i * 2^k and y = b + j * 2^l we have
+                // x * y = a * b + a * j * 2^l + b * i * 2^k + i * j * 2^(k + l).
+                let modulus_bits = [
+                    rhs.modulus_bits
+                        .saturating_add(self.remainder.trailing_zeros()),
+                    self.modulus_bits
+                        .saturating_add(rhs.remainder.trailing_zeros()),
+                    self.modulus_bits + rhs.modulus_bits,
+                ]
+                .into_iter()
+                .min()
+                .unwrap();
+                CongruenceDomain::new(
+                    self.remainder.wrapping_mul(rhs.remainder),
+                    modulus_bits,
+                    bytesize,
+                )
+            }
+            IntAnd => {
+                let known_zeros = (self.known_bits_mask() & !self.remainder)
+                    | (rhs.known_bits_mask() & !rhs.remainder);
+                CongruenceDomain::from_known_bits(
+                    known_bits | known_zeros,
+                    self.remainder & rhs.remainder,
+                    bytesize,
+                )
+            }
+            IntOr => {
+                let known_ones = (self.known_bits_mask() & self.remainder)
+                    | (rhs.known_bits_mask() & rhs.remainder);
+                CongruenceDomain::from_known_bits(
+                    known_bits | known_ones,
+                    self.remainder | rhs.remainder,
+                    bytesize,
+                )
+            }
+            IntXOr => CongruenceDomain::from_known_bits(
+                known_bits,
+                self.remainder ^ rhs.remainder,
+                bytesize,
+            ),
+            IntLeft | IntRight | IntSRight => self.shift(op, rhs),
+            Piece => {
+                let low_bits = rhs.bytesize.as_bit_length() as u32;
+                if rhs.is_exact() {
+                    CongruenceDomain::new(
+                        rhs.remainder | self.remainder.checked_shl(low_bits).unwrap_or(0),
+                        low_bits + self.modulus_bits,
+                        self.bytesize + rhs.bytesize,
+                    )
+                } else {
+                    CongruenceDomain::new(
+                        rhs.remainder,
+                        rhs.modulus_bits,
+                        self.bytesize + rhs.bytesize,
+                    )
+                }
+            }
+            IntEqual | IntNotEqual | IntLess | IntSLess | IntLessEqual | IntSLessEqual
+            | IntCarry | IntSCarry | IntSBorrow | IntDiv | IntSDiv | IntRem | IntSRem | BoolAnd
+            | BoolOr | BoolXOr | FloatEqual | FloatNotEqual | FloatLess | FloatLessEqual
+            | FloatAdd | FloatSub | FloatMult | FloatDiv => self.bin_op_on_exact_values(op, rhs),
+        }
+    }
+
+    /// Compute the congruence of the result of an unary operation.
+    fn un_op(&self, op: UnOpType) -> Self {
+        use UnOpType::*;
+        match op {
+            Int2Comp => CongruenceDomain::new(
+                self.remainder.wrapping_neg(),
+                self.modulus_bits,
+                self.bytesize,
+            ),
+            IntNegate => CongruenceDomain::new(!self.remainder, self.modulus_bits, self.bytesize),
+            _ => match self.try_to_bitvec().map(|value| value.un_op(op)) {
+                Ok(Ok(result)) => result.into(),
+                _ => CongruenceDomain::new_top(match op {
+                    FloatNaN => ByteSize::new(1),
+                    _ => self.bytesize,
+                }),
+            },
+        }
+    }
+
+    /// Extract a sub-bitvector of the represented values.
+    fn subpiece(&self, low_byte: ByteSize, size: ByteSize) -> Self {
+        if let Ok(value) = self.try_to_bitvec() {
+            return value.subpiece(low_byte, size).into();
+        }
+        let shift_amount = low_byte.as_bit_length() as u32;
+        CongruenceDomain::new(
+            self.remainder.checked_shr(shift_amount).unwrap_or(0),
+            self.modulus_bits.saturating_sub(shift_amount),
+            size,
+        )
+    }
+
+    /// Compute the congruence of the result of a cast operation.
+    /// Extensions of integers preserve the known least significant bits.
+    fn cast(&self, kind: CastOpType, width: ByteSize) -> Self {
+        if let Ok(Ok(result)) = self.try_to_bitvec().map(|value| value.cast(kind, width)) {
+            return result.into();
+        }
+        match kind {
+            CastOpType::IntZExt | CastOpType::IntSExt => {
+                CongruenceDomain::new(self.remainder, self.modulus_bits, width)
+            }
+            _ => CongruenceDomain::new_top(width),
+        }
+    }
+}
+
+impl From<Bitvector> for CongruenceDomain {
+    /// Create a domain representing exactly the given value.
+    /// For values larger than 8 bytes only the 64 least significant bits are known.
+    fn from(bitvec: Bitvector) -> Self {
+        let bytesize = bitvec.bytesize();
+        let remainder = bitvec
+            .into_resize_unsigned(std::cmp::min(bytesize, ByteSize::new(8)))
+            .try_to_u64()
+            .unwrap();
+        CongruenceDomain::new(remainder, max_modulus_bits(bytesize), bytesize)
+    }
+}
+
+impl From<&Interval> for CongruenceDomain {
+    /// Create the congruence implied by the stride and the bounds of the interval.
+    fn from(interval: &Interval) -> Self {
+        let bytesize = interval.bytesize();
+        if interval.start == interval.end {
+            return interval.start.clone().into();
+        }
+        if bytesize > ByteSize::new(8) {
+            return CongruenceDomain::new_top(bytesize);
+        }
+        CongruenceDomain::new(
+            interval
+                .start
+                .clone()
+                .into_resize_unsigned(ByteSize::new(8))
+                .try_to_u64()
+                .unwrap(),
+            interval.stride.trailing_zeros(),
+            bytesize,
+        )
+    }
+}
+
+impl TryToBitvec for CongruenceDomain {
+    /// If the domain represents exactly one value, return it.
+    fn try_to_bitvec(&self) -> Result<Bitvector, Error> {
+        if self.is_exact() {
+            Ok(Bitvector::from_u64(self.remainder).into_resize_unsigned(self.bytesize))
+        } else {
+            Err(anyhow!("More than one value in the congruence class."))
+        }
+    }
+}
+
+impl Display for CongruenceDomain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_top() {
+            write!(f, "Top:i{}", self.bytesize.as_bit_length())
+        } else if self.is_exact() {
+            write!(
+                f,
+                "0x{:x}:i{}",
+                self.remainder,
+                self.bytesize.as_bit_length()
+            )
+        } else {
+            write!(
+                f,
+                "0x{:x} mod 2^{}:i{}",
+                self.remainder,
+                self.modulus_bits,
+                self.bytesize.as_bit_length()
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitvec;
+
+    fn mock(remainder: u64, modulus_bits: u32) -> CongruenceDomain {
+        CongruenceDomain::new(remainder, modulus_bits, ByteSize::new(8))
+    }
+
+    #[test]
+    fn merging_and_ordering() {
+        let aligned = mock(0, 3);
+        let exact: CongruenceDomain = bitvec!("0x18:8").into();
+        assert_eq!(exact.merge(&bitvec!("0x8:8").into()), mock(8, 4));
+        assert_eq!(exact.merge(&aligned), aligned);
+        assert_eq!(aligned.merge(&mock(4, 3)), mock(0, 2));
+        assert!(exact.less_or_equal(&aligned));
+        assert!(!aligned.less_or_equal(&exact));
+        assert!(!mock(4, 3).less_or_equal(&aligned));
+        assert!(mock(1, 1).merge(&mock(0, 3)).is_top());
+        assert_eq!(exact.try_to_bitvec().unwrap(), bitvec!("0x18:8"));
+        assert!(aligned.try_to_bitvec().is_err());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let index = CongruenceDomain::new_top(ByteSize::new(8));
+        let eight: CongruenceDomain = bitvec!("8:8").into();
+        let offset = index.bin_op(BinOpType::IntMult, &eight);
+        assert_eq!(offset, mock(0, 3));
+        assert!(offset.is_aligned_to(8));
+        assert!(!offset.is_aligned_to(16));
+        let address = offset.bin_op(BinOpType::IntAdd, &bitvec!("0x1004:8").into());
+        assert_eq!(address, mock(4, 3));
+        assert_eq!(
+            address.bin_op(BinOpType::IntSub, &bitvec!("4:8").into()),
+            mock(0, 3)
+        );
+        assert_eq!(
+            mock(2, 2).bin_op(BinOpType::IntLeft, &bitvec!("2:8").into()),
+            mock(8, 4)
+        );
+        assert_eq!(
+            mock(4, 4).bin_op(BinOpType::IntRight, &bitvec!("2:8").into()),
+            mock(1, 2)
+        );
+        assert_eq!(mock(4, 3).un_op(UnOpType::Int2Comp), mock(4, 3));
+        assert_eq!(
+            mock(4, 3).cast(CastOpType::IntZExt, ByteSize::new(16)),
+            CongruenceDomain::new(4, 3, ByteSize::new(16))
+        );
+        assert_eq!(
+            mock(0x104, 12).subpiece(ByteSize::new(1), ByteSize::new(4)),
+            CongruenceDomain::new(1, 4, ByteSize::new(4))
+        );
+    }
+
+    #[test]
+    fn bit_masks() {
+        let unknown = CongruenceDomain::new_top(ByteSize::new(8));
+        // Aligning a value by masking out the lower bits.
+        let aligned = unknown.bin_op(BinOpType::IntAnd, &bitvec!("0xfffffffffffffff8:8").into());
+        assert_eq!(aligned, mock(0, 3));
+        let low_bits = unknown.bin_op(BinOpType::IntOr, &bitvec!("0x3:8").into());
+        assert_eq!(low_bits, mock(3, 2));
+        assert_eq!(
+            mock(5, 3).bin_op(BinOpType::IntXOr, &bitvec!("0x1:8").into()),
+            mock(4, 3)
+        );
+        let piece: CongruenceDomain = CongruenceDomain::new_top(ByteSize::new(4))
+            .bin_op(BinOpType::Piece, &bitvec!("0x10:4").into());
+        assert_eq!(piece, mock(0x10, 32));
+    }
+
+    #[test]
+    fn interval_refinement() {
+        let interval = Interval::new(bitvec!("0:8"), bitvec!("20:8"), 1);
+        let refined = mock(0, 3).refine_interval(&interval).unwrap();
+        assert_eq!(refined, Interval::new(bitvec!("0:8"), bitvec!("16:8"), 8));
+        let refined = mock(4, 3).refine_interval(&interval).unwrap();
+        assert_eq!(refined, Interval::new(bitvec!("4:8"), bitvec!("20:8"), 8));
+        let exact: CongruenceDomain = bitvec!("24:8").into();
+        assert!(exact.refine_interval(&interval).is_err());
+        assert_eq!(
+            CongruenceDomain::from(&Interval::new(bitvec!("4:8"), bitvec!("36:8"), 8)),
+            mock(4, 3)
+        );
+        assert_eq!(
+            CongruenceDomain::from(&Interval::from(bitvec!("-8:8"))),
+            bitvec!("-8:8").into()
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
-    AbstractDomain, AbstractIdentifier, HasTop, Interval, RegisterDomain, SizedDomain,
-    SpecializeByConditional, TryToBitvec, TryToInterval,
+    AbstractDomain, AbstractIdentifier, ApproximateMemorySize, DomainOrder, HasTop, Interval,
+    RegisterDomain, SizedDomain, SpecializeByConditional, TryToBitvec, TryToInterval,
 };
 use crate::intermediate_representation::*;
 use crate::prelude::*;
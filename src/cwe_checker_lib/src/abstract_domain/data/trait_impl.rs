@@ -21,6 +21,36 @@ impl<T: RegisterDomain> SizedDomain for DataDomain<T> {
     }
 }
 
+impl<T: RegisterDomain + DomainOrder> DomainOrder for DataDomain<T> {
+    /// Check whether every relative and absolute value of `self` is also contained in `other`
+    /// and whether `other` contains *Top* values if `self` does.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        let relative_values_contained = self.relative_values.iter().all(|(id, offset)| {
+            other
+                .relative_values
+                .get(id)
+                .is_some_and(|other_offset| offset.less_or_equal(other_offset))
+        });
+        let absolute_value_contained = match (&self.absolute_value, &other.absolute_value) {
+            (None, _) => true,
+            (Some(value), Some(other_value)) => value.less_or_equal(other_value),
+            (Some(_), None) => false,
+        };
+        relative_values_contained
+            && absolute_value_contained
+            && (!self.contains_top_values || other.contains_top_values)
+    }
+}
+
+impl<T: RegisterDomain> ApproximateMemorySize for DataDomain<T> {
+    /// Count the memory of the domain itself and of its relative values.
+    fn approximate_memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.relative_values.len()
+                * (std::mem::size_of::<AbstractIdentifier>() + std::mem::size_of::<T>())
+    }
+}
+
 impl<T: RegisterDomain> HasTop for DataDomain<T> {
     /// Generate a new *Top* element with the same bytesize as `self`.
     fn top(&self) -> Self {
@@ -165,4 +195,18 @@ mod tests {
             Some(&bv(10))
         );
     }
+
+    #[test]
+    fn data_order() {
+        let pointer = new_pointer("RAX", 0);
+        let value = new_value(42);
+        let merged_data = pointer.merge(&value);
+        assert!(pointer.less_or_equal(&merged_data));
+        assert!(value.less_or_equal(&merged_data));
+        assert!(!merged_data.less_or_equal(&pointer));
+        assert!(!pointer.less_or_equal(&new_pointer("RAX", 8)));
+        assert!(new_value(-1).less_or_equal(&value.merge(&new_value(-1))));
+        assert!(!pointer.less_or_equal(&Data::new_top(ByteSize::new(8))));
+        assert!(!Data::new_top(ByteSize::new(8)).less_or_equal(&pointer));
+    }
 }
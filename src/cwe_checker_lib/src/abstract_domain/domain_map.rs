@@ -128,6 +128,40 @@ where
     }
 }
 
+impl<K, V> DomainOrder for DomainMap<K, V, MergeTopStrategy>
+where
+    K: PartialOrd + Ord + Clone,
+    V: AbstractDomain + HasTop + DomainOrder,
+{
+    /// Keys missing in a map have *Top* values.
+    /// Thus `self` is less than or equal to `other` if every value in `other`
+    /// is greater than or equal to the value of the same key in `self`.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self == other
+            || other.iter().all(|(key, other_value)| {
+                self.get(key)
+                    .is_some_and(|value| value.less_or_equal(other_value))
+            })
+    }
+}
+
+impl<K, V, S> ApproximateMemorySize for DomainMap<K, V, S>
+where
+    K: PartialOrd + Ord + Clone,
+    V: AbstractDomain + ApproximateMemorySize,
+    S: MapMergeStrategy<K, V>,
+{
+    /// Count the memory of the map and of all values contained in it.
+    fn approximate_memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self
+                .inner
+                .values()
+                .map(|value| std::mem::size_of::<K>() + value.approximate_memory_size())
+                .sum::<usize>()
+    }
+}
+
 impl<K, V, S> Default for DomainMap<K, V, S>
 where
     K: PartialOrd + Ord + Clone,
@@ -3,7 +3,10 @@ use std::fmt::Display;
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 
-use super::{AbstractDomain, HasTop, RegisterDomain, SizedDomain, SpecializeByConditional};
+use super::operation_counters::{self, CountedDomain, DomainOperation};
+use super::{
+    AbstractDomain, DomainOrder, HasTop, RegisterDomain, SizedDomain, SpecializeByConditional,
+};
 use super::{TryToBitvec, TryToInterval};
 
 mod simple_interval;
@@ -150,6 +153,7 @@ impl IntervalDomain {
     ///
     /// After that the `widening_delay` is set to the length of the resulting interval.
     pub fn signed_merge_and_widen(&self, other: &IntervalDomain) -> IntervalDomain {
+        operation_counters::count(CountedDomain::Interval, DomainOperation::Merge);
         let mut merged_domain = self.signed_merge(other);
         if merged_domain.equal_as_value_sets(self)
             || merged_domain.equal_as_value_sets(other)
@@ -172,6 +176,7 @@ impl IntervalDomain {
                 return merged_domain;
             }
         }
+        operation_counters::count(CountedDomain::Interval, DomainOperation::Widen);
         let mut has_been_widened = false;
         if self.interval.start != other.interval.start
             && merged_domain.widening_lower_bound.is_some()
@@ -831,5 +836,13 @@ impl StrideRounding for Bitvector {
     }
 }
 
+impl DomainOrder for IntervalDomain {
+    /// Check whether the interval of `self` is contained in the interval of `other`.
+    /// Widening hints are ignored.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self.interval.signed_merge(&other.interval) == other.interval
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -140,6 +140,17 @@ fn signed_merge() {
     assert_eq!(var, expected_result);
 }
 
+#[test]
+fn interval_order() {
+    let small = IntervalDomain::mock(2, 4);
+    let large = IntervalDomain::mock(-1, 10);
+    assert!(small.less_or_equal(&large));
+    assert!(!large.less_or_equal(&small));
+    assert!(small.less_or_equal(&small.merge(&large)));
+    assert!(large.less_or_equal(&IntervalDomain::new_top(ByteSize::new(8))));
+    assert!(!IntervalDomain::mock(2, 12).less_or_equal(&large));
+}
+
 #[test]
 fn cast_zero_and_signed_extend() {
     // Zero extend
@@ -0,0 +1,292 @@
+//! Property-based tests of the lattice laws of abstract domains.
+//!
+//! The generators produce abstract values together with concrete values that they represent.
+//! The abstract values are built like in the analyses, i.e. by abstracting concrete values
+//! and by merging and combining the results.
+//! The tests check general laws of the domain operations,
+//! e.g. that merging is sound and commutative, that *Top* absorbs all values under merges,
+//! that ascending chains of merges stabilize after a bounded number of steps
+//! and that transfer functions overapproximate the corresponding concrete operations.
+//!
+//! Widening operators are in general not associative,
+//! so for merges that may widen only the soundness of both ways of merging is checked.
+
+use super::*;
+use proptest::prelude::*;
+
+/// The maximal number of changes in an ascending chain of merges of string domains.
+const STRING_CHAIN_BOUND: usize = 16;
+
+/// The maximal number of changes in an ascending chain of merges of interval domains.
+const INTERVAL_CHAIN_BOUND: usize = 4;
+
+/// A string domain whose concretization can be queried.
+trait Concretization {
+    /// Check whether the string is represented by the abstract value.
+    fn represents(&self, string: &str) -> bool;
+}
+
+impl Concretization for BricksDomain {
+    fn represents(&self, string: &str) -> bool {
+        self.contains_string(string)
+    }
+}
+
+impl Concretization for CharacterInclusionDomain {
+    fn represents(&self, string: &str) -> bool {
+        let Some((certain, possible)) = self.as_value() else {
+            return true;
+        };
+        certain
+            .as_value()
+            .is_some_and(|certain| certain.iter().all(|char| string.contains(*char)))
+            && possible
+                .as_value()
+                .is_none_or(|possible| string.chars().all(|char| possible.contains(&char)))
+    }
+}
+
+/// Strings over a small alphabet, so that generated strings share characters and substrings.
+fn concrete_string() -> impl Strategy<Value = String> {
+    "[ab/]{0,4}"
+}
+
+/// A string domain value merged from the abstractions of some concrete strings,
+/// optionally followed by an unknown suffix, together with the merged concrete strings.
+fn string_domain<T>() -> impl Strategy<Value = (T, Vec<String>)>
+where
+    T: AbstractDomain + DomainInsertion + From<String> + std::fmt::Debug,
+{
+    (
+        prop::collection::vec(concrete_string(), 1..4),
+        any::<bool>(),
+    )
+        .prop_map(|(strings, has_unknown_suffix)| {
+            let mut value = strings
+                .iter()
+                .map(|string| T::from(string.clone()))
+                .reduce(|value, other| value.merge(&other))
+                .unwrap();
+            if has_unknown_suffix {
+                value = value.append_string_domain(&T::create_top_value_domain());
+            }
+            (value, strings)
+        })
+}
+
+/// Check the laws of string domains for the given abstract values and their concrete strings.
+fn check_string_domain_laws<T>(
+    (first, first_strings): &(T, Vec<String>),
+    (second, second_strings): &(T, Vec<String>),
+    (third, third_strings): &(T, Vec<String>),
+) -> Result<(), TestCaseError>
+where
+    T: AbstractDomain + DomainInsertion + HasTop + Concretization + std::fmt::Debug,
+{
+    let merged = first.merge(second);
+    // Soundness
+    for string in first_strings.iter().chain(second_strings) {
+        prop_assert!(merged.represents(string), "{string:?} lost in {merged:?}");
+    }
+    // Commutativity and idempotence
+    prop_assert_eq!(&merged, &second.merge(first));
+    prop_assert_eq!(&first.merge(first), first);
+    // Associativity, in the sense that both ways of merging are sound
+    let left_associated = merged.merge(third);
+    let right_associated = first.merge(&second.merge(third));
+    for string in first_strings
+        .iter()
+        .chain(second_strings)
+        .chain(third_strings)
+    {
+        prop_assert!(left_associated.represents(string));
+        prop_assert!(right_associated.represents(string));
+    }
+    // Top absorption
+    let top = first.top();
+    prop_assert!(first.merge(&top).is_top());
+    prop_assert!(top.merge(first).is_top());
+    // Soundness of concatenation
+    let appended = first.append_string_domain(second);
+    for (prefix, suffix) in first_strings.iter().zip(second_strings) {
+        let string = format!("{prefix}{suffix}");
+        prop_assert!(
+            appended.represents(&string),
+            "{string:?} lost in {appended:?}"
+        );
+    }
+    Ok(())
+}
+
+/// Merge the abstractions of pairwise different strings one by one
+/// and return the number of merges that changed the value.
+fn count_string_chain_changes<T>(seed: &str, length: usize) -> usize
+where
+    T: AbstractDomain + From<String>,
+{
+    let mut value = T::from(seed.to_string());
+    let mut changes = 0;
+    for index in 0..length {
+        let merged = value.merge(&T::from(format!("{seed}{index}")));
+        if merged != value {
+            changes += 1;
+        }
+        value = merged;
+    }
+    changes
+}
+
+/// A strided interval of 4-byte values, optionally with widening hints,
+/// together with the values contained in it.
+fn interval_domain() -> impl Strategy<Value = (IntervalDomain, Vec<Bitvector>)> {
+    (
+        -1000i32..1000,
+        1u64..8,
+        0u64..16,
+        prop::option::of(1i32..100),
+        prop::option::of(1i32..100),
+    )
+        .prop_map(|(start, stride, count, lower_hint, upper_hint)| {
+            let stride = if count == 0 { 0 } else { stride };
+            let end = start + (stride * count) as i32;
+            let values: Vec<Bitvector> = (0..=count)
+                .map(|index| Bitvector::from_i32(start + (stride * index) as i32))
+                .collect();
+            let mut value = IntervalDomain::from(Interval::new(
+                Bitvector::from_i32(start),
+                Bitvector::from_i32(end),
+                stride,
+            ));
+            value.update_widening_lower_bound(
+                &lower_hint.map(|hint| Bitvector::from_i32(start - hint)),
+            );
+            value.update_widening_upper_bound(
+                &upper_hint.map(|hint| Bitvector::from_i32(end + hint)),
+            );
+            (value, values)
+        })
+}
+
+/// Check whether the interval domain contains the value.
+fn interval_contains(interval: &IntervalDomain, value: &Bitvector) -> bool {
+    interval
+        .try_to_interval()
+        .map_or(true, |interval| interval.contains(value))
+}
+
+proptest! {
+    #[test]
+    fn bricks_domain_laws(
+        first in string_domain::<BricksDomain>(),
+        second in string_domain::<BricksDomain>(),
+        third in string_domain::<BricksDomain>(),
+    ) {
+        check_string_domain_laws(&first, &second, &third)?;
+    }
+
+    #[test]
+    fn character_inclusion_domain_laws(
+        first in string_domain::<CharacterInclusionDomain>(),
+        second in string_domain::<CharacterInclusionDomain>(),
+        third in string_domain::<CharacterInclusionDomain>(),
+    ) {
+        check_string_domain_laws(&first, &second, &third)?;
+        // The domain is a lattice, so merging is associative.
+        prop_assert_eq!(
+            first.0.merge(&second.0).merge(&third.0),
+            first.0.merge(&second.0.merge(&third.0))
+        );
+    }
+
+    #[test]
+    fn string_domain_widening_terminates(seed in concrete_string(), length in 0usize..64) {
+        prop_assert!(count_string_chain_changes::<BricksDomain>(&seed, length) <= STRING_CHAIN_BOUND);
+        prop_assert!(
+            count_string_chain_changes::<CharacterInclusionDomain>(&seed, length) <= STRING_CHAIN_BOUND
+        );
+    }
+
+    #[test]
+    fn interval_domain_laws(
+        (first, first_values) in interval_domain(),
+        (second, second_values) in interval_domain(),
+        (third, third_values) in interval_domain(),
+    ) {
+        let merged = first.merge(&second);
+        for value in first_values.iter().chain(&second_values) {
+            prop_assert!(interval_contains(&merged, value), "{value:?} lost in {merged}");
+        }
+        prop_assert_eq!(&merged, &second.merge(&first));
+        prop_assert_eq!(&first.merge(&first), &first);
+        // Merging without widening is associative.
+        // The widening hints are rounded to the strides of intermediate results,
+        // so only the value sets are compared.
+        prop_assert!(first
+            .signed_merge(&second)
+            .signed_merge(&third)
+            .equal_as_value_sets(&first.signed_merge(&second.signed_merge(&third))));
+        let left_associated = merged.merge(&third);
+        let right_associated = first.merge(&second.merge(&third));
+        for value in first_values.iter().chain(&second_values).chain(&third_values) {
+            prop_assert!(interval_contains(&left_associated, value));
+            prop_assert!(interval_contains(&right_associated, value));
+        }
+        let top = first.top();
+        prop_assert!(first.merge(&top).is_top());
+        prop_assert!(top.merge(&first).is_top());
+    }
+
+    #[test]
+    fn interval_domain_widening_terminates(
+        (start, _) in interval_domain(),
+        step in 1i32..1000,
+        length in 0i32..64,
+    ) {
+        let mut value = start;
+        let mut changes = 0;
+        for index in 1..=length {
+            let counter = IntervalDomain::from(Bitvector::from_i32(index * step));
+            let merged = value.merge(&counter);
+            if merged != value {
+                changes += 1;
+            }
+            value = merged;
+        }
+        prop_assert!(changes <= INTERVAL_CHAIN_BOUND, "{changes} changes, last value {value}");
+    }
+
+    #[test]
+    fn interval_transfer_functions_are_sound(
+        (first, first_values) in interval_domain(),
+        (second, second_values) in interval_domain(),
+        first_index in any::<prop::sample::Index>(),
+        second_index in any::<prop::sample::Index>(),
+    ) {
+        let first_value = first_index.get(&first_values);
+        let second_value = second_index.get(&second_values);
+        for op in [
+            BinOpType::IntAdd,
+            BinOpType::IntSub,
+            BinOpType::IntMult,
+            BinOpType::IntAnd,
+            BinOpType::IntOr,
+            BinOpType::IntXOr,
+            BinOpType::IntSLess,
+        ] {
+            let concrete = first_value.bin_op(op, second_value).unwrap();
+            let abstract_value = first.bin_op(op, &second);
+            prop_assert!(
+                interval_contains(&abstract_value, &concrete),
+                "{op:?}: {concrete:?} not in {abstract_value}"
+            );
+        }
+        for op in [UnOpType::Int2Comp, UnOpType::IntNegate] {
+            let concrete = first_value.un_op(op).unwrap();
+            let abstract_value = first.un_op(op);
+            prop_assert!(
+                interval_contains(&abstract_value, &concrete),
+                "{op:?}: {concrete:?} not in {abstract_value}"
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{AbstractDomain, HasTop, SizedDomain};
+use super::{AbstractDomain, ApproximateMemorySize, DomainOrder, HasTop, SizedDomain};
 use crate::intermediate_representation::ByteSize;
 use crate::prelude::*;
 use crate::utils::debug::ToJsonCompact;
@@ -67,6 +67,40 @@ impl<T: AbstractDomain + SizedDomain + HasTop + std::fmt::Debug> HasTop for MemR
     }
 }
 
+impl<T> DomainOrder for MemRegion<T>
+where
+    T: AbstractDomain + SizedDomain + HasTop + DomainOrder + std::fmt::Debug,
+{
+    /// Offsets without a value contain *Top* values.
+    /// Thus `self` is less than or equal to `other` if for every value in `other`
+    /// `self` contains a lesser or equal value of the same size at the same offset.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self == other
+            || other.inner.values.iter().all(|(offset, other_value)| {
+                self.inner.values.get(offset).is_some_and(|value| {
+                    value.bytesize() == other_value.bytesize() && value.less_or_equal(other_value)
+                })
+            })
+    }
+}
+
+impl<T> ApproximateMemorySize for MemRegion<T>
+where
+    T: ApproximateMemorySize + AbstractDomain + SizedDomain + HasTop + std::fmt::Debug,
+{
+    /// Count the memory of the memory region and of all values contained in it.
+    fn approximate_memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + std::mem::size_of::<Inner<T>>()
+            + self
+                .inner
+                .values
+                .values()
+                .map(|value| std::mem::size_of::<i64>() + value.approximate_memory_size())
+                .sum::<usize>()
+    }
+}
+
 impl<T> ToJsonCompact for MemRegion<T>
 where
     T: ToJsonCompact + AbstractDomain + SizedDomain + HasTop + std::fmt::Debug,
@@ -19,11 +19,25 @@ pub use mem_region::MemRegion;
 mod interval;
 pub use interval::{Interval, IntervalDomain};
 
+mod congruence;
+pub use congruence::CongruenceDomain;
+
 mod bricks;
-pub use bricks::{BrickDomain, BricksDomain};
+pub use bricks::{
+    BrickDomain, BricksDomain, BricksMemoScope, FieldSize, FormatSpecifier, FormatString,
+};
 
 mod character_inclusion;
-pub use character_inclusion::{CharacterInclusionDomain, CharacterSet};
+pub use character_inclusion::{CharacterAlphabet, CharacterInclusionDomain, CharacterSet};
+
+mod nul_termination;
+pub use nul_termination::{NulTerminationDomain, TerminatedStringDomain};
+
+mod nullability;
+pub use nullability::NullabilityDomain;
+
+mod predicate;
+pub use predicate::PredicateDomain;
 
 mod strings;
 pub use strings::*;
@@ -31,6 +45,11 @@ pub use strings::*;
 mod domain_map;
 pub use domain_map::*;
 
+pub mod operation_counters;
+
+#[cfg(test)]
+mod lattice_laws;
+
 /// Marker trait to communicate that the implementing type is "cheap" to
 /// [`Clone.`].
 ///
@@ -78,6 +97,21 @@ pub trait AbstractDomain: Sized + Eq + Clone {
     fn is_top(&self) -> bool;
 }
 
+/// An abstract domain implementing this trait can compare its elements
+/// with respect to the partial order of the domain.
+///
+/// Fixpoint computations can use the order to detect stabilization of values
+/// that are not structurally equal, e.g. because of widening,
+/// and to skip merges with values that are already subsumed.
+pub trait DomainOrder {
+    /// Returns `true` if `self` is less than or equal to `other` with respect to the partial order,
+    /// i.e. if every value represented by `self` is also represented by `other`.
+    ///
+    /// Implementations may return `false` if the relation cannot be decided cheaply.
+    /// Returning `true` for elements that are not ordered is a bug.
+    fn less_or_equal(&self, other: &Self) -> bool;
+}
+
 /// A trait for types representing values with a fixed size (in bytes).
 ///
 /// For abstract domains, the bytesize is a parameter of the domain itself,
@@ -102,6 +136,16 @@ pub trait HasTop {
     fn top(&self) -> Self;
 }
 
+/// A trait for types that can estimate the amount of memory occupied by their values.
+///
+/// Fixpoint computations use the estimate to bound their memory usage.
+/// The estimate only needs to be approximately correct,
+/// e.g. data shared between several values may be counted for each value.
+pub trait ApproximateMemorySize {
+    /// Return the approximate number of bytes occupied by the value, including its heap allocations.
+    fn approximate_memory_size(&self) -> usize;
+}
+
 /// A trait for abstract domains that can represent values loaded into CPU register.
 ///
 /// The domain implements all general operations used to manipulate register values.
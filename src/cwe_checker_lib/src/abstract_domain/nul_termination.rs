@@ -0,0 +1,311 @@
+//! This module contains the NUL-Termination Domain.
+//!
+//! The domain tracks whether a string buffer is guaranteed to contain a
+//! terminating NUL byte. It is a three-valued flag:
+//!
+//! - *Terminated*: the buffer is known to be NUL-terminated,
+//! - *Unterminated*: the buffer is known to lack a NUL-terminator,
+//! - *Top*: the buffer may or may not be NUL-terminated.
+//!
+//! Merging two different values always yields *Top*.
+//!
+//! The [`TerminatedStringDomain`] combines the termination flag with a string domain
+//! so that string analyses can track the content and the termination of a buffer together.
+
+use std::fmt;
+
+use crate::prelude::*;
+
+use super::{AbstractDomain, DomainInsertion, DomainOrder, HasTop};
+
+/// The `NulTerminationDomain` describes whether a string buffer is NUL-terminated.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum NulTerminationDomain {
+    /// The buffer may or may not be NUL-terminated.
+    Top,
+    /// The buffer is known to be NUL-terminated.
+    Terminated,
+    /// The buffer is known to not be NUL-terminated.
+    Unterminated,
+}
+
+impl NulTerminationDomain {
+    /// Returns true if the buffer is not guaranteed to be NUL-terminated.
+    pub fn may_be_unterminated(&self) -> bool {
+        !matches!(self, NulTerminationDomain::Terminated)
+    }
+}
+
+impl AbstractDomain for NulTerminationDomain {
+    /// Two equal values are merged to themselves, all other combinations to *Top*.
+    fn merge(&self, other: &Self) -> Self {
+        if self == other {
+            *self
+        } else {
+            NulTerminationDomain::Top
+        }
+    }
+
+    /// Check if the value is *Top*.
+    fn is_top(&self) -> bool {
+        matches!(self, NulTerminationDomain::Top)
+    }
+}
+
+impl DomainOrder for NulTerminationDomain {
+    /// Every value is less than or equal to *Top* and to itself.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        other.is_top() || self == other
+    }
+}
+
+impl HasTop for NulTerminationDomain {
+    /// Return a *Top* value.
+    fn top(&self) -> Self {
+        NulTerminationDomain::Top
+    }
+}
+
+impl fmt::Display for NulTerminationDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NulTerminationDomain::Top => write!(f, "Top"),
+            NulTerminationDomain::Terminated => write!(f, "Terminated"),
+            NulTerminationDomain::Unterminated => write!(f, "Unterminated"),
+        }
+    }
+}
+
+/// The product of a string domain and the [`NulTerminationDomain`].
+///
+/// The string domain describes the content of a buffer up to the first NUL byte
+/// and the termination flag describes whether such a NUL byte exists.
+/// Strings created from constants or by string functions are NUL-terminated.
+/// Buffers that are truncated to a length not covering the NUL byte of any represented string,
+/// e.g. by `memcpy(buf, "abc", 3)`, are unterminated.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct TerminatedStringDomain<T> {
+    string: T,
+    termination: NulTerminationDomain,
+}
+
+impl<T> TerminatedStringDomain<T> {
+    /// Create a new domain from the given string domain and termination flag.
+    pub fn new(string: T, termination: NulTerminationDomain) -> Self {
+        TerminatedStringDomain {
+            string,
+            termination,
+        }
+    }
+
+    /// Get the string domain describing the content of the buffer.
+    pub fn get_string(&self) -> &T {
+        &self.string
+    }
+
+    /// Get the termination flag of the buffer.
+    pub fn get_termination(&self) -> NulTerminationDomain {
+        self.termination
+    }
+
+    /// Create a NUL-terminated string from the given string domain.
+    fn terminated(string: T) -> Self {
+        TerminatedStringDomain::new(string, NulTerminationDomain::Terminated)
+    }
+}
+
+impl<T: AbstractDomain> AbstractDomain for TerminatedStringDomain<T> {
+    /// Merge both components separately.
+    fn merge(&self, other: &Self) -> Self {
+        TerminatedStringDomain::new(
+            self.string.merge(&other.string),
+            self.termination.merge(&other.termination),
+        )
+    }
+
+    /// The value is *Top* if both components are *Top*.
+    fn is_top(&self) -> bool {
+        self.string.is_top() && self.termination.is_top()
+    }
+}
+
+impl<T: DomainOrder> DomainOrder for TerminatedStringDomain<T> {
+    /// Compare both components separately.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self.string.less_or_equal(&other.string)
+            && self.termination.less_or_equal(&other.termination)
+    }
+}
+
+impl<T: HasTop> HasTop for TerminatedStringDomain<T> {
+    /// Return a *Top* value.
+    fn top(&self) -> Self {
+        TerminatedStringDomain::new(self.string.top(), NulTerminationDomain::Top)
+    }
+}
+
+impl<T: From<String>> From<String> for TerminatedStringDomain<T> {
+    /// Create a NUL-terminated constant string.
+    fn from(string: String) -> Self {
+        TerminatedStringDomain::terminated(T::from(string))
+    }
+}
+
+impl<T: DomainInsertion> DomainInsertion for TerminatedStringDomain<T> {
+    /// Append the string domains.
+    /// The termination of the result is the termination of the appended string.
+    fn append_string_domain(&self, string_domain: &Self) -> Self {
+        TerminatedStringDomain::new(
+            self.string.append_string_domain(&string_domain.string),
+            string_domain.termination,
+        )
+    }
+
+    /// Create a NUL-terminated integer string.
+    fn create_integer_domain() -> Self {
+        TerminatedStringDomain::terminated(T::create_integer_domain())
+    }
+
+    /// Create a NUL-terminated char string.
+    fn create_char_domain() -> Self {
+        TerminatedStringDomain::terminated(T::create_char_domain())
+    }
+
+    /// Create a NUL-terminated float string.
+    fn create_float_value_domain() -> Self {
+        TerminatedStringDomain::terminated(T::create_float_value_domain())
+    }
+
+    /// Create a NUL-terminated pointer value string.
+    fn create_pointer_value_domain() -> Self {
+        TerminatedStringDomain::terminated(T::create_pointer_value_domain())
+    }
+
+    /// Create a *Top* value.
+    fn create_top_value_domain() -> Self {
+        TerminatedStringDomain::new(T::create_top_value_domain(), NulTerminationDomain::Top)
+    }
+
+    /// Create a NUL-terminated empty string.
+    fn create_empty_string_domain() -> Self {
+        TerminatedStringDomain::terminated(T::create_empty_string_domain())
+    }
+
+    /// Truncate the string to the given length in bytes.
+    ///
+    /// The result is unterminated if all represented strings are at least `length` bytes long,
+    /// since the NUL byte is cut off for all of them.
+    /// If all represented strings are shorter than `length`, the termination is unchanged.
+    /// Otherwise the result may or may not be NUL-terminated.
+    fn truncate(&self, length: usize) -> Self {
+        let termination = if self.string.get_min_length() >= length {
+            NulTerminationDomain::Unterminated
+        } else if self
+            .string
+            .get_max_length()
+            .is_some_and(|max_length| max_length < length)
+        {
+            self.termination
+        } else {
+            NulTerminationDomain::Top
+        };
+        TerminatedStringDomain::new(self.string.truncate(length), termination)
+    }
+
+    /// Return the minimal length of the string domain.
+    fn get_min_length(&self) -> usize {
+        self.string.get_min_length()
+    }
+
+    /// Return the maximal length of the string domain.
+    fn get_max_length(&self) -> Option<usize> {
+        self.string.get_max_length()
+    }
+
+    /// Intersect both components separately.
+    ///
+    /// Returns an error if one of the intersections is empty.
+    fn intersect(&self, other: &Self) -> Result<Self, Error> {
+        let termination = match (self.termination, other.termination) {
+            (NulTerminationDomain::Top, termination) | (termination, NulTerminationDomain::Top) => {
+                termination
+            }
+            (self_termination, other_termination) if self_termination == other_termination => {
+                self_termination
+            }
+            _ => return Err(anyhow!("Intersection of termination flags is empty.")),
+        };
+        Ok(TerminatedStringDomain::new(
+            self.string.intersect(&other.string)?,
+            termination,
+        ))
+    }
+
+    /// Merge the string domains with the given cardinality limit and the termination flags.
+    fn merge_with_cardinality_limit(&self, other: &Self, max_cardinality: usize) -> Self {
+        TerminatedStringDomain::new(
+            self.string
+                .merge_with_cardinality_limit(&other.string, max_cardinality),
+            self.termination.merge(&other.termination),
+        )
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for TerminatedStringDomain<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.string, self.termination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::BricksDomain;
+    use NulTerminationDomain::*;
+
+    #[test]
+    fn merging() {
+        assert_eq!(Terminated.merge(&Terminated), Terminated);
+        assert_eq!(Unterminated.merge(&Unterminated), Unterminated);
+        assert_eq!(Terminated.merge(&Unterminated), Top);
+        assert_eq!(Top.merge(&Terminated), Top);
+        assert!(Top.may_be_unterminated());
+        assert!(Unterminated.may_be_unterminated());
+        assert!(!Terminated.may_be_unterminated());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(Terminated.less_or_equal(&Top));
+        assert!(Unterminated.less_or_equal(&Unterminated));
+        assert!(!Terminated.less_or_equal(&Unterminated));
+        assert!(!Top.less_or_equal(&Terminated));
+    }
+
+    #[test]
+    fn string_truncation() {
+        let string: TerminatedStringDomain<BricksDomain> =
+            TerminatedStringDomain::from("abc".to_string());
+        assert_eq!(string.get_termination(), Terminated);
+        // The NUL byte is not copied.
+        let truncated = string.truncate(3);
+        assert_eq!(truncated.get_termination(), Unterminated);
+        assert_eq!(
+            truncated.get_string(),
+            &BricksDomain::from("abc".to_string())
+        );
+        assert_eq!(string.truncate(2).get_termination(), Unterminated);
+        // The NUL byte is copied.
+        assert_eq!(string.truncate(4), string);
+        // Only some of the represented strings are cut off.
+        let strings = string.merge(&TerminatedStringDomain::from("abcdef".to_string()));
+        assert_eq!(strings.truncate(4).get_termination(), Top);
+        // Appending a terminated string terminates the result.
+        assert_eq!(
+            truncated.append_string_domain(&string).get_termination(),
+            Terminated
+        );
+        assert!(Terminated.merge(&Unterminated).is_top());
+        assert!(string.intersect(&truncated).is_err());
+    }
+}
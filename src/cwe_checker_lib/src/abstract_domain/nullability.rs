@@ -0,0 +1,140 @@
+//! This module contains the Nullability Domain.
+//!
+//! The domain tracks whether a pointer value may be the NULL pointer:
+//!
+//! - *NotNull*: the pointer is known to not be NULL,
+//! - *Null*: the pointer is known to be NULL,
+//! - *MaybeNull*: the pointer may or may not be NULL,
+//! - *Top*: nothing is known about the value, e.g. because it is not tracked as a pointer.
+//!
+//! *NotNull* and *Null* are the minimal elements of the lattice and merge to *MaybeNull*.
+//! Values are refined to *NotNull* or *Null* by the outcome of NULL pointer checks.
+
+use std::fmt;
+
+use crate::prelude::*;
+
+use super::{AbstractDomain, DomainOrder, HasTop};
+
+/// The `NullabilityDomain` describes whether a pointer may be the NULL pointer.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum NullabilityDomain {
+    /// Nothing is known about the value.
+    Top,
+    /// The pointer may or may not be NULL.
+    MaybeNull,
+    /// The pointer is known to not be NULL.
+    NotNull,
+    /// The pointer is known to be NULL.
+    Null,
+}
+
+impl NullabilityDomain {
+    /// Returns true if the pointer is tracked and may be NULL.
+    ///
+    /// *Top* values are not tracked as pointers and thus not considered to be possibly NULL.
+    pub fn may_be_null(&self) -> bool {
+        matches!(self, NullabilityDomain::MaybeNull | NullabilityDomain::Null)
+    }
+
+    /// Restrict the value to the outcome of a NULL pointer check,
+    /// i.e. to *Null* if `is_null` is true and to *NotNull* otherwise.
+    ///
+    /// Returns an error if the outcome contradicts the value,
+    /// e.g. if a pointer known to not be NULL is checked to be NULL.
+    pub fn specialize_by_null_check(&self, is_null: bool) -> Result<Self, Error> {
+        use NullabilityDomain::*;
+        match (self, is_null) {
+            (NotNull, true) | (Null, false) => Err(anyhow!("Unsatisfiable NULL pointer check")),
+            (_, true) => Ok(Null),
+            (_, false) => Ok(NotNull),
+        }
+    }
+}
+
+impl AbstractDomain for NullabilityDomain {
+    /// Two equal values are merged to themselves.
+    /// *NotNull*, *Null* and *MaybeNull* merge to *MaybeNull*
+    /// and all combinations with *Top* merge to *Top*.
+    fn merge(&self, other: &Self) -> Self {
+        if self == other {
+            *self
+        } else if self.is_top() || other.is_top() {
+            NullabilityDomain::Top
+        } else {
+            NullabilityDomain::MaybeNull
+        }
+    }
+
+    /// Check if the value is *Top*.
+    fn is_top(&self) -> bool {
+        matches!(self, NullabilityDomain::Top)
+    }
+}
+
+impl DomainOrder for NullabilityDomain {
+    /// Every value is less than or equal to *Top* and to itself.
+    /// *NotNull* and *Null* are also less than *MaybeNull*.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        other.is_top()
+            || self == other
+            || (*other == NullabilityDomain::MaybeNull
+                && matches!(self, NullabilityDomain::NotNull | NullabilityDomain::Null))
+    }
+}
+
+impl HasTop for NullabilityDomain {
+    /// Return a *Top* value.
+    fn top(&self) -> Self {
+        NullabilityDomain::Top
+    }
+}
+
+impl fmt::Display for NullabilityDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NullabilityDomain::Top => write!(f, "Top"),
+            NullabilityDomain::MaybeNull => write!(f, "MaybeNull"),
+            NullabilityDomain::NotNull => write!(f, "NotNull"),
+            NullabilityDomain::Null => write!(f, "Null"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use NullabilityDomain::*;
+
+    #[test]
+    fn merging() {
+        assert_eq!(Null.merge(&Null), Null);
+        assert_eq!(Null.merge(&NotNull), MaybeNull);
+        assert_eq!(NotNull.merge(&MaybeNull), MaybeNull);
+        assert_eq!(Top.merge(&Null), Top);
+        assert!(MaybeNull.may_be_null());
+        assert!(Null.may_be_null());
+        assert!(!NotNull.may_be_null());
+        assert!(!Top.may_be_null());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(Null.less_or_equal(&MaybeNull));
+        assert!(NotNull.less_or_equal(&Top));
+        assert!(MaybeNull.less_or_equal(&Top));
+        assert!(!Null.less_or_equal(&NotNull));
+        assert!(!MaybeNull.less_or_equal(&NotNull));
+        assert!(!Top.less_or_equal(&MaybeNull));
+    }
+
+    #[test]
+    fn null_check_specialization() {
+        assert_eq!(MaybeNull.specialize_by_null_check(true).unwrap(), Null);
+        assert_eq!(MaybeNull.specialize_by_null_check(false).unwrap(), NotNull);
+        assert_eq!(Top.specialize_by_null_check(false).unwrap(), NotNull);
+        assert_eq!(Null.specialize_by_null_check(true).unwrap(), Null);
+        assert!(NotNull.specialize_by_null_check(true).is_err());
+        assert!(Null.specialize_by_null_check(false).is_err());
+    }
+}
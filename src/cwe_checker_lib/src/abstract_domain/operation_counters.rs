@@ -0,0 +1,125 @@
+//! Counters for the number of expensive operations of abstract domains.
+//!
+//! The counters help to evaluate performance-motivated changes of the abstract domains
+//! and of the analyses using them, e.g. whether a change reduces the number of merges
+//! in a fixpoint computation.
+//! Counting is disabled by default and can be enabled with [`enable_counting`],
+//! so that the counters do not slow down normal analysis runs.
+
+use crate::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Whether operations are counted.
+static COUNTING_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The counters indexed by domain and operation.
+static COUNTERS: [[AtomicU64; DomainOperation::COUNT]; CountedDomain::COUNT] =
+    [const { [const { AtomicU64::new(0) }; DomainOperation::COUNT] }; CountedDomain::COUNT];
+
+/// The abstract domains whose operations are counted.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CountedDomain {
+    /// The [`BricksDomain`](super::BricksDomain).
+    Bricks,
+    /// The [`CharacterInclusionDomain`](super::CharacterInclusionDomain).
+    CharacterInclusion,
+    /// The [`IntervalDomain`](super::IntervalDomain).
+    Interval,
+}
+
+impl CountedDomain {
+    /// The number of counted domains.
+    const COUNT: usize = 3;
+
+    /// All counted domains.
+    const ALL: [CountedDomain; CountedDomain::COUNT] = [
+        CountedDomain::Bricks,
+        CountedDomain::CharacterInclusion,
+        CountedDomain::Interval,
+    ];
+}
+
+/// The counted operations of abstract domains.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum DomainOperation {
+    /// A merge of two values.
+    Merge,
+    /// A normalization of a value.
+    Normalize,
+    /// A widening of a value, i.e. a merge that loses precision to ensure termination.
+    Widen,
+}
+
+impl DomainOperation {
+    /// The number of counted operations.
+    const COUNT: usize = 3;
+
+    /// All counted operations.
+    const ALL: [DomainOperation; DomainOperation::COUNT] = [
+        DomainOperation::Merge,
+        DomainOperation::Normalize,
+        DomainOperation::Widen,
+    ];
+}
+
+/// The number of times an operation of an abstract domain was executed.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct OperationCount {
+    /// The abstract domain.
+    pub domain: CountedDomain,
+    /// The operation.
+    pub operation: DomainOperation,
+    /// The number of executions of the operation.
+    pub count: u64,
+}
+
+/// Enable counting of abstract domain operations for the rest of the program run.
+pub fn enable_counting() {
+    COUNTING_ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Increment the counter of the given operation if counting is enabled.
+pub(crate) fn count(domain: CountedDomain, operation: DomainOperation) {
+    if COUNTING_ENABLED.load(Ordering::Relaxed) {
+        COUNTERS[domain as usize][operation as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns the counters of all operations that were executed at least once.
+pub fn get_operation_counts() -> Vec<OperationCount> {
+    let mut counts = Vec::new();
+    for domain in CountedDomain::ALL {
+        for operation in DomainOperation::ALL {
+            let count = COUNTERS[domain as usize][operation as usize].load(Ordering::Relaxed);
+            if count > 0 {
+                counts.push(OperationCount {
+                    domain,
+                    operation,
+                    count,
+                });
+            }
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting() {
+        count(CountedDomain::Bricks, DomainOperation::Widen);
+        enable_counting();
+        count(CountedDomain::Bricks, DomainOperation::Widen);
+        count(CountedDomain::Bricks, DomainOperation::Widen);
+        // Other tests may count operations concurrently.
+        let widen_count = get_operation_counts()
+            .into_iter()
+            .find(|count| {
+                (count.domain, count.operation) == (CountedDomain::Bricks, DomainOperation::Widen)
+            })
+            .unwrap();
+        assert!(widen_count.count >= 2);
+    }
+}
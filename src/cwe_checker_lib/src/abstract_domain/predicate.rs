@@ -0,0 +1,168 @@
+//! This module contains the Predicate Domain.
+//!
+//! The domain records facts that are known to hold for a value (usually a string),
+//! e.g. that the value was passed through a sanitizer function
+//! or that its length was checked against a constant.
+//! A fact is only known after a merge if it is known for both inputs,
+//! i.e. the domain represents facts that hold on all paths.
+//! The *Top* value of the domain represents that no fact is known.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::prelude::*;
+
+use super::{AbstractDomain, DomainOrder, HasTop};
+
+/// The `PredicateDomain` records the facts known about a value.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct PredicateDomain {
+    /// The names of the sanitizer functions that the value was passed through.
+    sanitizers: BTreeSet<String>,
+    /// An upper bound for the length of the value, if its length was checked against a constant.
+    length_bound: Option<u64>,
+}
+
+impl PredicateDomain {
+    /// Create a new domain representing the fact that the value was passed through the given sanitizer.
+    pub fn from_sanitizer(sanitizer: &str) -> PredicateDomain {
+        PredicateDomain {
+            sanitizers: BTreeSet::from([sanitizer.to_string()]),
+            length_bound: None,
+        }
+    }
+
+    /// Create a new domain representing the fact that the length of the value is at most `bound`.
+    pub fn from_length_bound(bound: u64) -> PredicateDomain {
+        PredicateDomain {
+            sanitizers: BTreeSet::new(),
+            length_bound: Some(bound),
+        }
+    }
+
+    /// Returns true if the value was passed through at least one sanitizer function.
+    pub fn is_sanitized(&self) -> bool {
+        !self.sanitizers.is_empty()
+    }
+
+    /// Get the names of the sanitizer functions that the value was passed through.
+    pub fn get_sanitizers(&self) -> &BTreeSet<String> {
+        &self.sanitizers
+    }
+
+    /// Get the known upper bound for the length of the value.
+    pub fn get_length_bound(&self) -> Option<u64> {
+        self.length_bound
+    }
+
+    /// Combine the facts known in `self` and `other`,
+    /// e.g. after a new fact was learned about a value.
+    pub fn intersect(&self, other: &Self) -> Self {
+        PredicateDomain {
+            sanitizers: self.sanitizers.union(&other.sanitizers).cloned().collect(),
+            length_bound: match (self.length_bound, other.length_bound) {
+                (Some(bound), Some(other_bound)) => Some(std::cmp::min(bound, other_bound)),
+                (bound, other_bound) => bound.or(other_bound),
+            },
+        }
+    }
+}
+
+impl AbstractDomain for PredicateDomain {
+    /// Keep only the facts that are known for both inputs.
+    fn merge(&self, other: &Self) -> Self {
+        PredicateDomain {
+            sanitizers: self
+                .sanitizers
+                .intersection(&other.sanitizers)
+                .cloned()
+                .collect(),
+            length_bound: match (self.length_bound, other.length_bound) {
+                (Some(bound), Some(other_bound)) => Some(std::cmp::max(bound, other_bound)),
+                _ => None,
+            },
+        }
+    }
+
+    /// Check if no fact is known.
+    fn is_top(&self) -> bool {
+        self.sanitizers.is_empty() && self.length_bound.is_none()
+    }
+}
+
+impl DomainOrder for PredicateDomain {
+    /// `self` is less or equal to `other` if all facts known in `other` are also known in `self`.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        let bound_is_less_or_equal = match (self.length_bound, other.length_bound) {
+            (_, None) => true,
+            (Some(bound), Some(other_bound)) => bound <= other_bound,
+            (None, Some(_)) => false,
+        };
+        bound_is_less_or_equal && self.sanitizers.is_superset(&other.sanitizers)
+    }
+}
+
+impl HasTop for PredicateDomain {
+    /// Return a *Top* value.
+    fn top(&self) -> Self {
+        PredicateDomain::default()
+    }
+}
+
+impl fmt::Display for PredicateDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_top() {
+            return write!(f, "Top");
+        }
+        let mut facts: Vec<String> = self
+            .sanitizers
+            .iter()
+            .map(|sanitizer| format!("sanitized by {sanitizer}"))
+            .collect();
+        if let Some(bound) = self.length_bound {
+            facts.push(format!("length <= {bound}"));
+        }
+        write!(f, "{}", facts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merging_and_intersecting() {
+        let sanitized = PredicateDomain::from_sanitizer("escape_shell_arg");
+        let bounded = PredicateDomain::from_length_bound(64);
+        let both = sanitized.intersect(&bounded);
+        assert!(both.is_sanitized());
+        assert_eq!(both.get_length_bound(), Some(64));
+        assert_eq!(both.merge(&sanitized), sanitized);
+        assert!(sanitized.merge(&bounded).is_top());
+        assert_eq!(
+            bounded.merge(&PredicateDomain::from_length_bound(32)),
+            bounded
+        );
+        assert_eq!(
+            bounded
+                .intersect(&PredicateDomain::from_length_bound(32))
+                .get_length_bound(),
+            Some(32)
+        );
+        assert_eq!(
+            format!("{both}"),
+            "sanitized by escape_shell_arg, length <= 64"
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        let sanitized = PredicateDomain::from_sanitizer("escape_shell_arg");
+        let bounded = PredicateDomain::from_length_bound(64);
+        assert!(sanitized.less_or_equal(&sanitized.top()));
+        assert!(!sanitized.top().less_or_equal(&sanitized));
+        assert!(!sanitized.less_or_equal(&bounded));
+        assert!(PredicateDomain::from_length_bound(32).less_or_equal(&bounded));
+        assert!(sanitized.intersect(&bounded).less_or_equal(&bounded));
+    }
+}
@@ -1,5 +1,12 @@
+use super::DomainOrder;
+use crate::prelude::*;
+use std::collections::BTreeSet;
+
 /// A set of functions that all abstract string domains should implement.
-pub trait DomainInsertion {
+///
+/// String domains also have to provide their partial order,
+/// so that fixpoint computations over string states can detect stabilization.
+pub trait DomainInsertion: DomainOrder + Sized {
     /// Inserts a string domain at a certain position if order is considered.
     fn append_string_domain(&self, string_domain: &Self) -> Self;
     /// Creates a string domain with characters that usually appear in an integer value.
@@ -14,4 +21,32 @@ pub trait DomainInsertion {
     fn create_top_value_domain() -> Self;
     /// Creates an empty string domain.
     fn create_empty_string_domain() -> Self;
+    /// Returns a string domain representing the prefixes of at most `length` bytes
+    /// of the strings represented by `self`.
+    ///
+    /// Lengths are measured in bytes to match the lengths of memory accesses.
+    fn truncate(&self, length: usize) -> Self;
+    /// Returns a lower bound for the length in bytes of the strings represented by `self`.
+    fn get_min_length(&self) -> usize;
+    /// Returns an upper bound for the length in bytes of the strings represented by `self`
+    /// or `None` if the length is unbounded.
+    fn get_max_length(&self) -> Option<usize>;
+    /// Returns a string domain that contains all strings represented by both `self` and `other`.
+    /// The result may overapproximate the exact intersection.
+    ///
+    /// Returns an error if the intersection is known to be empty.
+    fn intersect(&self, other: &Self) -> Result<Self, Error>;
+    /// Merges two string domains.
+    /// Parts of the result that would represent more than `max_cardinality` different strings
+    /// may be overapproximated by *Top* to keep the size of the domain bounded.
+    fn merge_with_cardinality_limit(&self, other: &Self, max_cardinality: usize) -> Self;
+}
+
+/// A trait for string domains that can produce examples of the concrete strings they represent.
+pub trait TryToConcrete {
+    /// Returns a set of at most `max_count` concrete strings that are represented by `self`.
+    ///
+    /// Returns an error if no such set can be computed,
+    /// e.g. because `self` represents too many strings or contains unknown parts.
+    fn try_to_concrete(&self, max_count: usize) -> Result<BTreeSet<String>, Error>;
 }
@@ -15,7 +15,7 @@ use super::forward_interprocedural_fixpoint;
 use super::graph::*;
 use super::interprocedural_fixpoint_generic::*;
 use crate::intermediate_representation::*;
-use petgraph::graph::EdgeIndex;
+use petgraph::graph::{EdgeIndex, NodeIndex};
 use std::marker::PhantomData;
 
 /// The context for an backward interprocedural fixpoint computation.
@@ -141,6 +141,11 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
     ) -> Option<Self::NodeValue> {
         let graph = self.context.get_graph();
         let (start_node, end_node) = graph.edge_endpoints(edge).unwrap();
+        let _span = tracing::trace_span!(
+            "function",
+            function = %graph[start_node].get_function().term.name
+        )
+        .entered();
 
         match graph.edge_weight(edge).unwrap() {
             // Added rev() function to iterator to iterate backwards over the definitions
@@ -153,7 +158,7 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
                 });
                 end_val.map(NodeValue::Value)
             }
-            Edge::ReturnCombine(_) => {
+            Edge::ReturnCombine(_) | Edge::ExceptionHandler(_) => {
                 Some(Self::NodeValue::Value(node_value.unwrap_value().clone()))
             }
             // The Call Edge value is added to the CallSourceCombinator.
@@ -225,6 +230,12 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
                 .map(NodeValue::Value),
         }
     }
+
+    /// Isolate all nodes of the function containing the node,
+    /// so that the function is skipped if its analysis fails.
+    fn get_nodes_to_isolate(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        get_nodes_of_same_function(self.get_graph(), node)
+    }
 }
 
 /// This struct is a wrapper to create a general fixpoint context out of an interprocedural fixpoint context.
@@ -1,4 +1,9 @@
 //! Generate call graphs out of a program term.
+//!
+//! The call graphs in this module only contain internal functions and direct calls,
+//! which makes them suitable as graphs for fixpoint computations.
+//! For a whole-program call graph that also contains external functions and resolved indirect calls
+//! and that supports reachability and dominator queries see [`CallGraph`](crate::analysis::graph::call::CallGraph).
 
 use crate::intermediate_representation::*;
 use petgraph::{graph::DiGraph, graph::NodeIndex, visit::EdgeRef};
@@ -0,0 +1,166 @@
+//! A concrete interpreter for `Def` terms and expressions of the intermediate representation.
+//!
+//! The interpreter executes `Def` terms on concrete register values and a concrete byte-addressed memory.
+//! It is the reference semantics for differential tests of the analyses:
+//! A program is executed by the interpreter on concrete inputs sampled from an abstract state
+//! and by the transfer functions of an analysis on the abstract state.
+//! The abstract results are sound if they represent the concrete results.
+//!
+//! Expressions are evaluated with the operations of [`BitvectorExtended`],
+//! i.e. with the same semantics that the analyses use for constant folding.
+//! Reading an uninitialized register or memory byte or evaluating an `Unknown` expression is an error,
+//! since the result of the execution would depend on values that the inputs do not determine.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+/// The concrete state of a program, i.e. the values of registers and memory.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ConcreteState {
+    /// The values of the initialized registers.
+    registers: HashMap<Variable, Bitvector>,
+    /// The values of the initialized memory bytes, indexed by their address.
+    memory: BTreeMap<u64, u8>,
+    /// The endianness of memory accesses.
+    is_little_endian: bool,
+}
+
+impl ConcreteState {
+    /// Create a new state where no register and no memory byte is initialized.
+    pub fn new(is_little_endian: bool) -> ConcreteState {
+        ConcreteState {
+            registers: HashMap::new(),
+            memory: BTreeMap::new(),
+            is_little_endian,
+        }
+    }
+
+    /// Get the value of a register.
+    /// Returns an error if the register is not initialized.
+    pub fn get_register(&self, var: &Variable) -> Result<&Bitvector, Error> {
+        self.registers
+            .get(var)
+            .ok_or_else(|| anyhow!("Register {} is not initialized", var.name))
+    }
+
+    /// Set the value of a register.
+    pub fn set_register(&mut self, var: &Variable, value: Bitvector) {
+        self.registers.insert(var.clone(), value);
+    }
+
+    /// Evaluate the expression in the current state.
+    pub fn eval(&self, expression: &Expression) -> Result<Bitvector, Error> {
+        use Expression::*;
+        match expression {
+            Var(var) => self.get_register(var).cloned(),
+            Const(bitvector) => Ok(bitvector.clone()),
+            BinOp { op, lhs, rhs } => self.eval(lhs)?.bin_op(*op, &self.eval(rhs)?),
+            UnOp { op, arg } => self.eval(arg)?.un_op(*op),
+            Cast { op, size, arg } => self.eval(arg)?.cast(*op, *size),
+            Unknown { description, .. } => Err(anyhow!(
+                "Unknown expression cannot be evaluated: {description}"
+            )),
+            Subpiece {
+                low_byte,
+                size,
+                arg,
+            } => Ok(self.eval(arg)?.subpiece(*low_byte, *size)),
+        }
+    }
+
+    /// Evaluate the address expression to a memory address.
+    fn eval_address(&self, address: &Expression) -> Result<u64, Error> {
+        self.eval(address)?
+            .try_to_u64()
+            .map_err(|_| anyhow!("Address is not a valid 64-bit value"))
+    }
+
+    /// Execute the `Def` term.
+    ///
+    /// If the execution fails, the state is not modified.
+    pub fn handle_def(&mut self, def: &Def) -> Result<(), Error> {
+        match def {
+            Def::Assign { var, value } => {
+                let value = self.eval(value)?;
+                self.set_register(var, value);
+            }
+            Def::Load { var, address } => {
+                let value = self.load(self.eval_address(address)?, var.size)?;
+                self.set_register(var, value);
+            }
+            Def::Store { address, value } => {
+                let address = self.eval_address(address)?;
+                let value = self.eval(value)?;
+                self.store(address, &value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a value of the given size from memory.
+    /// Returns an error if one of the bytes is not initialized.
+    pub fn load(&self, address: u64, size: ByteSize) -> Result<Bitvector, Error> {
+        let mut bytes = (0..u64::from(size))
+            .map(|index| {
+                let byte_address = address.wrapping_add(index);
+                self.memory
+                    .get(&byte_address)
+                    .copied()
+                    .ok_or_else(|| anyhow!("Memory at {byte_address:#x} is not initialized"))
+            })
+            .collect::<Result<Vec<u8>, Error>>()?;
+        if self.is_little_endian {
+            bytes.reverse();
+        }
+        let mut bytes = bytes.into_iter();
+        let mut value = Bitvector::from_u8(bytes.next().ok_or_else(|| anyhow!("Empty load"))?);
+        for byte in bytes {
+            value = value.bin_op(BinOpType::Piece, &Bitvector::from_u8(byte))?;
+        }
+        Ok(value)
+    }
+
+    /// Write a value to memory.
+    pub fn store(&mut self, address: u64, value: &Bitvector) {
+        let size = u64::from(value.bytesize());
+        for index in 0..size {
+            let byte_index = if self.is_little_endian {
+                index
+            } else {
+                size - 1 - index
+            };
+            let byte = value
+                .subpiece(ByteSize::new(byte_index), ByteSize::new(1))
+                .try_to_u8()
+                .unwrap();
+            self.memory.insert(address.wrapping_add(index), byte);
+        }
+    }
+
+    /// Read the NUL-terminated string starting at the given address.
+    /// Returns an error if a byte before the terminating NUL byte is not initialized.
+    ///
+    /// Bytes that are not valid UTF-8 are replaced by the Unicode replacement character.
+    pub fn read_string(&self, address: u64) -> Result<String, Error> {
+        let mut bytes = Vec::new();
+        for byte_address in address.. {
+            match self.memory.get(&byte_address) {
+                Some(0) => break,
+                Some(byte) => bytes.push(*byte),
+                None => return Err(anyhow!("String at {address:#x} is not terminated")),
+            }
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Write the string and a terminating NUL byte to memory.
+    pub fn write_string(&mut self, address: u64, string: &str) {
+        for (index, byte) in string.bytes().chain([0]).enumerate() {
+            self.memory.insert(address.wrapping_add(index as u64), byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,289 @@
+use super::*;
+use crate::abstract_domain::{
+    AbstractIdentifier, BricksDomain, CharacterAlphabet, DataDomain, Interval, IntervalDomain,
+    TryToInterval,
+};
+use crate::analysis::pointer_inference::State as PiState;
+use crate::analysis::string_abstraction::state::State as StringState;
+use crate::{def, expr, variable};
+use proptest::prelude::*;
+use std::collections::{BTreeSet, HashSet};
+
+/// The concrete address of the stack frame base in the differential tests.
+const STACK_BASE: u64 = 0x1000_0000;
+
+/// The general purpose registers of the generated programs.
+const REGISTERS: [&str; 4] = ["r0", "r1", "r2", "r3"];
+
+fn register(index: usize) -> Variable {
+    Variable {
+        name: REGISTERS[index].to_string(),
+        size: ByteSize::new(4),
+        is_temp: false,
+    }
+}
+
+/// The lowest byte of the expression.
+fn low_byte(arg: Expression) -> Expression {
+    Expression::Subpiece {
+        low_byte: ByteSize::new(0),
+        size: ByteSize::new(1),
+        arg: Box::new(arg),
+    }
+}
+
+/// The expression extended to 4 bytes.
+fn extend(op: CastOpType, arg: Expression) -> Expression {
+    Expression::Cast {
+        op,
+        size: ByteSize::new(4),
+        arg: Box::new(arg),
+    }
+}
+
+#[test]
+fn expression_evaluation() {
+    let mut state = ConcreteState::new(true);
+    state.set_register(&variable!("RAX:8"), Bitvector::from_i64(5));
+    assert_eq!(
+        state.eval(&expr!("RAX:8 + 0x3:8")).unwrap(),
+        Bitvector::from_i64(8)
+    );
+    state.set_register(&variable!("RBX:8"), Bitvector::from_i64(-2));
+    let expression = extend(CastOpType::IntSExt, low_byte(expr!("RBX:8")));
+    assert_eq!(state.eval(&expression).unwrap(), Bitvector::from_i32(-2));
+    // Uninitialized registers and unknown values cannot be evaluated.
+    assert!(state.eval(&expr!("RCX:8")).is_err());
+    assert!(state
+        .eval(&Expression::Unknown {
+            description: "unknown".to_string(),
+            size: ByteSize::new(8),
+        })
+        .is_err());
+}
+
+#[test]
+fn memory_accesses() {
+    for is_little_endian in [true, false] {
+        let mut state = ConcreteState::new(is_little_endian);
+        state.set_register(&variable!("sp:4"), Bitvector::from_u32(0x100));
+        state
+            .handle_def(&def!["store: Store at sp:4 + 0x4:4 := 0x6e69622f:4"].term)
+            .unwrap();
+        state
+            .handle_def(&def!["load: r0:2 := Load from sp:4 + 0x4:4"].term)
+            .unwrap();
+        let expected = if is_little_endian { 0x622f } else { 0x6e69 };
+        assert_eq!(
+            *state.get_register(&variable!("r0:2")).unwrap(),
+            Bitvector::from_u16(expected)
+        );
+        // A failed execution does not modify the state.
+        let state_before = state.clone();
+        assert!(state
+            .handle_def(&def!["load: r0:2 := Load from sp:4 + 0x7:4"].term)
+            .is_err());
+        assert_eq!(state, state_before);
+    }
+
+    let mut state = ConcreteState::new(true);
+    state.store(0x100, &Bitvector::from_u32(0x6e69622f));
+    assert!(state.read_string(0x100).is_err());
+    state.store(0x104, &Bitvector::from_u32(0x68732f));
+    assert_eq!(state.read_string(0x100).unwrap(), "/bin/sh");
+    state.write_string(0x102, "sh");
+    assert_eq!(state.read_string(0x100).unwrap(), "/bsh");
+}
+
+/// An expression over the registers of the generated programs that evaluates to a 4-byte value.
+fn expression() -> impl Strategy<Value = Expression> {
+    let leaf = prop_oneof![
+        (0..REGISTERS.len()).prop_map(|index| Expression::Var(register(index))),
+        (-64i32..64).prop_map(|value| Expression::Const(Bitvector::from_i32(value))),
+    ];
+    leaf.prop_recursive(3, 16, 2, |inner| {
+        prop_oneof![
+            (
+                prop::sample::select(vec![
+                    BinOpType::IntAdd,
+                    BinOpType::IntSub,
+                    BinOpType::IntMult,
+                    BinOpType::IntAnd,
+                    BinOpType::IntOr,
+                    BinOpType::IntXOr,
+                ]),
+                inner.clone(),
+                inner.clone()
+            )
+                .prop_map(|(op, lhs, rhs)| Expression::BinOp {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                }),
+            (
+                prop::sample::select(vec![UnOpType::Int2Comp, UnOpType::IntNegate]),
+                inner.clone()
+            )
+                .prop_map(|(op, arg)| Expression::UnOp {
+                    op,
+                    arg: Box::new(arg),
+                }),
+            (
+                prop::sample::select(vec![CastOpType::IntZExt, CastOpType::IntSExt]),
+                inner.clone()
+            )
+                .prop_map(|(op, arg)| extend(op, low_byte(arg))),
+            (inner.clone(), inner).prop_map(|(lhs, rhs)| extend(
+                CastOpType::IntZExt,
+                Expression::BinOp {
+                    op: BinOpType::IntSLess,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                }
+            )),
+        ]
+    })
+}
+
+/// A `Def` term of a generated program.
+/// Memory accesses read and write the stack frame at (possibly overlapping) offsets.
+fn def_term() -> impl Strategy<Value = Def> {
+    let stack_address = (-16i32..=-4).prop_map(|offset| expr!("sp:4").plus_const(offset.into()));
+    prop_oneof![
+        (0..REGISTERS.len(), expression()).prop_map(|(index, value)| Def::Assign {
+            var: register(index),
+            value,
+        }),
+        (0..REGISTERS.len(), stack_address.clone()).prop_map(|(index, address)| Def::Load {
+            var: register(index),
+            address,
+        }),
+        (stack_address, expression()).prop_map(|(address, value)| Def::Store { address, value }),
+    ]
+}
+
+/// Check whether the abstract value represents the concrete value.
+/// Offsets relative to the stack frame are relative to [`STACK_BASE`].
+fn represents(
+    data: &DataDomain<IntervalDomain>,
+    concrete: &Bitvector,
+    stack_id: &AbstractIdentifier,
+) -> bool {
+    let contains = |interval: &IntervalDomain, value: &Bitvector| match interval.try_to_interval() {
+        Ok(interval) => interval.contains(value),
+        Err(_) => true,
+    };
+    if data.contains_top() {
+        return true;
+    }
+    if data
+        .get_absolute_value()
+        .is_some_and(|absolute| contains(absolute, concrete))
+    {
+        return true;
+    }
+    let stack_offset = concrete.bin_op(BinOpType::IntSub, &Bitvector::from_u32(STACK_BASE as u32));
+    data.get_relative_values().iter().any(|(id, offset)| {
+        id == stack_id
+            && stack_offset
+                .as_ref()
+                .is_ok_and(|value| contains(offset, value))
+    })
+}
+
+proptest! {
+    #[test]
+    fn pointer_inference_transfer_functions_are_sound(
+        initial_values in prop::collection::vec((-100i32..100, 0i32..8, 0i32..8), REGISTERS.len()),
+        program in prop::collection::vec(def_term(), 1..12),
+    ) {
+        let global_memory = RuntimeMemoryImage::empty(true);
+        let stack_register = variable!("sp:4");
+        let mut pi_state = PiState::new(&stack_register, Tid::new("func"), BTreeSet::new());
+        let mut concrete_state = ConcreteState::new(true);
+        concrete_state.set_register(&stack_register, Bitvector::from_u32(STACK_BASE as u32));
+        // The abstract state knows nothing about the stack contents,
+        // so the concrete stack frame may contain arbitrary initial values.
+        concrete_state.store(STACK_BASE - 32, &Bitvector::zero(apint::BitWidth::from(ByteSize::new(32))));
+        for (index, (start, length, sample)) in initial_values.into_iter().enumerate() {
+            let interval = Interval::new(
+                Bitvector::from_i32(start),
+                Bitvector::from_i32(start + length),
+                1,
+            );
+            pi_state.set_register(&register(index), IntervalDomain::from(interval).into());
+            concrete_state.set_register(&register(index), Bitvector::from_i32(start + sample.min(length)));
+        }
+
+        for def in program.iter() {
+            concrete_state.handle_def(def).unwrap();
+            match def {
+                Def::Assign { var, value } => pi_state.handle_register_assign(var, value),
+                Def::Load { var, address } => {
+                    let _ = pi_state.handle_load(var, address, &global_memory);
+                }
+                Def::Store { address, value } => {
+                    let _ = pi_state.handle_store(address, value, &global_memory);
+                }
+            }
+        }
+
+        for index in 0..REGISTERS.len() {
+            let var = register(index);
+            let concrete = concrete_state.get_register(&var).unwrap();
+            let abstract_value = pi_state.get_register(&var);
+            prop_assert!(
+                represents(&abstract_value, concrete, &pi_state.stack_id),
+                "{}: {concrete:?} not in {abstract_value:?}", var.name
+            );
+        }
+        for offset in -16i64..=-4 {
+            let address = expr!("sp:4").plus_const(offset);
+            let concrete = concrete_state.load(STACK_BASE.wrapping_add_signed(offset), ByteSize::new(4)).unwrap();
+            let abstract_value = pi_state.load_value(&address, ByteSize::new(4), &global_memory).unwrap();
+            prop_assert!(
+                represents(&abstract_value, &concrete, &pi_state.stack_id),
+                "stack[{offset}]: {concrete:?} not in {abstract_value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn immediate_stack_strings_are_sound(
+        stores in prop::collection::vec(
+            (0i64..6, prop::collection::vec(prop::sample::select(b"ab/\0".to_vec()), 4)),
+            1..8,
+        ),
+    ) {
+        let global_memory = RuntimeMemoryImage::empty(true);
+        let mut string_state = StringState::<BricksDomain>::mock_with_default_pi_state(Sub::mock("func"));
+        let mut concrete_state = ConcreteState::new(true);
+        concrete_state.set_register(&variable!("sp:4"), Bitvector::from_u32(STACK_BASE as u32));
+
+        for (slot, bytes) in stores {
+            let address = expr!("sp:4").plus_const(8 + 4 * slot);
+            let value = Expression::Const(Bitvector::from_u32(u32::from_le_bytes(bytes.try_into().unwrap())));
+            concrete_state
+                .handle_def(&Def::Store { address: address.clone(), value: value.clone() })
+                .unwrap();
+            string_state.handle_store(
+                &address,
+                &value,
+                &global_memory,
+                &HashSet::new(),
+                CharacterAlphabet::Utf8,
+            );
+        }
+
+        for (offset, string_domain) in string_state.get_stack_offset_to_string_map() {
+            // Strings whose terminating NUL byte is not written are not fully determined.
+            let Ok(concrete) = concrete_state.read_string(STACK_BASE + *offset as u64) else {
+                continue;
+            };
+            prop_assert!(
+                string_domain.contains_string(&concrete),
+                "stack[{offset}]: {concrete:?} not in {string_domain}"
+            );
+        }
+    }
+}
@@ -0,0 +1,449 @@
+//! Def-use chains for the functions of a program.
+//!
+//! For each use of a location (a register or memory) by a `Def` or `Jmp` term,
+//! the def-use chains contain the definitions of the location that may reach the use.
+//! Conversely, they contain the uses reached by each definition.
+//! Analyses can use the chains to follow the flow of values directly
+//! instead of propagating states through all terms of a function.
+//!
+//! The chains are computed by a reaching definitions analysis on the blocks of a function.
+//! The following approximations are used:
+//! - Memory is treated as a single location.
+//!   Stores and calls add a definition of memory without removing the other reaching definitions.
+//! - A call uses the parameter registers and defines the return registers of the calling convention.
+//!   For calls to extern symbols the parameters and return values of the symbol are used instead.
+//! - A `Return` instruction uses the return registers of the calling convention.
+//! - Values that are not defined inside the function are defined by the function entry.
+
+use crate::intermediate_representation::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A location whose value is tracked by the def-use chains.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub enum Location {
+    /// A register.
+    Var(Variable),
+    /// The whole memory.
+    Memory,
+}
+
+/// The program point at which a value is defined.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub enum DefSite {
+    /// The value is an input value of the function.
+    Entry,
+    /// The value is defined by the `Def` or `Jmp` term with the given TID.
+    Term(Tid),
+}
+
+/// Maps locations to the definitions of the location that may reach the current program point.
+///
+/// Locations missing in the map are only defined by the function entry.
+type ReachingDefs = BTreeMap<Location, BTreeSet<DefSite>>;
+
+/// The locations used and the locations defined by a term.
+pub type UsesAndDefs = (Vec<Location>, Vec<Location>);
+
+/// The def-use chains of a function.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct DefUseChains {
+    /// Maps the TIDs of terms to the reaching definitions of the locations that they use.
+    definitions: HashMap<Tid, BTreeMap<Location, BTreeSet<DefSite>>>,
+    /// Maps definitions to the terms using them together with the used location.
+    uses: HashMap<DefSite, BTreeSet<(Location, Tid)>>,
+}
+
+impl DefUseChains {
+    /// Compute the def-use chains of the given function.
+    ///
+    /// Terms in blocks that are not reachable from the first block of the function are ignored.
+    pub fn new(project: &Project, sub: &Term<Sub>) -> DefUseChains {
+        let mut chains = DefUseChains::default();
+        let block_states = compute_reaching_defs_at_block_starts(project, sub);
+        for block in sub.term.blocks.iter() {
+            let Some(mut state) = block_states.get(&block.tid).cloned() else {
+                continue;
+            };
+            for (tid, (uses, defs)) in get_uses_and_defs(project, sub, block) {
+                let term_definitions = chains.definitions.entry(tid.clone()).or_default();
+                for location in uses {
+                    let definitions = state
+                        .get(&location)
+                        .cloned()
+                        .unwrap_or_else(|| BTreeSet::from([DefSite::Entry]));
+                    for definition in definitions.iter() {
+                        chains
+                            .uses
+                            .entry(definition.clone())
+                            .or_default()
+                            .insert((location.clone(), tid.clone()));
+                    }
+                    term_definitions
+                        .entry(location)
+                        .or_default()
+                        .extend(definitions);
+                }
+                update_reaching_defs(&mut state, tid, &defs);
+            }
+        }
+
+        chains
+    }
+
+    /// Returns true if the chains contain the given term,
+    /// i.e. if the term is contained in a reachable block of the function.
+    pub fn contains_term(&self, tid: &Tid) -> bool {
+        self.definitions.contains_key(tid)
+    }
+
+    /// Get the TIDs of all terms contained in the chains.
+    pub fn terms(&self) -> impl Iterator<Item = &Tid> {
+        self.definitions.keys()
+    }
+
+    /// Get the reaching definitions of all locations used by the given term.
+    pub fn get_definitions(&self, tid: &Tid) -> Option<&BTreeMap<Location, BTreeSet<DefSite>>> {
+        self.definitions.get(tid)
+    }
+
+    /// Get the definitions of the location that reach its use by the given term.
+    ///
+    /// Returns `None` if the term does not use the location.
+    pub fn get_definitions_of_location(
+        &self,
+        tid: &Tid,
+        location: &Location,
+    ) -> Option<&BTreeSet<DefSite>> {
+        self.definitions.get(tid)?.get(location)
+    }
+
+    /// Get all uses reached by the given definition together with the used location.
+    pub fn get_uses(&self, definition: &DefSite) -> impl Iterator<Item = &(Location, Tid)> {
+        self.uses.get(definition).into_iter().flatten()
+    }
+}
+
+/// Compute the def-use chains of all functions of the program.
+pub fn compute_def_use_chains(project: &Project) -> BTreeMap<Tid, DefUseChains> {
+    project
+        .program
+        .term
+        .subs
+        .values()
+        .map(|sub| (sub.tid.clone(), DefUseChains::new(project, sub)))
+        .collect()
+}
+
+/// Compute the reaching definitions at the start of each reachable block of the function.
+fn compute_reaching_defs_at_block_starts<'a>(
+    project: &Project,
+    sub: &'a Term<Sub>,
+) -> HashMap<&'a Tid, ReachingDefs> {
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    let mut block_states: HashMap<&Tid, ReachingDefs> = HashMap::new();
+    let Some(entry_block) = sub.term.blocks.first() else {
+        return block_states;
+    };
+    block_states.insert(&entry_block.tid, ReachingDefs::new());
+    let mut worklist = vec![&entry_block.tid];
+    while let Some(block_tid) = worklist.pop() {
+        let mut state = block_states[block_tid].clone();
+        let block = blocks[block_tid];
+        for (tid, (_, defs)) in get_uses_and_defs(project, sub, block) {
+            update_reaching_defs(&mut state, tid, &defs);
+        }
+        for successor in get_successors(block) {
+            let Some((successor, _)) = blocks.get_key_value(successor) else {
+                continue;
+            };
+            let merged_state = match block_states.get(successor) {
+                None => state.clone(),
+                Some(old_state) => merge_reaching_defs(old_state, &state),
+            };
+            if block_states.get(successor) != Some(&merged_state) {
+                block_states.insert(successor, merged_state);
+                worklist.push(successor);
+            }
+        }
+    }
+
+    block_states
+}
+
+/// Return the locations used and defined by each `Def` and `Jmp` term of the block in order.
+pub fn get_uses_and_defs<'a>(
+    project: &Project,
+    sub: &Term<Sub>,
+    block: &'a Term<Blk>,
+) -> Vec<(&'a Tid, UsesAndDefs)> {
+    let defs = block
+        .term
+        .defs
+        .iter()
+        .map(|def| (&def.tid, get_def_uses_and_defs(&def.term)));
+    let jmps = block
+        .term
+        .jmps
+        .iter()
+        .map(|jmp| (&jmp.tid, get_jmp_uses_and_defs(project, sub, &jmp.term)));
+
+    defs.chain(jmps).collect()
+}
+
+/// Return the locations used and defined by the `Def`.
+fn get_def_uses_and_defs(def: &Def) -> UsesAndDefs {
+    match def {
+        Def::Assign { var, value } => {
+            (get_input_locations(value), vec![Location::Var(var.clone())])
+        }
+        Def::Load { var, address } => {
+            let mut uses = get_input_locations(address);
+            uses.push(Location::Memory);
+            (uses, vec![Location::Var(var.clone())])
+        }
+        Def::Store { address, value } => {
+            let mut uses = get_input_locations(address);
+            uses.extend(get_input_locations(value));
+            (uses, vec![Location::Memory])
+        }
+    }
+}
+
+/// Return the locations used and defined by the `Jmp`.
+fn get_jmp_uses_and_defs(project: &Project, sub: &Term<Sub>, jmp: &Jmp) -> UsesAndDefs {
+    let calling_convention = project.get_specific_calling_convention(&sub.term.calling_convention);
+    match jmp {
+        Jmp::Branch(_) => (Vec::new(), Vec::new()),
+        Jmp::BranchInd(target) => (get_input_locations(target), Vec::new()),
+        Jmp::CBranch { condition, .. } => (get_input_locations(condition), Vec::new()),
+        Jmp::Return(target) => {
+            let mut uses = get_input_locations(target);
+            if let Some(cconv) = calling_convention {
+                uses.extend(
+                    cconv
+                        .integer_return_register
+                        .iter()
+                        .map(|var| Location::Var(var.clone())),
+                );
+                uses.extend(
+                    cconv
+                        .float_return_register
+                        .iter()
+                        .flat_map(get_input_locations),
+                );
+            }
+            (uses, Vec::new())
+        }
+        Jmp::Call { target, .. } => {
+            if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                let mut uses: Vec<Location> = symbol
+                    .parameters
+                    .iter()
+                    .flat_map(get_arg_locations)
+                    .collect();
+                uses.push(Location::Memory);
+                let mut defs: Vec<Location> = symbol
+                    .return_values
+                    .iter()
+                    .flat_map(get_arg_locations)
+                    .collect();
+                defs.push(Location::Memory);
+                (uses, defs)
+            } else {
+                get_call_uses_and_defs(calling_convention, Vec::new())
+            }
+        }
+        Jmp::CallInd { target, .. } => {
+            get_call_uses_and_defs(calling_convention, get_input_locations(target))
+        }
+        Jmp::CallOther { .. } => (Vec::new(), vec![Location::Memory]),
+    }
+}
+
+/// Return the locations used and defined by a call to an internal function
+/// according to the given calling convention.
+fn get_call_uses_and_defs(
+    calling_convention: Option<&CallingConvention>,
+    mut uses: Vec<Location>,
+) -> UsesAndDefs {
+    let mut defs = vec![Location::Memory];
+    uses.push(Location::Memory);
+    if let Some(cconv) = calling_convention {
+        uses.extend(
+            cconv
+                .get_all_parameter_register()
+                .into_iter()
+                .map(|var| Location::Var(var.clone())),
+        );
+        defs.extend(
+            cconv
+                .integer_return_register
+                .iter()
+                .map(|var| Location::Var(var.clone())),
+        );
+        defs.extend(
+            cconv
+                .float_return_register
+                .iter()
+                .flat_map(get_input_locations),
+        );
+    }
+
+    (uses, defs)
+}
+
+/// Return the registers used by the expression.
+fn get_input_locations(expression: &Expression) -> Vec<Location> {
+    expression
+        .input_vars()
+        .into_iter()
+        .map(|var| Location::Var(var.clone()))
+        .collect()
+}
+
+/// Return the registers that an argument is passed in
+/// or that are used to compute its address on the stack.
+fn get_arg_locations(arg: &Arg) -> Vec<Location> {
+    match arg {
+        Arg::Register { expr, .. } => get_input_locations(expr),
+        Arg::Stack { address, .. } => {
+            let mut locations = get_input_locations(address);
+            locations.push(Location::Memory);
+            locations
+        }
+    }
+}
+
+/// Update the reaching definitions with the locations defined by the term with the given TID.
+///
+/// Definitions of registers replace all other reaching definitions of the register,
+/// while memory definitions are added to the reaching memory definitions.
+fn update_reaching_defs(state: &mut ReachingDefs, tid: &Tid, defs: &[Location]) {
+    for location in defs {
+        match location {
+            Location::Var(_) => {
+                state.insert(
+                    location.clone(),
+                    BTreeSet::from([DefSite::Term(tid.clone())]),
+                );
+            }
+            Location::Memory => {
+                state
+                    .entry(Location::Memory)
+                    .or_insert_with(|| BTreeSet::from([DefSite::Entry]))
+                    .insert(DefSite::Term(tid.clone()));
+            }
+        }
+    }
+}
+
+/// Merge the reaching definitions of two predecessors of a block.
+///
+/// If a location is only defined in one of the states,
+/// then the function entry is a reaching definition for the location in the other state.
+fn merge_reaching_defs(state: &ReachingDefs, other: &ReachingDefs) -> ReachingDefs {
+    let mut merged_state = ReachingDefs::new();
+    for location in state.keys().chain(other.keys()) {
+        let mut definitions = BTreeSet::new();
+        for side in [state, other] {
+            match side.get(location) {
+                Some(side_definitions) => definitions.extend(side_definitions.iter().cloned()),
+                None => {
+                    definitions.insert(DefSite::Entry);
+                }
+            }
+        }
+        merged_state.insert(location.clone(), definitions);
+    }
+
+    merged_state
+}
+
+/// Return the TIDs of the blocks that control flow may reach directly after the given block
+/// without leaving the function.
+fn get_successors(block: &Term<Blk>) -> Vec<&Tid> {
+    let mut successors = Vec::new();
+    for jmp in block.term.jmps.iter() {
+        match &jmp.term {
+            Jmp::Branch(target) | Jmp::CBranch { target, .. } => successors.push(target),
+            Jmp::BranchInd(_) => {
+                successors.extend(block.term.ind_jump_targets().into_iter().flatten())
+            }
+            Jmp::Call { return_, .. }
+            | Jmp::CallInd { return_, .. }
+            | Jmp::CallOther { return_, .. } => successors.extend(return_.iter()),
+            Jmp::Return(_) => (),
+        }
+    }
+
+    successors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{defs, expr, variable};
+
+    #[test]
+    fn def_use_chains() {
+        let project = Project::mock_x64();
+        let mut first_block = Blk::mock_with_tid("blk_0");
+        first_block.term.defs = defs!["def_0: RAX:8 = 0x1:8", "def_1: Store at RSP:8 := RAX:8"];
+        first_block.term.jmps = vec![
+            Term {
+                tid: Tid::new("jmp_0"),
+                term: Jmp::CBranch {
+                    target: Tid::new("blk_1"),
+                    condition: expr!("ZF:1"),
+                },
+            },
+            Jmp::branch("jmp_1", "blk_2"),
+        ];
+        let mut second_block = Blk::mock_with_tid("blk_1");
+        second_block.term.defs = defs!["def_2: RAX:8 := Load from RSP:8"];
+        second_block.term.jmps = vec![Jmp::branch("jmp_2", "blk_2")];
+        let mut third_block = Blk::mock_with_tid("blk_2");
+        third_block.term.defs = defs!["def_3: RBX:8 = RAX:8 + RCX:8"];
+        let sub = Term {
+            tid: Tid::new("func"),
+            term: Sub::new::<_, &str>("func", vec![first_block, second_block, third_block], None),
+        };
+        let chains = DefUseChains::new(&project, &sub);
+
+        let rax = Location::Var(variable!("RAX:8"));
+        let def_site = |name: &str| DefSite::Term(Tid::new(name));
+        assert_eq!(
+            chains.get_definitions_of_location(&Tid::new("def_3"), &rax),
+            Some(&BTreeSet::from([def_site("def_0"), def_site("def_2")]))
+        );
+        assert_eq!(
+            chains.get_definitions_of_location(
+                &Tid::new("def_3"),
+                &Location::Var(variable!("RCX:8"))
+            ),
+            Some(&BTreeSet::from([DefSite::Entry]))
+        );
+        assert_eq!(
+            chains.get_definitions_of_location(&Tid::new("def_2"), &Location::Memory),
+            Some(&BTreeSet::from([DefSite::Entry, def_site("def_1")]))
+        );
+        assert!(chains
+            .get_definitions_of_location(&Tid::new("def_2"), &rax)
+            .is_none());
+        let uses_of_def_0: Vec<_> = chains.get_uses(&def_site("def_0")).collect();
+        assert_eq!(
+            uses_of_def_0,
+            vec![
+                &(rax.clone(), Tid::new("def_1")),
+                &(rax.clone(), Tid::new("def_3"))
+            ]
+        );
+        assert!(chains.contains_term(&Tid::new("jmp_2")));
+        assert_eq!(chains.terms().count(), 7);
+    }
+}
@@ -0,0 +1,152 @@
+//! Explanations of why a CWE warning was generated.
+//!
+//! An [`Explanation`] lists the abstract facts that triggered a check,
+//! e.g. the abstract value of a parameter at a call,
+//! together with the merges of states at which the values involved in the facts lost precision.
+//! The merges are only known if the Pointer Inference recorded precision losses,
+//! see the `record_precision_losses` option of its [configuration](super::pointer_inference::Config).
+//!
+//! E.g. a format string parameter that is not a constant address is often not controlled by the user.
+//! Instead, the Pointer Inference merged two different constant addresses at the start of a block,
+//! which is shown as a merge in the explanation of the warning.
+//!
+//! Only merges in the function of the warning are considered
+//! and only the precision losses of register values are recorded.
+
+use super::fixpoint::PrecisionLoss;
+use super::graph::Graph;
+use super::pointer_inference::PointerInference;
+use crate::abstract_domain::{DataDomain, IntervalDomain};
+use crate::intermediate_representation::{Arg, Expression};
+use crate::prelude::*;
+use petgraph::graph::NodeIndex;
+use std::fmt;
+
+/// The reasons why a CWE warning was generated.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord, Default)]
+pub struct Explanation {
+    /// The abstract facts that triggered the check.
+    pub facts: Vec<String>,
+    /// The merges and calls at which the values involved in the facts lost precision.
+    pub merges: Vec<MergeLocation>,
+}
+
+/// A merge of states or a call at which a value lost precision.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct MergeLocation {
+    /// The TID of the block at whose start the value lost precision.
+    pub tid: String,
+    /// The address of the block.
+    pub address: String,
+    /// A description of the lost precision.
+    pub description: String,
+}
+
+impl MergeLocation {
+    /// Create the merge location of a precision loss at the given node of the graph.
+    pub fn new(graph: &Graph, node: NodeIndex, precision_loss: &PrecisionLoss) -> MergeLocation {
+        let block = graph[node].get_block();
+        MergeLocation {
+            tid: block.tid.to_string(),
+            address: block.tid.address().to_string(),
+            description: precision_loss.description.clone(),
+        }
+    }
+}
+
+impl Explanation {
+    /// Create an explanation consisting of the given fact.
+    pub fn new(fact: impl ToString) -> Explanation {
+        Explanation {
+            facts: vec![fact.to_string()],
+            merges: Vec::new(),
+        }
+    }
+
+    /// Explain the abstract value of a parameter at the given node of the Pointer Inference.
+    ///
+    /// If the parameter is passed in a register,
+    /// the merges in the function of the node at which the register lost precision are added.
+    /// Returns `None` if the Pointer Inference did not record precision losses,
+    /// i.e. if explanations were not requested.
+    pub fn explain_parameter(
+        pointer_inference: &PointerInference,
+        node: NodeIndex,
+        parameter: &Arg,
+        fact: impl ToString,
+    ) -> Option<Explanation> {
+        if !pointer_inference.records_precision_losses() {
+            return None;
+        }
+        let mut explanation = Explanation::new(fact);
+        // Only precision losses of registers are recorded,
+        // so stack parameters are explained by the fact alone.
+        if let Arg::Register {
+            expr: Expression::Var(register),
+            ..
+        } = parameter
+        {
+            let graph = pointer_inference.get_graph();
+            explanation.merges = pointer_inference
+                .get_register_precision_losses(node, register)?
+                .into_iter()
+                .map(|(merge_node, loss)| MergeLocation::new(graph, merge_node, loss))
+                .collect();
+        }
+        explanation.merges.sort();
+        explanation.merges.dedup();
+        Some(explanation)
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for fact in self.facts.iter() {
+            write!(formatter, "\n    because: {fact}")?;
+        }
+        for merge in self.merges.iter() {
+            write!(
+                formatter,
+                "\n    precision lost at {}: {}",
+                merge.tid, merge.description
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Format an abstract value for explanations, e.g. `Value: 0x1000:i64` for a constant.
+pub fn format_abstract_value(value: &DataDomain<IntervalDomain>) -> String {
+    match value.to_json_compact() {
+        serde_json::Value::String(value) => value,
+        value => value.to_string(),
+    }
+}
+
+/// Format a parameter for explanations, i.e. its register or the address of its stack slot.
+pub fn format_parameter(parameter: &Arg) -> String {
+    match parameter {
+        Arg::Register { expr, .. } => expr.to_string(),
+        Arg::Stack { address, .. } => format!("[{address}]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explanation_display() {
+        let mut explanation = Explanation::new("The format string parameter RDI has the value Top");
+        explanation.merges.push(MergeLocation {
+            tid: "blk_00101000".to_string(),
+            address: "1052672".to_string(),
+            description: "RDI is Top after merging a and b".to_string(),
+        });
+        assert_eq!(
+            explanation.to_string(),
+            "\n    because: The format string parameter RDI has the value Top\
+             \n    precision lost at blk_00101000: RDI is Top after merging a and b"
+        );
+    }
+}
@@ -34,13 +34,74 @@
 //! to match the intended starting conditions of the fixpoint computation.
 //! The `Computation` object also contains methods to actually run the fixpoint computation after the starting values are set
 //! and methods to retrieve the results of the computation.
+//!
+//! # Worklist order
+//!
+//! The worklist algorithm always updates the not yet stabilized node with the highest priority next.
+//! By default the priorities are given by [`get_reverse_post_order_priorities`]:
+//! Strongly connected components of the graph are stabilized in topological order
+//! and the nodes inside a strongly connected component are visited in reverse post-order.
+//! Thus a node is usually only updated after all of its predecessors (ignoring back edges) have been updated,
+//! which reduces the number of times that nodes inside of loops have to be revisited.
+//!
+//! # Aborting computations
+//!
+//! A running fixpoint computation can be stopped from another thread
+//! by setting the abort flag of the computation (see [`Computation::set_abort_flag`]).
+//! The flag is checked before each node update.
+//! After an abort the node values computed so far can still be retrieved,
+//! but they are only an intermediate result of the fixpoint computation.
+//! The nodes that were not yet stabilized remain on the worklist,
+//! so that the computation can be resumed after the flag was reset.
+//!
+//! # Memory limit
+//!
+//! Node values of some analyses can grow very large.
+//! To prevent running out of memory, a limit for the approximate memory usage of all node values can be set
+//! (see [`Computation::set_memory_limit`]).
+//! The memory usage is estimated through [`Context::get_approximate_memory_size`].
+//! If the limit is exceeded, the largest node values are replaced by less precise but smaller values
+//! computed by [`Context::degrade_value`] until the memory usage falls below three quarters of the limit.
+//! The nodes whose values were degraded can be retrieved through [`Computation::get_degraded_nodes`].
+//!
+//! # Failure isolation
+//!
+//! A panic in a transition function (e.g. caused by an unsupported instruction)
+//! does not abort the whole computation.
+//! Instead, the nodes returned by [`Context::get_nodes_to_isolate`] for the start node of the edge
+//! are removed from the computation:
+//! Their values are deleted and no values get propagated to or from them afterwards.
+//! The interprocedural fixpoint computations isolate all nodes of the function containing the failing node,
+//! so that the rest of the program can still be analyzed.
+//! The nodes at which transition functions panicked can be retrieved through [`Computation::get_failures`].
+//!
+//! # Precision losses
+//!
+//! If enabled through [`Computation::enable_precision_loss_recording`],
+//! the computation records for each node the parts of its value that lost precision when values were merged at the node,
+//! e.g. registers that contained a constant in one of the merged values but not in the merged value.
+//! The precision losses of merges are computed by [`Context::get_precision_losses`].
+//! Transition functions may also lose precision, e.g. for calls to unknown functions.
+//! These precision losses are computed by [`Context::get_edge_precision_losses`]
+//! and recorded at the end node of the edge.
+//!
+//! Only the first precision loss of each part of a node value is recorded,
+//! since later merges usually cannot lose more precision.
+//! In addition, the first precision loss that makes the part of the value `Top` is recorded,
+//! so that one can find the program points at which values become `Top` and the [causes](PrecisionLossCause) for it.
+//! The recorded precision losses can be retrieved through [`Computation::get_precision_losses`],
+//! e.g. to explain why an analysis result is imprecise.
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use fnv::FnvHashMap;
 use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
-use petgraph::visit::EdgeRef;
+use petgraph::visit::{DfsPostOrder, EdgeRef, VisitMap};
+use serde::{Deserialize, Serialize};
 
 /// The context of a fixpoint computation.
 ///
@@ -60,10 +121,115 @@ pub trait Context {
     /// This function describes how to merge two values
     fn merge(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> Self::NodeValue;
 
+    /// Returns `true` if `val1` is less than or equal to `val2` with respect to the partial order on node values.
+    ///
+    /// Merging a value into a node value that is greater or equal is skipped
+    /// and a node is considered stabilized if its merged value is less or equal to its old value.
+    /// The default implementation only detects equal values.
+    /// Contexts whose merge function performs widening should override it
+    /// (e.g. using [`DomainOrder`](crate::abstract_domain::DomainOrder))
+    /// to detect stabilization of values that are not structurally equal.
+    fn less_or_equal(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> bool {
+        val1 == val2
+    }
+
     /// This function describes how the value at the end node of an edge is computed from the value at the start node of the edge.
     /// The function can return None to indicate that no end value gets generated through this edge.
     /// E.g. In a control flow graph, if the edge cannot be taken for the given start value, this function should return None.
     fn update_edge(&self, value: &Self::NodeValue, edge: EdgeIndex) -> Option<Self::NodeValue>;
+
+    /// Returns the approximate number of bytes of memory occupied by the value.
+    ///
+    /// Only used if a memory limit is set for the computation.
+    /// The default implementation returns zero, i.e. the memory usage is not tracked.
+    fn get_approximate_memory_size(&self, _value: &Self::NodeValue) -> usize {
+        0
+    }
+
+    /// Returns a less precise value that occupies less memory than the given value
+    /// and that is greater or equal to it with respect to the partial order on node values.
+    ///
+    /// Used to reduce the memory usage if the memory limit of the computation is exceeded.
+    /// The default implementation returns `None`, i.e. values cannot be degraded.
+    fn degrade_value(&self, _value: &Self::NodeValue) -> Option<Self::NodeValue> {
+        None
+    }
+
+    /// Returns the nodes that are removed from the computation
+    /// if a transition function of an outgoing edge of the given node panics.
+    ///
+    /// The default implementation only removes the given node.
+    fn get_nodes_to_isolate(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        vec![node]
+    }
+
+    /// Returns the parts of the merged value `merged` of `val1` and `val2`
+    /// that are less precise than in at least one of the two values.
+    ///
+    /// Only called if the recording of precision losses is enabled for the computation.
+    /// The default implementation returns no precision losses.
+    fn get_precision_losses(
+        &self,
+        _val1: &Self::NodeValue,
+        _val2: &Self::NodeValue,
+        _merged: &Self::NodeValue,
+    ) -> Vec<PrecisionLoss> {
+        Vec::new()
+    }
+
+    /// Returns the parts of the value `new_value` computed by the transition function of the given edge
+    /// that are less precise than in the value `value` at the start node of the edge.
+    ///
+    /// Only called if the recording of precision losses is enabled for the computation.
+    /// The default implementation returns no precision losses.
+    fn get_edge_precision_losses(
+        &self,
+        _value: &Self::NodeValue,
+        _new_value: &Self::NodeValue,
+        _edge: EdgeIndex,
+    ) -> Vec<PrecisionLoss> {
+        Vec::new()
+    }
+}
+
+/// A loss of precision of a part of a node value.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct PrecisionLoss {
+    /// The part of the value that lost precision, e.g. the name of a register.
+    pub location: String,
+    /// The cause of the lost precision.
+    pub cause: PrecisionLossCause,
+    /// Set if the part of the value became `Top`.
+    pub top: bool,
+    /// A description of the lost precision, e.g. the merged values.
+    pub description: String,
+}
+
+/// The cause of a [`PrecisionLoss`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub enum PrecisionLossCause {
+    /// Two values were merged.
+    Merge,
+    /// Two values were merged and the merged value was widened.
+    Widening,
+    /// A function without known effects was called, e.g. an extern function.
+    /// Contains the name of the function or `None` if the call target is unknown.
+    UnknownCallee(Option<String>),
+}
+
+impl std::fmt::Display for PrecisionLossCause {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrecisionLossCause::Merge => write!(formatter, "merge"),
+            PrecisionLossCause::Widening => write!(formatter, "widening"),
+            PrecisionLossCause::UnknownCallee(Some(name)) => {
+                write!(formatter, "call to unknown function {name}")
+            }
+            PrecisionLossCause::UnknownCallee(None) => {
+                write!(formatter, "call to unknown target")
+            }
+        }
+    }
 }
 
 /// The computation struct contains an intermediate result of a fixpoint computation
@@ -96,18 +262,48 @@ pub struct Computation<T: Context> {
     worklist: BTreeSet<usize>,
     /// The internal map containing all known node values.
     node_values: FnvHashMap<NodeIndex, T::NodeValue>,
+    /// The number of node updates performed so far by the worklist algorithm.
+    steps: u64,
+    /// If set to `true`, the worklist algorithm stops before the next node update.
+    abort_flag: Option<Arc<AtomicBool>>,
+    /// The limit for the approximate memory usage of all node values in bytes.
+    memory_limit: Option<usize>,
+    /// The approximate memory usage of the node values.
+    /// Only tracked if a memory limit is set.
+    memory_usage: MemoryUsage,
+    /// The nodes whose values were degraded to stay below the memory limit.
+    degraded_nodes: BTreeSet<NodeIndex>,
+    /// Maps the nodes at which a transition function panicked to the panic message.
+    failures: BTreeMap<NodeIndex, String>,
+    /// The nodes that were removed from the computation because of a failure.
+    isolated_nodes: BTreeSet<NodeIndex>,
+    /// Maps nodes to the precision losses caused by merges at the node.
+    /// Only recorded if set to `Some`.
+    precision_losses: Option<BTreeMap<NodeIndex, Vec<PrecisionLoss>>>,
+}
+
+/// The approximate memory usage of the node values of a computation.
+#[derive(Default)]
+struct MemoryUsage {
+    /// The approximate memory size of each node value.
+    node_sizes: FnvHashMap<NodeIndex, usize>,
+    /// The sum of all node value sizes.
+    total: usize,
+}
+
+impl MemoryUsage {
+    /// Update the size of the value of the given node.
+    fn update(&mut self, node: NodeIndex, size: usize) {
+        let old_size = self.node_sizes.insert(node, size).unwrap_or(0);
+        self.total = self.total - old_size + size;
+    }
 }
 
 impl<T: Context> Computation<T> {
     /// Create a new fixpoint computation from a fixpoint problem, the corresponding graph
     /// and a default value for all nodes if one should exists.
     pub fn new(fp_context: T, default_value: Option<T::NodeValue>) -> Self {
-        let graph = fp_context.get_graph();
-        // order the nodes in weak topological order
-        let priority_sorted_nodes: Vec<NodeIndex> = petgraph::algo::kosaraju_scc(&graph)
-            .into_iter()
-            .flatten()
-            .collect();
+        let priority_sorted_nodes = get_reverse_post_order_priorities(fp_context.get_graph());
         Self::from_node_priority_list(fp_context, default_value, priority_sorted_nodes)
     }
 
@@ -140,6 +336,14 @@ impl<T: Context> Computation<T> {
             priority_to_node_list: priority_sorted_nodes,
             worklist,
             node_values,
+            steps: 0,
+            abort_flag: None,
+            memory_limit: None,
+            memory_usage: MemoryUsage::default(),
+            degraded_nodes: BTreeSet::new(),
+            failures: BTreeMap::new(),
+            isolated_nodes: BTreeSet::new(),
+            precision_losses: None,
         }
     }
 
@@ -149,16 +353,35 @@ impl<T: Context> Computation<T> {
     }
 
     /// Set the value of a node and mark the node as not yet stabilized.
+    ///
+    /// Nodes that were removed from the computation because of a failure are not changed.
     pub fn set_node_value(&mut self, node: NodeIndex, value: T::NodeValue) {
+        if self.isolated_nodes.contains(&node) {
+            return;
+        }
+        if self.memory_limit.is_some() {
+            let size = self.fp_context.get_approximate_memory_size(&value);
+            self.memory_usage.update(node, size);
+        }
         self.node_values.insert(node, value);
         self.worklist.insert(self.node_priority_list[node.index()]);
     }
 
     /// Merge the value at a node with some new value.
+    /// The node is only marked as not yet stabilized if the merged value is not subsumed by the old value.
     fn merge_node_value(&mut self, node: NodeIndex, value: T::NodeValue) {
         if let Some(old_value) = self.node_values.get(&node) {
+            if self.fp_context.less_or_equal(&value, old_value) {
+                return;
+            }
             let merged_value = self.fp_context.merge(&value, old_value);
-            if merged_value != *old_value {
+            if !self.fp_context.less_or_equal(&merged_value, old_value) {
+                if self.precision_losses.is_some() {
+                    let losses =
+                        self.fp_context
+                            .get_precision_losses(old_value, &value, &merged_value);
+                    self.record_precision_losses(node, losses);
+                }
                 self.set_node_value(node, merged_value);
             }
         } else {
@@ -166,6 +389,25 @@ impl<T: Context> Computation<T> {
         }
     }
 
+    /// Record the precision losses at the node if the recording of precision losses is enabled.
+    ///
+    /// For each part of the node value only the first precision loss
+    /// and the first precision loss to `Top` are recorded.
+    fn record_precision_losses(&mut self, node: NodeIndex, losses: Vec<PrecisionLoss>) {
+        let Some(precision_losses) = self.precision_losses.as_mut() else {
+            return;
+        };
+        let recorded_losses = precision_losses.entry(node).or_default();
+        for loss in losses {
+            if !recorded_losses
+                .iter()
+                .any(|recorded| recorded.location == loss.location && recorded.top == loss.top)
+            {
+                recorded_losses.push(loss);
+            }
+        }
+    }
+
     /// Compute and update the value at the end node of an edge.
     fn update_edge(&mut self, edge: EdgeIndex) {
         let (start_node, end_node) = self
@@ -175,12 +417,20 @@ impl<T: Context> Computation<T> {
             .expect("Edge not found");
         if let Some(start_val) = self.node_values.get(&start_node) {
             if let Some(new_end_val) = self.fp_context.update_edge(start_val, edge) {
+                if self.precision_losses.is_some() {
+                    let losses =
+                        self.fp_context
+                            .get_edge_precision_losses(start_val, &new_end_val, edge);
+                    self.record_precision_losses(end_node, losses);
+                }
                 self.merge_node_value(end_node, new_end_val);
             }
         }
     }
 
     /// Update all outgoing edges of a node.
+    ///
+    /// If the update of an edge panics, the nodes to isolate for the node are removed from the computation.
     fn update_node(&mut self, node: NodeIndex) {
         let edges: Vec<EdgeIndex> = self
             .fp_context
@@ -189,12 +439,142 @@ impl<T: Context> Computation<T> {
             .map(|edge_ref| edge_ref.id())
             .collect();
         for edge in edges {
-            self.update_edge(edge);
+            if let Err(panic) = catch_unwind(AssertUnwindSafe(|| self.update_edge(edge))) {
+                self.isolate_failed_node(node, get_panic_message(panic.as_ref()));
+                return;
+            }
+        }
+    }
+
+    /// Remove the nodes to isolate for the failed node from the computation.
+    fn isolate_failed_node(&mut self, node: NodeIndex, message: String) {
+        tracing::warn!(
+            node = node.index(),
+            "Transition function panicked: {message}"
+        );
+        self.failures.insert(node, message);
+        for isolated_node in self.fp_context.get_nodes_to_isolate(node) {
+            self.node_values.remove(&isolated_node);
+            if self.memory_limit.is_some() {
+                self.memory_usage.update(isolated_node, 0);
+            }
+            self.worklist
+                .remove(&self.node_priority_list[isolated_node.index()]);
+            self.isolated_nodes.insert(isolated_node);
+        }
+    }
+
+    /// Set a flag that can be used to abort the fixpoint computation,
+    /// e.g. from another thread.
+    ///
+    /// The computation stops before the next node update once the flag is set to `true`.
+    pub fn set_abort_flag(&mut self, abort_flag: Arc<AtomicBool>) {
+        self.abort_flag = Some(abort_flag);
+    }
+
+    /// Returns `true` if the abort flag of the computation is set.
+    ///
+    /// If the fixpoint algorithm was aborted, the node values are only an intermediate result
+    /// and the nodes that were not yet stabilized remain on the worklist.
+    pub fn is_aborted(&self) -> bool {
+        self.abort_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// Set a limit in bytes for the approximate memory usage of all node values.
+    ///
+    /// If the limit is exceeded during the computation,
+    /// the largest node values get degraded (see the module-level documentation).
+    pub fn set_memory_limit(&mut self, memory_limit: usize) {
+        self.memory_limit = Some(memory_limit);
+        self.recompute_memory_usage();
+    }
+
+    /// Get the approximate memory usage of all node values in bytes.
+    ///
+    /// Returns zero if no memory limit is set, since the memory usage is only tracked if a limit exists.
+    pub fn get_memory_usage(&self) -> usize {
+        self.memory_usage.total
+    }
+
+    /// Get the nodes whose values were degraded because the memory limit was exceeded.
+    pub fn get_degraded_nodes(&self) -> &BTreeSet<NodeIndex> {
+        &self.degraded_nodes
+    }
+
+    /// Get the nodes at which a transition function panicked together with the panic messages.
+    ///
+    /// The nodes to isolate for these nodes were removed from the computation.
+    pub fn get_failures(&self) -> &BTreeMap<NodeIndex, String> {
+        &self.failures
+    }
+
+    /// Record the precision losses caused by merges during the computation
+    /// (see the module-level documentation).
+    pub fn enable_precision_loss_recording(&mut self) {
+        self.precision_losses.get_or_insert_with(BTreeMap::new);
+    }
+
+    /// Get the recorded precision losses of each node
+    /// or `None` if the recording of precision losses is not enabled.
+    pub fn get_precision_losses(&self) -> Option<&BTreeMap<NodeIndex, Vec<PrecisionLoss>>> {
+        self.precision_losses.as_ref()
+    }
+
+    /// Recompute the approximate memory size of all node values.
+    fn recompute_memory_usage(&mut self) {
+        self.memory_usage = MemoryUsage::default();
+        for (node, value) in self.node_values.iter() {
+            let size = self.fp_context.get_approximate_memory_size(value);
+            self.memory_usage.update(*node, size);
+        }
+    }
+
+    /// If the memory limit is exceeded, degrade the largest node values
+    /// until the memory usage falls below three quarters of the limit.
+    ///
+    /// Degraded nodes are marked as not yet stabilized,
+    /// so that the degraded values get propagated to their successors.
+    fn enforce_memory_limit(&mut self) {
+        let Some(memory_limit) = self.memory_limit else {
+            return;
+        };
+        if self.memory_usage.total <= memory_limit {
+            return;
+        }
+        let mut nodes_by_size: Vec<(usize, NodeIndex)> = self
+            .memory_usage
+            .node_sizes
+            .iter()
+            .map(|(node, size)| (*size, *node))
+            .collect();
+        nodes_by_size.sort_unstable_by(|a, b| b.cmp(a));
+        for (size, node) in nodes_by_size {
+            if self.memory_usage.total <= memory_limit / 4 * 3 {
+                break;
+            }
+            let Some(degraded_value) = self
+                .node_values
+                .get(&node)
+                .and_then(|value| self.fp_context.degrade_value(value))
+            else {
+                continue;
+            };
+            if self.fp_context.get_approximate_memory_size(&degraded_value) < size {
+                self.set_node_value(node, degraded_value);
+                self.degraded_nodes.insert(node);
+            }
         }
     }
 
     /// Remove the highest priority node from the internal worklist and return it.
+    ///
+    /// Returns `None` if the worklist is empty or if the computation was aborted.
     fn take_next_node_from_worklist(&mut self) -> Option<NodeIndex> {
+        if self.is_aborted() {
+            return None;
+        }
         if let Some(priority) = self.worklist.iter().next_back().cloned() {
             let priority = self.worklist.take(&priority).unwrap();
             Some(self.priority_to_node_list[priority])
@@ -209,31 +589,60 @@ impl<T: Context> Computation<T> {
     /// If a node does not stabilize after `max_steps` visits, the end result
     /// will not be a fixpoint but only an intermediate result of a fixpoint
     /// computation.
+    /// The same holds if the computation gets aborted through its abort flag.
     pub fn compute_with_max_steps(&mut self, max_steps: u64) {
         let mut steps = vec![0; self.fp_context.get_graph().node_count()];
 
         let mut non_stabilized_nodes = BTreeSet::new();
+        if self.memory_limit.is_some() {
+            self.recompute_memory_usage();
+        }
 
-        while let Some(priority) = self.worklist.iter().next_back().cloned() {
-            let priority = self.worklist.take(&priority).unwrap();
-            let node = self.priority_to_node_list[priority];
+        while let Some(node) = self.take_next_node_from_worklist() {
             if steps[node.index()] < max_steps {
                 steps[node.index()] += 1;
+                self.steps += 1;
                 self.update_node(node);
+                self.enforce_memory_limit();
             } else {
-                non_stabilized_nodes.insert(priority);
+                non_stabilized_nodes.insert(self.node_priority_list[node.index()]);
             }
         }
         // After the algorithm finished, the new worklist is the list of non-stabilized nodes
-        self.worklist = non_stabilized_nodes;
+        self.worklist.append(&mut non_stabilized_nodes);
+        self.trace_summary();
     }
 
     /// Compute the fixpoint of the fixpoint problem.
-    /// If the fixpoint algorithm does not converge to a fixpoint, this function will not terminate.
+    /// If the fixpoint algorithm does not converge to a fixpoint,
+    /// this function will only terminate if the computation gets aborted through its abort flag.
     pub fn compute(&mut self) {
+        if self.memory_limit.is_some() {
+            self.recompute_memory_usage();
+        }
         while let Some(node) = self.take_next_node_from_worklist() {
+            self.steps += 1;
             self.update_node(node);
+            self.enforce_memory_limit();
         }
+        self.trace_summary();
+    }
+
+    /// Emit an event summarizing the computation,
+    /// i.e. the number of steps and the number of nodes whose values are imprecise or missing.
+    fn trace_summary(&self) {
+        tracing::debug!(
+            steps = self.steps,
+            non_stabilized_nodes = self.worklist.len(),
+            degraded_nodes = self.degraded_nodes.len(),
+            isolated_nodes = self.isolated_nodes.len(),
+            "Fixpoint computation finished"
+        );
+    }
+
+    /// Get the number of node updates performed so far by the fixpoint algorithm.
+    pub fn get_number_of_steps(&self) -> u64 {
+        self.steps
     }
 
     /// Get a reference to the internal map where one can look up the current values of all nodes
@@ -275,6 +684,49 @@ impl<T: Context> Computation<T> {
     }
 }
 
+/// Get the message of a panic from its payload.
+fn get_panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
+
+/// Returns the nodes of the graph ordered by their priority for the worklist algorithm,
+/// i.e. nodes with a higher index in the returned list are stabilized first.
+///
+/// The strongly connected components of the graph are ordered topologically.
+/// The nodes inside a strongly connected component are ordered in reverse post-order
+/// of a depth-first search that enters the component through one of its entry nodes.
+pub fn get_reverse_post_order_priorities<N, E>(graph: &DiGraph<N, E>) -> Vec<NodeIndex> {
+    // The SCCs are returned in reverse topological order.
+    let sccs = petgraph::algo::kosaraju_scc(graph);
+    let mut post_order_numbers = vec![0; graph.node_count()];
+    let mut next_post_order_number = 0;
+    let mut dfs = DfsPostOrder::empty(graph);
+    // Starting the search in the topologically first SCCs ensures
+    // that all other SCCs are entered through one of their entry nodes.
+    for start_node in sccs.iter().rev().flatten() {
+        if dfs.discovered.is_visited(start_node) {
+            continue;
+        }
+        dfs.move_to(*start_node);
+        while let Some(node) = dfs.next(graph) {
+            post_order_numbers[node.index()] = next_post_order_number;
+            next_post_order_number += 1;
+        }
+    }
+    sccs.into_iter()
+        .flat_map(|mut scc| {
+            scc.sort_unstable_by_key(|node| post_order_numbers[node.index()]);
+            scc
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,6 +775,128 @@ mod tests {
         assert_eq!(0, *solution.get_node_value(NodeIndex::new(5)).unwrap());
     }
 
+    #[test]
+    fn count_steps() {
+        let mut graph: DiGraph<(), u64> = DiGraph::new();
+        for _i in 0..3 {
+            graph.add_node(());
+        }
+        graph.add_edge(NodeIndex::new(0), NodeIndex::new(1), 1);
+        graph.add_edge(NodeIndex::new(1), NodeIndex::new(2), 1);
+
+        let mut solution = Computation::new(FPContext { graph }, None);
+        assert_eq!(solution.get_number_of_steps(), 0);
+        solution.set_node_value(NodeIndex::new(0), 0);
+        solution.compute();
+        assert_eq!(solution.get_number_of_steps(), 3);
+    }
+
+    #[test]
+    fn reverse_post_order_priorities() {
+        let mut graph: DiGraph<(), u64> = DiGraph::new();
+        for _i in 0..5 {
+            graph.add_node(());
+        }
+        // The loop 1 -> 2 -> 3 -> 1 is entered through node 1
+        // and its nodes were added to the graph in reverse order.
+        graph.add_edge(NodeIndex::new(3), NodeIndex::new(1), 1);
+        graph.add_edge(NodeIndex::new(2), NodeIndex::new(3), 1);
+        graph.add_edge(NodeIndex::new(1), NodeIndex::new(2), 1);
+        graph.add_edge(NodeIndex::new(0), NodeIndex::new(1), 1);
+        graph.add_edge(NodeIndex::new(3), NodeIndex::new(4), 1);
+
+        let priorities: Vec<usize> = get_reverse_post_order_priorities(&graph)
+            .into_iter()
+            .map(|node| node.index())
+            .collect();
+        assert_eq!(priorities, vec![4, 3, 2, 1, 0]);
+
+        // Every node is visited exactly once, since no node is visited before its predecessors in the loop.
+        let mut solution = Computation::new(FPContext { graph }, None);
+        solution.set_node_value(NodeIndex::new(0), 0);
+        solution.compute();
+        assert_eq!(solution.get_number_of_steps(), 5);
+        assert_eq!(*solution.get_node_value(NodeIndex::new(4)).unwrap(), 4);
+    }
+
+    /// A context whose merge function counts the number of merges,
+    /// so that merged values are never structurally equal to the old value.
+    struct CountingFPContext {
+        graph: DiGraph<(), u64>,
+        /// If unset, the default order that only detects equal values is used.
+        use_partial_order: bool,
+    }
+
+    impl Context for CountingFPContext {
+        type EdgeLabel = u64;
+        type NodeLabel = ();
+        type NodeValue = (u64, u64);
+
+        fn get_graph(&self) -> &DiGraph<(), u64> {
+            &self.graph
+        }
+
+        fn merge(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> Self::NodeValue {
+            (std::cmp::max(val1.0, val2.0), val1.1 + val2.1 + 1)
+        }
+
+        fn less_or_equal(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> bool {
+            if self.use_partial_order {
+                val1.0 <= val2.0
+            } else {
+                val1 == val2
+            }
+        }
+
+        fn update_edge(&self, value: &Self::NodeValue, edge: EdgeIndex) -> Option<Self::NodeValue> {
+            Some((
+                std::cmp::min(value.0 + self.graph.edge_weight(edge).unwrap(), 10),
+                value.1,
+            ))
+        }
+    }
+
+    #[test]
+    fn fixpoint_with_partial_order() {
+        let mut graph: DiGraph<(), u64> = DiGraph::new();
+        for _i in 0..3 {
+            graph.add_node(());
+        }
+        graph.add_edge(NodeIndex::new(0), NodeIndex::new(1), 1);
+        graph.add_edge(NodeIndex::new(1), NodeIndex::new(2), 2);
+        graph.add_edge(NodeIndex::new(2), NodeIndex::new(1), 3);
+
+        let mut solution = Computation::new(
+            CountingFPContext {
+                graph: graph.clone(),
+                use_partial_order: true,
+            },
+            None,
+        );
+        solution.set_node_value(NodeIndex::new(0), (0, 0));
+        solution.compute_with_max_steps(20);
+
+        assert!(solution.has_stabilized());
+        assert_eq!(10, solution.get_node_value(NodeIndex::new(1)).unwrap().0);
+        assert_eq!(10, solution.get_node_value(NodeIndex::new(2)).unwrap().0);
+
+        // With the default order the merged values never compare equal to the old values,
+        // so the loop is iterated until the step limit is reached.
+        let mut solution_without_order = Computation::new(
+            CountingFPContext {
+                graph,
+                use_partial_order: false,
+            },
+            None,
+        );
+        solution_without_order.set_node_value(NodeIndex::new(0), (0, 0));
+        solution_without_order.compute_with_max_steps(20);
+
+        assert!(!solution_without_order.has_stabilized());
+        assert!(solution.get_number_of_steps() < 10);
+        assert!(solution_without_order.get_number_of_steps() >= 40);
+    }
+
     #[test]
     fn fixpoint_with_default_value() {
         let mut graph: DiGraph<(), u64> = DiGraph::new();
@@ -344,6 +918,302 @@ mod tests {
         assert_eq!(3, *solution.get_node_value(NodeIndex::new(12)).unwrap());
     }
 
+    /// A context that sets the abort flag once the given value is computed for a node.
+    struct AbortingFPContext {
+        graph: DiGraph<(), u64>,
+        abort_flag: Arc<AtomicBool>,
+        abort_at: u64,
+    }
+
+    impl Context for AbortingFPContext {
+        type EdgeLabel = u64;
+        type NodeLabel = ();
+        type NodeValue = u64;
+
+        fn get_graph(&self) -> &DiGraph<(), u64> {
+            &self.graph
+        }
+
+        fn merge(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> Self::NodeValue {
+            std::cmp::min(*val1, *val2)
+        }
+
+        fn update_edge(&self, value: &Self::NodeValue, edge: EdgeIndex) -> Option<Self::NodeValue> {
+            let new_value = value + self.graph.edge_weight(edge).unwrap();
+            if new_value == self.abort_at {
+                self.abort_flag.store(true, Ordering::Relaxed);
+            }
+            Some(new_value)
+        }
+    }
+
+    #[test]
+    fn abort_computation() {
+        let mut graph: DiGraph<(), u64> = DiGraph::new();
+        for _i in 0..10 {
+            graph.add_node(());
+        }
+        for i in 0..9 {
+            graph.add_edge(NodeIndex::new(i), NodeIndex::new(i + 1), 1);
+        }
+        let abort_flag = Arc::new(AtomicBool::new(false));
+        let context = AbortingFPContext {
+            graph,
+            abort_flag: abort_flag.clone(),
+            abort_at: 3,
+        };
+        let mut computation = Computation::new(context, None);
+        computation.set_abort_flag(abort_flag.clone());
+        computation.set_node_value(NodeIndex::new(0), 0);
+        computation.compute_with_max_steps(10);
+
+        // The partial state is available after the abort.
+        assert!(computation.is_aborted());
+        assert!(!computation.has_stabilized());
+        assert_eq!(computation.get_number_of_steps(), 3);
+        assert_eq!(*computation.get_node_value(NodeIndex::new(3)).unwrap(), 3);
+        assert!(computation.get_node_value(NodeIndex::new(4)).is_none());
+        assert_eq!(computation.get_worklist(), vec![NodeIndex::new(3)]);
+
+        // The computation can be resumed after resetting the flag.
+        abort_flag.store(false, Ordering::Relaxed);
+        computation.compute();
+        assert!(!computation.is_aborted());
+        assert!(computation.has_stabilized());
+        assert_eq!(*computation.get_node_value(NodeIndex::new(9)).unwrap(), 9);
+    }
+
+    /// A context whose node values are the lists of edge weights along a path to the node.
+    /// Degraded values are empty lists.
+    struct GrowingFPContext {
+        graph: DiGraph<(), u64>,
+    }
+
+    impl Context for GrowingFPContext {
+        type EdgeLabel = u64;
+        type NodeLabel = ();
+        type NodeValue = Vec<u64>;
+
+        fn get_graph(&self) -> &DiGraph<(), u64> {
+            &self.graph
+        }
+
+        fn merge(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> Self::NodeValue {
+            std::cmp::max_by_key(val1, val2, |value| value.len()).clone()
+        }
+
+        fn update_edge(&self, value: &Self::NodeValue, edge: EdgeIndex) -> Option<Self::NodeValue> {
+            let mut new_value = value.clone();
+            new_value.push(*self.graph.edge_weight(edge).unwrap());
+            Some(new_value)
+        }
+
+        fn get_approximate_memory_size(&self, value: &Self::NodeValue) -> usize {
+            value.len() * std::mem::size_of::<u64>()
+        }
+
+        fn degrade_value(&self, _value: &Self::NodeValue) -> Option<Self::NodeValue> {
+            Some(Vec::new())
+        }
+    }
+
+    #[test]
+    fn memory_limit() {
+        let mut graph: DiGraph<(), u64> = DiGraph::new();
+        for _i in 0..10 {
+            graph.add_node(());
+        }
+        for i in 0..9 {
+            graph.add_edge(NodeIndex::new(i), NodeIndex::new(i + 1), 1);
+        }
+
+        let mut computation = Computation::new(
+            GrowingFPContext {
+                graph: graph.clone(),
+            },
+            None,
+        );
+        computation.set_node_value(NodeIndex::new(0), Vec::new());
+        computation.compute();
+        assert!(computation.get_degraded_nodes().is_empty());
+        assert_eq!(computation.get_memory_usage(), 0);
+        assert_eq!(
+            computation.get_node_value(NodeIndex::new(9)).unwrap().len(),
+            9
+        );
+
+        let mut computation = Computation::new(GrowingFPContext { graph }, None);
+        computation.set_memory_limit(100);
+        computation.set_node_value(NodeIndex::new(0), Vec::new());
+        computation.compute();
+        assert!(computation.has_stabilized());
+        assert!(computation.get_memory_usage() <= 100);
+        assert!(!computation.get_degraded_nodes().is_empty());
+        // Values after degraded nodes only contain the edges after the degraded node.
+        assert!(computation.get_node_value(NodeIndex::new(9)).unwrap().len() < 9);
+    }
+
+    /// A context whose transition function panics for edges with weight zero.
+    /// The nodes are grouped into functions of three nodes each.
+    struct PanickingFPContext {
+        graph: DiGraph<(), u64>,
+    }
+
+    impl Context for PanickingFPContext {
+        type EdgeLabel = u64;
+        type NodeLabel = ();
+        type NodeValue = u64;
+
+        fn get_graph(&self) -> &DiGraph<(), u64> {
+            &self.graph
+        }
+
+        fn merge(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> Self::NodeValue {
+            std::cmp::min(*val1, *val2)
+        }
+
+        fn update_edge(&self, value: &Self::NodeValue, edge: EdgeIndex) -> Option<Self::NodeValue> {
+            match self.graph.edge_weight(edge).unwrap() {
+                0 => panic!("Unsupported edge"),
+                weight => Some(value + weight),
+            }
+        }
+
+        fn get_nodes_to_isolate(&self, node: NodeIndex) -> Vec<NodeIndex> {
+            let function_start = node.index() / 3 * 3;
+            (function_start..function_start + 3)
+                .map(NodeIndex::new)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn failure_isolation() {
+        let mut graph: DiGraph<(), u64> = DiGraph::new();
+        for _i in 0..6 {
+            graph.add_node(());
+        }
+        graph.add_edge(NodeIndex::new(0), NodeIndex::new(1), 1);
+        graph.add_edge(NodeIndex::new(1), NodeIndex::new(2), 0);
+        graph.add_edge(NodeIndex::new(3), NodeIndex::new(4), 1);
+        graph.add_edge(NodeIndex::new(4), NodeIndex::new(5), 1);
+
+        let mut computation = Computation::new(PanickingFPContext { graph }, None);
+        computation.set_node_value(NodeIndex::new(0), 0);
+        computation.set_node_value(NodeIndex::new(3), 0);
+        computation.compute();
+
+        assert!(computation.has_stabilized());
+        assert_eq!(computation.get_failures().len(), 1);
+        assert_eq!(
+            computation.get_failures()[&NodeIndex::new(1)],
+            "Unsupported edge"
+        );
+        // All nodes of the failed function are removed from the computation.
+        for node in 0..3 {
+            assert!(computation.get_node_value(NodeIndex::new(node)).is_none());
+        }
+        computation.set_node_value(NodeIndex::new(0), 0);
+        assert!(computation.get_node_value(NodeIndex::new(0)).is_none());
+        // The other function is still analyzed.
+        assert_eq!(computation.get_node_value(NodeIndex::new(5)), Some(&2));
+    }
+
+    /// A context whose values are constants or `None`, which represents an unknown value.
+    /// Edges with weight zero make the value unknown.
+    struct ConstantFPContext {
+        graph: DiGraph<(), u64>,
+    }
+
+    impl Context for ConstantFPContext {
+        type EdgeLabel = u64;
+        type NodeLabel = ();
+        type NodeValue = Option<u64>;
+
+        fn get_graph(&self) -> &DiGraph<(), u64> {
+            &self.graph
+        }
+
+        fn merge(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> Self::NodeValue {
+            if val1 == val2 {
+                *val1
+            } else {
+                None
+            }
+        }
+
+        fn update_edge(&self, value: &Self::NodeValue, edge: EdgeIndex) -> Option<Self::NodeValue> {
+            match self.graph.edge_weight(edge).unwrap() {
+                0 => Some(None),
+                weight => Some(value.map(|value| value + weight)),
+            }
+        }
+
+        fn get_edge_precision_losses(
+            &self,
+            value: &Self::NodeValue,
+            new_value: &Self::NodeValue,
+            _edge: EdgeIndex,
+        ) -> Vec<PrecisionLoss> {
+            if value.is_some() && new_value.is_none() {
+                vec![PrecisionLoss {
+                    location: "value".to_string(),
+                    cause: PrecisionLossCause::UnknownCallee(None),
+                    top: true,
+                    description: format!("{value:?} became unknown"),
+                }]
+            } else {
+                Vec::new()
+            }
+        }
+
+        fn get_precision_losses(
+            &self,
+            val1: &Self::NodeValue,
+            val2: &Self::NodeValue,
+            merged: &Self::NodeValue,
+        ) -> Vec<PrecisionLoss> {
+            if merged.is_none() && (val1.is_some() || val2.is_some()) {
+                vec![PrecisionLoss {
+                    location: "value".to_string(),
+                    cause: PrecisionLossCause::Merge,
+                    top: true,
+                    description: format!("Merged {val1:?} and {val2:?}"),
+                }]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    #[test]
+    fn precision_loss_recording() {
+        let mut graph: DiGraph<(), u64> = DiGraph::new();
+        for _i in 0..6 {
+            graph.add_node(());
+        }
+        graph.add_edge(NodeIndex::new(0), NodeIndex::new(2), 1);
+        graph.add_edge(NodeIndex::new(1), NodeIndex::new(2), 2);
+        graph.add_edge(NodeIndex::new(2), NodeIndex::new(3), 1);
+        graph.add_edge(NodeIndex::new(4), NodeIndex::new(5), 0);
+        let mut computation = Computation::new(ConstantFPContext { graph }, None);
+        assert!(computation.get_precision_losses().is_none());
+        computation.enable_precision_loss_recording();
+        computation.set_node_value(NodeIndex::new(0), Some(0));
+        computation.set_node_value(NodeIndex::new(1), Some(0));
+        computation.set_node_value(NodeIndex::new(4), Some(0));
+        computation.compute();
+
+        assert_eq!(computation.get_node_value(NodeIndex::new(3)), Some(&None));
+        let precision_losses = computation.get_precision_losses().unwrap();
+        assert_eq!(precision_losses[&NodeIndex::new(2)].len(), 1);
+        assert_eq!(precision_losses[&NodeIndex::new(2)][0].location, "value");
+        assert_eq!(
+            precision_losses[&NodeIndex::new(5)][0].cause,
+            PrecisionLossCause::UnknownCallee(None)
+        );
+    }
+
     #[test]
     fn worklist_node_order() {
         let mut graph: DiGraph<(), u64> = DiGraph::new();
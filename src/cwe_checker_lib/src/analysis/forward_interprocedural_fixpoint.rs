@@ -8,14 +8,28 @@
 //! first construct a context object implementing the `Context`trait.
 //! Use it to construct a `Computation` object.
 //! The `Computation` object provides the necessary methods for the actual fixpoint computation.
-
-use super::fixpoint::Context as GeneralFPContext;
+//!
+//! # Recursion
+//!
+//! For calls inside recursive functions the value at the return site of the callee,
+//! i.e. the summary of the callee, depends on the value at the return site itself.
+//! Computing the fixpoint of such strongly connected components of the call graph
+//! may converge very slowly.
+//! Contexts can bound the number of times that the summary of a recursive call is applied at its return site
+//! (see [`Context::get_recursion_bound`]).
+//! Afterwards the return from the recursive call is computed by [`Context::update_recursive_call_fallback`]
+//! from the value before the call alone,
+//! so that changes of the callee summary no longer propagate back into the recursion.
+
+use super::fixpoint::{Context as GeneralFPContext, PrecisionLoss};
 use super::graph::*;
 use super::interprocedural_fixpoint_generic::*;
 
 use crate::intermediate_representation::*;
 
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use petgraph::graph::EdgeIndex;
 use petgraph::graph::NodeIndex;
@@ -40,6 +54,14 @@ pub trait Context<'a> {
     /// Merge two node values.
     fn merge(&self, value1: &Self::Value, value2: &Self::Value) -> Self::Value;
 
+    /// Returns `true` if `value1` is less than or equal to `value2` with respect to the partial order on values.
+    /// See [`fixpoint::Context::less_or_equal`](super::fixpoint::Context::less_or_equal) for how it is used.
+    ///
+    /// The default implementation only detects equal values.
+    fn less_or_equal(&self, value1: &Self::Value, value2: &Self::Value) -> bool {
+        value1 == value2
+    }
+
     /// Transition function for `Def` terms.
     /// The transition function for a basic block is computed
     /// by iteratively applying this function to the starting value for each `Def` term in the basic block.
@@ -56,6 +78,8 @@ pub trait Context<'a> {
     ) -> Option<Self::Value>;
 
     /// Transition function for in-program calls.
+    ///
+    /// For tail calls the `call` is the branch to the first block of the callee.
     fn update_call(
         &self,
         value: &Self::Value,
@@ -88,23 +112,107 @@ pub trait Context<'a> {
         block_before_condition: &Term<Blk>,
         is_true: bool,
     ) -> Option<Self::Value>;
+
+    /// The maximal number of times that the summary of the callee of a recursive call
+    /// is applied at the return site of the call.
+    /// See the module-level documentation for more information.
+    ///
+    /// The default implementation returns `None`, i.e. the number is not bounded.
+    fn get_recursion_bound(&self) -> Option<u64> {
+        None
+    }
+
+    /// Transition function for returns from recursive calls
+    /// whose callee summary was applied more often than the recursion bound allows.
+    /// Only the value at the callsite is known,
+    /// so the result should represent the effects of an arbitrary call.
+    ///
+    /// The default implementation returns `None`, i.e. no information flows to the return site.
+    fn update_recursive_call_fallback(
+        &self,
+        _value_before_call: &Self::Value,
+        _call_term: &Term<Jmp>,
+    ) -> Option<Self::Value> {
+        None
+    }
+
+    /// Returns the approximate number of bytes of memory occupied by the value.
+    /// See [`GeneralFPContext::get_approximate_memory_size`] for more information.
+    fn get_approximate_memory_size(&self, _value: &Self::Value) -> usize {
+        0
+    }
+
+    /// Returns a less precise value that occupies less memory than the given value.
+    /// See [`GeneralFPContext::degrade_value`] for more information.
+    fn degrade_value(&self, _value: &Self::Value) -> Option<Self::Value> {
+        None
+    }
+
+    /// Returns the parts of the merged value that lost precision compared to the two merged values.
+    /// See [`GeneralFPContext::get_precision_losses`] for more information.
+    fn get_precision_losses(
+        &self,
+        _value1: &Self::Value,
+        _value2: &Self::Value,
+        _merged: &Self::Value,
+    ) -> Vec<PrecisionLoss> {
+        Vec::new()
+    }
+
+    /// Returns the parts of the value `new_value` after a call stub that lost precision
+    /// compared to the value `value` before the call.
+    /// See [`GeneralFPContext::get_edge_precision_losses`] for more information.
+    fn get_call_stub_precision_losses(
+        &self,
+        _value: &Self::Value,
+        _new_value: &Self::Value,
+        _call: &Term<Jmp>,
+    ) -> Vec<PrecisionLoss> {
+        Vec::new()
+    }
 }
 
 /// This struct is a wrapper to create a general fixpoint context out of an interprocedural fixpoint context.
 pub struct GeneralizedContext<'a, T: Context<'a>> {
     context: T,
+    /// Counts how often the callee summaries of recursive calls were applied at their return sites.
+    /// Only contains entries if the context has a recursion bound.
+    recursive_call_counters: HashMap<Tid, AtomicU64>,
     _phantom_graph_reference: PhantomData<Graph<'a>>,
 }
 
 impl<'a, T: Context<'a>> GeneralizedContext<'a, T> {
     /// Create a new generalized context out of an interprocedural context object.
     pub fn new(context: T) -> Self {
+        let recursive_call_counters = if context.get_recursion_bound().is_some() {
+            get_recursive_call_tids(context.get_graph())
+                .into_iter()
+                .map(|call_tid| (call_tid, AtomicU64::new(0)))
+                .collect()
+        } else {
+            HashMap::new()
+        };
         GeneralizedContext {
             context,
+            recursive_call_counters,
             _phantom_graph_reference: PhantomData,
         }
     }
 
+    /// Returns `true` if the callee summary of the given recursive call
+    /// was already applied as often as the recursion bound of the context allows.
+    ///
+    /// Each call of this function counts as one application of the summary.
+    fn recursion_bound_exceeded(&self, call_term: &Term<Jmp>) -> bool {
+        match (
+            self.context.get_recursion_bound(),
+            self.recursive_call_counters.get(&call_term.tid),
+        ) {
+            (Some(bound), Some(counter)) => counter.fetch_add(1, Ordering::Relaxed) >= bound,
+            _ => false,
+        }
+    }
+
     /// Get the inner context object.
     pub fn get_context(&self) -> &T {
         &self.context
@@ -146,6 +254,112 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
         }
     }
 
+    /// Compare two values using the order from the interprocedural context object.
+    /// Missing values of combinator nodes are less than or equal to all other values.
+    fn less_or_equal(&self, val1: &Self::NodeValue, val2: &Self::NodeValue) -> bool {
+        use NodeValue::*;
+        let option_less_or_equal = |v1: &Option<T::Value>, v2: &Option<T::Value>| match (v1, v2) {
+            (None, _) => true,
+            (Some(v1), Some(v2)) => self.context.less_or_equal(v1, v2),
+            (Some(_), None) => false,
+        };
+        match (val1, val2) {
+            (Value(value1), Value(value2)) => self.context.less_or_equal(value1, value2),
+            (
+                CallFlowCombinator {
+                    call_stub: call1,
+                    interprocedural_flow: return1,
+                },
+                CallFlowCombinator {
+                    call_stub: call2,
+                    interprocedural_flow: return2,
+                },
+            ) => option_less_or_equal(call1, call2) && option_less_or_equal(return1, return2),
+            _ => false,
+        }
+    }
+
+    /// Sum up the approximate memory sizes of the values contained in the node value.
+    fn get_approximate_memory_size(&self, value: &Self::NodeValue) -> usize {
+        match value {
+            NodeValue::Value(value) => self.context.get_approximate_memory_size(value),
+            NodeValue::CallFlowCombinator {
+                call_stub,
+                interprocedural_flow,
+            } => [call_stub, interprocedural_flow]
+                .into_iter()
+                .flatten()
+                .map(|value| self.context.get_approximate_memory_size(value))
+                .sum(),
+        }
+    }
+
+    /// Degrade the values contained in the node value.
+    /// Values of combinator nodes that cannot be degraded are kept.
+    fn degrade_value(&self, value: &Self::NodeValue) -> Option<Self::NodeValue> {
+        match value {
+            NodeValue::Value(value) => self.context.degrade_value(value).map(NodeValue::Value),
+            NodeValue::CallFlowCombinator {
+                call_stub,
+                interprocedural_flow,
+            } => {
+                let degraded_call_stub = call_stub
+                    .as_ref()
+                    .and_then(|value| self.context.degrade_value(value));
+                let degraded_interprocedural_flow = interprocedural_flow
+                    .as_ref()
+                    .and_then(|value| self.context.degrade_value(value));
+                if degraded_call_stub.is_none() && degraded_interprocedural_flow.is_none() {
+                    return None;
+                }
+                Some(NodeValue::CallFlowCombinator {
+                    call_stub: degraded_call_stub.or_else(|| call_stub.clone()),
+                    interprocedural_flow: degraded_interprocedural_flow
+                        .or_else(|| interprocedural_flow.clone()),
+                })
+            }
+        }
+    }
+
+    /// Isolate all nodes of the function containing the node,
+    /// so that the function is skipped if its analysis fails.
+    fn get_nodes_to_isolate(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        get_nodes_of_same_function(self.get_graph(), node)
+    }
+
+    /// Get the precision losses of merged values from the interprocedural context object.
+    /// Merges of combinator nodes are not considered.
+    fn get_precision_losses(
+        &self,
+        val1: &Self::NodeValue,
+        val2: &Self::NodeValue,
+        merged: &Self::NodeValue,
+    ) -> Vec<PrecisionLoss> {
+        match (val1, val2, merged) {
+            (NodeValue::Value(value1), NodeValue::Value(value2), NodeValue::Value(merged)) => {
+                self.context.get_precision_losses(value1, value2, merged)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the precision losses of extern call stubs from the interprocedural context object.
+    /// Other edges are not considered.
+    fn get_edge_precision_losses(
+        &self,
+        value: &Self::NodeValue,
+        new_value: &Self::NodeValue,
+        edge: EdgeIndex,
+    ) -> Vec<PrecisionLoss> {
+        match (&self.get_graph()[edge], value, new_value) {
+            (Edge::ExternCallStub(call), NodeValue::Value(value), NodeValue::Value(new_value)) => {
+                self.context
+                    .get_call_stub_precision_losses(value, new_value, call)
+            }
+            _ => Vec::new(),
+        }
+    }
+
     /// Forward edge transition function.
     /// Applies the transition functions from the interprocedural context object
     /// corresponding to the type of the provided edge.
@@ -156,6 +370,11 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
     ) -> Option<Self::NodeValue> {
         let graph = self.context.get_graph();
         let (start_node, end_node) = graph.edge_endpoints(edge).unwrap();
+        let _span = tracing::trace_span!(
+            "function",
+            function = %graph[start_node].get_function().term.name
+        )
+        .entered();
 
         match graph.edge_weight(edge).unwrap() {
             Edge::Block => {
@@ -168,6 +387,10 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
                 end_val.map(NodeValue::Value)
             }
             Edge::CallCombine(_) => Some(Self::NodeValue::Value(node_value.unwrap_value().clone())),
+            // The state before the call is propagated to the exception handler.
+            Edge::ExceptionHandler(_) => {
+                Some(Self::NodeValue::Value(node_value.unwrap_value().clone()))
+            }
             Edge::Call(call) => self
                 .context
                 .update_call(
@@ -199,6 +422,15 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
                         _ => panic!("Malformed Control flow graph"),
                     };
                     let return_from_jmp = &return_from_block.term.jmps[0];
+                    if interprocedural_flow.is_some() && self.recursion_bound_exceeded(call_term) {
+                        return call_stub
+                            .as_ref()
+                            .and_then(|value_before_call| {
+                                self.context
+                                    .update_recursive_call_fallback(value_before_call, call_term)
+                            })
+                            .map(NodeValue::Value);
+                    }
                     self.context
                         .update_return(
                             interprocedural_flow.as_ref(),
@@ -210,10 +442,22 @@ impl<'a, T: Context<'a>> GeneralFPContext for GeneralizedContext<'a, T> {
                         .map(NodeValue::Value)
                 }
             },
-            Edge::ExternCallStub(call) => self
-                .context
-                .update_call_stub(node_value.unwrap_value(), call)
-                .map(NodeValue::Value),
+            Edge::ExternCallStub(call) => {
+                let value = self
+                    .context
+                    .update_call_stub(node_value.unwrap_value(), call);
+                // Calls to non-returning functions are redirected to artificial return targets.
+                // Nothing flows past them.
+                if graph[end_node]
+                    .get_block()
+                    .tid
+                    .is_artificial_return_target_block()
+                {
+                    None
+                } else {
+                    value.map(NodeValue::Value)
+                }
+            }
             Edge::Jump(jump, untaken_conditional) => {
                 let value_after_condition = if let Jmp::CBranch {
                     target: _,
@@ -276,20 +520,14 @@ pub fn create_computation<'a, T: Context<'a>>(
 pub fn create_bottom_up_worklist(graph: &Graph) -> Vec<NodeIndex> {
     let mut graph = graph.clone();
     graph.retain_edges(|frozen, edge| !matches!(frozen[edge], Edge::Call(..)));
-    petgraph::algo::kosaraju_scc(&graph)
-        .into_iter()
-        .flatten()
-        .collect()
+    super::fixpoint::get_reverse_post_order_priorities(&graph)
 }
 
 /// Returns a node ordering with caller nodes behind callee nodes.
 pub fn create_top_down_worklist(graph: &Graph) -> Vec<NodeIndex> {
     let mut graph = graph.clone();
     graph.retain_edges(|frozen, edge| !matches!(frozen[edge], Edge::CrReturnStub));
-    petgraph::algo::kosaraju_scc(&graph)
-        .into_iter()
-        .flatten()
-        .collect()
+    super::fixpoint::get_reverse_post_order_priorities(&graph)
 }
 
 /// Generate a new computation from the corresponding context and an optional default value for nodes.
@@ -0,0 +1,497 @@
+//! Position-independent hashes of functions.
+//!
+//! Binaries of the same firmware image or project often contain the same code,
+//! e.g. functions of statically linked libraries.
+//! Such functions are lifted to the same IR up to the addresses used in the code.
+//! The hash of a function is computed over a normalized form of its IR that does not depend on these addresses:
+//! - Constants pointing to global memory are replaced by a placeholder,
+//!   since the addresses of global variables differ between binaries.
+//! - Temporary variables are numbered in the order of their first occurrence in the function.
+//! - Targets of jumps inside the function are replaced by the index of the target block.
+//! - Calls to extern symbols are represented by the name of the symbol,
+//!   calls to other functions of the binary by a placeholder.
+//!
+//! Note that the IR depends on the CPU architecture and the Ghidra version used for lifting,
+//! so hashes of different architectures or Ghidra versions are not comparable.
+//!
+//! # Statically linked third-party code
+//!
+//! A [`HashDatabase`] contains the hashes of the functions of known library builds,
+//! e.g. of common libc, zlib and OpenSSL versions.
+//! CWE warnings in functions whose hashes are contained in the database are labelled
+//! as being in statically linked third-party code, see [`label_third_party_code`].
+//! Such warnings usually have to be fixed by updating the library instead of changing the analyzed code.
+//!
+//! The database bundled with the cwe_checker is installed as `function_hashes.json` in the configuration directory.
+//! The hashes of further library builds can be added with the `hash-library` subcommand
+//! from binaries that statically link the library, e.g. small test programs.
+//! Since the IR of small functions is not specific to a library, only functions with at least
+//! [`MIN_LIBRARY_FUNCTION_SIZE`] IR instructions are added to the database.
+
+use crate::error::CweCheckerError;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use crate::utils::read_config_file;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::Hasher;
+use std::path::Path;
+
+/// The file name of the bundled hash database in the configuration directory.
+pub const BUNDLED_HASH_DATABASE: &str = "function_hashes.json";
+
+/// The minimal number of IR instructions (`Def` and `Jmp` terms) of functions in a [`HashDatabase`].
+pub const MIN_LIBRARY_FUNCTION_SIZE: usize = 20;
+
+/// Compute the position-independent hash of a function as a hexadecimal string.
+pub fn hash_function(project: &Project, sub: &Term<Sub>) -> String {
+    let mut hasher = fnv::FnvHasher::default();
+    hasher.write(FunctionNormalizer::new(project, sub).normalize().as_bytes());
+    format!("{:016x}", hasher.finish())
+}
+
+/// Compute the position-independent hashes of all functions of the project.
+pub fn hash_functions(project: &Project) -> BTreeMap<Tid, String> {
+    project
+        .program
+        .term
+        .subs
+        .values()
+        .map(|sub| (sub.tid.clone(), hash_function(project, sub)))
+        .collect()
+}
+
+/// Set the function hash of the CWE warnings to the hash of the function containing their first address.
+///
+/// If the first address is contained in more than one function, the first of these functions is used.
+pub fn add_function_hashes<'a>(
+    project: &Project,
+    warnings: impl IntoIterator<Item = &'a mut CweWarning>,
+) {
+    let mut warning_functions = WarningFunctions::new(project);
+    for warning in warnings {
+        if let Some((_, hash)) = warning_functions.get(warning) {
+            warning.function_hash = Some(hash);
+        }
+    }
+}
+
+/// Label the CWE warnings in functions of the known libraries of the database
+/// by setting their `third_party_library` field.
+///
+/// Returns the number of labelled warnings.
+pub fn label_third_party_code<'a>(
+    project: &Project,
+    database: &HashDatabase,
+    warnings: impl IntoIterator<Item = &'a mut CweWarning>,
+) -> usize {
+    let mut warning_functions = WarningFunctions::new(project);
+    let mut num_labelled = 0;
+    for warning in warnings {
+        let Some((function, hash)) = warning_functions.get(warning) else {
+            continue;
+        };
+        if function.term.num_insn() < MIN_LIBRARY_FUNCTION_SIZE {
+            continue;
+        }
+        if let Some(library) = database.find_library(&project.cpu_architecture, &hash) {
+            warning.third_party_library = Some(library.to_string());
+            num_labelled += 1;
+        }
+    }
+    num_labelled
+}
+
+/// The hashes of the functions of known library builds.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct HashDatabase {
+    /// The hashed library builds.
+    pub libraries: Vec<LibraryHashes>,
+}
+
+/// The hashes of the functions of a library build.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct LibraryHashes {
+    /// The name and version of the library, e.g. `zlib 1.3.1`.
+    pub name: String,
+    /// The CPU architecture of the build, see [`Project::cpu_architecture`].
+    pub cpu_architecture: String,
+    /// The hashes of the functions of the library.
+    pub function_hashes: BTreeSet<String>,
+}
+
+impl HashDatabase {
+    /// Read the hash database from a JSON file.
+    pub fn load(path: &Path) -> Result<HashDatabase, CweCheckerError> {
+        let file = std::io::BufReader::new(
+            std::fs::File::open(path)
+                .context("Could not open the hash database")
+                .map_err(CweCheckerError::Config)?,
+        );
+        serde_json::from_reader(file)
+            .context("Parsing of the hash database failed")
+            .map_err(CweCheckerError::Config)
+    }
+
+    /// Read the hash database bundled with the cwe_checker from the configuration directory.
+    pub fn load_bundled() -> Result<HashDatabase, CweCheckerError> {
+        serde_json::from_value(read_config_file(BUNDLED_HASH_DATABASE)?)
+            .context("Parsing of the hash database failed")
+            .map_err(CweCheckerError::Config)
+    }
+
+    /// Write the hash database as JSON to the given file.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let file = std::fs::File::create(path).context("Could not create the hash database")?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    /// Add the hashes of all functions of the project with at least [`MIN_LIBRARY_FUNCTION_SIZE`] IR instructions
+    /// to the hashes of the library with the given name.
+    ///
+    /// Returns the number of added hashes.
+    pub fn add_library(&mut self, name: &str, project: &Project) -> usize {
+        let hashes = project
+            .program
+            .term
+            .subs
+            .values()
+            .filter(|sub| sub.term.num_insn() >= MIN_LIBRARY_FUNCTION_SIZE)
+            .map(|sub| hash_function(project, sub));
+        let library = match self.libraries.iter_mut().position(|library| {
+            library.name == name && library.cpu_architecture == project.cpu_architecture
+        }) {
+            Some(index) => &mut self.libraries[index],
+            None => {
+                self.libraries.push(LibraryHashes {
+                    name: name.to_string(),
+                    cpu_architecture: project.cpu_architecture.clone(),
+                    function_hashes: BTreeSet::new(),
+                });
+                self.libraries.last_mut().unwrap()
+            }
+        };
+        let num_hashes = library.function_hashes.len();
+        library.function_hashes.extend(hashes);
+        library.function_hashes.len() - num_hashes
+    }
+
+    /// Get the name of the first library of the given CPU architecture containing a function with the given hash.
+    pub fn find_library(&self, cpu_architecture: &str, hash: &str) -> Option<&str> {
+        self.libraries
+            .iter()
+            .find(|library| {
+                library.cpu_architecture == cpu_architecture
+                    && library.function_hashes.contains(hash)
+            })
+            .map(|library| library.name.as_str())
+    }
+}
+
+/// Finds the functions containing CWE warnings and caches their hashes.
+struct WarningFunctions<'a> {
+    project: &'a Project,
+    /// Maps the addresses of all instructions to the functions containing them.
+    functions_at_addresses: HashMap<u64, BTreeSet<Tid>>,
+    /// The hashes of the functions computed so far.
+    hashes: HashMap<Tid, String>,
+}
+
+impl<'a> WarningFunctions<'a> {
+    fn new(project: &'a Project) -> WarningFunctions<'a> {
+        WarningFunctions {
+            project,
+            functions_at_addresses: project.program.term.get_functions_at_addresses(),
+            hashes: HashMap::new(),
+        }
+    }
+
+    /// Get the function containing the first address of the warning together with its hash.
+    ///
+    /// If the first address is contained in more than one function, the first of these functions is returned.
+    fn get(&mut self, warning: &CweWarning) -> Option<(&'a Term<Sub>, String)> {
+        let tid = warning
+            .get_address()
+            .and_then(|address| self.functions_at_addresses.get(&address))
+            .and_then(|functions| functions.first())?;
+        let function = &self.project.program.term.subs[tid];
+        let hash = self
+            .hashes
+            .entry(tid.clone())
+            .or_insert_with(|| hash_function(self.project, function));
+        Some((function, hash.clone()))
+    }
+}
+
+/// Generates the normalized textual representation of a function that its hash is computed from.
+struct FunctionNormalizer<'a> {
+    project: &'a Project,
+    sub: &'a Term<Sub>,
+    /// Maps the TIDs of the blocks of the function to their indices.
+    block_indices: HashMap<&'a Tid, usize>,
+    /// Maps the names of temporary variables to the order of their first occurrence.
+    temp_vars: HashMap<String, usize>,
+}
+
+impl<'a> FunctionNormalizer<'a> {
+    fn new(project: &'a Project, sub: &'a Term<Sub>) -> FunctionNormalizer<'a> {
+        FunctionNormalizer {
+            project,
+            sub,
+            block_indices: sub
+                .term
+                .blocks
+                .iter()
+                .enumerate()
+                .map(|(index, block)| (&block.tid, index))
+                .collect(),
+            temp_vars: HashMap::new(),
+        }
+    }
+
+    /// Generate the normalized representation with one line per block header and instruction.
+    fn normalize(mut self) -> String {
+        let mut lines = Vec::new();
+        for (index, block) in self.sub.term.blocks.iter().enumerate() {
+            lines.push(format!("block {index}"));
+            for def in block.term.defs.iter() {
+                let line = self.normalize_def(&def.term);
+                lines.push(line);
+            }
+            for jmp in block.term.jmps.iter() {
+                let line = self.normalize_jmp(&jmp.term);
+                lines.push(line);
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn normalize_def(&mut self, def: &Def) -> String {
+        let mut def = def.clone();
+        match &mut def {
+            Def::Load { var, address } => {
+                self.normalize_variable(var);
+                self.normalize_expression(address);
+            }
+            Def::Store { address, value } => {
+                self.normalize_expression(address);
+                self.normalize_expression(value);
+            }
+            Def::Assign { var, value } => {
+                self.normalize_variable(var);
+                self.normalize_expression(value);
+            }
+        }
+        def.to_string()
+    }
+
+    fn normalize_jmp(&mut self, jmp: &Jmp) -> String {
+        match jmp {
+            Jmp::Branch(target) => format!("Jump to {}", self.block_index(target)),
+            Jmp::BranchInd(target) => format!("Jump to {}", self.normalized(target)),
+            Jmp::CBranch { target, condition } => format!(
+                "If {} jump to {}",
+                self.normalized(condition),
+                self.block_index(target)
+            ),
+            Jmp::Call { target, return_ } => {
+                let callee = match self.project.program.term.extern_symbols.get(target) {
+                    Some(symbol) => symbol.name.as_str(),
+                    None => "function",
+                };
+                format!("call {callee} ret {}", self.return_index(return_))
+            }
+            Jmp::CallInd { target, return_ } => format!(
+                "call {} ret {}",
+                self.normalized(target),
+                self.return_index(return_)
+            ),
+            Jmp::Return(expr) => format!("ret {}", self.normalized(expr)),
+            Jmp::CallOther {
+                description,
+                return_,
+            } => format!("call {description} ret {}", self.return_index(return_)),
+        }
+    }
+
+    /// Get the normalized representation of the expression.
+    fn normalized(&mut self, expr: &Expression) -> String {
+        let mut expr = expr.clone();
+        self.normalize_expression(&mut expr);
+        expr.to_string()
+    }
+
+    /// Rename temporary variables and replace constants pointing to global memory by a placeholder.
+    fn normalize_expression(&mut self, expr: &mut Expression) {
+        match expr {
+            Expression::Var(var) => self.normalize_variable(var),
+            Expression::Const(constant) => {
+                if self
+                    .project
+                    .runtime_memory_image
+                    .is_global_memory_address(constant)
+                {
+                    *expr = Expression::Unknown {
+                        description: format!("global:{}", constant.bytesize()),
+                        size: constant.bytesize(),
+                    };
+                }
+            }
+            Expression::BinOp { lhs, rhs, .. } => {
+                self.normalize_expression(lhs);
+                self.normalize_expression(rhs);
+            }
+            Expression::UnOp { arg, .. }
+            | Expression::Cast { arg, .. }
+            | Expression::Subpiece { arg, .. } => self.normalize_expression(arg),
+            Expression::Unknown { .. } => (),
+        }
+    }
+
+    /// Rename temporary variables by the order of their first occurrence.
+    fn normalize_variable(&mut self, var: &mut Variable) {
+        if var.is_temp {
+            let next_index = self.temp_vars.len();
+            let index = *self.temp_vars.entry(var.name.clone()).or_insert(next_index);
+            var.name = format!("$T{index}");
+        }
+    }
+
+    /// Get the index of a block of the function as a string.
+    fn block_index(&self, tid: &Tid) -> String {
+        match self.block_indices.get(tid) {
+            Some(index) => index.to_string(),
+            None => "extern".to_string(),
+        }
+    }
+
+    /// Get the index of the return block of a call as a string.
+    fn return_index(&self, return_: &Option<Tid>) -> String {
+        match return_ {
+            Some(tid) => self.block_index(tid),
+            None => "none".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{expr, variable};
+
+    fn mock_function(name: &str, var: &str, constant: &str, callee: &str) -> Term<Sub> {
+        let var = Variable {
+            is_temp: var.starts_with('$'),
+            ..variable!(format!("{var}:8"))
+        };
+        let mut block = Blk::mock_with_tid(&format!("{name}_block"));
+        block.term.defs = vec![
+            Def::assign(&format!("{name}_def_1"), var.clone(), expr!(constant)),
+            Def::assign(
+                &format!("{name}_def_2"),
+                variable!("RDI:8"),
+                Expression::Var(var).plus_const(8),
+            ),
+        ];
+        block.term.jmps = vec![Jmp::call(
+            &format!("{name}_call"),
+            callee,
+            Some(&format!("{name}_block")),
+        )];
+        Term::new(
+            Tid::new(name),
+            Sub::new::<_, &str>(&name, vec![block], None),
+        )
+    }
+
+    #[test]
+    fn position_independent_hashes() {
+        let project = Project::mock_x64();
+        let hash = |sub: &Term<Sub>| hash_function(&project, sub);
+        let function = mock_function("a", "$U1000", "0x3002:8", "malloc");
+        // Addresses of global memory and names of blocks and temporary variables do not matter.
+        let relocated = mock_function("b", "$U2000", "0x3004:8", "malloc");
+        assert_eq!(hash(&function), hash(&relocated));
+        assert_eq!(hash(&function).len(), 16);
+        // Other constants, registers and called symbols do matter.
+        assert_ne!(
+            hash(&function),
+            hash(&mock_function("c", "$U1000", "0x5:8", "malloc"))
+        );
+        assert_ne!(
+            hash(&function),
+            hash(&mock_function("d", "RAX", "0x3002:8", "malloc"))
+        );
+        assert_ne!(
+            hash(&function),
+            hash(&mock_function("e", "$U1000", "0x3002:8", "free"))
+        );
+    }
+
+    #[test]
+    fn hashes_of_warnings() {
+        let mut project = Project::mock_x64();
+        let mut function = mock_function("a", "$U1000", "0x3002:8", "malloc");
+        function.tid = Tid::new_function("0x1000");
+        function.term.blocks[0].tid = Tid::new_block("0x1000", 0);
+        let expected_hash = hash_function(&project, &function);
+        project
+            .program
+            .term
+            .subs
+            .insert(function.tid.clone(), function);
+
+        let mut warnings = [
+            CweWarning::new("CWE476", "0.3", "description").addresses(vec!["4096".to_string()]),
+            CweWarning::new("CWE476", "0.3", "description").addresses(vec!["8192".to_string()]),
+        ];
+        add_function_hashes(&project, warnings.iter_mut());
+        assert_eq!(warnings[0].function_hash, Some(expected_hash));
+        assert_eq!(warnings[1].function_hash, None);
+    }
+
+    #[test]
+    fn label_warnings_in_third_party_code() {
+        let mut project = Project::mock_x64();
+        let mut function = mock_function("a", "$U1000", "0x3002:8", "malloc");
+        function.tid = Tid::new_function("0x1000");
+        function.term.blocks[0].tid = Tid::new_block("0x1000", 0);
+        let def = function.term.blocks[0].term.defs[1].clone();
+        function.term.blocks[0]
+            .term
+            .defs
+            .extend(std::iter::repeat_n(def, MIN_LIBRARY_FUNCTION_SIZE));
+        project
+            .program
+            .term
+            .subs
+            .insert(function.tid.clone(), function);
+
+        let mut database = HashDatabase::default();
+        assert_eq!(database.add_library("zlib 1.3.1", &project), 1);
+        assert_eq!(database.add_library("zlib 1.3.1", &project), 0);
+        assert_eq!(database.libraries.len(), 1);
+
+        let mut warnings =
+            [CweWarning::new("CWE476", "0.3", "description").addresses(vec!["4096".to_string()])];
+        assert_eq!(
+            label_third_party_code(&project, &database, warnings.iter_mut()),
+            1
+        );
+        assert_eq!(
+            warnings[0].third_party_library.as_deref(),
+            Some("zlib 1.3.1")
+        );
+        assert!(warnings[0]
+            .to_string()
+            .ends_with("(in statically linked third-party code: zlib 1.3.1)"));
+        // Hashes of builds for other CPU architectures do not match.
+        database.libraries[0].cpu_architecture = "ARM".to_string();
+        warnings[0].third_party_library = None;
+        assert_eq!(
+            label_third_party_code(&project, &database, warnings.iter_mut()),
+            0
+        );
+    }
+}
@@ -319,17 +319,16 @@ impl<'a> Context<'a> {
     ) {
         let stack_register = &self.project.stack_pointer_register;
         let stack_pointer = state_before_call.get_register(stack_register);
-        match self.project.cpu_architecture.as_str() {
-            "x86" | "x86_32" | "x86_64" => {
-                let offset = Bitvector::from_u64(stack_register.size.into())
-                    .into_truncate(apint::BitWidth::from(stack_register.size))
-                    .unwrap();
-                new_state.set_register(
-                    stack_register,
-                    stack_pointer.bin_op(BinOpType::IntAdd, &offset.into()),
-                );
-            }
-            _ => new_state.set_register(stack_register, stack_pointer),
+        let return_address_size = self.project.get_return_address_size();
+        if return_address_size == ByteSize::new(0) {
+            new_state.set_register(stack_register, stack_pointer);
+        } else {
+            let offset = Bitvector::from_u64(return_address_size.into())
+                .into_resize_unsigned(stack_register.size);
+            new_state.set_register(
+                stack_register,
+                stack_pointer.bin_op(BinOpType::IntAdd, &offset.into()),
+            );
         }
     }
 }
@@ -42,7 +42,7 @@ use crate::analysis::fixpoint::Computation;
 use crate::analysis::forward_interprocedural_fixpoint::create_computation;
 use crate::analysis::forward_interprocedural_fixpoint::GeneralizedContext;
 use crate::analysis::graph::*;
-use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::interprocedural_fixpoint_generic::{get_failure_logs, NodeValue};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::{LogMessage, WithLogs};
@@ -82,7 +82,7 @@ fn generate_fixpoint_computation<'a>(
                         calling_convention,
                         pointer_recursion_depth_limit,
                     );
-                    if project.cpu_architecture.contains("MIPS") {
+                    if project.get_cpu_architecture() == CpuArchitecture::Mips {
                         let _ = fn_start_state
                             .set_mips_link_register(&sub.tid, project.stack_pointer_register.size);
                     }
@@ -181,9 +181,9 @@ pub fn compute_function_signatures<'a>(
         computation.compute_with_max_steps(100);
     }
 
+    let mut logs = get_failure_logs(&computation, "Function Signature Analysis");
     let mut fn_sig_map = extract_fn_signatures_from_fixpoint(project, graph, computation);
     // Sanitize the parameters
-    let mut logs = Vec::new();
     for (fn_tid, fn_sig) in fn_sig_map.iter_mut() {
         let info_log = fn_sig.sanitize(project);
         for log in info_log {
@@ -283,16 +283,14 @@ impl FunctionSignature {
     ///   as no proper sanitation pass is implemented for such cases yet.
     /// * Merge intersecting stack parameters
     fn sanitize(&mut self, project: &Project) -> Vec<String> {
-        match project.cpu_architecture.as_str() {
-            "x86" | "x86_32" | "x86_64" => {
-                let return_addr_location = AbstractLocation::from_stack_position(
-                    &project.stack_pointer_register,
-                    0,
-                    project.get_pointer_bytesize(),
-                );
-                self.parameters.remove(&return_addr_location);
-            }
-            _ => (),
+        let return_address_size = project.get_return_address_size();
+        if return_address_size != ByteSize::new(0) {
+            let return_addr_location = AbstractLocation::from_stack_position(
+                &project.stack_pointer_register,
+                0,
+                return_address_size,
+            );
+            self.parameters.remove(&return_addr_location);
         }
         // FIXME: We check for intersecting stack parameter register, but not for intersecting nested parameters.
         // We should add a check for these to generate log messages (but probably without trying to merge such parameters)
@@ -36,9 +36,12 @@
 //!   program are converted to *ExternCallStub* edges from the *BlkEnd* node of
 //!   the callsite to the *BlkStart* node of the basic block the call returns to
 //!   (if the call returns at all).
-//! * Right now indirect calls are handled as if they were extern calls, i.e. an
-//!   *ExternCallStub* edge is added. This behaviour will change in the future,
-//!   when better indirect call handling is implemented.
+//! * Indirect calls are handled as if they were extern calls, i.e. an
+//!   *ExternCallStub* edge is added. If some targets of the indirect call are
+//!   known, then *Call* edges to these targets are added in addition.
+//! * If a call may throw an exception that is handled by an exception handler
+//!   (landing pad) of the caller, then an *ExceptionHandler* edge is added from
+//!   the *BlkEnd* node of the callsite to the *BlkStart* node of the handler.
 //! * For each in-program call
 //!   ([`image`](../../../../../doc/images/internal_function_call.png)) and
 //!   corresponding return jump two nodes and four edges are generated:
@@ -54,6 +57,19 @@
 //! The artificial *CallReturn* nodes enable enriching the information flowing
 //! through a return edge with information recovered from the corresponding
 //! callsite during a fixpoint computation.
+//!
+//! ## Tail calls and non-returning functions
+//!
+//! * An unconditional branch to the first block of another function (or to the
+//!   inlined copy of it) is a tail call. Since the callee returns directly to
+//!   the callers of the current function, the branch is still converted to a
+//!   *Jump* edge to the (inlined) target block. Additionally a *CallSource*
+//!   node with a *CallCombine* and a *Call* edge to the first block of the
+//!   callee is added, so that the callee receives an entry state from the tail
+//!   call. No return edges are generated for these call edges.
+//! * Calls to functions that are marked as non-returning do not get
+//!   *CallReturn* nodes, i.e. no information flows from the callsite to the
+//!   return-to site of such calls.
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::debug::ToJsonCompact;
@@ -153,6 +169,23 @@ impl<'a> Node<'a> {
             }
         }
     }
+
+    /// Get the function that the node belongs to.
+    ///
+    /// For `CallReturn` and `CallSource` nodes this is the function containing the call.
+    pub fn get_function(&self) -> &'a Term<Sub> {
+        use Node::*;
+        match self {
+            BlkStart(_blk, sub) | BlkEnd(_blk, sub) => sub,
+            CallSource {
+                source: (_blk, sub),
+                ..
+            }
+            | CallReturn {
+                call: (_blk, sub), ..
+            } => sub,
+        }
+    }
 }
 
 impl<'a> std::fmt::Display for Node<'a> {
@@ -212,6 +245,10 @@ pub enum Edge<'a> {
     /// An artificial edge to combine intra- and interprocedural data flows at the return-to site of calls.
     /// See the module-level documentation for more information.
     ReturnCombine(&'a Term<Jmp>),
+    /// An edge from the callsite of a call that may throw an exception
+    /// to the exception handler (landing pad) for the call.
+    /// The edge is labeled with the call instruction.
+    ExceptionHandler(&'a Term<Jmp>),
 }
 
 impl<'a> std::fmt::Display for Edge<'a> {
@@ -229,6 +266,7 @@ impl<'a> std::fmt::Display for Edge<'a> {
             Self::CrReturnStub => write!(formatter, "CrReturnStub"),
             Self::CallCombine(..) => write!(formatter, "CallCombine"),
             Self::ReturnCombine(..) => write!(formatter, "ReturnCombine"),
+            Self::ExceptionHandler(..) => write!(formatter, "ExceptionHandler"),
         }
     }
 }
@@ -246,6 +284,10 @@ struct GraphBuilder<'a> {
     jump_targets: HashMap<(Tid, Tid), (NodeIndex, NodeIndex)>,
     /// for each function the list of return addresses of the corresponding call sites
     return_addresses: HashMap<Tid, Vec<(NodeIndex, NodeIndex)>>,
+    /// Maps the TID of the first block of each function to the TID of the function.
+    sub_start_blocks: HashMap<Tid, Tid>,
+    /// The (block_tid, sub_tid) pairs of the blocks contained in the functions of the `program`.
+    program_blocks: HashSet<(Tid, Tid)>,
     /// A list of `BlkEnd` nodes for which outgoing edges still have to be added to the graph.
     block_worklist: Vec<NodeIndex>,
     /// List of `LogMessage` generated by `build` function.
@@ -262,6 +304,8 @@ impl<'a> GraphBuilder<'a> {
             call_targets: HashMap::new(),
             jump_targets: HashMap::new(),
             return_addresses: HashMap::new(),
+            sub_start_blocks: HashMap::new(),
+            program_blocks: HashSet::new(),
             block_worklist: Vec::new(),
             log_messages: Vec::new(),
         }
@@ -289,6 +333,8 @@ impl<'a> GraphBuilder<'a> {
         for sub in subs {
             for block in sub.term.blocks.iter() {
                 self.add_block(block, sub);
+                self.program_blocks
+                    .insert((block.tid.clone(), sub.tid.clone()));
             }
         }
     }
@@ -300,6 +346,8 @@ impl<'a> GraphBuilder<'a> {
                 let start_block = &sub.term.blocks[0];
                 let target_index = self.jump_targets[&(start_block.tid.clone(), sub.tid.clone())];
                 self.call_targets.insert(sub.tid.clone(), target_index);
+                self.sub_start_blocks
+                    .insert(start_block.tid.clone(), sub.tid.clone());
             } else {
                 self.log_messages.push(LogMessage::new_info(format!(
                     "{} contains no blocks",
@@ -359,6 +407,62 @@ impl<'a> GraphBuilder<'a> {
         }
     }
 
+    /// Return the TID of the function that is tail-called by a jump from `source_block` to `target_tid` inside `sub`.
+    ///
+    /// The target is either the first block of another function
+    /// or the copy of it that was inlined into `sub`.
+    /// Jumps in blocks that were inlined from other functions are not tail calls of `sub`.
+    fn get_tail_call_target(
+        &self,
+        source_block: &Term<Blk>,
+        target_tid: &Tid,
+        sub: &Term<Sub>,
+    ) -> Option<&Tid> {
+        if source_block.tid.has_id_suffix(&sub.id_suffix())
+            || !self
+                .program_blocks
+                .contains(&(source_block.tid.clone(), sub.tid.clone()))
+        {
+            return None;
+        }
+        let callee_tid = self.sub_start_blocks.get(target_tid).or_else(|| {
+            let original_tid = target_tid.without_id_suffix(&sub.id_suffix())?;
+            self.sub_start_blocks.get(&original_tid)
+        })?;
+        (*callee_tid != sub.tid).then_some(callee_tid)
+    }
+
+    /// Add a *CallSource* node together with the *CallCombine* and *Call* edges
+    /// from the `source` node to the start node of the `target` function.
+    ///
+    /// Returns the index of the *CallSource* node
+    /// or `None` if the target function has no start node.
+    fn add_call_source_node(
+        &mut self,
+        source: NodeIndex,
+        target: &Tid,
+        jump: &'a Term<Jmp>,
+    ) -> Option<NodeIndex> {
+        let (source_block, sub_term) = match self.graph[source] {
+            Node::BlkEnd(source_block, sub_term) => (source_block, sub_term),
+            _ => panic!(),
+        };
+        let (target_node, _) = *self.call_targets.get(target)?;
+        let (target_block, target_sub) = match self.graph[target_node] {
+            Node::BlkStart(target_block, target_sub) => (target_block, target_sub),
+            _ => panic!(),
+        };
+        let call_source_node = self.graph.add_node(Node::CallSource {
+            source: (source_block, sub_term),
+            target: (target_block, target_sub),
+        });
+        self.graph
+            .add_edge(source, call_source_node, Edge::CallCombine(jump));
+        self.graph
+            .add_edge(call_source_node, target_node, Edge::Call(jump));
+        Some(call_source_node)
+    }
+
     /// add call edges and interprocedural jump edges for a specific jump term to the graph
     fn add_jump_edge(
         &mut self,
@@ -371,8 +475,16 @@ impl<'a> GraphBuilder<'a> {
             _ => panic!(),
         };
         match &jump.term {
-            Jmp::Branch(tid)
-            | Jmp::CBranch {
+            Jmp::Branch(tid) => {
+                self.add_intraprocedural_edge(source, tid, jump, untaken_conditional);
+                if let Some(callee_tid) = self
+                    .get_tail_call_target(source_block, tid, sub_term)
+                    .cloned()
+                {
+                    self.add_call_source_node(source, &callee_tid, jump);
+                }
+            }
+            Jmp::CBranch {
                 target: tid,
                 condition: _,
             } => {
@@ -403,29 +515,17 @@ impl<'a> GraphBuilder<'a> {
                             .add_edge(source, return_to_node, Edge::ExternCallStub(jump));
                     }
                 } else {
-                    let mut call_source_node: Option<NodeIndex> = None;
-                    if let Some((target_node, _)) = self.call_targets.get(target) {
-                        let (target_block, target_sub) = match self.graph[*target_node] {
-                            Node::BlkStart(target_block, target_sub) => (target_block, target_sub),
-                            _ => panic!(),
-                        };
-                        call_source_node = Some(self.graph.add_node(Node::CallSource {
-                            source: (source_block, sub_term),
-                            target: (target_block, target_sub),
-                        }));
-                        self.graph.add_edge(
-                            source,
-                            *call_source_node.as_ref().unwrap(),
-                            Edge::CallCombine(jump),
-                        );
-                        self.graph.add_edge(
-                            *call_source_node.as_ref().unwrap(),
-                            *target_node,
-                            Edge::Call(jump),
-                        );
-                    } // TODO: Log message for the else-case?
-                    if let Some(return_node) = return_to_node_option {
-                        if let Some(cs_node) = call_source_node {
+                    // TODO: Log message for the case that the target has no start node?
+                    let call_source_node = self.add_call_source_node(source, target, jump);
+                    let is_non_returning = self
+                        .program
+                        .subs
+                        .get(target)
+                        .is_some_and(|sub| sub.term.is_non_returning());
+                    if let (Some(return_node), Some(cs_node)) =
+                        (return_to_node_option, call_source_node)
+                    {
+                        if !is_non_returning {
                             self.return_addresses
                                 .entry(target.clone())
                                 .and_modify(|vec| vec.push((cs_node, return_node)))
@@ -435,9 +535,9 @@ impl<'a> GraphBuilder<'a> {
                 }
             }
             Jmp::CallInd { target: _, return_ } => {
-                // Right now we only add an artificial extern call stub for indirect calls.
-                // TODO: Handle cases where the call target may be known.
-                if let Some(return_tid) = return_ {
+                // We always add an artificial extern call stub for indirect calls,
+                // since the known call targets may be incomplete.
+                let return_to_node_option = if let Some(return_tid) = return_ {
                     let return_to_node = if let Some((return_to_node, _)) = self
                         .jump_targets
                         .get(&(return_tid.clone(), sub_term.tid.clone()))
@@ -449,6 +549,29 @@ impl<'a> GraphBuilder<'a> {
                     };
                     self.graph
                         .add_edge(source, return_to_node, Edge::ExternCallStub(jump));
+                    Some(return_to_node)
+                } else {
+                    None
+                };
+                // Add call edges to the known targets of the indirect call.
+                for target in source_block.term.ind_call_targets().into_iter().flatten() {
+                    if self.extern_subs.contains(target) {
+                        continue;
+                    }
+                    let call_source_node = self.add_call_source_node(source, target, jump);
+                    let is_non_returning = self
+                        .program
+                        .subs
+                        .get(target)
+                        .is_some_and(|sub| sub.term.is_non_returning());
+                    if let (Some(return_node), Some(cs_node), false) =
+                        (return_to_node_option, call_source_node, is_non_returning)
+                    {
+                        self.return_addresses
+                            .entry(target.clone())
+                            .and_modify(|vec| vec.push((cs_node, return_node)))
+                            .or_insert_with(|| vec![(cs_node, return_node)]);
+                    }
                 }
             }
             Jmp::CallOther {
@@ -475,6 +598,21 @@ impl<'a> GraphBuilder<'a> {
             }
             _ => panic!("Basic block with more than 2 jumps encountered"),
         }
+        if let (Some(handler_tid), Some(call)) = (
+            block.term.exception_handler(),
+            jumps
+                .iter()
+                .find(|jump| matches!(jump.term, Jmp::Call { .. } | Jmp::CallInd { .. })),
+        ) {
+            let sub_term = self.graph[node].get_sub();
+            if let Some((handler_node, _)) = self
+                .jump_targets
+                .get(&(handler_tid.clone(), sub_term.tid.clone()))
+            {
+                self.graph
+                    .add_edge(node, *handler_node, Edge::ExceptionHandler(call));
+            }
+        }
     }
 
     /// For each return instruction and each corresponding call, add the following to the graph:
@@ -499,7 +637,7 @@ impl<'a> GraphBuilder<'a> {
                 .term
                 .jmps
                 .iter()
-                .find(|jump| matches!(jump.term, Jmp::Call { .. }))
+                .find(|jump| matches!(jump.term, Jmp::Call { .. } | Jmp::CallInd { .. }))
                 .unwrap();
             let return_combine_node = self.graph.add_node(Node::CallReturn {
                 call: (call_block, caller_sub),
@@ -586,6 +724,62 @@ pub fn get_entry_nodes_of_subs(graph: &Graph) -> HashMap<Tid, NodeIndex> {
     sub_to_entry_node_map
 }
 
+/// Returns all nodes that belong to the same function as the given node (including the node itself).
+pub fn get_nodes_of_same_function(graph: &Graph, node: NodeIndex) -> Vec<NodeIndex> {
+    let sub_tid = &graph[node].get_function().tid;
+    graph
+        .node_indices()
+        .filter(|other_node| graph[*other_node].get_function().tid == *sub_tid)
+        .collect()
+}
+
+/// Returns the TIDs of all recursive calls in the graph,
+/// i.e. of calls whose caller and callee are contained in the same strongly connected component of the call graph.
+///
+/// The call graph is derived from the *Call* edges of the graph,
+/// so that it also contains tail calls and the known targets of indirect calls.
+pub fn get_recursive_call_tids(graph: &Graph) -> HashSet<Tid> {
+    let mut call_graph: DiGraph<&Tid, ()> = DiGraph::new();
+    let mut sub_to_node_map: HashMap<&Tid, NodeIndex> = HashMap::new();
+    let mut calls = Vec::new();
+    for edge in graph.edge_references() {
+        let Edge::Call(call) = edge.weight() else {
+            continue;
+        };
+        let caller = match graph[edge.source()] {
+            Node::CallSource {
+                source: (_, caller),
+                ..
+            } => &caller.tid,
+            node => &node.get_sub().tid,
+        };
+        let callee = &graph[edge.target()].get_sub().tid;
+        let [caller_node, callee_node] = [caller, callee].map(|sub| {
+            *sub_to_node_map
+                .entry(sub)
+                .or_insert_with(|| call_graph.add_node(sub))
+        });
+        call_graph.add_edge(caller_node, callee_node, ());
+        calls.push((caller_node, callee_node, &call.tid));
+    }
+    let mut node_to_component_map = HashMap::new();
+    for (component, nodes) in petgraph::algo::tarjan_scc(&call_graph)
+        .into_iter()
+        .enumerate()
+    {
+        for node in nodes {
+            node_to_component_map.insert(node, component);
+        }
+    }
+    calls
+        .into_iter()
+        .filter(|(caller, callee, _)| {
+            node_to_component_map[caller] == node_to_component_map[callee]
+        })
+        .map(|(_, _, call_tid)| call_tid.clone())
+        .collect()
+}
+
 impl ToJsonCompact for Graph<'_> {
     fn to_json_compact(&self) -> serde_json::Value {
         let mut map = serde_json::Map::new();
@@ -625,6 +819,7 @@ impl ToJsonCompact for Graph<'_> {
         let mut cr_return_stub_edges = 0u64;
         let mut call_combine_edges = 0u64;
         let mut return_combine_edges = 0u64;
+        let mut exception_handler_edges = 0u64;
 
         for edge in self.edge_references() {
             edges_map.insert(
@@ -640,6 +835,7 @@ impl ToJsonCompact for Graph<'_> {
                 Edge::CrReturnStub => cr_return_stub_edges += 1,
                 Edge::CallCombine(..) => call_combine_edges += 1,
                 Edge::ReturnCombine(..) => return_combine_edges += 1,
+                Edge::ExceptionHandler(..) => exception_handler_edges += 1,
             }
         }
 
@@ -652,6 +848,7 @@ impl ToJsonCompact for Graph<'_> {
         edge_counts_map.insert("cr_return_stub".into(), cr_return_stub_edges.into());
         edge_counts_map.insert("call_combine".into(), call_combine_edges.into());
         edge_counts_map.insert("return_combine".into(), return_combine_edges.into());
+        edge_counts_map.insert("exception_handler".into(), exception_handler_edges.into());
 
         map.insert("node_counts".into(), node_counts_map.into());
         map.insert("edge_counts".into(), edge_counts_map.into());
@@ -752,6 +949,117 @@ mod tests {
         assert_eq!(graph.edge_count(), 20);
     }
 
+    #[test]
+    fn tail_calls_and_non_returning_calls() {
+        let mut program = mock_program();
+        // Replace the return of `sub2` by a tail call to `sub1`.
+        let sub2 = program.term.subs.get_mut(&Tid::new("sub2")).unwrap();
+        sub2.term.blocks[1].term.jmps[0].term = Jmp::Branch(Tid::new("sub1_blk1"));
+        let graph = get_program_cfg(&program);
+        // The jump edge is kept and a CallSource node with two edges is added.
+        // The calls to `sub2` do not get return edges anymore.
+        assert_eq!(graph.node_count(), 15);
+        assert_eq!(graph.edge_count(), 17);
+        let tail_call_edges = graph
+            .edge_references()
+            .filter(|edge| match edge.weight() {
+                Edge::Call(jmp) => matches!(jmp.term, Jmp::Branch(_)),
+                _ => false,
+            })
+            .count();
+        assert_eq!(tail_call_edges, 1);
+
+        let mut program = mock_program();
+        program
+            .term
+            .subs
+            .get_mut(&Tid::new("sub2"))
+            .unwrap()
+            .term
+            .mark_non_returning();
+        let graph = get_program_cfg(&program);
+        assert!(!graph
+            .node_weights()
+            .any(|node| matches!(node, Node::CallReturn { .. })));
+    }
+
+    #[test]
+    fn recursive_calls() {
+        // The conditional jump from `sub2` to `sub1_blk1` inlines the call to `sub2` into `sub2`.
+        let mut program = mock_program();
+        let graph = get_program_cfg(&program);
+        assert_eq!(
+            get_recursive_call_tids(&graph),
+            HashSet::from([Tid::new("call")])
+        );
+
+        // Without the conditional jump no function is recursive.
+        let sub2 = program.term.subs.get_mut(&Tid::new("sub2")).unwrap();
+        sub2.term.blocks[0].term.jmps.remove(0);
+        let graph = get_program_cfg(&program);
+        assert!(get_recursive_call_tids(&graph).is_empty());
+
+        // Let `sub2` call itself.
+        let sub2 = program.term.subs.get_mut(&Tid::new("sub2")).unwrap();
+        sub2.term.blocks[0].term.jmps[0] = Term {
+            tid: Tid::new("recursive_call"),
+            term: Jmp::Call {
+                target: Tid::new("sub2"),
+                return_: Some(Tid::new("sub2_blk2")),
+            },
+        };
+        let graph = get_program_cfg(&program);
+        assert_eq!(
+            get_recursive_call_tids(&graph),
+            HashSet::from([Tid::new("recursive_call")])
+        );
+    }
+
+    #[test]
+    fn indirect_calls_with_known_targets() {
+        let mut program = mock_program();
+        let sub1 = program.term.subs.get_mut(&Tid::new("sub1")).unwrap();
+        let call_block = &mut sub1.term.blocks[0].term;
+        call_block.jmps[0].term = Jmp::CallInd {
+            target: expr!("RAX:8"),
+            return_: Some(Tid::new("sub1_blk2")),
+        };
+        call_block.set_ind_call_targets(vec![Tid::new("sub2")]);
+        let graph = get_program_cfg(&program);
+        // Compared to the direct call, a call stub edge is added in addition to the call edges.
+        // Since the calling block is also the target of a jump in `sub2`, this happens twice.
+        assert_eq!(graph.node_count(), 16);
+        assert_eq!(graph.edge_count(), 22);
+        assert!(graph.edge_references().any(|edge| matches!(
+            edge.weight(),
+            Edge::Call(Term {
+                term: Jmp::CallInd { .. },
+                ..
+            })
+        )));
+    }
+
+    #[test]
+    fn exception_handler_edges() {
+        let mut program = mock_program();
+        let sub1 = program.term.subs.get_mut(&Tid::new("sub1")).unwrap();
+        let handler = Term {
+            tid: Tid::new("sub1_handler"),
+            term: Blk::default(),
+        };
+        sub1.term.blocks.push(handler);
+        sub1.term.blocks[0]
+            .term
+            .set_exception_handler(Tid::new("sub1_handler"));
+        let graph = get_program_cfg(&program);
+        let handler_edges = graph
+            .edge_references()
+            .filter(|edge| matches!(edge.weight(), Edge::ExceptionHandler(_)))
+            .count();
+        // The calling block is also contained in `sub2`, but the handler block is not.
+        assert_eq!(handler_edges, 1);
+    }
+
     #[test]
     fn add_indirect_jumps() {
         let indirect_jmp_term = Term {
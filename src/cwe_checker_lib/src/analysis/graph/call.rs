@@ -1,13 +1,24 @@
 //! Call graphs.
+//!
+//! The call graph contains a node for every internal and external function of
+//! the program. Edges represent direct calls and indirect calls whose targets
+//! have been resolved.
+//!
+//! Checkers can query the graph for the functions that are reachable from a
+//! function and for the dominators of functions, i.e., the functions that lie
+//! on every call path from a root function. For visualization the graph can be
+//! exported to DOT and JSON.
 use crate::analysis::graph::intraprocedural_cfg::IntraproceduralCfg;
 use crate::intermediate_representation::{Jmp, Program, Sub, Term, Tid};
 use crate::utils::debug::ToJsonCompact;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
+use std::fmt::Write;
 
+use petgraph::algo::{dominators, has_path_connecting};
 use petgraph::graph::{DiGraph, NodeIndex};
-use petgraph::visit::{EdgeRef, IntoNodeReferences};
+use petgraph::visit::{Dfs, EdgeRef, IntoNodeReferences};
 use petgraph::Direction;
 
 /// Whole-program call graph.
@@ -105,6 +116,109 @@ impl<'a> CallGraph<'a> {
                 (&self.graph[target], e_ref.weight())
             })
     }
+
+    /// Returns the TIDs of all functions that are reachable from the function
+    /// `f` via calls, including `f` itself.
+    ///
+    /// Returns an empty set if `f` is not a function of the program.
+    pub fn reachable_functions(&self, f: &Tid) -> BTreeSet<&'a Tid> {
+        let mut reachable = BTreeSet::new();
+        let Some(fn_idx) = self.fn_tid_to_idx_map.get(f) else {
+            return reachable;
+        };
+        let mut dfs = Dfs::new(&self.graph, *fn_idx);
+
+        while let Some(idx) = dfs.next(&self.graph) {
+            reachable.insert(self.graph[idx].tid());
+        }
+
+        reachable
+    }
+
+    /// Returns true iff the function `g` is reachable from the function `f`
+    /// via calls.
+    ///
+    /// Every function is reachable from itself. Returns false if `f` or `g`
+    /// is not a function of the program.
+    pub fn is_reachable(&self, f: &Tid, g: &Tid) -> bool {
+        let (Some(f_idx), Some(g_idx)) =
+            (self.fn_tid_to_idx_map.get(f), self.fn_tid_to_idx_map.get(g))
+        else {
+            return false;
+        };
+
+        has_path_connecting(&self.graph, *f_idx, *g_idx, None)
+    }
+
+    /// Returns a mapping that takes each function that is reachable from the
+    /// function `root` to the set of its dominators.
+    ///
+    /// A function `g` dominates a function `f` if every call path from `root`
+    /// to `f` passes through `g`. Every function dominates itself. Returns an
+    /// empty mapping if `root` is not a function of the program.
+    pub fn dominators(&self, root: &Tid) -> BTreeMap<&'a Tid, BTreeSet<&'a Tid>> {
+        let Some(root_idx) = self.fn_tid_to_idx_map.get(root) else {
+            return BTreeMap::new();
+        };
+        let doms = dominators::simple_fast(&self.graph, *root_idx);
+
+        self.graph
+            .node_indices()
+            .filter_map(|idx| {
+                let dominators = doms
+                    .dominators(idx)?
+                    .map(|dom_idx| self.graph[dom_idx].tid())
+                    .collect::<BTreeSet<&'a Tid>>();
+
+                Some((self.graph[idx].tid(), dominators))
+            })
+            .collect()
+    }
+
+    /// Returns a DOT representation of this call graph.
+    ///
+    /// External functions are drawn as boxes. Edges that only represent
+    /// indirect calls are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph callgraph {\n");
+
+        for (idx, node) in self.graph.node_references() {
+            let shape = if node.is_external() { "box" } else { "ellipse" };
+            writeln!(
+                dot,
+                "    {} [label=\"{}\", shape={}];",
+                idx.index(),
+                escape_dot_label(&node.to_string()),
+                shape
+            )
+            .unwrap();
+        }
+        for edge in self.graph.edge_references() {
+            let style = if edge.weight().direct_call_sites.is_empty() {
+                "dashed"
+            } else {
+                "solid"
+            };
+            writeln!(
+                dot,
+                "    {} -> {} [label=\"{}\", style={}];",
+                edge.source().index(),
+                edge.target().index(),
+                escape_dot_label(&edge.weight().to_string()),
+                style
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+/// Escapes the characters of a DOT label that have a special meaning inside of
+/// a quoted string.
+fn escape_dot_label(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
 /// Call graph node.
@@ -133,6 +247,14 @@ impl fmt::Display for CgNode<'_> {
 }
 
 impl<'a> CgNode<'a> {
+    /// Returns the TID of the function that corresponds to this node.
+    pub fn tid(&self) -> &'a Tid {
+        match self {
+            CgNode::Function(Term { tid, .. }, _) => tid,
+            CgNode::ExtFunction(tid) => tid,
+        }
+    }
+
     /// Returns true iff this node corresponds to an external function.
     pub fn is_external(&self) -> bool {
         matches!(self, CgNode::ExtFunction(..))
@@ -196,9 +318,14 @@ impl<'a> CgEdge<'a> {
         (self.direct_call_sites.len() + self.indirect_call_sites.len()) as u64
     }
 
-    /// Analyzes the given CFG to construct a mapping from callee `Tid`s to the
-    /// corresponding call graph edges.
-    fn analyze_callees(cfg: &IntraproceduralCfg<'a>) -> HashMap<&'a Tid, CgEdge<'a>> {
+    /// Analyzes the given function and its CFG to construct a mapping from
+    /// callee `Tid`s to the corresponding call graph edges.
+    ///
+    /// Indirect calls are only considered if their targets have been resolved.
+    fn analyze_callees(
+        f: &'a Term<Sub>,
+        cfg: &IntraproceduralCfg<'a>,
+    ) -> HashMap<&'a Tid, CgEdge<'a>> {
         let mut callee_tid_to_edge_map: HashMap<&'a Tid, CgEdge<'a>> = HashMap::new();
 
         for (callee_tid, call_site) in cfg.call_sites().flat_map(|(start_idx, _)| {
@@ -214,6 +341,20 @@ impl<'a> CgEdge<'a> {
                 .add_call_site(call_site);
         }
 
+        for blk in f.blocks() {
+            let Some(ind_call_targets) = blk.ind_call_targets() else {
+                continue;
+            };
+            let call_site = CallSite::from_insn(blk.jmps.first().unwrap());
+
+            for callee_tid in ind_call_targets {
+                callee_tid_to_edge_map
+                    .entry(callee_tid)
+                    .or_default()
+                    .add_call_site(call_site.clone());
+            }
+        }
+
         callee_tid_to_edge_map
     }
 }
@@ -299,7 +440,7 @@ impl<'a> CallGraphBuilder<'a> {
             }
 
             // Remember edges we need to add later.
-            fn_tid_to_callees_map.push((fn_tid, CgEdge::analyze_callees(&cfg)));
+            fn_tid_to_callees_map.push((fn_tid, CgEdge::analyze_callees(fn_term, &cfg)));
 
             let idx = self
                 .graph
@@ -323,3 +464,143 @@ impl<'a> CallGraphBuilder<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::*;
+    use crate::variable;
+
+    /// Mocks a function that calls the given targets one after another.
+    ///
+    /// Targets prefixed with `*` are called indirectly.
+    fn mock_fn(name: &str, call_targets: &[&str]) -> Term<Sub> {
+        let mut f = Sub::mock(name);
+        for (i, target) in call_targets.iter().enumerate() {
+            let mut blk = Blk::mock_with_tid(&format!("{name}_blk_{i}"));
+            let return_ = Some(Tid::new(format!("{name}_blk_{}", i + 1)));
+            let call = match target.strip_prefix('*') {
+                Some(_) => Jmp::CallInd {
+                    target: Expression::Var(variable!("RAX:8")),
+                    return_,
+                },
+                None => Jmp::Call {
+                    target: Tid::new(target),
+                    return_,
+                },
+            };
+            blk.term.jmps.push(Term {
+                tid: Tid::new(format!("{name}_call_{i}")),
+                term: call,
+            });
+            if let Some(target) = target.strip_prefix('*') {
+                blk.term.set_ind_call_targets([Tid::new(target)]);
+            }
+            f.term.blocks.push(blk);
+        }
+        let mut ret = Blk::mock_with_tid(&format!("{name}_blk_{}", call_targets.len()));
+        ret.term.jmps.push(Term {
+            tid: Tid::new(format!("{name}_ret")),
+            term: Jmp::Return(Expression::Var(variable!("RSP:8"))),
+        });
+        f.term.blocks.push(ret);
+
+        f
+    }
+
+    /// Mocks a program with the call graph
+    /// `main -> {a, *b}`, `a -> {c, malloc}`, `b -> c`, `c -> a`.
+    fn mock_program() -> Program {
+        let mut p = Program::mock_x64();
+        let malloc = p
+            .extern_symbols
+            .values()
+            .find(|symbol| symbol.name == "malloc")
+            .unwrap()
+            .tid
+            .to_string();
+        for f in [
+            mock_fn("main", &["a", "*b"]),
+            mock_fn("a", &["c", &malloc]),
+            mock_fn("b", &["c"]),
+            mock_fn("c", &["a"]),
+        ] {
+            p.subs.insert(f.tid.clone(), f);
+        }
+
+        p
+    }
+
+    #[test]
+    fn resolved_indirect_calls() {
+        let p = mock_program();
+        let cg = CallGraph::new(&p);
+
+        let (callee, edge) = cg
+            .callees(&Tid::new("main"))
+            .find(|(callee, _)| callee.tid() == &Tid::new("b"))
+            .unwrap();
+        assert!(!callee.is_external());
+        assert_eq!(edge.num_cs(), 1);
+        assert!(edge.indirect_call_sites().all(CallSite::is_indirect));
+        assert_eq!(edge.to_string(), "i:main_call_1,");
+        assert_eq!(cg.callers(&Tid::new("c")).count(), 2);
+    }
+
+    #[test]
+    fn reachability() {
+        let p = mock_program();
+        let cg = CallGraph::new(&p);
+        let [main, a, b, c] = ["main", "a", "b", "c"].map(Tid::new);
+
+        let reachable = cg.reachable_functions(&b);
+        assert_eq!(reachable.len(), 4);
+        assert!(reachable.contains(&b));
+        assert!(reachable.contains(&c));
+        assert!(reachable.contains(&a));
+        assert!(!reachable.contains(&main));
+        assert!(cg.is_reachable(&main, &b));
+        assert!(cg.is_reachable(&c, &c));
+        assert!(!cg.is_reachable(&a, &b));
+
+        let unknown = Tid::new("unknown");
+        assert!(cg.reachable_functions(&unknown).is_empty());
+        assert!(!cg.is_reachable(&unknown, &b));
+        assert!(!cg.is_reachable(&main, &unknown));
+    }
+
+    #[test]
+    fn call_graph_dominators() {
+        let p = mock_program();
+        let cg = CallGraph::new(&p);
+        let [main, a, b, c] = ["main", "a", "b", "c"].map(Tid::new);
+
+        let doms = cg.dominators(&main);
+        assert_eq!(doms[&main], BTreeSet::from([&main]));
+        assert_eq!(doms[&a], BTreeSet::from([&main, &a]));
+        assert_eq!(doms[&b], BTreeSet::from([&main, &b]));
+        assert_eq!(doms[&c], BTreeSet::from([&main, &c]));
+        assert_eq!(doms.len(), 5);
+
+        let doms = cg.dominators(&b);
+        assert_eq!(doms[&a], BTreeSet::from([&b, &c, &a]));
+        assert!(!doms.contains_key(&main));
+
+        assert!(cg.dominators(&Tid::new("unknown")).is_empty());
+    }
+
+    #[test]
+    fn dot_export() {
+        let p = mock_program();
+        let cg = CallGraph::new(&p);
+        let dot = cg.to_dot();
+
+        assert!(dot.starts_with("digraph callgraph {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("[label=\"main\", shape=ellipse];"));
+        assert!(dot.contains("shape=box];"));
+        assert!(dot.contains("[label=\"i:main_call_1,\", style=dashed];"));
+        assert!(dot.contains("[label=\"d:main_call_0,\", style=solid];"));
+        assert_eq!(escape_dot_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
@@ -0,0 +1,558 @@
+//! Provenance of user input that reaches sink calls.
+//!
+//! Checks like the ones for CWE-78 and CWE-134 know that the parameter of a sink call
+//! may be controlled by the user, but not through which input channel.
+//! This module tracks user input from its [sources](InputSources) to the sink calls
+//! so that findings can be annotated with a [`ReproductionHint`],
+//! e.g. for generating fuzzing harnesses that reproduce them.
+//!
+//! For each source a taint analysis is run, where the taint marks the start of the input in memory.
+//! The following input channels are tracked:
+//! - Environment variables returned by the string symbols of the [`InputSources`], e.g. `getenv`.
+//!   The name of the variable is known if it is a constant string.
+//! - The program arguments pointed to by the argument vector of the program argument functions, e.g. `main`.
+//! - Data read by the buffer symbols of the [`InputSources`] from file descriptor 0, i.e. stdin.
+//!
+//! Taint is propagated into the callees of the function containing the source,
+//! back to its callers through return values and memory objects known to the caller,
+//! and into the destination buffers of string copy functions.
+//! At a sink call the offset of the parameter from the start of the tainted data is computed.
+//! For input that was copied before reaching the sink, the offset is relative to the start of the copy.
+
+use crate::abstract_domain::{
+    AbstractIdentifier, AbstractLocation, AbstractMemoryLocation, IntervalDomain, TryToBitvec,
+};
+use crate::analysis::forward_interprocedural_fixpoint::create_computation;
+use crate::analysis::graph::{Edge, Graph, HasCfg, Node, NodeIndex};
+use crate::analysis::input_sources::InputSources;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::library_summaries::LibrarySummaries;
+use crate::analysis::pointer_inference::{Data as PiData, PointerInference};
+use crate::analysis::taint::state::State as TaintState;
+use crate::analysis::taint::{Taint, TaintAnalysis, TaintSource};
+use crate::analysis::vsa_results::{HasVsaResult, VsaResult};
+use crate::intermediate_representation::*;
+use crate::pipeline::AnalysisResults;
+use crate::prelude::*;
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Extern symbols that copy the string given by their second parameter
+/// into the buffer given by their first parameter.
+const PROPAGATION_SYMBOLS: [&str; 8] = [
+    "memcpy", "memmove", "sprintf", "snprintf", "strcat", "strcpy", "strncat", "strncpy",
+];
+
+/// The channel through which user input enters the program.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum InputChannel {
+    /// An environment variable.
+    Environment,
+    /// A program argument.
+    ProgramArguments,
+    /// The standard input of the program.
+    Stdin,
+}
+
+impl std::fmt::Display for InputChannel {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputChannel::Environment => write!(formatter, "env"),
+            InputChannel::ProgramArguments => write!(formatter, "argv"),
+            InputChannel::Stdin => write!(formatter, "stdin"),
+        }
+    }
+}
+
+/// The origin of user input that reaches the parameter of a sink call.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct InputOrigin {
+    /// The input channel.
+    pub channel: InputChannel,
+    /// The name of the environment variable or the program argument, e.g. `argv[1]`, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// The address of the source call or of the function receiving the program arguments.
+    pub source_address: String,
+    /// The offset of the sink parameter from the start of the input, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_offset: Option<i64>,
+}
+
+/// A description of the input that may trigger a CWE warning.
+///
+/// Meant to be consumed by tools that generate fuzzing harnesses or test cases for findings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct ReproductionHint {
+    /// Where the input enters the program.
+    #[serde(flatten)]
+    pub origin: InputOrigin,
+    /// Known constant content that precedes the input in the sink parameter.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prefixes: Vec<String>,
+    /// Example content of the input that exploits the sink.
+    pub payload: String,
+}
+
+impl ReproductionHint {
+    /// Create a new hint for input from the given origin.
+    pub fn new(origin: InputOrigin, prefixes: Vec<String>, payload: &str) -> ReproductionHint {
+        ReproductionHint {
+            origin,
+            prefixes,
+            payload: payload.to_string(),
+        }
+    }
+}
+
+/// A call to an input source or a function receiving the program arguments,
+/// together with the taint state at the start of the taint analysis for it.
+struct SourceCall {
+    /// The input channel.
+    channel: InputChannel,
+    /// The name of the environment variable or the program argument, if known.
+    key: Option<String>,
+    /// The address of the source.
+    address: String,
+    /// The node where the taint analysis starts.
+    node: NodeIndex,
+    /// The taint state at the start node.
+    state: TaintState,
+}
+
+/// Get the origins of user input that may reach the given parameters of the sink calls.
+///
+/// The `sinks` map the TIDs of calls to extern symbols to the checked parameters of the call.
+/// Returns the input origins for each sink call that is reached by user input.
+pub fn get_input_origins_at_sinks(
+    analysis_results: &AnalysisResults,
+    input_sources: &InputSources,
+    sinks: &BTreeMap<Tid, Vec<Arg>>,
+) -> BTreeMap<Tid, BTreeSet<InputOrigin>> {
+    let Some(pi_result) = analysis_results.pointer_inference else {
+        return BTreeMap::new();
+    };
+    let mut origins: BTreeMap<Tid, BTreeSet<InputOrigin>> = BTreeMap::new();
+    if sinks.is_empty() {
+        return origins;
+    }
+    for source in get_source_calls(analysis_results.project, pi_result, input_sources) {
+        let (sink_sender, sink_receiver) = crossbeam_channel::unbounded();
+        let context = Context {
+            project: analysis_results.project,
+            pi_result,
+            sinks,
+            library_summaries: analysis_results.library_summaries,
+            sink_sender,
+        };
+        let mut computation = create_computation(context, None);
+        computation.set_node_value(source.node, NodeValue::Value(source.state));
+        computation.compute_with_max_steps(100);
+
+        for (sink_tid, input_offset) in sink_receiver.try_iter() {
+            origins.entry(sink_tid).or_default().insert(InputOrigin {
+                channel: source.channel,
+                key: source.key.clone(),
+                source_address: source.address.clone(),
+                input_offset,
+            });
+        }
+    }
+
+    origins
+}
+
+/// Find all calls to input sources and all functions receiving the program arguments
+/// and compute the start states of the taint analyses for them.
+fn get_source_calls(
+    project: &Project,
+    pi_result: &PointerInference,
+    input_sources: &InputSources,
+) -> Vec<SourceCall> {
+    let graph = pi_result.get_graph();
+    let mut sources = Vec::new();
+    for edge in graph.edge_references() {
+        let Edge::ExternCallStub(jmp) = edge.weight() else {
+            continue;
+        };
+        let Jmp::Call { target, .. } = &jmp.term else {
+            continue;
+        };
+        let Some(symbol) = project.program.term.extern_symbols.get(target) else {
+            continue;
+        };
+        let mut state = TaintState::new_empty();
+        let (channel, key) = if input_sources.is_string_source(&symbol.name) {
+            let Some(input) = symbol
+                .get_unique_return_register()
+                .ok()
+                .and_then(|register| {
+                    pi_result.eval_at_node(edge.target(), &Expression::Var(register.clone()))
+                })
+            else {
+                continue;
+            };
+            state.save_taint_to_memory(&input, new_input_taint(TaintSource::Environment));
+            let key = get_constant_string_parameter(project, pi_result, symbol, &jmp.tid);
+            (InputChannel::Environment, key)
+        } else if let Some(buffer_source) = input_sources.get_buffer_source(&symbol.name) {
+            // Only calls like `read(0, buf, size)` are known to read from stdin.
+            let reads_stdin = buffer_source.label == TaintSource::File
+                && buffer_source.buffer_index != 0
+                && symbol
+                    .parameters
+                    .first()
+                    .and_then(|param| pi_result.eval_parameter_arg_at_call(&jmp.tid, param))
+                    .and_then(|fd| fd.get_if_absolute_value()?.try_to_offset().ok())
+                    == Some(0);
+            let Some(buffer) = symbol
+                .parameters
+                .get(buffer_source.buffer_index)
+                .filter(|_| reads_stdin)
+                .and_then(|param| pi_result.eval_parameter_arg_at_call(&jmp.tid, param))
+            else {
+                continue;
+            };
+            state.save_taint_to_memory(&buffer, new_input_taint(TaintSource::File));
+            (InputChannel::Stdin, None)
+        } else {
+            continue;
+        };
+        sources.push(SourceCall {
+            channel,
+            key,
+            address: jmp.tid.address().to_string(),
+            node: edge.target(),
+            state,
+        });
+    }
+    sources.append(&mut get_program_argument_sources(
+        project,
+        graph,
+        pi_result,
+        input_sources,
+    ));
+
+    sources
+}
+
+/// Get the program arguments that the configured program argument functions access.
+///
+/// For each program argument that the pointer inference tracks at the start of such a function
+/// a separate source is returned, so that the index of the argument is known at the sinks.
+fn get_program_argument_sources(
+    project: &Project,
+    graph: &Graph,
+    pi_result: &PointerInference,
+    input_sources: &InputSources,
+) -> Vec<SourceCall> {
+    let mut sources = Vec::new();
+    for node in graph.node_indices() {
+        let Node::BlkStart(blk, sub) = graph[node] else {
+            continue;
+        };
+        if sub.term.blocks.first().map(|first| &first.tid) != Some(&blk.tid) {
+            continue;
+        }
+        let Some(NodeValue::Value(pi_state)) = pi_result.get_node_value(node) else {
+            continue;
+        };
+        let arguments = pi_state
+            .memory
+            .get_all_object_ids()
+            .into_iter()
+            .filter(|id| input_sources.is_program_argument(project, sub, id));
+        for argument in arguments {
+            let mut state = TaintState::new_empty();
+            state.save_taint_to_memory(
+                &PiData::from_target(
+                    argument.clone(),
+                    IntervalDomain::from(Bitvector::zero(project.get_pointer_bytesize().into())),
+                ),
+                new_input_taint(TaintSource::ProgramArguments),
+            );
+            sources.push(SourceCall {
+                channel: InputChannel::ProgramArguments,
+                key: get_program_argument_name(project, &argument),
+                address: sub.tid.address().to_string(),
+                node,
+                state,
+            });
+        }
+    }
+
+    sources
+}
+
+/// Get the name of the program argument represented by the given abstract identifier, e.g. `argv[1]`.
+fn get_program_argument_name(project: &Project, argument: &AbstractIdentifier) -> Option<String> {
+    let AbstractLocation::Pointer(_, AbstractMemoryLocation::Location { offset, .. }) =
+        argument.get_location()
+    else {
+        return None;
+    };
+    let pointer_size = u64::from(project.get_pointer_bytesize()) as i64;
+
+    Some(format!("argv[{}]", offset / pointer_size))
+}
+
+/// Get the constant string passed as the first parameter of the given call, e.g. the name of an environment variable.
+fn get_constant_string_parameter(
+    project: &Project,
+    pi_result: &PointerInference,
+    symbol: &ExternSymbol,
+    call_tid: &Tid,
+) -> Option<String> {
+    let address = pi_result
+        .eval_parameter_arg_at_call(call_tid, symbol.parameters.first()?)?
+        .get_if_absolute_value()?
+        .try_to_bitvec()
+        .ok()?;
+
+    project
+        .runtime_memory_image
+        .read_string_until_null_terminator(&address)
+        .ok()
+        .map(|string| string.to_string())
+}
+
+/// Create the taint that marks the first byte of user input.
+fn new_input_taint(label: TaintSource) -> Taint {
+    Taint::new_tainted(ByteSize::new(1), label)
+}
+
+/// The context of the taint analysis for a single source of user input.
+struct Context<'a, 'b> {
+    project: &'a Project,
+    pi_result: &'a PointerInference<'b>,
+    /// The checked parameters of the sink calls.
+    sinks: &'a BTreeMap<Tid, Vec<Arg>>,
+    library_summaries: Option<&'a LibrarySummaries>,
+    /// Used to send the TIDs of sink calls reached by the input
+    /// together with the offsets of the parameters into the input.
+    sink_sender: crossbeam_channel::Sender<(Tid, Option<i64>)>,
+}
+
+impl Context<'_, '_> {
+    /// Get the taint of the given parameter of a call.
+    ///
+    /// Returns `None` if the parameter is not tainted.
+    /// Otherwise the offset of the parameter into the tainted data is returned if the parameter points to it.
+    fn get_parameter_taint(
+        &self,
+        state: &TaintState,
+        parameter: &Arg,
+        call_tid: &Tid,
+    ) -> Option<Option<i64>> {
+        if let Arg::Register { expr, .. } = parameter {
+            if state.eval(expr).is_tainted() {
+                return Some(None);
+            }
+        }
+        let value = self
+            .pi_result
+            .eval_parameter_arg_at_call(call_tid, parameter)?;
+
+        state.get_offset_to_taint(&value).map(Some)
+    }
+}
+
+impl<'a> HasCfg<'a> for Context<'a, '_> {
+    fn get_cfg(&self) -> &Graph<'a> {
+        self.pi_result.get_graph()
+    }
+}
+
+impl HasVsaResult<PiData> for Context<'_, '_> {
+    fn vsa_result(&self) -> &impl VsaResult<ValueDomain = PiData> {
+        self.pi_result
+    }
+}
+
+impl AsRef<Project> for Context<'_, '_> {
+    fn as_ref(&self) -> &Project {
+        self.project
+    }
+}
+
+impl<'a> TaintAnalysis<'a> for Context<'a, '_> {
+    fn get_library_summaries(&self) -> Option<&LibrarySummaries> {
+        self.library_summaries
+    }
+
+    /// Propagate the taint into the called function.
+    fn update_call(
+        &self,
+        state: &TaintState,
+        call: &Term<Jmp>,
+        _target: &Node,
+        _calling_convention: &Option<String>,
+    ) -> Option<TaintState> {
+        let callee_state = state.new_callee_state(self.pi_result.get_call_renaming_map(&call.tid));
+        if callee_state.is_empty() {
+            None
+        } else {
+            Some(callee_state)
+        }
+    }
+
+    /// Report sink calls reached by the input and propagate the taint through string copy functions.
+    fn update_extern_call(
+        &self,
+        state: &TaintState,
+        call: &Term<Jmp>,
+        project: &Project,
+        extern_symbol: &ExternSymbol,
+    ) -> Option<TaintState> {
+        if let Some(parameters) = self.sinks.get(&call.tid) {
+            for parameter in parameters {
+                if let Some(input_offset) = self.get_parameter_taint(state, parameter, &call.tid) {
+                    self.sink_sender
+                        .send((call.tid.clone(), input_offset))
+                        .expect("failed to send sink call");
+                }
+            }
+        }
+        if extern_symbol.no_return {
+            return None;
+        }
+        let mut new_state = state.clone();
+        if PROPAGATION_SYMBOLS.contains(&extern_symbol.name.as_str()) {
+            let is_tainted = extern_symbol
+                .parameters
+                .iter()
+                .skip(1)
+                .any(|param| self.get_parameter_taint(state, param, &call.tid).is_some());
+            if let (true, Some(destination)) = (
+                is_tainted,
+                extern_symbol
+                    .parameters
+                    .first()
+                    .and_then(|param| self.pi_result.eval_parameter_arg_at_call(&call.tid, param)),
+            ) {
+                new_state.save_taint_to_memory(&destination, new_input_taint(TaintSource::Custom));
+            }
+        }
+        new_state.remove_non_callee_saved_taint(project.get_calling_convention(extern_symbol));
+
+        Some(new_state)
+    }
+
+    /// Propagate the taint of return registers and of memory objects known to the caller back to the caller.
+    fn update_return_callee(
+        &self,
+        state: &TaintState,
+        call_term: &Term<Jmp>,
+        _return_term: &Term<Jmp>,
+        calling_convention: &Option<String>,
+    ) -> Option<TaintState> {
+        let (mut register_taint, mut memory_taint) = state.clone().into_mem_reg_taint();
+        let renaming_map = self.pi_result.get_call_renaming_map(&call_term.tid);
+        memory_taint.retain(|aid, _| {
+            renaming_map.is_some_and(|renaming_map| {
+                renaming_map
+                    .get(aid)
+                    .is_some_and(|value| value.referenced_ids().next().is_some())
+            })
+        });
+        let return_registers = self
+            .project
+            .get_specific_calling_convention(calling_convention)
+            .map(|calling_convention| calling_convention.get_all_return_register())
+            .unwrap_or_default();
+        register_taint
+            .retain(|register, taint| return_registers.contains(&register) && taint.is_tainted());
+
+        Some(TaintState::from_mem_reg_taint(register_taint, memory_taint))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::{AbstractDomain, SizedDomain};
+
+    #[test]
+    fn offset_to_input() {
+        let id = AbstractIdentifier::mock("getenv", "RAX", 8);
+        let pointer = |offset: i64| {
+            PiData::from_target(
+                id.clone(),
+                IntervalDomain::from(Bitvector::from_i64(offset)),
+            )
+        };
+        let mut state = TaintState::new_empty();
+        state.save_taint_to_memory(&pointer(4), new_input_taint(TaintSource::Environment));
+
+        assert_eq!(state.get_offset_to_taint(&pointer(4)), Some(0));
+        assert_eq!(state.get_offset_to_taint(&pointer(10)), Some(6));
+        assert_eq!(state.get_offset_to_taint(&pointer(2)), None);
+        assert_eq!(
+            state.get_offset_to_taint(&pointer(4).merge(&PiData::new_top(ByteSize::new(8)))),
+            Some(0)
+        );
+        let other_id = AbstractIdentifier::mock("main", "RDI", 8);
+        assert_eq!(
+            state.get_offset_to_taint(&PiData::from_target(
+                other_id.clone(),
+                IntervalDomain::from(Bitvector::from_i64(4))
+            )),
+            None
+        );
+
+        let renaming_map = BTreeMap::from([(other_id.clone(), pointer(2))]);
+        let callee_state = state.new_callee_state(Some(&renaming_map));
+        assert_eq!(
+            callee_state.get_offset_to_taint(&PiData::from_target(
+                other_id,
+                IntervalDomain::from(Bitvector::from_i64(2))
+            )),
+            Some(0)
+        );
+        assert!(state.new_callee_state(None).is_empty());
+    }
+
+    #[test]
+    fn program_argument_name() {
+        let project = Project::mock_x64();
+        let argument = AbstractIdentifier::new(
+            Tid::new("main"),
+            AbstractLocation::Pointer(
+                crate::variable!("RSI:8"),
+                AbstractMemoryLocation::Location {
+                    offset: 16,
+                    size: ByteSize::new(8),
+                },
+            ),
+        );
+        assert_eq!(
+            get_program_argument_name(&project, &argument),
+            Some("argv[2]".to_string())
+        );
+        let register = AbstractIdentifier::mock("main", "RSI", 8);
+        assert_eq!(get_program_argument_name(&project, &register), None);
+    }
+
+    #[test]
+    fn serialize_hint() {
+        let origin = InputOrigin {
+            channel: InputChannel::Environment,
+            key: Some("CMD".to_string()),
+            source_address: "00101234".to_string(),
+            input_offset: Some(0),
+        };
+        let hint = ReproductionHint::new(origin, vec!["ls ".to_string()], "; id #");
+        assert_eq!(
+            serde_json::to_value(&hint).unwrap(),
+            serde_json::json!({
+                "channel": "Environment",
+                "key": "CMD",
+                "source_address": "00101234",
+                "input_offset": 0,
+                "prefixes": ["ls "],
+                "payload": "; id #"
+            })
+        );
+        assert_eq!(InputChannel::Stdin.to_string(), "stdin");
+    }
+}
@@ -0,0 +1,346 @@
+//! Sources of user input of a program.
+//!
+//! Environment variables and the program arguments are controlled by the user who starts the program.
+//! The [`InputSources`] configuration describes where these inputs enter the program,
+//! so that the string abstraction can model them as strings with unknown content
+//! and taint analyses can mark them as tainted.
+//!
+//! Four kinds of sources are modeled:
+//! - Extern functions like `getenv` that return a pointer to a string controlled by the user.
+//! - Extern functions like `read` or `fgets` that write user input into a buffer.
+//!   The maximal number of bytes written is given by the size parameters of the call,
+//!   so that checks can reason about the maximal length of the input.
+//! - Extern functions like `getopt` that parse the program arguments
+//!   and return values derived from them.
+//!   Note that the `optarg` variable set by `getopt` is not modeled,
+//!   since the IR does not contain the names of global variables.
+//! - Functions like `main` whose second parameter is the argument vector of the program.
+//!   The strings pointed to by the argument vector are controlled by the user.
+
+use crate::abstract_domain::{
+    AbstractIdentifier, AbstractLocation, AbstractMemoryLocation, SizedDomain, TryToBitvec,
+};
+use crate::analysis::pointer_inference::Data as PiData;
+use crate::analysis::taint::{state::State as TaintState, Taint, TaintSource};
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// The configuration of the sources of user input of a program.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct InputSources {
+    /// Names of extern functions returning a pointer to a string controlled by the user.
+    #[serde(default)]
+    pub string_symbols: Vec<String>,
+    /// Names of extern functions returning values derived from the program arguments.
+    #[serde(default)]
+    pub argument_parsing_symbols: Vec<String>,
+    /// Names of functions whose second parameter is the argument vector of the program.
+    #[serde(default)]
+    pub program_argument_functions: Vec<String>,
+    /// Names of extern functions that write user input into a buffer,
+    /// mapped to the parameters of the buffer and its size.
+    #[serde(default)]
+    pub buffer_symbols: BTreeMap<String, BufferSource>,
+}
+
+/// The parameters of an extern function that writes user input into a buffer.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BufferSource {
+    /// The index of the parameter pointing to the buffer.
+    pub buffer_index: usize,
+    /// The indices of the parameters whose product is the maximal number of bytes written to the buffer.
+    pub size_indices: Vec<usize>,
+    /// The source that the written data originates from.
+    #[serde(default)]
+    pub label: TaintSource,
+    /// Whether the written data is terminated by a null byte
+    /// that counts towards the maximal number of written bytes, e.g. for `fgets`.
+    #[serde(default)]
+    pub null_terminated: bool,
+}
+
+impl BufferSource {
+    /// Get the maximal number of bytes that a call to the extern symbol writes to the buffer.
+    ///
+    /// The `eval_parameter` function computes the value of a parameter at the call.
+    /// Returns `None` if one of the size parameters is not a known constant.
+    pub fn get_max_input_length(
+        &self,
+        extern_symbol: &ExternSymbol,
+        eval_parameter: impl Fn(&Arg) -> Option<PiData>,
+    ) -> Option<u64> {
+        self.size_indices.iter().try_fold(1u64, |length, index| {
+            let size = eval_parameter(extern_symbol.parameters.get(*index)?)?;
+            let size = size.get_if_absolute_value()?.try_to_offset().ok()?;
+            length.checked_mul(u64::try_from(size).ok()?)
+        })
+    }
+
+    /// Get the maximal length of the string that a call to the extern symbol writes to the buffer,
+    /// i.e. the maximal number of written bytes without the terminating null byte.
+    ///
+    /// Returns `None` if one of the size parameters is not a known constant.
+    pub fn get_max_string_length(
+        &self,
+        extern_symbol: &ExternSymbol,
+        eval_parameter: impl Fn(&Arg) -> Option<PiData>,
+    ) -> Option<u64> {
+        let length = self.get_max_input_length(extern_symbol, eval_parameter)?;
+        if self.null_terminated {
+            Some(length.saturating_sub(1))
+        } else {
+            Some(length)
+        }
+    }
+}
+
+impl Default for InputSources {
+    fn default() -> Self {
+        InputSources {
+            string_symbols: vec!["getenv".to_string(), "secure_getenv".to_string()],
+            argument_parsing_symbols: vec![
+                "getopt".to_string(),
+                "getopt_long".to_string(),
+                "getopt_long_only".to_string(),
+            ],
+            program_argument_functions: vec!["main".to_string()],
+            buffer_symbols: [
+                ("read", 1, vec![2], TaintSource::File, false),
+                ("recv", 1, vec![2], TaintSource::Network, false),
+                ("recvfrom", 1, vec![2], TaintSource::Network, false),
+                ("fread", 0, vec![1, 2], TaintSource::File, false),
+                ("fgets", 0, vec![1], TaintSource::File, true),
+            ]
+            .into_iter()
+            .map(
+                |(name, buffer_index, size_indices, label, null_terminated)| {
+                    (
+                        name.to_string(),
+                        BufferSource {
+                            buffer_index,
+                            size_indices,
+                            label,
+                            null_terminated,
+                        },
+                    )
+                },
+            )
+            .collect(),
+        }
+    }
+}
+
+impl InputSources {
+    /// Returns `true` if the extern symbol returns a pointer to a string controlled by the user.
+    pub fn is_string_source(&self, symbol_name: &str) -> bool {
+        self.string_symbols.iter().any(|name| name == symbol_name)
+    }
+
+    /// Returns `true` if the return values of the extern symbol are controlled by the user.
+    pub fn is_source(&self, symbol_name: &str) -> bool {
+        self.is_string_source(symbol_name)
+            || self
+                .argument_parsing_symbols
+                .iter()
+                .any(|name| name == symbol_name)
+    }
+
+    /// Get the parameters of the buffer and its size if the extern symbol writes user input into a buffer.
+    pub fn get_buffer_source(&self, symbol_name: &str) -> Option<&BufferSource> {
+        self.buffer_symbols.get(symbol_name)
+    }
+
+    /// Get the register containing the argument vector of the program at the start of the function.
+    ///
+    /// Returns `None` if the function is not one of the configured program argument functions
+    /// or if its calling convention is unknown.
+    pub fn get_argument_vector_register<'a>(
+        &self,
+        project: &'a Project,
+        sub: &Term<Sub>,
+    ) -> Option<&'a Variable> {
+        if !self.program_argument_functions.contains(&sub.term.name) {
+            return None;
+        }
+        project
+            .get_specific_calling_convention(&sub.term.calling_convention)?
+            .integer_parameter_register
+            .get(1)
+    }
+
+    /// Returns `true` if the abstract identifier represents one of the program arguments,
+    /// i.e. a string pointed to by an entry of the argument vector of the given function.
+    pub fn is_program_argument(
+        &self,
+        project: &Project,
+        sub: &Term<Sub>,
+        id: &AbstractIdentifier,
+    ) -> bool {
+        let Some(argv) = self.get_argument_vector_register(project, sub) else {
+            return false;
+        };
+        id.get_tid() == &sub.tid
+            && matches!(
+                id.get_location(),
+                AbstractLocation::Pointer(var, AbstractMemoryLocation::Location { .. }) if var == argv
+            )
+    }
+
+    /// Taint the return registers of a call to an extern symbol if the symbol is an input source.
+    ///
+    /// The taint is labeled as coming from the environment for string sources
+    /// and as coming from the program arguments for argument parsing functions.
+    /// Returns `true` if the symbol is an input source.
+    pub fn taint_return_values(
+        &self,
+        state: &mut TaintState,
+        extern_symbol: &ExternSymbol,
+    ) -> bool {
+        let label = if self.is_string_source(&extern_symbol.name) {
+            TaintSource::Environment
+        } else if self.is_source(&extern_symbol.name) {
+            TaintSource::ProgramArguments
+        } else {
+            return false;
+        };
+        for return_arg in extern_symbol.return_values.iter() {
+            if let Arg::Register {
+                expr: Expression::Var(var),
+                ..
+            } = return_arg
+            {
+                state.set_register_taint(var, Taint::new_tainted(var.size, label));
+            }
+        }
+        true
+    }
+
+    /// Taint the start of the buffer written by a call to an extern symbol
+    /// if the symbol writes user input into a buffer.
+    ///
+    /// Returns `true` if the symbol writes user input into a buffer.
+    pub fn taint_input_buffer(
+        &self,
+        state: &mut TaintState,
+        vsa_result: &impl VsaResult<ValueDomain = PiData>,
+        call_tid: &Tid,
+        extern_symbol: &ExternSymbol,
+    ) -> bool {
+        let Some(source) = self.get_buffer_source(&extern_symbol.name) else {
+            return false;
+        };
+        if let Some(buffer) = extern_symbol
+            .parameters
+            .get(source.buffer_index)
+            .and_then(|param| vsa_result.eval_parameter_arg_at_call(call_tid, param))
+        {
+            let taint = Taint::new_tainted(buffer.bytesize(), source.label);
+            state.save_taint_to_memory(&buffer, taint);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::RegisterDomain;
+    use crate::variable;
+
+    #[test]
+    fn program_arguments() {
+        let project = Project::mock_x64();
+        let sources = InputSources::default();
+        let mut sub = Term {
+            tid: Tid::new("main"),
+            term: Sub::new::<_, &str>("main", Vec::new(), None),
+        };
+        assert_eq!(
+            sources.get_argument_vector_register(&project, &sub),
+            Some(&variable!("RSI:8"))
+        );
+        let argument = AbstractIdentifier::new(
+            Tid::new("main"),
+            AbstractLocation::Pointer(
+                variable!("RSI:8"),
+                AbstractMemoryLocation::Location {
+                    offset: 8,
+                    size: ByteSize::new(8),
+                },
+            ),
+        );
+        assert!(sources.is_program_argument(&project, &sub, &argument));
+        let argument_vector = AbstractIdentifier::from_var(Tid::new("main"), &variable!("RSI:8"));
+        assert!(!sources.is_program_argument(&project, &sub, &argument_vector));
+
+        sub.term.name = "other".to_string();
+        assert!(!sources.is_program_argument(&project, &sub, &argument));
+    }
+
+    #[test]
+    fn taint_sources() {
+        let sources = InputSources::default();
+        assert!(sources.is_string_source("getenv"));
+        assert!(!sources.is_string_source("getopt"));
+        assert!(sources.is_source("getopt"));
+        assert!(!sources.is_source("malloc"));
+
+        let mut state = TaintState::new_empty();
+        let symbol = ExternSymbol::mock_x64("getenv");
+        assert!(sources.taint_return_values(&mut state, &symbol));
+        let taint = state.get_register_taint(&variable!("RAX:8"));
+        assert_eq!(taint.get_labels(), Some(TaintSource::Environment.into()));
+        let mut getopt = ExternSymbol::mock_x64("getopt");
+        getopt.return_values = vec![Arg::mock_register("RDX", 8)];
+        assert!(sources.taint_return_values(&mut state, &getopt));
+        let taint = state.eval(&Expression::Var(variable!("RAX:8"))).bin_op(
+            BinOpType::IntAdd,
+            &state.get_register_taint(&variable!("RDX:8")),
+        );
+        let labels = taint.get_labels().unwrap();
+        assert!(labels.contains(TaintSource::Environment));
+        assert!(labels.contains(TaintSource::ProgramArguments));
+        assert!(!labels.contains(TaintSource::Network));
+        assert_eq!(taint.to_string(), "Tainted:8(env,argv)");
+        assert!(!sources.taint_return_values(&mut state, &ExternSymbol::mock_x64("malloc")));
+    }
+
+    #[test]
+    fn max_input_length() {
+        let sources = InputSources::default();
+        let mut fread = ExternSymbol::mock_x64("fread");
+        fread.parameters = ["RDI", "RSI", "RDX", "RCX"]
+            .into_iter()
+            .map(|name| Arg::mock_register(name, 8))
+            .collect();
+        let source = sources.get_buffer_source("fread").unwrap();
+        let eval_parameter = |param: &Arg| match param {
+            Arg::Register {
+                expr: Expression::Var(var),
+                ..
+            } if var.name == "RSI" => Some(PiData::from(Bitvector::from_u64(4))),
+            Arg::Register {
+                expr: Expression::Var(var),
+                ..
+            } if var.name == "RDX" => Some(PiData::from(Bitvector::from_u64(16))),
+            _ => Some(PiData::new_top(ByteSize::new(8))),
+        };
+        assert_eq!(
+            source.get_max_input_length(&fread, eval_parameter),
+            Some(64)
+        );
+        let fgets = sources.get_buffer_source("fgets").unwrap();
+        assert_eq!(fgets.get_max_input_length(&fread, eval_parameter), Some(4));
+        assert_eq!(fgets.get_max_string_length(&fread, eval_parameter), Some(3));
+        let read = sources.get_buffer_source("read").unwrap();
+        assert_eq!(read.get_max_input_length(&fread, eval_parameter), Some(16));
+        assert_eq!(read.get_max_string_length(&fread, eval_parameter), Some(16));
+        assert_eq!(
+            read.get_max_input_length(&fread, |_| Some(PiData::new_top(ByteSize::new(8)))),
+            None
+        );
+        assert!(sources.get_buffer_source("getenv").is_none());
+    }
+}
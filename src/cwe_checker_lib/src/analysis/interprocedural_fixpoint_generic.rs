@@ -1,7 +1,11 @@
 //! Types and functions shared between the implementations
 //! of forward and backward interprocedural fixpoint computations.
 
+use super::fixpoint::{Computation, Context};
+use super::graph::Node;
 use crate::prelude::*;
+use crate::utils::log::LogMessage;
+use std::collections::BTreeMap;
 
 /// NodeValue that can either be a single abstract value or a
 /// composition of the abstract value computed following an interprocedural call in the graph
@@ -52,3 +56,32 @@ where
         (None, None) => None,
     }
 }
+
+/// Generate an error log message for each function that was skipped by the fixpoint computation,
+/// because a transition function panicked at one of its nodes.
+///
+/// The `source` is the name of the analysis that is added to the log messages.
+pub fn get_failure_logs<'a, T>(computation: &Computation<T>, source: &str) -> Vec<LogMessage>
+where
+    T: Context<NodeLabel = Node<'a>>,
+{
+    let graph = computation.get_graph();
+    let failed_functions: BTreeMap<&Tid, (&str, &str)> = computation
+        .get_failures()
+        .iter()
+        .map(|(node, message)| {
+            let sub = graph[*node].get_function();
+            (&sub.tid, (sub.term.name.as_str(), message.as_str()))
+        })
+        .collect();
+    failed_functions
+        .into_iter()
+        .map(|(sub_tid, (sub_name, message))| {
+            LogMessage::new_error(format!(
+                "Analysis of function {sub_name} failed and the function was skipped: {message}"
+            ))
+            .location(sub_tid.clone())
+            .source(source)
+        })
+        .collect()
+}
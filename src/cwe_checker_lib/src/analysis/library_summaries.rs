@@ -0,0 +1,656 @@
+//! Summaries of the functions of shared libraries for the analysis across binary boundaries.
+//!
+//! Calls to functions of shared libraries are usually treated as calls to opaque extern symbols.
+//! If the libraries that a binary depends on are analyzed as well,
+//! the functions exported by them can be summarized
+//! and the summaries can be used in the analysis of the binary importing them.
+//!
+//! A [`FunctionSummary`] describes
+//! - the number of parameters of the function according to its function signature,
+//! - which parameters of the function are passed on to sink functions like `system`,
+//!   either directly or through other functions of the library,
+//! - which parameters may be returned by the function,
+//! - which constant strings of the library may be returned by the function,
+//! - which parameter is the size of the memory object returned by the function
+//!   if the function wraps an allocation function like `malloc`.
+//!
+//! The summaries can be serialized, so that the summaries of common libraries
+//! only have to be computed once and can be reused for all binaries linking them.
+//!
+//! The parameters are given by their index in the integer parameter registers
+//! of the calling convention of the function.
+//! Imports are resolved to the summarized functions by their names.
+
+use crate::abstract_domain::{AbstractIdentifier, AbstractLocation, TryToBitvec};
+use crate::analysis::function_signature::FunctionSignature;
+use crate::analysis::pointer_inference::Data as PiData;
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The configuration of the computation of library summaries.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct Config {
+    /// Names of sink functions like `system`.
+    /// The summaries track which parameters are passed on to these functions.
+    pub sink_symbols: BTreeSet<String>,
+    /// Names of allocation functions like `malloc`, mapped to the index of their size parameter.
+    #[serde(default)]
+    pub allocation_functions: BTreeMap<String, usize>,
+}
+
+/// The summary of a function of a shared library.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct FunctionSummary {
+    /// The number of integer parameters of the function.
+    pub parameter_count: usize,
+    /// Maps the indices of parameters to the names of the sink functions that they are passed to.
+    pub sinks: BTreeMap<usize, BTreeSet<String>>,
+    /// The indices of the parameters that may be returned by the function.
+    pub returned_parameters: BTreeSet<usize>,
+    /// The index of the size parameter if the function returns memory
+    /// allocated by one of the configured allocation functions.
+    #[serde(default)]
+    pub allocation_size_parameter: Option<usize>,
+    /// The string literals of the library that the function may return.
+    /// Empty if the function may also return other values.
+    #[serde(default)]
+    pub returned_strings: BTreeSet<String>,
+}
+
+/// The summaries of the functions exported by one or more shared libraries,
+/// mapped by the names of the functions.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct LibrarySummaries {
+    /// The summaries of the exported functions.
+    pub functions: BTreeMap<String, FunctionSummary>,
+}
+
+impl LibrarySummaries {
+    /// Compute the summaries of the functions exported by the library given by the project.
+    ///
+    /// The exported functions are the entry points of the project.
+    /// Summaries of internal functions are used to track parameters through calls inside the library,
+    /// but only the summaries of exported functions are returned.
+    /// The `vsa_result` should be the result of the Pointer Inference of the library.
+    pub fn compute(
+        project: &Project,
+        function_signatures: &BTreeMap<Tid, FunctionSignature>,
+        vsa_result: &impl VsaResult<ValueDomain = PiData>,
+        config: &Config,
+    ) -> LibrarySummaries {
+        let subs = &project.program.term.subs;
+        let mut summaries: BTreeMap<Tid, FunctionSummary> = BTreeMap::new();
+        // Summaries only grow, so that iterating until nothing changes terminates.
+        // Each iteration propagates the summaries one call level further up the call graph.
+        for _ in 0..=subs.len() {
+            let mut changed = false;
+            for sub in subs.values() {
+                let summary = summarize_function(
+                    project,
+                    sub,
+                    function_signatures,
+                    vsa_result,
+                    config,
+                    &summaries,
+                );
+                if summaries.get(&sub.tid) != Some(&summary) {
+                    summaries.insert(sub.tid.clone(), summary);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let functions = summaries
+            .into_iter()
+            .filter(|(tid, _)| project.program.term.entry_points.contains(tid))
+            .map(|(tid, summary)| (subs[&tid].term.name.clone(), summary))
+            .collect();
+        LibrarySummaries { functions }
+    }
+
+    /// Add the summaries of another library.
+    ///
+    /// If both contain a function with the same name, the summary of `self` is kept,
+    /// since the dynamic linker also resolves symbols to the first library defining them.
+    pub fn extend(&mut self, other: LibrarySummaries) {
+        for (name, summary) in other.functions {
+            self.functions.entry(name).or_insert(summary);
+        }
+    }
+
+    /// Get the summary of the library function that the extern symbol resolves to.
+    pub fn get(&self, extern_symbol: &ExternSymbol) -> Option<&FunctionSummary> {
+        self.functions.get(&extern_symbol.name)
+    }
+
+    /// Resolve the extern symbols of the project to the summarized library functions.
+    ///
+    /// Extern symbols with unknown parameters get the parameters of the library function,
+    /// and extern symbols with unknown return values get the first integer return register
+    /// if the library function may return one of its parameters or a string literal.
+    /// Returns the number of extern symbols that were resolved to library functions.
+    pub fn resolve_imports(&self, project: &mut Project) -> usize {
+        let mut resolved_symbols = Vec::new();
+        for symbol in project.program.term.extern_symbols.values() {
+            let Some(summary) = self.get(symbol) else {
+                continue;
+            };
+            let resolver = project.get_extern_symbol_resolver(symbol);
+            let mut resolved_symbol = symbol.clone();
+            if resolved_symbol.parameters.is_empty() {
+                resolved_symbol.parameters = (0..summary.parameter_count)
+                    .map(|index| resolver.get_integer_parameter_arg(index))
+                    .collect();
+            }
+            if resolved_symbol.return_values.is_empty()
+                && !(summary.returned_parameters.is_empty() && summary.returned_strings.is_empty())
+            {
+                if let Some(register) = resolver
+                    .get_calling_convention()
+                    .integer_return_register
+                    .first()
+                {
+                    resolved_symbol.return_values = vec![Arg::from_var(register.clone(), None)];
+                }
+            }
+            resolved_symbols.push(resolved_symbol);
+        }
+        let num_resolved = resolved_symbols.len();
+        for symbol in resolved_symbols {
+            project
+                .program
+                .term
+                .extern_symbols
+                .insert(symbol.tid.clone(), symbol);
+        }
+        num_resolved
+    }
+
+    /// Get the library functions that wrap allocation functions,
+    /// mapped to the index of their size parameter.
+    ///
+    /// The result has the format of the `allocation_wrappers` of the Pointer Inference configuration.
+    pub fn get_allocation_wrappers(&self) -> BTreeMap<String, usize> {
+        self.functions
+            .iter()
+            .filter_map(|(name, summary)| {
+                summary
+                    .allocation_size_parameter
+                    .map(|index| (name.clone(), index))
+            })
+            .collect()
+    }
+
+    /// Get the library functions that return string literals,
+    /// mapped to the strings that they may return.
+    ///
+    /// The result has the format of the `returned_string_symbols` of the string abstraction configuration.
+    pub fn get_returned_strings(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.functions
+            .iter()
+            .filter(|(_, summary)| !summary.returned_strings.is_empty())
+            .map(|(name, summary)| (name.clone(), summary.returned_strings.clone()))
+            .collect()
+    }
+
+    /// Get the parameters of the extern symbol that the library function passes to one of the given sinks,
+    /// together with the names of these sinks.
+    pub fn get_sink_parameters<'a>(
+        &self,
+        extern_symbol: &'a ExternSymbol,
+        sink_symbols: &[String],
+    ) -> Vec<(&'a Arg, Vec<&str>)> {
+        let Some(summary) = self.get(extern_symbol) else {
+            return Vec::new();
+        };
+        summary
+            .sinks
+            .iter()
+            .filter_map(|(index, sinks)| {
+                let parameter = extern_symbol.parameters.get(*index)?;
+                let sinks: Vec<&str> = sinks
+                    .iter()
+                    .filter(|sink| sink_symbols.contains(sink))
+                    .map(|sink| sink.as_str())
+                    .collect();
+                (!sinks.is_empty()).then_some((parameter, sinks))
+            })
+            .collect()
+    }
+}
+
+/// Compute the summary of a function using the already known summaries of the functions it calls.
+///
+/// Only allocations by calls in the function itself are considered for the size parameter.
+fn summarize_function(
+    project: &Project,
+    sub: &Term<Sub>,
+    function_signatures: &BTreeMap<Tid, FunctionSignature>,
+    vsa_result: &impl VsaResult<ValueDomain = PiData>,
+    config: &Config,
+    summaries: &BTreeMap<Tid, FunctionSummary>,
+) -> FunctionSummary {
+    let mut summary = FunctionSummary::default();
+    let Some(cconv) = project.get_specific_calling_convention(&sub.term.calling_convention) else {
+        return summary;
+    };
+    let parameter_ids: Vec<AbstractIdentifier> = cconv
+        .integer_parameter_register
+        .iter()
+        .map(|register| {
+            AbstractIdentifier::new(
+                sub.tid.clone(),
+                AbstractLocation::Register(register.clone()),
+            )
+        })
+        .collect();
+    let get_parameter_indices = |value: &PiData| -> Vec<usize> {
+        value
+            .referenced_ids()
+            .filter_map(|id| parameter_ids.iter().position(|param_id| param_id == id))
+            .collect()
+    };
+    if let Some(signature) = function_signatures.get(&sub.tid) {
+        summary.parameter_count = cconv
+            .integer_parameter_register
+            .iter()
+            .rposition(|register| {
+                signature
+                    .parameters
+                    .contains_key(&AbstractLocation::Register(register.clone()))
+            })
+            .map_or(0, |index| index + 1);
+    }
+
+    let jmps = || {
+        sub.term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.jmps.iter())
+    };
+    // Maps allocation calls to the indices of the parameters that the allocation size is derived from.
+    let mut allocation_calls: BTreeMap<&Tid, Vec<usize>> = BTreeMap::new();
+    for jmp in jmps() {
+        if let Some(size) = get_allocation_size_param(project, jmp, &config.allocation_functions)
+            .and_then(|size_param| vsa_result.eval_parameter_arg_at_call(&jmp.tid, &size_param))
+        {
+            allocation_calls.insert(&jmp.tid, get_parameter_indices(&size));
+        }
+    }
+    let mut allocation_size_parameters = BTreeSet::new();
+    let mut returns_other_values = false;
+
+    for jmp in jmps() {
+        match &jmp.term {
+            Jmp::Call { target, .. } => {
+                if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                    if !config.sink_symbols.contains(&symbol.name) {
+                        continue;
+                    }
+                    for parameter in symbol.parameters.iter() {
+                        let Some(value) =
+                            vsa_result.eval_parameter_arg_at_call(&jmp.tid, parameter)
+                        else {
+                            continue;
+                        };
+                        for index in get_parameter_indices(&value) {
+                            summary
+                                .sinks
+                                .entry(index)
+                                .or_default()
+                                .insert(symbol.name.clone());
+                        }
+                    }
+                } else if let (Some(callee), Some(callee_summary)) =
+                    (project.program.term.subs.get(target), summaries.get(target))
+                {
+                    let Some(callee_cconv) =
+                        project.get_specific_calling_convention(&callee.term.calling_convention)
+                    else {
+                        continue;
+                    };
+                    for (callee_index, sinks) in callee_summary.sinks.iter() {
+                        let Some(value) = callee_cconv
+                            .integer_parameter_register
+                            .get(*callee_index)
+                            .and_then(|register| {
+                                vsa_result.eval_parameter_location_at_call(
+                                    &jmp.tid,
+                                    &AbstractLocation::Register(register.clone()),
+                                )
+                            })
+                        else {
+                            continue;
+                        };
+                        for index in get_parameter_indices(&value) {
+                            summary
+                                .sinks
+                                .entry(index)
+                                .or_default()
+                                .extend(sinks.iter().cloned());
+                        }
+                    }
+                }
+            }
+            Jmp::Return(_) => {
+                let Some(return_register) = cconv.integer_return_register.first() else {
+                    continue;
+                };
+                if let Some(value) =
+                    vsa_result.eval_at_jmp(&jmp.tid, &Expression::Var(return_register.clone()))
+                {
+                    summary
+                        .returned_parameters
+                        .extend(get_parameter_indices(&value));
+                    match get_string_literal(project, &value) {
+                        Some(string) => {
+                            summary.returned_strings.insert(string);
+                        }
+                        None => returns_other_values = true,
+                    }
+                    for id in value.referenced_ids() {
+                        if let Some(indices) = allocation_calls.get(id.get_tid()) {
+                            allocation_size_parameters.extend(indices.iter().copied());
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    if allocation_size_parameters.len() == 1 {
+        summary.allocation_size_parameter = allocation_size_parameters.pop_first();
+    }
+    if returns_other_values {
+        summary.returned_strings.clear();
+    }
+    summary
+}
+
+/// Get the string literal that the value points to
+/// if the value is a constant address in the global memory of the project.
+fn get_string_literal(project: &Project, value: &PiData) -> Option<String> {
+    let address = value.get_if_absolute_value()?.try_to_bitvec().ok()?;
+    project
+        .runtime_memory_image
+        .read_string_until_null_terminator(&address)
+        .ok()
+        .map(str::to_string)
+}
+
+/// Get the size parameter if the jump is a call to one of the given allocation functions.
+///
+/// For calls to extern symbols the parameter is taken from the extern symbol.
+/// For calls to internal functions the parameter is assumed to be the integer parameter
+/// with the given index in the calling convention of the callee.
+fn get_allocation_size_param(
+    project: &Project,
+    jmp: &Term<Jmp>,
+    allocation_functions: &BTreeMap<String, usize>,
+) -> Option<Arg> {
+    let Jmp::Call { target, .. } = &jmp.term else {
+        return None;
+    };
+    if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+        let index = allocation_functions.get(&symbol.name)?;
+        symbol.parameters.get(*index).cloned()
+    } else {
+        let callee = project.program.term.subs.get(target)?;
+        let index = allocation_functions.get(&callee.term.name)?;
+        project
+            .get_calling_convention_resolver(&callee.term.calling_convention)
+            .map(|resolver| resolver.get_integer_parameter_arg(*index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::IntervalDomain;
+    use crate::analysis::function_signature::AccessPattern;
+    use crate::analysis::graph::NodeIndex;
+    use crate::analysis::taint::{
+        propagate_returned_parameter_taint, state::State as TaintState, Taint, TaintSource,
+    };
+    use crate::variable;
+    use std::collections::HashMap;
+
+    /// A VSA result returning the same value for all parameters at a jump.
+    struct MockVsaResult(HashMap<Tid, PiData>);
+
+    impl VsaResult for MockVsaResult {
+        type ValueDomain = PiData;
+
+        fn eval_value_at_def(&self, _def_tid: &Tid) -> Option<PiData> {
+            None
+        }
+
+        fn eval_address_at_def(&self, _def_tid: &Tid) -> Option<PiData> {
+            None
+        }
+
+        fn eval_parameter_arg_at_call(&self, jmp_tid: &Tid, _param: &Arg) -> Option<PiData> {
+            self.0.get(jmp_tid).cloned()
+        }
+
+        fn eval_parameter_location_at_call(
+            &self,
+            jmp_tid: &Tid,
+            _param: &AbstractLocation,
+        ) -> Option<PiData> {
+            self.0.get(jmp_tid).cloned()
+        }
+
+        fn eval_at_jmp(&self, jmp_tid: &Tid, _expression: &Expression) -> Option<PiData> {
+            self.0.get(jmp_tid).cloned()
+        }
+
+        fn eval_at_node(&self, _node: NodeIndex, _expression: &Expression) -> Option<PiData> {
+            None
+        }
+    }
+
+    /// A pointer to the parameter of the function in the given register.
+    fn param_pointer(function: &str, register: &str) -> PiData {
+        PiData::from_target(
+            AbstractIdentifier::from_var(Tid::new(function), &variable!(format!("{register}:8"))),
+            IntervalDomain::from(Bitvector::from_i64(0)),
+        )
+    }
+
+    /// A function with one block for each of the given jumps.
+    fn mock_sub(name: &str, jmps: Vec<Term<Jmp>>) -> Term<Sub> {
+        let mut sub = Sub::mock(name);
+        for (index, jmp) in jmps.into_iter().enumerate() {
+            let mut block = Blk::mock_with_tid(&format!("{name}_block_{index}"));
+            block.term.jmps.push(jmp);
+            sub.term.blocks.push(block);
+        }
+        sub
+    }
+
+    /// A return jump.
+    fn mock_return(tid: &str) -> Term<Jmp> {
+        Term {
+            tid: Tid::new(tid),
+            term: Jmp::Return(Expression::Var(variable!("RSP:8"))),
+        }
+    }
+
+    /// A library where `run_cmd` passes its first parameter to `system`,
+    /// `wrapper` passes its second parameter to `run_cmd` and returns its first parameter
+    /// `xalloc` returns memory allocated by `malloc` with its first parameter as size
+    /// and `version` returns the string literal `Hello World`.
+    fn mock_library() -> (Project, BTreeMap<Tid, FunctionSignature>, MockVsaResult) {
+        let mut project = Project::mock_x64();
+        let system = ExternSymbol::mock_x64("system");
+        project
+            .program
+            .term
+            .extern_symbols
+            .insert(system.tid.clone(), system);
+        let run_cmd = mock_sub("run_cmd", vec![Jmp::call("call_system", "system", None)]);
+        let wrapper = mock_sub(
+            "wrapper",
+            vec![
+                Jmp::call("call_run_cmd", "run_cmd", None),
+                mock_return("return"),
+            ],
+        );
+        let xalloc = mock_sub(
+            "xalloc",
+            vec![
+                Jmp::call("call_malloc", "malloc", None),
+                mock_return("xalloc_return"),
+            ],
+        );
+        let version = mock_sub("version", vec![mock_return("version_return")]);
+        for sub in [run_cmd, wrapper, xalloc, version] {
+            project.program.term.entry_points.insert(sub.tid.clone());
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+
+        let mut signature = FunctionSignature::new();
+        for register in ["RDI:8", "RSI:8"] {
+            signature.parameters.insert(
+                AbstractLocation::Register(variable!(register)),
+                AccessPattern::new_unknown_access(),
+            );
+        }
+        let function_signatures = BTreeMap::from([(Tid::new("wrapper"), signature)]);
+        let vsa_result = MockVsaResult(HashMap::from([
+            (Tid::new("call_system"), param_pointer("run_cmd", "RDI")),
+            (Tid::new("call_run_cmd"), param_pointer("wrapper", "RSI")),
+            (Tid::new("return"), param_pointer("wrapper", "RDI")),
+            (Tid::new("call_malloc"), param_pointer("xalloc", "RDI")),
+            (
+                Tid::new("xalloc_return"),
+                param_pointer("call_malloc", "RAX"),
+            ),
+            (
+                Tid::new("version_return"),
+                PiData::from(Bitvector::from_u64(0x3002)),
+            ),
+        ]));
+        (project, function_signatures, vsa_result)
+    }
+
+    fn mock_config() -> Config {
+        Config {
+            sink_symbols: BTreeSet::from(["system".to_string()]),
+            allocation_functions: BTreeMap::from([("malloc".to_string(), 0)]),
+        }
+    }
+
+    #[test]
+    fn compute_summaries() {
+        let (project, function_signatures, vsa_result) = mock_library();
+        let summaries =
+            LibrarySummaries::compute(&project, &function_signatures, &vsa_result, &mock_config());
+
+        let system = BTreeSet::from(["system".to_string()]);
+        assert_eq!(
+            summaries.functions["run_cmd"],
+            FunctionSummary {
+                parameter_count: 0,
+                sinks: BTreeMap::from([(0, system.clone())]),
+                returned_parameters: BTreeSet::new(),
+                allocation_size_parameter: None,
+                returned_strings: BTreeSet::new(),
+            }
+        );
+        assert_eq!(
+            summaries.functions["wrapper"],
+            FunctionSummary {
+                parameter_count: 2,
+                sinks: BTreeMap::from([(1, system)]),
+                returned_parameters: BTreeSet::from([0]),
+                allocation_size_parameter: None,
+                returned_strings: BTreeSet::new(),
+            }
+        );
+        assert_eq!(
+            summaries.functions["xalloc"].allocation_size_parameter,
+            Some(0)
+        );
+        assert_eq!(
+            summaries.get_allocation_wrappers(),
+            BTreeMap::from([("xalloc".to_string(), 0)])
+        );
+        assert_eq!(
+            summaries.get_returned_strings(),
+            BTreeMap::from([(
+                "version".to_string(),
+                BTreeSet::from(["Hello World".to_string()])
+            )])
+        );
+    }
+
+    #[test]
+    fn resolve_imports() {
+        let (library, function_signatures, vsa_result) = mock_library();
+        let summaries =
+            LibrarySummaries::compute(&library, &function_signatures, &vsa_result, &mock_config());
+        let mut project = Project::mock_x64();
+        let mut wrapper = ExternSymbol::mock_x64("wrapper");
+        wrapper.parameters = Vec::new();
+        wrapper.return_values = Vec::new();
+        project
+            .program
+            .term
+            .extern_symbols
+            .insert(wrapper.tid.clone(), wrapper);
+
+        assert_eq!(summaries.resolve_imports(&mut project), 1);
+        let wrapper = &project.program.term.extern_symbols[&Tid::new("wrapper")];
+        assert_eq!(
+            wrapper.parameters,
+            vec![Arg::mock_register("RDI", 8), Arg::mock_register("RSI", 8)]
+        );
+        assert_eq!(wrapper.return_values, vec![Arg::mock_register("RAX", 8)]);
+        let sink_parameters = summaries.get_sink_parameters(wrapper, &["system".to_string()]);
+        assert_eq!(
+            sink_parameters,
+            vec![(&Arg::mock_register("RSI", 8), vec!["system"])]
+        );
+        assert!(summaries
+            .get_sink_parameters(wrapper, &["popen".to_string()])
+            .is_empty());
+    }
+
+    #[test]
+    fn propagate_returned_taint() {
+        let (library, function_signatures, vsa_result) = mock_library();
+        let summaries = LibrarySummaries::compute(
+            &library,
+            &function_signatures,
+            &vsa_result,
+            &Config::default(),
+        );
+        let wrapper = ExternSymbol {
+            parameters: vec![Arg::mock_register("RDI", 8), Arg::mock_register("RSI", 8)],
+            ..ExternSymbol::mock_x64("wrapper")
+        };
+        let mut state_before_call = TaintState::new_empty();
+        state_before_call.set_register_taint(
+            &variable!("RDI:8"),
+            Taint::new_tainted(ByteSize::new(8), TaintSource::Network),
+        );
+        let mut state_after_call = TaintState::new_empty();
+
+        propagate_returned_parameter_taint(
+            &summaries,
+            &state_before_call,
+            &mut state_after_call,
+            &wrapper,
+        );
+        assert_eq!(
+            state_after_call.get_register_taint(&variable!("RAX:8")),
+            Taint::new_tainted(ByteSize::new(8), TaintSource::Network)
+        );
+    }
+}
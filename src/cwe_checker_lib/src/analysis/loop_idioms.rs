@@ -0,0 +1,555 @@
+//! Recognition of loops that implement common memory and string operations.
+//!
+//! Compilers inline small copies and string scans as loops instead of calls to
+//! `memcpy` or `strlen`, so they are invisible to the handlers of extern
+//! symbols. This module recognizes such loops in the IR and summarizes their
+//! semantics, so that the analyses can handle them like the corresponding
+//! calls.
+//!
+//! The following idioms are recognized:
+//! - [Copy loops](LoopIdiom::Copy) load an element from a source buffer and
+//!   store it to a destination buffer in each iteration, e.g. inlined `memcpy`
+//!   or `rep movsb` loops.
+//! - [Scan loops](LoopIdiom::Scan) load a whole SIMD register (e.g. an SSE or
+//!   NEON register) of characters per iteration and compare all of them with
+//!   zero at once, e.g. inlined vectorized `strlen` loops. Such a loop reads up
+//!   to the end of the vector that contains the terminating null byte, i.e.
+//!   past the end of the string. Since the loaded vectors are aligned these
+//!   reads never cross a page boundary and are harmless, but to the analyses
+//!   they look like out-of-bounds reads.
+//! - [Decode loops](LoopIdiom::Decode) load an element of a buffer, combine it
+//!   with a constant key by XOR, addition or subtraction and store the result
+//!   back to the same address, e.g. the decoding of obfuscated stack strings.
+//!
+//! A loop is only recognized if it consists of a single block that jumps back
+//! to itself and has a conditional exit. Loops spanning several basic blocks
+//! are not recognized.
+
+use crate::intermediate_representation::*;
+
+use std::collections::HashMap;
+
+/// The minimal size in bytes of a load to be considered a vector load.
+pub const MIN_VECTOR_SIZE: u64 = 16;
+
+/// The semantics of a recognized loop.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoopIdiom {
+    /// A loop that copies one element from a source to a destination buffer in
+    /// each iteration.
+    Copy {
+        /// The load of the copied element.
+        load: Tid,
+        /// The store of the copied element.
+        store: Tid,
+        /// The address of the first copied element of the source buffer when
+        /// evaluated at the start of the loop.
+        source: Expression,
+        /// The address of the first copied element of the destination buffer
+        /// when evaluated at the start of the loop.
+        destination: Expression,
+        /// A register that is decremented by one in each iteration. At the
+        /// start of the loop it contains the number of copied elements.
+        count: Option<Variable>,
+    },
+    /// A loop that scans a string for its terminating null character by
+    /// loading a vector of characters in each iteration.
+    Scan {
+        /// The load of the string characters.
+        load: Tid,
+    },
+    /// A loop that decodes a buffer in place by combining each element with a
+    /// constant key.
+    Decode {
+        /// The load of the encoded element.
+        load: Tid,
+        /// The store of the decoded element.
+        store: Tid,
+        /// The address of the first decoded element when evaluated at the
+        /// start of the loop.
+        buffer: Expression,
+        /// The operation that computes the decoded element from the encoded
+        /// element and the key, i.e. `IntXOr`, `IntAdd` or `IntSub`.
+        operation: BinOpType,
+        /// The key that is the right hand side of the operation.
+        key: Bitvector,
+        /// A register that is decremented by one in each iteration. At the
+        /// start of the loop it contains the number of decoded elements.
+        count: Option<Variable>,
+    },
+}
+
+/// A summary of a loop that implements a common memory or string operation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoopSummary {
+    /// The block containing the loop.
+    pub block: Tid,
+    /// The number of bytes processed in each iteration.
+    pub step: ByteSize,
+    /// The semantics of the loop.
+    pub idiom: LoopIdiom,
+}
+
+impl LoopSummary {
+    /// Returns the summary of the given block if the block matches one of the
+    /// recognized loop idioms.
+    pub fn from_block(blk: &Term<Blk>) -> Option<Self> {
+        let jumps_to_itself = blk.term.jmps.iter().any(|jmp| match &jmp.term {
+            Jmp::Branch(target) | Jmp::CBranch { target, .. } => *target == blk.tid,
+            _ => false,
+        });
+        let has_conditional_exit = blk
+            .term
+            .jmps
+            .iter()
+            .any(|jmp| matches!(jmp.term, Jmp::CBranch { .. }));
+        if !jumps_to_itself || !has_conditional_exit {
+            return None;
+        }
+        let loads: Vec<&Term<Def>> = blk
+            .term
+            .defs
+            .iter()
+            .filter(|def| matches!(def.term, Def::Load { .. }))
+            .collect();
+        let stores: Vec<&Term<Def>> = blk
+            .term
+            .defs
+            .iter()
+            .filter(|def| matches!(def.term, Def::Store { .. }))
+            .collect();
+
+        match (loads.as_slice(), stores.as_slice()) {
+            ([load], [store]) => Self::from_copy_loop(blk, load, store)
+                .or_else(|| Self::from_decode_loop(blk, load, store)),
+            (loads, []) => loads
+                .iter()
+                .find_map(|load| Self::from_scan_loop(blk, load)),
+            _ => None,
+        }
+    }
+
+    /// Returns the summary of a copy loop with the given load and store.
+    fn from_copy_loop(blk: &Term<Blk>, load: &Term<Def>, store: &Term<Def>) -> Option<Self> {
+        let (
+            Def::Load {
+                var,
+                address: source,
+            },
+            Def::Store {
+                address: destination,
+                value,
+            },
+        ) = (&load.term, &store.term)
+        else {
+            return None;
+        };
+        let step = var.size;
+        let is_copy = *value == Expression::Var(var.clone())
+            && get_assignments_between(blk, load, store)
+                .all(|(assigned_var, _)| assigned_var != var)
+            && is_advanced_after(blk, source, &load.tid, step)
+            && is_advanced_after(blk, destination, &store.tid, step);
+        if !is_copy {
+            return None;
+        }
+
+        Some(LoopSummary {
+            block: blk.tid.clone(),
+            step,
+            idiom: LoopIdiom::Copy {
+                load: load.tid.clone(),
+                store: store.tid.clone(),
+                source: source.clone(),
+                destination: destination.clone(),
+                count: get_counter(blk),
+            },
+        })
+    }
+
+    /// Returns the summary of a decode loop with the given load and store.
+    fn from_decode_loop(blk: &Term<Blk>, load: &Term<Def>, store: &Term<Def>) -> Option<Self> {
+        let (
+            Def::Load { var, address },
+            Def::Store {
+                address: store_address,
+                value,
+            },
+        ) = (&load.term, &store.term)
+        else {
+            return None;
+        };
+        let step = var.size;
+        if address != store_address || !is_advanced_after(blk, address, &store.tid, step) {
+            return None;
+        }
+        // The stored value may be computed by an assignment between the load and the store.
+        let value = match value {
+            Expression::Var(stored_var) => get_assignments_between(blk, load, store)
+                .filter(|(assigned_var, _)| *assigned_var == stored_var)
+                .map(|(_, value)| value)
+                .last()?,
+            value => value,
+        };
+        let (operation, key) = get_decode_operation(value, var)?;
+        if key.bytesize() != step {
+            return None;
+        }
+
+        Some(LoopSummary {
+            block: blk.tid.clone(),
+            step,
+            idiom: LoopIdiom::Decode {
+                load: load.tid.clone(),
+                store: store.tid.clone(),
+                buffer: address.clone(),
+                operation,
+                key,
+                count: get_counter(blk),
+            },
+        })
+    }
+
+    /// Returns the summary of a scan loop with the given load.
+    fn from_scan_loop(blk: &Term<Blk>, load: &Term<Def>) -> Option<Self> {
+        let Def::Load { var, address } = &load.term else {
+            return None;
+        };
+        if u64::from(var.size) < MIN_VECTOR_SIZE
+            || !address
+                .input_vars()
+                .into_iter()
+                .any(|input| is_advanced_by(blk, input, var.size))
+        {
+            return None;
+        }
+
+        Some(LoopSummary {
+            block: blk.tid.clone(),
+            step: var.size,
+            idiom: LoopIdiom::Scan {
+                load: load.tid.clone(),
+            },
+        })
+    }
+
+    /// Returns true if the given jump enters the loop from outside.
+    pub fn is_entered_by(&self, jump: &Term<Jmp>, target: &Term<Blk>) -> bool {
+        target.tid == self.block && !target.term.jmps.iter().any(|jmp| jmp.tid == jump.tid)
+    }
+}
+
+/// Returns the constant that is added to the given variable by the given
+/// expression, if the expression has the form `var + c` or `var - c`.
+fn get_increment(expression: &Expression, var: &Variable) -> Option<i64> {
+    let Expression::BinOp { op, lhs, rhs } = expression else {
+        return None;
+    };
+    let increment = match (op, lhs.as_ref(), rhs.as_ref()) {
+        (BinOpType::IntAdd, Expression::Var(input), Expression::Const(increment))
+        | (BinOpType::IntAdd, Expression::Const(increment), Expression::Var(input))
+            if input == var =>
+        {
+            increment.try_to_i64().ok()?
+        }
+        (BinOpType::IntSub, Expression::Var(input), Expression::Const(decrement))
+            if input == var =>
+        {
+            -decrement.try_to_i64().ok()?
+        }
+        _ => return None,
+    };
+    Some(increment)
+}
+
+/// Returns the assignments of the given block between the given load and store.
+fn get_assignments_between<'a>(
+    blk: &'a Term<Blk>,
+    load: &'a Term<Def>,
+    store: &'a Term<Def>,
+) -> impl Iterator<Item = (&'a Variable, &'a Expression)> {
+    blk.term
+        .defs
+        .iter()
+        .skip_while(move |def| def.tid != load.tid)
+        .take_while(move |def| def.tid != store.tid)
+        .filter_map(|def| match &def.term {
+            Def::Assign { var, value } => Some((var, value)),
+            _ => None,
+        })
+}
+
+/// Returns a register that the given block decrements by one.
+fn get_counter(blk: &Term<Blk>) -> Option<Variable> {
+    blk.term.defs.iter().find_map(|def| match &def.term {
+        Def::Assign { var, value } if get_increment(value, var) == Some(-1) => Some(var.clone()),
+        _ => None,
+    })
+}
+
+/// Returns the operation and the key if the given expression combines the
+/// given variable with a constant key by XOR, addition or subtraction.
+fn get_decode_operation(expression: &Expression, var: &Variable) -> Option<(BinOpType, Bitvector)> {
+    let Expression::BinOp { op, lhs, rhs } = expression else {
+        return None;
+    };
+    match (op, lhs.as_ref(), rhs.as_ref()) {
+        (
+            BinOpType::IntXOr | BinOpType::IntAdd | BinOpType::IntSub,
+            Expression::Var(input),
+            Expression::Const(key),
+        )
+        | (BinOpType::IntXOr | BinOpType::IntAdd, Expression::Const(key), Expression::Var(input))
+            if input == var =>
+        {
+            Some((*op, key.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the given block adds the given size to the given register.
+fn is_advanced_by(blk: &Term<Blk>, register: &Variable, size: ByteSize) -> bool {
+    blk.term.defs.iter().any(|def| match &def.term {
+        Def::Assign { var, value } => {
+            var == register && get_increment(value, var) == Some(u64::from(size) as i64)
+        }
+        _ => false,
+    })
+}
+
+/// Returns true if a register of the given address is advanced by the given
+/// size and no register of the address is changed before the given access.
+fn is_advanced_after(blk: &Term<Blk>, address: &Expression, access: &Tid, size: ByteSize) -> bool {
+    let input_vars = address.input_vars();
+    let is_changed_before_access = blk
+        .term
+        .defs
+        .iter()
+        .take_while(|def| def.tid != *access)
+        .any(|def| match &def.term {
+            Def::Assign { var, .. } | Def::Load { var, .. } => input_vars.contains(&var),
+            Def::Store { .. } => false,
+        });
+    !is_changed_before_access
+        && input_vars
+            .into_iter()
+            .any(|input| is_advanced_by(blk, input, size))
+}
+
+/// Returns the summaries of all recognized loops of the program, indexed by
+/// the TID of the loop block.
+pub fn find_loop_idioms(project: &Project) -> HashMap<Tid, LoopSummary> {
+    project
+        .program
+        .term
+        .subs
+        .values()
+        .flat_map(|sub| sub.term.blocks.iter())
+        .filter_map(LoopSummary::from_block)
+        .map(|summary| (summary.block.clone(), summary))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::parsing;
+    use crate::{defs, expr, variable};
+
+    /// Create a block that loops back to itself until the zero flag is set.
+    fn mock_loop_block(defs: Vec<Term<Def>>) -> Term<Blk> {
+        let mut blk = Blk::mock_with_tid("loop");
+        blk.term.defs = defs;
+        blk.term.jmps = vec![
+            Term {
+                tid: Tid::new("exit"),
+                term: Jmp::CBranch {
+                    target: Tid::new("after_loop"),
+                    condition: expr!("ZF:1"),
+                },
+            },
+            Term {
+                tid: Tid::new("back_edge"),
+                term: Jmp::Branch(Tid::new("loop")),
+            },
+        ];
+        blk
+    }
+
+    #[test]
+    fn scan_loops() {
+        let blk = mock_loop_block(defs![
+            "load: XMM1:16 := Load from RAX:8",
+            "RAX:8 = RAX:8 + 0x10:8"
+        ]);
+        assert_eq!(
+            LoopSummary::from_block(&blk),
+            Some(LoopSummary {
+                block: Tid::new("loop"),
+                step: ByteSize::new(16),
+                idiom: LoopIdiom::Scan {
+                    load: Tid::new("load")
+                },
+            })
+        );
+
+        // Loads of single characters are no vector loads.
+        let blk = mock_loop_block(defs![
+            "load: CL:1 := Load from RAX:8",
+            "RAX:8 = RAX:8 + 0x1:8"
+        ]);
+        assert_eq!(LoopSummary::from_block(&blk), None);
+
+        // The pointer has to be advanced by the vector size.
+        let blk = mock_loop_block(defs![
+            "load: XMM1:16 := Load from RAX:8",
+            "RAX:8 = RAX:8 + 0x8:8"
+        ]);
+        assert_eq!(LoopSummary::from_block(&blk), None);
+    }
+
+    #[test]
+    fn copy_loops() {
+        // A `rep movsb` loop.
+        let blk = mock_loop_block(defs![
+            "load: AL:1 := Load from RSI:8",
+            "store: Store at RDI:8 := AL:1",
+            "RSI:8 = RSI:8 + 0x1:8",
+            "RDI:8 = RDI:8 + 0x1:8",
+            "RCX:8 = RCX:8 - 0x1:8"
+        ]);
+        assert_eq!(
+            LoopSummary::from_block(&blk),
+            Some(LoopSummary {
+                block: Tid::new("loop"),
+                step: ByteSize::new(1),
+                idiom: LoopIdiom::Copy {
+                    load: Tid::new("load"),
+                    store: Tid::new("store"),
+                    source: expr!("RSI:8"),
+                    destination: expr!("RDI:8"),
+                    count: Some(variable!("RCX:8")),
+                },
+            })
+        );
+
+        // A copy loop with a common index register and without a counter.
+        let blk = mock_loop_block(defs![
+            "load: RAX:8 := Load from RSI:8 + RDX:8",
+            "store: Store at RDI:8 + RDX:8 := RAX:8",
+            "RDX:8 = RDX:8 + 0x8:8"
+        ]);
+        let summary = LoopSummary::from_block(&blk).unwrap();
+        assert_eq!(summary.step, ByteSize::new(8));
+        assert!(matches!(summary.idiom, LoopIdiom::Copy { count: None, .. }));
+
+        // The address register is changed before the load.
+        let blk = mock_loop_block(defs![
+            "RSI:8 = RSI:8 + 0x1:8",
+            "load: AL:1 := Load from RSI:8",
+            "store: Store at RDI:8 := AL:1",
+            "RDI:8 = RDI:8 + 0x1:8"
+        ]);
+        assert_eq!(LoopSummary::from_block(&blk), None);
+
+        // Vectorized loads followed by a store are no scan loops.
+        let blk = mock_loop_block(defs![
+            "load: XMM1:16 := Load from RAX:8",
+            "Store at RDI:8 := 0x0:8",
+            "RAX:8 = RAX:8 + 0x10:8"
+        ]);
+        assert_eq!(LoopSummary::from_block(&blk), None);
+
+        let blk = mock_loop_block(defs![
+            "load: AL:1 := Load from RSI:8",
+            "store: Store at RDI:8 := AL:1",
+            "RSI:8 = RSI:8 + 0x1:8",
+            "RDI:8 = RDI:8 + 0x1:8"
+        ]);
+        let summary = LoopSummary::from_block(&blk).unwrap();
+        assert!(summary.is_entered_by(
+            &Term {
+                tid: Tid::new("entry"),
+                term: Jmp::Branch(Tid::new("loop"))
+            },
+            &blk
+        ));
+        assert!(!summary.is_entered_by(&blk.term.jmps[1], &blk));
+    }
+
+    #[test]
+    fn decode_loops() {
+        // A loop that decodes a buffer in place by adding a constant key.
+        let blk = mock_loop_block(defs![
+            "load: AL:1 := Load from RDI:8",
+            "AL:1 = AL:1 + 0x5:1",
+            "store: Store at RDI:8 := AL:1",
+            "RDI:8 = RDI:8 + 0x1:8",
+            "RCX:8 = RCX:8 - 0x1:8"
+        ]);
+        assert_eq!(
+            LoopSummary::from_block(&blk),
+            Some(LoopSummary {
+                block: Tid::new("loop"),
+                step: ByteSize::new(1),
+                idiom: LoopIdiom::Decode {
+                    load: Tid::new("load"),
+                    store: Tid::new("store"),
+                    buffer: expr!("RDI:8"),
+                    operation: BinOpType::IntAdd,
+                    key: Bitvector::from_u8(5),
+                    count: Some(variable!("RCX:8")),
+                },
+            })
+        );
+
+        // A XOR decode loop over 4-byte elements without a counter.
+        let mut blk = mock_loop_block(defs![
+            "load: EAX:4 := Load from RDI:8",
+            "store: Store at RDI:8 := EAX:4",
+            "RDI:8 = RDI:8 + 0x4:8"
+        ]);
+        blk.term.defs.insert(
+            1,
+            Term {
+                tid: Tid::new("decode"),
+                term: Def::Assign {
+                    var: variable!("EAX:4"),
+                    value: Expression::BinOp {
+                        op: BinOpType::IntXOr,
+                        lhs: Box::new(expr!("0x42424242:4")),
+                        rhs: Box::new(expr!("EAX:4")),
+                    },
+                },
+            },
+        );
+        let summary = LoopSummary::from_block(&blk).unwrap();
+        assert_eq!(summary.step, ByteSize::new(4));
+        assert!(matches!(
+            summary.idiom,
+            LoopIdiom::Decode {
+                operation: BinOpType::IntXOr,
+                count: None,
+                ..
+            }
+        ));
+
+        // The key has to be constant.
+        let blk = mock_loop_block(defs![
+            "load: AL:1 := Load from RDI:8",
+            "AL:1 = AL:1 + BL:1",
+            "store: Store at RDI:8 := AL:1",
+            "RDI:8 = RDI:8 + 0x1:8"
+        ]);
+        assert_eq!(LoopSummary::from_block(&blk), None);
+
+        // Elements that are decoded in place but not advanced are no loop idiom.
+        let blk = mock_loop_block(defs![
+            "load: AL:1 := Load from RDI:8",
+            "AL:1 = AL:1 + 0x5:1",
+            "store: Store at RDI:8 := AL:1"
+        ]);
+        assert_eq!(LoopSummary::from_block(&blk), None);
+    }
+}
@@ -3,12 +3,25 @@
 
 pub mod backward_interprocedural_fixpoint;
 pub mod callgraph;
+pub mod concrete_interpreter;
+pub mod def_use;
+pub mod explanation;
 pub mod fixpoint;
 pub mod forward_interprocedural_fixpoint;
+pub mod function_hashing;
 pub mod function_signature;
 pub mod graph;
+pub mod input_provenance;
+pub mod input_sources;
 pub mod interprocedural_fixpoint_generic;
+pub mod library_summaries;
+pub mod loop_idioms;
 pub mod pointer_inference;
+pub mod seeding;
+pub mod slicing;
 pub mod string_abstraction;
+pub mod symbolic_execution;
 pub mod taint;
+pub mod variadic_arguments;
 pub mod vsa_results;
+pub mod vtables;
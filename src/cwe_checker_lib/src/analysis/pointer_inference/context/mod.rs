@@ -1,4 +1,5 @@
 use crate::abstract_domain::*;
+use crate::analysis::fixpoint::PrecisionLoss;
 use crate::analysis::function_signature::AccessPattern;
 use crate::analysis::function_signature::FunctionSignature;
 use crate::analysis::graph::Graph;
@@ -41,6 +42,16 @@ pub struct Context<'a> {
     pub log_collector: crossbeam_channel::Sender<LogThreadMsg>,
     /// Names of `malloc`-like extern functions.
     pub allocation_symbols: Vec<String>,
+    /// Names of `realloc`-like extern functions.
+    pub reallocation_symbols: Vec<String>,
+    /// Names of functions that wrap `malloc`-like functions, mapped to the index of their size parameter.
+    pub allocation_wrappers: BTreeMap<String, usize>,
+    /// Names of extern functions returning a pointer to a string controlled by the user.
+    pub input_string_symbols: Vec<String>,
+    /// The maximal number of times that the callee state of a recursive call is applied at the return site.
+    pub recursion_bound: Option<u64>,
+    /// The command of the SMT solver used to refine values at compound branch conditions.
+    pub smt_solver: Option<Vec<String>>,
 }
 
 impl<'a> Context<'a> {
@@ -60,6 +71,11 @@ impl<'a> Context<'a> {
                 crate::analysis::function_signature::stubs::generate_param_access_stubs(),
             log_collector,
             allocation_symbols: config.allocation_symbols,
+            reallocation_symbols: config.reallocation_symbols,
+            allocation_wrappers: config.allocation_wrappers,
+            input_string_symbols: config.input_string_symbols,
+            recursion_bound: config.recursion_bound,
+            smt_solver: config.smt_solver,
         }
     }
 
@@ -72,7 +88,7 @@ impl<'a> Context<'a> {
         var: &Variable,
         address: &Expression,
     ) -> bool {
-        if self.project.cpu_architecture.contains("MIPS") && var.name == "gp" {
+        if self.project.get_cpu_architecture() == CpuArchitecture::Mips && var.name == "gp" {
             if let Ok(gp_val) =
                 state.load_value(address, var.size, &self.project.runtime_memory_image)
             {
@@ -114,14 +130,9 @@ impl<'a> Context<'a> {
         &self,
         state_before_return: &State,
     ) -> Result<(), Error> {
-        let expected_stack_pointer_offset = match self.project.cpu_architecture.as_str() {
-            "x86" | "x86_32" | "x86_64" => {
-                Bitvector::from_u64(u64::from(self.project.get_pointer_bytesize()))
-                    .into_truncate(apint::BitWidth::from(self.project.get_pointer_bytesize()))
-                    .unwrap()
-            }
-            _ => Bitvector::zero(apint::BitWidth::from(self.project.get_pointer_bytesize())),
-        };
+        let expected_stack_pointer_offset =
+            Bitvector::from_u64(u64::from(self.project.get_return_address_size()))
+                .into_resize_unsigned(self.project.get_pointer_bytesize());
         match state_before_return
             .get_register(&self.project.stack_pointer_register)
             .get_if_unique_target()
@@ -145,24 +156,9 @@ impl<'a> Context<'a> {
         call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
     ) -> State {
-        let address_bytesize = self.project.get_pointer_bytesize();
-
         match extern_symbol.get_unique_return_register() {
             Ok(return_register) => {
-                let object_id = AbstractIdentifier::new(
-                    call.tid.clone(),
-                    AbstractLocation::from_var(return_register).unwrap(),
-                );
-                state.memory.add_abstract_object(
-                    object_id.clone(),
-                    address_bytesize,
-                    Some(super::object::ObjectType::Heap),
-                );
-                let pointer = Data::from_target(
-                    object_id,
-                    Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
-                );
-                state.set_register(return_register, pointer);
+                self.add_new_object_in_register(&mut state, &call.tid, return_register);
                 state
             }
             Err(err) => {
@@ -173,6 +169,103 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Add a new heap object that was created by the call with the given TID
+    /// and store a pointer to it in the given return register.
+    fn add_new_object_in_register(
+        &self,
+        state: &mut State,
+        call_tid: &Tid,
+        return_register: &Variable,
+    ) {
+        let address_bytesize = self.project.get_pointer_bytesize();
+        let object_id = AbstractIdentifier::new(
+            call_tid.clone(),
+            AbstractLocation::from_var(return_register).unwrap(),
+        );
+        state.memory.add_abstract_object(
+            object_id.clone(),
+            address_bytesize,
+            Some(super::object::ObjectType::Heap),
+        );
+        let pointer = Data::from_target(
+            object_id,
+            Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
+        );
+        state.set_register(return_register, pointer);
+    }
+
+    /// Return `true` if the function with the given TID is one of the configured allocation wrappers.
+    fn is_allocation_wrapper(&self, fn_tid: &Tid) -> bool {
+        self.project
+            .program
+            .term
+            .subs
+            .get(fn_tid)
+            .is_some_and(|sub| self.allocation_wrappers.contains_key(&sub.term.name))
+    }
+
+    /// Handle a call to a `realloc`-like function.
+    ///
+    /// A new heap object is created for the return value of the call
+    /// (see [`add_new_object_in_call_return_register`](Self::add_new_object_in_call_return_register)).
+    /// Since the function may move the memory region,
+    /// the contents of the memory objects that the first parameter may point to are copied into the new object.
+    /// If the first parameter is the `NULL` pointer, then the call behaves like a call to `malloc`.
+    fn handle_realloc_call(
+        &self,
+        state: &State,
+        new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        let mut new_state =
+            self.add_new_object_in_call_return_register(new_state, call, extern_symbol);
+        let (Some(param), Ok(return_register)) = (
+            extern_symbol.parameters.first(),
+            extern_symbol.get_unique_return_register(),
+        ) else {
+            return new_state;
+        };
+        let old_pointer = match state.eval_parameter_arg(param, &self.project.runtime_memory_image)
+        {
+            Ok(old_pointer) => old_pointer,
+            Err(err) => {
+                self.log_debug(Err(err), Some(&call.tid));
+                return new_state;
+            }
+        };
+        let mut old_contents: Option<AbstractObject> = None;
+        for id in old_pointer.get_relative_values().keys() {
+            if let Some(object) = state.memory.get_object(id) {
+                old_contents = Some(match old_contents {
+                    Some(contents) => contents.merge(object),
+                    None => object.clone(),
+                });
+            }
+        }
+        let Some(mut old_contents) = old_contents else {
+            return new_state;
+        };
+        if old_pointer.contains_top() || old_pointer.get_absolute_value().is_some() {
+            // The pointer may be `NULL`, in which case the new object is empty.
+            old_contents = old_contents.merge(&AbstractObject::new(
+                Some(super::object::ObjectType::Heap),
+                self.project.get_pointer_bytesize(),
+            ));
+        }
+        let object_id = AbstractIdentifier::new(
+            call.tid.clone(),
+            AbstractLocation::from_var(return_register).unwrap(),
+        );
+        if let Some(object) = new_state.memory.get_object_mut(&object_id) {
+            object.overwrite_with(
+                &old_contents,
+                &Bitvector::zero(apint::BitWidth::from(self.project.get_pointer_bytesize())).into(),
+            );
+        }
+        new_state
+    }
+
     /// Check whether the jump is an indirect call whose target evaluates to a *Top* value in the given state.
     fn is_indirect_call_with_top_target(&self, state: &State, call: &Term<Jmp>) -> bool {
         match &call.term {
@@ -195,17 +288,16 @@ impl<'a> Context<'a> {
     ) {
         let stack_register = &self.project.stack_pointer_register;
         let stack_pointer = state_before_call.get_register(stack_register);
-        match self.project.cpu_architecture.as_str() {
-            "x86" | "x86_32" | "x86_64" => {
-                let offset = Bitvector::from_u64(stack_register.size.into())
-                    .into_truncate(apint::BitWidth::from(stack_register.size))
-                    .unwrap();
-                new_state.set_register(
-                    stack_register,
-                    stack_pointer.bin_op(BinOpType::IntAdd, &offset.into()),
-                );
-            }
-            _ => new_state.set_register(stack_register, stack_pointer),
+        let return_address_size = self.project.get_return_address_size();
+        if return_address_size == ByteSize::new(0) {
+            new_state.set_register(stack_register, stack_pointer);
+        } else {
+            let offset = Bitvector::from_u64(return_address_size.into())
+                .into_resize_unsigned(stack_register.size);
+            new_state.set_register(
+                stack_register,
+                stack_pointer.bin_op(BinOpType::IntAdd, &offset.into()),
+            );
         }
     }
 
@@ -305,8 +397,18 @@ impl<'a> Context<'a> {
                 "(NULL Pointer Dereference) Memory access at {} may result in a NULL dereference",
                 tid.address()
             ),
+            related: Vec::new(),
+            source_location: None,
+            demangled_symbols: BTreeMap::new(),
+            mitigations: BTreeSet::new(),
+            severity: Severity::Normal,
+            reproduction_hints: Vec::new(),
+            confirmed: false,
+            explanation: None,
+            function_hash: None,
+            third_party_library: None,
         };
-        let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+        let _ = self.log_collector.send(warning.into());
     }
 
     /// Merge global memory data from the callee global memory object to the caller global memory object
@@ -443,6 +545,5 @@ fn mark_values_in_caller_global_mem_as_potentially_overwritten(
     }
 }
 
-// TODO: Fix tests.
-//#[cfg(test)]
-//mod tests;
+#[cfg(test)]
+mod tests;
@@ -25,7 +25,7 @@ fn call_term(target_name: &str) -> Term<Jmp> {
 
 fn return_term(target_name: &str) -> Term<Jmp> {
     Term {
-        tid: Tid::new(format!("return")),
+        tid: Tid::new("return".to_string()),
         term: Jmp::Return(Expression::Unknown {
             description: target_name.into(),
             size: ByteSize::new(8),
@@ -34,11 +34,31 @@ fn return_term(target_name: &str) -> Term<Jmp> {
 }
 
 fn mock_project() -> (Project, Config) {
-    let project = Project::mock_x64();
+    let mut project = Project::mock_x64();
+    let mut realloc = ExternSymbol::mock_x64("realloc");
+    realloc.parameters.push(Arg::mock_register("RSI", 8));
+    project
+        .program
+        .term
+        .extern_symbols
+        .insert(realloc.tid.clone(), realloc);
+    let xmalloc = ExternSymbol::mock_x64("xmalloc");
+    project
+        .program
+        .term
+        .extern_symbols
+        .insert(xmalloc.tid.clone(), xmalloc);
     (
         project,
         Config {
             allocation_symbols: vec!["malloc".into()],
+            reallocation_symbols: vec!["realloc".into()],
+            allocation_wrappers: BTreeMap::new(),
+            input_string_symbols: Vec::new(),
+            recursion_bound: None,
+            memory_limit_mib: None,
+            smt_solver: None,
+            record_precision_losses: false,
         },
     )
 }
@@ -140,6 +160,118 @@ fn context_problem_implementation() {
         .is_top());
 }
 
+#[test]
+fn handle_realloc_call() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::pointer_inference::Data;
+
+    let context = mock_context();
+    let state = State::new(&variable!("RSP:8"), Tid::new("main"), BTreeSet::new());
+    let global_memory = &context.project.runtime_memory_image;
+
+    let mut state = context
+        .update_call_stub(&state, &call_term("malloc"))
+        .unwrap();
+    let old_pointer = Data::from_target(new_id("call_malloc", "RAX"), bv(0));
+    state
+        .store_value(&old_pointer, &bv(42).into(), global_memory)
+        .unwrap();
+    state.set_register(&variable!("RDI:8"), old_pointer);
+
+    // The contents of the old object are copied into the new object.
+    let state_after_realloc = context
+        .update_call_stub(&state, &call_term("realloc"))
+        .unwrap();
+    let new_pointer = Data::from_target(new_id("call_realloc", "RAX"), bv(0));
+    assert_eq!(
+        state_after_realloc.get_register(&variable!("RAX:8")),
+        new_pointer
+    );
+    assert_eq!(
+        state_after_realloc
+            .load_value_from_address(&new_pointer, ByteSize::new(8), global_memory)
+            .unwrap(),
+        bv(42).into()
+    );
+
+    // Functions that are not configured as `realloc`-like do not copy the contents.
+    let mut context = mock_context();
+    context.reallocation_symbols = Vec::new();
+    let state_after_realloc = context
+        .update_call_stub(&state, &call_term("realloc"))
+        .unwrap();
+    assert!(state_after_realloc
+        .load_value_from_address(&new_pointer, ByteSize::new(8), global_memory)
+        .unwrap()
+        .is_top());
+}
+
+#[test]
+fn handle_allocation_wrapper_call() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::pointer_inference::object::ObjectType;
+    use crate::analysis::pointer_inference::Data;
+
+    let mut context = mock_context();
+    context.allocation_wrappers = BTreeMap::from([("xmalloc".to_string(), 0)]);
+    let state = State::new(&variable!("RSP:8"), Tid::new("main"), BTreeSet::new());
+
+    let state = context
+        .update_call_stub(&state, &call_term("xmalloc"))
+        .unwrap();
+    let object_id = new_id("call_xmalloc", "RAX");
+    assert_eq!(
+        state.get_register(&variable!("RAX:8")),
+        Data::from_target(object_id.clone(), bv(0))
+    );
+    assert_eq!(
+        state
+            .memory
+            .get_object(&object_id)
+            .unwrap()
+            .get_object_type(),
+        Some(ObjectType::Heap)
+    );
+}
+
+#[test]
+fn allocation_wrapper_size_params() {
+    let (mut project, _) = mock_project();
+    let mut block = Blk::mock();
+    block.term.jmps = vec![call_term("xmalloc"), call_term("my_alloc")];
+    project.program.term.subs = BTreeMap::from([
+        (
+            Tid::new("main"),
+            Term {
+                tid: Tid::new("main"),
+                term: Sub::new("main", vec![block], Some("__stdcall")),
+            },
+        ),
+        (
+            Tid::new("my_alloc"),
+            Term {
+                tid: Tid::new("my_alloc"),
+                term: Sub::new("my_alloc", Vec::new(), Some("__stdcall")),
+            },
+        ),
+    ]);
+    let allocation_wrappers =
+        BTreeMap::from([("xmalloc".to_string(), 0), ("my_alloc".to_string(), 1)]);
+
+    // The size of the heap object created by a wrapper call is given by the configured parameter.
+    let size_params =
+        super::super::compute_allocation_wrapper_size_params(&project, &allocation_wrappers);
+    assert_eq!(size_params.len(), 2);
+    assert_eq!(
+        size_params[&Tid::new("call_xmalloc")],
+        Arg::mock_register("RDI", 8)
+    );
+    assert_eq!(
+        size_params[&Tid::new("call_my_alloc")],
+        Arg::from_var(variable!("RSI:8"), None)
+    );
+}
+
 #[test]
 fn update_return() {
     use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
@@ -195,11 +327,11 @@ fn update_return() {
     );
     state_before_call.set_register(
         &variable!("RDI:8"),
-        Data::from_target(param_obj_id.clone(), bv(0).into()),
+        Data::from_target(param_obj_id.clone(), bv(0)),
     );
     state_before_call.set_register(
         &variable!("RBX:8"),
-        Data::from_target(param_obj_id.clone(), bv(0).into()),
+        Data::from_target(param_obj_id.clone(), bv(0)),
     );
 
     let state = context
@@ -215,29 +347,22 @@ fn update_return() {
     assert_eq!(state.stack_id, new_id("caller", "RSP"));
     assert_eq!(
         state.get_register(&variable!("RAX:8")),
-        Data::from_target(
-            AbstractIdentifier::mock("call_callee", "RAX", 8),
-            bv(0).into()
-        )
+        Data::from_target(AbstractIdentifier::mock("call_callee", "RAX", 8), bv(0))
     );
     assert_eq!(
         state.get_register(&variable!("RBX:8")),
-        Data::from_target(param_obj_id.clone(), bv(0).into())
+        Data::from_target(param_obj_id.clone(), bv(0))
     );
     assert_eq!(
         state.get_register(&variable!("RDX:8")),
-        Data::from_target(param_obj_id.clone(), bv(0).into())
+        Data::from_target(param_obj_id.clone(), bv(0))
     );
     assert_eq!(
         state.get_register(&variable!("RSP:8")),
-        Data::from_target(new_id("caller", "RSP"), bv(-8).into())
+        Data::from_target(new_id("caller", "RSP"), bv(-8))
     );
     assert_eq!(state.memory.get_all_object_ids().len(), 4);
-    assert!(state
-        .memory
-        .get_all_object_ids()
-        .get(&param_obj_id)
-        .is_some());
+    assert!(state.memory.get_all_object_ids().contains(&param_obj_id));
     let value = state
         .memory
         .get_object(&AbstractIdentifier::mock("call_callee", "RAX", 8))
@@ -292,11 +417,11 @@ fn get_unsound_caller_ids() {
     let mut callee_id_to_caller_data_map = BTreeMap::new();
     callee_id_to_caller_data_map.insert(
         new_id("callee", "RDI"),
-        Data::from_target(new_id("caller", "RAX"), bv(1).into()),
+        Data::from_target(new_id("caller", "RAX"), bv(1)),
     );
     callee_id_to_caller_data_map.insert(
         new_id("callee", "RSI"),
-        Data::from_target(new_id("caller", "RAX"), bv(2).into()),
+        Data::from_target(new_id("caller", "RAX"), bv(2)),
     );
     callee_id_to_caller_data_map.insert(
         AbstractIdentifier::new(
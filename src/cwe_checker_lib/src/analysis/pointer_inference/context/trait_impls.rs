@@ -13,6 +13,11 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         value1.merge(value2)
     }
 
+    /// Compare two states using the partial order of the contained values.
+    fn less_or_equal(&self, value1: &State, value2: &State) -> bool {
+        value1.less_or_equal(value2)
+    }
+
     /// Update the state according to the effects of the given `Def` term.
     fn update_def(&self, state: &Self::Value, def: &Term<Def>) -> Option<Self::Value> {
         let mut new_state = state.clone();
@@ -75,11 +80,11 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         _target_node: &crate::analysis::graph::Node,
         _calling_convention: &Option<String>,
     ) -> Option<State> {
-        if let Jmp::Call { .. } = call_term.term {
+        if let Jmp::Call { .. } | Jmp::CallInd { .. } | Jmp::Branch(_) = call_term.term {
             // No information flows from caller to the callee in the analysis.
+            // Branches correspond to tail calls.
+            // Indirect calls have call edges to their known call targets.
             None
-        } else if let Jmp::CallInd { .. } = call_term.term {
-            panic!("Indirect call edges not yet supported.")
         } else {
             panic!("Malformed control flow graph: Call edge was not a call.")
         }
@@ -223,6 +228,16 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 .memory
                 .assume_arbitrary_writes_to_object(id, &BTreeSet::new());
         }
+        // Calls to allocation wrappers are handled like calls to `malloc`.
+        if self.is_allocation_wrapper(state_before_return.get_fn_tid()) {
+            if let Some(return_register) = cconv.integer_return_register.first() {
+                self.add_new_object_in_register(
+                    &mut state_after_return,
+                    &call_term.tid,
+                    return_register,
+                );
+            }
+        }
         // Cleanup
         state_after_return.remove_unreferenced_objects();
 
@@ -265,7 +280,22 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                     );
                     Some(new_state)
                 }
-                malloc_like_fn if self.allocation_symbols.iter().any(|x| x == malloc_like_fn) => {
+                realloc_like_fn
+                    if self
+                        .reallocation_symbols
+                        .iter()
+                        .any(|x| x == realloc_like_fn) =>
+                {
+                    Some(self.handle_realloc_call(state, new_state, call, extern_symbol))
+                }
+                malloc_like_fn
+                    if self.allocation_symbols.iter().any(|x| x == malloc_like_fn)
+                        || self.allocation_wrappers.contains_key(malloc_like_fn)
+                        || self
+                            .input_string_symbols
+                            .iter()
+                            .any(|x| x == malloc_like_fn) =>
+                {
                     Some(self.add_new_object_in_call_return_register(
                         new_state,
                         call,
@@ -305,12 +335,76 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         is_true: bool,
     ) -> Option<State> {
         let mut specialized_state = state.clone();
-        match specialized_state
+        if specialized_state
             .specialize_by_expression_result(condition, Bitvector::from_u8(is_true as u8).into())
+            .is_err()
         {
-            Ok(_) => Some(specialized_state),
             // State is unsatisfiable
-            Err(_) => None,
+            return None;
+        }
+        #[cfg(feature = "smt")]
+        if let Some(solver) = &self.smt_solver {
+            if specialized_state
+                .specialize_by_smt_solver(condition, is_true, solver)
+                .is_err()
+            {
+                return None;
+            }
         }
+        Some(specialized_state)
+    }
+
+    /// Get the configured maximal number of updates of return sites of recursive calls.
+    fn get_recursion_bound(&self) -> Option<u64> {
+        self.recursion_bound
+    }
+
+    /// Estimate the memory occupied by the state.
+    fn get_approximate_memory_size(&self, state: &State) -> usize {
+        state.approximate_memory_size()
+    }
+
+    /// Collapse the state, keeping only the value of the stack pointer.
+    fn degrade_value(&self, state: &State) -> Option<State> {
+        let mut degraded_state = state.clone();
+        degraded_state.collapse_to_top(&self.project.stack_pointer_register);
+        Some(degraded_state)
+    }
+
+    /// Get the registers whose values are exact in one of the states but not in the merged state.
+    fn get_precision_losses(
+        &self,
+        value1: &State,
+        value2: &State,
+        merged: &State,
+    ) -> Vec<PrecisionLoss> {
+        value1.get_register_precision_losses(value2, merged)
+    }
+
+    /// Get the registers that became `Top` because of a call to an extern function
+    /// or to an unknown target.
+    fn get_call_stub_precision_losses(
+        &self,
+        state: &State,
+        new_state: &State,
+        call: &Term<Jmp>,
+    ) -> Vec<PrecisionLoss> {
+        let callee = match &call.term {
+            Jmp::Call { target, .. } => self
+                .extern_symbol_map
+                .get(target)
+                .map(|symbol| symbol.name.as_str()),
+            _ => None,
+        };
+        state.get_register_precision_losses_of_call(new_state, callee)
+    }
+
+    /// Handle the return from a recursive call like the return from a call to an unknown function.
+    fn update_recursive_call_fallback(
+        &self,
+        state_before_call: &State,
+        _call_term: &Term<Jmp>,
+    ) -> Option<State> {
+        self.handle_call_to_generic_unknown_function(state_before_call)
     }
 }
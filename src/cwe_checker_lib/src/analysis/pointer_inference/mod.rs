@@ -24,12 +24,12 @@
 //!
 //! See the `Config` struct for configurable analysis parameters.
 
-use super::fixpoint::Computation;
+use super::fixpoint::{Computation, PrecisionLoss};
 use super::forward_interprocedural_fixpoint::GeneralizedContext;
-use super::interprocedural_fixpoint_generic::NodeValue;
+use super::interprocedural_fixpoint_generic::{get_failure_logs, NodeValue};
 use crate::abstract_domain::{AbstractIdentifier, DataDomain, IntervalDomain, SizedDomain};
 use crate::analysis::forward_interprocedural_fixpoint::Context as _;
-use crate::analysis::graph::{Graph, Node};
+use crate::analysis::graph::{get_nodes_of_same_function, Graph, Node};
 use crate::checkers::prelude::*;
 use crate::intermediate_representation::*;
 use crate::prelude::*;
@@ -37,6 +37,8 @@ use crate::utils::log::*;
 use petgraph::graph::NodeIndex;
 use petgraph::visit::IntoNodeReferences;
 use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
 mod context;
 pub mod object;
@@ -53,12 +55,39 @@ const VERSION: &str = "0.2";
 /// The recursion limit for nested pointers.
 const POINTER_RECURSION_DEPTH_LIMIT: u64 = 2;
 
+/// The "Memory" CWE check, which reports the CWE warnings generated during the analysis.
+pub struct MemoryCheck;
+
+impl Checker for MemoryCheck {
+    fn name(&self) -> &'static str {
+        "Memory"
+    }
+
+    fn version(&self) -> &'static str {
+        VERSION
+    }
+
+    fn cwes(&self) -> Vec<u32> {
+        vec![476]
+    }
+
+    fn required_analyses(&self) -> &'static [Analysis] {
+        &[Analysis::PointerInference]
+    }
+
+    fn run(
+        &self,
+        analysis_results: &AnalysisResults,
+        config: &serde_json::Value,
+        debug_settings: &debug::Settings,
+    ) -> WithLogs<Vec<CweWarning>> {
+        extract_pi_analysis_results(analysis_results, config, debug_settings)
+    }
+}
+
 /// The name and version number of the "Memory" CWE check.
-pub static CWE_MODULE: CweModule = CweModule {
-    name: "Memory",
-    version: VERSION,
-    run: extract_pi_analysis_results,
-};
+pub static CWE_MODULE: MemoryCheck = MemoryCheck;
+register_checker!(CWE_MODULE);
 
 /// The abstract domain to use for absolute values.
 pub type ValueDomain = IntervalDomain;
@@ -72,6 +101,53 @@ pub struct Config {
     /// Names of extern functions that are `malloc`-like,
     /// i.e. the unique return value is a pointer to a newly allocated chunk of memory or a NULL pointer.
     pub allocation_symbols: Vec<String>,
+    /// Names of extern functions that are `realloc`-like,
+    /// i.e. the first parameter points to a memory region that is moved into the returned memory region.
+    ///
+    /// The contents of the old memory region are copied into the new heap object created for the return value.
+    #[serde(default)]
+    pub reallocation_symbols: Vec<String>,
+    /// Names of functions that wrap `malloc`-like functions,
+    /// mapped to the index of their size parameter.
+    ///
+    /// Calls to these functions are handled like calls to `malloc`,
+    /// regardless of whether the wrapper is an extern or an internal function.
+    #[serde(default)]
+    pub allocation_wrappers: BTreeMap<String, usize>,
+    /// Names of extern functions returning a pointer to a string controlled by the user, e.g. `getenv`.
+    ///
+    /// A new memory object is created for the returned string,
+    /// so that the string abstraction can track it.
+    #[serde(default)]
+    pub input_string_symbols: Vec<String>,
+    /// The maximal number of times that the state at the return site of a call to a recursive function
+    /// is updated with the state at the end of the callee.
+    ///
+    /// Afterwards the call is handled like a call to an unknown function,
+    /// which ensures that the fixpoint computation for recursive functions terminates quickly.
+    /// If not set, the number of updates is not bounded.
+    #[serde(default)]
+    pub recursion_bound: Option<u64>,
+    /// The limit for the approximate memory usage of all states of the fixpoint computation in MiB.
+    ///
+    /// If the limit is exceeded, the largest states are collapsed,
+    /// i.e. all knowledge about register and memory contents except for the stack pointer is removed from them.
+    /// If not set, the memory usage is not bounded.
+    #[serde(default)]
+    pub memory_limit_mib: Option<u64>,
+    /// The command of an SMT-LIB 2 solver that reads from its standard input, e.g. `["z3", "-in"]`.
+    ///
+    /// If set, values are also refined at branch conditions that are too complex for the
+    /// specialization by the abstract domains, e.g. `(x & 0xff) < n && n <= 16`.
+    /// Only used if the library is compiled with the `smt` feature.
+    #[serde(default)]
+    pub smt_solver: Option<Vec<String>>,
+    /// If set, the merges and calls at which register values lose precision are recorded,
+    /// so that CWE warnings can be explained by them
+    /// and the program points at which values become `Top` can be reported.
+    /// See [`PointerInference::get_register_precision_losses`].
+    #[serde(default)]
+    pub record_precision_losses: bool,
 }
 
 /// A wrapper struct for the pointer inference computation object.
@@ -95,6 +171,8 @@ pub struct PointerInference<'a> {
     /// Maps the TIDs of call instructions to a map mapping callee IDs to the corresponding value in the caller.
     /// The map will be filled after the fixpoint computation finished.
     id_renaming_maps_at_calls: HashMap<Tid, BTreeMap<AbstractIdentifier, Data>>,
+    /// Maps the TIDs of calls to allocation wrappers to the size parameter of the call.
+    allocation_wrapper_size_params: HashMap<Tid, Arg>,
 }
 
 impl<'a> PointerInference<'a> {
@@ -105,13 +183,24 @@ impl<'a> PointerInference<'a> {
         log_sender: crossbeam_channel::Sender<LogThreadMsg>,
         print_stats: bool,
     ) -> PointerInference<'a> {
-        let context = Context::new(analysis_results, config, log_sender.clone());
         let project = analysis_results.project;
+        let allocation_wrapper_size_params =
+            compute_allocation_wrapper_size_params(project, &config.allocation_wrappers);
+        let memory_limit = config.memory_limit_mib;
+        let record_precision_losses = config.record_precision_losses;
+        let context = Context::new(analysis_results, config, log_sender.clone());
         let function_signatures = analysis_results.function_signatures.unwrap();
         let sub_to_entry_node_map = crate::analysis::graph::get_entry_nodes_of_subs(context.graph);
 
         let mut fixpoint_computation =
             super::forward_interprocedural_fixpoint::create_computation_with_bottom_up_worklist_order(context, None);
+        if let Some(memory_limit) = memory_limit {
+            fixpoint_computation
+                .set_memory_limit(usize::try_from(memory_limit << 20).unwrap_or(usize::MAX));
+        }
+        if record_precision_losses {
+            fixpoint_computation.enable_precision_loss_recording();
+        }
         if print_stats {
             let _ = log_sender.send(LogThreadMsg::Log(
                 LogMessage::new_info(format!(
@@ -128,7 +217,7 @@ impl<'a> PointerInference<'a> {
                 &project.stack_pointer_register,
                 sub_tid.clone(),
             );
-            if project.cpu_architecture.contains("MIPS") {
+            if project.get_cpu_architecture() == CpuArchitecture::Mips {
                 let _ = fn_entry_state
                     .set_mips_link_register(&sub_tid, project.stack_pointer_register.size);
             }
@@ -145,6 +234,7 @@ impl<'a> PointerInference<'a> {
             addresses_at_defs: HashMap::new(),
             states_at_tids: HashMap::new(),
             id_renaming_maps_at_calls: HashMap::new(),
+            allocation_wrapper_size_params,
         }
     }
 
@@ -157,17 +247,51 @@ impl<'a> PointerInference<'a> {
         if print_stats {
             self.count_blocks_with_state();
         }
-        if !self.computation.has_stabilized() {
+        if self.computation.is_aborted() {
+            let worklist_size = self.computation.get_worklist().len();
+            self.log_info(format!(
+                "Fixpoint computation was aborted. Remaining worklist size: {worklist_size}"
+            ));
+        } else if !self.computation.has_stabilized() {
             let worklist_size = self.computation.get_worklist().len();
             self.log_info(format!(
                 "Fixpoint did not stabilize. Remaining worklist size: {worklist_size}"
             ));
         }
+        self.log_degraded_functions();
+        for log_msg in get_failure_logs(&self.computation, "Pointer Inference") {
+            let _ = self.log_collector.send(LogThreadMsg::Log(log_msg));
+        }
         if print_stats {
             statistics::compute_and_log_mem_access_stats(self);
         }
     }
 
+    /// Generate a log message for each function containing states
+    /// that were collapsed because the memory limit was exceeded.
+    fn log_degraded_functions(&self) {
+        let graph = self.computation.get_graph();
+        let degraded_functions: BTreeMap<&Tid, &str> = self
+            .computation
+            .get_degraded_nodes()
+            .iter()
+            .map(|node| graph[*node].get_function())
+            .map(|sub| (&sub.tid, sub.term.name.as_str()))
+            .collect();
+        for (sub_tid, sub_name) in degraded_functions {
+            self.log_info(format!(
+                "Memory limit exceeded. Reduced the precision of states in function {sub_name} ({sub_tid})"
+            ));
+        }
+    }
+
+    /// Set a flag that can be used to abort the fixpoint computation, e.g. from another thread.
+    ///
+    /// If the computation gets aborted, the analysis results only contain the states computed so far.
+    pub fn set_abort_flag(&mut self, abort_flag: Arc<AtomicBool>) {
+        self.computation.set_abort_flag(abort_flag);
+    }
+
     /// Print results serialized as YAML to stdout
     pub fn print_yaml(&self) {
         let graph = self.computation.get_graph();
@@ -242,6 +366,16 @@ impl<'a> PointerInference<'a> {
         ));
     }
 
+    /// Get the number of node updates performed by the fixpoint computation.
+    pub fn get_fixpoint_steps(&self) -> u64 {
+        self.computation.get_number_of_steps()
+    }
+
+    /// Get the nodes that did not stabilize during the fixpoint computation.
+    pub fn get_non_stabilized_nodes(&self) -> Vec<NodeIndex> {
+        self.computation.get_worklist()
+    }
+
     /// Send an info log message to the log collector.
     fn log_info(&self, msg: impl Into<String>) {
         let log_msg = LogMessage::new_info(msg.into()).source("Pointer Inference");
@@ -330,12 +464,82 @@ impl<'a> PointerInference<'a> {
         }
     }
 
+    /// Get the size parameter of the given call if it is a call to one of the configured allocation wrappers.
+    pub fn get_allocation_wrapper_size_param(&self, call_tid: &Tid) -> Option<&Arg> {
+        self.allocation_wrapper_size_params.get(call_tid)
+    }
+
+    /// Returns `true` if the merges at which register values lose precision are recorded.
+    pub fn records_precision_losses(&self) -> bool {
+        self.computation.get_precision_losses().is_some()
+    }
+
+    /// Get the recorded precision losses of register values at each node
+    /// or `None` if the recording of precision losses is not enabled in the configuration.
+    pub fn get_precision_losses(&self) -> Option<&BTreeMap<NodeIndex, Vec<PrecisionLoss>>> {
+        self.computation.get_precision_losses()
+    }
+
+    /// Get the merges in the function containing the given node
+    /// at which the value of the given register lost precision.
+    ///
+    /// Returns `None` if the recording of precision losses is not enabled in the configuration.
+    pub fn get_register_precision_losses(
+        &self,
+        node: NodeIndex,
+        register: &Variable,
+    ) -> Option<Vec<(NodeIndex, &PrecisionLoss)>> {
+        let precision_losses = self.computation.get_precision_losses()?;
+        let mut register_losses = Vec::new();
+        for function_node in get_nodes_of_same_function(self.get_graph(), node) {
+            if let Some(losses) = precision_losses.get(&function_node) {
+                register_losses.extend(
+                    losses
+                        .iter()
+                        .filter(|loss| loss.location == register.name)
+                        .map(|loss| (function_node, loss)),
+                );
+            }
+        }
+        Some(register_losses)
+    }
+
     /// Get the state of the fixpoint computation at the block end node before the given jump instruction.
     /// This function only yields results after the fixpoint has been computed.
     pub fn get_state_at_jmp_tid(&self, jmp_tid: &Tid) -> Option<&State> {
         self.states_at_tids.get(jmp_tid)
     }
 
+    /// Get the state of the fixpoint computation directly before the given instruction.
+    ///
+    /// For jump instructions this is the state at the end of the containing block.
+    /// For other instructions the state is recomputed from the state at the start of the containing block.
+    /// This function only yields results after the fixpoint has been computed.
+    pub fn get_state_at_tid(&self, tid: &Tid) -> Option<State> {
+        if let Some(state) = self.states_at_tids.get(tid) {
+            return Some(state.clone());
+        }
+        let context = self.computation.get_context().get_context();
+        let graph = self.computation.get_graph();
+        for node in graph.node_indices() {
+            let Node::BlkStart(blk, _sub) = graph[node] else {
+                continue;
+            };
+            let Some(position) = blk.term.defs.iter().position(|def| def.tid == *tid) else {
+                continue;
+            };
+            let Some(NodeValue::Value(node_state)) = self.computation.get_node_value(node) else {
+                continue;
+            };
+            let mut state = node_state.clone();
+            for def in &blk.term.defs[..position] {
+                state = context.update_def(&state, def)?;
+            }
+            return Some(state);
+        }
+        None
+    }
+
     /// Get the mapping from callee IDs to caller values for the given call.
     /// This function only yields results after the fixpoint has been computed.
     ///
@@ -418,6 +622,51 @@ impl<'a> PointerInference<'a> {
     }
 }
 
+/// Compute a map from the TIDs of calls to allocation wrappers to the size parameter of the call.
+///
+/// For calls to extern symbols the parameter is taken from the extern symbol.
+/// For calls to internal functions the parameter is assumed to be the integer parameter register
+/// with the given index in the calling convention of the callee.
+/// Size parameters passed on the stack are not supported for internal functions.
+fn compute_allocation_wrapper_size_params(
+    project: &Project,
+    allocation_wrappers: &BTreeMap<String, usize>,
+) -> HashMap<Tid, Arg> {
+    let mut size_params = HashMap::new();
+    if allocation_wrappers.is_empty() {
+        return size_params;
+    }
+    for sub in project.program.term.subs.values() {
+        for jmp in sub.term.blocks.iter().flat_map(|blk| blk.term.jmps.iter()) {
+            let Jmp::Call { target, .. } = &jmp.term else {
+                continue;
+            };
+            let size_param = if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                allocation_wrappers
+                    .get(&symbol.name)
+                    .and_then(|index| symbol.parameters.get(*index))
+                    .cloned()
+            } else if let Some(callee) = project.program.term.subs.get(target) {
+                allocation_wrappers
+                    .get(&callee.term.name)
+                    .and_then(|index| {
+                        project
+                            .get_specific_calling_convention(&callee.term.calling_convention)?
+                            .integer_parameter_register
+                            .get(*index)
+                            .map(|register| Arg::from_var(register.clone(), None))
+                    })
+            } else {
+                None
+            };
+            if let Some(size_param) = size_param {
+                size_params.insert(jmp.tid.clone(), size_param);
+            }
+        }
+    }
+    size_params
+}
+
 /// The entry point for the memory analysis check.
 /// Does not actually compute anything
 /// but just extracts the results of the already computed pointer inference analysis.
@@ -463,15 +712,13 @@ pub fn run<'a>(
     computation
 }
 
-// TODO: Fix tests.
-/*
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::abstract_domain::AbstractLocation;
     use crate::analysis::vsa_results::VsaResult;
+    use crate::intermediate_representation::ByteSize;
     use crate::intermediate_representation::{Arg, Expression, RuntimeMemoryImage, Tid};
-    use crate::ByteSize;
 
     impl<'a> PointerInference<'a> {
         pub fn mock(project: &'a Project) -> PointerInference<'a> {
@@ -479,6 +726,13 @@ pub mod tests {
             let analysis_results: &'a AnalysisResults = Box::leak(analysis_results);
             let config = Config {
                 allocation_symbols: vec!["malloc".to_string()],
+                reallocation_symbols: Vec::new(),
+                allocation_wrappers: BTreeMap::new(),
+                input_string_symbols: Vec::new(),
+                recursion_bound: None,
+                memory_limit_mib: None,
+                smt_solver: None,
+                record_precision_losses: false,
             };
             let (log_sender, _) = crossbeam_channel::unbounded();
             PointerInference::new(analysis_results, config, log_sender, false)
@@ -500,6 +754,12 @@ pub mod tests {
         pub fn get_mut_states_at_tids(&mut self) -> &mut HashMap<Tid, State> {
             &mut self.states_at_tids
         }
+
+        pub fn get_mut_id_renaming_maps_at_calls(
+            &mut self,
+        ) -> &mut HashMap<Tid, BTreeMap<AbstractIdentifier, Data>> {
+            &mut self.id_renaming_maps_at_calls
+        }
     }
 
     /// Simple placeholder for an object that implements the [`VsaResult`]
@@ -582,4 +842,3 @@ pub mod tests {
         }
     }
 }
-*/
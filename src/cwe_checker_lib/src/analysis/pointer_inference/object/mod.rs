@@ -210,6 +210,30 @@ impl AbstractDomain for AbstractObject {
     }
 }
 
+impl DomainOrder for AbstractObject {
+    /// Compare the pointer targets, the uniqueness, the type and the memory regions of the objects.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self == other
+            || (self
+                .inner
+                .pointer_targets
+                .is_subset(&other.inner.pointer_targets)
+                && (self.inner.is_unique || !other.inner.is_unique)
+                && (other.inner.type_.is_none() || self.inner.type_ == other.inner.type_)
+                && self.inner.memory.less_or_equal(&other.inner.memory))
+    }
+}
+
+impl ApproximateMemorySize for AbstractObject {
+    /// Count the memory of the object, its pointer targets and its memory region.
+    fn approximate_memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + std::mem::size_of::<Inner>()
+            + self.inner.pointer_targets.len() * std::mem::size_of::<AbstractIdentifier>()
+            + self.inner.memory.approximate_memory_size()
+    }
+}
+
 impl AbstractObject {
     /// Get a more compact json-representation of the abstract object.
     /// Intended for pretty printing, not useable for serialization/deserialization.
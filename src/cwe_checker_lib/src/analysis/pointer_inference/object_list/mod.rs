@@ -192,6 +192,33 @@ impl AbstractDomain for AbstractObjectList {
     }
 }
 
+impl DomainOrder for AbstractObjectList {
+    /// Every object of `self` has to be contained in `other` with a greater or equal value.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self == other
+            || self.objects.iter().all(|(id, object)| {
+                other
+                    .objects
+                    .get(id)
+                    .is_some_and(|other_object| object.less_or_equal(other_object))
+            })
+    }
+}
+
+impl ApproximateMemorySize for AbstractObjectList {
+    /// Count the memory of the list and of all objects contained in it.
+    fn approximate_memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self
+                .objects
+                .values()
+                .map(|object| {
+                    std::mem::size_of::<AbstractIdentifier>() + object.approximate_memory_size()
+                })
+                .sum::<usize>()
+    }
+}
+
 impl AbstractObjectList {
     /// Get a more compact json-representation of the abstract object list.
     /// Intended for pretty printing, not useable for serialization/deserialization.
@@ -2,6 +2,8 @@ use super::object::AbstractObject;
 use super::object_list::AbstractObjectList;
 use super::Data;
 use crate::abstract_domain::*;
+use crate::analysis::explanation::format_abstract_value;
+use crate::analysis::fixpoint::{PrecisionLoss, PrecisionLossCause};
 use crate::analysis::function_signature::AccessPattern;
 use crate::analysis::function_signature::FunctionSignature;
 use crate::intermediate_representation::*;
@@ -12,6 +14,8 @@ use std::sync::Arc;
 
 mod access_handling;
 mod id_manipulation;
+#[cfg(feature = "smt")]
+mod smt_refinement;
 mod value_specialization;
 
 /// Contains all information known about the state of a program at a specific point of time.
@@ -292,6 +296,19 @@ impl State {
         }
     }
 
+    /// Remove all knowledge about the contents of registers and memory objects from the state,
+    /// except for the value of the stack register.
+    ///
+    /// The memory objects themselves and their possible pointer targets are kept.
+    /// This reduces the memory occupied by the state at the cost of precision.
+    pub fn collapse_to_top(&mut self, stack_register: &Variable) {
+        self.register.retain(|var, _| var == stack_register);
+        for object in self.memory.iter_objects_mut() {
+            let address_bytesize = object.get_mem_region().get_address_bytesize();
+            object.overwrite_mem_region(MemRegion::new(address_bytesize));
+        }
+    }
+
     /// Clear all non-callee-saved registers from the state.
     /// This automatically also removes all virtual registers.
     /// The parameter is a list of callee-saved register names.
@@ -405,6 +422,26 @@ impl AbstractDomain for State {
     }
 }
 
+impl DomainOrder for State {
+    /// Compare the register values and the memory objects of states of the same function.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self == other
+            || (self.stack_id == other.stack_id
+                && self.known_global_addresses == other.known_global_addresses
+                && self.register.less_or_equal(&other.register)
+                && self.memory.less_or_equal(&other.memory))
+    }
+}
+
+impl ApproximateMemorySize for State {
+    /// Count the memory of the register values and of all memory objects.
+    fn approximate_memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.register.approximate_memory_size()
+            + self.memory.approximate_memory_size()
+    }
+}
+
 impl State {
     /// Get a more compact json-representation of the state.
     /// Intended for pretty printing, not useable for serialization/deserialization.
@@ -428,6 +465,107 @@ impl State {
     }
 }
 
+impl State {
+    /// Get the registers whose values are exact in `self` or `other` but not in the merged state `merged`,
+    /// e.g. registers that contain different constants in `self` and `other`,
+    /// and the registers that are `Top` in the merged state but not in both `self` and `other`.
+    ///
+    /// A value is exact if it is a constant or a pointer with a constant offset into a single memory object.
+    /// If neither value is `Top` but the merged value is, the merged value was widened.
+    pub fn get_register_precision_losses(
+        &self,
+        other: &State,
+        merged: &State,
+    ) -> Vec<PrecisionLoss> {
+        let registers: BTreeSet<&Variable> =
+            self.register.keys().chain(other.register.keys()).collect();
+        registers
+            .into_iter()
+            .filter_map(|register| {
+                let value1 = self.get_register(register);
+                let value2 = other.get_register(register);
+                let merged_value = merged.get_register(register);
+                let (cause, top) = if is_top_value(&merged_value)
+                    && !(is_top_value(&value1) && is_top_value(&value2))
+                {
+                    if is_top_value(&value1) || is_top_value(&value2) {
+                        (PrecisionLossCause::Merge, true)
+                    } else {
+                        (PrecisionLossCause::Widening, true)
+                    }
+                } else if (is_exact_value(&value1) || is_exact_value(&value2))
+                    && !is_exact_value(&merged_value)
+                {
+                    (PrecisionLossCause::Merge, false)
+                } else {
+                    return None;
+                };
+                Some(PrecisionLoss {
+                    location: register.name.clone(),
+                    cause,
+                    top,
+                    description: format!(
+                        "{} is {} after merging {} and {}",
+                        register.name,
+                        format_abstract_value(&merged_value),
+                        format_abstract_value(&value1),
+                        format_abstract_value(&value2)
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Get the registers that are not `Top` in `self` but `Top` in the state `after_call`
+    /// after a call to a function without known effects.
+    ///
+    /// The callee is the name of the called function or `None` if the call target is unknown.
+    pub fn get_register_precision_losses_of_call(
+        &self,
+        after_call: &State,
+        callee: Option<&str>,
+    ) -> Vec<PrecisionLoss> {
+        let callee_description = match callee {
+            Some(name) => format!("the call to {name}"),
+            None => "a call to an unknown target".to_string(),
+        };
+        self.register
+            .iter()
+            .filter(|(register, value)| {
+                !is_top_value(value) && is_top_value(&after_call.get_register(register))
+            })
+            .map(|(register, value)| PrecisionLoss {
+                location: register.name.clone(),
+                cause: PrecisionLossCause::UnknownCallee(callee.map(str::to_string)),
+                top: true,
+                description: format!(
+                    "{} is Top after {callee_description}, it was {} before",
+                    register.name,
+                    format_abstract_value(value)
+                ),
+            })
+            .collect()
+    }
+}
+
+/// Returns `true` if the value may be unknown,
+/// i.e. if it contains `Top` values or its absolute value is the `Top` interval.
+fn is_top_value(value: &Data) -> bool {
+    value.contains_top()
+        || value
+            .get_absolute_value()
+            .is_some_and(|value| value.is_top())
+}
+
+/// Returns `true` if the value is a constant or a pointer with a constant offset into a single memory object.
+fn is_exact_value(value: &Data) -> bool {
+    let is_constant = |value: &IntervalDomain| value.try_to_bitvec().is_ok();
+    value.get_if_absolute_value().is_some_and(is_constant)
+        || value
+            .get_if_unique_target()
+            .is_some_and(|(_id, offset)| is_constant(offset))
+}
+
 /// Sort parameters by recursion depth.
 /// Helper function when one has to iterate over parameters in order of their recursion depth.
 fn sort_params_by_recursion_depth(
@@ -0,0 +1,275 @@
+//! Methods of [`State`] for refining values through compound branch conditions
+//! with an external SMT solver.
+//!
+//! The specialization in [`value_specialization`] handles comparisons of a
+//! single input variable with known values. Conditions like
+//! `(x & 0xff) < n && n <= 16` combine several comparisons or compare values
+//! that are not invertible in the abstract domains. For such conditions the
+//! values of the input variables are translated to bitvector constraints and
+//! the solver computes the minimal and maximal values of each variable for
+//! which the condition can still hold.
+
+use super::*;
+use crate::analysis::symbolic_execution::smt::{SatResult, SmtLibSession};
+
+/// The maximal size in bytes of variables whose values are refined.
+const MAX_REFINED_VARIABLE_SIZE: u64 = 8;
+
+impl State {
+    /// Restrict the absolute values of the input variables of `condition` on
+    /// `self` so that `condition` can evaluate to `is_true`.
+    ///
+    /// Conditions that are handled by
+    /// [`specialize_by_expression_result`](State::specialize_by_expression_result)
+    /// are ignored. The state is left unchanged if the solver fails or cannot
+    /// decide a query.
+    ///
+    /// Returns an error if the condition cannot evaluate to `is_true`
+    /// for the values represented by `self`.
+    pub fn specialize_by_smt_solver(
+        &mut self,
+        condition: &Expression,
+        is_true: bool,
+        solver: &[String],
+    ) -> Result<(), Error> {
+        if !is_compound_condition(condition) {
+            return Ok(());
+        }
+        let Ok(mut session) = SmtLibSession::new(solver) else {
+            return Ok(());
+        };
+        let variables: BTreeSet<&Variable> = condition.input_vars().into_iter().collect();
+        let mut refinable_variables = Vec::new();
+        for var in variables {
+            let value = self.eval(&Expression::Var(var.clone()));
+            if var.size > ByteSize::new(MAX_REFINED_VARIABLE_SIZE)
+                || !value.get_relative_values().is_empty()
+            {
+                // Pointers and large values are not restricted.
+                continue;
+            }
+            let interval = value
+                .try_to_interval()
+                .unwrap_or_else(|_| Interval::new_top(var.size));
+            for constraint in get_interval_constraints(var, &interval) {
+                if session.assert(&constraint).is_err() {
+                    return Ok(());
+                }
+            }
+            if interval.start != interval.end {
+                refinable_variables.push((var, interval));
+            }
+        }
+        let condition = if is_true {
+            condition.clone()
+        } else {
+            Expression::UnOp {
+                op: UnOpType::BoolNegate,
+                arg: Box::new(condition.clone()),
+            }
+        };
+        match session.assert(&condition).and_then(|_| session.check_sat()) {
+            Ok(SatResult::Sat) => (),
+            Ok(SatResult::Unsat) => return Err(anyhow!("Unsatisfiable state")),
+            Ok(SatResult::Unknown) | Err(_) => return Ok(()),
+        }
+
+        for (var, interval) in refinable_variables {
+            let Some((min, max)) = compute_bounds(&mut session, var, &interval) else {
+                continue;
+            };
+            let refined_interval = Interval::new(
+                Bitvector::from_i64(min).into_resize_signed(var.size),
+                Bitvector::from_i64(max).into_resize_signed(var.size),
+                interval.stride,
+            );
+            let refined_value = self
+                .eval(&Expression::Var(var.clone()))
+                .intersect(&IntervalDomain::from(refined_interval).into())?;
+            self.set_register(var, refined_value);
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if the condition contains operations other than comparisons,
+/// additions and subtractions, i.e. if it cannot be handled completely by
+/// [`State::specialize_by_expression_result`].
+fn is_compound_condition(condition: &Expression) -> bool {
+    use BinOpType::*;
+    match condition {
+        Expression::Var(_) | Expression::Const(_) | Expression::Unknown { .. } => false,
+        Expression::BinOp { op, lhs, rhs } => {
+            !matches!(
+                op,
+                IntEqual
+                    | IntNotEqual
+                    | IntLess
+                    | IntSLess
+                    | IntLessEqual
+                    | IntSLessEqual
+                    | IntAdd
+                    | IntSub
+            ) || is_compound_condition(lhs)
+                || is_compound_condition(rhs)
+        }
+        Expression::UnOp { arg, .. }
+        | Expression::Cast { arg, .. }
+        | Expression::Subpiece { arg, .. } => is_compound_condition(arg),
+    }
+}
+
+/// Get the constraints that restrict the variable to the values of the interval.
+fn get_interval_constraints(var: &Variable, interval: &Interval) -> Vec<Expression> {
+    let var_expr = Expression::Var(var.clone());
+    let start = Expression::Const(interval.start.clone());
+    let end = Expression::Const(interval.end.clone());
+    let mut constraints = vec![
+        bin_op(start.clone(), BinOpType::IntSLessEqual, var_expr.clone()),
+        bin_op(var_expr.clone(), BinOpType::IntSLessEqual, end),
+    ];
+    if interval.stride > 1 {
+        let stride = Bitvector::from_u64(interval.stride).into_resize_unsigned(var.size);
+        let offset = bin_op(var_expr, BinOpType::IntSub, start);
+        constraints.push(bin_op(
+            bin_op(offset, BinOpType::IntRem, Expression::Const(stride)),
+            BinOpType::IntEqual,
+            Expression::Const(Bitvector::zero(var.size.into())),
+        ));
+    }
+    constraints
+}
+
+/// Build the binary operation `lhs op rhs`.
+fn bin_op(lhs: Expression, op: BinOpType, rhs: Expression) -> Expression {
+    Expression::BinOp {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    }
+}
+
+/// Compute the minimal and maximal signed value of the variable that
+/// satisfies the constraints of the session by binary search over the interval.
+///
+/// Returns `None` if the solver fails or cannot decide a query.
+fn compute_bounds(
+    session: &mut SmtLibSession,
+    var: &Variable,
+    interval: &Interval,
+) -> Option<(i64, i64)> {
+    let var_expr = Expression::Var(var.clone());
+    let mut is_satisfiable = |lhs: Expression, rhs: Expression| {
+        let constraint = bin_op(lhs, BinOpType::IntSLessEqual, rhs);
+        match session.check_sat_assuming(&constraint) {
+            Ok(SatResult::Sat) => Some(true),
+            Ok(SatResult::Unsat) => Some(false),
+            Ok(SatResult::Unknown) | Err(_) => None,
+        }
+    };
+    let constant = |value: i128| {
+        Expression::Const(Bitvector::from_i64(value as i64).into_resize_signed(var.size))
+    };
+    let (start, end) = (
+        interval
+            .start
+            .clone()
+            .into_resize_signed(ByteSize::new(8))
+            .try_to_i64()
+            .ok()? as i128,
+        interval
+            .end
+            .clone()
+            .into_resize_signed(ByteSize::new(8))
+            .try_to_i64()
+            .ok()? as i128,
+    );
+
+    // Search the minimal value.
+    let (mut low, mut high) = (start, end);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if is_satisfiable(var_expr.clone(), constant(middle))? {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    let min = low;
+    // Search the maximal value.
+    let (mut low, mut high) = (min, end);
+    while low < high {
+        let middle = low + (high - low + 1) / 2;
+        if is_satisfiable(constant(middle), var_expr.clone())? {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    Some((min as i64, high as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitvec, expr, variable};
+
+    #[test]
+    fn compound_conditions() {
+        let comparison = bin_op(expr!("RAX:8 + 0x1:8"), BinOpType::IntLess, expr!("RBX:8"));
+        assert!(!is_compound_condition(&comparison));
+        let masked = bin_op(expr!("RAX:8"), BinOpType::IntAnd, expr!("0xff:8"));
+        assert!(is_compound_condition(&bin_op(
+            masked,
+            BinOpType::IntLess,
+            expr!("RBX:8")
+        )));
+    }
+
+    #[test]
+    fn interval_constraints() {
+        let interval = Interval::new(bitvec!("0x10:8"), bitvec!("0x30:8"), 8);
+        let constraints = get_interval_constraints(&variable!("RAX:8"), &interval);
+        assert_eq!(constraints.len(), 3);
+    }
+
+    /// Requires the `z3` solver, which is installed in the CI job of the `smt` feature.
+    #[test]
+    fn specialize_by_compound_condition() {
+        let solver = vec!["z3".to_string(), "-in".to_string()];
+        let mut state = State::new(&variable!("RSP:8"), Tid::new("func"), BTreeSet::new());
+        state.set_register(
+            &variable!("RAX:8"),
+            IntervalDomain::mock(0x20, 0xfff).into(),
+        );
+        let masked = bin_op(expr!("RAX:8"), BinOpType::IntAnd, expr!("0xff:8"));
+        let condition = bin_op(masked.clone(), BinOpType::IntLess, expr!("0x40:8"));
+
+        let mut true_state = state.clone();
+        true_state
+            .specialize_by_smt_solver(&condition, true, &solver)
+            .unwrap();
+        assert_eq!(
+            true_state.get_register(&variable!("RAX:8")),
+            IntervalDomain::mock(0x20, 0xf3f).into()
+        );
+        let mut false_state = state.clone();
+        false_state
+            .specialize_by_smt_solver(&condition, false, &solver)
+            .unwrap();
+        assert_eq!(
+            false_state.get_register(&variable!("RAX:8")),
+            IntervalDomain::mock(0x40, 0xfff).into()
+        );
+
+        // The masked value is always at least 0x20 for values in [0x120, 0x13f].
+        state.set_register(
+            &variable!("RAX:8"),
+            IntervalDomain::mock(0x120, 0x13f).into(),
+        );
+        let condition = bin_op(masked, BinOpType::IntLess, expr!("0x20:8"));
+        assert!(state
+            .specialize_by_smt_solver(&condition, true, &solver)
+            .is_err());
+    }
+}
@@ -82,6 +82,35 @@ fn state() {
     assert_eq!(state.memory.get_num_objects(), 2);
 }
 
+#[test]
+fn state_order() {
+    let global_memory = RuntimeMemoryImage::mock();
+    let state = State::new(&variable!("RSP:8"), Tid::new("time0"), BTreeSet::new());
+    let stack_addr = Data::from_target(new_id("time0", "RSP"), bv(-8));
+    let interval = |start: i64, end: i64| -> Data { IntervalDomain::mock(start, end).into() };
+
+    let mut small_state = state.clone();
+    small_state.set_register(&variable!("RAX:8"), interval(0, 5));
+    small_state
+        .store_value(&stack_addr, &interval(1, 2), &global_memory)
+        .unwrap();
+    let mut large_state = state.clone();
+    large_state.set_register(&variable!("RAX:8"), interval(0, 10));
+    large_state
+        .store_value(&stack_addr, &interval(0, 3), &global_memory)
+        .unwrap();
+
+    assert!(small_state.less_or_equal(&large_state));
+    assert!(!large_state.less_or_equal(&small_state));
+    // Unknown register and memory values are *Top*.
+    assert!(small_state.less_or_equal(&state));
+    assert!(!state.less_or_equal(&small_state));
+    // The merge of two states is greater than both states.
+    let merged_state = small_state.merge(&large_state);
+    assert!(small_state.less_or_equal(&merged_state));
+    assert!(large_state.less_or_equal(&merged_state));
+}
+
 #[test]
 fn clear_parameters_on_the_stack_on_extern_calls() {
     let global_memory = RuntimeMemoryImage::mock();
@@ -109,6 +138,7 @@ fn clear_parameters_on_the_stack_on_extern_calls() {
         return_values: Vec::new(),
         no_return: false,
         has_var_args: false,
+        alias: None,
     };
     // check the value before
     let pointer = Data::from_target(new_id("time0", "RSP"), bv(-12));
@@ -496,3 +526,42 @@ fn test_merge_mem_objects_with_unique_abstract_location() {
         .unwrap();
     assert_eq!(state, expected_state);
 }
+
+#[test]
+fn register_precision_losses() {
+    let mut state = State::new(&variable!("RSP:8"), Tid::new("time0"), BTreeSet::new());
+    let mut other_state = state.clone();
+    state.set_register(&variable!("RAX:8"), bv(1).into());
+    other_state.set_register(&variable!("RAX:8"), bv(2).into());
+    state.set_register(&variable!("RBX:8"), bv(3).into());
+    other_state.set_register(&variable!("RBX:8"), bv(3).into());
+    state.set_register(&variable!("RCX:8"), bv(4).into());
+    let merged_state = state.merge(&other_state);
+    let losses = state.get_register_precision_losses(&other_state, &merged_state);
+    assert_eq!(losses.len(), 2);
+    assert_eq!(losses[0].location, "RAX");
+    assert_eq!(losses[0].cause, PrecisionLossCause::Merge);
+    assert!(!losses[0].top);
+    assert!(losses[0].description.starts_with("RAX is "));
+    assert_eq!(losses[1].location, "RCX");
+    assert!(losses[1].top);
+    assert!(state
+        .get_register_precision_losses(&state, &state)
+        .is_empty());
+}
+
+#[test]
+fn register_precision_losses_of_call() {
+    let mut state = State::new(&variable!("RSP:8"), Tid::new("time0"), BTreeSet::new());
+    state.set_register(&variable!("RAX:8"), bv(1).into());
+    let mut after_call = state.clone();
+    after_call.clear_non_callee_saved_register(&[variable!("RSP:8")]);
+    let losses = state.get_register_precision_losses_of_call(&after_call, Some("getline"));
+    assert_eq!(losses.len(), 1);
+    assert_eq!(losses[0].location, "RAX");
+    assert_eq!(
+        losses[0].cause,
+        PrecisionLossCause::UnknownCallee(Some("getline".to_string()))
+    );
+    assert!(losses[0].top);
+}
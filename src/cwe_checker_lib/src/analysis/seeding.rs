@@ -0,0 +1,124 @@
+//! Selection of the functions at which fixpoint computations are started.
+//!
+//! By default the analyses start at the entry of every function of the program.
+//! For shared libraries it is often more useful to only start at the exported
+//! functions, since these are the functions that are called with parameters
+//! controlled by other programs. The [`EntryPointSeeding`] strategy can be
+//! configured for the string abstraction and taint analyses.
+
+use crate::analysis::graph::{get_entry_nodes_of_subs, Graph, NodeIndex};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+
+/// Strategy for selecting the functions at which a fixpoint computation is started.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub enum EntryPointSeeding {
+    /// Start at every function of the program.
+    #[default]
+    AllFunctions,
+    /// Start at the entry points of the program.
+    /// For shared libraries these are the exported functions.
+    Exports,
+    /// Start at the functions whose names match the given regular expression.
+    SymbolRegex(String),
+    /// Start at the functions with the given (hexadecimal) addresses.
+    Addresses(Vec<String>),
+}
+
+impl EntryPointSeeding {
+    /// Return the TIDs of the functions selected by the strategy.
+    ///
+    /// Returns an error if the regular expression of the strategy is invalid.
+    pub fn get_seed_functions(&self, project: &Project) -> Result<BTreeSet<Tid>, Error> {
+        let subs = project.program.term.subs.values();
+        let seed_functions = match self {
+            EntryPointSeeding::AllFunctions => subs.map(|sub| sub.tid.clone()).collect(),
+            EntryPointSeeding::Exports => project.program.term.entry_points.clone(),
+            EntryPointSeeding::SymbolRegex(pattern) => {
+                let regex = Regex::new(pattern)?;
+                subs.filter(|sub| regex.is_match(&sub.term.name))
+                    .map(|sub| sub.tid.clone())
+                    .collect()
+            }
+            EntryPointSeeding::Addresses(addresses) => {
+                let addresses: BTreeSet<TidAddress> = addresses
+                    .iter()
+                    .map(TidAddress::from)
+                    .filter(|address| !address.is_unknown())
+                    .collect();
+                subs.filter(|sub| addresses.contains(&sub.tid.address()))
+                    .map(|sub| sub.tid.clone())
+                    .collect()
+            }
+        };
+
+        Ok(seed_functions)
+    }
+
+    /// Return the entry nodes in the control flow graph of the functions selected by the strategy.
+    pub fn get_seed_nodes(
+        &self,
+        project: &Project,
+        graph: &Graph,
+    ) -> Result<HashMap<Tid, NodeIndex>, Error> {
+        let seed_functions = self.get_seed_functions(project)?;
+        let mut entry_nodes = get_entry_nodes_of_subs(graph);
+        entry_nodes.retain(|sub_tid, _| seed_functions.contains(sub_tid));
+
+        Ok(entry_nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_x64();
+        for (tid, name) in [
+            ("FUN_0x1000", "main"),
+            ("FUN_0x2000", "lib_export"),
+            ("FUN_0x3000", "lib_internal"),
+        ] {
+            let sub = Term {
+                tid: Tid::mock(tid),
+                term: Sub::new::<_, &str>(name, Vec::new(), None),
+            };
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project.program.term.entry_points = BTreeSet::from([Tid::mock("FUN_0x2000")]);
+        project
+    }
+
+    #[test]
+    fn seed_functions() {
+        let project = mock_project();
+        let all_functions = EntryPointSeeding::AllFunctions
+            .get_seed_functions(&project)
+            .unwrap();
+        assert_eq!(all_functions.len(), project.program.term.subs.len());
+        assert_eq!(
+            EntryPointSeeding::Exports
+                .get_seed_functions(&project)
+                .unwrap(),
+            BTreeSet::from([Tid::mock("FUN_0x2000")])
+        );
+        assert_eq!(
+            EntryPointSeeding::SymbolRegex("^lib_".to_string())
+                .get_seed_functions(&project)
+                .unwrap(),
+            BTreeSet::from([Tid::mock("FUN_0x2000"), Tid::mock("FUN_0x3000")])
+        );
+        assert!(EntryPointSeeding::SymbolRegex("(".to_string())
+            .get_seed_functions(&project)
+            .is_err());
+        assert_eq!(
+            EntryPointSeeding::Addresses(vec!["0x1000".to_string(), "invalid".to_string()])
+                .get_seed_functions(&project)
+                .unwrap(),
+            BTreeSet::from([Tid::mock("FUN_0x1000")])
+        );
+    }
+}
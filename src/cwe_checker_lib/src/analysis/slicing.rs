@@ -0,0 +1,266 @@
+//! Forward and backward program slicing.
+//!
+//! The backward slice of a term contains all terms that may influence the values used by it.
+//! The forward slice of a term contains all terms whose values may be influenced by it.
+//! Slices are computed on a [`DependencyGraph`] between the `Def` and `Jmp` terms of the program,
+//! which is derived from the [def-use chains](crate::analysis::def_use) of each function.
+//! For interprocedural slices, the input values of a function depend on all calls to the function
+//! and the return registers defined by a call depend on the `Return` instructions of the callee.
+//!
+//! Slices can be printed for debugging purposes through the `--slice` command line option.
+
+use crate::analysis::def_use::{DefSite, DefUseChains};
+use crate::intermediate_representation::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The dependencies between the terms of a program.
+///
+/// Besides the TIDs of `Def` and `Jmp` terms, the graph also contains the TIDs of functions.
+/// They represent the input values of the functions at their entry points.
+pub struct DependencyGraph {
+    /// Maps each term to the terms whose values it uses.
+    dependencies: HashMap<Tid, BTreeSet<Tid>>,
+    /// Maps each term to the terms that use its values.
+    dependents: HashMap<Tid, BTreeSet<Tid>>,
+    /// The TIDs of all `Def` and `Jmp` terms of the program.
+    terms: HashSet<Tid>,
+}
+
+impl DependencyGraph {
+    /// Compute the dependency graph of the program.
+    ///
+    /// If `interprocedural` is `false`, then slices do not cross function boundaries.
+    pub fn new(project: &Project, interprocedural: bool) -> DependencyGraph {
+        let mut graph = DependencyGraph {
+            dependencies: HashMap::new(),
+            dependents: HashMap::new(),
+            terms: HashSet::new(),
+        };
+        for sub in project.program.term.subs.values() {
+            graph.add_function_dependencies(project, sub);
+        }
+        if interprocedural {
+            graph.add_call_dependencies(project);
+        }
+
+        graph
+    }
+
+    /// Return the backward slice of the given terms, i.e. all terms that may influence them.
+    ///
+    /// The terms themselves are part of the slice.
+    pub fn backward_slice(&self, criteria: &[Tid]) -> BTreeSet<Tid> {
+        self.get_reachable_terms(criteria, &self.dependencies)
+    }
+
+    /// Return the forward slice of the given terms, i.e. all terms that may be influenced by them.
+    ///
+    /// The terms themselves are part of the slice.
+    pub fn forward_slice(&self, criteria: &[Tid]) -> BTreeSet<Tid> {
+        self.get_reachable_terms(criteria, &self.dependents)
+    }
+
+    /// Return all terms reachable from the given terms in the given edge map.
+    fn get_reachable_terms(
+        &self,
+        criteria: &[Tid],
+        edges: &HashMap<Tid, BTreeSet<Tid>>,
+    ) -> BTreeSet<Tid> {
+        let mut visited: HashSet<&Tid> = criteria.iter().collect();
+        let mut worklist: Vec<&Tid> = criteria.iter().collect();
+        while let Some(tid) = worklist.pop() {
+            for next in edges.get(tid).into_iter().flatten() {
+                if visited.insert(next) {
+                    worklist.push(next);
+                }
+            }
+        }
+
+        visited
+            .into_iter()
+            .filter(|tid| self.terms.contains(tid))
+            .cloned()
+            .collect()
+    }
+
+    /// Add a dependency of the `user` term on the `definition` term.
+    fn add_dependency(&mut self, user: &Tid, definition: &Tid) {
+        self.dependencies
+            .entry(user.clone())
+            .or_default()
+            .insert(definition.clone());
+        self.dependents
+            .entry(definition.clone())
+            .or_default()
+            .insert(user.clone());
+    }
+
+    /// Add the def-use dependencies inside the function to the graph.
+    ///
+    /// Uses of input values of the function depend on the function TID.
+    fn add_function_dependencies(&mut self, project: &Project, sub: &Term<Sub>) {
+        let chains = DefUseChains::new(project, sub);
+        for tid in chains.terms() {
+            self.terms.insert(tid.clone());
+            let definitions = chains
+                .get_definitions(tid)
+                .into_iter()
+                .flat_map(|definitions| definitions.values().flatten());
+            for definition in definitions {
+                match definition {
+                    DefSite::Entry => self.add_dependency(tid, &sub.tid),
+                    DefSite::Term(definition) => self.add_dependency(tid, definition),
+                }
+            }
+        }
+    }
+
+    /// Add the dependencies between calls and the called functions to the graph.
+    fn add_call_dependencies(&mut self, project: &Project) {
+        let subs = &project.program.term.subs;
+        for block in subs.values().flat_map(|sub| sub.term.blocks.iter()) {
+            for jmp in block.term.jmps.iter() {
+                let callees: Vec<&Tid> = match &jmp.term {
+                    Jmp::Call { target, .. } => vec![target],
+                    Jmp::CallInd { .. } => block
+                        .term
+                        .ind_call_targets()
+                        .into_iter()
+                        .flatten()
+                        .collect(),
+                    _ => continue,
+                };
+                for callee in callees.into_iter().filter_map(|tid| subs.get(tid)) {
+                    self.add_dependency(&callee.tid, &jmp.tid);
+                    for return_jmp in callee.term.jmps() {
+                        if matches!(return_jmp.term, Jmp::Return(_)) {
+                            self.add_dependency(&jmp.tid, &return_jmp.tid);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Return the TIDs of all `Def` and `Jmp` terms at the given address.
+pub fn get_terms_at_address(project: &Project, address: TidAddress) -> Vec<Tid> {
+    let blocks = project
+        .program
+        .term
+        .subs
+        .values()
+        .flat_map(|sub| sub.term.blocks.iter());
+    let mut terms = BTreeSet::new();
+    for block in blocks {
+        let defs = block.term.defs.iter().map(|def| &def.tid);
+        let jmps = block.term.jmps.iter().map(|jmp| &jmp.tid);
+        terms.extend(
+            defs.chain(jmps)
+                .filter(|tid| tid.address() == address)
+                .cloned(),
+        );
+    }
+
+    terms.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{defs, expr};
+
+    /// Create a project with a function `main` calling a function `callee`.
+    ///
+    /// The callee returns `RDI + 1` in `RAX`, which is stored into memory by `main`.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_x64();
+        let mut call_block = Blk::mock_with_tid("main_blk_0");
+        call_block.term.defs = defs![
+            "main_def_0: RDI:8 = 0x2a:8",
+            "main_def_1: RCX:8 = 0x1:8",
+            "main_def_2: RSI:8 = RCX:8"
+        ];
+        call_block.term.jmps = vec![Jmp::call("main_call", "callee", Some("main_blk_1"))];
+        let mut return_block = Blk::mock_with_tid("main_blk_1");
+        return_block.term.defs = defs![
+            "main_def_3: Store at RSP:8 := RAX:8",
+            "main_def_4: RBX:8 := Load from RSP:8",
+            "main_def_5: RCX:8 = 0x2:8"
+        ];
+        return_block.term.jmps = vec![Term {
+            tid: Tid::new("main_return"),
+            term: Jmp::Return(expr!("0x0:8")),
+        }];
+        let main = Term {
+            tid: Tid::new("main"),
+            term: Sub::new::<_, &str>("main", vec![call_block, return_block], None),
+        };
+
+        let mut callee_block = Blk::mock_with_tid("callee_blk");
+        callee_block.term.defs = defs!["callee_def: RAX:8 = RDI:8 + 0x1:8"];
+        callee_block.term.jmps = vec![Term {
+            tid: Tid::new("callee_return"),
+            term: Jmp::Return(expr!("0x0:8")),
+        }];
+        let callee = Term {
+            tid: Tid::new("callee"),
+            term: Sub::new::<_, &str>("callee", vec![callee_block], None),
+        };
+        for sub in [main, callee] {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    fn tids(names: &[&str]) -> BTreeSet<Tid> {
+        names.iter().map(|name| Tid::new(*name)).collect()
+    }
+
+    #[test]
+    fn intraprocedural_slices() {
+        let project = mock_project();
+        let graph = DependencyGraph::new(&project, false);
+        assert_eq!(
+            graph.backward_slice(&[Tid::new("main_def_4")]),
+            tids(&[
+                "main_def_0",
+                "main_def_1",
+                "main_def_2",
+                "main_call",
+                "main_def_3",
+                "main_def_4"
+            ])
+        );
+        assert_eq!(
+            graph.forward_slice(&[Tid::new("main_def_1")]),
+            tids(&[
+                "main_def_1",
+                "main_def_2",
+                "main_call",
+                "main_def_3",
+                "main_def_4",
+                "main_return"
+            ])
+        );
+        assert_eq!(
+            graph.backward_slice(&[Tid::new("callee_return")]),
+            tids(&["callee_def", "callee_return"])
+        );
+    }
+
+    #[test]
+    fn interprocedural_slices() {
+        let project = mock_project();
+        let graph = DependencyGraph::new(&project, true);
+        assert!(graph
+            .backward_slice(&[Tid::new("main_def_3")])
+            .is_superset(&tids(&["callee_def", "callee_return", "main_def_0"])));
+        assert!(graph
+            .forward_slice(&[Tid::new("main_def_0")])
+            .is_superset(&tids(&["callee_def", "callee_return", "main_def_3"])));
+        assert!(!graph
+            .forward_slice(&[Tid::new("main_def_5")])
+            .contains(&Tid::new("callee_def")));
+    }
+}
@@ -9,14 +9,25 @@ use std::{
 use petgraph::{graph::NodeIndex, visit::IntoNodeReferences};
 
 use crate::{
-    abstract_domain::{AbstractDomain, DomainInsertion, HasTop},
+    abstract_domain::{
+        AbstractDomain, AbstractIdentifier, AbstractLocation, CharacterAlphabet, DataDomain,
+        DomainInsertion, HasTop, IntervalDomain, PredicateDomain, TryToBitvec,
+    },
     analysis::{
-        forward_interprocedural_fixpoint::Context as _, graph::Node,
+        forward_interprocedural_fixpoint::Context as _,
+        graph::{Graph, Node},
+        input_sources::InputSources,
         interprocedural_fixpoint_generic::NodeValue,
+        loop_idioms::{find_loop_idioms, LoopIdiom, LoopSummary},
         pointer_inference::PointerInference as PointerInferenceComputation,
         pointer_inference::State as PointerInferenceState,
+        vsa_results::VsaResult,
+    },
+    intermediate_representation::{
+        BinOpType, Bitvector, Blk, ByteSize, Def, Expression, ExternSymbol, Jmp, Project, Term,
+        Tid, Variable,
     },
-    intermediate_representation::{Def, ExternSymbol, Project, Term, Tid},
+    prelude::*,
 };
 
 use super::{state::State, Config};
@@ -40,6 +51,18 @@ pub struct Context<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<
     pub extern_symbol_map: HashMap<Tid, &'a ExternSymbol>,
     /// Maps string symbols to their corresponding format string parameter index.
     pub format_string_index_map: HashMap<String, usize>,
+    /// The maximum number of different strings that a part of a string domain may represent
+    /// when merging states before it is overapproximated by *Top*.
+    pub max_string_cardinality: usize,
+    /// Maps the names of sanitizer functions to the index of the parameter pointing to the sanitized string.
+    pub sanitizer_index_map: HashMap<String, usize>,
+    /// The sources of user input of the program.
+    pub input_sources: InputSources,
+    /// Maps the names of extern functions returning string literals
+    /// to the string domain of the returned strings.
+    pub returned_string_map: HashMap<String, T>,
+    /// The alphabet of characters that string literals in global memory may consist of.
+    pub alphabet: CharacterAlphabet,
     /// A map to get the node index of the `BlkStart` node containing a given [`Def`] as the first `Def` of the block.
     /// The keys are of the form `(Def-TID, Current-Sub-TID)`
     /// to distinguish the nodes for blocks contained in more than one function.
@@ -52,6 +75,16 @@ pub struct Context<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<
     /// The keys are of the form `(Jmp-TID, Current-Sub-TID)`
     /// to distinguish the nodes for blocks contained in more than one function.
     pub jmp_to_blk_end_node_map: HashMap<(Tid, Tid), NodeIndex>,
+    /// Summaries of copy loops, which are handled like calls to memcpy,
+    /// indexed by the TID of the loop block.
+    pub copy_loop_summaries: HashMap<Tid, LoopSummary>,
+    /// Summaries of loops that decode stack strings in place,
+    /// indexed by the TID of the loop block.
+    pub decode_loop_summaries: HashMap<Tid, LoopSummary>,
+    /// A copy of the control flow graph that only contains the edges starting in the nodes
+    /// relevant for an on-demand computation.
+    /// If `None`, the whole control flow graph of the pointer inference is used.
+    pub restricted_graph: Option<Graph<'a>>,
     _phantom_string_domain: PhantomData<T>,
 }
 impl<'a, T: AbstractDomain + HasTop + Eq + From<String> + DomainInsertion> Context<'a, T> {
@@ -88,19 +121,57 @@ impl<'a, T: AbstractDomain + HasTop + Eq + From<String> + DomainInsertion> Conte
             }
         }
 
+        let (copy_loop_summaries, decode_loop_summaries) = find_loop_idioms(project)
+            .into_iter()
+            .filter(|(_, summary)| !matches!(summary.idiom, LoopIdiom::Scan { .. }))
+            .partition(|(_, summary)| matches!(summary.idiom, LoopIdiom::Copy { .. }));
+
         Context {
             project,
             pointer_inference_results,
             format_string_index_map: config.format_string_index.into_iter().collect(),
+            max_string_cardinality: config.max_string_cardinality,
+            sanitizer_index_map: config.sanitizer_symbols.into_iter().collect(),
+            input_sources: config.input_sources,
+            returned_string_map: config
+                .returned_string_symbols
+                .into_iter()
+                .filter_map(|(name, strings)| {
+                    let domain = strings
+                        .into_iter()
+                        .map(T::from)
+                        .reduce(|domain, other| domain.merge(&other))?;
+                    Some((name, domain))
+                })
+                .collect(),
+            alphabet: config.alphabet,
             string_symbol_map,
             extern_symbol_map,
             block_start_node_map,
             block_first_def_set,
             jmp_to_blk_end_node_map,
+            copy_loop_summaries,
+            decode_loop_summaries,
+            restricted_graph: None,
             _phantom_string_domain: PhantomData,
         }
     }
 
+    /// Restrict the fixpoint computation to the given nodes
+    /// by removing all edges starting outside of them from the control flow graph.
+    ///
+    /// The node indices of the graph do not change,
+    /// so that they still match the node indices of the pointer inference results.
+    pub fn restrict_to_nodes(&mut self, nodes: &HashSet<NodeIndex>) {
+        let mut graph = self.pointer_inference_results.get_graph().clone();
+        graph.retain_edges(|frozen_graph, edge| {
+            frozen_graph
+                .edge_endpoints(edge)
+                .is_some_and(|(source, _)| nodes.contains(&source))
+        });
+        self.restricted_graph = Some(graph);
+    }
+
     /// Get the current pointer inference state (if one can be found) for the given state.
     fn get_current_pointer_inference_state(
         &self,
@@ -122,6 +193,49 @@ impl<'a, T: AbstractDomain + HasTop + Eq + From<String> + DomainInsertion> Conte
         }
     }
 
+    /// Handles the entry into a loop that decodes a stack buffer in place.
+    ///
+    /// If the decoded buffer was built by immediate stores,
+    /// the decoded bytes are computed from the constant key of the loop.
+    /// If the number of decoded elements is unknown, decoding stops at the first NUL element.
+    fn handle_decode_loop(&self, state: &State<T>, summary: &LoopSummary) -> State<T> {
+        let mut new_state = state.clone();
+        let (
+            Some(pi_state),
+            LoopIdiom::Decode {
+                buffer,
+                operation,
+                key,
+                count,
+                ..
+            },
+        ) = (state.get_pointer_inference_state(), &summary.idiom)
+        else {
+            return new_state;
+        };
+        let Some(offset) = state.get_exact_stack_offset(buffer) else {
+            return new_state;
+        };
+        let element_count = count.as_ref().and_then(|count| {
+            pi_state
+                .eval(&Expression::Var(count.clone()))
+                .get_if_absolute_value()?
+                .try_to_bitvec()
+                .ok()?
+                .try_to_u64()
+                .ok()
+        });
+        new_state.decode_immediate_sequence(
+            offset,
+            *operation,
+            key,
+            element_count,
+            self.project.runtime_memory_image.is_little_endian,
+        );
+
+        new_state
+    }
+
     /// Update the pointer inference state contained in the given taint state
     /// according to the effect of the given `Def` term.
     fn update_pointer_inference_state(&self, state: &mut State<T>, def: &Term<Def>) {
@@ -131,6 +245,335 @@ impl<'a, T: AbstractDomain + HasTop + Eq + From<String> + DomainInsertion> Conte
             state.set_pointer_inference_state(new_pi_state);
         }
     }
+
+    /// Add the pointers returned by a called internal function
+    /// and the strings of the memory objects they point to to the caller state.
+    ///
+    /// The abstract IDs of the callee are translated to the caller
+    /// through the ID renaming map computed by the pointer inference for the call.
+    /// Pointers to string constants in global memory are returned unchanged.
+    fn add_returned_strings_from_callee(
+        &self,
+        state: &mut State<T>,
+        callee_state: &State<T>,
+        call_term: &Term<Jmp>,
+        calling_convention: &Option<String>,
+    ) {
+        let (Some(renaming_map), Some(cconv), Some(pi_state)) = (
+            self.pointer_inference_results
+                .get_id_renaming_map_at_call_tid(&call_term.tid),
+            self.project
+                .get_specific_calling_convention(calling_convention),
+            self.pointer_inference_results
+                .get_state_at_jmp_tid(&call_term.tid),
+        ) else {
+            return;
+        };
+        for return_register in cconv.get_all_return_register() {
+            let Some(callee_pointer) = callee_state
+                .get_variable_to_pointer_map()
+                .get(return_register)
+            else {
+                continue;
+            };
+            let mut pointer = callee_pointer.clone();
+            pointer.replace_all_ids(renaming_map);
+            if pointer.get_relative_values().is_empty() && pointer.get_absolute_value().is_none() {
+                continue;
+            }
+            for (callee_id, offset) in callee_pointer.get_relative_values() {
+                if let Some(string_domain) =
+                    callee_state.get_heap_string(callee_id, offset.try_to_offset().ok())
+                {
+                    let mut target: DataDomain<IntervalDomain> =
+                        DataDomain::from_target(callee_id.clone(), offset.clone());
+                    target.replace_all_ids(renaming_map);
+                    Self::add_new_string_abstract_domain(
+                        state,
+                        pi_state,
+                        target.get_relative_values(),
+                        string_domain,
+                    );
+                }
+            }
+            state.add_new_variable_to_pointer_entry(return_register.clone(), pointer);
+        }
+    }
+
+    /// Add the string returned by a call to an input source like `getenv`
+    /// or to a function returning string literals to the state.
+    ///
+    /// The pointer inference creates a new memory object for the string
+    /// whose ID consists of the call TID and the return register.
+    fn add_string_in_return_register(
+        &self,
+        state: &mut State<T>,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        domain: T,
+    ) {
+        let Ok(return_register) = extern_symbol.get_unique_return_register() else {
+            return;
+        };
+        let Ok(location) = AbstractLocation::from_var(return_register) else {
+            return;
+        };
+        let object_id = AbstractIdentifier::new(call.tid.clone(), location);
+        let pointer = DataDomain::from_target(
+            object_id.clone(),
+            Bitvector::zero(return_register.size.into()).into(),
+        );
+        state.add_new_heap_to_string_entry(object_id, Some(0), domain);
+        state.add_new_variable_to_pointer_entry(return_register.clone(), pointer);
+    }
+
+    /// If the output of a load points to program arguments,
+    /// add them to the state as strings with unknown content.
+    ///
+    /// Program arguments are the strings pointed to by the entries of the argument vector,
+    /// i.e. the targets of values loaded from the memory object of the argument vector parameter.
+    fn add_program_argument_strings(&self, state: &mut State<T>, output: &Variable) {
+        let (Some(sub), Some(pi_state)) =
+            (state.get_current_sub(), state.get_pointer_inference_state())
+        else {
+            return;
+        };
+        let value = pi_state.eval(&Expression::Var(output.clone()));
+        let arguments: Vec<AbstractIdentifier> = value
+            .get_relative_values()
+            .keys()
+            .filter(|id| {
+                self.input_sources
+                    .is_program_argument(self.project, sub, id)
+                    && !state.get_heap_to_string_map().contains_key(id)
+            })
+            .cloned()
+            .collect();
+        if arguments.is_empty() {
+            return;
+        }
+        for argument in arguments {
+            state.add_new_heap_to_string_entry(argument, Some(0), T::create_top_value_domain());
+        }
+        state.add_new_variable_to_pointer_entry(output.clone(), value);
+    }
+
+    /// Refine the string domains according to the given branch condition.
+    ///
+    /// On branches where the return value of a string comparison call is zero,
+    /// the compared strings are intersected with the strings they were compared to.
+    /// On branches where the first character of a tracked string equals a constant,
+    /// the string is intersected with the set of strings starting with that character.
+    ///
+    /// Returns an error if the branch is infeasible, i.e. if one of the intersections is empty.
+    fn specialize_string_domains_by_condition(
+        &self,
+        state: &mut State<T>,
+        condition: &Expression,
+        block: &Term<Blk>,
+        is_true: bool,
+    ) -> Result<(), Error> {
+        let (Some((compared_expression, constant, condition_index)), Some(pi_state)) = (
+            get_equality_with_constant(condition, block, is_true),
+            block.term.jmps.first().and_then(|jmp| {
+                self.pointer_inference_results
+                    .get_state_at_jmp_tid(&jmp.tid)
+            }),
+        ) else {
+            return Ok(());
+        };
+        if let Some(comparisons) = get_compared_variable(compared_expression)
+            .filter(|_| constant.is_zero())
+            .and_then(|var| state.get_string_comparisons(var))
+        {
+            for (pointer, domain) in comparisons.clone() {
+                state.intersect_string_domain(&pointer, &domain, pi_state)?;
+            }
+        } else if let Some(load_def) = get_loading_def(block, compared_expression, condition_index)
+        {
+            let (Some(address), Some(character)) = (
+                self.pointer_inference_results
+                    .eval_address_at_def(&load_def.tid),
+                Context::<T>::parse_bitvec_to_char(constant.clone()),
+            ) else {
+                return Ok(());
+            };
+            let domain = if character == '\0' {
+                T::create_empty_string_domain()
+            } else {
+                T::from(character.to_string()).append_string_domain(&T::create_top_value_domain())
+            };
+            state.intersect_string_domain(&address, &domain, pi_state)?;
+        }
+
+        Ok(())
+    }
+
+    /// Record length bounds for strings on branches where their length,
+    /// as returned by a `strlen` call, is known to be less than a constant.
+    fn specialize_string_predicates_by_condition(
+        &self,
+        state: &mut State<T>,
+        condition: &Expression,
+        block: &Term<Blk>,
+        is_true: bool,
+    ) {
+        let (Some((compared_expression, bound)), Some(pi_state)) = (
+            get_upper_bound_check(condition, block, is_true),
+            block.term.jmps.first().and_then(|jmp| {
+                self.pointer_inference_results
+                    .get_state_at_jmp_tid(&jmp.tid)
+            }),
+        ) else {
+            return;
+        };
+        if let Some(pointer) = get_compared_variable(compared_expression)
+            .and_then(|var| state.get_string_length_pointer(var))
+            .cloned()
+        {
+            state.add_string_predicates(
+                &pointer,
+                &PredicateDomain::from_length_bound(bound),
+                pi_state,
+            );
+        }
+    }
+}
+
+/// Returns the expression and the constant upper bound for it on the branch given by `is_true`
+/// if the condition is an (unsigned or signed) less-than comparison with a constant.
+///
+/// If the condition is a variable, it is resolved to its last assignment in the block.
+fn get_upper_bound_check<'b>(
+    condition: &'b Expression,
+    block: &'b Term<Blk>,
+    is_true: bool,
+) -> Option<(&'b Expression, u64)> {
+    let condition = match condition {
+        Expression::Var(var) => {
+            match get_last_def_of_variable(block, var, block.term.defs.len())? {
+                (_, Def::Assign { value, .. }) => value,
+                _ => return None,
+            }
+        }
+        _ => condition,
+    };
+    let Expression::BinOp { op, lhs, rhs } = condition else {
+        return None;
+    };
+    use BinOpType::*;
+    match (op, lhs.as_ref(), rhs.as_ref(), is_true) {
+        // expression < constant
+        (IntLess | IntSLess, expression, Expression::Const(constant), true) => {
+            Some((expression, constant.try_to_u64().ok()?.checked_sub(1)?))
+        }
+        // expression <= constant
+        (IntLessEqual | IntSLessEqual, expression, Expression::Const(constant), true) => {
+            Some((expression, constant.try_to_u64().ok()?))
+        }
+        // !(constant < expression)
+        (IntLess | IntSLess, Expression::Const(constant), expression, false) => {
+            Some((expression, constant.try_to_u64().ok()?))
+        }
+        // !(constant <= expression)
+        (IntLessEqual | IntSLessEqual, Expression::Const(constant), expression, false) => {
+            Some((expression, constant.try_to_u64().ok()?.checked_sub(1)?))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the expression that equals a constant on the branch given by `is_true`
+/// if the condition is an equality comparison with a constant.
+///
+/// If the condition is a variable, it is resolved to its last assignment in the block.
+/// The index of the corresponding `Def` in the block is returned as well.
+fn get_equality_with_constant<'b>(
+    condition: &'b Expression,
+    block: &'b Term<Blk>,
+    is_true: bool,
+) -> Option<(&'b Expression, &'b Bitvector, usize)> {
+    let (condition, condition_index) = match condition {
+        Expression::Var(var) => {
+            match get_last_def_of_variable(block, var, block.term.defs.len())? {
+                (index, Def::Assign { value, .. }) => (value, index),
+                _ => return None,
+            }
+        }
+        _ => (condition, block.term.defs.len()),
+    };
+    let Expression::BinOp { op, lhs, rhs } = condition else {
+        return None;
+    };
+    let (expression, constant) = match (lhs.as_ref(), rhs.as_ref()) {
+        (expression, Expression::Const(constant)) | (Expression::Const(constant), expression) => {
+            (expression, constant)
+        }
+        _ => return None,
+    };
+    match op {
+        BinOpType::IntEqual if is_true => Some((expression, constant, condition_index)),
+        BinOpType::IntNotEqual if !is_true => Some((expression, constant, condition_index)),
+        _ => None,
+    }
+}
+
+/// Returns the variable whose (truncated or extended) value is given by the expression.
+fn get_compared_variable(expression: &Expression) -> Option<&Variable> {
+    match expression {
+        Expression::Var(var) => Some(var),
+        Expression::Subpiece { arg, .. } | Expression::Cast { arg, .. } => {
+            get_compared_variable(arg)
+        }
+        Expression::BinOp {
+            op: BinOpType::IntAnd,
+            lhs,
+            rhs,
+        } if lhs == rhs => get_compared_variable(lhs),
+        _ => None,
+    }
+}
+
+/// Returns the last `Def` before the given index in the block that assigns to the given variable.
+fn get_last_def_of_variable<'b>(
+    block: &'b Term<Blk>,
+    var: &Variable,
+    end_index: usize,
+) -> Option<(usize, &'b Def)> {
+    block.term.defs[..end_index]
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, def)| match &def.term {
+            Def::Assign { var: output, .. } | Def::Load { var: output, .. } => output == var,
+            Def::Store { .. } => false,
+        })
+        .map(|(index, def)| (index, &def.term))
+}
+
+/// Returns the `Def` that loads the single byte that the given expression evaluates to.
+///
+/// Assignments to the compared variables before the given index in the block are followed
+/// until a `Load` is found.
+fn get_loading_def<'b>(
+    block: &'b Term<Blk>,
+    expression: &Expression,
+    end_index: usize,
+) -> Option<&'b Term<Def>> {
+    let mut var = get_compared_variable(expression)?.clone();
+    let mut end_index = end_index;
+    while let Some((index, def)) = get_last_def_of_variable(block, &var, end_index) {
+        match def {
+            Def::Load { var: output, .. } if output.size == ByteSize::new(1) => {
+                return Some(&block.term.defs[index])
+            }
+            Def::Assign { value, .. } => var = get_compared_variable(value)?.clone(),
+            _ => return None,
+        }
+        end_index = index;
+    }
+
+    None
 }
 
 #[cfg(test)]
@@ -7,16 +7,20 @@ use crate::abstract_domain::{
     AbstractIdentifier, DomainInsertion, HasTop, IntervalDomain, TryToBitvec,
 };
 use crate::analysis::pointer_inference::State as PointerInferenceState;
-use crate::intermediate_representation::{Bitvector, Datatype};
+use crate::intermediate_representation::{Bitvector, Datatype, Jmp, Term};
 use crate::{abstract_domain::AbstractDomain, intermediate_representation::ExternSymbol};
 
 use super::super::state::State;
 use super::Context;
 
+mod input;
 mod memcpy;
+mod memset;
+mod predicates;
 mod scanf;
 mod sprintf;
 mod strcat;
+mod strcmp;
 
 impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Context<'a, T> {
     /// Handles generic symbol calls by deleting all non callee saved pointer entries.
@@ -53,6 +57,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
     /// as good as possible by checking the input parameters.
     pub fn handle_string_symbol_calls(
         &self,
+        call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
         state: &State<T>,
     ) -> State<T> {
@@ -60,10 +65,12 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
             "scanf" | "__isoc99_scanf" => self.handle_scanf_calls(state, extern_symbol),
             "sscanf" | "__isoc99_sscanf" => self.handle_sscanf_calls(state, extern_symbol),
             "sprintf" | "snprintf" | "vsprintf" | "vsnprintf" => {
-                self.handle_sprintf_and_snprintf_calls(state, extern_symbol)
+                self.handle_sprintf_and_snprintf_calls(state, call, extern_symbol)
             }
             "strcat" | "strncat" => self.handle_strcat_and_strncat_calls(state, extern_symbol),
-            "memcpy" => self.handle_memcpy_calls(state, extern_symbol),
+            "memcpy" | "memmove" => self.handle_memcpy_calls(state, extern_symbol),
+            "memset" => self.handle_memset_calls(state, extern_symbol),
+            "strcmp" | "strncmp" => self.handle_string_comparison_calls(state, extern_symbol),
             "free" => self.handle_free(state, extern_symbol),
             _ => panic!("Unexpected Extern Symbol."),
         };
@@ -94,7 +101,11 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                     );
                 }
             } else {
-                state.add_new_heap_to_string_entry(target.clone(), domain_input_string.clone());
+                state.add_new_heap_to_string_entry(
+                    target.clone(),
+                    offset.try_to_offset().ok(),
+                    domain_input_string.clone(),
+                );
             }
         }
     }
@@ -128,8 +139,8 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                 }
             } else {
                 // Check the heap map if the target points to a heap position.
-                if let Some(domain) = state.get_heap_to_string_map().get(target) {
-                    domains.push(domain.clone());
+                if let Some(domain) = state.get_heap_string(target, offset.try_to_offset().ok()) {
+                    domains.push(domain);
                 } else {
                     return T::create_top_value_domain();
                 }
@@ -201,7 +212,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         if let Ok(string) = self
             .project
             .runtime_memory_image
-            .read_string_until_null_terminator(&constant)
+            .read_string_literal(&constant)
         {
             if !string.is_empty() {
                 return Some(T::from(string.to_string()));
@@ -211,6 +222,24 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         None
     }
 
+    /// Returns the value of the parameter with the given index if it is a known constant.
+    pub fn get_constant_parameter(
+        &self,
+        extern_symbol: &ExternSymbol,
+        pi_state: &PointerInferenceState,
+        index: usize,
+    ) -> Option<u64> {
+        let param = extern_symbol.parameters.get(index)?;
+        pi_state
+            .eval_parameter_arg(param, &self.project.runtime_memory_image)
+            .ok()?
+            .get_if_absolute_value()?
+            .try_to_bitvec()
+            .ok()?
+            .try_to_u64()
+            .ok()
+    }
+
     /// Deletes string entries in the heap to string map if the corresponding pointer is used
     /// to free memory space.
     pub fn handle_free(&self, state: &State<T>, extern_symbol: &ExternSymbol) -> State<T> {
@@ -235,6 +264,5 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
     }
 }
 
-// TODO: Fix tests.
-//#[cfg(test)]
-//pub mod tests;
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,126 @@
+//! This module handles calls to extern functions that write user input into a buffer.
+
+use crate::abstract_domain::{AbstractDomain, DomainInsertion, HasTop, PredicateDomain};
+use crate::analysis::input_sources::BufferSource;
+use crate::analysis::string_abstraction::{context::Context, state::State};
+use crate::intermediate_representation::ExternSymbol;
+
+impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Context<'a, T> {
+    /// Handles calls to functions like `read` or `fgets` that write user input into a buffer.
+    ///
+    /// The buffer is overwritten with a string with unknown content.
+    /// If the size parameters of the call are constants,
+    /// the string gets the maximal number of written bytes as length bound,
+    /// excluding the terminating null byte for functions like `fgets`.
+    /// Checks at copy sinks, e.g. of `strcpy` in CWE-119, use this bound
+    /// as an upper bound for the number of copied bytes.
+    pub fn handle_input_buffer_calls(
+        &self,
+        state: &mut State<T>,
+        extern_symbol: &ExternSymbol,
+        source: &BufferSource,
+    ) {
+        let Some(pi_state) = state.get_pointer_inference_state().cloned() else {
+            return;
+        };
+        let eval_parameter = |param: &_| {
+            pi_state
+                .eval_parameter_arg(param, &self.project.runtime_memory_image)
+                .ok()
+        };
+        let Some(buffer) = extern_symbol
+            .parameters
+            .get(source.buffer_index)
+            .and_then(eval_parameter)
+        else {
+            return;
+        };
+        Self::add_new_string_abstract_domain(
+            state,
+            &pi_state,
+            buffer.get_relative_values(),
+            T::create_top_value_domain(),
+        );
+        if let Some(length) = source.get_max_string_length(extern_symbol, eval_parameter) {
+            state.add_string_predicates(
+                &buffer,
+                &PredicateDomain::from_length_bound(length),
+                &pi_state,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abstract_domain::{BricksDomain, SizedDomain},
+        analysis::input_sources::InputSources,
+        analysis::pointer_inference::{Data, PointerInference as PointerInferenceComputation},
+        analysis::string_abstraction::{
+            context::symbol_calls::tests::Setup,
+            tests::mock_project_with_intraprocedural_control_flow,
+        },
+        intermediate_representation::*,
+        variable,
+    };
+
+    #[test]
+    fn test_handle_input_buffer_calls() {
+        let memcpy_symbol = ExternSymbol::mock_memcpy_symbol_arm();
+        let project = mock_project_with_intraprocedural_control_flow(
+            vec![(memcpy_symbol.clone(), vec![true])],
+            "func",
+        );
+        let mut pi_results = PointerInferenceComputation::mock(&project);
+        pi_results.compute(false);
+        let setup: Setup<BricksDomain> = Setup::new(&pi_results);
+        let mut fgets_symbol = memcpy_symbol;
+        fgets_symbol.name = "fgets".to_string();
+        let fgets_source = InputSources::default()
+            .get_buffer_source("fgets")
+            .unwrap()
+            .clone();
+
+        let input_buffer_result = |source: &BufferSource, size: Option<i32>| {
+            let mut pi_state = setup.pi_state_before_symbol_call.clone();
+            let size = match size {
+                Some(size) => Bitvector::from_i32(size).into(),
+                None => Data::new_top(ByteSize::new(4)),
+            };
+            pi_state.set_register(&variable!("r1:4"), size);
+            let buffer = pi_state.get_register(&variable!("r0:4"));
+            let mut state = setup.state_before_call.clone();
+            state.set_pointer_inference_state(Some(pi_state.clone()));
+            setup
+                .context
+                .handle_input_buffer_calls(&mut state, &fgets_symbol, source);
+            (
+                state.get_stack_offset_to_string_map().get(&-60).cloned(),
+                state
+                    .get_string_predicates(&buffer, &pi_state)
+                    .get_length_bound(),
+            )
+        };
+
+        // The terminating null byte does not count towards the string length.
+        assert_eq!(
+            input_buffer_result(&fgets_source, Some(64)),
+            (Some(BricksDomain::Top), Some(63))
+        );
+        let unterminated_source = BufferSource {
+            null_terminated: false,
+            ..fgets_source.clone()
+        };
+        assert_eq!(
+            input_buffer_result(&unterminated_source, Some(64)),
+            (Some(BricksDomain::Top), Some(64))
+        );
+        // Without a constant size the buffer contains a string of unknown length.
+        assert_eq!(
+            input_buffer_result(&fgets_source, None),
+            (Some(BricksDomain::Top), None)
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Error;
 
 use crate::abstract_domain::AbstractIdentifier;
+use crate::analysis::loop_idioms::{LoopIdiom, LoopSummary};
 use crate::analysis::pointer_inference::State as PointerInferenceState;
 use crate::{
     abstract_domain::{
@@ -14,7 +15,10 @@ use std::collections::BTreeMap;
 use crate::prelude::*;
 
 impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Context<'a, T> {
-    /// Handles the detection of string parameters to memcpy calls.
+    /// Handles the detection of string parameters to memcpy and memmove calls.
+    ///
+    /// If the number of copied bytes is a known constant,
+    /// the copied string domains are truncated accordingly.
     pub fn handle_memcpy_calls(&self, state: &State<T>, extern_symbol: &ExternSymbol) -> State<T> {
         let mut new_state = state.clone();
         if let Some(pi_state) = state.get_pointer_inference_state() {
@@ -23,12 +27,16 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                 if let Ok(input_data) = self.has_input_target(extern_symbol, pi_state) {
                     input_target = Some(input_data)
                 }
+                let copy_length = self
+                    .get_constant_parameter(extern_symbol, pi_state, 2)
+                    .and_then(|length| usize::try_from(length).ok());
 
                 self.process_domains_for_memcpy_calls(
                     &mut new_state,
                     pi_state,
                     return_target,
                     input_target,
+                    copy_length,
                 );
             }
         }
@@ -36,6 +44,52 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         new_state
     }
 
+    /// Handles the entry into a copy loop like a call to memcpy.
+    ///
+    /// If the number of copied elements is a known constant,
+    /// the copied string domains are truncated accordingly.
+    pub fn handle_copy_loop(&self, state: &State<T>, summary: &LoopSummary) -> State<T> {
+        let mut new_state = state.clone();
+        let (
+            Some(pi_state),
+            LoopIdiom::Copy {
+                source,
+                destination,
+                count,
+                ..
+            },
+        ) = (state.get_pointer_inference_state(), &summary.idiom)
+        else {
+            return new_state;
+        };
+        let return_target = pi_state.eval(destination);
+        if return_target.get_relative_values().is_empty() {
+            return new_state;
+        }
+        let copy_length = count
+            .as_ref()
+            .and_then(|count| {
+                pi_state
+                    .eval(&Expression::Var(count.clone()))
+                    .get_if_absolute_value()?
+                    .try_to_bitvec()
+                    .ok()?
+                    .try_to_u64()
+                    .ok()
+            })
+            .and_then(|count| usize::try_from(count * u64::from(summary.step)).ok());
+
+        self.process_domains_for_memcpy_calls(
+            &mut new_state,
+            pi_state,
+            return_target,
+            Some(pi_state.eval(source)),
+            copy_length,
+        );
+
+        new_state
+    }
+
     /// Checks whether the first input parameter contains a return target.
     pub fn has_return_target(
         &self,
@@ -85,12 +139,15 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
     ///
     /// Note that it is assumed that a memcpy input is always a string as it is
     /// part of the *string.h* C header file.
+    ///
+    /// If `copy_length` is known, the copied string domains are truncated to it.
     pub fn process_domains_for_memcpy_calls(
         &self,
         state: &mut State<T>,
         pi_state: &PointerInferenceState,
         return_data: DataDomain<IntervalDomain>,
         input_data: Option<DataDomain<IntervalDomain>>,
+        copy_length: Option<usize>,
     ) {
         let relative_return_targets = return_data.get_relative_values();
         if let Some(input_target) = input_data {
@@ -109,6 +166,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                     pi_state,
                     &input_target,
                     relative_return_targets,
+                    copy_length,
                 )
             }
         } else {
@@ -129,6 +187,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         pi_state: &PointerInferenceState,
         input_target: &DataDomain<IntervalDomain>,
         relative_return_targets: &BTreeMap<AbstractIdentifier, IntervalDomain>,
+        copy_length: Option<usize>,
     ) {
         let domain_from_global_constant: Option<T> = self.get_constant_target(input_target);
 
@@ -150,7 +209,10 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                 _ => None,
             };
 
-        if let Some(output) = output_domain {
+        if let Some(mut output) = output_domain {
+            if let Some(copy_length) = copy_length {
+                output = output.truncate(copy_length);
+            }
             Context::<T>::add_new_string_abstract_domain(
                 state,
                 pi_state,
@@ -195,13 +257,12 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
     }
 }
 
-// TODO: Fix tests.
-/*
 #[cfg(test)]
 mod tests {
     use crate::{
         abstract_domain::{
-            AbstractIdentifier, AbstractLocation, CharacterInclusionDomain, CharacterSet,
+            AbstractIdentifier, AbstractLocation, BricksDomain, CharacterInclusionDomain,
+            CharacterSet, NulTerminationDomain, TerminatedStringDomain,
         },
         analysis::pointer_inference::PointerInference as PointerInferenceComputation,
         analysis::string_abstraction::{
@@ -247,9 +308,11 @@ mod tests {
                 4,
                 CharacterInclusionDomain::from("a".to_string()),
             );
-        setup
-            .state_before_call
-            .add_new_heap_to_string_entry(heap_id, CharacterInclusionDomain::from("b".to_string()));
+        setup.state_before_call.add_new_heap_to_string_entry(
+            heap_id,
+            Some(0),
+            CharacterInclusionDomain::from("b".to_string()),
+        );
 
         setup
             .pi_state_before_symbol_call
@@ -303,6 +366,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_handle_memmove_calls_with_copy_length() {
+        let mut memmove_symbol = ExternSymbol::mock_memcpy_symbol_arm();
+        memmove_symbol.name = "memmove".to_string();
+        let project = mock_project_with_intraprocedural_control_flow(
+            vec![(memmove_symbol.clone(), vec![true])],
+            "func",
+        );
+        let mut pi_results = PointerInferenceComputation::mock(&project);
+        pi_results.compute(false);
+
+        let mut setup: Setup<BricksDomain> = Setup::new(&pi_results);
+        setup
+            .pi_state_before_symbol_call
+            .set_register(&variable!("r2:4"), Bitvector::from_i32(9).into());
+        setup
+            .state_before_call
+            .set_pointer_inference_state(Some(setup.pi_state_before_symbol_call));
+
+        // The copied string is truncated to the number of copied bytes.
+        let new_state = setup.context.handle_string_symbol_calls(
+            &setup.call,
+            &memmove_symbol,
+            &setup.state_before_call,
+        );
+        assert_eq!(
+            BricksDomain::from("str1 str2".to_string()),
+            *new_state
+                .get_stack_offset_to_string_map()
+                .get(&-60)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_handle_memcpy_calls_with_termination() {
+        let memcpy_symbol = ExternSymbol::mock_memcpy_symbol_arm();
+        let project = mock_project_with_intraprocedural_control_flow(
+            vec![(memcpy_symbol.clone(), vec![true])],
+            "func",
+        );
+        let mut pi_results = PointerInferenceComputation::mock(&project);
+        pi_results.compute(false);
+        let setup: Setup<TerminatedStringDomain<BricksDomain>> = Setup::new(&pi_results);
+
+        let copied_termination = |copy_length: i32| {
+            let mut pi_state = setup.pi_state_before_symbol_call.clone();
+            pi_state.set_register(&variable!("r2:4"), Bitvector::from_i32(copy_length).into());
+            let mut state = setup.state_before_call.clone();
+            state.set_pointer_inference_state(Some(pi_state));
+            setup
+                .context
+                .handle_memcpy_calls(&state, &memcpy_symbol)
+                .get_stack_offset_to_string_map()
+                .get(&-60)
+                .unwrap()
+                .get_termination()
+        };
+
+        // The NUL byte of "str1 str2 str3 str4" is not copied.
+        assert_eq!(copied_termination(19), NulTerminationDomain::Unterminated);
+        // The NUL byte is copied.
+        assert_eq!(copied_termination(20), NulTerminationDomain::Terminated);
+    }
+
     #[test]
     fn test_has_return_target() {
         let memcpy_symbol = ExternSymbol::mock_memcpy_symbol_arm();
@@ -384,6 +512,7 @@ mod tests {
             &setup.pi_state_before_symbol_call,
             return_targets,
             Some(input_target),
+            None,
         );
 
         assert_eq!(
@@ -430,6 +559,7 @@ mod tests {
                 &setup.pi_state_before_symbol_call,
                 &input_target,
                 &return_targets,
+                None,
             );
 
         assert_eq!(
@@ -509,4 +639,3 @@ mod tests {
         ));
     }
 }
-*/
@@ -0,0 +1,114 @@
+use crate::abstract_domain::{AbstractDomain, DomainInsertion, HasTop};
+use crate::analysis::string_abstraction::{context::Context, state::State};
+use crate::intermediate_representation::ExternSymbol;
+
+/// The maximal number of bytes written by a memset call
+/// for which the resulting string is tracked exactly.
+const MAX_TRACKED_MEMSET_LENGTH: u64 = 256;
+
+impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Context<'a, T> {
+    /// Handles memset calls by overwriting the string domains of all destination targets.
+    ///
+    /// Calls that write zero bytes do not change the state.
+    /// If the fill character is zero, the destination contains an empty string.
+    /// If the fill character is an ASCII character and the number of written bytes
+    /// is a known constant, the destination contains the corresponding repetition
+    /// of the fill character, i.e. one character per written byte.
+    /// Otherwise the destination receives a *Top* value.
+    pub fn handle_memset_calls(&self, state: &State<T>, extern_symbol: &ExternSymbol) -> State<T> {
+        let mut new_state = state.clone();
+        if let Some(pi_state) = state.get_pointer_inference_state() {
+            if let Ok(return_target) = self.has_return_target(extern_symbol, pi_state) {
+                let fill_character = self
+                    .get_constant_parameter(extern_symbol, pi_state, 1)
+                    .map(|character| character as u8)
+                    .filter(u8::is_ascii)
+                    .map(char::from);
+                let length = self.get_constant_parameter(extern_symbol, pi_state, 2);
+                if length == Some(0) {
+                    return new_state;
+                }
+                let domain = match (fill_character, length) {
+                    (Some('\0'), _) => T::create_empty_string_domain(),
+                    (Some(character), Some(length)) if length <= MAX_TRACKED_MEMSET_LENGTH => {
+                        T::from(character.to_string().repeat(length as usize))
+                    }
+                    _ => T::create_top_value_domain(),
+                };
+
+                Context::<T>::add_new_string_abstract_domain(
+                    &mut new_state,
+                    pi_state,
+                    return_target.get_relative_values(),
+                    domain,
+                );
+            }
+        }
+
+        new_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abstract_domain::BricksDomain,
+        analysis::pointer_inference::PointerInference as PointerInferenceComputation,
+        analysis::string_abstraction::{
+            context::symbol_calls::tests::Setup,
+            tests::mock_project_with_intraprocedural_control_flow,
+        },
+        intermediate_representation::*,
+        variable,
+    };
+
+    #[test]
+    fn test_handle_memset_calls() {
+        let mut memset_symbol = ExternSymbol::mock_memcpy_symbol_arm();
+        memset_symbol.name = "memset".to_string();
+        let project = mock_project_with_intraprocedural_control_flow(
+            vec![(memset_symbol.clone(), vec![true])],
+            "func",
+        );
+        let mut pi_results = PointerInferenceComputation::mock(&project);
+        pi_results.compute(false);
+        let setup: Setup<BricksDomain> = Setup::new(&pi_results);
+
+        let memset_result = |fill_character: i32, length: i32| {
+            let mut pi_state = setup.pi_state_before_symbol_call.clone();
+            pi_state.set_register(
+                &variable!("r1:4"),
+                Bitvector::from_i32(fill_character).into(),
+            );
+            pi_state.set_register(&variable!("r2:4"), Bitvector::from_i32(length).into());
+            let mut state = setup.state_before_call.clone();
+            state.set_pointer_inference_state(Some(pi_state));
+            setup
+                .context
+                .handle_memset_calls(&state, &memset_symbol)
+                .get_stack_offset_to_string_map()
+                .get(&-60)
+                .cloned()
+        };
+
+        // One fill character is written per byte.
+        assert_eq!(
+            memset_result(0x41, 3),
+            Some(BricksDomain::from("AAA".to_string()))
+        );
+        // Zero bytes result in an empty string.
+        assert_eq!(
+            memset_result(0, 8),
+            Some(BricksDomain::from("".to_string()))
+        );
+        // Writing zero bytes does not change the state.
+        assert_eq!(memset_result(0x41, 0), None);
+        // Non-ASCII fill bytes and large lengths are not tracked exactly.
+        assert_eq!(memset_result(0xe4, 3), Some(BricksDomain::Top));
+        assert_eq!(
+            memset_result(0x41, MAX_TRACKED_MEMSET_LENGTH as i32 + 1),
+            Some(BricksDomain::Top)
+        );
+    }
+}
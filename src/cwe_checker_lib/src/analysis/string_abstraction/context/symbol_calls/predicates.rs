@@ -0,0 +1,57 @@
+use crate::abstract_domain::{AbstractDomain, DomainInsertion, HasTop, PredicateDomain};
+use crate::analysis::string_abstraction::{context::Context, state::State};
+use crate::intermediate_representation::ExternSymbol;
+
+impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Context<'a, T> {
+    /// Handles calls to configured sanitizer functions by recording
+    /// that the string pointed to by the sanitized parameter was passed through the sanitizer.
+    pub fn handle_sanitizer_calls(
+        &self,
+        state: &mut State<T>,
+        extern_symbol: &ExternSymbol,
+        parameter_index: usize,
+    ) {
+        let Some(pi_state) = state.get_pointer_inference_state().cloned() else {
+            return;
+        };
+        let Some(pointer) = extern_symbol
+            .parameters
+            .get(parameter_index)
+            .and_then(|param| {
+                pi_state
+                    .eval_parameter_arg(param, &self.project.runtime_memory_image)
+                    .ok()
+            })
+        else {
+            return;
+        };
+        state.add_string_predicates(
+            &pointer,
+            &PredicateDomain::from_sanitizer(&extern_symbol.name),
+            &pi_state,
+        );
+    }
+
+    /// Handles strlen calls by storing the pointer to the measured string for the return register.
+    /// If the length is later checked against a constant, the string gets a corresponding length bound.
+    pub fn handle_strlen_calls(&self, state: &mut State<T>, extern_symbol: &ExternSymbol) {
+        let Some(pi_state) = state.get_pointer_inference_state() else {
+            return;
+        };
+        let (Some(return_register), Some(param)) = (
+            self.project
+                .get_calling_convention(extern_symbol)
+                .integer_return_register
+                .first(),
+            extern_symbol.parameters.first(),
+        ) else {
+            return;
+        };
+        if let Ok(pointer) = pi_state.eval_parameter_arg(param, &self.project.runtime_memory_image)
+        {
+            if !pointer.get_relative_values().is_empty() {
+                state.add_string_length_entry(return_register.clone(), pointer);
+            }
+        }
+    }
+}
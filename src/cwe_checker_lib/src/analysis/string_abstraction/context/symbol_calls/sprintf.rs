@@ -1,12 +1,14 @@
 use regex::Match;
 
+use crate::analysis::graph::Node;
 use crate::analysis::pointer_inference::State as PointerInferenceState;
+use crate::analysis::variadic_arguments::MaterializedArguments;
 use crate::{
     abstract_domain::{
         AbstractDomain, DataDomain, DomainInsertion, HasTop, IntervalDomain, TryToBitvec,
     },
     analysis::string_abstraction::{context::Context, state::State},
-    intermediate_representation::{Arg, Datatype, ExternSymbol},
+    intermediate_representation::{Arg, Datatype, ExternSymbol, Jmp, Term},
     utils::arguments::{get_input_format_string, get_variable_parameters},
 };
 
@@ -19,6 +21,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
     pub fn handle_sprintf_and_snprintf_calls(
         &self,
         state: &State<T>,
+        call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
     ) -> State<T> {
         let mut new_state = state.clone();
@@ -35,6 +38,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                         self.parse_format_string_and_add_new_string_domain(
                             &mut new_state,
                             pi_state,
+                            call,
                             extern_symbol,
                             *format_string_index,
                             &return_pointer,
@@ -55,6 +59,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         &self,
         state: &mut State<T>,
         pi_state: &PointerInferenceState,
+        call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
         format_string_index: usize,
         return_pointer: &DataDomain<IntervalDomain>,
@@ -68,6 +73,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
             let returned_abstract_domain = self.create_string_domain_for_sprintf_snprintf(
                 pi_state,
                 state,
+                call,
                 extern_symbol,
                 input_format_string,
             );
@@ -90,10 +96,15 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
 
     /// Creates a string domain for a s(n)printf call by considering input constants
     /// and other domains.
+    ///
+    /// If the caller does not set up all arguments expected by the format string,
+    /// the values of the arguments are unreliable and the specifiers are
+    /// approximated according to their data type instead.
     pub fn create_string_domain_for_sprintf_snprintf(
         &self,
         pi_state: &PointerInferenceState,
         state: &State<T>,
+        call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
         input_format_string: String,
     ) -> T {
@@ -110,6 +121,16 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                 if var_args.is_empty() {
                     return T::create_top_value_domain();
                 }
+                if self.get_materialized_arguments(state, call).is_some_and(
+                    |materialized_arguments| {
+                        !var_args
+                            .iter()
+                            .all(|arg| materialized_arguments.is_materialized(arg))
+                    },
+                ) {
+                    return self
+                        .create_string_domain_using_data_type_approximations(input_format_string);
+                }
 
                 self.create_string_domain_using_constants_and_sub_domains(
                     input_format_string,
@@ -122,6 +143,22 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         }
     }
 
+    /// Returns the arguments that are set up in the block of the given call.
+    fn get_materialized_arguments(
+        &self,
+        state: &State<T>,
+        call: &Term<Jmp>,
+    ) -> Option<MaterializedArguments> {
+        let sub = state.get_current_sub()?;
+        let node = self
+            .jmp_to_blk_end_node_map
+            .get(&(call.tid.clone(), sub.tid.clone()))?;
+        let Node::BlkEnd(blk, _) = self.pointer_inference_results.get_graph()[*node] else {
+            return None;
+        };
+        MaterializedArguments::new(blk, &call.tid, self.project, self.pointer_inference_results)
+    }
+
     /// Creates a domain from a format string where all specifiers are approximated according
     /// to their data type. This ensures that, if there is a long data type, that the domain is
     /// no returned as *Top*.
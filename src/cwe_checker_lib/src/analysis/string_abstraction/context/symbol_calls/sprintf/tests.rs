@@ -25,7 +25,11 @@ fn test_handle_sprintf_and_snprintf_calls() {
 
     let new_state = setup
         .context
-        .handle_sprintf_and_snprintf_calls(&setup.state_before_call, &sprintf_symbol);
+        .handle_sprintf_and_snprintf_calls(
+            &setup.state_before_call,
+            &setup.call,
+            &sprintf_symbol,
+        );
 
     let stack_id = AbstractIdentifier::new(
         Tid::new("func"),
@@ -85,6 +89,7 @@ fn test_parse_format_string_and_add_new_string_domain() {
     setup.context.parse_format_string_and_add_new_string_domain(
         &mut setup.state_before_call,
         &setup.pi_state_before_symbol_call,
+        &setup.call,
         &sprintf_symbol,
         format_string_index,
         &return_pointer,
@@ -136,10 +141,25 @@ fn test_create_string_domain_for_sprintf_snprintf() {
         setup.context.create_string_domain_for_sprintf_snprintf(
             &setup.pi_state_before_symbol_call,
             &setup.state_before_call,
+            &setup.call,
             &sprintf_symbol,
             "cat %s %s %s %s".to_string(),
         )
     );
+
+    // The fifth argument is not set up by the caller.
+    assert_eq!(
+        setup
+            .context
+            .create_string_domain_using_data_type_approximations("cat %s %s %s %s %s".to_string()),
+        setup.context.create_string_domain_for_sprintf_snprintf(
+            &setup.pi_state_before_symbol_call,
+            &setup.state_before_call,
+            &setup.call,
+            &sprintf_symbol,
+            "cat %s %s %s %s %s".to_string(),
+        )
+    );
 }
 
 #[test]
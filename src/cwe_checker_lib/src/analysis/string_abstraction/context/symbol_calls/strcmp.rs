@@ -0,0 +1,80 @@
+use crate::abstract_domain::{AbstractDomain, DomainInsertion, HasTop, TryToBitvec};
+use crate::analysis::string_abstraction::{context::Context, state::State};
+use crate::intermediate_representation::ExternSymbol;
+
+impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Context<'a, T> {
+    /// Handles strcmp and strncmp calls by storing the compared strings for the return register.
+    ///
+    /// If the call returns zero, the string pointed to by one parameter
+    /// is contained in the string domain of the other parameter.
+    /// For strncmp only prefixes of the given length are compared.
+    /// If the length is unknown, no comparison is stored.
+    pub fn handle_string_comparison_calls(
+        &self,
+        state: &State<T>,
+        extern_symbol: &ExternSymbol,
+    ) -> State<T> {
+        let mut new_state = state.clone();
+        let Some(pi_state) = state.get_pointer_inference_state() else {
+            return new_state;
+        };
+        let Some(return_register) = self
+            .project
+            .get_calling_convention(extern_symbol)
+            .integer_return_register
+            .first()
+        else {
+            return new_state;
+        };
+        let compared_length = if extern_symbol.name == "strncmp" {
+            match self.get_constant_parameter(extern_symbol, pi_state, 2) {
+                Some(length) => Some(length as usize),
+                None => return new_state,
+            }
+        } else {
+            None
+        };
+        let pointers: Vec<_> = extern_symbol
+            .parameters
+            .iter()
+            .take(2)
+            .filter_map(|param| {
+                pi_state
+                    .eval_parameter_arg(param, &self.project.runtime_memory_image)
+                    .ok()
+            })
+            .collect();
+        let [first, second] = pointers.as_slice() else {
+            return new_state;
+        };
+
+        let mut comparisons = Vec::new();
+        for (pointer, other_pointer) in [(first, second), (second, first)] {
+            let domain = if let Some(address) = other_pointer
+                .get_if_absolute_value()
+                .and_then(|value| value.try_to_bitvec().ok())
+            {
+                match self.get_constant_string_domain(address) {
+                    Some(domain) => domain,
+                    None => continue,
+                }
+            } else {
+                Context::<T>::merge_domains_from_multiple_pointer_targets(
+                    state,
+                    pi_state,
+                    other_pointer.get_relative_values(),
+                )
+            };
+            let domain = match compared_length {
+                Some(length) => domain
+                    .truncate(length)
+                    .append_string_domain(&T::create_top_value_domain()),
+                None => domain,
+            };
+            comparisons.push((pointer.clone(), domain));
+        }
+        new_state.add_string_comparison_entry(return_register.clone(), comparisons);
+
+        new_state
+    }
+}
@@ -24,6 +24,7 @@ pub struct Setup<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<St
     pub context: Context<'a, T>,
     pub pi_state_before_symbol_call: PiState,
     pub state_before_call: State<T>,
+    pub call: Term<Jmp>,
 }
 
 impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String> + Debug> Setup<'a, T> {
@@ -47,7 +48,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String> + Debu
             .unwrap()
             .term
             .blocks
-            .get(0)
+            .first()
             .unwrap()
             .term
             .defs
@@ -57,10 +58,10 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String> + Debu
         }
 
         let context: Context<T> = Context::mock(
-            &pi_context.project,
-            mock_string_symbol_map(&pi_context.project),
+            pi_context.project,
+            mock_string_symbol_map(pi_context.project),
             mock_format_index_map(),
-            &pi_results,
+            pi_results,
         );
 
         let state_before_call: State<T> = State::mock_with_given_pi_state(
@@ -76,10 +77,29 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String> + Debu
             pi_state.clone(),
         );
 
+        let call = pi_context
+            .project
+            .program
+            .term
+            .subs
+            .values()
+            .next()
+            .unwrap()
+            .term
+            .blocks
+            .first()
+            .unwrap()
+            .term
+            .jmps
+            .first()
+            .unwrap()
+            .clone();
+
         Setup {
             context,
             pi_state_before_symbol_call: pi_state,
             state_before_call,
+            call,
         }
     }
 }
@@ -125,7 +145,7 @@ fn test_handle_generic_symbol_calls() {
 
     let new_state = setup
         .context
-        .handle_generic_symbol_calls(&memcpy_symbol, &mut setup.state_before_call);
+        .handle_generic_symbol_calls(&memcpy_symbol, &setup.state_before_call);
 
     assert!(new_state.get_variable_to_pointer_map().is_empty());
 }
@@ -184,7 +204,7 @@ fn test_add_new_string_abstract_domain() {
     Context::<CharacterInclusionDomain>::add_new_string_abstract_domain(
         &mut state,
         &setup.pi_state_before_symbol_call,
-        &stack_pointer.get_relative_values(),
+        stack_pointer.get_relative_values(),
         CharacterInclusionDomain::from("Hello World".to_string()),
     );
 
@@ -205,7 +225,7 @@ fn test_add_new_string_abstract_domain() {
     Context::<CharacterInclusionDomain>::add_new_string_abstract_domain(
         &mut state,
         &setup.pi_state_before_symbol_call,
-        &heap_pointer.get_relative_values(),
+        heap_pointer.get_relative_values(),
         CharacterInclusionDomain::Top,
     );
 
@@ -243,7 +263,7 @@ fn test_merge_domains_from_multiple_pointer_targets() {
         Context::<CharacterInclusionDomain>::merge_domains_from_multiple_pointer_targets(
             &setup.state_before_call,
             &setup.pi_state_before_symbol_call,
-            &domain_pointer.get_relative_values(),
+            domain_pointer.get_relative_values(),
         );
 
     assert_eq!(CharacterInclusionDomain::Top, returned_domain);
@@ -256,7 +276,7 @@ fn test_merge_domains_from_multiple_pointer_targets() {
         Context::<CharacterInclusionDomain>::merge_domains_from_multiple_pointer_targets(
             &setup.state_before_call,
             &setup.pi_state_before_symbol_call,
-            &domain_pointer.get_relative_values(),
+            domain_pointer.get_relative_values(),
         );
 
     assert_eq!(
@@ -270,20 +290,22 @@ fn test_merge_domains_from_multiple_pointer_targets() {
         Context::<CharacterInclusionDomain>::merge_domains_from_multiple_pointer_targets(
             &setup.state_before_call,
             &setup.pi_state_before_symbol_call,
-            &domain_pointer.get_relative_values(),
+            domain_pointer.get_relative_values(),
         );
 
     assert_eq!(CharacterInclusionDomain::Top, returned_domain);
 
     // Test Case 4: Stack and Heap pointer with two targets and both point to different domains.
-    setup
-        .state_before_call
-        .add_new_heap_to_string_entry(heap_id, CharacterInclusionDomain::from("b".to_string()));
+    setup.state_before_call.add_new_heap_to_string_entry(
+        heap_id,
+        Some(0),
+        CharacterInclusionDomain::from("b".to_string()),
+    );
     let returned_domain =
         Context::<CharacterInclusionDomain>::merge_domains_from_multiple_pointer_targets(
             &setup.state_before_call,
             &setup.pi_state_before_symbol_call,
-            &domain_pointer.get_relative_values(),
+            domain_pointer.get_relative_values(),
         );
 
     let expected_domain = CharacterInclusionDomain::Value((
@@ -307,9 +329,11 @@ fn test_handle_sprintf_and_snprintf_calls_known_format_string() {
 
     let setup: Setup<CharacterInclusionDomain> = Setup::new(&pi_results);
 
-    let new_state = setup
-        .context
-        .handle_sprintf_and_snprintf_calls(&setup.state_before_call, &sprintf_symbol);
+    let new_state = setup.context.handle_sprintf_and_snprintf_calls(
+        &setup.state_before_call,
+        &setup.call,
+        &sprintf_symbol,
+    );
 
     let expected_domain = CharacterInclusionDomain::Value((
         CharacterSet::Value(
@@ -323,7 +347,7 @@ fn test_handle_sprintf_and_snprintf_calls_known_format_string() {
     assert_eq!(
         *new_state
             .get_stack_offset_to_string_map()
-            .get(&(-0x54 as i64))
+            .get(&-0x54_i64)
             .unwrap(),
         expected_domain,
     );
@@ -342,15 +366,17 @@ fn test_handle_sprintf_and_snprintf_calls_unknown_format_string() {
 
     let setup: Setup<CharacterInclusionDomain> = Setup::new(&pi_results);
 
-    let new_state = setup
-        .context
-        .handle_sprintf_and_snprintf_calls(&setup.state_before_call, &sprintf_symbol);
+    let new_state = setup.context.handle_sprintf_and_snprintf_calls(
+        &setup.state_before_call,
+        &setup.call,
+        &sprintf_symbol,
+    );
 
     assert_eq!(
         CharacterInclusionDomain::Top,
         *new_state
             .get_stack_offset_to_string_map()
-            .get(&(-0x54 as i64))
+            .get(&-0x54_i64)
             .unwrap()
     );
 }
@@ -443,9 +469,11 @@ fn test_handle_free() {
         AbstractLocation::from_var(&r0_reg).unwrap(),
     );
 
-    setup
-        .state_before_call
-        .add_new_heap_to_string_entry(object_id.clone(), CharacterInclusionDomain::Top);
+    setup.state_before_call.add_new_heap_to_string_entry(
+        object_id.clone(),
+        Some(0),
+        CharacterInclusionDomain::Top,
+    );
 
     setup.state_before_call.set_pointer_inference_state(Some(
         pi_results
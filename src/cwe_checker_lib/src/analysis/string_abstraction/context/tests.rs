@@ -38,9 +38,17 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
             string_symbol_map: string_symbols,
             extern_symbol_map,
             format_string_index_map: format_string_index,
+            max_string_cardinality: 256,
+            sanitizer_index_map: HashMap::new(),
+            input_sources: InputSources::default(),
+            returned_string_map: HashMap::new(),
+            alphabet: CharacterAlphabet::default(),
             block_start_node_map,
             block_first_def_set,
             jmp_to_blk_end_node_map: jmp_to_blk_end_node_map,
+            copy_loop_summaries: HashMap::new(),
+            decode_loop_summaries: HashMap::new(),
+            restricted_graph: None,
             _phantom_string_domain: PhantomData,
         }
     }
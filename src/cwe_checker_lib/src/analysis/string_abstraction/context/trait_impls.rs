@@ -1,5 +1,5 @@
 use crate::{
-    abstract_domain::{AbstractDomain, DomainInsertion, HasTop},
+    abstract_domain::{AbstractDomain, DomainInsertion, DomainOrder, HasTop},
     analysis::string_abstraction::state::State,
     intermediate_representation::{Blk, Def, Expression, Jmp, Term},
 };
@@ -13,12 +13,20 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
 
     /// Get the underlying graph on which the analysis operates.
     fn get_graph(&self) -> &crate::analysis::graph::Graph<'a> {
-        self.pointer_inference_results.get_graph()
+        self.restricted_graph
+            .as_ref()
+            .unwrap_or_else(|| self.pointer_inference_results.get_graph())
     }
 
     /// Merge two state values.
+    /// String domains that would grow too large are overapproximated by *Top*.
     fn merge(&self, state1: &Self::Value, state2: &Self::Value) -> State<T> {
-        state1.merge(state2)
+        state1.merge_with_cardinality_limit(state2, self.max_string_cardinality)
+    }
+
+    /// Compare two states using the partial order of the string domains.
+    fn less_or_equal(&self, state1: &State<T>, state2: &State<T>) -> bool {
+        state1.less_or_equal(state2)
     }
 
     fn update_def(&self, state: &State<T>, def: &Term<Def>) -> Option<State<T>> {
@@ -37,17 +45,26 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
                 return None;
             }
         }
+        let load_address = match &def.term {
+            Def::Load { address, .. } => new_state
+                .get_pointer_inference_state()
+                .map(|pi_state| pi_state.eval(address)),
+            _ => None,
+        };
         self.update_pointer_inference_state(&mut new_state, def);
         match &def.term {
             Def::Assign {
                 var: output,
                 value: input,
             } => {
+                new_state.remove_string_comparison_entry(output);
+                new_state.remove_string_length_entry(output);
                 new_state.handle_assign_and_load(
                     output,
                     input,
                     &self.project.runtime_memory_image,
                     &self.block_first_def_set,
+                    self.alphabet,
                     true,
                 );
             }
@@ -55,33 +72,62 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
                 var: output,
                 address: input,
             } => {
+                new_state.remove_string_comparison_entry(output);
+                new_state.remove_string_length_entry(output);
                 new_state.handle_assign_and_load(
                     output,
                     input,
                     &self.project.runtime_memory_image,
                     &self.block_first_def_set,
+                    self.alphabet,
                     false,
                 );
+                self.add_program_argument_strings(&mut new_state, output);
+                if let Some(address) = load_address {
+                    new_state.handle_array_load(
+                        &def.tid,
+                        output,
+                        &address,
+                        &self.project.runtime_memory_image,
+                    );
+                }
             }
             Def::Store { address, value } => new_state.handle_store(
                 address,
                 value,
                 &self.project.runtime_memory_image,
                 &self.block_first_def_set,
+                self.alphabet,
             ),
         }
 
         Some(new_state)
     }
 
+    /// Jumps into copy loops are handled like calls to memcpy.
+    /// Jumps into decode loops decode the stack strings built by immediate stores.
     fn update_jump(
         &self,
         state: &State<T>,
-        _jump: &Term<Jmp>,
+        jump: &Term<Jmp>,
         _untaken_conditional: Option<&Term<Jmp>>,
-        _target: &Term<Blk>,
+        target: &Term<Blk>,
     ) -> Option<State<T>> {
-        let mut new_state = state.clone();
+        let mut new_state = match self
+            .copy_loop_summaries
+            .get(&target.tid)
+            .filter(|summary| summary.is_entered_by(jump, target))
+        {
+            Some(summary) => self.handle_copy_loop(state, summary),
+            None => match self
+                .decode_loop_summaries
+                .get(&target.tid)
+                .filter(|summary| summary.is_entered_by(jump, target))
+            {
+                Some(summary) => self.handle_decode_loop(state, summary),
+                None => state.clone(),
+            },
+        };
         new_state.set_pointer_inference_state(None);
         Some(new_state)
     }
@@ -96,17 +142,28 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
         None
     }
 
+    /// Pointers returned by the callee and the strings they point to are added to the caller state.
     fn update_return(
         &self,
-        _state: Option<&State<T>>,
+        state: Option<&State<T>>,
         state_before_call: Option<&State<T>>,
-        _call_term: &Term<Jmp>,
+        call_term: &Term<Jmp>,
         _return_term: &Term<Jmp>,
-        _calling_convention: &Option<String>,
+        calling_convention: &Option<String>,
     ) -> Option<State<T>> {
-        if let Some(state) = state_before_call {
-            let mut new_state = state.clone();
+        if let Some(state_before_call) = state_before_call {
+            let mut new_state = state_before_call.clone();
+            new_state.clear_string_comparison_map();
+            new_state.clear_string_length_map();
             self.handle_unknown_symbol_calls(&mut new_state);
+            if let Some(callee_state) = state {
+                self.add_returned_strings_from_callee(
+                    &mut new_state,
+                    callee_state,
+                    call_term,
+                    calling_convention,
+                );
+            }
             new_state.set_pointer_inference_state(None);
             return Some(new_state);
         }
@@ -116,14 +173,41 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
 
     fn update_call_stub(&self, state: &State<T>, call: &Term<Jmp>) -> Option<State<T>> {
         let mut new_state = state.clone();
+        new_state.clear_string_comparison_map();
+        new_state.clear_string_length_map();
         match &call.term {
             Jmp::Call { target, .. } => match self.extern_symbol_map.get(target) {
                 Some(symbol) => {
                     if let Some(string_symbol) = self.string_symbol_map.get(target) {
-                        new_state = self.handle_string_symbol_calls(string_symbol, &new_state);
+                        new_state =
+                            self.handle_string_symbol_calls(call, string_symbol, &new_state);
                     } else {
                         new_state = self.handle_generic_symbol_calls(symbol, &new_state);
                     }
+                    if self.input_sources.is_string_source(&symbol.name) {
+                        // The string is controlled by the user, so its content is unknown.
+                        self.add_string_in_return_register(
+                            &mut new_state,
+                            call,
+                            symbol,
+                            T::create_top_value_domain(),
+                        );
+                    } else if let Some(domain) = self.returned_string_map.get(&symbol.name) {
+                        self.add_string_in_return_register(
+                            &mut new_state,
+                            call,
+                            symbol,
+                            domain.clone(),
+                        );
+                    } else if let Some(source) = self.input_sources.get_buffer_source(&symbol.name)
+                    {
+                        self.handle_input_buffer_calls(&mut new_state, symbol, source);
+                    }
+                    if let Some(index) = self.sanitizer_index_map.get(&symbol.name) {
+                        self.handle_sanitizer_calls(&mut new_state, symbol, *index);
+                    } else if symbol.name == "strlen" {
+                        self.handle_strlen_calls(&mut new_state, symbol);
+                    }
                 }
                 None => panic!("Extern symbol not found."),
             },
@@ -135,17 +219,33 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
         Some(new_state)
     }
 
+    /// Refine the string domains on branches where a string comparison is known to succeed
+    /// and record length bounds of strings on branches where a length check is known to succeed.
+    /// Returns `None` if the branch is infeasible.
     fn specialize_conditional(
         &self,
         state: &State<T>,
-        _condition: &Expression,
-        _block_before_condition: &Term<Blk>,
-        _is_true: bool,
+        condition: &Expression,
+        block_before_condition: &Term<Blk>,
+        is_true: bool,
     ) -> Option<State<T>> {
-        Some(state.clone())
+        let mut new_state = state.clone();
+        self.specialize_string_domains_by_condition(
+            &mut new_state,
+            condition,
+            block_before_condition,
+            is_true,
+        )
+        .ok()?;
+        self.specialize_string_predicates_by_condition(
+            &mut new_state,
+            condition,
+            block_before_condition,
+            is_true,
+        );
+        Some(new_state)
     }
 }
 
-// TODO: Fix tests.
-//#[cfg(test)]
-//mod tests;
+#[cfg(test)]
+mod tests;
@@ -1,4 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+
+use petgraph::graph::NodeIndex;
 
 use crate::{
     abstract_domain::{
@@ -207,6 +209,90 @@ fn test_update_return() {
     );
 }
 
+#[test]
+fn test_update_return_with_returned_strings() {
+    let memcpy_symbol = ExternSymbol::mock_memcpy_symbol_arm();
+    let project = mock_project_with_intraprocedural_control_flow(
+        vec![(memcpy_symbol.clone(), vec![true])],
+        "func",
+    );
+    let mut pi_results = PointerInferenceComputation::mock(&project);
+    pi_results.compute(false);
+
+    let call = Jmp::call("call_callee", "callee", Some("block1"));
+    let cconv = Some("__stdcall".to_string());
+    // The heap object is created in the callee and keeps its ID in the caller.
+    let heap_id = AbstractIdentifier::new(
+        Tid::new("callee_malloc"),
+        AbstractLocation::from_var(&variable!("r0:4")).unwrap(),
+    );
+    let heap_pointer =
+        DataDomain::from_target(heap_id.clone(), IntervalDomain::from(bitvec!("0:4")));
+    let pi_state = pi_results
+        .get_node_value(NodeIndex::new(0))
+        .unwrap()
+        .unwrap_value()
+        .clone();
+    pi_results
+        .get_mut_states_at_tids()
+        .insert(call.tid.clone(), pi_state);
+    pi_results.get_mut_id_renaming_maps_at_calls().insert(
+        call.tid.clone(),
+        BTreeMap::from([(heap_id.clone(), heap_pointer.clone())]),
+    );
+    let setup: Setup<CharacterInclusionDomain> = Setup::new(&pi_results);
+
+    // A pointer to a string constructed by the callee.
+    let mut callee_state = setup.state_before_call.clone();
+    callee_state.add_new_variable_to_pointer_entry(variable!("r0:4"), heap_pointer.clone());
+    callee_state.add_new_heap_to_string_entry(
+        heap_id.clone(),
+        Some(0),
+        CharacterInclusionDomain::ci("abc"),
+    );
+    let new_state = setup
+        .context
+        .update_return(
+            Some(&callee_state),
+            Some(&setup.state_before_call),
+            &call,
+            &Jmp::branch("return", "block1"),
+            &cconv,
+        )
+        .unwrap();
+    assert_eq!(
+        new_state
+            .get_variable_to_pointer_map()
+            .get(&variable!("r0:4")),
+        Some(&heap_pointer)
+    );
+    assert_eq!(
+        new_state.get_heap_string(&heap_id, Some(0)),
+        Some(CharacterInclusionDomain::ci("abc"))
+    );
+
+    // A pointer to a string constant in global memory.
+    let constant_pointer = DataDomain::from(IntervalDomain::from(bitvec!("0x3002:4")));
+    let mut callee_state = setup.state_before_call.clone();
+    callee_state.add_new_variable_to_pointer_entry(variable!("r0:4"), constant_pointer.clone());
+    let new_state = setup
+        .context
+        .update_return(
+            Some(&callee_state),
+            Some(&setup.state_before_call),
+            &call,
+            &Jmp::branch("return", "block1"),
+            &cconv,
+        )
+        .unwrap();
+    assert_eq!(
+        new_state
+            .get_variable_to_pointer_map()
+            .get(&variable!("r0:4")),
+        Some(&constant_pointer)
+    );
+}
+
 #[test]
 fn test_update_call_stub() {
     let memcpy_symbol = ExternSymbol::mock_memcpy_symbol_arm();
@@ -234,3 +320,50 @@ fn test_update_call_stub() {
             .unwrap()
     );
 }
+
+#[test]
+fn test_update_call_stub_with_returned_string_literal() {
+    let version_symbol = ExternSymbol::mock_arm32("version");
+    let memcpy_symbol = ExternSymbol::mock_memcpy_symbol_arm();
+    let project = mock_project_with_intraprocedural_control_flow(
+        vec![(memcpy_symbol.clone(), vec![true])],
+        "func",
+    );
+    let mut pi_results = PointerInferenceComputation::mock(&project);
+    pi_results.compute(false);
+
+    let mut setup: Setup<CharacterInclusionDomain> = Setup::new(&pi_results);
+    setup
+        .context
+        .extern_symbol_map
+        .insert(version_symbol.tid.clone(), &version_symbol);
+    let version_domain = CharacterInclusionDomain::ci("1.0");
+    setup
+        .context
+        .returned_string_map
+        .insert("version".to_string(), version_domain.clone());
+
+    let call_to_version = Jmp::call("call_version", "version", Some("blk1"));
+    let new_state = setup
+        .context
+        .update_call_stub(&setup.state_before_call, &call_to_version)
+        .unwrap();
+
+    let object_id = AbstractIdentifier::new(
+        Tid::new("call_version"),
+        AbstractLocation::from_var(&variable!("r0:4")).unwrap(),
+    );
+    assert_eq!(
+        new_state.get_heap_to_string_map()[&object_id].get(Some(0)),
+        Some(version_domain)
+    );
+    assert_eq!(
+        new_state
+            .get_variable_to_pointer_map()
+            .get(&variable!("r0:4")),
+        Some(&DataDomain::from_target(
+            object_id,
+            IntervalDomain::from(bitvec!("0:4"))
+        ))
+    );
+}
@@ -1,30 +1,53 @@
 //! A fixpoint analysis that abstracts strings in the program using various string abstract domains.
 //! These include the Character Inclusion Domain and Bricks Domain among others.
+//!
+//! By default, string states are computed for every program point.
+//! If sink symbols are configured in the `on_demand_sinks` field of the configuration,
+//! then the analysis runs in on-demand mode instead:
+//! It computes a backward slice of the control flow graph, i.e. all nodes from which
+//! a call to one of the sink symbols is reachable,
+//! and only computes the fixpoint on the slice.
+//! String states are then only available at the nodes of the slice
+//! and at their direct successors (e.g. the return sites of the sink calls).
+//!
+//! Strings controlled by the user of the program are modeled as strings with unknown content.
+//! These are the strings returned by functions like `getenv`
+//! and the program arguments pointed to by the argument vector of `main`,
+//! see the `input_sources` field of the configuration.
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashSet},
     fmt::Debug,
 };
 
 use crate::{
-    abstract_domain::{AbstractDomain, DomainInsertion, HasTop},
-    intermediate_representation::Project,
+    abstract_domain::{
+        AbstractDomain, BricksMemoScope, CharacterAlphabet, DomainInsertion, HasTop,
+        PredicateDomain,
+    },
+    intermediate_representation::{Arg, Jmp, Project},
     prelude::*,
+    utils::log::LogMessage,
 };
 
 use self::state::State;
 
 use super::{
-    fixpoint::Computation, forward_interprocedural_fixpoint::GeneralizedContext, graph::Graph,
-    interprocedural_fixpoint_generic::NodeValue,
+    fixpoint::Computation,
+    forward_interprocedural_fixpoint::GeneralizedContext,
+    graph::{Graph, Node},
+    input_sources::InputSources,
+    interprocedural_fixpoint_generic::{get_failure_logs, NodeValue},
     pointer_inference::PointerInference as PointerInferenceComputation,
+    seeding::EntryPointSeeding,
 };
 
 pub mod context;
+pub mod report;
 pub mod state;
 
 use context::*;
-use petgraph::graph::NodeIndex;
+use petgraph::{graph::NodeIndex, visit::IntoNodeReferences, Direction};
 
 /// Configurable parameters for the analysis.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
@@ -35,6 +58,32 @@ pub struct Config {
     /// The index of the format string parameter in the function signature
     /// of an external symbol.
     pub format_string_index: BTreeMap<String, usize>,
+    /// The maximum number of different strings that a part of a string domain may represent
+    /// when merging states before it is overapproximated by *Top*.
+    pub max_string_cardinality: usize,
+    /// Names of sanitizer functions mapped to the index of the parameter
+    /// pointing to the string that is sanitized by the function.
+    #[serde(default)]
+    pub sanitizer_symbols: BTreeMap<String, usize>,
+    /// The functions at which the analysis is started.
+    #[serde(default)]
+    pub entry_points: EntryPointSeeding,
+    /// Names of extern functions at whose call sites string states are queried.
+    /// If not empty, the analysis only computes string states
+    /// for the program points that may influence calls to these functions.
+    #[serde(default)]
+    pub on_demand_sinks: Vec<String>,
+    /// The sources of user input whose strings are modeled as strings with unknown content.
+    #[serde(default)]
+    pub input_sources: InputSources,
+    /// Names of extern functions returning a pointer to one of the given string literals,
+    /// e.g. functions of shared libraries according to their summaries.
+    #[serde(default)]
+    pub returned_string_symbols: BTreeMap<String, BTreeSet<String>>,
+    /// The alphabet of characters that string literals in global memory may consist of.
+    /// Global data that is probably binary data with respect to the alphabet is not tracked as a string.
+    #[serde(default)]
+    pub alphabet: CharacterAlphabet,
 }
 
 /// A wrapper struct for the string abstraction computation object.
@@ -46,39 +95,40 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
     StringAbstraction<'a, T>
 {
     /// Generate a new string abstraction computation for a project.
+    ///
+    /// Returns an error if the entry point configuration is invalid,
+    /// e.g. if it contains an invalid regular expression.
     pub fn new(
         project: &'a Project,
         control_flow_graph: &'a Graph<'a>,
         pointer_inference_results: &'a PointerInferenceComputation<'a>,
         config: Config,
-    ) -> StringAbstraction<'a, T> {
-        let context = Context::new(project, pointer_inference_results, config);
-
-        let mut sub_to_entry_blocks_map = HashMap::new();
-        for sub in project.program.term.subs.values() {
-            if let Some(entry_block) = sub.term.blocks.first() {
-                sub_to_entry_blocks_map.insert(sub.tid.clone(), entry_block.tid.clone());
-            }
-        }
-        let mut tid_to_graph_indices_map = HashMap::new();
-        for node in control_flow_graph.node_indices() {
-            if let super::graph::Node::BlkStart(block, sub) = control_flow_graph[node] {
-                tid_to_graph_indices_map.insert((block.tid.clone(), sub.tid.clone()), node);
-            }
-        }
-        let sub_to_entry_node_map: HashMap<Tid, NodeIndex> = sub_to_entry_blocks_map
-            .into_iter()
-            .filter_map(|(sub_tid, block_tid)| {
-                tid_to_graph_indices_map
-                    .get(&(block_tid, sub_tid.clone()))
-                    .map(|start_node_index| (sub_tid, *start_node_index))
-            })
-            .collect();
+    ) -> Result<StringAbstraction<'a, T>, Error> {
+        let sub_to_entry_node_map = config
+            .entry_points
+            .get_seed_nodes(project, control_flow_graph)?;
+        let on_demand_sinks = config.on_demand_sinks.clone();
+        let mut context = Context::new(project, pointer_inference_results, config);
+        let slice = if on_demand_sinks.is_empty() {
+            None
+        } else {
+            let graph = pointer_inference_results.get_graph();
+            let sink_nodes = get_call_nodes_of_symbols(project, graph, &on_demand_sinks);
+            let slice = get_backward_slice(graph, &sink_nodes);
+            context.restrict_to_nodes(&slice);
+            Some(slice)
+        };
 
         let mut fixpoint_computation =
             super::forward_interprocedural_fixpoint::create_computation(context, None);
 
         for (_, start_node_index) in sub_to_entry_node_map.into_iter() {
+            if slice
+                .as_ref()
+                .is_some_and(|slice| !slice.contains(&start_node_index))
+            {
+                continue;
+            }
             fixpoint_computation.set_node_value(
                 start_node_index,
                 super::interprocedural_fixpoint_generic::NodeValue::Value(State::new(
@@ -88,17 +138,26 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
             );
         }
 
-        StringAbstraction {
+        Ok(StringAbstraction {
             computation: fixpoint_computation,
-        }
+        })
     }
 
     /// Compute the fixpoint of the string abstraction analysis.
     /// Has a `max_steps` bound for the fixpoint algorithm to prevent infinite loops.
+    ///
+    /// Merges of Bricks domains are memoized during the computation.
     pub fn compute(&mut self) {
+        let _memo_scope = BricksMemoScope::new();
         self.computation.compute_with_max_steps(100); // TODO: make max_steps configurable!
     }
 
+    /// Get the log messages for the functions that were skipped
+    /// because their analysis failed.
+    pub fn get_logs(&self) -> Vec<LogMessage> {
+        get_failure_logs(&self.computation, "String Abstraction")
+    }
+
     /// Get the string abstraction computation.
     pub fn get_computation(&self) -> &Computation<GeneralizedContext<'a, Context<'a, T>>> {
         &self.computation
@@ -120,21 +179,130 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
     pub fn get_node_value(&self, node_id: NodeIndex) -> Option<&NodeValue<State<T>>> {
         self.computation.get_node_value(node_id)
     }
+
+    /// Get the state of the fixpoint computation directly before the given instruction.
+    ///
+    /// For jump instructions this is the state at the end of the containing block.
+    /// For other instructions the state is recomputed from the state at the start of the containing block.
+    /// This function only yields results after the fixpoint has been computed.
+    pub fn get_state_at_tid(&self, tid: &Tid) -> Option<State<T>> {
+        use super::forward_interprocedural_fixpoint::Context as _;
+        let context = self.get_context();
+        let graph = self.get_graph();
+        for node in graph.node_indices() {
+            match graph[node] {
+                Node::BlkEnd(blk, _sub) if blk.term.jmps.iter().any(|jmp| jmp.tid == *tid) => {
+                    if let Some(NodeValue::Value(state)) = self.get_node_value(node) {
+                        return Some(state.clone());
+                    }
+                }
+                Node::BlkStart(blk, _sub) => {
+                    let Some(position) = blk.term.defs.iter().position(|def| def.tid == *tid)
+                    else {
+                        continue;
+                    };
+                    let Some(NodeValue::Value(node_state)) = self.get_node_value(node) else {
+                        continue;
+                    };
+                    let mut state = node_state.clone();
+                    for def in &blk.term.defs[..position] {
+                        state = context.update_def(&state, def)?;
+                    }
+                    return Some(state);
+                }
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Get the facts known about the string that the given parameter points to
+    /// directly before the given call.
+    ///
+    /// No facts are known if there is no state at the call
+    /// or if the parameter could not be evaluated.
+    pub fn get_string_predicates_at_call(
+        &self,
+        call_tid: &Tid,
+        parameter: &Arg,
+    ) -> PredicateDomain {
+        let Some(state) = self.get_state_at_tid(call_tid) else {
+            return PredicateDomain::default();
+        };
+        let Some(pi_state) = state.get_pointer_inference_state() else {
+            return PredicateDomain::default();
+        };
+        match pi_state
+            .eval_parameter_arg(parameter, &self.get_context().project.runtime_memory_image)
+        {
+            Ok(pointer) => state.get_string_predicates(&pointer, pi_state),
+            Err(_) => PredicateDomain::default(),
+        }
+    }
+}
+
+/// Return the `BlkEnd` nodes of the graph whose blocks end with a call to one of the given extern symbols.
+pub fn get_call_nodes_of_symbols(
+    project: &Project,
+    graph: &Graph,
+    symbol_names: &[String],
+) -> HashSet<NodeIndex> {
+    let symbol_tids: HashSet<_> = project
+        .program
+        .term
+        .extern_symbols
+        .values()
+        .filter(|symbol| symbol_names.contains(&symbol.name))
+        .map(|symbol| &symbol.tid)
+        .collect();
+    graph
+        .node_references()
+        .filter_map(|(node_id, node)| {
+            match node {
+            Node::BlkEnd(block, _)
+                if block.term.jmps.iter().any(|jmp| {
+                    matches!(&jmp.term, Jmp::Call { target, .. } if symbol_tids.contains(target))
+                }) =>
+            {
+                Some(node_id)
+            }
+            _ => None,
+        }
+        })
+        .collect()
+}
+
+/// Return all nodes of the graph from which at least one of the given sink nodes is reachable.
+/// The sink nodes themselves are contained in the result.
+pub fn get_backward_slice(graph: &Graph, sink_nodes: &HashSet<NodeIndex>) -> HashSet<NodeIndex> {
+    let mut slice = sink_nodes.clone();
+    let mut worklist: Vec<NodeIndex> = sink_nodes.iter().copied().collect();
+    while let Some(node) = worklist.pop() {
+        for predecessor in graph.neighbors_directed(node, Direction::Incoming) {
+            if slice.insert(predecessor) {
+                worklist.push(predecessor);
+            }
+        }
+    }
+
+    slice
 }
 
 /// Compute the string abstraction and return its results.
+///
+/// Returns an error if the entry point configuration is invalid.
 pub fn run<'a, T: AbstractDomain + HasTop + Eq + From<String> + DomainInsertion>(
     project: &'a Project,
     control_flow_graph: &'a Graph<'a>,
     pointer_inference: &'a PointerInferenceComputation<'a>,
     config: Config,
-) -> StringAbstraction<'a, T> {
+) -> Result<StringAbstraction<'a, T>, Error> {
     let mut string_abstraction =
-        StringAbstraction::new(project, control_flow_graph, pointer_inference, config);
+        StringAbstraction::new(project, control_flow_graph, pointer_inference, config)?;
 
     string_abstraction.compute();
 
-    string_abstraction
+    Ok(string_abstraction)
 }
 
 #[cfg(test)]
@@ -0,0 +1,264 @@
+//! A report of the abstract string arguments at calls to extern string functions.
+//!
+//! The report lists for each call to one of the given extern symbols
+//! the abstract values of the string arguments computed by the string abstraction.
+//! It is meant for reverse engineering, e.g. for finding out which commands may be passed to `system`.
+
+use super::context::Context;
+use super::state::State;
+use super::StringAbstraction;
+use crate::abstract_domain::{AbstractDomain, BricksDomain, TryToBitvec};
+use crate::analysis::graph::Edge;
+use crate::analysis::pointer_inference::{PointerInference, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use petgraph::visit::EdgeRef;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The abstract string arguments of a call to an extern function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct CallSiteStrings {
+    /// The TID of the call instruction.
+    pub call: Tid,
+    /// The name of the function containing the call.
+    pub caller: String,
+    /// The name of the called extern function.
+    pub callee: String,
+    /// The string arguments of the call.
+    pub arguments: Vec<StringArgument>,
+}
+
+/// The abstract value of a string argument.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StringArgument {
+    /// The register containing the pointer to the string.
+    pub register: String,
+    /// The abstract value of the string in the Bricks domain.
+    pub value: String,
+    /// The minimal length of the string.
+    pub min_length: u64,
+    /// The maximal length of the string or `None` if the length is unbounded.
+    pub max_length: Option<u64>,
+    /// The characters that may occur in the string or `None` if any character may occur.
+    pub characters: Option<BTreeSet<char>>,
+}
+
+impl StringArgument {
+    /// Create the argument summary from the abstract value of the string.
+    pub fn new(register: &str, value: &BricksDomain) -> StringArgument {
+        let (min_length, max_length, characters) = match value {
+            BricksDomain::Top => (0, None, None),
+            BricksDomain::Value(bricks) => {
+                let mut min_length = 0;
+                let mut max_length = Some(0);
+                let mut characters = Some(BTreeSet::new());
+                for brick in bricks.iter() {
+                    let Some(brick) = brick.as_value() else {
+                        max_length = None;
+                        characters = None;
+                        continue;
+                    };
+                    let lengths = brick
+                        .get_sequence()
+                        .iter()
+                        .map(|s| s.chars().count() as u64);
+                    min_length += lengths.clone().min().unwrap_or(0) * u64::from(brick.get_min());
+                    max_length = max_length
+                        .map(|max| max + lengths.max().unwrap_or(0) * u64::from(brick.get_max()));
+                    if let Some(characters) = characters.as_mut() {
+                        characters.extend(brick.get_sequence().iter().flat_map(|s| s.chars()));
+                    }
+                }
+                (min_length, max_length, characters)
+            }
+        };
+        StringArgument {
+            register: register.to_string(),
+            value: value.to_string(),
+            min_length,
+            max_length,
+            characters,
+        }
+    }
+
+    /// Restrict the maximal length of the string to the given bound,
+    /// e.g. a length bound learned from a length check or from the size of an input buffer.
+    pub fn bound_max_length(&mut self, bound: u64) {
+        self.max_length = Some(match self.max_length {
+            Some(max_length) => std::cmp::min(max_length, bound),
+            None => bound,
+        });
+    }
+}
+
+impl fmt::Display for CallSiteStrings {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            formatter,
+            "{} ({}) {} -> {}",
+            self.call.address(),
+            self.call,
+            self.caller,
+            self.callee
+        )?;
+        for argument in self.arguments.iter() {
+            let max_length = match argument.max_length {
+                Some(max_length) => max_length.to_string(),
+                None => "inf".to_string(),
+            };
+            let characters = match &argument.characters {
+                Some(characters) => format!("{:?}", characters.iter().collect::<String>()),
+                None => "any".to_string(),
+            };
+            writeln!(
+                formatter,
+                "  {}: {}, length {}..{}, characters {}",
+                argument.register, argument.value, argument.min_length, max_length, characters
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the abstract string arguments of all calls to the extern symbols with the given names.
+///
+/// Only arguments passed in registers that point to strings tracked by the string abstraction are reported.
+/// The call sites are sorted by the TIDs of the calls.
+pub fn get_call_site_strings(
+    string_abstraction: &StringAbstraction<BricksDomain>,
+    pointer_inference: &PointerInference,
+    symbol_names: &[String],
+) -> Vec<CallSiteStrings> {
+    let project = string_abstraction.get_context().project;
+    let graph = string_abstraction.get_graph();
+    let mut call_sites = Vec::new();
+    for edge in graph.edge_references() {
+        let Edge::ExternCallStub(jmp) = edge.weight() else {
+            continue;
+        };
+        let Jmp::Call { target, .. } = &jmp.term else {
+            continue;
+        };
+        let Some(symbol) = project.program.term.extern_symbols.get(target) else {
+            continue;
+        };
+        if !symbol_names.contains(&symbol.name) {
+            continue;
+        }
+        let (Some(state), Some(pi_state)) = (
+            string_abstraction.get_node_value(edge.source()),
+            pointer_inference.get_node_value(edge.source()),
+        ) else {
+            continue;
+        };
+        let state = state.unwrap_value();
+        let arguments = symbol
+            .parameters
+            .iter()
+            .filter_map(|parameter| match parameter {
+                Arg::Register {
+                    expr: Expression::Var(var),
+                    ..
+                } => {
+                    let pi_state = pi_state.unwrap_value();
+                    let value = get_string_value(state, pi_state, var, project)?;
+                    let mut argument = StringArgument::new(&var.name, &value);
+                    let pointer = &state.get_variable_to_pointer_map()[var];
+                    if let Some(bound) = state
+                        .get_string_predicates(pointer, pi_state)
+                        .get_length_bound()
+                    {
+                        argument.bound_max_length(bound);
+                    }
+                    Some(argument)
+                }
+                _ => None,
+            })
+            .collect();
+        call_sites.push(CallSiteStrings {
+            call: jmp.tid.clone(),
+            caller: state
+                .get_current_sub()
+                .map(|sub| sub.term.name.clone())
+                .unwrap_or_default(),
+            callee: symbol.name.clone(),
+            arguments,
+        });
+    }
+    call_sites.sort_by(|a, b| a.call.cmp(&b.call));
+
+    call_sites
+}
+
+/// Get the abstract value of the string that the variable points to.
+///
+/// Returns `None` if the variable does not point to a tracked string.
+fn get_string_value(
+    state: &State<BricksDomain>,
+    pi_state: &PointerInferenceState,
+    var: &Variable,
+    project: &Project,
+) -> Option<BricksDomain> {
+    let pointer = state.get_variable_to_pointer_map().get(var)?;
+    let mut value: Option<BricksDomain> = None;
+    if !pointer.get_relative_values().is_empty() {
+        value = Some(
+            Context::<BricksDomain>::merge_domains_from_multiple_pointer_targets(
+                state,
+                pi_state,
+                pointer.get_relative_values(),
+            ),
+        );
+    }
+    if let Some(address) = pointer.get_absolute_value() {
+        let global_string = address
+            .try_to_bitvec()
+            .ok()
+            .and_then(|address| {
+                project
+                    .runtime_memory_image
+                    .read_string_until_null_terminator(&address)
+                    .ok()
+            })
+            .map(|string| BricksDomain::from(string.to_string()))
+            .unwrap_or(BricksDomain::Top);
+        value = Some(match value {
+            Some(value) => value.merge(&global_string),
+            None => global_string,
+        });
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::BrickDomain;
+
+    #[test]
+    fn string_argument_summary() {
+        let value =
+            BricksDomain::Value(vec![BrickDomain::new("ls ".to_string()), BrickDomain::Top]);
+        let argument = StringArgument::new("RDI", &value);
+        assert_eq!(argument.min_length, 3);
+        assert_eq!(argument.max_length, None);
+        assert_eq!(argument.characters, None);
+
+        let argument = StringArgument::new("RDI", &BricksDomain::from("abca".to_string()));
+        assert_eq!(argument.min_length, 4);
+        assert_eq!(argument.max_length, Some(4));
+        assert_eq!(
+            argument.characters,
+            Some(['a', 'b', 'c'].into_iter().collect())
+        );
+
+        let mut argument = StringArgument::new("RSI", &BricksDomain::Top);
+        assert_eq!(argument.min_length, 0);
+        assert_eq!(argument.max_length, None);
+        argument.bound_max_length(64);
+        assert_eq!(argument.max_length, Some(64));
+        argument.bound_max_length(128);
+        assert_eq!(argument.max_length, Some(64));
+    }
+}
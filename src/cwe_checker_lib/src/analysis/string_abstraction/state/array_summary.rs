@@ -0,0 +1,171 @@
+//! Summarization of arrays of string pointers that are accessed with unknown indices.
+//!
+//! Tables of format strings or command fragments are often indexed by a variable,
+//! e.g. `system(commands[i])`.
+//! Since the accessed element is unknown,
+//! all elements of the array that the index may refer to are summarized into one element,
+//! i.e. the loaded value points to the merge of the strings of all these elements.
+//! Stores to an unknown index are weak updates of all tracked elements in the accessed range.
+//!
+//! Arrays on the stack and arrays in global read-only memory are summarized.
+
+use super::State;
+use crate::abstract_domain::{
+    AbstractDomain, AbstractIdentifier, AbstractLocation, DataDomain, DomainInsertion, HasTop,
+    Interval, IntervalDomain, SizedDomain, TryToBitvec, TryToInterval,
+};
+use crate::intermediate_representation::{RuntimeMemoryImage, Variable};
+use crate::prelude::*;
+
+/// The maximal number of elements of an array that are summarized.
+///
+/// Accesses to larger address ranges are not modeled.
+pub const MAX_ARRAY_SUMMARY_ELEMENTS: u64 = 64;
+
+impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T> {
+    /// Handle a load of a value from an array of string pointers with an unknown index.
+    ///
+    /// If the address of the load may point to several elements of a tracked stack array
+    /// or of an array of string literal pointers in global read-only memory,
+    /// the output variable points to the summary of all these elements afterwards.
+    /// Strings of global elements are merged into a summary string
+    /// whose abstract ID consists of the TID of the load and the output variable.
+    ///
+    /// Loads with exactly known addresses are ignored.
+    pub fn handle_array_load(
+        &mut self,
+        load_tid: &Tid,
+        output: &Variable,
+        address: &DataDomain<IntervalDomain>,
+        runtime_memory_image: &RuntimeMemoryImage,
+    ) {
+        if self.get_variable_to_pointer_map().contains_key(output) {
+            return;
+        }
+        let Some(elements) =
+            self.collect_array_elements(address, output.size, runtime_memory_image)
+        else {
+            return;
+        };
+        let mut summary_pointer: Option<DataDomain<IntervalDomain>> = None;
+        let mut summary_string: Option<T> = None;
+        for element in elements {
+            let mut relative_pointer = element.clone();
+            relative_pointer.set_absolute_value(None);
+            if !relative_pointer.get_relative_values().is_empty() {
+                summary_pointer = Some(match summary_pointer {
+                    Some(pointer) => pointer.merge(&relative_pointer),
+                    None => relative_pointer,
+                });
+            }
+            if let Some(address) = element.get_absolute_value() {
+                let string = address
+                    .try_to_bitvec()
+                    .ok()
+                    .and_then(|address| runtime_memory_image.read_string_literal(&address).ok())
+                    .map(|string| T::from(string.to_string()))
+                    .unwrap_or_else(T::create_top_value_domain);
+                summary_string = Some(match summary_string {
+                    Some(summary) => summary.merge(&string),
+                    None => string,
+                });
+            }
+        }
+        if let Some(string) = summary_string {
+            let Ok(location) = AbstractLocation::from_var(output) else {
+                return;
+            };
+            let summary_id = AbstractIdentifier::new(load_tid.clone(), location);
+            let pointer_to_summary = DataDomain::from_target(
+                summary_id.clone(),
+                Bitvector::zero(output.size.into()).into(),
+            );
+            self.add_new_heap_to_string_entry(summary_id, Some(0), string);
+            summary_pointer = Some(match summary_pointer {
+                Some(pointer) => pointer.merge(&pointer_to_summary),
+                None => pointer_to_summary,
+            });
+        }
+        if let Some(pointer) = summary_pointer {
+            self.add_new_variable_to_pointer_entry(output.clone(), pointer);
+        }
+    }
+
+    /// Merge the given string pointer into all tracked elements of a stack array
+    /// that the target offset interval may refer to.
+    pub fn weak_update_stack_array_elements(
+        &mut self,
+        offsets: &IntervalDomain,
+        string_pointer: &DataDomain<IntervalDomain>,
+    ) {
+        let Some(offsets) = get_element_offsets(offsets, string_pointer.bytesize()) else {
+            return;
+        };
+        for offset in offsets {
+            if let Some(element) = self.stack_offset_to_pointer_map.get_mut(&offset) {
+                *element = element.merge(string_pointer);
+            }
+        }
+    }
+
+    /// Collect the elements of the arrays that the given address may point to.
+    ///
+    /// Returns `None` if the address points to a unique element,
+    /// if it does not point to a tracked stack array or an array of string pointers in global memory,
+    /// or if it may point to too many elements.
+    fn collect_array_elements(
+        &self,
+        address: &DataDomain<IntervalDomain>,
+        element_size: ByteSize,
+        runtime_memory_image: &RuntimeMemoryImage,
+    ) -> Option<Vec<DataDomain<IntervalDomain>>> {
+        let pi_state = self.get_pointer_inference_state()?;
+        let mut elements = Vec::new();
+        for (target, offsets) in address.get_relative_values() {
+            if !State::<T>::is_stack_pointer(pi_state, target) || offsets.try_to_offset().is_ok() {
+                return None;
+            }
+            for offset in get_element_offsets(offsets, element_size)? {
+                if let Some(element) = self.stack_offset_to_pointer_map.get(&offset) {
+                    elements.push(element.clone());
+                }
+            }
+        }
+        if let Some(addresses) = address.get_absolute_value() {
+            if addresses.try_to_bitvec().is_ok() {
+                return None;
+            }
+            for element_address in get_element_offsets(addresses, element_size)? {
+                let element_address =
+                    Bitvector::from_i64(element_address).into_resize_unsigned(addresses.bytesize());
+                let element = runtime_memory_image
+                    .read(&element_address, element_size)
+                    .ok()??;
+                runtime_memory_image.read_string_literal(&element).ok()?;
+                elements.push(DataDomain::from(element));
+            }
+        }
+
+        (!elements.is_empty()).then_some(elements)
+    }
+}
+
+/// Get the offsets of all array elements of the given size contained in the interval.
+///
+/// The offsets are spaced by the stride of the interval
+/// or by the element size if the stride is smaller than the element size.
+/// Returns `None` if the interval contains more than [`MAX_ARRAY_SUMMARY_ELEMENTS`] elements.
+fn get_element_offsets(interval: &IntervalDomain, element_size: ByteSize) -> Option<Vec<i64>> {
+    let Interval { start, end, stride } = interval.try_to_interval().ok()?;
+    let (start, end) = (start.try_to_i64().ok()?, end.try_to_i64().ok()?);
+    let step = std::cmp::max(stride, u64::from(element_size));
+    let count = u64::try_from(end.checked_sub(start)?).ok()? / step + 1;
+    if count > MAX_ARRAY_SUMMARY_ELEMENTS {
+        return None;
+    }
+    Some(
+        (0..count)
+            .map(|index| start + (index * step) as i64)
+            .collect(),
+    )
+}
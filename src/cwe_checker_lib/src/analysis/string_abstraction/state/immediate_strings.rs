@@ -0,0 +1,314 @@
+//! Strings that are built on the stack by stores of immediate values.
+//!
+//! Compilers often inline short string literals as multi-byte immediates,
+//! e.g. `"/bin/sh"` is written to a stack buffer by a 4-byte store of `"/bin"`
+//! followed by a 4-byte store of `"/sh\0"`.
+//! The bytes of such stores are decoded according to the endianness of the binary.
+//! A store to the offset directly behind an unterminated byte sequence that was built this way
+//! appends its bytes to the sequence.
+//! Byte sequences that consist of printable characters followed by NUL bytes are strings.
+//!
+//! Obfuscated strings are stored encoded and decoded in place by a
+//! [decode loop](crate::analysis::loop_idioms::LoopIdiom::Decode) before they are used.
+//! If the key of the decode loop is constant, the decoded string is computed from the
+//! stored bytes.
+
+use super::State;
+use crate::abstract_domain::{
+    AbstractDomain, CharacterAlphabet, DataDomain, DomainInsertion, HasTop, IntervalDomain,
+    TryToBitvec,
+};
+use crate::intermediate_representation::{BinOpType, BitvectorExtended, Expression};
+use crate::prelude::*;
+
+/// The minimal number of non-NUL bytes of an immediate value that starts a new byte sequence.
+///
+/// Values with fewer bytes are more likely to be integers than strings.
+const MIN_IMMEDIATE_STRING_LENGTH: usize = 2;
+
+impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T> {
+    /// Add the bytes of an immediate value that is stored on the stack
+    /// to the byte sequence at the target address.
+    ///
+    /// If the target address is the end of an unterminated byte sequence built by previous
+    /// immediate stores, the bytes are appended to this sequence.
+    /// Otherwise, a new sequence starts at the target address if the value contains
+    /// enough non-NUL bytes.
+    /// If the resulting sequence is a string, it is added to the stack string map.
+    pub fn add_immediate_characters_to_stack_string(
+        &mut self,
+        target_address: &Expression,
+        constant: &Bitvector,
+        is_little_endian: bool,
+    ) {
+        let Some(offset) = self.get_exact_stack_offset(target_address) else {
+            return;
+        };
+        let Some(bytes) = to_bytes(constant, is_little_endian) else {
+            return;
+        };
+        if self.overwrite_immediate_sequences(offset, &bytes) {
+            return;
+        }
+        let continued_sequence_start = self
+            .stack_offset_to_immediate_bytes_map
+            .iter()
+            .find(|(start, sequence)| {
+                **start + sequence.len() as i64 == offset
+                    && sequence.last() != Some(&0)
+                    && self.is_unchanged_immediate_sequence(**start, sequence)
+            })
+            .map(|(start, _)| *start);
+        let (start, mut sequence) = match continued_sequence_start {
+            Some(start) => {
+                let sequence = self.stack_offset_to_immediate_bytes_map.remove(&start);
+                (start, sequence.unwrap())
+            }
+            None if bytes.iter().filter(|byte| **byte != 0).count()
+                >= MIN_IMMEDIATE_STRING_LENGTH =>
+            {
+                (offset, Vec::new())
+            }
+            None => return,
+        };
+        sequence.extend(bytes);
+        self.set_immediate_sequence(start, sequence);
+    }
+
+    /// Decode the elements of the stack buffer at the given offset in place
+    /// by combining each element with the key.
+    ///
+    /// The elements are decoded until `element_count` elements were decoded
+    /// or, if the count is unknown, until an element consisting of NUL bytes is reached.
+    /// Decoding stops at the end of the byte sequence built by immediate stores that contains
+    /// the offset.
+    /// If no such sequence is known, a string tracked at the offset is set to *Top*.
+    pub fn decode_immediate_sequence(
+        &mut self,
+        offset: i64,
+        operation: BinOpType,
+        key: &Bitvector,
+        element_count: Option<u64>,
+        is_little_endian: bool,
+    ) {
+        let containing_sequence = self
+            .stack_offset_to_immediate_bytes_map
+            .iter()
+            .find(|(start, sequence)| {
+                (**start..**start + sequence.len() as i64).contains(&offset)
+                    && self.is_unchanged_immediate_sequence(**start, sequence)
+            })
+            .map(|(start, sequence)| (*start, sequence.clone()));
+        let Some((start, mut sequence)) = containing_sequence else {
+            if self.stack_offset_to_string_map.contains_key(&offset) {
+                self.add_new_stack_offset_to_string_entry(offset, T::create_top_value_domain());
+            }
+            return;
+        };
+        let step = u64::from(key.bytesize()) as usize;
+        let elements = sequence[(offset - start) as usize..].chunks_exact_mut(step);
+        for (index, element) in elements.enumerate() {
+            let is_last_element = match element_count {
+                Some(count) => index as u64 >= count,
+                None => element.iter().all(|byte| *byte == 0),
+            };
+            if is_last_element {
+                break;
+            }
+            let decoded_element = from_bytes(element, is_little_endian)
+                .and_then(|encoded| encoded.bin_op(operation, key).ok())
+                .and_then(|decoded| to_bytes(&decoded, is_little_endian));
+            let Some(decoded_element) = decoded_element else {
+                return;
+            };
+            element.copy_from_slice(&decoded_element);
+        }
+        self.set_immediate_sequence(start, sequence);
+    }
+
+    /// Overwrite the bytes of the byte sequences that overlap with the bytes written at the offset.
+    /// Bytes written directly behind the end of an overlapping sequence extend the sequence.
+    ///
+    /// Returns whether a sequence contains the offset,
+    /// i.e. whether the written bytes do not start a new sequence.
+    fn overwrite_immediate_sequences(&mut self, offset: i64, bytes: &[u8]) -> bool {
+        let end = offset + bytes.len() as i64;
+        let overwritten_sequence_starts: Vec<i64> = self
+            .stack_offset_to_immediate_bytes_map
+            .iter()
+            .filter(|(start, sequence)| {
+                **start < end
+                    && offset < **start + sequence.len() as i64
+                    && self.is_unchanged_immediate_sequence(**start, sequence)
+            })
+            .map(|(start, _)| *start)
+            .collect();
+        let mut contains_offset = false;
+        for start in overwritten_sequence_starts {
+            contains_offset |= start <= offset;
+            let mut sequence = self
+                .stack_offset_to_immediate_bytes_map
+                .remove(&start)
+                .unwrap();
+            for (position, byte) in (offset - start..).zip(bytes) {
+                match usize::try_from(position) {
+                    Ok(position) if position < sequence.len() => sequence[position] = *byte,
+                    Ok(_) => sequence.push(*byte),
+                    Err(_) => (),
+                }
+            }
+            self.set_immediate_sequence(start, sequence);
+        }
+        contains_offset
+    }
+
+    /// Set the byte sequence starting at the given stack offset.
+    ///
+    /// If the sequence is a string, it is added to the stack string map.
+    /// Otherwise a string tracked at the offset is set to *Top*.
+    fn set_immediate_sequence(&mut self, start: i64, sequence: Vec<u8>) {
+        match to_string_domain(&sequence) {
+            Some(string_domain) => self.add_new_stack_offset_to_string_entry(start, string_domain),
+            None if self.stack_offset_to_string_map.contains_key(&start) => {
+                self.add_new_stack_offset_to_string_entry(start, T::create_top_value_domain())
+            }
+            None => (),
+        }
+        self.stack_offset_to_immediate_bytes_map
+            .insert(start, sequence);
+    }
+
+    /// Check that the stack string at the start of the byte sequence
+    /// was not overwritten since the last immediate store to the sequence.
+    fn is_unchanged_immediate_sequence(&self, start: i64, sequence: &[u8]) -> bool {
+        match (
+            to_string_domain::<T>(sequence),
+            self.stack_offset_to_string_map.get(&start),
+        ) {
+            (Some(string_domain), Some(tracked_string_domain)) => {
+                string_domain == *tracked_string_domain
+            }
+            (None, tracked_string_domain) => tracked_string_domain.is_none_or(T::is_top),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Get the stack offset that the address points to
+    /// if it is exactly known and the address cannot point anywhere else.
+    pub fn get_exact_stack_offset(&self, address: &Expression) -> Option<i64> {
+        let pi_state = self.get_pointer_inference_state()?;
+        let pointer: DataDomain<IntervalDomain> = pi_state.eval(address);
+        if pointer.get_absolute_value().is_some() || pointer.contains_top() {
+            return None;
+        }
+        match pointer.get_relative_values().iter().next() {
+            Some((target, offset))
+                if pointer.get_relative_values().len() == 1
+                    && State::<T>::is_stack_pointer(pi_state, target) =>
+            {
+                offset.try_to_offset().ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Get the bytes of the constant in memory order.
+fn to_bytes(constant: &Bitvector, is_little_endian: bool) -> Option<Vec<u8>> {
+    let size = u64::from(constant.bytesize());
+    let bytes: Option<Vec<u8>> = (0..size)
+        .map(|index| {
+            constant
+                .subpiece(ByteSize::new(index), ByteSize::new(1))
+                .try_to_u8()
+                .ok()
+        })
+        .collect();
+    let mut bytes = bytes?;
+    if !is_little_endian {
+        bytes.reverse();
+    }
+    Some(bytes)
+}
+
+/// Get the constant represented by the bytes in memory order.
+fn from_bytes(bytes: &[u8], is_little_endian: bool) -> Option<Bitvector> {
+    let mut bytes = bytes.to_vec();
+    if is_little_endian {
+        bytes.reverse();
+    }
+    let mut bytes = bytes.into_iter();
+    let mut bitvector = Bitvector::from_u8(bytes.next()?);
+    for byte in bytes {
+        bitvector = bitvector
+            .bin_op(BinOpType::Piece, &Bitvector::from_u8(byte))
+            .ok()?;
+    }
+    Some(bitvector)
+}
+
+/// Get the string domain of the byte sequence if it is a string,
+/// i.e. if it consists of printable ASCII characters followed by NUL bytes.
+/// Immediate values are always decoded as ASCII,
+/// since they rarely contain the bytes of multi-byte characters.
+///
+/// Strings without a terminating NUL byte may continue with unknown characters.
+fn to_string_domain<T: DomainInsertion + From<String>>(sequence: &[u8]) -> Option<T> {
+    let length = sequence
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(sequence.len());
+    let (string, terminator) = sequence.split_at(length);
+    if CharacterAlphabet::AsciiPrintable.is_probably_binary_data(string)
+        || terminator.iter().any(|byte| *byte != 0)
+    {
+        return None;
+    }
+    let string_domain = T::from(String::from_utf8_lossy(string).into_owned());
+    if terminator.is_empty() {
+        Some(string_domain.append_string_domain(&T::create_top_value_domain()))
+    } else {
+        Some(string_domain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::BricksDomain;
+
+    #[test]
+    fn byte_conversion() {
+        let constant = Bitvector::from_u32(0x6e69622f);
+        assert_eq!(to_bytes(&constant, true).unwrap(), b"/bin");
+        assert_eq!(to_bytes(&constant, false).unwrap(), b"nib/");
+        assert_eq!(from_bytes(b"/bin", true).unwrap(), constant);
+        assert_eq!(from_bytes(b"nib/", false).unwrap(), constant);
+        assert_eq!(
+            to_bytes(&Bitvector::from_u16(0x6873), false).unwrap(),
+            b"hs"
+        );
+    }
+
+    #[test]
+    fn string_domains() {
+        assert_eq!(
+            to_string_domain::<BricksDomain>(b"/bin/sh\0"),
+            Some(BricksDomain::from("/bin/sh".to_string()))
+        );
+        assert_eq!(
+            to_string_domain::<BricksDomain>(b"sh\0\0"),
+            Some(BricksDomain::from("sh".to_string()))
+        );
+        assert_eq!(
+            to_string_domain::<BricksDomain>(b"/bin"),
+            Some(
+                BricksDomain::from("/bin".to_string())
+                    .append_string_domain(&BricksDomain::create_top_value_domain())
+            )
+        );
+        // Characters after the NUL byte and unprintable bytes are not strings.
+        assert_eq!(to_string_domain::<BricksDomain>(b"AA\0A"), None);
+        assert_eq!(to_string_domain::<BricksDomain>(b"\x01AAA"), None);
+    }
+}
@@ -2,13 +2,16 @@
 //! the String Abstraction analysis.
 //! Its content changes until a fixpoint is reached.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::sync::Arc;
 
 use itertools::Itertools;
 use petgraph::graph::NodeIndex;
 
-use crate::abstract_domain::{DataDomain, DomainInsertion, HasTop, TryToBitvec};
+use crate::abstract_domain::{
+    CharacterAlphabet, DataDomain, DomainInsertion, DomainOrder, HasTop, PredicateDomain,
+    TryToBitvec,
+};
 use crate::intermediate_representation::{ExternSymbol, Project, RuntimeMemoryImage};
 use crate::{abstract_domain::IntervalDomain, prelude::*};
 use crate::{
@@ -18,6 +21,12 @@ use crate::{
     intermediate_representation::{Expression, Sub, Variable},
 };
 
+mod array_summary;
+pub use array_summary::MAX_ARRAY_SUMMARY_ELEMENTS;
+mod immediate_strings;
+mod object_fields;
+pub use object_fields::{ObjectFields, MAX_FIELDS_PER_OBJECT};
+
 /// Contains all information known about the state of a program at a specific point of time.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct State<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> {
@@ -32,10 +41,26 @@ pub struct State<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String
     /// Tracks strings that lie directly on the stack.
     /// Maps the stack offset to the abstract string domain.
     stack_offset_to_string_map: HashMap<i64, T>,
-    /// Maps the heap abstract identifier of an memory object to the corresponding string abstract domain
-    /// representing its content.
-    /// For simplicity reasons it is assumed that a heap object only represents one string at offset 0.
-    heap_to_string_map: HashMap<AbstractIdentifier, T>,
+    /// Maps the stack offsets of byte sequences that were built by stores of immediate values
+    /// to the bytes stored so far, e.g. the characters of a string including its terminating NUL byte.
+    stack_offset_to_immediate_bytes_map: HashMap<i64, Vec<u8>>,
+    /// Maps the heap abstract identifier of an memory object to the abstract string domains
+    /// of the strings contained in its fields, e.g. the strings of the members of a struct.
+    heap_to_string_map: HashMap<AbstractIdentifier, ObjectFields<T>>,
+    /// Maps the return registers of string comparison calls to the compared pointers
+    /// and the string domains they are compared to.
+    /// Used to refine the string domains on branches where the comparison succeeded.
+    string_comparison_map: HashMap<Variable, Vec<(DataDomain<IntervalDomain>, T)>>,
+    /// Maps the return registers of `strlen` calls to the pointers to the measured strings.
+    /// Used to learn length bounds of strings on branches where the length was checked against a constant.
+    string_length_map: HashMap<Variable, DataDomain<IntervalDomain>>,
+    /// Maps stack offsets to the facts known about the strings at these offsets,
+    /// e.g. whether they were passed through a sanitizer function.
+    stack_offset_to_predicate_map: HashMap<i64, PredicateDomain>,
+    /// Maps the heap abstract identifiers of memory objects and the offsets of their fields
+    /// to the facts known about the strings they contain.
+    /// Fields of collapsed memory objects have no known facts.
+    heap_to_predicate_map: HashMap<(AbstractIdentifier, i64), PredicateDomain>,
     /// Holds the currently analyzed subroutine term
     current_sub: Arc<Option<Term<Sub>>>,
     /// The state of the pointer inference analysis.
@@ -49,7 +74,109 @@ pub struct State<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String
 
 impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> AbstractDomain for State<T> {
     /// Merges two states.
+    /// The cardinality of the merged string domains is not limited.
     fn merge(&self, other: &Self) -> Self {
+        self.merge_with_cardinality_limit(other, usize::MAX)
+    }
+
+    /// The state has no explicit Top element.
+    fn is_top(&self) -> bool {
+        false
+    }
+}
+
+impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> DomainOrder for State<T> {
+    /// Compare two states of the same function.
+    ///
+    /// Pointers of `self` have to be contained in `other` and
+    /// facts known in `other` have to be known in `self`.
+    /// String domains are only compared if both states track strings at the same locations.
+    /// The pointer inference state is ignored, since it is also ignored when merging.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self == other
+            || (self
+                .unassigned_return_pointer
+                .is_subset(&other.unassigned_return_pointer)
+                && pointers_less_or_equal(
+                    &self.variable_to_pointer_map,
+                    &other.variable_to_pointer_map,
+                )
+                && pointers_less_or_equal(
+                    &self.stack_offset_to_pointer_map,
+                    &other.stack_offset_to_pointer_map,
+                )
+                && same_keys_less_or_equal(
+                    &self.stack_offset_to_string_map,
+                    &other.stack_offset_to_string_map,
+                )
+                && same_keys_less_or_equal(&self.heap_to_string_map, &other.heap_to_string_map)
+                && other
+                    .stack_offset_to_immediate_bytes_map
+                    .iter()
+                    .all(|(offset, bytes)| {
+                        self.stack_offset_to_immediate_bytes_map.get(offset) == Some(bytes)
+                    })
+                && other
+                    .string_comparison_map
+                    .iter()
+                    .all(|(var, comparisons)| {
+                        self.string_comparison_map.get(var) == Some(comparisons)
+                    })
+                && other
+                    .string_length_map
+                    .iter()
+                    .all(|(var, pointer)| self.string_length_map.get(var) == Some(pointer))
+                && predicates_less_or_equal(
+                    &self.stack_offset_to_predicate_map,
+                    &other.stack_offset_to_predicate_map,
+                )
+                && predicates_less_or_equal(
+                    &self.heap_to_predicate_map,
+                    &other.heap_to_predicate_map,
+                )
+                && self.current_sub == other.current_sub)
+    }
+}
+
+impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T> {
+    /// Creates a new state.
+    pub fn new(
+        node_index: NodeIndex,
+        pointer_inference_results: &PointerInferenceComputation,
+    ) -> State<T> {
+        let mut pi_state: Option<PointerInferenceState> = None;
+        if let Some(pi_node) = pointer_inference_results.get_node_value(node_index) {
+            pi_state = Some(pi_node.unwrap_value().clone());
+        }
+
+        let mut current_sub = None;
+        if let Some(node) = pointer_inference_results
+            .get_graph()
+            .node_weight(node_index)
+        {
+            current_sub = Some(node.get_sub().clone());
+        }
+
+        State {
+            unassigned_return_pointer: HashSet::new(),
+            variable_to_pointer_map: HashMap::new(),
+            stack_offset_to_pointer_map: HashMap::new(),
+            stack_offset_to_string_map: HashMap::new(),
+            stack_offset_to_immediate_bytes_map: HashMap::new(),
+            heap_to_string_map: HashMap::new(),
+            string_comparison_map: HashMap::new(),
+            string_length_map: HashMap::new(),
+            stack_offset_to_predicate_map: HashMap::new(),
+            heap_to_predicate_map: HashMap::new(),
+            current_sub: Arc::new(current_sub),
+            pointer_inference_state: pi_state,
+        }
+    }
+
+    /// Merges two states.
+    /// Parts of the merged string domains that would represent more than `max_cardinality`
+    /// different strings are overapproximated by *Top*.
+    pub fn merge_with_cardinality_limit(&self, other: &Self, max_cardinality: usize) -> Self {
         let unassigned_return_pointer = self
             .unassigned_return_pointer
             .union(&other.unassigned_return_pointer)
@@ -80,8 +207,11 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> AbstractD
 
         for (offset, other_string_domain) in other.stack_offset_to_string_map.iter() {
             if let Some(string_domain) = self.stack_offset_to_string_map.get(offset) {
-                stack_offset_to_string_map
-                    .insert(*offset, string_domain.merge(other_string_domain));
+                stack_offset_to_string_map.insert(
+                    *offset,
+                    string_domain
+                        .merge_with_cardinality_limit(other_string_domain, max_cardinality),
+                );
             } else {
                 stack_offset_to_string_map.insert(*offset, T::create_top_value_domain());
             }
@@ -89,20 +219,67 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> AbstractD
 
         let mut heap_to_string_map = self.heap_to_string_map.clone();
 
-        for (id, other_string_domain) in other.heap_to_string_map.iter() {
-            if let Some(string_domain) = self.heap_to_string_map.get(id) {
-                heap_to_string_map.insert(id.clone(), string_domain.merge(other_string_domain));
+        for (id, other_object) in other.heap_to_string_map.iter() {
+            if let Some(object) = self.heap_to_string_map.get(id) {
+                heap_to_string_map.insert(
+                    id.clone(),
+                    object.merge_with(
+                        other_object,
+                        |string_domain, other_string_domain| {
+                            string_domain
+                                .merge_with_cardinality_limit(other_string_domain, max_cardinality)
+                        },
+                        T::create_top_value_domain(),
+                    ),
+                );
             } else {
-                heap_to_string_map.insert(id.clone(), T::create_top_value_domain());
+                heap_to_string_map.insert(
+                    id.clone(),
+                    other_object.with_all_values(T::create_top_value_domain()),
+                );
             }
         }
 
+        let string_comparison_map = self
+            .string_comparison_map
+            .iter()
+            .filter(|(var, comparisons)| other.string_comparison_map.get(var) == Some(comparisons))
+            .map(|(var, comparisons)| (var.clone(), comparisons.clone()))
+            .collect();
+
+        let string_length_map = self
+            .string_length_map
+            .iter()
+            .filter(|(var, pointer)| other.string_length_map.get(var) == Some(pointer))
+            .map(|(var, pointer)| (var.clone(), pointer.clone()))
+            .collect();
+
+        let stack_offset_to_immediate_bytes_map = self
+            .stack_offset_to_immediate_bytes_map
+            .iter()
+            .filter(|(offset, bytes)| {
+                other.stack_offset_to_immediate_bytes_map.get(offset) == Some(bytes)
+            })
+            .map(|(offset, bytes)| (*offset, bytes.clone()))
+            .collect();
+
         let mut new_state = State {
             unassigned_return_pointer,
             variable_to_pointer_map,
             stack_offset_to_pointer_map,
             stack_offset_to_string_map,
+            stack_offset_to_immediate_bytes_map,
             heap_to_string_map,
+            string_comparison_map,
+            string_length_map,
+            stack_offset_to_predicate_map: merge_predicate_maps(
+                &self.stack_offset_to_predicate_map,
+                &other.stack_offset_to_predicate_map,
+            ),
+            heap_to_predicate_map: merge_predicate_maps(
+                &self.heap_to_predicate_map,
+                &other.heap_to_predicate_map,
+            ),
             current_sub: self.current_sub.clone(),
             pointer_inference_state: self.pointer_inference_state.clone(),
         };
@@ -112,49 +289,18 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> AbstractD
         new_state
     }
 
-    /// The state has no explicit Top element.
-    fn is_top(&self) -> bool {
-        false
-    }
-}
-
-impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T> {
-    /// Creates a new state.
-    pub fn new(
-        node_index: NodeIndex,
-        pointer_inference_results: &PointerInferenceComputation,
-    ) -> State<T> {
-        let mut pi_state: Option<PointerInferenceState> = None;
-        if let Some(pi_node) = pointer_inference_results.get_node_value(node_index) {
-            pi_state = Some(pi_node.unwrap_value().clone());
-        }
-
-        let mut current_sub = None;
-        if let Some(node) = pointer_inference_results
-            .get_graph()
-            .node_weight(node_index)
-        {
-            current_sub = Some(node.get_sub().clone());
-        }
-
-        State {
-            unassigned_return_pointer: HashSet::new(),
-            variable_to_pointer_map: HashMap::new(),
-            stack_offset_to_pointer_map: HashMap::new(),
-            stack_offset_to_string_map: HashMap::new(),
-            heap_to_string_map: HashMap::new(),
-            current_sub: Arc::new(current_sub),
-            pointer_inference_state: pi_state,
-        }
-    }
-
     /// Removes all entries from the string maps.
     pub fn set_all_maps_empty(&mut self) {
         self.unassigned_return_pointer = HashSet::new();
         self.heap_to_string_map = HashMap::new();
         self.stack_offset_to_pointer_map = HashMap::new();
         self.stack_offset_to_string_map = HashMap::new();
+        self.stack_offset_to_immediate_bytes_map = HashMap::new();
         self.variable_to_pointer_map = HashMap::new();
+        self.string_comparison_map = HashMap::new();
+        self.string_length_map = HashMap::new();
+        self.stack_offset_to_predicate_map = HashMap::new();
+        self.heap_to_predicate_map = HashMap::new();
     }
 
     /// Adds a return pointer to the unassigned return pointer set.
@@ -178,19 +324,198 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     }
 
     /// Adds a new offset to string entry to the map.
+    /// Since the string is overwritten, the facts known about the old string are removed.
     pub fn add_new_stack_offset_to_string_entry(&mut self, offset: i64, string_domain: T) {
         self.stack_offset_to_string_map
             .insert(offset, string_domain);
+        self.stack_offset_to_predicate_map.remove(&offset);
     }
 
-    /// Adds a new heap id to string entry to the map.
-    pub fn add_new_heap_to_string_entry(&mut self, heap_id: AbstractIdentifier, string_domain: T) {
-        self.heap_to_string_map.insert(heap_id, string_domain);
+    /// Adds a new string at the given offset of the heap object to the map.
+    /// If the offset is unknown, the heap object is collapsed.
+    /// Since the string is overwritten, the facts known about the old string are removed.
+    pub fn add_new_heap_to_string_entry(
+        &mut self,
+        heap_id: AbstractIdentifier,
+        offset: Option<i64>,
+        string_domain: T,
+    ) {
+        let object = match self.heap_to_string_map.entry(heap_id.clone()) {
+            Entry::Occupied(entry) => {
+                let object = entry.into_mut();
+                object.insert(offset, string_domain);
+                object
+            }
+            Entry::Vacant(entry) => entry.insert(ObjectFields::new(offset, string_domain)),
+        };
+        match offset {
+            Some(offset) if !object.is_collapsed() => {
+                self.heap_to_predicate_map.remove(&(heap_id, offset));
+            }
+            _ => self
+                .heap_to_predicate_map
+                .retain(|(id, _), _| *id != heap_id),
+        }
     }
 
-    /// Removes a string from the heap to string map for the given abstract id.
+    /// Removes the strings and the facts known about them for the given abstract id.
     pub fn remove_heap_to_string_entry(&mut self, heap_id: &AbstractIdentifier) {
         self.heap_to_string_map.remove(heap_id);
+        self.heap_to_predicate_map
+            .retain(|(id, _), _| id != heap_id);
+    }
+
+    /// Returns the string at the given offset of the heap object.
+    /// If the offset is unknown, the merge of all strings in the object is returned.
+    pub fn get_heap_string(&self, heap_id: &AbstractIdentifier, offset: Option<i64>) -> Option<T> {
+        self.heap_to_string_map.get(heap_id)?.get(offset)
+    }
+
+    /// Adds the comparisons performed by a string comparison call
+    /// whose result is stored in the given return register.
+    pub fn add_string_comparison_entry(
+        &mut self,
+        return_register: Variable,
+        comparisons: Vec<(DataDomain<IntervalDomain>, T)>,
+    ) {
+        self.string_comparison_map
+            .insert(return_register, comparisons);
+    }
+
+    /// Returns the comparisons whose result is stored in the given variable.
+    pub fn get_string_comparisons(
+        &self,
+        variable: &Variable,
+    ) -> Option<&Vec<(DataDomain<IntervalDomain>, T)>> {
+        self.string_comparison_map.get(variable)
+    }
+
+    /// Removes the comparison results stored in the given variable.
+    pub fn remove_string_comparison_entry(&mut self, variable: &Variable) {
+        self.string_comparison_map.remove(variable);
+    }
+
+    /// Removes all comparison results, e.g. since the registers holding them were overwritten by a call.
+    pub fn clear_string_comparison_map(&mut self) {
+        self.string_comparison_map = HashMap::new();
+    }
+
+    /// Adds the pointer to the string whose length is stored in the given return register of a `strlen` call.
+    pub fn add_string_length_entry(
+        &mut self,
+        return_register: Variable,
+        pointer: DataDomain<IntervalDomain>,
+    ) {
+        self.string_length_map.insert(return_register, pointer);
+    }
+
+    /// Returns the pointer to the string whose length is stored in the given variable.
+    pub fn get_string_length_pointer(
+        &self,
+        variable: &Variable,
+    ) -> Option<&DataDomain<IntervalDomain>> {
+        self.string_length_map.get(variable)
+    }
+
+    /// Removes the string length stored in the given variable.
+    pub fn remove_string_length_entry(&mut self, variable: &Variable) {
+        self.string_length_map.remove(variable);
+    }
+
+    /// Removes all string lengths, e.g. since the registers holding them were overwritten by a call.
+    pub fn clear_string_length_map(&mut self) {
+        self.string_length_map = HashMap::new();
+    }
+
+    /// Adds the given facts to the facts known about the strings that `pointer` points to.
+    ///
+    /// Targets with unknown offsets and fields of collapsed heap objects are ignored.
+    pub fn add_string_predicates(
+        &mut self,
+        pointer: &DataDomain<IntervalDomain>,
+        predicates: &PredicateDomain,
+        pi_state: &PointerInferenceState,
+    ) {
+        for (target, offset) in pointer.get_relative_values() {
+            let Ok(offset) = offset.try_to_offset() else {
+                continue;
+            };
+            let known_predicates = if State::<T>::is_stack_pointer(pi_state, target) {
+                self.stack_offset_to_predicate_map
+                    .entry(offset)
+                    .or_default()
+            } else if self
+                .heap_to_string_map
+                .get(target)
+                .is_some_and(ObjectFields::is_collapsed)
+            {
+                continue;
+            } else {
+                self.heap_to_predicate_map
+                    .entry((target.clone(), offset))
+                    .or_default()
+            };
+            *known_predicates = known_predicates.intersect(predicates);
+        }
+    }
+
+    /// Returns the facts known about all strings that `pointer` may point to.
+    ///
+    /// If the pointer has no relative targets or some target is not tracked, no facts are known.
+    pub fn get_string_predicates(
+        &self,
+        pointer: &DataDomain<IntervalDomain>,
+        pi_state: &PointerInferenceState,
+    ) -> PredicateDomain {
+        let mut predicates: Option<PredicateDomain> = None;
+        for (target, offset) in pointer.get_relative_values() {
+            let target_predicates = offset.try_to_offset().ok().and_then(|offset| {
+                if State::<T>::is_stack_pointer(pi_state, target) {
+                    self.stack_offset_to_predicate_map.get(&offset)
+                } else {
+                    self.heap_to_predicate_map.get(&(target.clone(), offset))
+                }
+            });
+            let Some(target_predicates) = target_predicates else {
+                return PredicateDomain::default();
+            };
+            predicates = Some(match predicates {
+                Some(predicates) => predicates.merge(target_predicates),
+                None => target_predicates.clone(),
+            });
+        }
+
+        predicates.unwrap_or_default()
+    }
+
+    /// Intersects the string domain that `pointer` points to with the given domain.
+    ///
+    /// Only strings that are already tracked and that are the unique target of the pointer are refined.
+    /// Returns an error if the intersection is empty.
+    pub fn intersect_string_domain(
+        &mut self,
+        pointer: &DataDomain<IntervalDomain>,
+        domain: &T,
+        pi_state: &PointerInferenceState,
+    ) -> Result<(), Error> {
+        let Some((target, offset)) = pointer.get_if_unique_target() else {
+            return Ok(());
+        };
+        let Ok(offset) = offset.try_to_offset() else {
+            return Ok(());
+        };
+        let string_domain = if *target == pi_state.stack_id {
+            self.stack_offset_to_string_map.get_mut(&offset)
+        } else {
+            self.heap_to_string_map
+                .get_mut(target)
+                .and_then(|object| object.get_mut(offset))
+        };
+        if let Some(string_domain) = string_domain {
+            *string_domain = string_domain.intersect(domain)?;
+        }
+
+        Ok(())
     }
 
     /// Returns a reference to the variable to pointer map.
@@ -217,7 +542,7 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     }
 
     /// Returns a reference to the heap to string map.
-    pub fn get_heap_to_string_map(&self) -> &HashMap<AbstractIdentifier, T> {
+    pub fn get_heap_to_string_map(&self) -> &HashMap<AbstractIdentifier, ObjectFields<T>> {
         &self.heap_to_string_map
     }
 
@@ -270,9 +595,12 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     pub fn filter_string_map_entries(
         &self,
         pi_state: &PointerInferenceState,
-    ) -> (HashMap<i64, T>, HashMap<AbstractIdentifier, T>) {
+    ) -> (
+        HashMap<i64, T>,
+        HashMap<AbstractIdentifier, ObjectFields<T>>,
+    ) {
         let mut stack_strings: HashMap<i64, T> = HashMap::new();
-        let mut heap_strings: HashMap<AbstractIdentifier, T> = HashMap::new();
+        let mut heap_strings: HashMap<AbstractIdentifier, ObjectFields<T>> = HashMap::new();
         for pointer in self.collect_all_tracked_pointers().iter() {
             for (target, offset) in pointer.get_relative_values().iter() {
                 if State::<T>::is_stack_pointer(pi_state, target) {
@@ -293,22 +621,24 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     }
 
     /// Evaluates the constant used as input of a Def Term.
-    /// It checks whether it is a constant address pointing to global read only
-    /// memory. If so, a pointer is added to the register map.
+    /// It checks whether it is a constant address pointing to a string literal
+    /// in global read only memory. If so, a pointer is added to the register map.
+    ///
+    /// String literals that are probably binary data with respect to the given alphabet are ignored.
     pub fn evaluate_constant(
         &self,
         runtime_memory_image: &RuntimeMemoryImage,
         block_first_def_set: &HashSet<(Tid, Tid)>,
+        alphabet: CharacterAlphabet,
         constant: Bitvector,
     ) -> Option<DataDomain<IntervalDomain>> {
         if let Ok(address) = constant.try_to_u64() {
-            if !block_first_def_set
-                .iter()
-                .any(|(def_tid, _)| u64::try_from(def_tid.address()).unwrap() == address)
-                && runtime_memory_image.is_global_memory_address(&constant)
+            if !block_first_def_set.iter().any(|(def_tid, _)| {
+                u64::try_from(def_tid.address()).is_ok_and(|def_address| def_address == address)
+            }) && runtime_memory_image.is_global_memory_address(&constant)
                 && runtime_memory_image
-                    .read_string_until_null_terminator(&constant)
-                    .is_ok()
+                    .read_string_literal(&constant)
+                    .is_ok_and(|string| !alphabet.is_probably_binary_data(string.as_bytes()))
             {
                 return Some(DataDomain::from(IntervalDomain::new(
                     constant.clone(),
@@ -327,6 +657,7 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
         input: &Expression,
         runtime_memory_image: &RuntimeMemoryImage,
         block_first_def_set: &HashSet<(Tid, Tid)>,
+        alphabet: CharacterAlphabet,
         is_assign: bool,
     ) {
         let mut is_string_pointer = false;
@@ -336,11 +667,13 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
                 output,
                 runtime_memory_image,
                 block_first_def_set,
+                alphabet,
             )
         } else if is_assign {
             is_string_pointer = self.add_global_pointer_if_input_is_string_constant(
                 runtime_memory_image,
                 block_first_def_set,
+                alphabet,
                 output,
                 input,
             )
@@ -361,13 +694,17 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
         output: &Variable,
         runtime_memory_image: &RuntimeMemoryImage,
         block_first_def_set: &HashSet<(Tid, Tid)>,
+        alphabet: CharacterAlphabet,
     ) -> bool {
         let output_domain = pi_state.eval(&Expression::Var(output.clone()));
         if let Some(value) = output_domain.get_absolute_value() {
             if let Ok(constant) = value.try_to_bitvec() {
-                if let Some(global_pointer) =
-                    self.evaluate_constant(runtime_memory_image, block_first_def_set, constant)
-                {
+                if let Some(global_pointer) = self.evaluate_constant(
+                    runtime_memory_image,
+                    block_first_def_set,
+                    alphabet,
+                    constant,
+                ) {
                     self.variable_to_pointer_map
                         .insert(output.clone(), global_pointer);
                     self.add_relative_targets_to_string_maps(pi_state, &output_domain);
@@ -387,13 +724,17 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
         &mut self,
         runtime_memory_image: &RuntimeMemoryImage,
         block_first_def_set: &HashSet<(Tid, Tid)>,
+        alphabet: CharacterAlphabet,
         output: &Variable,
         input: &Expression,
     ) -> bool {
         if let Expression::Const(constant) = input {
-            if let Some(global_pointer) =
-                self.evaluate_constant(runtime_memory_image, block_first_def_set, constant.clone())
-            {
+            if let Some(global_pointer) = self.evaluate_constant(
+                runtime_memory_image,
+                block_first_def_set,
+                alphabet,
+                constant.clone(),
+            ) {
                 self.variable_to_pointer_map
                     .insert(output.clone(), global_pointer);
 
@@ -418,9 +759,21 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
                         .entry(offset_value)
                         .or_insert_with(T::create_top_value_domain);
                 }
-            } else if !self.heap_to_string_map.contains_key(target) {
-                self.heap_to_string_map
-                    .insert(target.clone(), T::create_top_value_domain());
+            } else {
+                let offset = offset.try_to_offset().ok();
+                match self.heap_to_string_map.get_mut(target) {
+                    Some(object) => {
+                        if let Some(offset) = offset.filter(|offset| !object.contains(*offset)) {
+                            object.insert(Some(offset), T::create_top_value_domain());
+                        }
+                    }
+                    None => {
+                        self.heap_to_string_map.insert(
+                            target.clone(),
+                            ObjectFields::new(offset, T::create_top_value_domain()),
+                        );
+                    }
+                }
             }
         }
     }
@@ -483,7 +836,7 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     ) -> bool {
         let mut contains_string_target = false;
         let mut new_stack_entries: Vec<i64> = Vec::new();
-        let mut new_heap_entries: Vec<AbstractIdentifier> = Vec::new();
+        let mut new_heap_entries: Vec<(AbstractIdentifier, Option<i64>)> = Vec::new();
         for (target, offset) in pointer.get_relative_values().iter() {
             if State::<T>::is_stack_pointer(pi_state, target) {
                 if let Ok(offset_value) = offset.try_to_offset() {
@@ -493,10 +846,15 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
                         new_stack_entries.push(offset_value);
                     }
                 }
-            } else if self.heap_to_string_map.contains_key(target) {
-                contains_string_target = true;
             } else {
-                new_heap_entries.push(target.clone());
+                let offset = offset.try_to_offset().ok();
+                match (self.heap_to_string_map.get(target), offset) {
+                    (Some(_), None) => contains_string_target = true,
+                    (Some(object), Some(offset)) if object.contains(offset) => {
+                        contains_string_target = true
+                    }
+                    _ => new_heap_entries.push((target.clone(), offset)),
+                }
             }
         }
 
@@ -511,18 +869,18 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     }
 
     /// Adds *Top* values to stack and heap maps for additional pointer targets.
+    /// The heap entries consist of the heap id and the offset of the field, if known.
     pub fn add_top_domain_values_for_additional_pointer_targets(
         &mut self,
         new_stack_entries: Vec<i64>,
-        new_heap_entries: Vec<AbstractIdentifier>,
+        new_heap_entries: Vec<(AbstractIdentifier, Option<i64>)>,
     ) {
         for entry in new_stack_entries.iter() {
             self.stack_offset_to_string_map
                 .insert(*entry, T::create_top_value_domain());
         }
-        for entry in new_heap_entries.iter() {
-            self.heap_to_string_map
-                .insert(entry.clone(), T::create_top_value_domain());
+        for (heap_id, offset) in new_heap_entries {
+            self.add_new_heap_to_string_entry(heap_id, offset, T::create_top_value_domain());
         }
     }
 
@@ -538,21 +896,32 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     }
 
     /// Handles store Def Terms.
+    ///
+    /// Stores of immediate values that are no string pointers may write characters
+    /// of a string on the stack, see [`State::add_immediate_characters_to_stack_string`].
     pub fn handle_store(
         &mut self,
         target_address: &Expression,
         value: &Expression,
         runtime_memory_image: &RuntimeMemoryImage,
         block_first_def_set: &HashSet<(Tid, Tid)>,
+        alphabet: CharacterAlphabet,
     ) {
         match value {
             Expression::Const(constant) => {
                 if let Some(data) = self.evaluate_constant(
                     runtime_memory_image,
                     block_first_def_set,
+                    alphabet,
                     constant.clone(),
                 ) {
                     self.add_pointer_to_stack_map(target_address, data);
+                } else {
+                    self.add_immediate_characters_to_stack_string(
+                        target_address,
+                        constant,
+                        runtime_memory_image.is_little_endian,
+                    );
                 }
             }
             _ => {
@@ -570,6 +939,7 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
                                     &Expression::Const(constant_value),
                                     runtime_memory_image,
                                     block_first_def_set,
+                                    alphabet,
                                 )
                             }
                         }
@@ -580,6 +950,9 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     }
 
     /// If a string pointer is to be stored on the stack, add it to the stack map.
+    ///
+    /// Stores to unknown offsets are weak updates of the tracked stack array elements
+    /// that the offsets may refer to.
     pub fn add_pointer_to_stack_map(
         &mut self,
         target: &Expression,
@@ -592,6 +965,8 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
                     if let Ok(offset_value) = offset.try_to_offset() {
                         self.stack_offset_to_pointer_map
                             .insert(offset_value, string_pointer.clone());
+                    } else {
+                        self.weak_update_stack_array_elements(offset, &string_pointer);
                     }
                 }
             }
@@ -623,5 +998,106 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     }
 }
 
+impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String> + std::fmt::Display>
+    State<T>
+{
+    /// Get a more compact json-representation of the state.
+    /// Intended for pretty printing, not useable for serialization/deserialization.
+    pub fn to_json_compact(&self) -> serde_json::Value {
+        use serde_json::*;
+        let mut state_map = Map::new();
+        let register = self
+            .variable_to_pointer_map
+            .iter()
+            .map(|(var, pointer)| (var.name.clone(), pointer.to_json_compact()))
+            .collect();
+        state_map.insert("register".into(), Value::Object(register));
+        let stack_pointers = self
+            .stack_offset_to_pointer_map
+            .iter()
+            .map(|(offset, pointer)| (format!("{offset}"), pointer.to_json_compact()))
+            .collect();
+        state_map.insert("stack_pointers".into(), Value::Object(stack_pointers));
+        let stack_strings = self
+            .stack_offset_to_string_map
+            .iter()
+            .map(|(offset, string)| (format!("{offset}"), Value::String(string.to_string())))
+            .collect();
+        state_map.insert("stack_strings".into(), Value::Object(stack_strings));
+        let heap_strings = self
+            .heap_to_string_map
+            .iter()
+            .map(|(id, object)| {
+                let fields: Map<String, Value> = object
+                    .iter()
+                    .map(|(offset, string)| {
+                        (format!("{offset}"), Value::String(string.to_string()))
+                    })
+                    .collect();
+                let object = json!({
+                    "collapsed": object.is_collapsed(),
+                    "fields": fields,
+                });
+                (format!("{id}"), object)
+            })
+            .collect();
+        state_map.insert("heap_strings".into(), Value::Object(heap_strings));
+
+        Value::Object(state_map)
+    }
+}
+
+/// Merge two maps of predicates.
+/// Only keys contained in both maps whose merged predicates are not *Top* are kept.
+/// Check whether every pointer in `map` is contained in the pointer of the same key in `other`.
+fn pointers_less_or_equal<K: Eq + std::hash::Hash>(
+    map: &HashMap<K, DataDomain<IntervalDomain>>,
+    other: &HashMap<K, DataDomain<IntervalDomain>>,
+) -> bool {
+    map.iter().all(|(key, pointer)| {
+        other
+            .get(key)
+            .is_some_and(|other_pointer| pointer.less_or_equal(other_pointer))
+    })
+}
+
+/// Check whether both maps have the same keys
+/// and every value in `map` is less than or equal to the value of the same key in `other`.
+fn same_keys_less_or_equal<K: Eq + std::hash::Hash, V: DomainOrder>(
+    map: &HashMap<K, V>,
+    other: &HashMap<K, V>,
+) -> bool {
+    map.len() == other.len()
+        && map.iter().all(|(key, value)| {
+            other
+                .get(key)
+                .is_some_and(|other_value| value.less_or_equal(other_value))
+        })
+}
+
+/// Check whether every fact known in `other` is also known in `map`.
+/// Keys without facts are represented by missing entries.
+fn predicates_less_or_equal<K: Eq + std::hash::Hash>(
+    map: &HashMap<K, PredicateDomain>,
+    other: &HashMap<K, PredicateDomain>,
+) -> bool {
+    other.iter().all(|(key, other_predicates)| {
+        map.get(key)
+            .is_some_and(|predicates| predicates.less_or_equal(other_predicates))
+    })
+}
+
+fn merge_predicate_maps<K: Clone + Eq + std::hash::Hash>(
+    map: &HashMap<K, PredicateDomain>,
+    other: &HashMap<K, PredicateDomain>,
+) -> HashMap<K, PredicateDomain> {
+    map.iter()
+        .filter_map(|(key, predicates)| {
+            let merged = predicates.merge(other.get(key)?);
+            (!merged.is_top()).then(|| (key.clone(), merged))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,251 @@
+//! Field-sensitive representation of the content of memory objects.
+
+use std::collections::BTreeMap;
+
+use crate::abstract_domain::{AbstractDomain, DomainOrder};
+use crate::prelude::*;
+
+/// The maximal number of fields that are tracked separately in a memory object.
+///
+/// Objects with more fields are collapsed, see [`ObjectFields`].
+pub const MAX_FIELDS_PER_OBJECT: usize = 16;
+
+/// The values contained in the fields of a memory object, indexed by their byte offset in the object.
+///
+/// E.g. the strings `ctx->hostname` and `ctx->username` of a struct `ctx`
+/// are tracked as two fields of the same memory object.
+///
+/// If a value is written to an unknown offset or if more than [`MAX_FIELDS_PER_OBJECT`] fields are written,
+/// the object is *collapsed*, i.e. all its fields are merged into one value
+/// that represents the content of every offset of the object.
+/// Writes to collapsed objects are weak updates.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ObjectFields<V> {
+    /// Maps byte offsets to the values of the fields.
+    /// If the object is collapsed, the only entry is at offset zero.
+    fields: BTreeMap<i64, V>,
+    /// Whether the object is collapsed.
+    collapsed: bool,
+}
+
+impl<V: AbstractDomain> ObjectFields<V> {
+    /// Create a new object containing the given value at the given offset.
+    ///
+    /// If the offset is unknown, the object is collapsed.
+    pub fn new(offset: Option<i64>, value: V) -> Self {
+        match offset {
+            Some(offset) => ObjectFields {
+                fields: BTreeMap::from([(offset, value)]),
+                collapsed: false,
+            },
+            None => Self::new_collapsed(value),
+        }
+    }
+
+    /// Create a new collapsed object whose content is represented by the given value.
+    pub fn new_collapsed(value: V) -> Self {
+        ObjectFields {
+            fields: BTreeMap::from([(0, value)]),
+            collapsed: true,
+        }
+    }
+
+    /// Returns `true` if the fields of the object are not tracked separately.
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Get the value of the field at the given offset.
+    ///
+    /// For collapsed objects the value representing all fields is returned.
+    /// If the offset is unknown, the merge of all fields is returned.
+    pub fn get(&self, offset: Option<i64>) -> Option<V> {
+        match offset {
+            _ if self.collapsed => self.fields.values().next().cloned(),
+            Some(offset) => self.fields.get(&offset).cloned(),
+            None => self.get_merged_value(),
+        }
+    }
+
+    /// Get a mutable reference to the field at the given offset.
+    ///
+    /// Returns `None` for collapsed objects,
+    /// since their values do not belong to a specific field.
+    pub fn get_mut(&mut self, offset: i64) -> Option<&mut V> {
+        if self.collapsed {
+            None
+        } else {
+            self.fields.get_mut(&offset)
+        }
+    }
+
+    /// Returns `true` if the object contains a value for the given offset.
+    pub fn contains(&self, offset: i64) -> bool {
+        self.collapsed || self.fields.contains_key(&offset)
+    }
+
+    /// Write the value to the field at the given offset.
+    ///
+    /// If the offset is unknown or if the number of fields would exceed [`MAX_FIELDS_PER_OBJECT`],
+    /// the object is collapsed.
+    pub fn insert(&mut self, offset: Option<i64>, value: V) {
+        match offset {
+            Some(offset)
+                if !self.collapsed
+                    && (self.fields.contains_key(&offset)
+                        || self.fields.len() < MAX_FIELDS_PER_OBJECT) =>
+            {
+                self.fields.insert(offset, value);
+            }
+            _ => {
+                let merged = match self.get_merged_value() {
+                    Some(old_value) => old_value.merge(&value),
+                    None => value,
+                };
+                *self = Self::new_collapsed(merged);
+            }
+        }
+    }
+
+    /// Merge two objects.
+    ///
+    /// Fields contained in both objects are merged by `merge_values`.
+    /// Fields only contained in `other` are set to `default`.
+    /// If one of the objects is collapsed, the result is collapsed.
+    pub fn merge_with(&self, other: &Self, merge_values: impl Fn(&V, &V) -> V, default: V) -> Self {
+        if self.collapsed || other.collapsed {
+            let (Some(value), Some(other_value)) =
+                (self.get_merged_value(), other.get_merged_value())
+            else {
+                return Self::new_collapsed(default);
+            };
+            return Self::new_collapsed(merge_values(&value, &other_value));
+        }
+        let mut merged = self.clone();
+        for (offset, other_value) in other.fields.iter() {
+            let value = match self.fields.get(offset) {
+                Some(value) => merge_values(value, other_value),
+                None => default.clone(),
+            };
+            merged.insert(Some(*offset), value);
+        }
+        merged
+    }
+
+    /// Returns an object with the same fields as `self` that all contain the given value.
+    pub fn with_all_values(&self, value: V) -> Self {
+        ObjectFields {
+            fields: self
+                .fields
+                .keys()
+                .map(|offset| (*offset, value.clone()))
+                .collect(),
+            collapsed: self.collapsed,
+        }
+    }
+
+    /// Iterate over the fields of the object and their offsets.
+    pub fn iter(&self) -> impl Iterator<Item = (&i64, &V)> {
+        self.fields.iter()
+    }
+
+    /// Get the merge of the values of all fields.
+    fn get_merged_value(&self) -> Option<V> {
+        self.fields
+            .values()
+            .cloned()
+            .reduce(|merged, value| merged.merge(&value))
+    }
+}
+
+impl<V: DomainOrder> DomainOrder for ObjectFields<V> {
+    /// Objects are only compared if they have the same fields.
+    fn less_or_equal(&self, other: &Self) -> bool {
+        self.collapsed == other.collapsed
+            && self.fields.len() == other.fields.len()
+            && self.fields.iter().all(|(offset, value)| {
+                other
+                    .fields
+                    .get(offset)
+                    .is_some_and(|other_value| value.less_or_equal(other_value))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::CharacterInclusionDomain;
+
+    fn string(content: &str) -> CharacterInclusionDomain {
+        CharacterInclusionDomain::from(content.to_string())
+    }
+
+    #[test]
+    fn field_sensitive_access() {
+        let mut object = ObjectFields::new(Some(0), string("host"));
+        object.insert(Some(8), string("user"));
+        assert!(!object.is_collapsed());
+        assert_eq!(object.get(Some(0)), Some(string("host")));
+        assert_eq!(object.get(Some(8)), Some(string("user")));
+        assert_eq!(object.get(Some(4)), None);
+        assert!(object.contains(8));
+        assert!(!object.contains(4));
+        assert_eq!(
+            object.get(None),
+            Some(string("host").merge(&string("user")))
+        );
+
+        object.insert(Some(0), string("name"));
+        assert_eq!(object.get(Some(0)), Some(string("name")));
+        *object.get_mut(8).unwrap() = string("u");
+        assert_eq!(object.get(Some(8)), Some(string("u")));
+    }
+
+    #[test]
+    fn collapse() {
+        let mut object = ObjectFields::new(Some(0), string("a"));
+        for offset in 1..MAX_FIELDS_PER_OBJECT as i64 {
+            object.insert(Some(offset), string("a"));
+        }
+        assert!(!object.is_collapsed());
+        object.insert(Some(100), string("b"));
+        assert!(object.is_collapsed());
+        assert_eq!(object.iter().count(), 1);
+        assert_eq!(object.get(Some(3)), Some(string("a").merge(&string("b"))));
+        assert!(object.get_mut(0).is_none());
+        assert!(object.contains(42));
+
+        let mut object = ObjectFields::new(Some(0), string("a"));
+        object.insert(None, string("b"));
+        assert!(object.is_collapsed());
+        // Writes to collapsed objects are weak updates.
+        object.insert(Some(0), string("c"));
+        assert_eq!(
+            object.get(Some(0)),
+            Some(string("a").merge(&string("b")).merge(&string("c")))
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut object = ObjectFields::new(Some(0), string("ab"));
+        object.insert(Some(8), string("cd"));
+        let mut other = ObjectFields::new(Some(0), string("ac"));
+        other.insert(Some(16), string("ef"));
+
+        let merged = object.merge_with(&other, |a, b| a.merge(b), CharacterInclusionDomain::Top);
+        assert_eq!(merged.get(Some(0)), Some(string("ab").merge(&string("ac"))));
+        assert_eq!(merged.get(Some(8)), Some(string("cd")));
+        assert_eq!(merged.get(Some(16)), Some(CharacterInclusionDomain::Top));
+
+        let collapsed = ObjectFields::new(None, string("x"));
+        let merged =
+            object.merge_with(&collapsed, |a, b| a.merge(b), CharacterInclusionDomain::Top);
+        assert!(merged.is_collapsed());
+        assert_eq!(
+            merged.get(Some(8)),
+            Some(string("ab").merge(&string("cd")).merge(&string("x")))
+        );
+    }
+}
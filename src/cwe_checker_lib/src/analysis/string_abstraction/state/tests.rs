@@ -1,12 +1,15 @@
 use super::*;
 use crate::{
-    abstract_domain::{AbstractLocation, CharacterInclusionDomain},
+    abstract_domain::{
+        AbstractLocation, BricksDomain, CharacterInclusionDomain, Interval, PredicateDomain,
+    },
     analysis::{
         pointer_inference::State as PiState,
         string_abstraction::tests::mock_project_with_intraprocedural_control_flow,
     },
-    expr,
+    bitvec, expr,
     intermediate_representation::*,
+    utils::binary::MemorySegment,
     variable,
 };
 use std::collections::BTreeSet;
@@ -23,7 +26,12 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
             variable_to_pointer_map: HashMap::new(),
             stack_offset_to_pointer_map: HashMap::new(),
             stack_offset_to_string_map: HashMap::new(),
+            stack_offset_to_immediate_bytes_map: HashMap::new(),
             heap_to_string_map: HashMap::new(),
+            string_comparison_map: HashMap::new(),
+            string_length_map: HashMap::new(),
+            stack_offset_to_predicate_map: HashMap::new(),
+            heap_to_predicate_map: HashMap::new(),
             current_sub: Arc::new(Some(current_sub)),
             pointer_inference_state: Some(pi_state),
         }
@@ -35,7 +43,12 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
             variable_to_pointer_map: HashMap::new(),
             stack_offset_to_pointer_map: HashMap::new(),
             stack_offset_to_string_map: HashMap::new(),
+            stack_offset_to_immediate_bytes_map: HashMap::new(),
             heap_to_string_map: HashMap::new(),
+            string_comparison_map: HashMap::new(),
+            string_length_map: HashMap::new(),
+            stack_offset_to_predicate_map: HashMap::new(),
+            heap_to_predicate_map: HashMap::new(),
             current_sub: Arc::new(Some(current_sub)),
             pointer_inference_state: Some(pi_state),
         }
@@ -97,15 +110,18 @@ fn test_delete_string_map_entries_if_no_pointer_targets_are_tracked() {
     state
         .stack_offset_to_string_map
         .insert(4, CharacterInclusionDomain::Top); // will be deleted
-    state
-        .heap_to_string_map
-        .insert(heap_id_1.clone(), CharacterInclusionDomain::Top); // pointer tracked
-    state
-        .heap_to_string_map
-        .insert(heap_id_2.clone(), CharacterInclusionDomain::Top); // pointer tracked
-    state
-        .heap_to_string_map
-        .insert(heap_id_3.clone(), CharacterInclusionDomain::Top); // will be deleted
+    state.heap_to_string_map.insert(
+        heap_id_1.clone(),
+        ObjectFields::new(Some(0), CharacterInclusionDomain::Top),
+    ); // pointer tracked
+    state.heap_to_string_map.insert(
+        heap_id_2.clone(),
+        ObjectFields::new(Some(0), CharacterInclusionDomain::Top),
+    ); // pointer tracked
+    state.heap_to_string_map.insert(
+        heap_id_3.clone(),
+        ObjectFields::new(Some(0), CharacterInclusionDomain::Top),
+    ); // will be deleted
 
     let new_state = state.delete_string_map_entries_if_no_pointer_targets_are_tracked();
 
@@ -115,12 +131,12 @@ fn test_delete_string_map_entries_if_no_pointer_targets_are_tracked() {
     );
     assert_eq!(new_state.stack_offset_to_string_map.get(&4), None);
     assert_eq!(
-        new_state.heap_to_string_map.get(&heap_id_1),
-        Some(&CharacterInclusionDomain::Top)
+        new_state.get_heap_string(&heap_id_1, Some(0)),
+        Some(CharacterInclusionDomain::Top)
     );
     assert_eq!(
-        new_state.heap_to_string_map.get(&heap_id_2),
-        Some(&CharacterInclusionDomain::Top)
+        new_state.get_heap_string(&heap_id_2, Some(0)),
+        Some(CharacterInclusionDomain::Top)
     );
     assert_eq!(new_state.heap_to_string_map.get(&heap_id_3), None);
 }
@@ -138,7 +154,12 @@ fn test_evaluate_constant() {
         Some(DataDomain::from(Bitvector::from_i32(
             constant.clone().try_to_i32().unwrap()
         ))),
-        state.evaluate_constant(&runtime_memory_image, &block_first_def_set, constant)
+        state.evaluate_constant(
+            &runtime_memory_image,
+            &block_first_def_set,
+            CharacterAlphabet::Utf8,
+            constant
+        )
     );
 
     assert_eq!(
@@ -146,6 +167,7 @@ fn test_evaluate_constant() {
         state.evaluate_constant(
             &runtime_memory_image,
             &block_first_def_set,
+            CharacterAlphabet::Utf8,
             Bitvector::from_i32(0x1234)
         )
     );
@@ -178,6 +200,7 @@ fn test_handle_assign_and_load() {
         &constant_input,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
         true,
     );
     assert_eq!(
@@ -193,6 +216,7 @@ fn test_handle_assign_and_load() {
         &constant_input,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
         true,
     );
     assert_eq!(
@@ -207,6 +231,7 @@ fn test_handle_assign_and_load() {
         &return_address_input,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
         true,
     );
     assert!(state.variable_to_pointer_map.is_empty());
@@ -230,6 +255,7 @@ fn test_handle_assign_and_load() {
         &other_input,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
         true,
     );
 
@@ -245,6 +271,7 @@ fn test_handle_assign_and_load() {
         &constant_input,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
         false,
     );
 
@@ -255,6 +282,7 @@ fn test_handle_assign_and_load() {
         &other_input,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
         false,
     );
 
@@ -326,7 +354,7 @@ fn test_add_pointer_to_variable_maps_if_tracked() {
     );
     assert_eq!(
         CharacterInclusionDomain::Top,
-        *mock_state.get_heap_to_string_map().get(&heap_id).unwrap()
+        mock_state.get_heap_string(&heap_id, Some(0)).unwrap()
     );
     mock_state.set_all_maps_empty();
 
@@ -442,6 +470,7 @@ fn test_handle_store() {
         &value_location,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
     );
 
     assert!(mock_state.stack_offset_to_pointer_map.is_empty());
@@ -456,6 +485,7 @@ fn test_handle_store() {
         &value_location,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
     );
 
     assert_eq!(
@@ -475,6 +505,7 @@ fn test_handle_store() {
         &value_location,
         &runtime_memory_image,
         &block_first_def_set,
+        CharacterAlphabet::Utf8,
     );
 
     assert_eq!(
@@ -491,6 +522,145 @@ fn test_handle_store() {
     // Test Case 6: Global address pointer in variable.
 }
 
+#[test]
+fn test_handle_store_of_immediate_characters() {
+    let block_first_def_set: HashSet<(Tid, Tid)> = HashSet::new();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let mut mock_state = State::<BricksDomain>::mock_with_default_pi_state(Sub::mock("func"));
+
+    // "/bin" followed by "/sh\0" at the next offset.
+    mock_state.handle_store(
+        &expr!("sp:4 + 0x8:4"),
+        &expr!("0x6e69622f:4"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    assert_eq!(
+        *mock_state.stack_offset_to_string_map.get(&8).unwrap(),
+        BricksDomain::from("/bin".to_string())
+            .append_string_domain(&BricksDomain::create_top_value_domain())
+    );
+    mock_state.handle_store(
+        &expr!("sp:4 + 0xc:4"),
+        &expr!("0x68732f:4"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    assert_eq!(
+        *mock_state.stack_offset_to_string_map.get(&8).unwrap(),
+        BricksDomain::from("/bin/sh".to_string())
+    );
+    assert!(!mock_state.stack_offset_to_string_map.contains_key(&12));
+
+    // A terminated string is not continued.
+    mock_state.handle_store(
+        &expr!("sp:4 + 0x10:4"),
+        &expr!("0x6161:2"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    assert_eq!(
+        *mock_state.stack_offset_to_string_map.get(&8).unwrap(),
+        BricksDomain::from("/bin/sh".to_string())
+    );
+    // Integers are no strings.
+    mock_state.handle_store(
+        &expr!("sp:4 + 0x20:4"),
+        &expr!("0x12345:4"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    assert!(!mock_state.stack_offset_to_string_map.contains_key(&0x20));
+    // Overwritten characters are replaced.
+    mock_state.handle_store(
+        &expr!("sp:4 + 0x30:4"),
+        &expr!("0x61616161:4"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    mock_state.handle_store(
+        &expr!("sp:4 + 0x30:4"),
+        &expr!("0x61:4"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    assert_eq!(
+        *mock_state.stack_offset_to_string_map.get(&0x30).unwrap(),
+        BricksDomain::from("a".to_string())
+    );
+
+    // The bytes of big-endian binaries are stored in reverse order.
+    let runtime_memory_image = RuntimeMemoryImage::empty(false);
+    mock_state.set_all_maps_empty();
+    mock_state.handle_store(
+        &expr!("sp:4 + 0x8:4"),
+        &expr!("0x2f736800:4"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    assert_eq!(
+        *mock_state.stack_offset_to_string_map.get(&8).unwrap(),
+        BricksDomain::from("/sh".to_string())
+    );
+}
+
+#[test]
+fn test_decode_immediate_sequence() {
+    let block_first_def_set: HashSet<(Tid, Tid)> = HashSet::new();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let mut mock_state = State::<BricksDomain>::mock_with_default_pi_state(Sub::mock("func"));
+    let key = Bitvector::from_u8(0x42);
+
+    // "/bin/sh" XOR 0x42 followed by an unencoded NUL byte.
+    mock_state.handle_store(
+        &expr!("sp:4 + 0x8:4"),
+        &expr!("0x2c2b206d:4"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    mock_state.handle_store(
+        &expr!("sp:4 + 0xc:4"),
+        &expr!("0x2a316d:4"),
+        &runtime_memory_image,
+        &block_first_def_set,
+        CharacterAlphabet::Utf8,
+    );
+    let mut decoded_state = mock_state.clone();
+    decoded_state.decode_immediate_sequence(8, BinOpType::IntXOr, &key, None, true);
+    assert_eq!(
+        *decoded_state.stack_offset_to_string_map.get(&8).unwrap(),
+        BricksDomain::from("/bin/sh".to_string())
+    );
+
+    // Only the given number of elements is decoded.
+    let mut decoded_state = mock_state.clone();
+    decoded_state.decode_immediate_sequence(8, BinOpType::IntXOr, &key, Some(4), true);
+    assert_eq!(
+        *decoded_state.stack_offset_to_string_map.get(&8).unwrap(),
+        BricksDomain::from("/binm1*".to_string())
+    );
+
+    // Strings that were not built by immediate stores cannot be decoded.
+    mock_state.set_all_maps_empty();
+    mock_state
+        .stack_offset_to_string_map
+        .insert(8, BricksDomain::from("abc".to_string()));
+    mock_state.decode_immediate_sequence(8, BinOpType::IntXOr, &key, None, true);
+    assert!(mock_state
+        .stack_offset_to_string_map
+        .get(&8)
+        .unwrap()
+        .is_top());
+}
+
 #[test]
 fn test_add_pointer_to_stack_map() {
     let r2_reg = variable!("r2:4");
@@ -550,3 +720,327 @@ fn test_remove_non_callee_saved_pointer_entries_for_external_symbol() {
         .variable_to_pointer_map
         .contains_key(&variable!("r11:4")));
 }
+
+#[test]
+fn test_intersect_string_domain() {
+    let mut state: State<CharacterInclusionDomain> =
+        State::mock_with_default_pi_state(Sub::mock("func"));
+    let pi_state = state.get_pointer_inference_state().unwrap().clone();
+    let stack_pointer: DataDomain<IntervalDomain> =
+        DataDomain::from_target(pi_state.stack_id.clone(), Bitvector::from_i32(-8).into());
+    let prefix = CharacterInclusionDomain::from("/".to_string())
+        .append_string_domain(&CharacterInclusionDomain::Top);
+
+    // Untracked strings are not refined.
+    assert!(state
+        .intersect_string_domain(&stack_pointer, &prefix, &pi_state)
+        .is_ok());
+    assert!(state.stack_offset_to_string_map.is_empty());
+
+    state
+        .stack_offset_to_string_map
+        .insert(-8, CharacterInclusionDomain::Top);
+    assert!(state
+        .intersect_string_domain(&stack_pointer, &prefix, &pi_state)
+        .is_ok());
+    assert_eq!(state.stack_offset_to_string_map.get(&-8), Some(&prefix));
+
+    state
+        .stack_offset_to_string_map
+        .insert(-8, CharacterInclusionDomain::from("usr".to_string()));
+    assert!(state
+        .intersect_string_domain(&stack_pointer, &prefix, &pi_state)
+        .is_err());
+}
+
+#[test]
+fn test_string_predicates() {
+    let mut state: State<CharacterInclusionDomain> =
+        State::mock_with_default_pi_state(Sub::mock("func"));
+    let pi_state = state.get_pointer_inference_state().unwrap().clone();
+    let stack_pointer: DataDomain<IntervalDomain> =
+        DataDomain::from_target(pi_state.stack_id.clone(), bitvec!("-8:4").into());
+    let heap_id = AbstractIdentifier::new(
+        Tid::new("func"),
+        AbstractLocation::from_var(&variable!("r5:4")).unwrap(),
+    );
+    let heap_pointer: DataDomain<IntervalDomain> =
+        DataDomain::from_target(heap_id.clone(), bitvec!("0:4").into());
+    let sanitized = PredicateDomain::from_sanitizer("sanitize");
+
+    state.add_string_predicates(&stack_pointer, &sanitized, &pi_state);
+    assert!(state
+        .get_string_predicates(&stack_pointer, &pi_state)
+        .is_sanitized());
+    // Not all targets of the pointer are sanitized.
+    let mut both_pointers = stack_pointer.clone();
+    both_pointers.insert_relative_value(heap_id.clone(), bitvec!("0:4").into());
+    assert!(state
+        .get_string_predicates(&both_pointers, &pi_state)
+        .is_top());
+    state.add_string_predicates(&heap_pointer, &sanitized, &pi_state);
+    state.add_string_predicates(
+        &heap_pointer,
+        &PredicateDomain::from_length_bound(16),
+        &pi_state,
+    );
+    assert!(state
+        .get_string_predicates(&both_pointers, &pi_state)
+        .is_sanitized());
+
+    // Facts are kept on merge only if known in both states.
+    let mut other_state = state.clone();
+    other_state.remove_heap_to_string_entry(&heap_id);
+    let merged_state = state.merge(&other_state);
+    assert_eq!(
+        merged_state.get_string_predicates(&stack_pointer, &pi_state),
+        sanitized
+    );
+    assert!(merged_state
+        .get_string_predicates(&heap_pointer, &pi_state)
+        .is_top());
+
+    // Overwriting a string removes the known facts.
+    state.add_new_stack_offset_to_string_entry(-8, CharacterInclusionDomain::Top);
+    assert!(state
+        .get_string_predicates(&stack_pointer, &pi_state)
+        .is_top());
+    assert_eq!(
+        state
+            .get_string_predicates(&heap_pointer, &pi_state)
+            .get_length_bound(),
+        Some(16)
+    );
+}
+
+#[test]
+fn test_field_sensitive_heap_strings() {
+    let mut state: State<CharacterInclusionDomain> =
+        State::mock_with_default_pi_state(Sub::mock("func"));
+    let pi_state = state.get_pointer_inference_state().unwrap().clone();
+    let heap_id = AbstractIdentifier::new(
+        Tid::new("func"),
+        AbstractLocation::from_var(&variable!("r5:4")).unwrap(),
+    );
+    let field_pointer = |offset: i32| -> DataDomain<IntervalDomain> {
+        DataDomain::from_target(heap_id.clone(), Bitvector::from_i32(offset).into())
+    };
+    let hostname = CharacterInclusionDomain::from("example.org".to_string());
+    let username = CharacterInclusionDomain::from("root".to_string());
+    state.add_new_heap_to_string_entry(heap_id.clone(), Some(0), hostname.clone());
+    state.add_new_heap_to_string_entry(heap_id.clone(), Some(8), username.clone());
+    assert_eq!(
+        state.get_heap_string(&heap_id, Some(0)),
+        Some(hostname.clone())
+    );
+    assert_eq!(
+        state.get_heap_string(&heap_id, Some(8)),
+        Some(username.clone())
+    );
+    assert_eq!(
+        state.get_heap_string(&heap_id, None),
+        Some(hostname.merge(&username))
+    );
+
+    // Facts are known per field.
+    let sanitized = PredicateDomain::from_sanitizer("sanitize");
+    state.add_string_predicates(&field_pointer(8), &sanitized, &pi_state);
+    assert!(state
+        .get_string_predicates(&field_pointer(8), &pi_state)
+        .is_sanitized());
+    assert!(state
+        .get_string_predicates(&field_pointer(0), &pi_state)
+        .is_top());
+
+    // Writing to an unknown offset collapses the object and removes all facts.
+    state.add_new_heap_to_string_entry(heap_id.clone(), None, CharacterInclusionDomain::Top);
+    assert!(state.get_heap_to_string_map()[&heap_id].is_collapsed());
+    assert_eq!(
+        state.get_heap_string(&heap_id, Some(8)),
+        Some(CharacterInclusionDomain::Top)
+    );
+    assert!(state
+        .get_string_predicates(&field_pointer(8), &pi_state)
+        .is_top());
+    state.add_string_predicates(&field_pointer(8), &sanitized, &pi_state);
+    assert!(state
+        .get_string_predicates(&field_pointer(8), &pi_state)
+        .is_top());
+}
+
+#[test]
+fn test_array_summary() {
+    let mut state: State<CharacterInclusionDomain> =
+        State::mock_with_default_pi_state(Sub::mock("func"));
+    let stack_id = state
+        .get_pointer_inference_state()
+        .unwrap()
+        .stack_id
+        .clone();
+    let mut memory_image = RuntimeMemoryImage::mock();
+    memory_image.memory_segments.push(MemorySegment {
+        bytes: [0x00, 0x50, 0x00, 0x00, 0x0c, 0x50, 0x00, 0x00].to_vec(),
+        base_address: 0x8000,
+        read_flag: true,
+        write_flag: false,
+        execute_flag: false,
+    });
+    let element_range = |start, end| -> IntervalDomain { Interval::new(start, end, 4).into() };
+    let heap_id = AbstractIdentifier::new(
+        Tid::new("func"),
+        AbstractLocation::from_var(&variable!("r5:4")).unwrap(),
+    );
+    let heap_pointer: DataDomain<IntervalDomain> =
+        DataDomain::from_target(heap_id.clone(), bitvec!("0:4").into());
+    state.add_new_heap_to_string_entry(heap_id.clone(), Some(0), "ls".to_string().into());
+    state
+        .stack_offset_to_pointer_map
+        .insert(-8, heap_pointer.clone());
+    state
+        .stack_offset_to_pointer_map
+        .insert(-4, DataDomain::from(bitvec!("0x5000:4")));
+
+    // Load from a stack array with an unknown index.
+    let stack_array: DataDomain<IntervalDomain> = DataDomain::from_target(
+        stack_id.clone(),
+        element_range(bitvec!("-8:4"), bitvec!("-4:4")),
+    );
+    state.handle_array_load(
+        &Tid::new("load"),
+        &variable!("r0:4"),
+        &stack_array,
+        &memory_image,
+    );
+    let summary_id = AbstractIdentifier::new(
+        Tid::new("load"),
+        AbstractLocation::from_var(&variable!("r0:4")).unwrap(),
+    );
+    let pointer = &state.get_variable_to_pointer_map()[&variable!("r0:4")];
+    assert!(pointer.get_relative_values().contains_key(&heap_id));
+    assert!(pointer.get_relative_values().contains_key(&summary_id));
+    assert_eq!(
+        state.get_heap_string(&summary_id, Some(0)),
+        Some("/dev/sd%c%d".to_string().into())
+    );
+
+    // Load from a table of string literals in global memory.
+    let global_array: DataDomain<IntervalDomain> =
+        element_range(bitvec!("0x8000:4"), bitvec!("0x8004:4")).into();
+    state.handle_array_load(
+        &Tid::new("load"),
+        &variable!("r1:4"),
+        &global_array,
+        &memory_image,
+    );
+    let summary_id = AbstractIdentifier::new(
+        Tid::new("load"),
+        AbstractLocation::from_var(&variable!("r1:4")).unwrap(),
+    );
+    assert_eq!(
+        state.get_heap_string(&summary_id, Some(0)),
+        Some(
+            CharacterInclusionDomain::from("/dev/sd%c%d".to_string())
+                .merge(&"cat %s".to_string().into())
+        )
+    );
+
+    // Loads from exact addresses or from arrays not containing string literals are not summarized.
+    let exact_address = DataDomain::from_target(stack_id.clone(), bitvec!("-8:4").into());
+    state.handle_array_load(
+        &Tid::new("load"),
+        &variable!("r2:4"),
+        &exact_address,
+        &memory_image,
+    );
+    let byte_array: DataDomain<IntervalDomain> =
+        element_range(bitvec!("0x1000:4"), bitvec!("0x1004:4")).into();
+    state.handle_array_load(
+        &Tid::new("load"),
+        &variable!("r2:4"),
+        &byte_array,
+        &memory_image,
+    );
+    assert!(!state
+        .get_variable_to_pointer_map()
+        .contains_key(&variable!("r2:4")));
+
+    // Stores to an unknown index are weak updates.
+    let other_pointer = DataDomain::from(bitvec!("0x500c:4"));
+    state.weak_update_stack_array_elements(
+        &element_range(bitvec!("-8:4"), bitvec!("-4:4")),
+        &other_pointer,
+    );
+    assert_eq!(
+        state.stack_offset_to_pointer_map[&-8],
+        heap_pointer.merge(&other_pointer)
+    );
+    assert!(state.stack_offset_to_pointer_map[&-4]
+        .get_absolute_value()
+        .unwrap()
+        .try_to_bitvec()
+        .is_err());
+}
+
+#[test]
+fn test_state_order() {
+    let mut state: State<BricksDomain> = State::mock_with_default_pi_state(Sub::mock("func"));
+    let stack_id = state
+        .get_pointer_inference_state()
+        .unwrap()
+        .stack_id
+        .clone();
+    state.add_new_variable_to_pointer_entry(
+        variable!("r0:4"),
+        DataDomain::from_target(stack_id, IntervalDomain::from(bitvec!("-8:4"))),
+    );
+    state.add_new_stack_offset_to_string_entry(-8, BricksDomain::from("a".to_string()));
+    let mut other_state = state.clone();
+    other_state.add_new_stack_offset_to_string_entry(-8, BricksDomain::from("b".to_string()));
+    let merged_state = state.merge(&other_state);
+
+    assert!(state.less_or_equal(&merged_state));
+    assert!(other_state.less_or_equal(&merged_state));
+    assert!(!merged_state.less_or_equal(&state));
+    assert!(!state.less_or_equal(&other_state));
+    // States tracking strings at different locations are not compared.
+    let mut larger_state = merged_state.clone();
+    larger_state.add_new_stack_offset_to_string_entry(-16, BricksDomain::Top);
+    assert!(!merged_state.less_or_equal(&larger_state));
+}
+
+#[test]
+fn test_to_json_compact() {
+    let mut state: State<BricksDomain> = State::mock_with_default_pi_state(Sub::mock("func"));
+    let stack_id = AbstractIdentifier::new(
+        Tid::new("func"),
+        AbstractLocation::from_var(&variable!("sp:4")).unwrap(),
+    );
+    let heap_id = AbstractIdentifier::new(
+        Tid::new("malloc_call"),
+        AbstractLocation::from_var(&variable!("r0:4")).unwrap(),
+    );
+    let pointer: DataDomain<IntervalDomain> =
+        DataDomain::from_target(stack_id, Bitvector::from_i32(-8).into());
+    state.add_new_variable_to_pointer_entry(variable!("r0:4"), pointer.clone());
+    state.add_new_stack_offset_to_string_entry(-8, BricksDomain::from("cmd".to_string()));
+    state.add_new_heap_to_string_entry(
+        heap_id.clone(),
+        Some(4),
+        BricksDomain::from("user".to_string()),
+    );
+
+    let json = state.to_json_compact();
+    assert_eq!(json["register"]["r0"], pointer.to_json_compact());
+    assert_eq!(
+        json["stack_strings"]["-8"],
+        BricksDomain::from("cmd".to_string()).to_string()
+    );
+    assert_eq!(
+        json["heap_strings"][heap_id.to_string()],
+        serde_json::json!({
+            "collapsed": false,
+            "fields": {"4": BricksDomain::from("user".to_string()).to_string()},
+        })
+    );
+    assert_eq!(json["stack_pointers"], serde_json::json!({}));
+}
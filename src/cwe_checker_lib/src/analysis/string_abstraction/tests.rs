@@ -374,7 +374,7 @@ fn mock_block_with_function_call(
         "strcat" => mock_defs_for_strcat(*config.get(0).unwrap(), blk_num),
         "free" => vec![],
         "malloc" => mock_defs_for_malloc(blk_num),
-        "memcpy" => mock_defs_for_memcpy(*config.get(0).unwrap(), blk_num),
+        "memcpy" | "memmove" | "memset" => mock_defs_for_memcpy(*config.first().unwrap(), blk_num),
         _ => panic!("Invalid symbol name for def mock"),
     };
     blk.term.defs = defs;
@@ -436,3 +436,79 @@ pub fn mock_project_with_intraprocedural_control_flow(
     project.program.term = program;
     project
 }
+
+#[test]
+fn backward_slice_of_sink_calls() {
+    let project = mock_project_with_intraprocedural_control_flow(
+        vec![
+            (ExternSymbol::mock_sprintf_symbol_arm(), vec![true]),
+            (ExternSymbol::mock_strcat_symbol_arm(), vec![true]),
+        ],
+        "func",
+    );
+    let graph = crate::analysis::graph::get_program_cfg(&project.program);
+
+    let sink_nodes = super::get_call_nodes_of_symbols(&project, &graph, &["strcat".to_string()]);
+    assert_eq!(sink_nodes.len(), 1);
+    // The start and end nodes of the first two blocks.
+    let slice = super::get_backward_slice(&graph, &sink_nodes);
+    assert_eq!(slice.len(), 4);
+
+    let sink_nodes = super::get_call_nodes_of_symbols(&project, &graph, &["sprintf".to_string()]);
+    assert_eq!(super::get_backward_slice(&graph, &sink_nodes).len(), 2);
+    let sink_nodes = super::get_call_nodes_of_symbols(&project, &graph, &["system".to_string()]);
+    assert!(super::get_backward_slice(&graph, &sink_nodes).is_empty());
+}
+
+#[test]
+fn string_predicates_at_call() {
+    use super::{context::Context, state::State, StringAbstraction};
+    use crate::abstract_domain::{
+        AbstractDomain, CharacterInclusionDomain, DataDomain, PredicateDomain,
+    };
+    use crate::analysis::forward_interprocedural_fixpoint::create_computation;
+    use crate::analysis::graph::Node;
+    use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+    use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
+    use crate::{bitvec, variable};
+    use petgraph::visit::IntoNodeReferences;
+    use std::collections::HashMap;
+
+    let project = mock_project_with_intraprocedural_control_flow(
+        vec![(ExternSymbol::mock_strcat_symbol_arm(), vec![false])],
+        "func",
+    );
+    let pi_results = PointerInferenceComputation::mock(&project);
+    let call_tid = Tid::new("func_strcat_0");
+    let (blk_end_node, _) = pi_results
+        .get_graph()
+        .node_references()
+        .find(|(_, node)| matches!(node, Node::BlkEnd(blk, _) if blk.tid == Tid::new("block0")))
+        .unwrap();
+    let mut state: State<CharacterInclusionDomain> =
+        State::mock_with_default_pi_state(project.program.term.subs[&Tid::new("func")].clone());
+    let mut pi_state = state.get_pointer_inference_state().unwrap().clone();
+    let source = DataDomain::from_target(pi_state.stack_id.clone(), bitvec!("-8:4").into());
+    pi_state.set_register(&variable!("r1:4"), source.clone());
+    state.add_string_predicates(&source, &PredicateDomain::from_length_bound(16), &pi_state);
+    state.set_pointer_inference_state(Some(pi_state));
+
+    let context = Context::mock(&project, HashMap::new(), HashMap::new(), &pi_results);
+    let mut computation = create_computation(context, None);
+    computation.set_node_value(blk_end_node, NodeValue::Value(state));
+    let string_abstraction = StringAbstraction { computation };
+
+    let strcat = ExternSymbol::mock_strcat_symbol_arm();
+    assert_eq!(
+        string_abstraction
+            .get_string_predicates_at_call(&call_tid, &strcat.parameters[1])
+            .get_length_bound(),
+        Some(16)
+    );
+    assert!(string_abstraction
+        .get_string_predicates_at_call(&call_tid, &strcat.parameters[0])
+        .is_top());
+    assert!(string_abstraction
+        .get_string_predicates_at_call(&Tid::new("unknown_call"), &strcat.parameters[1])
+        .is_top());
+}
@@ -0,0 +1,470 @@
+//! Bounded symbolic execution for confirming CWE warnings.
+//!
+//! The checks report warnings based on over-approximating analyses, so the
+//! code location of a warning may be unreachable in practice. This module
+//! tries to find a witness for a warning: it enumerates a bounded number of
+//! acyclic paths through the function of the warning that start at the
+//! function entry and end at the term of the warning, executes the `Def` and
+//! `Jmp` terms along each path symbolically and checks the resulting path
+//! condition with an [SMT backend](smt::SmtBackend). If a path condition is
+//! satisfiable, the warning is marked as
+//! [confirmed](crate::utils::log::CweWarning::confirmed).
+//!
+//! If the warning contains a flow trace, i.e. `trace=<address>` entries in its
+//! `other` field, only paths that visit the blocks of the trace inside the
+//! function in the order of the trace are executed.
+//!
+//! The symbolic execution is intraprocedural. The values of all registers and
+//! memory at the function entry are free variables. Calls overwrite all
+//! registers except the callee-saved ones and the stack pointer with fresh
+//! variables and forget all stored values. Loads are only resolved if a store
+//! to a syntactically equal address precedes them on the path, otherwise
+//! the loaded value is a fresh variable. A confirmed warning is thus reachable
+//! for some entry state of its function, which need not be a reachable state
+//! of the whole program.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+
+use std::collections::{BTreeMap, HashMap};
+
+pub mod smt;
+use smt::{BoundedSearch, SatResult, SmtBackend, SmtLibSolver};
+
+/// The configuration of the witness validation.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// The names of the checks whose warnings are validated, e.g. `CWE476`.
+    pub checks: Vec<String>,
+    /// The maximal number of paths that are executed per warning.
+    #[serde(default = "default_max_paths")]
+    pub max_paths: usize,
+    /// The maximal number of blocks on a path.
+    #[serde(default = "default_max_path_length")]
+    pub max_path_length: usize,
+    /// The command of an external SMT solver that reads SMT-LIB 2 scripts from
+    /// its standard input, e.g. `["z3", "-in"]`.
+    ///
+    /// If not set, the built-in [bounded search](smt::BoundedSearch) is used.
+    #[serde(default)]
+    pub solver: Option<Vec<String>>,
+}
+
+fn default_max_paths() -> usize {
+    8
+}
+
+fn default_max_path_length() -> usize {
+    64
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            checks: Vec::new(),
+            max_paths: default_max_paths(),
+            max_path_length: default_max_path_length(),
+            solver: None,
+        }
+    }
+}
+
+/// Try to confirm the given warnings by symbolic execution and mark the
+/// confirmed warnings.
+///
+/// Only warnings of the checks listed in the configuration are validated.
+/// Returns log messages summarizing the validation.
+pub fn confirm_cwe_warnings<'a>(
+    project: &Project,
+    warnings: impl IntoIterator<Item = &'a mut CweWarning>,
+    config: &Config,
+) -> Vec<LogMessage> {
+    let backend: Box<dyn SmtBackend> = match &config.solver {
+        Some(command) => match SmtLibSolver::new(command.clone()) {
+            Ok(solver) => Box::new(solver),
+            Err(error) => {
+                return vec![LogMessage::new_error(error.to_string()).source("Witness Validation")]
+            }
+        },
+        None => Box::new(BoundedSearch::default()),
+    };
+    let mut logs = Vec::new();
+    let (mut validated, mut confirmed) = (0, 0);
+    for warning in warnings
+        .into_iter()
+        .filter(|warning| config.checks.contains(&warning.name))
+    {
+        validated += 1;
+        match find_witness(project, warning, backend.as_ref(), config) {
+            Ok(true) => {
+                warning.confirmed = true;
+                confirmed += 1;
+            }
+            Ok(false) => (),
+            Err(error) => {
+                logs.push(LogMessage::new_error(error.to_string()).source("Witness Validation"));
+                break;
+            }
+        }
+    }
+    logs.push(
+        LogMessage::new_debug(format!(
+            "Confirmed {confirmed} of {validated} validated CWE warnings"
+        ))
+        .source("Witness Validation"),
+    );
+    logs
+}
+
+/// Search for a path to the term of the warning with a satisfiable path condition.
+///
+/// Returns an error only if the SMT backend failed.
+fn find_witness(
+    project: &Project,
+    warning: &CweWarning,
+    backend: &dyn SmtBackend,
+    config: &Config,
+) -> Result<bool, Error> {
+    let Some((sub, target)) = find_target(project, warning) else {
+        return Ok(false);
+    };
+    let trace = warning.get_trace();
+    for path in enumerate_paths(
+        sub,
+        &target,
+        &trace,
+        config.max_paths,
+        config.max_path_length,
+    ) {
+        if let Some(path_condition) = get_path_condition(project, sub, &path, &target) {
+            if backend.check_sat(&path_condition)? == SatResult::Sat {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// The position of the term of a warning inside its function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Target {
+    /// The index of the block containing the term.
+    block_index: usize,
+    /// The TID of the term. Equals the block TID if the warning points to the
+    /// start of the block.
+    tid: Tid,
+}
+
+/// Find the term of the warning in the program.
+///
+/// The term is identified by the first TID of the warning or, if the warning
+/// has no TIDs, by its first address.
+fn find_target<'a>(project: &'a Project, warning: &CweWarning) -> Option<(&'a Sub, Target)> {
+    let matches = |tid: &Tid| match warning.tids.first() {
+        Some(warning_tid) => tid.to_string() == *warning_tid,
+        None => {
+            warning.get_address().is_some()
+                && u64::try_from(tid.address()).ok() == warning.get_address()
+        }
+    };
+    for sub in project.program.term.subs.values() {
+        for (block_index, block) in sub.term.blocks.iter().enumerate() {
+            let term_tids = block
+                .term
+                .defs
+                .iter()
+                .map(|def| &def.tid)
+                .chain(block.term.jmps.iter().map(|jmp| &jmp.tid));
+            if let Some(tid) = std::iter::once(&block.tid)
+                .chain(term_tids)
+                .find(|tid| matches(tid))
+            {
+                let target = Target {
+                    block_index,
+                    tid: tid.clone(),
+                };
+                return Some((&sub.term, target));
+            }
+        }
+    }
+    None
+}
+
+/// Check whether the path visits the blocks of the trace that are contained in
+/// the function in the order of the trace.
+fn follows_trace(sub: &Sub, path: &[usize], trace: &[&str]) -> bool {
+    let get_address = |block: &Term<Blk>| {
+        let address = block.tid.address();
+        (!address.is_unknown()).then(|| address.to_string())
+    };
+    let mut path_addresses = path
+        .iter()
+        .filter_map(|index| get_address(&sub.blocks[*index]));
+    trace
+        .iter()
+        .filter(|address| {
+            sub.blocks
+                .iter()
+                .any(|block| get_address(block).as_deref() == Some(**address))
+        })
+        .all(|address| path_addresses.any(|path_address| path_address == *address))
+}
+
+/// Enumerate acyclic paths of blocks from the entry block of the function to
+/// the block of the target that follow the given trace.
+///
+/// Paths are given as lists of block indices.
+fn enumerate_paths(
+    sub: &Sub,
+    target: &Target,
+    trace: &[&str],
+    max_paths: usize,
+    max_path_length: usize,
+) -> Vec<Vec<usize>> {
+    let block_indices: HashMap<&Tid, usize> = sub
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| (&block.tid, index))
+        .collect();
+    let successors: Vec<Vec<usize>> = sub
+        .blocks
+        .iter()
+        .map(|block| {
+            let mut successors: Vec<usize> = block
+                .term
+                .jmps
+                .iter()
+                .filter_map(|jmp| get_intraprocedural_target(&jmp.term))
+                .filter_map(|tid| block_indices.get(tid).copied())
+                .collect();
+            successors.dedup();
+            successors
+        })
+        .collect();
+
+    let mut paths = Vec::new();
+    if sub.blocks.is_empty() {
+        return paths;
+    }
+    // Depth-first search with a stack of paths.
+    let mut stack = vec![vec![0]];
+    while let Some(path) = stack.pop() {
+        let last = *path.last().unwrap();
+        if last == target.block_index {
+            if follows_trace(sub, &path, trace) {
+                paths.push(path);
+                if paths.len() >= max_paths {
+                    break;
+                }
+            }
+            continue;
+        }
+        if path.len() >= max_path_length {
+            continue;
+        }
+        for successor in successors[last].iter().rev() {
+            if !path.contains(successor) {
+                let mut extended_path = path.clone();
+                extended_path.push(*successor);
+                stack.push(extended_path);
+            }
+        }
+    }
+    paths
+}
+
+/// Get the TID of the block that the jump continues to inside its function.
+fn get_intraprocedural_target(jmp: &Jmp) -> Option<&Tid> {
+    match jmp {
+        Jmp::Branch(target) | Jmp::CBranch { target, .. } => Some(target),
+        Jmp::Call { return_, .. }
+        | Jmp::CallInd { return_, .. }
+        | Jmp::CallOther { return_, .. } => return_.as_ref(),
+        Jmp::BranchInd(_) | Jmp::Return(_) => None,
+    }
+}
+
+/// Execute the path symbolically and return its path condition.
+///
+/// Returns `None` if the path cannot be taken, e.g. because a block on the
+/// path is left by an unconditional jump to another block.
+fn get_path_condition(
+    project: &Project,
+    sub: &Sub,
+    path: &[usize],
+    target: &Target,
+) -> Option<Vec<Expression>> {
+    let mut state = State::new();
+    for (position, block_index) in path.iter().enumerate() {
+        let block = &sub.blocks[*block_index];
+        if block.tid == target.tid {
+            break;
+        }
+        for def in block.term.defs.iter() {
+            if def.tid == target.tid {
+                return Some(state.path_condition);
+            }
+            state.execute_def(&def.term);
+        }
+        let next_block = path.get(position + 1).map(|index| &sub.blocks[*index].tid);
+        for jmp in block.term.jmps.iter() {
+            if jmp.tid == target.tid {
+                return Some(state.path_condition);
+            }
+            match &jmp.term {
+                Jmp::CBranch { target, condition } => {
+                    let condition = state.eval_with_fresh_unknowns(condition);
+                    if Some(target) == next_block {
+                        state.path_condition.push(condition);
+                        break;
+                    }
+                    state.path_condition.push(Expression::UnOp {
+                        op: UnOpType::BoolNegate,
+                        arg: Box::new(condition),
+                    });
+                }
+                Jmp::Branch(target) if Some(target) == next_block => break,
+                Jmp::Call { return_, .. }
+                | Jmp::CallInd { return_, .. }
+                | Jmp::CallOther { return_, .. }
+                    if return_.as_ref() == next_block =>
+                {
+                    state.havoc_call(project);
+                    break;
+                }
+                _ => return None,
+            }
+        }
+    }
+    Some(state.path_condition)
+}
+
+/// The state of the symbolic execution.
+///
+/// All values are expressions over the free variables of the execution.
+#[derive(Clone, Debug, Default)]
+struct State {
+    /// The current values of the registers that differ from their values at
+    /// the function entry.
+    registers: BTreeMap<Variable, Expression>,
+    /// The stores on the path as pairs of address and stored value.
+    memory: Vec<(Expression, Expression)>,
+    /// The conditions of the branches taken on the path.
+    path_condition: Vec<Expression>,
+    /// The number of fresh variables created so far.
+    fresh_variable_count: usize,
+}
+
+impl State {
+    /// Create the state at the function entry.
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a fresh variable of the given size.
+    fn fresh_variable(&mut self, size: ByteSize) -> Expression {
+        self.fresh_variable_count += 1;
+        Expression::Var(Variable {
+            name: format!("$symbolic_{}", self.fresh_variable_count),
+            size,
+            is_temp: false,
+        })
+    }
+
+    /// Evaluate the expression in the state.
+    fn eval(&self, expression: &Expression) -> Expression {
+        let mut result = expression.clone();
+        self.substitute_registers(&mut result);
+        result.substitute_trivial_operations();
+        result
+    }
+
+    /// Replace the registers in the expression with their current values.
+    fn substitute_registers(&self, expression: &mut Expression) {
+        match expression {
+            Expression::Var(var) => {
+                if let Some(value) = self.registers.get(var) {
+                    *expression = value.clone();
+                }
+            }
+            Expression::Const(_) | Expression::Unknown { .. } => (),
+            Expression::BinOp { lhs, rhs, .. } => {
+                self.substitute_registers(lhs);
+                self.substitute_registers(rhs);
+            }
+            Expression::UnOp { arg, .. }
+            | Expression::Cast { arg, .. }
+            | Expression::Subpiece { arg, .. } => self.substitute_registers(arg),
+        }
+    }
+
+    /// Evaluate the expression and replace unknown subexpressions with fresh variables.
+    fn eval_with_fresh_unknowns(&mut self, expression: &Expression) -> Expression {
+        let mut result = self.eval(expression);
+        self.replace_unknowns(&mut result);
+        result
+    }
+
+    fn replace_unknowns(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Unknown { size, .. } => *expression = self.fresh_variable(*size),
+            Expression::Var(_) | Expression::Const(_) => (),
+            Expression::BinOp { lhs, rhs, .. } => {
+                self.replace_unknowns(lhs);
+                self.replace_unknowns(rhs);
+            }
+            Expression::UnOp { arg, .. }
+            | Expression::Cast { arg, .. }
+            | Expression::Subpiece { arg, .. } => self.replace_unknowns(arg),
+        }
+    }
+
+    /// Execute the `Def` term.
+    fn execute_def(&mut self, def: &Def) {
+        match def {
+            Def::Assign { var, value } => {
+                let value = self.eval_with_fresh_unknowns(value);
+                self.registers.insert(var.clone(), value);
+            }
+            Def::Load { var, address } => {
+                let address = self.eval(address);
+                let value = self
+                    .memory
+                    .iter()
+                    .rev()
+                    .find(|(stored_address, value)| {
+                        *stored_address == address && value.bytesize() == var.size
+                    })
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_else(|| self.fresh_variable(var.size));
+                self.registers.insert(var.clone(), value);
+            }
+            Def::Store { address, value } => {
+                let address = self.eval(address);
+                let value = self.eval_with_fresh_unknowns(value);
+                self.memory.push((address, value));
+            }
+        }
+    }
+
+    /// Overwrite the registers that a call may change with fresh variables
+    /// and forget all stored values.
+    fn havoc_call(&mut self, project: &Project) {
+        let callee_saved = project
+            .get_standard_calling_convention()
+            .map(|cconv| cconv.callee_saved_register.as_slice())
+            .unwrap_or_default();
+        for register in project.register_set.iter() {
+            if *register != project.stack_pointer_register && !callee_saved.contains(register) {
+                let value = self.fresh_variable(register.size);
+                self.registers.insert(register.clone(), value);
+            }
+        }
+        self.memory.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,544 @@
+//! Backends for checking the satisfiability of path conditions.
+//!
+//! A path condition is a list of boolean (i.e. one byte sized) expressions
+//! over the free variables of a symbolic execution. It is satisfiable if there
+//! exists an assignment of the free variables such that all expressions
+//! evaluate to a non-zero value.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// The result of a satisfiability check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SatResult {
+    /// A satisfying assignment exists.
+    Sat,
+    /// No satisfying assignment exists.
+    Unsat,
+    /// The backend could not decide the satisfiability.
+    Unknown,
+}
+
+/// A backend that checks the satisfiability of path conditions.
+pub trait SmtBackend {
+    /// Check whether all given constraints can be satisfied simultaneously.
+    ///
+    /// Returns an error if the backend failed, e.g. because an external
+    /// solver could not be executed.
+    fn check_sat(&self, constraints: &[Expression]) -> Result<SatResult, Error>;
+}
+
+/// A built-in backend that searches for a satisfying assignment by evaluating
+/// the constraints for a bounded number of candidate assignments.
+///
+/// The candidate values of each variable are zero, one, minus one and the
+/// constants referenced in the constraints together with their neighbours.
+/// The backend can only prove unsatisfiability for constraints without free
+/// variables. Otherwise it returns [`SatResult::Unknown`] if no satisfying
+/// assignment was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundedSearch {
+    /// The maximal number of assignments that are evaluated.
+    pub max_assignments: usize,
+}
+
+impl Default for BoundedSearch {
+    fn default() -> Self {
+        BoundedSearch {
+            max_assignments: 4096,
+        }
+    }
+}
+
+impl SmtBackend for BoundedSearch {
+    fn check_sat(&self, constraints: &[Expression]) -> Result<SatResult, Error> {
+        let variables: Vec<&Variable> = constraints
+            .iter()
+            .flat_map(|constraint| constraint.input_vars())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let constants: Vec<Bitvector> = constraints
+            .iter()
+            .filter_map(|constraint| constraint.referenced_constants())
+            .flatten()
+            .collect();
+        let candidates: Vec<Vec<Bitvector>> = variables
+            .iter()
+            .map(|var| get_candidate_values(var.size, &constants))
+            .collect();
+
+        let mut indices = vec![0; variables.len()];
+        for _ in 0..self.max_assignments {
+            let assignment: BTreeMap<&Variable, &Bitvector> = variables
+                .iter()
+                .zip(indices.iter())
+                .zip(candidates.iter())
+                .map(|((var, index), values)| (*var, &values[*index]))
+                .collect();
+            let mut satisfied = true;
+            for constraint in constraints {
+                match evaluate(constraint, &assignment) {
+                    Ok(value) if !value.is_zero() => (),
+                    Ok(_) => {
+                        satisfied = false;
+                        break;
+                    }
+                    Err(_) => return Ok(SatResult::Unknown),
+                }
+            }
+            if satisfied {
+                return Ok(SatResult::Sat);
+            } else if variables.is_empty() {
+                return Ok(SatResult::Unsat);
+            }
+            // Advance to the next assignment.
+            let mut position = 0;
+            loop {
+                if position == indices.len() {
+                    return Ok(SatResult::Unknown);
+                }
+                indices[position] += 1;
+                if indices[position] < candidates[position].len() {
+                    break;
+                }
+                indices[position] = 0;
+                position += 1;
+            }
+        }
+        Ok(SatResult::Unknown)
+    }
+}
+
+/// Get the candidate values of a variable with the given size.
+fn get_candidate_values(size: ByteSize, constants: &[Bitvector]) -> Vec<Bitvector> {
+    let one = Bitvector::one(size.into());
+    let mut values = vec![Bitvector::zero(size.into()), one.clone(), -one.clone()];
+    for constant in constants {
+        let constant = constant.clone().into_resize_unsigned(size);
+        for value in [&constant + &one, constant.clone() - &one, constant] {
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+    }
+    values
+}
+
+/// Evaluate the expression for the given assignment of its input variables.
+///
+/// Returns an error for unassigned variables and for operations that cannot
+/// be evaluated, e.g. float operations or operations on mismatching sizes.
+pub fn evaluate(
+    expression: &Expression,
+    assignment: &BTreeMap<&Variable, &Bitvector>,
+) -> Result<Bitvector, Error> {
+    use BinOpType::*;
+    match expression {
+        Expression::Var(var) => assignment
+            .get(var)
+            .map(|value| (*value).clone())
+            .ok_or_else(|| anyhow!("Unassigned variable {var}")),
+        Expression::Const(value) => Ok(value.clone()),
+        Expression::BinOp { op, lhs, rhs } => {
+            let lhs = evaluate(lhs, assignment)?;
+            let mut rhs = evaluate(rhs, assignment)?;
+            match op {
+                Piece => (),
+                IntLeft | IntRight | IntSRight if rhs.try_to_u64().is_err() => {
+                    // The shift amount is larger than any bitvector.
+                    rhs = Bitvector::from_u64(u64::MAX);
+                }
+                IntLeft | IntRight | IntSRight => (),
+                _ if lhs.width() != rhs.width() => {
+                    return Err(anyhow!("Operands of mismatching sizes"))
+                }
+                _ => (),
+            }
+            lhs.bin_op(*op, &rhs)
+        }
+        Expression::UnOp {
+            op: UnOpType::BoolNegate,
+            arg,
+        } => {
+            let value = evaluate(arg, assignment)?;
+            Ok(Bitvector::from_u8(value.is_zero() as u8))
+        }
+        Expression::UnOp { op, arg } => evaluate(arg, assignment)?.un_op(*op),
+        Expression::Cast { op, size, arg } => {
+            let value = evaluate(arg, assignment)?;
+            if matches!(op, CastOpType::IntZExt | CastOpType::IntSExt) && *size < value.bytesize() {
+                return Err(anyhow!("Extension to a smaller size"));
+            }
+            value.cast(*op, *size)
+        }
+        Expression::Subpiece {
+            low_byte,
+            size,
+            arg,
+        } => {
+            let value = evaluate(arg, assignment)?;
+            if *low_byte + *size > value.bytesize() {
+                return Err(anyhow!("Subpiece out of bounds"));
+            }
+            Ok(value.subpiece(*low_byte, *size))
+        }
+        Expression::Unknown { description, .. } => {
+            Err(anyhow!("Cannot evaluate unknown expression {description}"))
+        }
+    }
+}
+
+/// A backend that checks the satisfiability with an external SMT solver.
+///
+/// The constraints are translated to the SMT-LIB 2 theory of fixed-size
+/// bitvectors and passed to the standard input of the solver command,
+/// e.g. `z3 -in`. Constraints that cannot be translated (e.g. because they
+/// contain float operations) are reported as [`SatResult::Unknown`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmtLibSolver {
+    /// The solver executable followed by its arguments.
+    command: Vec<String>,
+}
+
+impl SmtLibSolver {
+    /// Create a new backend that runs the given solver command.
+    ///
+    /// Returns an error if the command is empty.
+    pub fn new(command: Vec<String>) -> Result<Self, Error> {
+        if command.is_empty() {
+            return Err(anyhow!("Empty SMT solver command"));
+        }
+        Ok(SmtLibSolver { command })
+    }
+}
+
+impl SmtBackend for SmtLibSolver {
+    fn check_sat(&self, constraints: &[Expression]) -> Result<SatResult, Error> {
+        let Ok(script) = to_smtlib(constraints) else {
+            return Ok(SatResult::Unknown);
+        };
+        let mut child = Command::new(&self.command[0])
+            .args(&self.command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context(format!("Could not run SMT solver {}", self.command[0]))?;
+        child.stdin.take().unwrap().write_all(script.as_bytes())?;
+        let output = child.wait_with_output()?;
+        match String::from_utf8_lossy(&output.stdout).lines().next() {
+            Some("sat") => Ok(SatResult::Sat),
+            Some("unsat") => Ok(SatResult::Unsat),
+            Some("unknown") => Ok(SatResult::Unknown),
+            _ => Err(anyhow!(
+                "Unexpected output of SMT solver {}",
+                self.command[0]
+            )),
+        }
+    }
+}
+
+/// An interactive session with an external SMT solver.
+///
+/// The solver process is kept running for the lifetime of the session,
+/// so that several queries over the same constraints can be checked
+/// incrementally. Variables are declared automatically on first use.
+pub struct SmtLibSession {
+    /// The solver process.
+    process: Child,
+    /// The standard input of the solver process.
+    input: ChildStdin,
+    /// The standard output of the solver process.
+    output: BufReader<ChildStdout>,
+    /// The variables declared so far.
+    declared_variables: BTreeSet<Variable>,
+}
+
+impl SmtLibSession {
+    /// Start a session with the given solver command.
+    pub fn new(command: &[String]) -> Result<Self, Error> {
+        let (program, arguments) = command
+            .split_first()
+            .ok_or_else(|| anyhow!("Empty SMT solver command"))?;
+        let mut process = Command::new(program)
+            .args(arguments)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context(format!("Could not run SMT solver {program}"))?;
+        let input = process.stdin.take().unwrap();
+        let output = BufReader::new(process.stdout.take().unwrap());
+        let mut session = SmtLibSession {
+            process,
+            input,
+            output,
+            declared_variables: BTreeSet::new(),
+        };
+        session.send("(set-logic QF_BV)\n")?;
+        Ok(session)
+    }
+
+    /// Add a constraint that has to evaluate to a non-zero value.
+    ///
+    /// Returns an error if the constraint has no SMT-LIB translation.
+    pub fn assert(&mut self, constraint: &Expression) -> Result<(), Error> {
+        let formula = self.declare_and_translate(constraint)?;
+        self.send(&format!("(assert {formula})\n"))
+    }
+
+    /// Check the satisfiability of all constraints added so far.
+    pub fn check_sat(&mut self) -> Result<SatResult, Error> {
+        self.send("(check-sat)\n")?;
+        self.read_sat_result()
+    }
+
+    /// Check the satisfiability of all constraints added so far together with
+    /// the given additional constraint, which is not kept for later queries.
+    pub fn check_sat_assuming(&mut self, constraint: &Expression) -> Result<SatResult, Error> {
+        let formula = self.declare_and_translate(constraint)?;
+        self.send(&format!(
+            "(push 1)\n(assert {formula})\n(check-sat)\n(pop 1)\n"
+        ))?;
+        self.read_sat_result()
+    }
+
+    /// Declare the yet undeclared variables of the constraint and translate it.
+    fn declare_and_translate(&mut self, constraint: &Expression) -> Result<String, Error> {
+        let formula = constraint_to_smtlib(constraint)?;
+        let mut declarations = String::new();
+        for var in constraint.input_vars() {
+            if self.declared_variables.insert(var.clone()) {
+                declarations += &declaration_to_smtlib(var);
+            }
+        }
+        self.send(&declarations)?;
+        Ok(formula)
+    }
+
+    /// Send commands to the solver.
+    fn send(&mut self, commands: &str) -> Result<(), Error> {
+        self.input.write_all(commands.as_bytes())?;
+        self.input.flush()?;
+        Ok(())
+    }
+
+    /// Read the answer of the solver to a `check-sat` command.
+    fn read_sat_result(&mut self) -> Result<SatResult, Error> {
+        let mut line = String::new();
+        self.output.read_line(&mut line)?;
+        match line.trim() {
+            "sat" => Ok(SatResult::Sat),
+            "unsat" => Ok(SatResult::Unsat),
+            "unknown" => Ok(SatResult::Unknown),
+            answer => Err(anyhow!("Unexpected answer of SMT solver: {answer}")),
+        }
+    }
+}
+
+impl Drop for SmtLibSession {
+    fn drop(&mut self) {
+        let _ = self.send("(exit)\n");
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Translate the constraints to an SMT-LIB 2 script that checks their satisfiability.
+///
+/// Boolean values are represented as one byte sized bitvectors.
+/// Returns an error for expressions that have no translation.
+pub fn to_smtlib(constraints: &[Expression]) -> Result<String, Error> {
+    let variables: BTreeSet<&Variable> = constraints
+        .iter()
+        .flat_map(|constraint| constraint.input_vars())
+        .collect();
+    let mut script = String::from("(set-logic QF_BV)\n");
+    for var in variables {
+        script += &declaration_to_smtlib(var);
+    }
+    for constraint in constraints {
+        script += &format!("(assert {})\n", constraint_to_smtlib(constraint)?);
+    }
+    script += "(check-sat)\n";
+    Ok(script)
+}
+
+/// Translate the declaration of a variable to an SMT-LIB 2 command.
+fn declaration_to_smtlib(var: &Variable) -> String {
+    format!(
+        "(declare-const |{}| (_ BitVec {}))\n",
+        var.name,
+        var.size.as_bit_length()
+    )
+}
+
+/// Translate a constraint to an SMT-LIB 2 formula that holds if the constraint
+/// evaluates to a non-zero value.
+fn constraint_to_smtlib(constraint: &Expression) -> Result<String, Error> {
+    Ok(format!(
+        "(not (= {} (_ bv0 {})))",
+        expression_to_smtlib(constraint)?,
+        constraint.bytesize().as_bit_length()
+    ))
+}
+
+/// Translate an expression to an SMT-LIB 2 bitvector term.
+fn expression_to_smtlib(expression: &Expression) -> Result<String, Error> {
+    use BinOpType::*;
+    let term = match expression {
+        Expression::Var(var) => format!("|{}|", var.name),
+        Expression::Const(value) => format!(
+            "(_ bv{} {})",
+            value
+                .try_to_u64()
+                .map_err(|_| anyhow!("Constant too large"))?,
+            value.width().to_usize()
+        ),
+        Expression::BinOp { op, lhs, rhs } => {
+            let width = lhs.bytesize().as_bit_length();
+            let lhs = expression_to_smtlib(lhs)?;
+            let rhs_width = rhs.bytesize().as_bit_length();
+            let rhs = expression_to_smtlib(rhs)?;
+            let bool_term =
+                |predicate: &str| format!("(ite ({predicate} {lhs} {rhs}) (_ bv1 8) (_ bv0 8))");
+            match op {
+                Piece => format!("(concat {lhs} {rhs})"),
+                IntEqual => bool_term("="),
+                IntNotEqual => bool_term("distinct"),
+                IntLess => bool_term("bvult"),
+                IntSLess => bool_term("bvslt"),
+                IntLessEqual => bool_term("bvule"),
+                IntSLessEqual => bool_term("bvsle"),
+                IntCarry => {
+                    format!("(ite (bvult (bvadd {lhs} {rhs}) {lhs}) (_ bv1 8) (_ bv0 8))")
+                }
+                IntAdd => format!("(bvadd {lhs} {rhs})"),
+                IntSub => format!("(bvsub {lhs} {rhs})"),
+                IntXOr | BoolXOr => format!("(bvxor {lhs} {rhs})"),
+                IntAnd | BoolAnd => format!("(bvand {lhs} {rhs})"),
+                IntOr | BoolOr => format!("(bvor {lhs} {rhs})"),
+                IntMult => format!("(bvmul {lhs} {rhs})"),
+                IntDiv => format!("(bvudiv {lhs} {rhs})"),
+                IntRem => format!("(bvurem {lhs} {rhs})"),
+                IntSDiv => format!("(bvsdiv {lhs} {rhs})"),
+                IntSRem => format!("(bvsrem {lhs} {rhs})"),
+                IntLeft | IntRight | IntSRight => {
+                    // The shift amount may have a different size than the shifted value.
+                    let rhs = match rhs_width.cmp(&width) {
+                        std::cmp::Ordering::Less => {
+                            format!("((_ zero_extend {}) {rhs})", width - rhs_width)
+                        }
+                        std::cmp::Ordering::Equal => rhs,
+                        std::cmp::Ordering::Greater => {
+                            format!("((_ extract {} 0) {rhs})", width - 1)
+                        }
+                    };
+                    let function = match op {
+                        IntLeft => "bvshl",
+                        IntRight => "bvlshr",
+                        _ => "bvashr",
+                    };
+                    format!("({function} {lhs} {rhs})")
+                }
+                IntSCarry | IntSBorrow | FloatEqual | FloatNotEqual | FloatLess
+                | FloatLessEqual | FloatAdd | FloatSub | FloatMult | FloatDiv => {
+                    return Err(anyhow!("No SMT-LIB translation for {op:?}"))
+                }
+            }
+        }
+        Expression::UnOp { op, arg } => {
+            let arg = expression_to_smtlib(arg)?;
+            match op {
+                UnOpType::IntNegate => format!("(bvnot {arg})"),
+                UnOpType::Int2Comp => format!("(bvneg {arg})"),
+                UnOpType::BoolNegate => format!("(ite (= {arg} (_ bv0 8)) (_ bv1 8) (_ bv0 8))"),
+                _ => return Err(anyhow!("No SMT-LIB translation for {op:?}")),
+            }
+        }
+        Expression::Cast { op, size, arg } => {
+            let extension = size.as_bit_length() as i64 - arg.bytesize().as_bit_length() as i64;
+            let arg = expression_to_smtlib(arg)?;
+            match op {
+                CastOpType::IntZExt if extension >= 0 => {
+                    format!("((_ zero_extend {extension}) {arg})")
+                }
+                CastOpType::IntSExt if extension >= 0 => {
+                    format!("((_ sign_extend {extension}) {arg})")
+                }
+                _ => return Err(anyhow!("No SMT-LIB translation for {op:?}")),
+            }
+        }
+        Expression::Subpiece {
+            low_byte,
+            size,
+            arg,
+        } => format!(
+            "((_ extract {} {}) {})",
+            (*low_byte + *size).as_bit_length() - 1,
+            low_byte.as_bit_length(),
+            expression_to_smtlib(arg)?
+        ),
+        Expression::Unknown { description, .. } => {
+            return Err(anyhow!("No SMT-LIB translation for {description}"))
+        }
+    };
+    Ok(term)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr;
+
+    fn equal(lhs: Expression, rhs: Expression) -> Expression {
+        Expression::BinOp {
+            op: BinOpType::IntEqual,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        }
+    }
+
+    #[test]
+    fn bounded_search() {
+        let backend = BoundedSearch::default();
+        let constraint = equal(expr!("RDI:8 + 0x1:8"), expr!("0x2a:8"));
+        assert_eq!(
+            backend
+                .check_sat(std::slice::from_ref(&constraint))
+                .unwrap(),
+            SatResult::Sat
+        );
+
+        let contradiction = Expression::UnOp {
+            op: UnOpType::BoolNegate,
+            arg: Box::new(constraint.clone()),
+        };
+        assert_eq!(
+            backend.check_sat(&[constraint, contradiction]).unwrap(),
+            SatResult::Unknown
+        );
+        let false_constraint = equal(expr!("0x1:8"), expr!("0x2:8"));
+        assert_eq!(
+            backend.check_sat(&[false_constraint]).unwrap(),
+            SatResult::Unsat
+        );
+    }
+
+    #[test]
+    fn smtlib_translation() {
+        let constraint = equal(expr!("RDI:8 + 0x1:8"), expr!("0x2a:8"));
+        assert_eq!(
+            to_smtlib(&[constraint]).unwrap(),
+            "(set-logic QF_BV)\n\
+             (declare-const |RDI| (_ BitVec 64))\n\
+             (assert (not (= (ite (= (bvadd |RDI| (_ bv1 64)) (_ bv42 64)) (_ bv1 8) (_ bv0 8)) (_ bv0 8))))\n\
+             (check-sat)\n"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use super::*;
+use crate::{def, expr, variable};
+
+fn equal(lhs: Expression, rhs: Expression) -> Expression {
+    Expression::BinOp {
+        op: BinOpType::IntEqual,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    }
+}
+
+/// Create a function whose block `target` is only reached if `condition` holds.
+///
+/// The entry block computes `RAX:8 = RDI:8 + 0x1:8` before branching.
+fn mock_sub(condition: Expression) -> Term<Sub> {
+    let mut entry = Blk::mock_with_tid("entry");
+    entry.term.defs = vec![def!["RAX:8 = RDI:8 + 0x1:8"]];
+    entry.term.jmps = vec![
+        Term {
+            tid: Tid::new("branch"),
+            term: Jmp::CBranch {
+                target: Tid::new("target"),
+                condition,
+            },
+        },
+        Term {
+            tid: Tid::new("fallthrough"),
+            term: Jmp::Branch(Tid::new("other")),
+        },
+    ];
+    let mut target = Blk::mock_with_tid("target");
+    target.term.defs = vec![def!["sink: RBX:8 = RAX:8"]];
+    target.term.jmps = vec![Term {
+        tid: Tid::new("return_target"),
+        term: Jmp::Return(expr!("RSP:8")),
+    }];
+    let mut other = Blk::mock_with_tid("other");
+    other.term.jmps = vec![Term {
+        tid: Tid::new("return_other"),
+        term: Jmp::Return(expr!("RSP:8")),
+    }];
+    let mut sub = Sub::mock("func");
+    sub.term.blocks = vec![entry, target, other];
+    sub
+}
+
+fn mock_project(sub: Term<Sub>) -> Project {
+    let mut project = Project::mock_x64();
+    project.program.term.subs.insert(sub.tid.clone(), sub);
+    project
+}
+
+fn mock_warning(tid: &str) -> CweWarning {
+    CweWarning::new("CWE476", "0.3", "Warning").tids(vec![tid.to_string()])
+}
+
+#[test]
+fn path_enumeration() {
+    let sub = mock_sub(expr!("ZF:1"));
+    let target = Target {
+        block_index: 1,
+        tid: Tid::new("sink"),
+    };
+    assert_eq!(
+        enumerate_paths(&sub.term, &target, &[], 8, 64),
+        vec![vec![0, 1]]
+    );
+    assert!(enumerate_paths(&sub.term, &target, &[], 8, 1).is_empty());
+
+    let path_condition = get_path_condition(&Project::mock_x64(), &sub.term, &[0, 1], &target);
+    assert_eq!(
+        path_condition,
+        Some(vec![Expression::Var(variable!("ZF:1"))])
+    );
+    // The entry block does not branch to the other block if the condition holds.
+    let target = Target {
+        block_index: 2,
+        tid: Tid::new("other"),
+    };
+    assert_eq!(
+        get_path_condition(&Project::mock_x64(), &sub.term, &[0, 2], &target),
+        Some(vec![Expression::UnOp {
+            op: UnOpType::BoolNegate,
+            arg: Box::new(expr!("ZF:1")),
+        }])
+    );
+}
+
+#[test]
+fn symbolic_state() {
+    let project = Project::mock_x64();
+    let mut state = State::new();
+    state.execute_def(&def!["RAX:8 = RDI:8 + 0x1:8"].term);
+    state.execute_def(&def!["Store at RSP:8 := RAX:8"].term);
+    state.execute_def(&def!["RCX:8 := Load from RSP:8"].term);
+    assert_eq!(state.eval(&expr!("RCX:8")), expr!("RDI:8 + 0x1:8"));
+
+    state.havoc_call(&project);
+    // Callee-saved registers and the stack pointer keep their values.
+    assert_eq!(state.eval(&expr!("RBX:8")), expr!("RBX:8"));
+    assert_eq!(state.eval(&expr!("RSP:8")), expr!("RSP:8"));
+    assert_ne!(state.eval(&expr!("RAX:8")), expr!("RDI:8 + 0x1:8"));
+    state.execute_def(&def!["RCX:8 := Load from RSP:8"].term);
+    assert_ne!(state.eval(&expr!("RCX:8")), expr!("RDI:8 + 0x1:8"));
+}
+
+#[test]
+fn warning_confirmation() {
+    let config = Config {
+        checks: vec!["CWE476".to_string()],
+        ..Config::default()
+    };
+    // The target block is reachable for `RDI == 0x29`.
+    let project = mock_project(mock_sub(equal(expr!("RAX:8"), expr!("0x2a:8"))));
+    let mut warnings = [mock_warning("sink"), mock_warning("unknown_tid")];
+    confirm_cwe_warnings(&project, warnings.iter_mut(), &config);
+    assert!(warnings[0].confirmed);
+    assert!(!warnings[1].confirmed);
+
+    // Warnings of other checks are not validated.
+    let mut warning = mock_warning("sink");
+    warning.name = "CWE190".to_string();
+    confirm_cwe_warnings(&project, std::iter::once(&mut warning), &config);
+    assert!(!warning.confirmed);
+
+    // The target block is unreachable.
+    let project = mock_project(mock_sub(equal(expr!("0x1:8"), expr!("0x2a:8"))));
+    let mut warning = mock_warning("sink");
+    confirm_cwe_warnings(&project, std::iter::once(&mut warning), &config);
+    assert!(!warning.confirmed);
+}
+
+#[test]
+fn trace_following() {
+    let block = |address: &str, jmps: Vec<Term<Jmp>>| {
+        let mut block = Blk::mock_with_tid("block");
+        block.tid = Tid::new_block(address, 0);
+        block.term.jmps = jmps;
+        block
+    };
+    let branch = |tid: &str, target: &str| Term {
+        tid: Tid::new(tid),
+        term: Jmp::Branch(Tid::new_block(target, 0)),
+    };
+    // The left block is unreachable, the right block is reachable.
+    let entry = block(
+        "1000",
+        vec![
+            Term {
+                tid: Tid::new("branch"),
+                term: Jmp::CBranch {
+                    target: Tid::new_block("1010", 0),
+                    condition: equal(expr!("0x1:8"), expr!("0x2a:8")),
+                },
+            },
+            branch("fallthrough", "1020"),
+        ],
+    );
+    let left = block("1010", vec![branch("left_to_sink", "1030")]);
+    let right = block("1020", vec![branch("right_to_sink", "1030")]);
+    let mut sink = block("1030", Vec::new());
+    sink.term.defs = vec![def!["sink: RBX:8 = RAX:8"]];
+    let mut sub = Sub::mock("func");
+    sub.term.blocks = vec![entry, left, right, sink];
+    let trace = |addresses: &[&str]| -> Vec<Vec<String>> {
+        vec![addresses
+            .iter()
+            .map(|address| format!("trace={}", Tid::new_block(*address, 0).address()))
+            .collect()]
+    };
+    let target = Target {
+        block_index: 3,
+        tid: Tid::new("sink"),
+    };
+    let left_warning = mock_warning("sink").other(trace(&["1010", "1030"]));
+    let left_trace = left_warning.get_trace();
+    assert_eq!(
+        enumerate_paths(&sub.term, &target, &left_trace, 8, 64),
+        vec![vec![0, 1, 3]]
+    );
+
+    let config = Config {
+        checks: vec!["CWE476".to_string()],
+        ..Config::default()
+    };
+    let project = mock_project(sub);
+    let mut warnings = [
+        mock_warning("sink"),
+        mock_warning("sink").other(trace(&["1020", "1030"])),
+        mock_warning("sink").other(trace(&["1010", "1030"])),
+        // Blocks of the trace outside of the function are ignored.
+        mock_warning("sink").other(trace(&["2000", "1020", "1030"])),
+    ];
+    confirm_cwe_warnings(&project, warnings.iter_mut(), &config);
+    assert!(warnings[0].confirmed);
+    assert!(warnings[1].confirmed);
+    assert!(!warnings[2].confirmed);
+    assert!(warnings[3].confirmed);
+}
@@ -10,12 +10,18 @@
 //! perform by implementing the [`TaintAnalysis`] trait. See its documentation
 //! for further information.
 
-use crate::abstract_domain::{AbstractDomain, HasTop, RegisterDomain, SizedDomain};
+use crate::abstract_domain::{
+    AbstractDomain, HasTop, NullabilityDomain, RegisterDomain, SizedDomain,
+};
 use crate::analysis::graph::Node as CfgNode;
+use crate::analysis::library_summaries::LibrarySummaries;
 use crate::analysis::pointer_inference::Data as PiData;
 use crate::analysis::{
-    forward_interprocedural_fixpoint,
+    fixpoint::Computation,
+    forward_interprocedural_fixpoint::{self, GeneralizedContext},
     graph::{Graph as Cfg, HasCfg},
+    interprocedural_fixpoint_generic::NodeValue,
+    seeding::EntryPointSeeding,
     vsa_results::{HasVsaResult, VsaResult},
 };
 use crate::intermediate_representation::*;
@@ -24,9 +30,12 @@ use crate::utils::debug::ToJsonCompact;
 use std::convert::AsRef;
 use std::fmt::Display;
 
+pub mod sanitizers;
 pub mod state;
 
+use sanitizers::Sanitizer;
 use state::State;
+use std::collections::BTreeMap;
 
 /// Trait representing the definition of a Taint Analysis.
 ///
@@ -87,6 +96,34 @@ pub trait TaintAnalysis<'a>: HasCfg<'a> + HasVsaResult<PiData> + AsRef<Project>
         }
     }
 
+    /// Returns the user-supplied annotations of sanitizer functions,
+    /// mapped by the names of the annotated functions.
+    ///
+    /// After calls to these functions the taint framework removes the
+    /// configured taint labels from the memory objects that the sanitized
+    /// parameter points to, see [`sanitizers::apply_sanitizers`].
+    ///
+    /// # Default
+    ///
+    /// Returns `None`, i.e., no sanitizers are known.
+    fn get_sanitizers(&self) -> Option<&BTreeMap<String, Sanitizer>> {
+        None
+    }
+
+    /// Returns the summaries of the functions of the shared libraries
+    /// that the analyzed binary imports.
+    ///
+    /// After calls to summarized library functions the taint of the parameters
+    /// that the function may return is added to the return register,
+    /// see [`propagate_returned_parameter_taint`].
+    ///
+    /// # Default
+    ///
+    /// Returns `None`, i.e., calls to library functions are opaque.
+    fn get_library_summaries(&self) -> Option<&LibrarySummaries> {
+        None
+    }
+
     /// Transition function for edges of type [`Call`].
     ///
     /// Corresponds to intra-program calls, i.e., the target function is
@@ -141,6 +178,8 @@ pub trait TaintAnalysis<'a>: HasCfg<'a> + HasVsaResult<PiData> + AsRef<Project>
     /// # Default
     ///
     /// Remove taint from non-callee-saved registers.
+    /// For summarized library functions the taint of returned parameters
+    /// is propagated to the return register.
     fn update_call_stub(&self, state: &State, call: &Term<Jmp>) -> Option<State> {
         match &call.term {
             Jmp::Call { target, .. } => {
@@ -152,11 +191,19 @@ pub trait TaintAnalysis<'a>: HasCfg<'a> + HasVsaResult<PiData> + AsRef<Project>
                     .get(target)
                     .expect("TA: BUG: Unable to find extern symbol for call.");
 
-                match self.update_extern_call(state, call, project, extern_symbol) {
-                    Some(new_state) if new_state.is_empty() => {
-                        self.handle_empty_state_out(&call.tid)
-                    }
-                    new_state_option => new_state_option,
+                let mut new_state = self.update_extern_call(state, call, project, extern_symbol)?;
+                if let Some(summaries) = self.get_library_summaries() {
+                    propagate_returned_parameter_taint(
+                        summaries,
+                        state,
+                        &mut new_state,
+                        extern_symbol,
+                    );
+                }
+                if new_state.is_empty() {
+                    self.handle_empty_state_out(&call.tid)
+                } else {
+                    Some(new_state)
                 }
             }
             Jmp::CallInd { .. } => self.update_call_generic(state, &call.tid, &None),
@@ -412,6 +459,92 @@ pub trait TaintAnalysis<'a>: HasCfg<'a> + HasVsaResult<PiData> + AsRef<Project>
     }
 }
 
+/// Set the start states of a taint computation at the entry points of the
+/// functions selected by the `seeding` strategy.
+///
+/// In the start states the integer parameter registers of the calling
+/// convention of the function are tainted, i.e., all parameters are assumed to
+/// be controlled by the caller. Functions without a known calling convention
+/// are skipped.
+pub fn seed_entry_points<'a, T: TaintAnalysis<'a>>(
+    computation: &mut Computation<GeneralizedContext<'a, T>>,
+    seeding: &EntryPointSeeding,
+) -> Result<(), Error> {
+    let project: &Project = computation.get_context().get_context().as_ref();
+    let graph = computation.get_graph();
+    let start_states: Vec<_> = seeding
+        .get_seed_nodes(project, graph)?
+        .into_values()
+        .filter_map(|node| {
+            let calling_convention = project
+                .get_specific_calling_convention(&graph[node].get_sub().term.calling_convention)?;
+            Some((
+                node,
+                State::new_with_tainted_parameters(calling_convention, TaintSource::Custom),
+            ))
+        })
+        .collect();
+    for (node, state) in start_states {
+        computation.set_node_value(node, NodeValue::Value(state));
+    }
+
+    Ok(())
+}
+
+/// Add the taint of the parameters that a summarized library function may return
+/// to the return register in the state after the call.
+///
+/// Only parameters passed in registers are considered.
+pub fn propagate_returned_parameter_taint(
+    summaries: &LibrarySummaries,
+    state_before_call: &State,
+    state_after_call: &mut State,
+    extern_symbol: &ExternSymbol,
+) {
+    let (Some(summary), Ok(return_register)) = (
+        summaries.get(extern_symbol),
+        extern_symbol.get_unique_return_register(),
+    ) else {
+        return;
+    };
+    let mut return_taint = state_after_call.get_register_taint(return_register);
+    for index in summary.returned_parameters.iter() {
+        if let Some(Arg::Register { expr, .. }) = extern_symbol.parameters.get(*index) {
+            if let Some(labels) = state_before_call.eval(expr).get_labels() {
+                return_taint.merge_with(&Taint::Tainted(return_register.size, labels));
+            }
+        }
+    }
+    if return_taint.is_tainted() {
+        state_after_call.set_register_taint(return_register, return_taint);
+    }
+}
+
+/// Remove the taint of a call to a sanitizer of the taint analysis from the state after the call.
+///
+/// Returns the result of [`TaintAnalysis::handle_empty_state_out`] if no taint remains.
+fn handle_sanitizer_calls<'a, T: TaintAnalysis<'a>>(
+    context: &T,
+    mut state: State,
+    call: &Term<Jmp>,
+) -> Option<State> {
+    let Some(sanitizers) = context.get_sanitizers() else {
+        return Some(state);
+    };
+    sanitizers::apply_sanitizers(
+        sanitizers,
+        &mut state,
+        context.vsa_result(),
+        <T as AsRef<Project>>::as_ref(context),
+        call,
+    );
+    if state.is_empty() {
+        context.handle_empty_state_out(&call.tid)
+    } else {
+        Some(state)
+    }
+}
+
 impl<'a, T: TaintAnalysis<'a>> forward_interprocedural_fixpoint::Context<'a> for T {
     type Value = State;
 
@@ -423,14 +556,22 @@ impl<'a, T: TaintAnalysis<'a>> forward_interprocedural_fixpoint::Context<'a> for
         state1.merge(state2)
     }
 
+    /// Refine the nullability of the taint by NULL pointer checks.
+    ///
+    /// Returns `None` if the branch cannot be taken because it contradicts
+    /// the known nullability of the taint.
     fn specialize_conditional(
         &self,
         state: &Self::Value,
-        _condition: &Expression,
-        _block_before_condition: &Term<Blk>,
-        _is_true: bool,
+        condition: &Expression,
+        block_before_condition: &Term<Blk>,
+        is_true: bool,
     ) -> Option<Self::Value> {
-        Some(state.clone())
+        let mut specialized_state = state.clone();
+        specialized_state
+            .specialize_by_null_check(condition, block_before_condition, is_true)
+            .ok()?;
+        Some(specialized_state)
     }
 
     fn update_call(
@@ -444,7 +585,8 @@ impl<'a, T: TaintAnalysis<'a>> forward_interprocedural_fixpoint::Context<'a> for
     }
 
     fn update_call_stub(&self, state: &Self::Value, call: &Term<Jmp>) -> Option<Self::Value> {
-        <Self as TaintAnalysis>::update_call_stub(self, state, call)
+        let new_state = <Self as TaintAnalysis>::update_call_stub(self, state, call)?;
+        handle_sanitizer_calls(self, new_state, call)
     }
 
     fn update_jump(
@@ -475,19 +617,134 @@ impl<'a, T: TaintAnalysis<'a>> forward_interprocedural_fixpoint::Context<'a> for
         return_term: &Term<Jmp>,
         calling_convention: &Option<String>,
     ) -> Option<State> {
-        <Self as TaintAnalysis>::update_return(
+        let new_state = <Self as TaintAnalysis>::update_return(
             self,
             state_before_return,
             state_before_call,
             call_term,
             return_term,
             calling_convention,
-        )
+        )?;
+        handle_sanitizer_calls(self, new_state, call_term)
+    }
+}
+
+/// Allocation functions that never return NULL,
+/// e.g. because they terminate the program if the allocation fails.
+const NON_NULL_ALLOCATORS: [&str; 6] = [
+    "operator.new",
+    "operator.new[]",
+    "xmalloc",
+    "xcalloc",
+    "g_malloc",
+    "g_malloc0",
+];
+
+/// Get the nullability of pointers returned by the given extern symbol.
+///
+/// Allocation functions that never return NULL return *NotNull* pointers,
+/// all other functions may return NULL.
+pub fn get_return_value_nullability(symbol: &ExternSymbol) -> NullabilityDomain {
+    if NON_NULL_ALLOCATORS.contains(&symbol.name.as_str()) {
+        NullabilityDomain::NotNull
+    } else {
+        NullabilityDomain::MaybeNull
+    }
+}
+
+/// A source of tainted values.
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Default,
+)]
+pub enum TaintSource {
+    /// Environment variables, e.g. returned by `getenv`.
+    Environment,
+    /// Data received over the network, e.g. by `recv`.
+    Network,
+    /// Data read from files, e.g. by `fread`.
+    File,
+    /// The program arguments, e.g. the argument vector of `main` or values returned by `getopt`.
+    ProgramArguments,
+    /// Any other source defined by the taint analysis, e.g. the return value of a checked function.
+    #[default]
+    Custom,
+}
+
+impl TaintSource {
+    /// All taint sources.
+    pub const ALL: [TaintSource; 5] = [
+        TaintSource::Environment,
+        TaintSource::Network,
+        TaintSource::File,
+        TaintSource::ProgramArguments,
+        TaintSource::Custom,
+    ];
+}
+
+impl Display for TaintSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TaintSource::Environment => "env",
+            TaintSource::Network => "network",
+            TaintSource::File => "file",
+            TaintSource::ProgramArguments => "argv",
+            TaintSource::Custom => "custom",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The set of sources that a tainted value may originate from.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct TaintLabels(u8);
+
+impl TaintLabels {
+    /// Returns `true` if the value may originate from the given source.
+    pub fn contains(&self, source: TaintSource) -> bool {
+        self.0 & Self::from(source).0 != 0
+    }
+
+    /// Get the set of sources that either `self` or `other` may originate from.
+    pub fn union(&self, other: &Self) -> Self {
+        TaintLabels(self.0 | other.0)
+    }
+
+    /// Get the set of sources contained in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        TaintLabels(self.0 & !other.0)
+    }
+
+    /// Returns `true` if the set contains no sources.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over the sources contained in the set.
+    pub fn iter(&self) -> impl Iterator<Item = TaintSource> + '_ {
+        TaintSource::ALL
+            .into_iter()
+            .filter(|source| self.contains(*source))
+    }
+}
+
+impl From<TaintSource> for TaintLabels {
+    fn from(source: TaintSource) -> Self {
+        TaintLabels(1 << source as u8)
+    }
+}
+
+impl Display for TaintLabels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sources: Vec<String> = self.iter().map(|source| source.to_string()).collect();
+        write!(f, "{}", sources.join(","))
     }
 }
 
 /// An abstract domain representing a value that is either tainted or not.
 ///
+/// Tainted values are labeled with the sources they may originate from,
+/// so that checks can apply different policies for different sources.
+///
 /// Note that the [merge](Taint::merge)-function does not respect the partial
 /// order that is implied by the naming scheme of the variants! In fact, the
 /// whole analysis does not enforce any partial order for this domain. This
@@ -496,8 +753,9 @@ impl<'a, T: TaintAnalysis<'a>> forward_interprocedural_fixpoint::Context<'a> for
 /// whether a value should be tainted or not.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Taint {
-    /// A tainted value of a particular bytesize.
-    Tainted(ByteSize),
+    /// A tainted value of a particular bytesize
+    /// together with the sources it may originate from.
+    Tainted(ByteSize, TaintLabels),
     /// An untainted value of a particular bytesize.
     Top(ByteSize),
 }
@@ -506,7 +764,7 @@ impl Display for Taint {
     /// Print the value of a `Taint` object.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Tainted(size) => write!(f, "Tainted:{size}"),
+            Self::Tainted(size, labels) => write!(f, "Tainted:{size}({labels})"),
             Self::Top(size) => write!(f, "Top:{size}"),
         }
     }
@@ -514,24 +772,32 @@ impl Display for Taint {
 
 impl AbstractDomain for Taint {
     /// The result of merging two `Taint` values is tainted if at least one input was tainted.
+    /// The sources of the result are the sources of both inputs.
     fn merge(&self, other: &Self) -> Self {
         use Taint::*;
         match (self, other) {
-            (Tainted(size), _) | (_, Tainted(size)) => Tainted(*size),
+            (Tainted(size, labels), Tainted(_, other_labels)) => {
+                Tainted(*size, labels.union(other_labels))
+            }
+            (Tainted(size, labels), _) | (_, Tainted(size, labels)) => Tainted(*size, *labels),
             _ => Top(self.bytesize()),
         }
     }
 
     /// Replaces `self` with `other` iff `self` is untainted and `other` is
-    /// tainted.
+    /// tainted. Adds the sources of `other` if both are tainted.
     ///
     /// No change to `self` is required in the other cases.
     fn merge_with(&mut self, other: &Self) -> &mut Self {
         use Taint::*;
 
-        if let (Top(_), Tainted(_)) = (&self, other) {
-            *self = *other;
-        };
+        match (&self, other) {
+            (Top(_), Tainted(..)) => *self = *other,
+            (Tainted(size, labels), Tainted(_, other_labels)) => {
+                *self = Tainted(*size, labels.union(other_labels))
+            }
+            _ => (),
+        }
 
         self
     }
@@ -546,7 +812,7 @@ impl SizedDomain for Taint {
     /// The size in bytes of the `Taint` value.
     fn bytesize(&self) -> ByteSize {
         match self {
-            Self::Tainted(size) | Self::Top(size) => *size,
+            Self::Tainted(size, _) | Self::Top(size) => *size,
         }
     }
 
@@ -565,13 +831,13 @@ impl HasTop for Taint {
 
 impl RegisterDomain for Taint {
     /// The result of a binary operation is tainted if at least one input value
-    /// was tainted.
+    /// was tainted. Its sources are the sources of both inputs.
     fn bin_op(&self, op: BinOpType, rhs: &Self) -> Self {
-        match (self, rhs) {
-            (Self::Tainted(_), _) | (_, Self::Tainted(_)) => {
-                Self::Tainted(self.bin_op_bytesize(op, rhs))
-            }
-            _ => Self::Top(self.bin_op_bytesize(op, rhs)),
+        let size = self.bin_op_bytesize(op, rhs);
+        match (self.get_labels(), rhs.get_labels()) {
+            (Some(labels), Some(rhs_labels)) => Self::Tainted(size, labels.union(&rhs_labels)),
+            (Some(labels), None) | (None, Some(labels)) => Self::Tainted(size, labels),
+            (None, None) => Self::Top(size),
         }
     }
 
@@ -582,8 +848,8 @@ impl RegisterDomain for Taint {
 
     /// A subpiece of a tainted value is again tainted.
     fn subpiece(&self, _low_byte: ByteSize, size: ByteSize) -> Self {
-        if let Self::Tainted(_) = self {
-            Self::Tainted(size)
+        if let Self::Tainted(_, labels) = self {
+            Self::Tainted(size, *labels)
         } else {
             Self::Top(size)
         }
@@ -591,8 +857,8 @@ impl RegisterDomain for Taint {
 
     /// The result of a cast operation is tainted if the input was tainted.
     fn cast(&self, _kind: CastOpType, width: ByteSize) -> Self {
-        if let Self::Tainted(_) = self {
-            Self::Tainted(width)
+        if let Self::Tainted(_, labels) = self {
+            Self::Tainted(width, *labels)
         } else {
             Self::Top(width)
         }
@@ -606,9 +872,35 @@ impl ToJsonCompact for Taint {
 }
 
 impl Taint {
+    /// Create a tainted value of the given bytesize originating from the given source.
+    pub fn new_tainted(size: ByteSize, source: TaintSource) -> Self {
+        Taint::Tainted(size, source.into())
+    }
+
     /// Checks whether the given value is in fact tainted.
     pub fn is_tainted(&self) -> bool {
-        matches!(self, Taint::Tainted(_))
+        matches!(self, Taint::Tainted(..))
+    }
+
+    /// Remove the given sources from the sources of the value.
+    ///
+    /// The value becomes untainted if no sources remain.
+    pub fn remove_labels(&self, labels: &TaintLabels) -> Self {
+        match self {
+            Taint::Tainted(size, own_labels) if !own_labels.difference(labels).is_empty() => {
+                Taint::Tainted(*size, own_labels.difference(labels))
+            }
+            _ => Taint::Top(self.bytesize()),
+        }
+    }
+
+    /// Get the sources that the value may originate from
+    /// or `None` if the value is not tainted.
+    pub fn get_labels(&self) -> Option<TaintLabels> {
+        match self {
+            Taint::Tainted(_, labels) => Some(*labels),
+            Taint::Top(_) => None,
+        }
     }
 }
 
@@ -622,7 +914,7 @@ mod tests {
 
     #[test]
     fn abstract_domain() {
-        let taint = Taint::Tainted(ByteSize::new(4));
+        let taint = Taint::new_tainted(ByteSize::new(4), TaintSource::Custom);
         let top = Taint::Top(ByteSize::new(4));
 
         assert_eq!(taint.merge(&top), taint);
@@ -634,8 +926,8 @@ mod tests {
     fn register_domain() {
         use crate::intermediate_representation::*;
 
-        let taint = Taint::Tainted(ByteSize::new(4));
-        let taint_8 = Taint::Tainted(ByteSize::new(8));
+        let taint = Taint::new_tainted(ByteSize::new(4), TaintSource::Custom);
+        let taint_8 = Taint::new_tainted(ByteSize::new(8), TaintSource::Custom);
         let top = Taint::Top(ByteSize::new(4));
 
         assert_eq!(taint.bin_op(BinOpType::IntAdd, &top), taint);
@@ -0,0 +1,152 @@
+//! User-supplied annotations of sanitizer functions.
+//!
+//! Programs often validate untrusted input in functions of their own,
+//! e.g. a `validate_path` function that rejects paths containing `..` components.
+//! Users can declare such functions in the configuration of a taint-based check.
+//! After a call to an annotated function the taint framework removes the configured
+//! taint labels from the memory objects that the sanitized parameter points to.
+
+use super::{state::State, TaintLabels, TaintSource};
+use crate::abstract_domain::AbstractLocation;
+use crate::analysis::pointer_inference::Data as PiData;
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// The annotation of a function that validates one of its parameters.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Sanitizer {
+    /// The index of the parameter pointing to the validated data.
+    pub parameter_index: usize,
+    /// The taint sources whose taint is removed by the function.
+    /// If empty, the taint of all sources is removed.
+    #[serde(default)]
+    pub labels: Vec<TaintSource>,
+    /// A description of what the function validates, e.g. "path traversal".
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Sanitizer {
+    /// Get the set of taint labels removed by the sanitizer.
+    pub fn get_removed_labels(&self) -> TaintLabels {
+        let labels: &[TaintSource] = if self.labels.is_empty() {
+            &TaintSource::ALL
+        } else {
+            &self.labels
+        };
+        labels.iter().fold(TaintLabels::default(), |set, label| {
+            set.union(&(*label).into())
+        })
+    }
+}
+
+/// Remove the taint of a call to an annotated sanitizer function from the state.
+///
+/// The sanitizers are given by the names of the annotated functions,
+/// which may be internal functions or extern symbols.
+/// Calls to functions without annotation do not change the state.
+pub fn apply_sanitizers(
+    sanitizers: &BTreeMap<String, Sanitizer>,
+    state: &mut State,
+    vsa_result: &impl VsaResult<ValueDomain = PiData>,
+    project: &Project,
+    call: &Term<Jmp>,
+) {
+    let Jmp::Call { target, .. } = &call.term else {
+        return;
+    };
+    let pointer = if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+        let Some(sanitizer) = sanitizers.get(&symbol.name) else {
+            return;
+        };
+        symbol
+            .parameters
+            .get(sanitizer.parameter_index)
+            .and_then(|param| vsa_result.eval_parameter_arg_at_call(&call.tid, param))
+            .map(|pointer| (sanitizer, pointer))
+    } else if let Some(sub) = project.program.term.subs.get(target) {
+        let Some(sanitizer) = sanitizers.get(&sub.term.name) else {
+            return;
+        };
+        project
+            .get_specific_calling_convention(&sub.term.calling_convention)
+            .and_then(|cconv| {
+                cconv
+                    .integer_parameter_register
+                    .get(sanitizer.parameter_index)
+            })
+            .and_then(|register| {
+                vsa_result.eval_parameter_location_at_call(
+                    &call.tid,
+                    &AbstractLocation::Register(register.clone()),
+                )
+            })
+            .map(|pointer| (sanitizer, pointer))
+    } else {
+        None
+    };
+    if let Some((sanitizer, pointer)) = pointer {
+        state.remove_labels_from_memory_objects(&pointer, sanitizer.get_removed_labels());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::{AbstractIdentifier, IntervalDomain};
+    use crate::analysis::taint::Taint;
+    use crate::variable;
+
+    #[test]
+    fn remove_labels_from_memory() {
+        let mut state = State::new_empty();
+        let object = AbstractIdentifier::from_var(Tid::new("call"), &variable!("RAX:8"));
+        let pointer = |offset: i64| {
+            PiData::from_target(
+                object.clone(),
+                IntervalDomain::from(Bitvector::from_i64(offset)),
+            )
+        };
+        state.save_taint_to_memory(
+            &pointer(0),
+            Taint::new_tainted(ByteSize::new(8), TaintSource::Network),
+        );
+        state.save_taint_to_memory(
+            &pointer(8),
+            Taint::new_tainted(ByteSize::new(8), TaintSource::Environment),
+        );
+        let sanitizer = Sanitizer {
+            parameter_index: 0,
+            labels: vec![TaintSource::Network],
+            description: String::new(),
+        };
+        state.remove_labels_from_memory_objects(&pointer(0), sanitizer.get_removed_labels());
+        assert!(!state.check_if_address_points_to_taint(pointer(0)));
+        assert!(state.check_if_address_points_to_taint(pointer(8)));
+        state.remove_labels_from_memory_objects(&pointer(0), TaintSource::Environment.into());
+        assert!(!state.has_memory_taint());
+    }
+
+    #[test]
+    fn removed_labels() {
+        let sanitizer: Sanitizer = serde_json::from_value(serde_json::json!({
+            "parameter_index": 1,
+            "labels": ["Network", "File"],
+            "description": "path traversal"
+        }))
+        .unwrap();
+        let labels = sanitizer.get_removed_labels();
+        assert!(labels.contains(TaintSource::Network));
+        assert!(labels.contains(TaintSource::File));
+        assert!(!labels.contains(TaintSource::Environment));
+
+        let sanitizer: Sanitizer =
+            serde_json::from_value(serde_json::json!({ "parameter_index": 0 })).unwrap();
+        let labels = sanitizer.get_removed_labels();
+        assert!(TaintSource::ALL
+            .into_iter()
+            .all(|label| labels.contains(label)));
+    }
+}
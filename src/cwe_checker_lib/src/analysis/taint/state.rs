@@ -5,8 +5,8 @@
 
 use crate::abstract_domain::AbstractLocation;
 use crate::abstract_domain::{
-    AbstractDomain, AbstractIdentifier, IntervalDomain, MemRegion, RegisterDomain, SizedDomain,
-    TryToBitvec,
+    AbstractDomain, AbstractIdentifier, IntervalDomain, MemRegion, NullabilityDomain,
+    RegisterDomain, SizedDomain, TryToBitvec,
 };
 use crate::analysis::graph::NodeIndex;
 use crate::analysis::pointer_inference::Data as PiData;
@@ -17,7 +17,7 @@ use crate::utils::debug::ToJsonCompact;
 
 use std::collections::BTreeMap;
 
-use super::Taint;
+use super::{Taint, TaintLabels, TaintSource};
 
 mod memory_taint;
 mod register_taint;
@@ -33,6 +33,11 @@ pub struct State {
     register_taint: RegisterTaint,
     /// The Taint contained in memory objects
     memory_taint: MemoryTaint,
+    /// Whether the tainted values may be NULL pointers.
+    ///
+    /// Analyses whose taint source is a returned pointer use this to track the
+    /// outcome of NULL pointer checks. It is *Top* for all other analyses.
+    nullability: NullabilityDomain,
 }
 
 impl ToJsonCompact for State {
@@ -55,15 +60,21 @@ impl ToJsonCompact for State {
 
         state_map.insert("registers".into(), register_taint);
         state_map.insert("memory".into(), memory_taint);
+        if !self.nullability.is_top() {
+            state_map.insert("nullability".into(), self.nullability.to_string().into());
+        }
 
         serde_json::Value::Object(state_map)
     }
 }
 
 impl PartialEq for State {
-    /// Two states are equal if the same values are tainted in both states.
+    /// Two states are equal if the same values are tainted in both states
+    /// and the tainted values have the same nullability.
     fn eq(&self, other: &Self) -> bool {
-        self.register_taint == other.register_taint && self.memory_taint == other.memory_taint
+        self.register_taint == other.register_taint
+            && self.memory_taint == other.memory_taint
+            && self.nullability == other.nullability
     }
 }
 
@@ -83,6 +94,7 @@ impl AbstractDomain for State {
     fn merge_with(&mut self, other: &Self) -> &mut Self {
         self.register_taint.merge_with(&other.register_taint);
         self.memory_taint.merge_with(&other.memory_taint);
+        self.nullability = self.nullability.merge(&other.nullability);
 
         self
     }
@@ -99,19 +111,20 @@ impl State {
         Self {
             register_taint: RegisterTaint::new(),
             memory_taint: MemoryTaint::new(),
+            nullability: NullabilityDomain::Top,
         }
     }
 
     /// Returns a state where only return values of the extern call are tainted.
+    ///
+    /// The taint is labeled with the given `label`.
     pub fn new_return(
         taint_source: &ExternSymbol,
+        label: TaintSource,
         vsa_result: &impl VsaResult<ValueDomain = PiData>,
         return_node: NodeIndex,
     ) -> Self {
-        let mut state = Self {
-            register_taint: RegisterTaint::new(),
-            memory_taint: MemoryTaint::new(),
-        };
+        let mut state = Self::new_empty();
 
         for return_arg in taint_source.return_values.iter() {
             match return_arg {
@@ -119,12 +132,12 @@ impl State {
                     for var in expr.input_vars() {
                         state
                             .register_taint
-                            .insert(var.clone(), Taint::Tainted(var.size));
+                            .insert(var.clone(), Taint::new_tainted(var.size, label));
                     }
                 }
                 Arg::Stack { address, size, .. } => {
                     if let Some(address) = vsa_result.eval_at_node(return_node, address) {
-                        state.save_taint_to_memory(&address, Taint::Tainted(*size));
+                        state.save_taint_to_memory(&address, Taint::new_tainted(*size, label));
                     }
                 }
             }
@@ -133,18 +146,35 @@ impl State {
         state
     }
 
+    /// Returns a state where only the integer parameter registers of the given
+    /// calling convention are tainted.
+    ///
+    /// Used as the start state of functions whose parameters are controlled by
+    /// the caller, e.g., exported functions of shared libraries.
+    /// The taint is labeled with the given source.
+    pub fn new_with_tainted_parameters(
+        calling_convention: &CallingConvention,
+        source: TaintSource,
+    ) -> Self {
+        let mut state = Self::new_empty();
+        for register in calling_convention.integer_parameter_register.iter() {
+            state
+                .register_taint
+                .insert(register.clone(), Taint::new_tainted(register.size, source));
+        }
+
+        state
+    }
+
     /// Evaluate whether the result of the given expression is tainted in the
     /// current state.
     pub fn eval(&self, expression: &Expression) -> Taint {
         match expression {
             Expression::Const(_) => Taint::Top(expression.bytesize()),
-            Expression::Var(var) => {
-                if self.register_taint.get(var).is_some() {
-                    Taint::Tainted(var.size)
-                } else {
-                    Taint::Top(var.size)
-                }
-            }
+            Expression::Var(var) => match self.register_taint.get(var) {
+                Some(Taint::Tainted(_, labels)) => Taint::Tainted(var.size, *labels),
+                _ => Taint::Top(var.size),
+            },
             Expression::BinOp { op, lhs, rhs } => {
                 let lhs_taint = self.eval(lhs);
                 let rhs_taint = self.eval(rhs);
@@ -220,6 +250,21 @@ impl State {
         }
     }
 
+    /// Remove the given taint labels from all values in the memory objects
+    /// that the given address may point to.
+    ///
+    /// Values without remaining labels become untainted.
+    pub fn remove_labels_from_memory_objects(&mut self, address: &PiData, labels: TaintLabels) {
+        for mem_id in address.get_relative_values().keys() {
+            if let Some(mem_region) = self.memory_taint.get_mut(mem_id) {
+                for taint in mem_region.values_mut() {
+                    *taint = taint.remove_labels(&labels);
+                }
+                mem_region.clear_top_values();
+            }
+        }
+    }
+
     /// Remove all knowledge about taints contained in memory objects.
     pub fn remove_all_memory_taints(&mut self) {
         self.memory_taint = MemoryTaint::new();
@@ -271,6 +316,25 @@ impl State {
             })
     }
 
+    /// Get the distance of the location that the given address points to
+    /// from the nearest tainted value at or before it in the same memory object.
+    ///
+    /// Returns `None` if the address has no unique exact target
+    /// or if no tainted value precedes the target in its memory object.
+    pub fn get_offset_to_taint(&self, address: &PiData) -> Option<i64> {
+        let (mem_id, offset) = get_if_unique_target(address)?;
+        let offset = offset.try_to_offset().ok()?;
+        let (taint_offset, _) = self
+            .memory_taint
+            .get(mem_id)?
+            .entry_map()
+            .range(..=offset)
+            .rev()
+            .find(|(_, taint)| taint.is_tainted())?;
+
+        Some(offset - taint_offset)
+    }
+
     /// Check if any register in the given register list contains taint.
     ///
     /// If `POINTER_TAINT` is selected, pointers to tainted memory are
@@ -431,7 +495,7 @@ impl State {
     pub fn has_register_taint(&self) -> bool {
         self.register_taint
             .iter()
-            .any(|(_, taint)| matches!(*taint, Taint::Tainted(_)))
+            .any(|(_, taint)| matches!(*taint, Taint::Tainted(..)))
     }
 
     /// Check whether there is any tainted memory in the state.
@@ -445,6 +509,63 @@ impl State {
             .any(|(_, taint)| taint.is_tainted())
     }
 
+    /// Get the nullability of the tainted values.
+    pub fn get_nullability(&self) -> NullabilityDomain {
+        self.nullability
+    }
+
+    /// Set the nullability of the tainted values.
+    ///
+    /// Setting a value other than *Top* enables the refinement of the
+    /// nullability by [NULL pointer checks](State::specialize_by_null_check).
+    pub fn set_nullability(&mut self, nullability: NullabilityDomain) {
+        self.nullability = nullability;
+    }
+
+    /// Refine the nullability of the tainted values by the outcome of the
+    /// conditional jump on `condition` at the end of `block`.
+    ///
+    /// The condition is resolved through the assignments in `block`, so that
+    /// comparisons computed into flag registers are recognized. Only
+    /// conditions that compare a tainted value with zero are NULL pointer
+    /// checks. The state is left unchanged for all other conditions and if
+    /// the nullability is not tracked.
+    ///
+    /// Returns an error if the outcome of the check is impossible in `self`.
+    pub fn specialize_by_null_check(
+        &mut self,
+        condition: &Expression,
+        block: &Term<Blk>,
+        is_true: bool,
+    ) -> Result<(), Error> {
+        if self.nullability.is_top() || !self.eval(condition).is_tainted() {
+            return Ok(());
+        }
+        if let Some(is_true_if_null) = get_null_check(&resolve_condition(condition, block)) {
+            self.nullability = self
+                .nullability
+                .specialize_by_null_check(is_true == is_true_if_null)?;
+        }
+        Ok(())
+    }
+
+    /// Check whether taking the jump depends on a tainted condition.
+    ///
+    /// A jump can depend on a tainted condition in two ways, either it is
+    /// executed because its condition evaluated to `true`, or because the
+    /// condition of the `untaken_conditional` evaluated to `false`.
+    pub fn jump_depends_on_taint(
+        &self,
+        jump: &Term<Jmp>,
+        untaken_conditional: Option<&Term<Jmp>>,
+    ) -> bool {
+        let is_tainted_branch = |jump: &Term<Jmp>| match &jump.term {
+            Jmp::CBranch { condition, .. } => self.eval(condition).is_tainted(),
+            _ => false,
+        };
+        is_tainted_branch(jump) || untaken_conditional.is_some_and(is_tainted_branch)
+    }
+
     /// Merges the given `other` state into this state with renaming of abstract
     /// identifiers.
     ///
@@ -464,10 +585,12 @@ impl State {
         let Self {
             register_taint: other_register_taint,
             memory_taint: other_memory_taint,
+            nullability: other_nullability,
         } = other;
 
-        // Naive merging works for register taint.
+        // Naive merging works for register taint and nullability.
         self.register_taint.merge_with(other_register_taint);
+        self.nullability = self.nullability.merge(other_nullability);
 
         let Some(renaming_map) = renaming_map else {
             // Without a renaming rule we can not do anything meaningful with
@@ -507,20 +630,95 @@ impl State {
         }
     }
 
+    /// Get the state at the start of a called function.
+    ///
+    /// This is the inverse of [`State::merge_with_renaming`]: The
+    /// `renaming_map` maps the abstract identifiers of the callee to their
+    /// values in the context of `self`, i.e., of the caller. Memory objects of
+    /// the callee get the taint of the caller objects that they correspond to.
+    /// The register taint and the nullability are passed on unchanged.
+    pub fn new_callee_state(
+        &self,
+        renaming_map: Option<&BTreeMap<AbstractIdentifier, PiData>>,
+    ) -> Self {
+        let mut callee_state = Self {
+            register_taint: self.register_taint.clone(),
+            memory_taint: MemoryTaint::new(),
+            nullability: self.nullability,
+        };
+        for (callee_aid, value) in renaming_map.into_iter().flatten() {
+            for (aid, offset_interval) in value.get_relative_values() {
+                if let (Some(memory_object), Ok(offset)) =
+                    (self.memory_taint.get(aid), offset_interval.try_to_offset())
+                {
+                    callee_state.memory_taint.merge_memory_object_with_offset(
+                        callee_aid,
+                        memory_object,
+                        -offset,
+                    );
+                }
+            }
+        }
+
+        callee_state
+    }
+
     /// Deconstructs a `State` into its register and memory taint maps.
     pub fn into_mem_reg_taint(self) -> (RegisterTaint, MemoryTaint) {
         (self.register_taint, self.memory_taint)
     }
 
     /// Constructs a `State` from register and memory taint maps.
+    ///
+    /// The nullability of the taint is not tracked in the new state.
     pub fn from_mem_reg_taint(register_taint: RegisterTaint, memory_taint: MemoryTaint) -> Self {
         Self {
             register_taint,
             memory_taint,
+            nullability: NullabilityDomain::Top,
         }
     }
 }
 
+/// Substitute the variables of the condition by the values assigned to them
+/// in the block before the conditional jump.
+///
+/// The substitution stops at the first load into a variable of the condition
+/// or if the resolved condition gets too complex.
+fn resolve_condition(condition: &Expression, block: &Term<Blk>) -> Expression {
+    const MAX_RECURSION_DEPTH: u64 = 16;
+    let mut resolved = condition.clone();
+    for def in block.term.defs.iter().rev() {
+        if resolved.recursion_depth() > MAX_RECURSION_DEPTH {
+            break;
+        }
+        match &def.term {
+            Def::Assign { var, value } => resolved.substitute_input_var(var, value),
+            Def::Load { var, .. } if resolved.input_vars().contains(&var) => break,
+            Def::Load { .. } | Def::Store { .. } => (),
+        }
+    }
+    resolved.substitute_trivial_operations();
+    resolved
+}
+
+/// If the expression compares a value with zero, return whether the
+/// expression is true if the value is zero.
+fn get_null_check(expression: &Expression) -> Option<bool> {
+    let Expression::BinOp { op, lhs, rhs } = expression else {
+        return None;
+    };
+    let is_zero = |expr: &Expression| matches!(expr, Expression::Const(value) if value.is_zero());
+    if is_zero(lhs) == is_zero(rhs) {
+        return None;
+    }
+    match op {
+        BinOpType::IntEqual => Some(true),
+        BinOpType::IntNotEqual => Some(false),
+        _ => None,
+    }
+}
+
 impl State {
     /// Get a more compact json-representation of the state.
     /// Intended for pretty printing, not useable for serialization/deserialization.
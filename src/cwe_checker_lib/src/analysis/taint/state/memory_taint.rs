@@ -98,14 +98,15 @@ fn merge_memory_object_with_offset(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::taint::TaintSource;
     use crate::prelude::*;
 
     // FIXME: This illustrates the current, unsound merging of memory taints. Make
     // sure to change this test when you work on a better memory model.
     #[test]
     fn merge_memory_object_overlapping() {
-        let taint_8 = Taint::Tainted(ByteSize::new(8));
-        let taint_4 = Taint::Tainted(ByteSize::new(4));
+        let taint_8 = Taint::new_tainted(ByteSize::new(8), TaintSource::Custom);
+        let taint_4 = Taint::new_tainted(ByteSize::new(4), TaintSource::Custom);
 
         let mut memory_object = MemRegion::<Taint>::new(ByteSize::new(8));
         let mut other_memory_object = MemRegion::<Taint>::new(ByteSize::new(8));
@@ -138,8 +139,8 @@ mod tests {
 
     #[test]
     fn merge_memory_object_nonoverlapping() {
-        let taint_8 = Taint::Tainted(ByteSize::new(8));
-        let taint_4 = Taint::Tainted(ByteSize::new(4));
+        let taint_8 = Taint::new_tainted(ByteSize::new(8), TaintSource::Custom);
+        let taint_4 = Taint::new_tainted(ByteSize::new(4), TaintSource::Custom);
         let untaint_4 = Taint::Top(ByteSize::new(4));
 
         let mut memory_object = MemRegion::<Taint>::new(ByteSize::new(8));
@@ -165,8 +166,8 @@ mod tests {
 
     #[test]
     fn merge_memory_object_with_offset_nonoverlapping() {
-        let taint_8 = Taint::Tainted(ByteSize::new(8));
-        let taint_4 = Taint::Tainted(ByteSize::new(4));
+        let taint_8 = Taint::new_tainted(ByteSize::new(8), TaintSource::Custom);
+        let taint_4 = Taint::new_tainted(ByteSize::new(4), TaintSource::Custom);
 
         let mut memory_object = MemRegion::<Taint>::new(ByteSize::new(8));
         let mut other_memory_object = MemRegion::<Taint>::new(ByteSize::new(8));
@@ -11,6 +11,7 @@ impl State {
         State {
             register_taint: RegisterTaint::new(),
             memory_taint: MemoryTaint::new(),
+            nullability: NullabilityDomain::Top,
         }
     }
 
@@ -33,12 +34,13 @@ impl State {
             return_values: vec![ret1, ret2],
             no_return: false,
             has_var_args: false,
+            alias: None,
         };
 
         let pi_state = PiState::new(&variable!("RSP:8"), Tid::new("func"), BTreeSet::new());
         let vsa_result = MockVsaResult::new(pi_state.clone(), None, None, None);
 
-        let state = State::new_return(&symbol, &vsa_result, NodeIndex::new(42));
+        let state = State::new_return(&symbol, TaintSource::Custom, &vsa_result, NodeIndex::new(42));
 
         (state, pi_state)
     }
@@ -62,7 +64,7 @@ fn new_pointer(location: &str, offset: i64) -> DataDomain<ValueDomain> {
 
 #[test]
 fn merge_state() {
-    let taint = Taint::Tainted(ByteSize::new(8));
+    let taint = Taint::new_tainted(ByteSize::new(8), TaintSource::Custom);
     let top = Taint::Top(ByteSize::new(8));
 
     let mut state = State::mock();
@@ -92,7 +94,7 @@ fn merge_state() {
 #[test]
 fn new_state() {
     let (state, pi_state) = State::mock_with_pi_state();
-    let taint = Taint::Tainted(ByteSize::new(8));
+    let taint = Taint::new_tainted(ByteSize::new(8), TaintSource::Custom);
 
     assert_eq!(state.register_taint.get(&variable!("RAX:8")), Some(&taint));
     assert_eq!(state.register_taint.get(&variable!("RSP:8")), None);
@@ -121,7 +123,7 @@ fn eval_expression() {
 fn check_register_list_for_taint() {
     let (mut state, mut pi_state) = State::mock_with_pi_state();
     let mut vsa_results = MockVsaResult::new(pi_state.clone(), None, None, None);
-    let taint = Taint::Tainted(ByteSize::new(8));
+    let taint = Taint::new_tainted(ByteSize::new(8), TaintSource::Custom);
     let untainted = Taint::Top(ByteSize::new(8));
     let (rdi, rsi, rdx) = (variable!("RDI:8"), variable!("RSI:8"), variable!("RDX:8"));
     let address = new_pointer("mem", 10);
@@ -179,7 +181,7 @@ fn check_extern_parameter_for_taint() {
         false
     );
 
-    state.set_register_taint(&variable!("RDI:8"), Taint::Tainted(ByteSize::new(8)));
+    state.set_register_taint(&variable!("RDI:8"), Taint::new_tainted(ByteSize::new(8), TaintSource::Custom));
     assert_eq!(
         state.check_extern_parameters_for_taint::<true>(
             &vsa_results,
@@ -189,7 +191,7 @@ fn check_extern_parameter_for_taint() {
         true
     );
 
-    let taint = Taint::Tainted(ByteSize::new(8));
+    let taint = Taint::new_tainted(ByteSize::new(8), TaintSource::Custom);
     let address = new_pointer("mem", 10);
     state.save_taint_to_memory(&address, taint);
     state.set_register_taint(&variable!("RDI:8"), Taint::Top(ByteSize::new(8)));
@@ -0,0 +1,331 @@
+//! Recovery of the arguments that are passed to calls of variadic functions.
+//!
+//! The number of arguments of a call to a variadic function like `printf` is
+//! not part of the signature of the function. It can only be recovered from
+//! the call site: The caller writes the arguments to the parameter registers
+//! and stores them to the stack right before the call. This module collects
+//! these materialized argument locations from the basic block of a call.
+//!
+//! Arguments that are set up in a different basic block than the call are not
+//! recognized, so all results are lower bounds for the actual arguments.
+
+use crate::abstract_domain::{DataDomain, IntervalDomain, TryToBitvec};
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::{Arg, Blk, Datatype, Def, Expression, Term, Variable};
+use crate::intermediate_representation::{ExternSymbol, Project};
+use crate::prelude::*;
+use crate::utils::arguments::calculate_parameter_locations;
+
+use std::collections::{BTreeSet, HashSet};
+
+/// The maximal number of stack slots that are considered as variadic
+/// arguments of a call.
+const MAX_STACK_SLOTS: usize = 32;
+
+/// The argument locations that are written in the basic block of a call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaterializedArguments {
+    /// Registers that are assigned or loaded before the call.
+    registers: HashSet<Variable>,
+    /// Offsets relative to the stack pointer at the call of all stack slots of
+    /// pointer size that are written before the call.
+    stack_offsets: BTreeSet<i64>,
+}
+
+/// The number of variadic argument slots of a call, grouped by their kind.
+///
+/// Arguments that are larger than a stack slot occupy several stack slots.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VariadicArgumentCount {
+    /// Number of arguments passed in integer parameter registers.
+    pub integer_registers: usize,
+    /// Number of arguments passed in float parameter registers.
+    pub float_registers: usize,
+    /// Number of stack slots of pointer size occupied by arguments.
+    pub stack_slots: usize,
+}
+
+impl MaterializedArguments {
+    /// Collects the argument locations written in the given block before the
+    /// given call.
+    ///
+    /// The stack offsets of stores are computed relative to the value of the
+    /// stack pointer at the call. Returns `None` if the value of the stack
+    /// pointer at the call is not a known offset into a stack frame.
+    pub fn new(
+        blk: &Term<Blk>,
+        call_tid: &Tid,
+        project: &Project,
+        vsa_result: &impl VsaResult<ValueDomain = DataDomain<IntervalDomain>>,
+    ) -> Option<Self> {
+        let stack_pointer = vsa_result.eval_at_jmp(
+            call_tid,
+            &Expression::Var(project.stack_pointer_register.clone()),
+        )?;
+        let (stack_id, stack_pointer_offset) = stack_pointer.get_if_unique_target()?;
+        let stack_pointer_offset = stack_pointer_offset.try_to_offset().ok()?;
+        let slot_size = u64::from(project.get_pointer_bytesize()) as i64;
+
+        let mut registers = HashSet::new();
+        let mut stack_offsets = BTreeSet::new();
+        for def in &blk.term.defs {
+            match &def.term {
+                Def::Assign { var, .. } | Def::Load { var, .. } => {
+                    if !var.is_temp {
+                        registers.insert(var.clone());
+                    }
+                }
+                Def::Store { value, .. } => {
+                    let Some(offset) = vsa_result
+                        .eval_address_at_def(&def.tid)
+                        .as_ref()
+                        .and_then(DataDomain::get_if_unique_target)
+                        .filter(|(id, _)| *id == stack_id)
+                        .and_then(|(_, offset)| offset.try_to_offset().ok())
+                    else {
+                        continue;
+                    };
+                    let offset = offset - stack_pointer_offset;
+                    let size = u64::from(value.bytesize()) as i64;
+                    stack_offsets.extend((offset..offset + size).step_by(slot_size as usize));
+                }
+            }
+        }
+
+        Some(MaterializedArguments {
+            registers,
+            stack_offsets,
+        })
+    }
+
+    /// Returns true if the given argument of the call is written before the
+    /// call.
+    ///
+    /// Stack arguments are materialized if the stack slot at their start
+    /// offset is written.
+    pub fn is_materialized(&self, arg: &Arg) -> bool {
+        match arg {
+            Arg::Register { expr, .. } => {
+                let input_vars = expr.input_vars();
+                !input_vars.is_empty() && input_vars.iter().all(|var| self.registers.contains(var))
+            }
+            Arg::Stack { .. } => arg
+                .eval_stack_offset()
+                .ok()
+                .and_then(|offset| offset.try_to_i64().ok())
+                .is_some_and(|offset| self.stack_offsets.contains(&offset)),
+        }
+    }
+
+    /// Returns the number of variadic argument slots of a call to the given
+    /// symbol that are written before the call.
+    ///
+    /// For each kind of argument location only the slots up to the first slot
+    /// that is not written are counted, since the callee reads its variadic
+    /// arguments in order.
+    pub fn count_variadic_arguments(
+        &self,
+        extern_symbol: &ExternSymbol,
+        project: &Project,
+    ) -> VariadicArgumentCount {
+        let resolver = project.get_extern_symbol_resolver(extern_symbol);
+        let calling_convention = resolver.get_calling_convention();
+        let pointer_size = project.get_pointer_bytesize();
+        let integer_slots = calculate_parameter_locations(
+            vec![
+                (Datatype::Integer, pointer_size);
+                calling_convention.integer_parameter_register.len() + MAX_STACK_SLOTS
+            ],
+            extern_symbol,
+            project,
+        );
+        let (integer_registers, stack_slots): (Vec<Arg>, Vec<Arg>) = integer_slots
+            .into_iter()
+            .partition(|arg| matches!(arg, Arg::Register { .. }));
+        let float_registers = if resolver.passes_variadic_floats_in_integer_registers() {
+            Vec::new()
+        } else {
+            let double_size = project
+                .datatype_properties
+                .get_size_from_data_type(Datatype::Double);
+            calculate_parameter_locations(
+                vec![
+                    (Datatype::Double, double_size);
+                    calling_convention.float_parameter_register.len()
+                ],
+                extern_symbol,
+                project,
+            )
+        };
+        let count_prefix = |args: &[Arg]| {
+            args.iter()
+                .take_while(|arg| self.is_materialized(arg))
+                .count()
+        };
+
+        VariadicArgumentCount {
+            integer_registers: count_prefix(&integer_registers),
+            float_registers: count_prefix(&float_registers),
+            stack_slots: count_prefix(&stack_slots),
+        }
+    }
+}
+
+impl VariadicArgumentCount {
+    /// Returns the number of argument slots occupied by the given variadic
+    /// arguments of a call to the given symbol.
+    pub fn from_arguments(args: &[Arg], extern_symbol: &ExternSymbol, project: &Project) -> Self {
+        let calling_convention = project.get_calling_convention(extern_symbol);
+        let slot_size = u64::from(project.get_pointer_bytesize());
+        let mut count = VariadicArgumentCount::default();
+        for arg in args {
+            match arg {
+                Arg::Register {
+                    expr: Expression::Var(var),
+                    ..
+                } if calling_convention.integer_parameter_register.contains(var) => {
+                    count.integer_registers += 1
+                }
+                Arg::Register { .. } => count.float_registers += 1,
+                Arg::Stack { size, .. } => {
+                    count.stack_slots += u64::from(*size).div_ceil(slot_size) as usize
+                }
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::{AbstractIdentifier, AbstractLocation, SizedDomain};
+    use crate::analysis::graph::NodeIndex;
+    use crate::intermediate_representation::parsing;
+    use crate::{bitvec, defs};
+
+    use std::collections::HashMap;
+
+    /// Value set analysis result that only knows the stack pointer at the call
+    /// and the addresses of stores.
+    struct MockVsaResult {
+        stack_pointer: DataDomain<IntervalDomain>,
+        addresses: HashMap<Tid, DataDomain<IntervalDomain>>,
+    }
+
+    impl VsaResult for MockVsaResult {
+        type ValueDomain = DataDomain<IntervalDomain>;
+
+        fn eval_value_at_def(&self, _def_tid: &Tid) -> Option<Self::ValueDomain> {
+            None
+        }
+
+        fn eval_address_at_def(&self, def_tid: &Tid) -> Option<Self::ValueDomain> {
+            self.addresses.get(def_tid).cloned()
+        }
+
+        fn eval_parameter_arg_at_call(
+            &self,
+            _jmp_tid: &Tid,
+            _param: &Arg,
+        ) -> Option<Self::ValueDomain> {
+            None
+        }
+
+        fn eval_parameter_location_at_call(
+            &self,
+            _jmp_tid: &Tid,
+            _param: &AbstractLocation,
+        ) -> Option<Self::ValueDomain> {
+            None
+        }
+
+        fn eval_at_jmp(
+            &self,
+            _jmp_tid: &Tid,
+            expression: &Expression,
+        ) -> Option<Self::ValueDomain> {
+            match expression {
+                Expression::Var(var) if var.name == "RSP" => Some(self.stack_pointer.clone()),
+                _ => None,
+            }
+        }
+
+        fn eval_at_node(
+            &self,
+            _node: NodeIndex,
+            _expression: &Expression,
+        ) -> Option<Self::ValueDomain> {
+            None
+        }
+    }
+
+    #[test]
+    fn materialized_printf_arguments() {
+        let project = Project::mock_x64();
+        let printf_symbol = ExternSymbol::mock_x64("printf");
+        let stack_id = AbstractIdentifier::mock("func", "RSP", 8);
+        let stack_offset =
+            |offset: &str| DataDomain::from_target(stack_id.clone(), bitvec!(offset).into());
+        let mut blk = Blk::mock();
+        blk.term.defs = defs![
+            "def_1: RSI:8 = 0x1:8",
+            "def_2: RDX:8 = 0x2:8",
+            "def_3: RCX:8 = 0x3:8",
+            "def_4: R8:8 = 0x4:8",
+            "def_5: R9:8 = 0x5:8",
+            "store_1: Store at RSP:8 + 0x8:8 := 0x6:8",
+            "store_2: Store at RSP:8 + 0x10:8 := 0x7:8",
+            "store_3: Store at RSP:8 + 0x20:8 := 0x8:8"
+        ];
+        let vsa_result = MockVsaResult {
+            // The return address is already pushed at the call.
+            stack_pointer: stack_offset("-64:8"),
+            addresses: HashMap::from([
+                (Tid::new("store_1"), stack_offset("-56:8")),
+                (Tid::new("store_2"), stack_offset("-48:8")),
+                (Tid::new("store_3"), stack_offset("-32:8")),
+            ]),
+        };
+
+        let materialized_arguments =
+            MaterializedArguments::new(&blk, &Tid::new("call"), &project, &vsa_result).unwrap();
+        assert!(materialized_arguments.is_materialized(&Arg::mock_register("R9", 8)));
+        assert!(!materialized_arguments.is_materialized(&Arg::mock_register("RAX", 8)));
+        // The store to the stack slot at offset 0x20 does not directly follow
+        // the other stack arguments.
+        assert_eq!(
+            materialized_arguments.count_variadic_arguments(&printf_symbol, &project),
+            VariadicArgumentCount {
+                integer_registers: 5,
+                float_registers: 0,
+                stack_slots: 2,
+            }
+        );
+        assert_eq!(
+            VariadicArgumentCount::from_arguments(
+                &calculate_parameter_locations(
+                    vec![(Datatype::Integer, ByteSize::new(8)); 7],
+                    &printf_symbol,
+                    &project
+                ),
+                &printf_symbol,
+                &project
+            ),
+            VariadicArgumentCount {
+                integer_registers: 5,
+                float_registers: 0,
+                stack_slots: 2,
+            }
+        );
+
+        // Without a known stack pointer no stack arguments can be recovered.
+        let vsa_result = MockVsaResult {
+            stack_pointer: DataDomain::new_top(ByteSize::new(8)),
+            addresses: HashMap::new(),
+        };
+        assert!(
+            MaterializedArguments::new(&blk, &Tid::new("call"), &project, &vsa_result).is_none()
+        );
+    }
+}
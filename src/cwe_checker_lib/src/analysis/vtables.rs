@@ -0,0 +1,351 @@
+//! Reconstruction of C++ virtual function tables (vtables) and resolution of virtual calls.
+//!
+//! A C++ constructor stores the address of the vtable of its class into the newly created object.
+//! A virtual call loads this vtable pointer from the object, loads the call target from
+//! a fixed offset in the vtable and then calls it indirectly.
+//!
+//! The analysis works in three steps:
+//! - Find all pointers to functions of the program in non-executable global memory.
+//! - Identify the vtables as the addresses of such function pointers
+//!   that are stored as constants by some function (the constructor writes).
+//!   The entries of a vtable are the function pointers following its address.
+//! - Resolve virtual call sites, i.e. indirect calls whose target is loaded from
+//!   an offset of a vtable pointer, to the vtable entries at that offset.
+//!
+//! The resolved targets are added to the indirect call targets of the calling block,
+//! so that the control flow graph contains call edges to them.
+//! Since the class of the object is not known at the call site,
+//! the call is resolved to the entries of all vtables at that offset.
+
+use crate::intermediate_representation::*;
+use crate::utils::log::LogMessage;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Virtual calls with more possible targets than this are not resolved,
+/// since the resulting call edges would add more imprecision than coverage.
+const MAX_VIRTUAL_CALL_TARGETS: usize = 32;
+
+/// A virtual function table.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Vtable {
+    /// The address of the vtable, i.e. the address that is stored into objects by constructors.
+    pub address: u64,
+    /// The functions referenced by the vtable in order.
+    pub entries: Vec<Tid>,
+    /// The functions that store the address of the vtable.
+    pub constructors: BTreeSet<Tid>,
+}
+
+/// Return a map from the addresses of all pointers to functions of the program
+/// in non-executable global memory to the TIDs of the corresponding functions.
+fn find_function_pointer_slots(project: &Project) -> BTreeMap<u64, Tid> {
+    let pointer_size = project.get_pointer_bytesize();
+    let functions: HashMap<u64, &Tid> = project
+        .program
+        .term
+        .subs
+        .keys()
+        .filter_map(|tid| Some((u64::try_from(tid.address()).ok()?, tid)))
+        .collect();
+    let mut slots = BTreeMap::new();
+    for segment in project.runtime_memory_image.memory_segments.iter() {
+        if segment.execute_flag || !segment.read_flag {
+            continue;
+        }
+        let end_address = segment.base_address + segment.bytes.len() as u64;
+        let mut address = segment
+            .base_address
+            .next_multiple_of(u64::from(pointer_size));
+        while address + u64::from(pointer_size) <= end_address {
+            if let Ok(Some(value)) = project
+                .runtime_memory_image
+                .read_pointer(address, pointer_size)
+            {
+                if let Some(function) = functions.get(&value) {
+                    slots.insert(address, (*function).clone());
+                }
+            }
+            address += u64::from(pointer_size);
+        }
+    }
+
+    slots
+}
+
+/// Return the constants stored into memory by the `Store` instructions of the block.
+///
+/// Values of registers that were assigned constants earlier in the block are also considered.
+fn get_stored_constants(block: &Term<Blk>) -> Vec<u64> {
+    let mut register_constants: HashMap<&str, u64> = HashMap::new();
+    let mut stored_constants = Vec::new();
+    for def in block.term.defs.iter() {
+        match &def.term {
+            Def::Assign {
+                var,
+                value: Expression::Const(constant),
+            } => {
+                if let Ok(constant) = constant.try_to_u64() {
+                    register_constants.insert(&var.name, constant);
+                } else {
+                    register_constants.remove(var.name.as_str());
+                }
+            }
+            Def::Assign { var, .. } | Def::Load { var, .. } => {
+                register_constants.remove(var.name.as_str());
+            }
+            Def::Store { value, .. } => match value {
+                Expression::Const(constant) => {
+                    stored_constants.extend(constant.try_to_u64().ok());
+                }
+                Expression::Var(var) => {
+                    stored_constants.extend(register_constants.get(var.name.as_str()));
+                }
+                _ => (),
+            },
+        }
+    }
+
+    stored_constants
+}
+
+/// Identify the vtables of the program.
+///
+/// A vtable is the address of a function pointer in global memory that is stored
+/// as a constant by some function of the program.
+/// Returns a map from vtable addresses to the vtables.
+pub fn find_vtables(project: &Project) -> BTreeMap<u64, Vtable> {
+    let slots = find_function_pointer_slots(project);
+    let pointer_size = u64::from(project.get_pointer_bytesize());
+    let mut vtables: BTreeMap<u64, Vtable> = BTreeMap::new();
+    for sub in project.program.term.subs.values() {
+        for block in sub.term.blocks.iter() {
+            for address in get_stored_constants(block) {
+                if !slots.contains_key(&address) {
+                    continue;
+                }
+                vtables
+                    .entry(address)
+                    .or_insert_with(|| Vtable {
+                        address,
+                        entries: (0..)
+                            .map_while(|index| slots.get(&(address + index * pointer_size)))
+                            .cloned()
+                            .collect(),
+                        constructors: BTreeSet::new(),
+                    })
+                    .constructors
+                    .insert(sub.tid.clone());
+            }
+        }
+    }
+
+    vtables
+}
+
+/// If the block ends with a virtual call, return the offset of the called function in the vtable.
+///
+/// A virtual call has the form
+/// ```txt
+/// vtable_pointer := Load from object_pointer
+/// target := Load from vtable_pointer + offset
+/// call target
+/// ```
+/// where the offset is optional.
+fn get_virtual_call_offset(block: &Term<Blk>) -> Option<u64> {
+    let Some(Jmp::CallInd {
+        target: Expression::Var(target),
+        ..
+    }) = block.term.jmps.first().map(|jmp| &jmp.term)
+    else {
+        return None;
+    };
+    let mut defs = block.term.defs.iter().rev().map(|def| &def.term);
+    let (vtable_pointer, offset) = match defs.find(|def| def_assigns(def, target))? {
+        Def::Load {
+            address: Expression::Var(vtable_pointer),
+            ..
+        } => (vtable_pointer, 0),
+        Def::Load {
+            address:
+                Expression::BinOp {
+                    op: BinOpType::IntAdd,
+                    lhs,
+                    rhs,
+                },
+            ..
+        } => match (lhs.as_ref(), rhs.as_ref()) {
+            (Expression::Var(vtable_pointer), Expression::Const(offset)) => {
+                (vtable_pointer, offset.try_to_u64().ok()?)
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match defs.find(|def| def_assigns(def, vtable_pointer))? {
+        Def::Load { .. } => Some(offset),
+        _ => None,
+    }
+}
+
+/// Returns true if the given `Def` assigns a value to the given variable.
+fn def_assigns(def: &Def, var: &Variable) -> bool {
+    match def {
+        Def::Load { var: def_var, .. } | Def::Assign { var: def_var, .. } => def_var == var,
+        Def::Store { .. } => false,
+    }
+}
+
+/// Resolve the virtual calls of the project and add the resolved targets
+/// to the indirect call targets of the calling blocks.
+///
+/// Returns log messages with statistics about the resolved calls.
+pub fn resolve_virtual_calls(project: &mut Project) -> Vec<LogMessage> {
+    let vtables = find_vtables(project);
+    if vtables.is_empty() {
+        return Vec::new();
+    }
+    let pointer_size = u64::from(project.get_pointer_bytesize());
+    let mut num_resolved_calls = 0;
+    for sub in project.program.term.subs.values_mut() {
+        for block in sub.term.blocks.iter_mut() {
+            let Some(offset) = get_virtual_call_offset(block) else {
+                continue;
+            };
+            if offset % pointer_size != 0 {
+                continue;
+            }
+            let index = (offset / pointer_size) as usize;
+            let mut targets: BTreeSet<Tid> = vtables
+                .values()
+                .filter_map(|vtable| vtable.entries.get(index))
+                .cloned()
+                .collect();
+            if targets.is_empty() || targets.len() > MAX_VIRTUAL_CALL_TARGETS {
+                continue;
+            }
+            if let Some(known_targets) = block.term.ind_call_targets() {
+                targets.extend(known_targets.cloned());
+            }
+            block.term.set_ind_call_targets(targets);
+            num_resolved_calls += 1;
+        }
+    }
+
+    vec![LogMessage::new_info(format!(
+        "Found {} vtables and resolved {} virtual calls.",
+        vtables.len(),
+        num_resolved_calls
+    ))
+    .source("Vtables")]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::parsing;
+    use crate::utils::binary::MemorySegment;
+    use crate::{defs, expr};
+
+    /// Create a project with the functions `FUN_0x7000`, `FUN_0x7100` and `FUN_0x7200`,
+    /// a vtable at address 0x8008 with entries pointing to the first two functions
+    /// and a constructor storing the vtable address into an object.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_x64();
+        let mut bytes = vec![0u8; 8];
+        for entry in [0x7000u64, 0x7100] {
+            bytes.extend(entry.to_le_bytes());
+        }
+        bytes.extend([0u8; 8]);
+        project
+            .runtime_memory_image
+            .memory_segments
+            .push(MemorySegment {
+                bytes,
+                base_address: 0x8000,
+                read_flag: true,
+                write_flag: false,
+                execute_flag: false,
+            });
+
+        let mut constructor_block = Blk::mock();
+        constructor_block.term.defs = defs!["RAX:8 = 0x8008:8", "Store at RDI:8 := RAX:8"];
+        let mut call_block = Blk::mock_with_tid("call_block");
+        call_block.term.defs = defs![
+            "RAX:8 := Load from RDI:8",
+            "RAX:8 := Load from RAX:8 + 0x8:8"
+        ];
+        call_block.term.jmps = vec![Term {
+            tid: Tid::new("virtual_call"),
+            term: Jmp::CallInd {
+                target: expr!("RAX:8"),
+                return_: None,
+            },
+        }];
+        for (tid, blocks) in [
+            ("FUN_0x7000", vec![constructor_block]),
+            ("FUN_0x7100", Vec::new()),
+            ("FUN_0x7200", vec![call_block]),
+        ] {
+            let sub = Term {
+                tid: Tid::mock(tid),
+                term: Sub::new::<_, &str>(tid, blocks, None),
+            };
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    #[test]
+    fn vtables() {
+        let project = mock_project();
+        let vtables = find_vtables(&project);
+        assert_eq!(
+            vtables,
+            BTreeMap::from([(
+                0x8008,
+                Vtable {
+                    address: 0x8008,
+                    entries: vec![Tid::mock("FUN_0x7000"), Tid::mock("FUN_0x7100")],
+                    constructors: BTreeSet::from([Tid::mock("FUN_0x7000")]),
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn virtual_calls() {
+        let mut project = mock_project();
+        resolve_virtual_calls(&mut project);
+        let call_block = &project.program.term.subs[&Tid::mock("FUN_0x7200")]
+            .term
+            .blocks[0];
+        assert_eq!(
+            call_block
+                .term
+                .ind_call_targets()
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec![&Tid::mock("FUN_0x7100")]
+        );
+
+        // Offsets outside of the vtables are not resolved.
+        let mut project = mock_project();
+        let call_block = &mut project
+            .program
+            .term
+            .subs
+            .get_mut(&Tid::mock("FUN_0x7200"))
+            .unwrap()
+            .term
+            .blocks[0];
+        call_block.term.defs = defs![
+            "RAX:8 := Load from RDI:8",
+            "RAX:8 := Load from RAX:8 + 0x10:8"
+        ];
+        resolve_virtual_calls(&mut project);
+        let call_block = &project.program.term.subs[&Tid::mock("FUN_0x7200")]
+            .term
+            .blocks[0];
+        assert!(call_block.term.ind_call_targets().is_none());
+    }
+}
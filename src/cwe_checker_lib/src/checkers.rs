@@ -15,26 +15,41 @@ pub const MODULES_LKM: [&str; 10] = [
 
 pub mod cwe_119;
 pub mod cwe_134;
+pub mod cwe_170;
 pub mod cwe_190;
 pub mod cwe_215;
 pub mod cwe_243;
 pub mod cwe_252;
+pub mod cwe_306;
+pub mod cwe_311;
+pub mod cwe_319;
+pub mod cwe_330;
 pub mod cwe_332;
 pub mod cwe_337;
 pub mod cwe_367;
+pub mod cwe_377;
+pub mod cwe_401;
 pub mod cwe_416;
 pub mod cwe_426;
 pub mod cwe_467;
+pub mod cwe_468;
 pub mod cwe_476;
 pub mod cwe_560;
+pub mod cwe_590;
+pub mod cwe_672;
 pub mod cwe_676;
+pub mod cwe_696;
 pub mod cwe_78;
 pub mod cwe_782;
 pub mod cwe_789;
+pub mod cwe_806;
+pub mod cwe_839;
+#[cfg(test)]
+mod test_utils;
 
 pub mod prelude {
     //! Prelude imports for CWE checkers.
-    pub use super::{cwe_module, CweModule, CweModuleFn};
+    pub use super::{cwe_module, register_checker, Analysis, Checker, CweModule, CweModuleFn};
     pub use crate::utils::debug;
     pub use crate::utils::log::{CweWarning, DeduplicateCweWarnings, LogMessage, WithLogs};
 }
@@ -42,10 +57,107 @@ use prelude::*;
 
 use crate::pipeline::AnalysisResults;
 
+#[doc(hidden)]
+pub use inventory;
+
 /// The generic function signature for the main function of a CWE module
 pub type CweModuleFn =
     fn(&AnalysisResults, &serde_json::Value, &debug::Settings) -> WithLogs<Vec<CweWarning>>;
 
+/// The analyses whose results a checker may require in the [`AnalysisResults`] struct.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Analysis {
+    /// The function signature analysis.
+    FunctionSignatures,
+    /// The pointer inference analysis.
+    PointerInference,
+    /// The string abstraction analysis.
+    StringAbstraction,
+}
+
+impl Analysis {
+    /// The analyses whose results are needed to compute this analysis.
+    pub fn dependencies(&self) -> &'static [Analysis] {
+        match self {
+            Analysis::FunctionSignatures => &[],
+            Analysis::PointerInference => &[Analysis::FunctionSignatures],
+            Analysis::StringAbstraction => &[Analysis::PointerInference],
+        }
+    }
+}
+
+/// Compute the analyses needed by the given checkers, including all transitive dependencies.
+///
+/// The analyses are returned in topological order,
+/// i.e. each analysis comes after all analyses that it depends on.
+/// The `additional_analyses` are scheduled even if no checker needs them.
+pub fn schedule_analyses(
+    checkers: &[&dyn Checker],
+    additional_analyses: &[Analysis],
+) -> Vec<Analysis> {
+    let mut schedule = Vec::new();
+    let required_analyses = checkers
+        .iter()
+        .flat_map(|checker| checker.required_analyses().iter())
+        .chain(additional_analyses.iter());
+    for analysis in required_analyses {
+        add_to_schedule(*analysis, &mut schedule);
+    }
+
+    schedule
+}
+
+/// Add the analysis to the schedule after all its dependencies, unless it is already scheduled.
+fn add_to_schedule(analysis: Analysis, schedule: &mut Vec<Analysis>) {
+    if schedule.contains(&analysis) {
+        return;
+    }
+    for dependency in analysis.dependencies() {
+        add_to_schedule(*dependency, schedule);
+    }
+    schedule.push(analysis);
+}
+
+impl std::fmt::Display for Analysis {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Analysis::FunctionSignatures => write!(formatter, "FunctionSignatures"),
+            Analysis::PointerInference => write!(formatter, "PointerInference"),
+            Analysis::StringAbstraction => write!(formatter, "StringAbstraction"),
+        }
+    }
+}
+
+/// The common interface of all checkers.
+///
+/// Checkers are registered with the [`register_checker`] macro
+/// and can then be retrieved through [`get_modules`].
+pub trait Checker: Sync {
+    /// The name of the checker, which is also its key in the configuration file.
+    fn name(&self) -> &'static str;
+
+    /// The version number of the checker.
+    fn version(&self) -> &'static str;
+
+    /// The numbers of the CWEs that the checker reports.
+    ///
+    /// By default, this is the number contained in checker names of the form `CWE<number>`.
+    fn cwes(&self) -> Vec<u32> {
+        cwe_number_from_name(self.name()).into_iter().collect()
+    }
+
+    /// The analyses whose results have to be present when running the checker.
+    fn required_analyses(&self) -> &'static [Analysis];
+
+    /// Run the checker and return the CWE warnings found during the check.
+    fn run(
+        &self,
+        analysis_results: &AnalysisResults,
+        config: &serde_json::Value,
+        debug_settings: &debug::Settings,
+    ) -> WithLogs<Vec<CweWarning>>;
+}
+
 /// A structure containing general information about a CWE analysis module,
 /// including the function to be called to run the analysis.
 pub struct CweModule {
@@ -56,17 +168,62 @@ pub struct CweModule {
     pub version: &'static str,
     /// The function that executes the check and returns CWE warnings found during the check.
     pub run: CweModuleFn,
+    /// The analyses that the check depends on.
+    pub required_analyses: &'static [Analysis],
 }
 
+impl Checker for CweModule {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn version(&self) -> &'static str {
+        self.version
+    }
+
+    fn required_analyses(&self) -> &'static [Analysis] {
+        self.required_analyses
+    }
+
+    fn run(
+        &self,
+        analysis_results: &AnalysisResults,
+        config: &serde_json::Value,
+        debug_settings: &debug::Settings,
+    ) -> WithLogs<Vec<CweWarning>> {
+        (self.run)(analysis_results, config, debug_settings)
+    }
+}
+
+/// A checker in the registry of all known checkers.
+pub struct CheckerRegistration(pub &'static dyn Checker);
+
+inventory::collect!(CheckerRegistration);
+
 #[macro_export]
-/// Defines a CWE checker module.
+/// Adds the given static [`Checker`] to the registry of all known checkers.
+macro_rules! register_checker {
+    ($checker:path) => {
+        $crate::checkers::inventory::submit! {
+            $crate::checkers::CheckerRegistration(&$checker)
+        }
+    };
+}
+pub use register_checker;
+
+#[macro_export]
+/// Defines a CWE checker module and registers it.
+///
+/// The analyses that the check depends on can be given after the entry point,
+/// e.g. `requires: [PointerInference]`.
 macro_rules! cwe_module {
     (
         $name:literal, $version:literal, $run:ident,
+        $(requires: [$($analysis:ident),* $(,)?],)?
         config: $($(#[doc = $config_doc:expr])*$config_key:ident: $config_type:ty),
         *$(,)?
      ) => {
-        cwe_module!($name, $version, $run);
+        cwe_module!($name, $version, $run $(, requires: [$($analysis),*])?);
         #[doc = "The checker-specific configuration."]
         #[derive(serde::Serialize, serde::Deserialize)]
         struct Config {
@@ -78,46 +235,96 @@ macro_rules! cwe_module {
             )*
         }
     };
-    ($name:literal, $version:literal, $run:ident$(,)?) => {
-        #[doc = "The checker's name, version, and entry point."]
+    (
+        $name:literal, $version:literal, $run:ident
+        $(, requires: [$($analysis:ident),* $(,)?])?$(,)?
+    ) => {
+        #[doc = "The checker's name, version, entry point and required analyses."]
         pub static CWE_MODULE: $crate::checkers::prelude::CweModule =
             $crate::checkers::prelude::CweModule {
                 name: $name,
                 version: $version,
                 run: $run,
+                required_analyses: &[$($($crate::checkers::Analysis::$analysis),*)?],
             };
+        $crate::checkers::register_checker!(CWE_MODULE);
     }
 }
 pub use cwe_module;
 
-impl std::fmt::Display for CweModule {
+impl std::fmt::Display for dyn Checker {
     /// Print the module name and its version number.
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(formatter, r#""{}": "{}""#, self.name, self.version)
+        write!(formatter, r#""{}": "{}""#, self.name(), self.version())
     }
 }
 
-/// Get a list of all known analysis modules.
-pub fn get_modules() -> Vec<&'static CweModule> {
-    vec![
-        &crate::checkers::cwe_78::CWE_MODULE,
-        &crate::checkers::cwe_119::CWE_MODULE,
-        &crate::checkers::cwe_134::CWE_MODULE,
-        &crate::checkers::cwe_190::CWE_MODULE,
-        &crate::checkers::cwe_215::CWE_MODULE,
-        &crate::checkers::cwe_243::CWE_MODULE,
-        &crate::checkers::cwe_252::CWE_MODULE,
-        &crate::checkers::cwe_332::CWE_MODULE,
-        &crate::checkers::cwe_337::CWE_MODULE,
-        &crate::checkers::cwe_367::CWE_MODULE,
-        &crate::checkers::cwe_416::CWE_MODULE,
-        &crate::checkers::cwe_426::CWE_MODULE,
-        &crate::checkers::cwe_467::CWE_MODULE,
-        &crate::checkers::cwe_476::CWE_MODULE,
-        &crate::checkers::cwe_560::CWE_MODULE,
-        &crate::checkers::cwe_676::CWE_MODULE,
-        &crate::checkers::cwe_782::CWE_MODULE,
-        &crate::checkers::cwe_789::CWE_MODULE,
-        &crate::analysis::pointer_inference::CWE_MODULE,
-    ]
+/// Return the CWE number of a checker name of the form `CWE<number>`.
+fn cwe_number_from_name(name: &str) -> Option<u32> {
+    name.strip_prefix("CWE")?.parse().ok()
+}
+
+/// Get a list of all registered checkers.
+///
+/// The checkers for specific CWEs are sorted by their CWE number,
+/// followed by all other checkers sorted by name.
+pub fn get_modules() -> Vec<&'static dyn Checker> {
+    let mut modules: Vec<&'static dyn Checker> = inventory::iter::<CheckerRegistration>
+        .into_iter()
+        .map(|registration| registration.0)
+        .collect();
+    modules.sort_by_key(|module| {
+        (
+            cwe_number_from_name(module.name()).unwrap_or(u32::MAX),
+            module.name(),
+        )
+    });
+
+    modules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        let modules = get_modules();
+        let names: Vec<&str> = modules.iter().map(|module| module.name()).collect();
+        assert_eq!(names.first(), Some(&"CWE78"));
+        assert_eq!(names.last(), Some(&"Memory"));
+        assert!(names.contains(&"CWE119"));
+        assert_eq!(names.len(), 32);
+
+        let cwe_119 = modules[names.iter().position(|name| *name == "CWE119").unwrap()];
+        assert_eq!(cwe_119.cwes(), vec![119]);
+        assert!(cwe_119
+            .required_analyses()
+            .contains(&Analysis::PointerInference));
+        let memory = modules.last().unwrap();
+        assert_eq!(memory.cwes(), vec![476]);
+    }
+
+    #[test]
+    fn analysis_scheduling() {
+        let modules = get_modules();
+        let checker = |name: &str| *modules.iter().find(|module| module.name() == name).unwrap();
+        assert!(schedule_analyses(&[checker("CWE676"), checker("CWE782")], &[]).is_empty());
+        assert_eq!(
+            schedule_analyses(&[checker("CWE476"), checker("CWE676")], &[]),
+            vec![Analysis::FunctionSignatures, Analysis::PointerInference]
+        );
+        assert_eq!(
+            schedule_analyses(&[checker("CWE78"), checker("CWE119")], &[]),
+            vec![
+                Analysis::FunctionSignatures,
+                Analysis::PointerInference,
+                Analysis::StringAbstraction
+            ]
+        );
+        assert_eq!(
+            schedule_analyses(&[], &[Analysis::PointerInference]),
+            vec![Analysis::FunctionSignatures, Analysis::PointerInference]
+        );
+    }
 }
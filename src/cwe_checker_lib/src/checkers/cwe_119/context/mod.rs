@@ -2,11 +2,14 @@ use crate::abstract_domain::*;
 use crate::analysis::callgraph::CallGraph;
 use crate::analysis::function_signature::FunctionSignature;
 use crate::analysis::graph::Graph;
+use crate::analysis::loop_idioms::{find_loop_idioms, LoopIdiom, LoopSummary};
 use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::analysis::string_abstraction::StringAbstraction;
 use crate::intermediate_representation::*;
 use crate::utils::log::{CweWarning, LogMessage, LogThreadMsg};
+use crate::utils::mitigations::{get_function_mitigations, Mitigation};
 use crate::{analysis::vsa_results::VsaResult, prelude::*};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use super::state::State;
 
@@ -27,6 +30,8 @@ pub struct Context<'a> {
     pub graph: &'a Graph<'a>,
     /// A pointer to the results of the pointer inference analysis.
     pub pointer_inference: &'a PointerInference<'a>,
+    /// A pointer to the results of the string abstraction, if it was computed.
+    pub string_abstraction: Option<&'a StringAbstraction<'a, BricksDomain>>,
     /// A pointer to the computed function signatures for all internal functions.
     pub function_signatures: &'a BTreeMap<Tid, FunctionSignature>,
     /// A map mapping the TID of  a function to the set of all known callsites of that function.
@@ -41,6 +46,13 @@ pub struct Context<'a> {
     pub call_to_caller_fn_map: HashMap<Tid, Tid>,
     /// The callgraph corresponding to the project.
     pub callgraph: CallGraph<'a>,
+    /// Summaries of loops that implement memory or string operations, indexed by the TID of the loop block.
+    pub loop_summaries: HashMap<Tid, LoopSummary>,
+    /// The TIDs of the vector loads of scan loops, e.g. inlined `strlen` loops,
+    /// which may read past the end of the string up to the end of the loaded vector.
+    pub scan_loop_loads: HashSet<Tid>,
+    /// The exploit mitigations of all functions protected by at least one mitigation, indexed by the function TID.
+    pub function_mitigations: HashMap<Tid, BTreeSet<Mitigation>>,
     /// A sender channel that can be used to collect logs in the corresponding logging thread.
     pub log_collector: crossbeam_channel::Sender<LogThreadMsg>,
 }
@@ -56,10 +68,19 @@ impl<'a> Context<'a> {
     {
         let project = analysis_results.project;
         let callgraph = crate::analysis::callgraph::get_program_callgraph(&project.program);
+        let loop_summaries = find_loop_idioms(project);
+        let scan_loop_loads = loop_summaries
+            .values()
+            .filter_map(|summary| match &summary.idiom {
+                LoopIdiom::Scan { load } => Some(load.clone()),
+                LoopIdiom::Copy { .. } | LoopIdiom::Decode { .. } => None,
+            })
+            .collect();
         Context {
             project,
             graph: analysis_results.control_flow_graph,
             pointer_inference: analysis_results.pointer_inference.unwrap(),
+            string_abstraction: analysis_results.string_abstraction,
             function_signatures: analysis_results.function_signatures.unwrap(),
             callee_to_callsites_map: compute_callee_to_call_sites_map(project),
             param_replacement_map: param_replacement::compute_param_replacement_map(
@@ -68,6 +89,9 @@ impl<'a> Context<'a> {
             malloc_tid_to_object_size_map: compute_size_values_of_malloc_calls(analysis_results),
             call_to_caller_fn_map: compute_call_to_caller_map(project),
             callgraph,
+            loop_summaries,
+            scan_loop_loads,
+            function_mitigations: get_function_mitigations(project),
             log_collector,
         }
     }
@@ -82,6 +106,16 @@ impl<'a> Context<'a> {
                 )
     }
 
+    /// Returns the exploit mitigations of the current function
+    /// if an out-of-bounds access to the given address may overflow its stack frame.
+    /// Overflows of other memory objects are not protected by these mitigations.
+    pub fn get_stack_mitigations(&self, state: &State, address: &Data) -> BTreeSet<Mitigation> {
+        match self.function_mitigations.get(state.get_fn_tid()) {
+            Some(mitigations) if state.may_point_to_own_stack_frame(address) => mitigations.clone(),
+            _ => BTreeSet::new(),
+        }
+    }
+
     /// Compute the size of a heap object created by a malloc-like function call.
     ///
     /// Uses the path hints in the given `object_id` to concretize the size if possible.
@@ -168,7 +202,13 @@ impl<'a> Context<'a> {
             .pointer_inference
             .eval_parameter_arg_at_call(call_tid, param)
         {
-            let warnings = state.check_address_access(&possible_address, ByteSize::new(1), self);
+            let param_congruence = state.eval_param_congruence(param);
+            let warnings = state.check_address_access(
+                &possible_address,
+                &param_congruence,
+                ByteSize::new(1),
+                self,
+            );
             if !warnings.is_empty() {
                 let description = match target_fn_name {
                     Some(target_name) => format!(
@@ -182,7 +222,8 @@ impl<'a> Context<'a> {
                     ),
                 };
                 let mut cwe_warning =
-                    CweWarning::new("CWE119", super::CWE_MODULE.version, description);
+                    CweWarning::new("CWE119", super::CWE_MODULE.version, description)
+                        .mitigations(self.get_stack_mitigations(state, &possible_address));
                 cwe_warning.tids = vec![format!("{call_tid}")];
                 cwe_warning.addresses = vec![call_tid.address().to_string()];
                 cwe_warning.other = vec![warnings];
@@ -190,6 +231,85 @@ impl<'a> Context<'a> {
             }
         }
     }
+
+    /// Check whether the source and destination buffers of a copy loop are large enough
+    /// for the number of bytes copied by the loop when it is entered by the given jump.
+    /// If not, then generate a CWE warning.
+    ///
+    /// The check is only done for copy loops with a known element count.
+    pub fn check_copy_loop_at_entry(
+        &self,
+        state: &mut State,
+        jump: &Term<Jmp>,
+        summary: &LoopSummary,
+    ) {
+        let LoopIdiom::Copy {
+            source,
+            destination,
+            count: Some(count),
+            ..
+        } = &summary.idiom
+        else {
+            return;
+        };
+        let Some(count) = self
+            .pointer_inference
+            .eval_at_jmp(&jump.tid, &Expression::Var(count.clone()))
+        else {
+            return;
+        };
+        let step =
+            Bitvector::from_u64(u64::from(summary.step)).into_resize_unsigned(count.bytesize());
+        let Some(size) = self
+            .compute_buffer_size_from_data_domain(count.bin_op(BinOpType::IntMult, &step.into()))
+        else {
+            return;
+        };
+        let mut warnings = Vec::new();
+        let mut mitigations = BTreeSet::new();
+        for buffer in [source, destination] {
+            if let Some(address) = self.pointer_inference.eval_at_jmp(&jump.tid, buffer) {
+                let congruence = state.eval_congruence(buffer);
+                let mut buffer_warnings =
+                    state.check_address_access(&address, &congruence, size, self);
+                if !buffer_warnings.is_empty() {
+                    mitigations.append(&mut self.get_stack_mitigations(state, &address));
+                }
+                warnings.append(&mut buffer_warnings);
+            }
+        }
+        if !warnings.is_empty() {
+            let description = format!(
+                "(Buffer Overflow) Copy loop at {} may access out-of-bounds memory.",
+                summary.block.address()
+            );
+            let mut cwe_warning = CweWarning::new("CWE119", super::CWE_MODULE.version, description)
+                .mitigations(mitigations);
+            cwe_warning.tids = vec![format!("{}", summary.block)];
+            cwe_warning.addresses = vec![summary.block.address().to_string()];
+            cwe_warning.other = vec![warnings];
+            self.log_collector.send(cwe_warning.into()).unwrap();
+        }
+    }
+
+    /// Compute the size of a buffer from a corresponding size value.
+    /// Returns `None` if no absolute size value could be determined for any reason.
+    ///
+    /// If a range of possible sizes is detected, use the smallest possible size,
+    /// as using larger sizes would lead to too many false positive CWE warnings.
+    pub fn compute_buffer_size_from_data_domain(&self, size: Data) -> Option<ByteSize> {
+        let size = self.recursively_substitute_param_values(&size);
+        let (lower_bound, _upper_bound) =
+            size.get_absolute_value()?.try_to_offset_interval().ok()?;
+        // FIXME: Currently we need to use the lower bound,
+        // because using the upper bound would lead to too many false positives.
+        // To fix this we probably need to implement tracking linear dependencies between values.
+        if lower_bound > 0 {
+            Some((lower_bound as u64).into())
+        } else {
+            None
+        }
+    }
 }
 
 /// Compute a map that maps the TIDs of functions to the set of TIDs of all known callsites to the corresponding function.
@@ -214,14 +334,25 @@ fn compute_callee_to_call_sites_map(project: &Project) -> HashMap<Tid, HashSet<T
 
 /// Compute a map mapping the TIDs of malloc-like calls (e.g. malloc, realloc, calloc)
 /// to the size value of the allocated object according to the pointer inference analysis.
-fn compute_size_values_of_malloc_calls(analysis_results: &AnalysisResults) -> HashMap<Tid, Data> {
+///
+/// Calls to the allocation wrappers configured for the pointer inference analysis are also included.
+pub fn compute_size_values_of_malloc_calls(
+    analysis_results: &AnalysisResults,
+) -> HashMap<Tid, Data> {
     let project = analysis_results.project;
     let pointer_inference = analysis_results.pointer_inference.unwrap();
     let mut malloc_size_map = HashMap::new();
     for sub in analysis_results.project.program.term.subs.values() {
         for blk in &sub.term.blocks {
             for jmp in &blk.term.jmps {
-                if let Jmp::Call { target, .. } = &jmp.term {
+                if let Some(size_param) =
+                    pointer_inference.get_allocation_wrapper_size_param(&jmp.tid)
+                {
+                    let size_value = pointer_inference
+                        .eval_parameter_arg_at_call(&jmp.tid, size_param)
+                        .unwrap_or_else(|| Data::new_top(size_param.bytesize()));
+                    malloc_size_map.insert(jmp.tid.clone(), size_value);
+                } else if let Jmp::Call { target, .. } = &jmp.term {
                     if let Some(symbol) = project.program.term.extern_symbols.get(target) {
                         if let Some(size_value) = compute_size_value_of_malloc_like_call(
                             &jmp.tid,
@@ -320,6 +451,5 @@ fn compute_call_to_caller_map(project: &Project) -> HashMap<Tid, Tid> {
     call_to_caller_map
 }
 
-// TODO: Fix tests.
-//#[cfg(test)]
-//pub mod tests;
+#[cfg(test)]
+pub mod tests;
@@ -72,3 +72,40 @@ fn test_malloc_zero_case() {
     let size_domain = context.compute_size_of_heap_object(&object_id);
     assert_eq!(size_domain.try_to_offset().unwrap(), 32);
 }
+
+#[test]
+fn test_aligned_loop_access() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as _;
+    use crate::def;
+
+    let mut project = Project::mock_x64();
+    project.program.term.subs = BTreeMap::from([(Tid::new("func"), Sub::mock("func"))]);
+    let stack_id = AbstractIdentifier::from_var(Tid::new("func"), &variable!("RSP:8"));
+    // The loop stores 8-byte words at `RSP - 16`, `RSP - 8` and `RSP`.
+    // The Pointer Inference analysis only knows that the offset of the store is at most 4.
+    let mut pi_results = PointerInference::mock(&project);
+    pi_results.get_mut_addresses_at_defs().insert(
+        Tid::new("store"),
+        Data::from_target(stack_id, IntervalDomain::mock(-16, 4)),
+    );
+    let analysis_results =
+        AnalysisResults::mock_from_project(&project).with_pointer_inference(Some(&pi_results));
+    let (log_collector, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&analysis_results, log_collector);
+    let start_state = State::new(&Tid::new("func"), &FunctionSignature::mock_x64(), &project);
+    let loop_start = context
+        .update_def(&start_state, &def!["RCX:8 = RSP:8 - 0x10:8"])
+        .unwrap();
+    let loop_end = context
+        .update_def(&loop_start, &def!["RCX:8 = RCX:8 + 0x8:8"])
+        .unwrap();
+    let loop_state = loop_start.merge(&loop_end);
+    let store = def!["store: Store at RCX:8 := RAX:8"];
+
+    // The store is aligned to the stack frame, so the last stored word ends at the upper bound.
+    context.update_def(&loop_state, &store).unwrap();
+    assert!(log_receiver.try_recv().is_err());
+    // Without the alignment the store may access the offsets up to 4.
+    context.update_def(&start_state, &store).unwrap();
+    assert!(log_receiver.try_recv().is_ok());
+}
@@ -19,15 +19,29 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
     /// If the given [`Def`] is a load or store instruction, check whether it may access addresses
     /// that are out of bounds of the corresponding memory object.
     /// Generate CWE warnings accordingly.
+    ///
+    /// Afterwards the register congruences are updated according to the [`Def`].
     fn update_def(&self, state: &State, def: &Term<Def>) -> Option<State> {
         let mut state = state.clone();
         match &def.term {
-            Def::Load { address: _, var } => {
+            Def::Load {
+                address: address_expr,
+                var,
+            } => {
                 let address = match self.pointer_inference.eval_address_at_def(&def.tid) {
                     Some(address) => address,
                     None => return None, // There seems to be no pointer inference state here.
                 };
-                let warnings = state.check_address_access(&address, var.size, self);
+                let address_congruence = state.eval_congruence(address_expr);
+                // Only the first character loaded by a scan loop
+                // has to be inside the bounds of the string.
+                let access_size = if self.scan_loop_loads.contains(&def.tid) {
+                    ByteSize::new(1)
+                } else {
+                    var.size
+                };
+                let warnings =
+                    state.check_address_access(&address, &address_congruence, access_size, self);
                 if !warnings.is_empty() {
                     let mut cwe_warning = CweWarning::new(
                         "CWE125",
@@ -43,12 +57,21 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                     self.log_collector.send(cwe_warning.into()).unwrap();
                 }
             }
-            Def::Store { address: _, value } => {
+            Def::Store {
+                address: address_expr,
+                value,
+            } => {
                 let address = match self.pointer_inference.eval_address_at_def(&def.tid) {
                     Some(address) => address,
                     None => return None, // There seems to be no pointer inference state here.
                 };
-                let warnings = state.check_address_access(&address, value.bytesize(), self);
+                let address_congruence = state.eval_congruence(address_expr);
+                let warnings = state.check_address_access(
+                    &address,
+                    &address_congruence,
+                    value.bytesize(),
+                    self,
+                );
                 if !warnings.is_empty() {
                     let mut cwe_warning = CweWarning::new(
                         "CWE787",
@@ -57,7 +80,8 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                             "(Out-of-bounds Write) Memory write at {} may be out of bounds.",
                             def.tid.address()
                         ),
-                    );
+                    )
+                    .mitigations(self.get_stack_mitigations(&state, &address));
                     cwe_warning.tids = vec![format!("{}", def.tid)];
                     cwe_warning.addresses = vec![def.tid.address().to_string()];
                     cwe_warning.other = vec![warnings];
@@ -66,19 +90,29 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             }
             Def::Assign { .. } => (),
         }
+        state.handle_def_congruences(&def.term);
 
         Some(state)
     }
 
     /// The state does not change for intraprocedural jumps.
+    /// If the jump enters a copy loop, the accesses of the whole loop are checked.
     fn update_jump(
         &self,
         state: &State,
-        _jump: &Term<Jmp>,
+        jump: &Term<Jmp>,
         _untaken_conditional: Option<&Term<Jmp>>,
-        _target: &Term<Blk>,
+        target: &Term<Blk>,
     ) -> Option<State> {
-        Some(state.clone())
+        let mut state = state.clone();
+        if let Some(summary) = self
+            .loop_summaries
+            .get(&target.tid)
+            .filter(|summary| summary.is_entered_by(jump, target))
+        {
+            self.check_copy_loop_at_entry(&mut state, jump, summary);
+        }
+        Some(state)
     }
 
     /// Always returns `None`, since the fixpoint computation is intraprocedural
@@ -94,7 +128,8 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         None
     }
 
-    /// Just return the `state_before_call` since the fixpoint comutation is intraprocedural.
+    /// Return the `state_before_call` since the fixpoint comutation is intraprocedural.
+    /// Only the congruences of registers that may be changed by the callee are removed.
     fn update_return(
         &self,
         _state_before_return: Option<&State>,
@@ -104,7 +139,9 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         _calling_convention: &Option<String>,
     ) -> Option<State> {
         // The analysis is intraprocedural
-        state_before_call.cloned()
+        let mut state = state_before_call.cloned()?;
+        state.remove_congruences_clobbered_by_call(self.project);
+        Some(state)
     }
 
     /// For calls to extern symbols check whether any parameter may point out of bounds of the corresponding memory object.
@@ -136,6 +173,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             }
             _ => (),
         }
+        state.remove_congruences_clobbered_by_call(self.project);
         Some(state)
     }
 
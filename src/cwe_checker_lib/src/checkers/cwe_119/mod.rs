@@ -20,10 +20,28 @@
 //! corresponding memory objects. Additionally, the check uses a lightweight
 //! dataflow fixpoint computation to ensure that for each memory object only the
 //! first access outside of its bounds is flagged as a CWE.
+//! The fixpoint computation also tracks the congruences (i.e. the alignment)
+//! of register values. They are used to restrict the offsets of aligned memory
+//! accesses, e.g. in loops copying whole words, to the offsets actually
+//! possible for the access.
+//! For calls to `sprintf` with a constant format string the
+//! [minimum output length](crate::abstract_domain::FormatString::get_min_output_length)
+//! of the format string is used as the size of the access to the destination
+//! buffer.
+//! If the [string abstraction](crate::analysis::string_abstraction) was
+//! computed for other checks, calls to `strcpy` and `stpcpy` whose source
+//! string has a known length bound, e.g. from a length check against a
+//! constant, are checked with this bound (plus the terminating null byte) as
+//! the size of the access to the destination buffer.
+//! [Copy loops](crate::analysis::loop_idioms) with a known element count are
+//! checked like calls to `memcpy` when they are entered. Vector loads of scan
+//! loops, e.g. inlined `strlen` loops, may read past the end of the string, so
+//! only their first byte is checked.
 //!
 //! Currently, the check is only partially interprocedural.
 //! Bounds of parameter objects can be detected, but bounds of memory objects
-//! created in called functions (other than the standard allocation functions)
+//! created in called functions (other than the standard allocation functions
+//! and the allocation wrappers configured for the Pointer Inference analysis)
 //! will not be detected.
 //!
 //! ## False Positives
@@ -33,6 +51,10 @@
 //! - If no exact bounds for a memory object could be inferred then the
 //!   strictest (smallest) bounds found are used, which can lead to false
 //!   positive warnings.
+//! - Overflows of the stack frame of functions protected by a stack canary or
+//!   compiled with `_FORTIFY_SOURCE` are still reported, but with a
+//!   [low severity](crate::utils::log::Severity::Low), since they are probably
+//!   detected at runtime. The mitigations are listed in the warning.
 //!
 //! ## False Negatives
 //!
@@ -63,7 +85,8 @@
 //!   miss object sizes of objects created in called functions. For example, if
 //!   allocations are wrapped in simple wrapper functions, the analysis will
 //!   miss overflows for corresponding objects, because it cannot determine
-//!   their object sizes.
+//!   their object sizes. Such wrappers can be declared as allocation wrappers
+//!   in the configuration of the Pointer Inference analysis.
 
 // FIXME: The current implementation uses path hints for memory object IDs to determine object sizes interprocedurally.
 // But the number of path hint combinations can grow exponentially
@@ -81,12 +104,18 @@ use crate::prelude::*;
 use crate::utils::log::LogThread;
 
 mod context;
+pub(crate) use context::compute_size_values_of_malloc_calls;
 use context::Context;
 mod state;
 use state::State;
 mod stubs;
 
-cwe_module!("CWE119", "0.3", check_cwe);
+cwe_module!(
+    "CWE119",
+    "0.3",
+    check_cwe,
+    requires: [FunctionSignatures, PointerInference]
+);
 
 /// Run the check for CWE-119: Buffer Overflows.
 ///
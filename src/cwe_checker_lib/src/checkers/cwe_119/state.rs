@@ -3,7 +3,7 @@ use super::Context;
 use super::Data;
 use crate::abstract_domain::*;
 use crate::analysis::function_signature::FunctionSignature;
-use crate::intermediate_representation::Project;
+use crate::intermediate_representation::*;
 use crate::prelude::*;
 use std::collections::BTreeMap;
 
@@ -11,6 +11,10 @@ use std::collections::BTreeMap;
 /// and lists of the lower and upper bounds for all known memory objects.
 ///
 /// The bounds of memory objects are computed the first time an access to it is observed.
+///
+/// The state also tracks the congruences of register values,
+/// which are used to refine the offsets of memory accesses computed by the Pointer Inference analysis.
+/// For pointers the congruence describes the offset of the pointer relative to the pointed-to memory object.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct State {
     /// The abstract identifier of the stack frame of the function that the state belongs to.
@@ -19,6 +23,8 @@ pub struct State {
     object_lower_bounds: DomainMap<AbstractIdentifier, BitvectorDomain, UnionMergeStrategy>,
     /// The upper bounds of all memory objects for which accesses have been observed.
     object_upper_bounds: DomainMap<AbstractIdentifier, BitvectorDomain, UnionMergeStrategy>,
+    /// The known congruences of register values.
+    register_congruences: DomainMap<Variable, CongruenceDomain, IntersectMergeStrategy>,
 }
 
 impl State {
@@ -30,11 +36,7 @@ impl State {
     pub fn new(function_tid: &Tid, function_sig: &FunctionSignature, project: &Project) -> State {
         let stack_id =
             AbstractIdentifier::from_var(function_tid.clone(), &project.stack_pointer_register);
-        let stack_upper_bound: i64 = match project.stack_pointer_register.name.as_str() {
-            "ESP" => 4,
-            "RSP" => 8,
-            _ => 0,
-        };
+        let stack_upper_bound = u64::from(project.get_return_address_size()) as i64;
         let stack_upper_bound = std::cmp::max(
             stack_upper_bound,
             function_sig.get_stack_params_total_size(&project.stack_pointer_register),
@@ -49,19 +51,106 @@ impl State {
                 .into_resize_signed(stack_id.bytesize())
                 .into(),
         )]);
+        // The stack pointer points to the start of the stack frame at the start of the function.
+        let register_congruences = BTreeMap::from([(
+            project.stack_pointer_register.clone(),
+            Bitvector::zero(project.stack_pointer_register.size.into()).into(),
+        )]);
         State {
             stack_id,
             object_lower_bounds: object_lower_bounds.into(),
             object_upper_bounds: object_upper_bounds.into(),
+            register_congruences: register_congruences.into(),
+        }
+    }
+
+    /// Returns the TID of the function that the state belongs to.
+    pub fn get_fn_tid(&self) -> &Tid {
+        self.stack_id.get_tid()
+    }
+
+    /// Returns `true` if the given address may point into the stack frame of the function that the state belongs to.
+    pub fn may_point_to_own_stack_frame(&self, address: &Data) -> bool {
+        address.get_relative_values().contains_key(&self.stack_id)
+    }
+
+    /// Evaluate the congruence of the given expression.
+    /// Registers without known congruence are evaluated to *Top*.
+    pub fn eval_congruence(&self, expression: &Expression) -> CongruenceDomain {
+        match expression {
+            Expression::Var(var) => self
+                .register_congruences
+                .get(var)
+                .cloned()
+                .unwrap_or_else(|| CongruenceDomain::new_top(var.size)),
+            Expression::Const(bitvector) => bitvector.clone().into(),
+            Expression::BinOp { op, lhs, rhs } => self
+                .eval_congruence(lhs)
+                .bin_op(*op, &self.eval_congruence(rhs)),
+            Expression::UnOp { op, arg } => self.eval_congruence(arg).un_op(*op),
+            Expression::Cast { op, size, arg } => self.eval_congruence(arg).cast(*op, *size),
+            Expression::Unknown { size, .. } => CongruenceDomain::new_top(*size),
+            Expression::Subpiece {
+                low_byte,
+                size,
+                arg,
+            } => self.eval_congruence(arg).subpiece(*low_byte, *size),
         }
     }
 
+    /// Evaluate the congruence of the given parameter.
+    /// The congruences of stack parameters are unknown.
+    pub fn eval_param_congruence(&self, param: &Arg) -> CongruenceDomain {
+        match param {
+            Arg::Register { expr, .. } => self.eval_congruence(expr),
+            Arg::Stack { size, .. } => CongruenceDomain::new_top(*size),
+        }
+    }
+
+    /// Update the register congruences according to the given `Def`.
+    /// The congruences of loaded values are unknown.
+    pub fn handle_def_congruences(&mut self, def: &Def) {
+        match def {
+            Def::Assign { var, value } => {
+                let congruence = self.eval_congruence(value);
+                if congruence.is_top() {
+                    self.register_congruences.remove(var);
+                } else {
+                    self.register_congruences.insert(var.clone(), congruence);
+                }
+            }
+            Def::Load { var, .. } => {
+                self.register_congruences.remove(var);
+            }
+            Def::Store { .. } => (),
+        }
+    }
+
+    /// Remove the congruences of all registers that may be changed by a call.
+    ///
+    /// Only the congruences of the stack pointer and of callee-saved registers
+    /// of the standard calling convention of the project are kept.
+    pub fn remove_congruences_clobbered_by_call(&mut self, project: &Project) {
+        let callee_saved_registers = project
+            .get_standard_calling_convention()
+            .map(|cconv| cconv.callee_saved_register.as_slice())
+            .unwrap_or_default();
+        self.register_congruences.retain(|var, _| {
+            *var == project.stack_pointer_register || callee_saved_registers.contains(var)
+        });
+    }
+
     /// Check for the given address whether the access to it would be in the boundaries for all possible target memory objects.
     /// Return a list of logging messages describing those cases
     /// where the access may fall outside of the corresponding memory object boundaries.
+    ///
+    /// The offsets of the access are restricted to the given congruence before checking them against the bounds.
+    /// This prevents false positives for aligned accesses (e.g. in loops copying whole words)
+    /// where the offset interval alone would allow unaligned offsets near the object bounds.
     pub fn check_address_access(
         &mut self,
         address: &Data,
+        address_congruence: &CongruenceDomain,
         value_size: ByteSize,
         context: &Context,
     ) -> Vec<String> {
@@ -70,7 +159,13 @@ impl State {
             if !self.object_lower_bounds.contains_key(id) {
                 self.compute_bounds_of_id(id, context);
             }
-            if let Ok((lower_offset, upper_offset)) = offset.try_to_offset_interval() {
+            let offset_interval = offset
+                .try_to_interval()
+                .and_then(|interval| address_congruence.refine_interval(&interval))
+                .and_then(|interval| {
+                    Ok((interval.start.try_to_i64()?, interval.end.try_to_i64()?))
+                });
+            if let Ok((lower_offset, upper_offset)) = offset_interval {
                 if let Ok(lower_bound) = self.object_lower_bounds.get(id).unwrap().try_to_offset() {
                     if lower_bound > lower_offset {
                         out_of_bounds_access_warnings.push(format!("For the object ID {id} access to the offset {lower_offset} may be smaller than the lower object bound of {lower_bound}."));
@@ -180,6 +275,7 @@ impl AbstractDomain for State {
             stack_id: self.stack_id.clone(),
             object_lower_bounds: self.object_lower_bounds.merge(&other.object_lower_bounds),
             object_upper_bounds: self.object_upper_bounds.merge(&other.object_upper_bounds),
+            register_congruences: self.register_congruences.merge(&other.register_congruences),
         }
     }
 
@@ -213,6 +309,15 @@ impl State {
             .map(|(id, bound)| Value::String(format!("{id}: {bound}")))
             .collect();
         state_map.insert("upper_bounds".to_string(), Value::Array(upper_bounds));
+        let register_congruences: Vec<_> = self
+            .register_congruences
+            .iter()
+            .map(|(var, congruence)| Value::String(format!("{}: {congruence}", var.name)))
+            .collect();
+        state_map.insert(
+            "register_congruences".to_string(),
+            Value::Array(register_congruences),
+        );
 
         Value::Object(state_map)
     }
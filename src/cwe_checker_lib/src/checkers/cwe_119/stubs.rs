@@ -1,8 +1,9 @@
 use super::{Context, State};
-use crate::abstract_domain::{RegisterDomain, TryToInterval};
-use crate::analysis::pointer_inference::Data;
+use crate::abstract_domain::{FormatString, RegisterDomain, TryToBitvec};
 use crate::utils::log::CweWarning;
+use crate::utils::mitigations::Mitigation;
 use crate::{analysis::vsa_results::VsaResult, intermediate_representation::*};
+use std::collections::BTreeSet;
 
 /// A struct containing all relevant information for handling an extern call.
 pub struct ExternCallHandler<'a, 'b> {
@@ -10,6 +11,8 @@ pub struct ExternCallHandler<'a, 'b> {
     state: &'a mut State,
     fn_symbol: &'a ExternSymbol,
     jump: &'a Term<Jmp>,
+    /// The exploit mitigations protecting the buffers for which out-of-bounds accesses were detected.
+    mitigations: BTreeSet<Mitigation>,
 }
 
 impl<'a, 'b> ExternCallHandler<'a, 'b> {
@@ -25,6 +28,7 @@ impl<'a, 'b> ExternCallHandler<'a, 'b> {
             state,
             fn_symbol,
             jump,
+            mitigations: BTreeSet::new(),
         }
     }
 
@@ -42,6 +46,14 @@ impl<'a, 'b> ExternCallHandler<'a, 'b> {
                 warnings.append(&mut self.check_buffer_size(0, 1))
             }
             "memchr" | "memset" => warnings.append(&mut self.check_buffer_size(0, 2)),
+            "sprintf" => match self.compute_buffer_size_from_format_string(1) {
+                Some(size) => warnings.append(&mut self.check_buffer_with_concrete_size(0, size)),
+                None => self.handle_generic_call(),
+            },
+            "stpcpy" | "strcpy" => match self.compute_buffer_size_from_string_length_bound(1) {
+                Some(size) => warnings.append(&mut self.check_buffer_with_concrete_size(0, size)),
+                None => self.handle_generic_call(),
+            },
             "getenv_s" | "read" | "recv" | "recvfrom" | "sendto" | "write" => {
                 warnings.append(&mut self.check_buffer_size(1, 2))
             }
@@ -60,7 +72,9 @@ impl<'a, 'b> ExternCallHandler<'a, 'b> {
                 self.fn_symbol.name,
                 self.jump.tid.address()
             );
-            let mut cwe_warning = CweWarning::new("CWE119", super::CWE_MODULE.version, description);
+            let mitigations = std::mem::take(&mut self.mitigations);
+            let mut cwe_warning = CweWarning::new("CWE119", super::CWE_MODULE.version, description)
+                .mitigations(mitigations);
             cwe_warning.tids = vec![format!("{}", self.jump.tid)];
             cwe_warning.addresses = vec![self.jump.tid.address().to_string()];
             cwe_warning.other = vec![warnings];
@@ -121,7 +135,15 @@ impl<'a, 'b> ExternCallHandler<'a, 'b> {
             None => return Vec::new(),
         };
 
-        self.state.check_address_access(&buffer, size, self.context)
+        let buffer_congruence = self.state.eval_param_congruence(buffer_param);
+        let warnings =
+            self.state
+                .check_address_access(&buffer, &buffer_congruence, size, self.context);
+        if !warnings.is_empty() {
+            self.mitigations
+                .append(&mut self.context.get_stack_mitigations(self.state, &buffer));
+        }
+        warnings
     }
 
     /// Compute the size of a buffer from a corresponding size parameter of an extern function.
@@ -131,7 +153,52 @@ impl<'a, 'b> ExternCallHandler<'a, 'b> {
             &self.jump.tid,
             self.fn_symbol.parameters.get(size_param_index)?,
         )?;
-        self.compute_buffer_size_from_data_domain(size)
+        self.context.compute_buffer_size_from_data_domain(size)
+    }
+
+    /// Compute the size of a buffer from the minimum output length of a constant format string parameter,
+    /// including the terminating null byte.
+    /// Returns `None` if the format string is not a constant string or cannot be parsed.
+    fn compute_buffer_size_from_format_string(
+        &self,
+        format_string_param_index: usize,
+    ) -> Option<ByteSize> {
+        let format_string = self.context.pointer_inference.eval_parameter_arg_at_call(
+            &self.jump.tid,
+            self.fn_symbol.parameters.get(format_string_param_index)?,
+        )?;
+        let address = format_string
+            .get_if_absolute_value()?
+            .try_to_bitvec()
+            .ok()?;
+        let format_string = self
+            .context
+            .project
+            .runtime_memory_image
+            .read_string_until_null_terminator(&address)
+            .ok()?;
+        let min_length = FormatString::parse(format_string)
+            .ok()?
+            .get_min_output_length();
+        Some((min_length + 1).into())
+    }
+
+    /// Compute the size of a buffer from the known upper bound for the length of a string parameter,
+    /// including the terminating null byte.
+    /// Returns `None` if the string abstraction was not computed or no length bound is known for the string.
+    fn compute_buffer_size_from_string_length_bound(
+        &self,
+        string_param_index: usize,
+    ) -> Option<ByteSize> {
+        let length_bound = self
+            .context
+            .string_abstraction?
+            .get_string_predicates_at_call(
+                &self.jump.tid,
+                self.fn_symbol.parameters.get(string_param_index)?,
+            )
+            .get_length_bound()?;
+        Some((length_bound + 1).into())
     }
 
     /// Compute the size of a buffer as the product from corresponding size and count parameters of an extern function.
@@ -150,26 +217,7 @@ impl<'a, 'b> ExternCallHandler<'a, 'b> {
             self.fn_symbol.parameters.get(count_param_index)?,
         )?;
         let size = size_param.bin_op(BinOpType::IntMult, &count_param);
-        self.compute_buffer_size_from_data_domain(size)
-    }
-
-    /// Compute the size of a buffer from a corresponding size value.
-    /// Returns `None` if no absolute size value could be determined for any reason.
-    ///
-    /// If a range of possible sizes is detected, use the smallest possible size,
-    /// as using larger sizes would lead to too many false positive CWE warnings.
-    fn compute_buffer_size_from_data_domain(&self, size: Data) -> Option<ByteSize> {
-        let size = self.context.recursively_substitute_param_values(&size);
-        let (lower_bound, _upper_bound) =
-            size.get_absolute_value()?.try_to_offset_interval().ok()?;
-        // FIXME: Currently we need to use the lower bound,
-        // because using the upper bound would lead to too many false positives.
-        // To fix this we probably need to implement tracking linear dependencies between values.
-        if lower_bound > 0 {
-            Some((lower_bound as u64).into())
-        } else {
-            None
-        }
+        self.context.compute_buffer_size_from_data_domain(size)
     }
 
     /// Generic call handler.
@@ -14,6 +14,13 @@
 //! content of the format string parameter and if it is not part of the global
 //! read only memory of the binary, a CWE warning is generated.
 //!
+//! Format strings in read only memory are [parsed](crate::abstract_domain::FormatString)
+//! to determine the number and the types of the variadic arguments that they
+//! expect. They are compared with the [arguments set up by the caller](crate::analysis::variadic_arguments)
+//! in the basic block of the call. If an expected argument is not set up or if
+//! the caller stores more arguments to the stack than expected, a CWE-685
+//! warning (Function Call With Incorrect Number of Arguments) is generated.
+//!
 //! ### Symbols configurable in config.json
 //!
 //! - Symbols that take a format string parameter.
@@ -24,19 +31,50 @@
 //!   source.
 //! - A pointer target could be lost but the format string was not externally
 //!   provided.
+//! - Arguments that are set up in a different basic block than the call are
+//!   reported as missing.
+//! - Stores to local variables that directly follow the outgoing stack
+//!   arguments are reported as excess arguments.
+//!
+//! ## False Negatives
+//!
+//! - Excess arguments are only detected if they are passed on the stack.
+//!
+//! ## Reproduction Hints
+//!
+//! If `reproduction_hints` is enabled in the `config.json`, user input is
+//! tracked to the format string parameters, see
+//! [`input_provenance`](crate::analysis::input_provenance). The input sources
+//! are taken from the string abstraction configuration if the string
+//! abstraction was computed. CWE-134 warnings at calls that are reached by an
+//! environment variable, a program argument or stdin get a
+//! [`ReproductionHint`] with an example format string that reads from and
+//! writes to the stack.
+//!
+//! ## Explanations
+//!
+//! If the Pointer Inference records precision losses (see the `--explain`
+//! command line flag), warnings get an [`Explanation`] with the abstract value
+//! of the format string parameter at the call and the merges at which the
+//! register holding the parameter lost precision in the calling function.
 use super::prelude::*;
 
-use crate::abstract_domain::TryToBitvec;
-use crate::analysis::graph::Edge;
+use crate::abstract_domain::{FormatString, TryToBitvec};
+use crate::analysis::explanation::{format_abstract_value, format_parameter, Explanation};
+use crate::analysis::graph::{Edge, Node};
+use crate::analysis::input_provenance::{get_input_origins_at_sinks, ReproductionHint};
+use crate::analysis::input_sources::InputSources;
 use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
 use crate::analysis::pointer_inference::PointerInference;
-use crate::intermediate_representation::ExternSymbol;
-use crate::intermediate_representation::Jmp;
+use crate::analysis::variadic_arguments::{MaterializedArguments, VariadicArgumentCount};
 use crate::intermediate_representation::RuntimeMemoryImage;
+use crate::intermediate_representation::{Arg, Datatype, Expression, Project};
+use crate::intermediate_representation::{ExternSymbol, Jmp, Term};
 use crate::prelude::*;
+use crate::utils::arguments::{calculate_parameter_locations, get_input_format_string};
 use crate::utils::log::CweWarning;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use petgraph::graph::NodeIndex;
 use petgraph::visit::EdgeRef;
@@ -45,13 +83,19 @@ cwe_module!(
     "CWE134",
     "0.1",
     check_cwe,
+    requires: [PointerInference],
     config:
         /// Names of symbols with a format string parameter.
         format_string_symbols: Vec<String>,
         /// Index of the format string parameter.
         format_string_index: HashMap<String, usize>,
+        /// Whether to add reproduction hints to warnings reached by user input.
+        reproduction_hints: bool,
 );
 
+/// The example input of reproduction hints. It leaks stack values and writes to memory.
+const FORMAT_STRING_PAYLOAD: &str = "%p%p%p%p%n";
+
 /// Categorization of the string location in memory.
 pub enum StringLocation {
     /// Global read only memory.
@@ -87,6 +131,7 @@ pub fn check_cwe(
 
     let pointer_inference_results = analysis_results.pointer_inference.unwrap();
     let mut cwe_warnings = Vec::new();
+    let mut sinks: BTreeMap<Tid, Vec<Arg>> = BTreeMap::new();
 
     for edge in pointer_inference_results.get_graph().edge_references() {
         if let Edge::ExternCallStub(jmp) = edge.weight() {
@@ -104,7 +149,36 @@ pub fn check_cwe(
                         location,
                         StringLocation::GlobalWriteable | StringLocation::NonGlobal
                     ) {
-                        cwe_warnings.push(generate_cwe_warning(&jmp.tid, symbol, &location));
+                        let explanation = explain_format_string(
+                            edge.source(),
+                            symbol,
+                            &format_string_index,
+                            pointer_inference_results,
+                            &analysis_results.project.runtime_memory_image,
+                        );
+                        cwe_warnings.push(
+                            generate_cwe_warning(&jmp.tid, symbol, &location)
+                                .explanation(explanation),
+                        );
+                        if let Some(parameter) = format_string_index
+                            .get(&symbol.name)
+                            .and_then(|index| symbol.parameters.get(*index))
+                        {
+                            sinks.insert(jmp.tid.clone(), vec![parameter.clone()]);
+                        }
+                    } else if matches!(location, StringLocation::GlobalReadable) {
+                        if let Some(mismatch) = get_format_argument_mismatch(
+                            edge.source(),
+                            jmp,
+                            symbol,
+                            &format_string_index,
+                            pointer_inference_results,
+                            project,
+                        ) {
+                            cwe_warnings.push(generate_argument_mismatch_warning(
+                                &jmp.tid, symbol, &mismatch,
+                            ));
+                        }
                     } else if matches!(location, StringLocation::Unknown) {
                         logs.push(LogMessage::new_debug(format!(
                             "{}: No PI result for call at {}.",
@@ -116,6 +190,14 @@ pub fn check_cwe(
         }
     }
 
+    if config.reproduction_hints {
+        let input_sources = analysis_results
+            .string_abstraction
+            .map(|string_abstraction| string_abstraction.get_context().input_sources.clone())
+            .unwrap_or_default();
+        add_reproduction_hints(&mut cwe_warnings, analysis_results, &input_sources, &sinks);
+    }
+
     WithLogs::new(
         cwe_warnings
             .deduplicate_first_address()
@@ -125,6 +207,29 @@ pub fn check_cwe(
     )
 }
 
+/// Add reproduction hints to the CWE-134 warnings at sink calls that are reached by user input.
+fn add_reproduction_hints(
+    cwe_warnings: &mut [CweWarning],
+    analysis_results: &AnalysisResults,
+    input_sources: &InputSources,
+    sinks: &BTreeMap<Tid, Vec<Arg>>,
+) {
+    let origins = get_input_origins_at_sinks(analysis_results, input_sources, sinks);
+    for (sink, origins) in origins {
+        let tid = sink.to_string();
+        let Some(warning) = cwe_warnings
+            .iter_mut()
+            .find(|warning| warning.name == CWE_MODULE.name && warning.tids.first() == Some(&tid))
+        else {
+            continue;
+        };
+        warning.reproduction_hints = origins
+            .into_iter()
+            .map(|origin| ReproductionHint::new(origin, Vec::new(), FORMAT_STRING_PAYLOAD))
+            .collect();
+    }
+}
+
 /// Returns a StringLocation based on the kind of memory holding the string.
 ///
 /// If no assumption about the string location can be made, unknown is returned.
@@ -167,6 +272,183 @@ fn locate_format_string(
     }
 }
 
+/// Explain a warning for the format string parameter of the call at the given node.
+///
+/// Returns `None` if the Pointer Inference does not record precision losses
+/// or has no state for the node.
+fn explain_format_string(
+    node: NodeIndex,
+    symbol: &ExternSymbol,
+    format_string_index: &HashMap<String, usize>,
+    pointer_inference_results: &PointerInference,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Option<Explanation> {
+    let Some(NodeValue::Value(pi_state)) = pointer_inference_results.get_node_value(node) else {
+        return None;
+    };
+    let parameter = symbol
+        .parameters
+        .get(*format_string_index.get(&symbol.name)?)?;
+    let value = match pi_state.eval_parameter_arg(parameter, runtime_memory_image) {
+        Ok(value) => format_abstract_value(&value),
+        Err(_) => "unknown".to_string(),
+    };
+    let fact = format!(
+        "The format string parameter {} of {} has the value {}",
+        format_parameter(parameter),
+        symbol.name,
+        value
+    );
+    Explanation::explain_parameter(pointer_inference_results, node, parameter, fact)
+}
+
+/// Mismatch between the variadic arguments expected by a constant format
+/// string and the arguments that the caller sets up.
+#[derive(Debug, PartialEq, Eq)]
+enum ArgumentMismatch {
+    /// Expected arguments that are not set up before the call.
+    Missing(Vec<Arg>),
+    /// More stack slots are set up before the call than expected.
+    Excess {
+        /// Number of stack slots expected by the format string.
+        expected: usize,
+        /// Number of stack slots set up before the call.
+        materialized: usize,
+    },
+}
+
+/// Compares the variadic arguments expected by the constant format string of
+/// a call with the arguments that are set up in the block of the call.
+///
+/// Excess arguments are only reported if all integer parameter registers are
+/// used by the expected arguments. Otherwise stores to local variables in the
+/// outgoing argument area cannot be distinguished from arguments.
+///
+/// Returns `None` if there is no mismatch, if the format string cannot be
+/// parsed or if the locations of its arguments cannot be computed.
+fn get_format_argument_mismatch(
+    node: NodeIndex,
+    jmp: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    format_string_index: &HashMap<String, usize>,
+    pointer_inference_results: &PointerInference,
+    project: &Project,
+) -> Option<ArgumentMismatch> {
+    let Node::BlkEnd(blk, _) = pointer_inference_results.get_graph()[node] else {
+        return None;
+    };
+    let Some(NodeValue::Value(pi_state)) = pointer_inference_results.get_node_value(node) else {
+        return None;
+    };
+    let format_string = get_input_format_string(
+        pi_state,
+        symbol,
+        *format_string_index.get(&symbol.name)?,
+        &project.runtime_memory_image,
+    )
+    .ok()?;
+    let mut arguments = Vec::new();
+    for datatype in FormatString::parse(&format_string)
+        .ok()?
+        .get_argument_datatypes()
+    {
+        let size = project
+            .datatype_properties
+            .get_size_from_data_type(datatype.clone());
+        let datatype = match datatype {
+            // Passed in the same registers as integers.
+            Datatype::Long | Datatype::LongLong => Datatype::Integer,
+            Datatype::LongDouble => return None,
+            datatype => datatype,
+        };
+        arguments.push((datatype, size));
+    }
+    let arguments = calculate_parameter_locations(arguments, symbol, project);
+    let materialized_arguments =
+        MaterializedArguments::new(blk, &jmp.tid, project, pointer_inference_results)?;
+
+    let missing_arguments: Vec<Arg> = arguments
+        .iter()
+        .filter(|argument| !materialized_arguments.is_materialized(argument))
+        .cloned()
+        .collect();
+    if !missing_arguments.is_empty() {
+        return Some(ArgumentMismatch::Missing(missing_arguments));
+    }
+
+    let expected = VariadicArgumentCount::from_arguments(&arguments, symbol, project);
+    let materialized = materialized_arguments.count_variadic_arguments(symbol, project);
+    let free_integer_registers = project
+        .get_calling_convention(symbol)
+        .integer_parameter_register
+        .len()
+        .saturating_sub(symbol.parameters.len());
+    if expected.integer_registers == free_integer_registers
+        && materialized.stack_slots > expected.stack_slots
+    {
+        Some(ArgumentMismatch::Excess {
+            expected: expected.stack_slots,
+            materialized: materialized.stack_slots,
+        })
+    } else {
+        None
+    }
+}
+
+/// Returns a human readable description of the location of an argument.
+fn describe_argument(argument: &Arg) -> String {
+    match argument {
+        Arg::Register {
+            expr: Expression::Var(var),
+            ..
+        } => var.name.clone(),
+        Arg::Register { expr, .. } => format!("{expr}"),
+        Arg::Stack { .. } => match argument
+            .eval_stack_offset()
+            .ok()
+            .and_then(|offset| offset.try_to_i64().ok())
+        {
+            Some(offset) => format!("the stack at offset {offset}"),
+            None => "the stack".to_string(),
+        },
+    }
+}
+
+/// Generate the CWE warning for a call whose constant format string does not
+/// match the arguments set up by the caller.
+fn generate_argument_mismatch_warning(
+    callsite: &Tid,
+    called_symbol: &ExternSymbol,
+    mismatch: &ArgumentMismatch,
+) -> CweWarning {
+    let description = match mismatch {
+        ArgumentMismatch::Missing(missing_arguments) => format!(
+            "(Format String Argument Mismatch) The format string of the call to {} at {} expects arguments in {} that are not set up before the call",
+            called_symbol.name,
+            callsite.address(),
+            missing_arguments
+                .iter()
+                .map(describe_argument)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        ArgumentMismatch::Excess {
+            expected,
+            materialized,
+        } => format!(
+            "(Format String Argument Mismatch) The format string of the call to {} at {} expects {} stack arguments but {} are set up before the call",
+            called_symbol.name,
+            callsite.address(),
+            expected,
+            materialized
+        ),
+    };
+    CweWarning::new("CWE685", CWE_MODULE.version, description)
+        .tids(vec![format!("{callsite}")])
+        .addresses(vec![callsite.address().to_string()])
+        .symbols(vec![called_symbol.name.clone()])
+}
+
 /// Generate the CWE warning for a detected instance of the CWE.
 fn generate_cwe_warning(
     callsite: &Tid,
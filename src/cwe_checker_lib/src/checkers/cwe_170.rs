@@ -0,0 +1,116 @@
+//! This module implements a check for CWE-170: Improper Null Termination.
+//!
+//! Functions that operate on C strings like `strlen` or `printf` with a `%s`
+//! format specifier read until they find a terminating NUL byte. If a buffer
+//! passed to them is not NUL-terminated, they read past the end of the buffer,
+//! which can lead to information leaks or crashes.
+//!
+//! See <https://cwe.mitre.org/data/definitions/170.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using an intraprocedural dataflow analysis based on the results of the
+//! [Pointer Inference analysis](`crate::analysis::pointer_inference`) the check
+//! tracks the NUL-termination of string buffers as a three-valued property
+//! (see [`NulTerminationDomain`](crate::abstract_domain::NulTerminationDomain)).
+//!
+//! - Calls to `terminating_symbols` (e.g. `strcpy`) mark the buffer given by
+//!   their first parameter as NUL-terminated.
+//! - Calls to `copy_symbols` (e.g. `strncpy` or `memcpy`) mark the buffer given
+//!   by their first parameter as possibly unterminated. If a constant string is
+//!   copied, the buffer is NUL-terminated if the number of copied bytes is
+//!   larger than the length of the string and unterminated otherwise.
+//! - Storing a NUL byte into a buffer marks it as NUL-terminated.
+//!
+//! A CWE warning is generated if a possibly unterminated buffer is passed to a
+//! parameter that expects a NUL-terminated string. These parameters are given
+//! by the `string_parameter_index` map and by `%s` format specifiers of calls
+//! to the symbols in the `format_string_index` map.
+//!
+//! ## False Positives
+//!
+//! - Since the size of buffers is unknown to the check, calls like
+//!   `strncpy(buf, src, sizeof(buf) - 1)` into a zero-initialized buffer are
+//!   still reported.
+//! - Since the analysis is not path-sensitive, infeasible paths may lead to
+//!   false positives.
+//!
+//! ## False Negatives
+//!
+//! - The analysis is intraprocedural. Buffers passed to other functions are
+//!   assumed to be NUL-terminated afterwards.
+//! - A NUL byte stored anywhere inside a tracked buffer marks the buffer as
+//!   terminated, even if it is stored after the end of the buffer.
+//! - Buffers that are only reachable through pointers with inexact offsets are
+//!   not tracked.
+use super::prelude::*;
+
+use crate::prelude::*;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+cwe_module!(
+    "CWE170",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// The names of extern functions that always NUL-terminate the buffer
+        /// given by their first parameter (e.g. `strcpy`).
+        terminating_symbols: Vec<String>,
+        /// The names of extern functions that copy data into the buffer given
+        /// by their first parameter without guaranteeing NUL-termination. The
+        /// second parameter is assumed to be the source and the third
+        /// parameter the number of copied bytes (e.g. `strncpy`).
+        copy_symbols: Vec<String>,
+        /// Maps the names of extern functions to the index of a parameter that
+        /// is expected to be a NUL-terminated string.
+        string_parameter_index: HashMap<String, usize>,
+        /// Maps the names of extern functions to the index of their format
+        /// string parameter. Parameters for `%s` format specifiers are
+        /// expected to be NUL-terminated strings.
+        format_string_index: HashMap<String, usize>,
+);
+
+mod context;
+use context::Context;
+mod state;
+use state::State;
+
+/// Run the check for CWE-170: Improper Null Termination.
+///
+/// The check is run as a fixpoint computation over the whole program,
+/// where each function is analyzed separately.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    config_json: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let config: Config = serde_json::from_value(config_json.clone()).unwrap();
+    let (cwe_warning_sender, cwe_warning_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(analysis_results, &config, cwe_warning_sender);
+
+    let mut fixpoint_computation =
+        crate::analysis::forward_interprocedural_fixpoint::create_computation(context, None);
+
+    for (sub_tid, entry_node_of_sub) in
+        crate::analysis::graph::get_entry_nodes_of_subs(analysis_results.control_flow_graph)
+    {
+        fixpoint_computation.set_node_value(
+            entry_node_of_sub,
+            crate::analysis::interprocedural_fixpoint_generic::NodeValue::Value(State::new(
+                sub_tid,
+            )),
+        );
+    }
+
+    fixpoint_computation.compute_with_max_steps(100);
+
+    let mut warnings = BTreeSet::new();
+    while let Ok(warning) = cwe_warning_receiver.try_recv() {
+        warnings.insert(warning);
+    }
+    let cwe_warnings: Vec<CweWarning> = warnings.into_iter().collect();
+
+    cwe_warnings.deduplicate_first_address()
+}
@@ -0,0 +1,356 @@
+use super::State;
+use super::CWE_MODULE;
+use crate::abstract_domain::{NulTerminationDomain, TryToBitvec};
+use crate::analysis::graph::Graph;
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::arguments;
+use crate::utils::log::CweWarning;
+use std::collections::{BTreeSet, HashMap};
+
+/// The context struct for the fixpoint algorithm that contains references to the analysis results
+/// of other analyses used in this analysis.
+pub struct Context<'a> {
+    /// A pointer to the project struct.
+    pub project: &'a Project,
+    /// A pointer to the control flow graph.
+    pub graph: &'a Graph<'a>,
+    /// A pointer to the results of the pointer inference analysis.
+    pub pointer_inference: &'a PointerInference<'a>,
+    /// Extern functions that always NUL-terminate the buffer given by their first parameter.
+    pub terminating_symbols: BTreeSet<String>,
+    /// Extern functions that copy a given number of bytes into the buffer given by their first parameter
+    /// without guaranteeing NUL-termination.
+    pub copy_symbols: BTreeSet<String>,
+    /// Extern functions that expect a NUL-terminated string at the given parameter index.
+    pub string_parameter_index: HashMap<String, usize>,
+    /// Extern functions with a format string at the given parameter index.
+    pub format_string_index: HashMap<String, usize>,
+    /// A sender channel that can be used to collect CWE warnings.
+    pub cwe_warning_collector: crossbeam_channel::Sender<CweWarning>,
+    /// Generic function arguments assumed for calls to functions where the real number of parameters are unknown.
+    generic_function_parameter: Vec<Arg>,
+}
+
+impl<'a> Context<'a> {
+    /// Generate a new context struct from the given analysis results, configuration and a channel for gathering CWE warnings.
+    pub fn new<'b>(
+        analysis_results: &'b AnalysisResults<'a>,
+        config: &super::Config,
+        cwe_warning_collector: crossbeam_channel::Sender<CweWarning>,
+    ) -> Context<'a>
+    where
+        'a: 'b,
+    {
+        let generic_function_parameter: Vec<_> =
+            if let Some(cconv) = analysis_results.project.get_standard_calling_convention() {
+                cconv
+                    .integer_parameter_register
+                    .iter()
+                    .map(|reg| Arg::from_var(reg.clone(), None))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+        Context {
+            project: analysis_results.project,
+            graph: analysis_results.control_flow_graph,
+            pointer_inference: analysis_results.pointer_inference.unwrap(),
+            terminating_symbols: config.terminating_symbols.iter().cloned().collect(),
+            copy_symbols: config.copy_symbols.iter().cloned().collect(),
+            string_parameter_index: config.string_parameter_index.clone(),
+            format_string_index: config.format_string_index.clone(),
+            cwe_warning_collector,
+            generic_function_parameter,
+        }
+    }
+
+    /// Evaluate the parameter with the given index of a call to an extern symbol.
+    fn eval_parameter(
+        &self,
+        call_tid: &Tid,
+        extern_symbol: &ExternSymbol,
+        index: usize,
+    ) -> Option<Data> {
+        let param = extern_symbol.parameters.get(index)?;
+        self.pointer_inference
+            .eval_parameter_arg_at_call(call_tid, param)
+    }
+
+    /// Compute the termination state of the destination buffer of a call to a copy function.
+    ///
+    /// If the source is a constant string, the destination is NUL-terminated
+    /// if the string is shorter than the number of copied bytes and unterminated otherwise,
+    /// e.g. for `strncpy(buf, "abc", 3)`.
+    /// In all other cases the destination may or may not be NUL-terminated.
+    fn get_termination_after_copy(
+        &self,
+        call_tid: &Tid,
+        extern_symbol: &ExternSymbol,
+    ) -> NulTerminationDomain {
+        let source_length = self
+            .eval_parameter(call_tid, extern_symbol, 1)
+            .and_then(|source| source.get_if_absolute_value()?.try_to_bitvec().ok())
+            .and_then(|address| {
+                self.project
+                    .runtime_memory_image
+                    .read_string_until_null_terminator(&address)
+                    .ok()
+                    .map(|string| string.len() as u64)
+            });
+        let copy_length = self
+            .eval_parameter(call_tid, extern_symbol, 2)
+            .and_then(|length| length.get_if_absolute_value()?.try_to_bitvec().ok())
+            .and_then(|length| length.try_to_u64().ok());
+        match (source_length, copy_length) {
+            (Some(source_length), Some(copy_length)) if source_length < copy_length => {
+                NulTerminationDomain::Terminated
+            }
+            (Some(_), Some(_)) => NulTerminationDomain::Unterminated,
+            _ => NulTerminationDomain::Top,
+        }
+    }
+
+    /// Return the parameters of a call to an extern symbol that are expected to be NUL-terminated strings.
+    fn get_string_parameters(&self, call_tid: &Tid, extern_symbol: &ExternSymbol) -> Vec<Arg> {
+        let mut string_params = Vec::new();
+        if let Some(param) = self
+            .string_parameter_index
+            .get(&extern_symbol.name)
+            .and_then(|index| extern_symbol.parameters.get(*index))
+        {
+            string_params.push(param.clone());
+        }
+        if self.format_string_index.contains_key(&extern_symbol.name) {
+            if let Some(pi_state) = self.pointer_inference.get_state_at_jmp_tid(call_tid) {
+                if let Ok(variable_params) = arguments::get_variable_parameters(
+                    self.project,
+                    pi_state,
+                    extern_symbol,
+                    &self.format_string_index,
+                ) {
+                    string_params.extend(
+                        variable_params
+                            .into_iter()
+                            .filter(|param| param.get_data_type() == Some(Datatype::Pointer)),
+                    );
+                }
+            }
+        }
+        string_params
+    }
+
+    /// Check the string parameters of a call to an extern symbol for possibly unterminated buffers
+    /// and generate a CWE warning if one is found.
+    fn check_string_parameters(&self, state: &State, call_tid: &Tid, extern_symbol: &ExternSymbol) {
+        let may_be_unterminated = self
+            .get_string_parameters(call_tid, extern_symbol)
+            .iter()
+            .filter_map(|param| {
+                let pointer = self
+                    .pointer_inference
+                    .eval_parameter_arg_at_call(call_tid, param)?;
+                state.get_termination(&pointer)
+            })
+            .any(|termination| termination.may_be_unterminated());
+        if may_be_unterminated {
+            self.generate_cwe_warning(call_tid, &extern_symbol.name);
+        }
+    }
+
+    /// Generate a CWE warning for a possibly unterminated string passed to the given call.
+    fn generate_cwe_warning(&self, call_tid: &Tid, symbol_name: &str) {
+        let warning = CweWarning::new(
+            CWE_MODULE.name,
+            CWE_MODULE.version,
+            format!(
+                "(Improper Null Termination) Possibly unterminated string passed to {} at {}",
+                symbol_name,
+                call_tid.address()
+            ),
+        )
+        .addresses(vec![call_tid.address().to_string()])
+        .tids(vec![format!("{call_tid}")])
+        .symbols(vec![symbol_name.to_string()]);
+        self.cwe_warning_collector.send(warning).unwrap();
+    }
+}
+
+impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Context<'a> {
+    type Value = State;
+
+    /// Get a reference to the control flow graph.
+    fn get_graph(&self) -> &Graph<'a> {
+        self.graph
+    }
+
+    /// Merge two node states.
+    fn merge(&self, state1: &State, state2: &State) -> State {
+        state1.merge(state2)
+    }
+
+    /// Mark buffers as NUL-terminated if a NUL byte is stored into them.
+    fn update_def(&self, state: &State, def: &Term<Def>) -> Option<State> {
+        let mut state = state.clone();
+        if let Def::Store { value, .. } = &def.term {
+            let stores_nul_byte = value.bytesize() == ByteSize::new(1)
+                && self
+                    .pointer_inference
+                    .eval_value_at_def(&def.tid)
+                    .and_then(|value| value.get_if_absolute_value()?.try_to_bitvec().ok())
+                    .is_some_and(|value| value.is_zero());
+            if stores_nul_byte {
+                if let Some(address) = self.pointer_inference.eval_address_at_def(&def.tid) {
+                    state.handle_stored_nul_byte(&address);
+                }
+            }
+        }
+        Some(state)
+    }
+
+    /// Just returns the unmodified state.
+    fn update_jump(
+        &self,
+        state: &State,
+        _jump: &Term<Jmp>,
+        _untaken_conditional: Option<&Term<Jmp>>,
+        _target: &Term<Blk>,
+    ) -> Option<State> {
+        Some(state.clone())
+    }
+
+    /// Always returns `None` since the analysis is intraprocedural.
+    fn update_call(
+        &self,
+        _state: &State,
+        _call: &Term<Jmp>,
+        _target: &crate::analysis::graph::Node,
+        _calling_convention: &Option<String>,
+    ) -> Option<State> {
+        None
+    }
+
+    /// Continue with the state before the call.
+    /// Buffers in memory objects that the callee may access through its parameters are forgotten,
+    /// since the callee may have modified them.
+    fn update_return(
+        &self,
+        _state: Option<&State>,
+        state_before_call: Option<&State>,
+        call: &Term<Jmp>,
+        _return_term: &Term<Jmp>,
+        _calling_convention: &Option<String>,
+    ) -> Option<State> {
+        let mut state = state_before_call?.clone();
+        for param in self.generic_function_parameter.iter() {
+            if let Some(pointer) = self
+                .pointer_inference
+                .eval_parameter_arg_at_call(&call.tid, param)
+            {
+                state.forget_objects(&pointer);
+            }
+        }
+        Some(state)
+    }
+
+    /// Check calls to extern symbols for possibly unterminated string parameters
+    /// and update the termination state of buffers written to by the call.
+    fn update_call_stub(&self, state: &State, call: &Term<Jmp>) -> Option<State> {
+        let mut state = state.clone();
+        let extern_symbol = match &call.term {
+            Jmp::Call { target, .. } => self.project.program.term.extern_symbols.get(target),
+            _ => None,
+        };
+        if let Some(extern_symbol) = extern_symbol {
+            self.check_string_parameters(&state, &call.tid, extern_symbol);
+            let termination = if self.terminating_symbols.contains(&extern_symbol.name) {
+                Some(NulTerminationDomain::Terminated)
+            } else if self.copy_symbols.contains(&extern_symbol.name) {
+                Some(self.get_termination_after_copy(&call.tid, extern_symbol))
+            } else {
+                None
+            };
+            if let (Some(termination), Some(destination)) = (
+                termination,
+                self.eval_parameter(&call.tid, extern_symbol, 0),
+            ) {
+                state.set_termination(&destination, termination);
+            }
+        }
+        Some(state)
+    }
+
+    /// Just returns the unmodified state.
+    fn specialize_conditional(
+        &self,
+        state: &State,
+        _condition: &Expression,
+        _block_before_condition: &Term<Blk>,
+        _is_true: bool,
+    ) -> Option<State> {
+        Some(state.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::State as PiState;
+    use crate::{defs, variable};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn termination_after_copy() {
+        let mut project = Project::mock_x64();
+        let mut strncpy = ExternSymbol::mock_x64("strncpy");
+        strncpy.parameters = vec![
+            Arg::mock_register("RDI", 8),
+            Arg::mock_register("RSI", 8),
+            Arg::mock_register("RDX", 8),
+        ];
+        project
+            .program
+            .term
+            .extern_symbols
+            .insert(strncpy.tid.clone(), strncpy.clone());
+        let config = serde_json::from_value(serde_json::json!({
+            "terminating_symbols": [],
+            "copy_symbols": ["strncpy"],
+            "string_parameter_index": {},
+            "format_string_index": {},
+        }))
+        .unwrap();
+        let pi_state_with_copy_length = |copy_length: &str| {
+            let mut pi_state = PiState::new(&variable!("RSP:8"), Tid::new("func"), BTreeSet::new());
+            // The source is the constant string "Hello World" of length 11.
+            for def in defs!["RSI:8 = 0x3002:8", format!("RDX:8 = {copy_length}")] {
+                if let Def::Assign { var, value } = def.term {
+                    pi_state.set_register(&var, pi_state.eval(&value));
+                }
+            }
+            pi_state
+        };
+        let expected = [
+            ("11:8", NulTerminationDomain::Unterminated),
+            ("12:8", NulTerminationDomain::Terminated),
+            ("RCX:8", NulTerminationDomain::Top),
+        ];
+        for (copy_length, termination) in expected {
+            let mut pi_results = PointerInference::mock(&project);
+            pi_results.get_mut_states_at_tids().insert(
+                Tid::new("call_strncpy"),
+                pi_state_with_copy_length(copy_length),
+            );
+            let analysis_results = AnalysisResults::mock_from_project(&project)
+                .with_pointer_inference(Some(&pi_results));
+            let (cwe_sender, _) = crossbeam_channel::unbounded();
+            let context = Context::new(&analysis_results, &config, cwe_sender);
+            assert_eq!(
+                context.get_termination_after_copy(&Tid::new("call_strncpy"), &strncpy),
+                termination
+            );
+        }
+    }
+}
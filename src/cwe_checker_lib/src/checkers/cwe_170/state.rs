@@ -0,0 +1,160 @@
+use crate::abstract_domain::{
+    AbstractDomain, AbstractIdentifier, DomainMap, NulTerminationDomain, TryToBitvec,
+    UnionMergeStrategy,
+};
+use crate::analysis::pointer_inference::Data;
+use crate::prelude::*;
+
+/// The `State` keeps track of the NUL-termination of string buffers.
+///
+/// A buffer is identified by the abstract ID of the memory object containing it
+/// and the offset of the start of the buffer inside the memory object.
+/// Buffers without an entry in the state are assumed to be NUL-terminated.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct State {
+    /// The TID of the current function.
+    pub current_fn_tid: Tid,
+    /// Map from buffer locations to their termination state.
+    buffers: DomainMap<(AbstractIdentifier, i64), NulTerminationDomain, UnionMergeStrategy>,
+}
+
+impl State {
+    /// Create a new state without any tracked buffers.
+    pub fn new(current_fn_tid: Tid) -> State {
+        State {
+            current_fn_tid,
+            buffers: DomainMap::new(),
+        }
+    }
+
+    /// Merge two states.
+    pub fn merge(&self, other: &Self) -> Self {
+        State {
+            current_fn_tid: self.current_fn_tid.clone(),
+            buffers: self.buffers.merge(&other.buffers),
+        }
+    }
+
+    /// Set the termination state of the buffers that `pointer` may point to.
+    ///
+    /// If the pointer has a unique target, the old termination state is overwritten.
+    /// Otherwise it is merged with the old state,
+    /// where untracked buffers are assumed to be NUL-terminated.
+    pub fn set_termination(&mut self, pointer: &Data, termination: NulTerminationDomain) {
+        let is_unique_target = pointer.get_relative_values().len() == 1 && !pointer.contains_top();
+        for buffer in get_buffer_locations(pointer) {
+            let new_termination = if is_unique_target {
+                termination
+            } else {
+                self.buffers
+                    .get(&buffer)
+                    .unwrap_or(&NulTerminationDomain::Terminated)
+                    .merge(&termination)
+            };
+            self.buffers.insert(buffer, new_termination);
+        }
+    }
+
+    /// Mark the buffer containing the byte at `address` as NUL-terminated.
+    ///
+    /// The containing buffer is the tracked buffer in the same memory object
+    /// with the largest start offset not exceeding the offset of the address.
+    /// Nothing is changed if the address does not have a unique target.
+    pub fn handle_stored_nul_byte(&mut self, address: &Data) {
+        if address.get_relative_values().len() != 1 || address.contains_top() {
+            return;
+        }
+        for (id, offset) in get_buffer_locations(address) {
+            let containing_buffer = self
+                .buffers
+                .range((id.clone(), i64::MIN)..=(id, offset))
+                .next_back()
+                .map(|(buffer, _)| buffer.clone());
+            if let Some(buffer) = containing_buffer {
+                self.buffers
+                    .insert(buffer, NulTerminationDomain::Terminated);
+            }
+        }
+    }
+
+    /// Return the merged termination state of all tracked buffers that `pointer` may point to.
+    ///
+    /// Returns `None` if the pointer does not point to the start of any tracked buffer.
+    pub fn get_termination(&self, pointer: &Data) -> Option<NulTerminationDomain> {
+        get_buffer_locations(pointer)
+            .filter_map(|buffer| self.buffers.get(&buffer).copied())
+            .reduce(|accum, termination| accum.merge(&termination))
+    }
+
+    /// Remove the termination state of all buffers in the memory objects that `pointer` may point to.
+    ///
+    /// Used when the buffers may be modified in ways that are not tracked, e.g. by calls to internal functions.
+    pub fn forget_objects(&mut self, pointer: &Data) {
+        for id in pointer.get_relative_values().keys() {
+            self.buffers.retain(|(buffer_id, _), _| buffer_id != id);
+        }
+    }
+}
+
+/// Return the buffer locations that the given pointer may point to.
+/// Targets with inexact offsets are ignored.
+fn get_buffer_locations(pointer: &Data) -> impl Iterator<Item = (AbstractIdentifier, i64)> + '_ {
+    pointer
+        .get_relative_values()
+        .iter()
+        .filter_map(|(id, offset)| Some((id.clone(), offset.try_to_offset().ok()?)))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn pointer(targets: &[(&str, i64)]) -> Data {
+        Data::mock_from_target_map(
+            targets
+                .iter()
+                .map(|(name, offset)| {
+                    (
+                        AbstractIdentifier::mock(*name, "RSP", 8),
+                        Bitvector::from_i64(*offset).into(),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn termination_tracking() {
+        use NulTerminationDomain::*;
+        let mut state = State::new(Tid::new("func"));
+        state.set_termination(&pointer(&[("stack", -64)]), Unterminated);
+        state.set_termination(&pointer(&[("stack", -32)]), Terminated);
+        assert_eq!(
+            state.get_termination(&pointer(&[("stack", -64)])),
+            Some(Unterminated)
+        );
+        assert_eq!(state.get_termination(&pointer(&[("stack", -60)])), None);
+        // A weak update with an untracked buffer yields Top.
+        state.set_termination(&pointer(&[("stack", -64), ("heap", 0)]), Unterminated);
+        assert_eq!(state.get_termination(&pointer(&[("heap", 0)])), Some(Top));
+        // A stored NUL byte terminates the containing buffer.
+        state.handle_stored_nul_byte(&pointer(&[("stack", -33)]));
+        assert_eq!(
+            state.get_termination(&pointer(&[("stack", -64)])),
+            Some(Terminated)
+        );
+        // Merging yields Top for differing values.
+        let mut other_state = state.clone();
+        other_state.set_termination(&pointer(&[("stack", -64)]), Unterminated);
+        let merged = state.merge(&other_state);
+        assert_eq!(
+            merged.get_termination(&pointer(&[("stack", -64)])),
+            Some(Top)
+        );
+        // Forgetting objects removes all their buffers.
+        state.forget_objects(&pointer(&[("stack", 0)]));
+        assert_eq!(state.get_termination(&pointer(&[("stack", -32)])), None);
+        assert_eq!(state.get_termination(&pointer(&[("heap", 0)])), Some(Top));
+    }
+}
@@ -45,6 +45,7 @@ cwe_module!(
     "CWE190",
     "0.1",
     check_cwe,
+    requires: [PointerInference],
     config:
         /// Functions with parameters that are typically susceptible to
         /// overflow/wraparound issues.
@@ -84,6 +84,7 @@ cwe_module!(
     "CWE252",
     "0.1",
     check_cwe,
+    requires: [PointerInference],
     config:
         /// Include more symbols in check.
         strict_mode: bool,
@@ -175,13 +176,14 @@ struct CweAnalysis<'a, 'b: 'a> {
 }
 
 impl<'a, 'b: 'a> CweAnalysis<'a, 'b> {
-    /// Creates a new CWE252 analysis for the given project and configuration.
-    fn new(analysis_results: &'a AnalysisResults<'b>, config: Config) -> Self {
+    /// Creates a new CWE252 analysis of the calls to the given external
+    /// functions.
+    fn new(analysis_results: &'a AnalysisResults<'b>, symbols: &HashSet<String>) -> Self {
         let channel = crossbeam_channel::unbounded();
         let cfg = analysis_results.control_flow_graph;
 
         Self {
-            worklist: Worklist::new(analysis_results, &config.into_symbols()),
+            worklist: Worklist::new(analysis_results, symbols),
             isolated_returns: Arc::new(get_isolated_returns(cfg)),
             project: analysis_results.project,
             pi_result: analysis_results.pointer_inference.unwrap(),
@@ -214,7 +216,7 @@ impl<'a, 'b: 'a> CweAnalysis<'a, 'b> {
     }
 
     /// Runs the CWE252 analysis and returns the generated warnings.
-    fn run(mut self) -> WithLogs<Vec<CweWarning>> {
+    fn run(mut self) -> Vec<CweWarning> {
         while let Some((isolated_returns, ta_comp_ctx)) = self.next_call_ctx() {
             let mut ta_comp = ta_comp_ctx.into_computation();
 
@@ -223,12 +225,7 @@ impl<'a, 'b: 'a> CweAnalysis<'a, 'b> {
             isolated_returns.analyze(&ta_comp);
         }
 
-        self.cwe_collector
-            .try_iter()
-            .collect::<Vec<_>>()
-            // FIXME: It would be nice to preserve all reasons during
-            // deduplication.
-            .deduplicate_addresses()
+        self.cwe_collector.try_iter().collect()
     }
 }
 
@@ -273,5 +270,22 @@ pub fn check_cwe(
     let config: Config =
         serde_json::from_value(cwe_params.clone()).expect("CWE252: invalid configuration");
 
-    CweAnalysis::new(analysis_results, config).run()
+    CweAnalysis::new(analysis_results, &config.into_symbols())
+        .run()
+        // FIXME: It would be nice to preserve all reasons during
+        // deduplication.
+        .deduplicate_addresses()
+}
+
+/// Returns the TIDs of all calls to the given external functions whose return
+/// values are not checked.
+pub(super) fn find_unchecked_calls(
+    analysis_results: &AnalysisResults,
+    symbols: &HashSet<String>,
+) -> HashSet<String> {
+    CweAnalysis::new(analysis_results, symbols)
+        .run()
+        .into_iter()
+        .filter_map(|cwe_warning| cwe_warning.tids.into_iter().next())
+        .collect()
 }
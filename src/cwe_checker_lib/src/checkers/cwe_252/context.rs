@@ -13,7 +13,7 @@ use crate::analysis::graph::{Graph as Cfg, HasCfg};
 use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
 use crate::analysis::pointer_inference::{Data as PiData, PointerInference};
 use crate::analysis::taint::state::State as TaState;
-use crate::analysis::taint::TaintAnalysis;
+use crate::analysis::taint::{TaintAnalysis, TaintSource};
 use crate::analysis::vsa_results::{HasVsaResult, VsaResult};
 use crate::intermediate_representation::{Blk, ExternSymbol, Jmp, Project, Term, Tid};
 use crate::utils::debug::ToJsonCompact;
@@ -81,7 +81,12 @@ impl<'a, 'b: 'a> TaComputationContext<'a, 'b> {
         let symbol = self.call.symbol;
         let vsa_result = self.vsa_result();
         let return_node = self.call.return_node;
-        let node_value = NodeValue::Value(TaState::new_return(symbol, vsa_result, return_node));
+        let node_value = NodeValue::Value(TaState::new_return(
+            symbol,
+            TaintSource::Custom,
+            vsa_result,
+            return_node,
+        ));
 
         let mut computation = fwd_fp_create_computation(self, None);
 
@@ -195,10 +200,6 @@ impl<'a> TaintAnalysis<'a> for TaComputationContext<'a, '_> {
     /// We assume that any check that depends on tainted values is a check of
     /// the return value of the fallible function, and that the program handles
     /// all outcomes correctly.
-    ///
-    /// A jump can depend on a tainted condition in two ways, either it is
-    /// executed because the condition evaluated to `true`, or because it
-    /// evaluated to `false`, both cases must be handled here.
     fn update_jump(
         &self,
         state: &TaState,
@@ -209,26 +210,12 @@ impl<'a> TaintAnalysis<'a> for TaComputationContext<'a, '_> {
         // If this control flow transfer depends on a condition involving
         // a tainted value then we do not propagate any taint information to
         // the destination.
-        match (&jump.term, untaken_conditional) {
-            // Directly depends on a tainted value.
-            (Jmp::CBranch { condition, .. }, _) if state.eval(condition).is_tainted() => None,
-            // Branch is only taken because a condition based on a tainted value
-            // evaluated to false.
-            (
-                _,
-                Some(Term {
-                    tid: _,
-                    term: Jmp::CBranch { condition, .. },
-                }),
-            ) if state.eval(condition).is_tainted() => None,
-            // Does not depend on tainted values.
-            _ => {
-                if state.is_empty() {
-                    self.handle_empty_state_out(&jump.tid)
-                } else {
-                    Some(state.clone())
-                }
-            }
+        if state.jump_depends_on_taint(jump, untaken_conditional) {
+            None
+        } else if state.is_empty() {
+            self.handle_empty_state_out(&jump.tid)
+        } else {
+            Some(state.clone())
         }
     }
 
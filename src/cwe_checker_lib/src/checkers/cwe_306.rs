@@ -0,0 +1,519 @@
+//! This module implements a heuristic check for CWE-306: Missing
+//! Authentication for Critical Function and its variant CWE-862: Missing
+//! Authorization in network daemons.
+//!
+//! Network daemons typically read commands from a client in a loop and
+//! dispatch them to handler code. If a handler performs privileged operations,
+//! like executing shell commands or opening system configuration files, and
+//! can be reached without any authentication of the client, then a remote
+//! attacker can trigger these operations.
+//!
+//! See <https://cwe.mitre.org/data/definitions/306.html> and
+//! <https://cwe.mitre.org/data/definitions/862.html> for detailed
+//! descriptions.
+//!
+//! ## How the check works
+//!
+//! The check looks for calls to the `network_input_symbols` (e.g. `accept` or
+//! `recv`) that are contained in a loop of their function. Starting at such a
+//! call, the control flow graph of the function is searched for privileged
+//! calls that are reachable without calling an authentication function first.
+//!
+//! - Privileged calls are calls to the `privileged_symbols` (e.g. `system`),
+//!   calls to the `file_open_symbols` whose first parameter is a constant path
+//!   starting with one of the `sensitive_path_prefixes` (e.g. `/etc/`), and
+//!   calls to internal functions that (transitively) perform a privileged call
+//!   without authenticating.
+//! - Authentication functions are the `authentication_symbols` (extern or
+//!   internal functions given by name) and all internal functions that
+//!   (transitively) call one of them.
+//!
+//! For each privileged call found, a CWE warning is generated.
+//!
+//! ## False Positives
+//!
+//! - Authentication that is not done through one of the configured
+//!   authentication functions is not recognized.
+//! - Privileged calls that do not depend on the received data at all, e.g.
+//!   logging through a fixed shell command, are flagged.
+//! - The check is not path-sensitive. Paths through the dispatcher that are
+//!   infeasible, e.g. because a flag set by a successful authentication is
+//!   checked, may lead to false positives.
+//!
+//! ## False Negatives
+//!
+//! - Dispatch loops that are split across several functions, e.g. where the
+//!   handler is called through a function pointer table, are only partially
+//!   covered, since indirect calls are not followed.
+//! - A path through a function that calls an authentication function counts as
+//!   authenticated, even if the result of the authentication is ignored.
+use super::prelude::*;
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::pointer_inference::PointerInference;
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+cwe_module!(
+    "CWE306",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// The names of extern functions that receive data from a network
+        /// client (e.g. `accept` or `recv`).
+        network_input_symbols: Vec<String>,
+        /// The names of (extern or internal) functions that authenticate the
+        /// client.
+        authentication_symbols: Vec<String>,
+        /// The names of extern functions that are always considered to be
+        /// privileged (e.g. `system`).
+        privileged_symbols: Vec<String>,
+        /// The names of extern functions that open the file given by their
+        /// first parameter (e.g. `fopen`).
+        file_open_symbols: Vec<String>,
+        /// Calls to the `file_open_symbols` are privileged if the opened path
+        /// is a constant string starting with one of these prefixes.
+        sensitive_path_prefixes: Vec<String>,
+);
+
+/// Return the target of the given jump if it is a direct call.
+fn get_call_target(jmp: &Term<Jmp>) -> Option<&Tid> {
+    match &jmp.term {
+        Jmp::Call { target, .. } => Some(target),
+        _ => None,
+    }
+}
+
+/// Return the blocks that control flow may reach directly after the given
+/// block inside the same function.
+fn get_successors(block: &Term<Blk>) -> impl Iterator<Item = &Tid> {
+    block.term.jmps.iter().filter_map(|jmp| match &jmp.term {
+        Jmp::Branch(target) | Jmp::CBranch { target, .. } => Some(target),
+        Jmp::Call { return_, .. }
+        | Jmp::CallInd { return_, .. }
+        | Jmp::CallOther { return_, .. } => return_.as_ref(),
+        Jmp::BranchInd(_) | Jmp::Return(_) => None,
+    })
+}
+
+/// Return the constant string that the first parameter of the call points to.
+fn get_constant_path_parameter<'a>(
+    project: &'a Project,
+    pointer_inference: &PointerInference,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+) -> Option<&'a str> {
+    let parameter = symbol.parameters.first()?;
+    let value = pointer_inference.eval_parameter_arg_at_call(&call.tid, parameter)?;
+    let address = value.get_if_absolute_value()?.try_to_bitvec().ok()?;
+    project
+        .runtime_memory_image
+        .read_string_until_null_terminator(&address)
+        .ok()
+}
+
+/// Map the TIDs of all privileged calls to extern functions to the name of the
+/// called function.
+fn compute_privileged_calls(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    config: &Config,
+) -> HashMap<Tid, String> {
+    let mut privileged_calls = HashMap::new();
+    for jmp in project.program.jmps() {
+        let Some(symbol) =
+            get_call_target(jmp).and_then(|target| project.program.term.extern_symbols.get(target))
+        else {
+            continue;
+        };
+        let is_privileged = if config.privileged_symbols.contains(&symbol.name) {
+            true
+        } else if config.file_open_symbols.contains(&symbol.name) {
+            get_constant_path_parameter(project, pointer_inference, jmp, symbol).is_some_and(
+                |path| {
+                    config
+                        .sensitive_path_prefixes
+                        .iter()
+                        .any(|prefix| path.starts_with(prefix))
+                },
+            )
+        } else {
+            false
+        };
+        if is_privileged {
+            privileged_calls.insert(jmp.tid.clone(), symbol.name.clone());
+        }
+    }
+    privileged_calls
+}
+
+/// Compute the TIDs of all functions that authenticate the client.
+///
+/// These are the extern and internal functions with one of the given names and
+/// all internal functions that (transitively) call one of them.
+fn compute_authenticating_functions(
+    project: &Project,
+    authentication_symbols: &[String],
+) -> HashSet<Tid> {
+    let program = &project.program.term;
+    let mut authenticating: HashSet<Tid> = program
+        .extern_symbols
+        .values()
+        .filter(|symbol| authentication_symbols.contains(&symbol.name))
+        .map(|symbol| symbol.tid.clone())
+        .chain(
+            program
+                .subs
+                .values()
+                .filter(|sub| authentication_symbols.contains(&sub.term.name))
+                .map(|sub| sub.tid.clone()),
+        )
+        .collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for sub in program.subs.values() {
+            if !authenticating.contains(&sub.tid)
+                && sub
+                    .term
+                    .blocks
+                    .iter()
+                    .flat_map(|block| block.term.jmps.iter())
+                    .filter_map(get_call_target)
+                    .any(|target| authenticating.contains(target))
+            {
+                authenticating.insert(sub.tid.clone());
+                changed = true;
+            }
+        }
+    }
+    authenticating
+}
+
+/// Compute the internal functions that (transitively) perform a privileged
+/// call without calling an authentication function.
+///
+/// Each function is mapped to one of the privileged calls that it reaches.
+fn compute_privileged_functions(
+    project: &Project,
+    privileged_calls: &HashMap<Tid, String>,
+    authenticating_functions: &HashSet<Tid>,
+) -> HashMap<Tid, Tid> {
+    let mut privileged_functions: HashMap<Tid, Tid> = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for sub in project.program.term.subs.values() {
+            if authenticating_functions.contains(&sub.tid)
+                || privileged_functions.contains_key(&sub.tid)
+            {
+                continue;
+            }
+            let privileged_call = sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.jmps.iter())
+                .find_map(|jmp| {
+                    if privileged_calls.contains_key(&jmp.tid) {
+                        Some(jmp.tid.clone())
+                    } else {
+                        get_call_target(jmp)
+                            .and_then(|target| privileged_functions.get(target))
+                            .cloned()
+                    }
+                });
+            if let Some(privileged_call) = privileged_call {
+                privileged_functions.insert(sub.tid.clone(), privileged_call);
+                changed = true;
+            }
+        }
+    }
+    privileged_functions
+}
+
+/// Return `true` if the given block is contained in a loop of the function.
+fn is_in_loop(sub: &Term<Sub>, block: &Term<Blk>) -> bool {
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    let mut visited = HashSet::new();
+    let mut worklist: Vec<&Tid> = get_successors(block).collect();
+    while let Some(block_tid) = worklist.pop() {
+        if *block_tid == block.tid {
+            return true;
+        }
+        if visited.insert(block_tid) {
+            if let Some(successor) = blocks.get(block_tid) {
+                worklist.extend(get_successors(successor));
+            }
+        }
+    }
+    false
+}
+
+/// Search the function for privileged calls that are reachable from the given
+/// block without calling an authentication function.
+///
+/// Returns pairs of the call in the function and the privileged call that it
+/// (transitively) leads to.
+fn find_unauthenticated_privileged_calls<'a>(
+    sub: &'a Term<Sub>,
+    start_block: &'a Term<Blk>,
+    privileged_calls: &HashMap<Tid, String>,
+    authenticating_functions: &HashSet<Tid>,
+    privileged_functions: &HashMap<Tid, Tid>,
+) -> Vec<(&'a Tid, Tid)> {
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    let mut findings = Vec::new();
+    let mut visited = HashSet::new();
+    let mut worklist: Vec<&Tid> = get_successors(start_block).collect();
+    while let Some(block_tid) = worklist.pop() {
+        let Some(block) = blocks.get(block_tid) else {
+            continue;
+        };
+        if !visited.insert(block_tid) {
+            continue;
+        }
+        let mut is_authenticated = false;
+        for jmp in block.term.jmps.iter() {
+            if privileged_calls.contains_key(&jmp.tid) {
+                findings.push((&jmp.tid, jmp.tid.clone()));
+            } else if let Some(target) = get_call_target(jmp) {
+                if authenticating_functions.contains(target) {
+                    is_authenticated = true;
+                } else if let Some(privileged_call) = privileged_functions.get(target) {
+                    findings.push((&jmp.tid, privileged_call.clone()));
+                }
+            }
+        }
+        if !is_authenticated {
+            worklist.extend(get_successors(block));
+        }
+    }
+    findings
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    input_call: &Tid,
+    call: &Tid,
+    privileged_call: &Tid,
+    privileged_symbol: &str,
+) -> CweWarning {
+    let mut tids = vec![format!("{call}"), format!("{input_call}")];
+    let mut addresses = vec![call.address().to_string(), input_call.address().to_string()];
+    if call != privileged_call {
+        tids.push(format!("{privileged_call}"));
+        addresses.push(privileged_call.address().to_string());
+    }
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Missing Authentication for Critical Function) Call to {} at {} is reachable from network input at {} in {} without authentication",
+            privileged_symbol,
+            privileged_call.address(),
+            input_call.address(),
+            sub.term.name
+        ),
+    )
+    .tids(tids)
+    .addresses(addresses)
+    .symbols(vec![sub.term.name.clone(), privileged_symbol.to_string()])
+}
+
+/// Execute the CWE check.
+///
+/// For each call to a network input function inside a loop we search for
+/// privileged calls that are reachable from it without authentication.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference.unwrap();
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    let privileged_calls = compute_privileged_calls(project, pointer_inference, &config);
+    let authenticating_functions =
+        compute_authenticating_functions(project, &config.authentication_symbols);
+    let privileged_functions =
+        compute_privileged_functions(project, &privileged_calls, &authenticating_functions);
+    let input_symbols: HashSet<&Tid> = project
+        .program
+        .term
+        .extern_symbols
+        .values()
+        .filter(|symbol| config.network_input_symbols.contains(&symbol.name))
+        .map(|symbol| &symbol.tid)
+        .collect();
+
+    for sub in project.program.functions() {
+        for block in sub.term.blocks.iter() {
+            let Some(input_call) = block.term.jmps.iter().find(|jmp| {
+                get_call_target(jmp).is_some_and(|target| input_symbols.contains(target))
+            }) else {
+                continue;
+            };
+            if !is_in_loop(sub, block) {
+                continue;
+            }
+            for (call, privileged_call) in find_unauthenticated_privileged_calls(
+                sub,
+                block,
+                &privileged_calls,
+                &authenticating_functions,
+                &privileged_functions,
+            ) {
+                cwe_warnings.push(generate_cwe_warning(
+                    sub,
+                    &input_call.tid,
+                    call,
+                    &privileged_call,
+                    &privileged_calls[&privileged_call],
+                ));
+            }
+        }
+    }
+
+    cwe_warnings.deduplicate_first_address()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variable;
+
+    /// Build a dispatcher function of the form
+    ///
+    /// ```text
+    /// loop:     recv(..)
+    ///           if (..) goto command
+    ///           authenticate(..)
+    ///           handler(..)
+    ///           goto loop
+    /// command:  system(..)
+    ///           goto loop
+    /// ```
+    fn mock_project() -> Project {
+        let mut project = Project::mock_x64();
+        for name in ["recv", "system", "authenticate"] {
+            let symbol = ExternSymbol::mock_x64(name);
+            project
+                .program
+                .term
+                .extern_symbols
+                .insert(symbol.tid.clone(), symbol);
+        }
+        let mut recv_blk = Blk::mock_with_tid("recv_blk");
+        recv_blk
+            .term
+            .jmps
+            .push(Jmp::call("recv_call", "recv", Some("dispatch_blk")));
+        let mut dispatch_blk = Blk::mock_with_tid("dispatch_blk");
+        dispatch_blk.term.jmps = vec![
+            Term {
+                tid: Tid::new("cbranch"),
+                term: Jmp::CBranch {
+                    target: Tid::new("command_blk"),
+                    condition: Expression::Var(variable!("ZF:1")),
+                },
+            },
+            Jmp::branch("to_auth", "auth_blk"),
+        ];
+        let mut command_blk = Blk::mock_with_tid("command_blk");
+        command_blk
+            .term
+            .jmps
+            .push(Jmp::call("system_call", "system", Some("recv_blk")));
+        let mut auth_blk = Blk::mock_with_tid("auth_blk");
+        auth_blk
+            .term
+            .jmps
+            .push(Jmp::call("auth_call", "authenticate", Some("handler_blk")));
+        let mut handler_blk = Blk::mock_with_tid("handler_blk");
+        handler_blk
+            .term
+            .jmps
+            .push(Jmp::call("handler_call", "handler", Some("recv_blk")));
+        let mut dispatcher = Sub::mock("dispatcher");
+        dispatcher.term.blocks = vec![recv_blk, dispatch_blk, command_blk, auth_blk, handler_blk];
+
+        let mut handler_entry = Blk::mock_with_tid("handler_entry");
+        handler_entry
+            .term
+            .jmps
+            .push(Jmp::call("handler_system_call", "system", None));
+        let mut handler = Sub::mock("handler");
+        handler.term.blocks = vec![handler_entry];
+
+        for sub in [dispatcher, handler] {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    #[test]
+    fn unauthenticated_privileged_calls() {
+        let project = mock_project();
+        let privileged_calls = HashMap::from([
+            (Tid::new("system_call"), "system".to_string()),
+            (Tid::new("handler_system_call"), "system".to_string()),
+        ]);
+        let authenticating_functions =
+            compute_authenticating_functions(&project, &["authenticate".to_string()]);
+        // The dispatcher authenticates on some of its paths.
+        assert_eq!(
+            authenticating_functions,
+            HashSet::from([Tid::new("authenticate"), Tid::new("dispatcher")])
+        );
+        let privileged_functions =
+            compute_privileged_functions(&project, &privileged_calls, &authenticating_functions);
+        assert_eq!(
+            privileged_functions,
+            HashMap::from([(Tid::new("handler"), Tid::new("handler_system_call"))])
+        );
+
+        let dispatcher = &project.program.term.subs[&Tid::new("dispatcher")];
+        let recv_blk = &dispatcher.term.blocks[0];
+        assert!(is_in_loop(dispatcher, recv_blk));
+        let findings = find_unauthenticated_privileged_calls(
+            dispatcher,
+            recv_blk,
+            &privileged_calls,
+            &authenticating_functions,
+            &privileged_functions,
+        );
+        // The handler is only called after authentication.
+        assert_eq!(
+            findings,
+            vec![(&Tid::new("system_call"), Tid::new("system_call"))]
+        );
+
+        // Without authentication the handler is flagged as well.
+        let findings = find_unauthenticated_privileged_calls(
+            dispatcher,
+            recv_blk,
+            &privileged_calls,
+            &HashSet::new(),
+            &privileged_functions,
+        );
+        assert_eq!(findings.len(), 2);
+        assert!(findings.contains(&(&Tid::new("handler_call"), Tid::new("handler_system_call"))));
+    }
+}
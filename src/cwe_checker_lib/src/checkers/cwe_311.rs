@@ -0,0 +1,235 @@
+//! CWE-311: Missing Encryption of Sensitive Data.
+//!
+//! Sensitive information like passwords or key material should not be stored
+//! in cleartext. If a program writes such data unencrypted to a file, anyone
+//! with read access to the file (or to a backup of it) can read it.
+//!
+//! See <https://cwe.mitre.org/data/definitions/311.html> for a detailed
+//! description.
+//!
+//! # Algorithm
+//!
+//! We reuse the taint analysis of the [CWE-319 check](super::cwe_319). The
+//! sources are
+//!
+//! - return values of calls to external functions that are known to return
+//!   sensitive data, e.g., `getpass`,
+//! - buffers that are filled with key material by external functions, e.g.,
+//!   the first parameter of `RAND_bytes`, and
+//! - return values of calls to lookup functions like `getenv`, where the
+//!   (constant) key string contains a keyword that indicates sensitive data.
+//!
+//! Taint is propagated into buffers by calls to string and memory copying
+//! functions. The sinks are functions that write to `FILE` streams, e.g.,
+//! `fwrite`. If the binary opens files by file descriptor, then `write`-like
+//! functions are also treated as sinks. A warning containing the flow trace is
+//! generated for every sink call with tainted arguments or arguments that
+//! point to tainted memory.
+//!
+//! Taint propagation is stopped along paths as soon as tainted data is passed
+//! to an encryption or hashing function, e.g., `EVP_EncryptUpdate`.
+//!
+//! # Limitations
+//!
+//! ## False Positives
+//!
+//! - We cannot distinguish between file descriptors of regular files and of
+//!   sockets or pipes. Thus, sending sensitive data over a socket may be
+//!   reported if the binary also opens files.
+//! - Files that are only accessible by the owner or that reside on an
+//!   encrypted file system are not distinguished from other files.
+//! - The sensitivity of data is guessed from keywords.
+//!
+//! ## False Negatives
+//!
+//! - Sensitive data that is passed to a function of the binary is not followed
+//!   into it, so writes inside helpers like a custom `save_config(password)`
+//!   routine that calls `fwrite` are missed.
+//! - Data that is written through variadic functions like `fprintf` is not
+//!   checked, since only the declared parameters of sinks are inspected.
+//! - Sensitive data that is encrypted by custom routines is treated as
+//!   cleartext, while data that is only partially protected by a call to an
+//!   encryption function is treated as encrypted.
+//!
+//! # Configuration
+//!
+//! The lists of source, sink, propagation, and encryption functions as well as
+//! the keywords that mark sensitive lookup keys can be configured via the
+//! `config.json`. Sanitizers are declared as for the CWE-319 check.
+use super::prelude::*;
+
+use super::cwe_319::context::Symbols;
+use super::cwe_319::{find_tainted_sink_calls, get_sources, SensitiveSource, SinkCall};
+use crate::analysis::taint::sanitizers::Sanitizer;
+use crate::prelude::*;
+use crate::utils::symbol_utils;
+
+use std::collections::{BTreeMap, HashSet};
+
+cwe_module!(
+    "CWE311",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// External symbols whose return values are sensitive.
+        source_symbols: HashSet<String>,
+        /// External symbols that write sensitive data, e.g. key material, into
+        /// the buffer given by their first parameter.
+        buffer_source_symbols: HashSet<String>,
+        /// External lookup functions whose return values are sensitive if
+        /// their first argument is a constant string containing a keyword.
+        lookup_symbols: HashSet<String>,
+        /// Keywords that mark a lookup key as referencing sensitive data.
+        keywords: Vec<String>,
+        /// External symbols that write data to files.
+        sink_symbols: HashSet<String>,
+        /// External symbols that are only treated as sinks if the binary opens
+        /// files by file descriptor.
+        file_write_symbols: HashSet<String>,
+        /// External symbols that open files and return a file descriptor.
+        file_open_symbols: HashSet<String>,
+        /// External symbols that copy data from their other parameters into
+        /// the buffer given by their first parameter.
+        propagation_symbols: HashSet<String>,
+        /// External symbols of encryption or hashing libraries.
+        encryption_symbols: HashSet<String>,
+        /// Functions that validate the data pointed to by one of their
+        /// parameters, e.g. by checking that it is not sensitive.
+        sanitizers: BTreeMap<String, Sanitizer>,
+);
+
+/// Generates the CWE warning for a tainted sink call.
+fn generate_cwe_warning(source: &SensitiveSource, sink: &SinkCall, trace: &[Tid]) -> CweWarning {
+    let source_name = source.get_symbol_name();
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Missing Encryption of Sensitive Data) Sensitive data obtained by {} ({}) is written unencrypted to a file by the call to {} at {}.",
+            source.jmp.tid.address(),
+            source_name,
+            sink.symbol_name,
+            sink.tid.address()
+        ),
+    )
+    .addresses(vec![
+        sink.tid.address().to_string(),
+        source.jmp.tid.address().to_string(),
+    ])
+    .tids(vec![format!("{}", sink.tid), format!("{}", source.jmp.tid)])
+    .symbols(vec![source_name, sink.symbol_name.clone()])
+    .other(vec![trace
+        .iter()
+        .map(|tid| format!("trace={}", tid.address()))
+        .collect()])
+}
+
+/// CWE-311: Missing Encryption of Sensitive Data.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let config: Config =
+        serde_json::from_value(cwe_params.clone()).expect("CWE311: invalid configuration");
+    let project = analysis_results.project;
+
+    let mut sinks = config.sink_symbols.clone();
+    if !symbol_utils::get_symbol_map_fast(project, &config.file_open_symbols).is_empty() {
+        sinks.extend(config.file_write_symbols.iter().cloned());
+    }
+    let symbols = Symbols {
+        sinks,
+        propagation: config.propagation_symbols.clone(),
+        tls: config.encryption_symbols.clone(),
+        sanitizers: config.sanitizers.clone(),
+        library_summaries: analysis_results.library_summaries.cloned(),
+    };
+
+    let sources = get_sources(
+        analysis_results,
+        &config.source_symbols,
+        &config.buffer_source_symbols,
+        &config.lookup_symbols,
+        &config.keywords,
+    );
+    let mut cwe_warnings: Vec<CweWarning> =
+        find_tainted_sink_calls(analysis_results, sources, &symbols)
+            .iter()
+            .map(|(source, sink, trace)| generate_cwe_warning(source, sink, trace))
+            .collect();
+    cwe_warnings.sort();
+
+    cwe_warnings.deduplicate_addresses()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::PointerInference;
+    use crate::checkers::test_utils::{self, CallSequence};
+    use crate::intermediate_representation::*;
+
+    /// Returns a project with a function that calls the given external
+    /// functions in order and passes the return value of the first call to all
+    /// following calls.
+    fn mock_project(symbols: &[&str]) -> Project {
+        CallSequence::new(symbols).pass_return_value("RDI").build()
+    }
+
+    fn mock_config() -> serde_json::Value {
+        serde_json::json!({
+            "source_symbols": ["getpass"],
+            "buffer_source_symbols": ["RAND_bytes"],
+            "lookup_symbols": ["getenv"],
+            "keywords": ["passw"],
+            "sink_symbols": ["fwrite"],
+            "file_write_symbols": ["write"],
+            "file_open_symbols": ["open"],
+            "propagation_symbols": ["strcpy"],
+            "encryption_symbols": ["EVP_EncryptUpdate"],
+            "sanitizers": {}
+        })
+    }
+
+    fn run_check(project: &Project) -> Vec<CweWarning> {
+        let pi_result = PointerInference::mock(project);
+        test_utils::run_check(project, &pi_result, check_cwe, &mock_config())
+    }
+
+    #[test]
+    fn sensitive_data_written_to_file() {
+        let warnings = run_check(&mock_project(&["getpass", "other_function", "fwrite"]));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec!["call_fwrite_2".to_string(), "call_getpass_0".to_string()]
+        );
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["getpass".to_string(), "fwrite".to_string()]
+        );
+
+        // Data that is not sensitive is not reported.
+        assert!(run_check(&mock_project(&["getline", "other_function", "fwrite"])).is_empty());
+    }
+
+    #[test]
+    fn encrypted_data_is_not_reported() {
+        let project = mock_project(&["getpass", "EVP_EncryptUpdate", "fwrite"]);
+        assert!(run_check(&project).is_empty());
+    }
+
+    #[test]
+    fn write_is_only_a_sink_if_files_are_opened() {
+        assert!(run_check(&mock_project(&["getpass", "other_function", "write"])).is_empty());
+
+        let warnings = run_check(&mock_project(&["getpass", "open", "write"]));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["getpass".to_string(), "write".to_string()]
+        );
+    }
+}
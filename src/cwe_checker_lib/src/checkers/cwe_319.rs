@@ -0,0 +1,548 @@
+//! CWE-319: Cleartext Transmission of Sensitive Information.
+//!
+//! Sensitive information like passwords, keys or access tokens should only be
+//! transmitted over encrypted channels. If a program sends such data over a
+//! plain network socket, any attacker that is able to observe the network
+//! traffic can read it.
+//!
+//! # Examples
+//!
+//! See the following CVEs for examples:
+//!
+//! - CVE-2019-6569
+//! - CVE-2020-10607
+//! - CVE-2022-29519
+//!
+//! Also see [CWE319 at Mitre].
+//!
+//! [CWE319 at Mitre]: https://cwe.mitre.org/data/definitions/319.html
+//!
+//! # Algorithm
+//!
+//! We perform a taint analysis where the sources are
+//!
+//! - return values of calls to external functions that are known to return
+//!   sensitive data, e.g., `getpass`, and
+//! - return values of calls to lookup functions like `getenv`, where the
+//!   (constant) key string contains a keyword that indicates sensitive data,
+//!   e.g., `getenv("DB_PASSWORD")`.
+//!
+//! Taint is propagated into buffers by calls to string and memory copying
+//! functions. The sinks are the plaintext network functions, e.g., `send`. If
+//! the binary creates sockets, then `write`-like functions are also treated as
+//! sinks. A warning is generated for every sink call with tainted arguments or
+//! arguments that point to tainted memory. The warning contains the flow trace,
+//! i.e., the addresses of the basic blocks on a tainted path from the source to
+//! the sink.
+//!
+//! Taint propagation is stopped along paths as soon as tainted data is passed
+//! to a function of a TLS or encryption library, e.g., `SSL_write`.
+//!
+//! # Limitations
+//!
+//! ## False Positives
+//!
+//! - We cannot distinguish between file descriptors of sockets and of regular
+//!   files. Thus, writing sensitive data to a file may be reported if the
+//!   binary also creates sockets.
+//! - The sensitivity of data is guessed from keywords. Key strings containing
+//!   a keyword do not necessarily reference sensitive data.
+//! - Sockets that are connected to a local peer (e.g. Unix domain sockets) are
+//!   not distinguished from network sockets.
+//!
+//! ## False Negatives
+//!
+//! - Sensitive data that is passed to a function of the binary is not followed
+//!   into it, so sends inside wrappers like a custom `send_all` are missed.
+//!   Taint only flows back out of called functions through return registers
+//!   and memory objects that are known to the caller.
+//! - Sensitive data that is obtained through functions not in the list of
+//!   sources is not detected.
+//! - Taint can be lost due to Pointer Inference inexactness or unmodeled
+//!   library functions.
+//! - Sensitive data that is encrypted by custom routines is treated as
+//!   cleartext, while data that is only partially protected by a TLS function
+//!   call is treated as encrypted.
+//!
+//! # Configuration
+//!
+//! The lists of source, sink, propagation, and TLS functions as well as the
+//! keywords that mark sensitive lookup keys can be configured via the
+//! `config.json`. Functions of the analyzed program that sanitize the data
+//! pointed to by one of their parameters can be declared in the `sanitizers`
+//! section. The taint is removed from that data after calls to them.
+//!
+//! If the shared libraries imported by the binary were analyzed as well,
+//! taint is propagated through calls to library functions that return one of their parameters.
+use super::prelude::*;
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::graph::{Edge, Graph, Node, NodeIndex};
+use crate::analysis::pointer_inference::PointerInference;
+use crate::analysis::taint::sanitizers::Sanitizer;
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::{ExternSymbol, Jmp, Project, Term};
+use crate::pipeline::AnalysisResults;
+use crate::prelude::*;
+use crate::utils::symbol_utils;
+
+use petgraph::visit::EdgeRef;
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+pub(super) mod context;
+
+use context::*;
+
+cwe_module!(
+    "CWE319",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// External symbols whose return values are sensitive.
+        source_symbols: HashSet<String>,
+        /// External lookup functions whose return values are sensitive if
+        /// their first argument is a constant string containing a keyword.
+        lookup_symbols: HashSet<String>,
+        /// Keywords that mark a lookup key as referencing sensitive data.
+        keywords: Vec<String>,
+        /// External symbols that transmit data over the network in cleartext.
+        sink_symbols: HashSet<String>,
+        /// External symbols that are only treated as sinks if the binary
+        /// creates sockets.
+        socket_write_symbols: HashSet<String>,
+        /// External symbols that create sockets.
+        socket_symbols: HashSet<String>,
+        /// External symbols that copy data from their other parameters into
+        /// the buffer given by their first parameter.
+        propagation_symbols: HashSet<String>,
+        /// External symbols of TLS or encryption libraries.
+        tls_symbols: HashSet<String>,
+        /// Functions that validate the data pointed to by one of their
+        /// parameters, e.g. by checking that it is not sensitive.
+        sanitizers: BTreeMap<String, Sanitizer>,
+);
+
+/// Call to an external function that returns sensitive data.
+#[derive(Clone, Copy)]
+pub(super) struct SensitiveSource<'a> {
+    /// Information about the function that was called.
+    pub(super) symbol: &'a ExternSymbol,
+    /// CFG node where the call will return to.
+    pub(super) return_node: NodeIndex,
+    /// IR instruction of the call.
+    pub(super) jmp: &'a Term<Jmp>,
+    /// True if the function writes the sensitive data into the buffer given by
+    /// its first parameter instead of returning it.
+    pub(super) taints_first_parameter: bool,
+}
+
+impl SensitiveSource<'_> {
+    /// Returns a copy of the name of the external function that was called.
+    pub fn get_symbol_name(&self) -> String {
+        self.symbol.name.clone()
+    }
+}
+
+/// A call to a sink function that was reached by tainted data.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(super) struct SinkCall {
+    /// TID of the call instruction.
+    pub(super) tid: Tid,
+    /// Name of the called sink function.
+    pub(super) symbol_name: String,
+}
+
+/// Returns true if the given lookup key contains one of the keywords.
+///
+/// The comparison is case-insensitive.
+pub(super) fn is_sensitive_key(key: &str, keywords: &[String]) -> bool {
+    let key = key.to_lowercase();
+
+    keywords
+        .iter()
+        .any(|keyword| key.contains(&keyword.to_lowercase()))
+}
+
+/// Returns the constant string that is passed as the first argument to the
+/// given call, if it can be determined.
+pub(super) fn get_constant_key_string(
+    pi_result: &PointerInference,
+    project: &Project,
+    symbol: &ExternSymbol,
+    call_tid: &Tid,
+) -> Option<String> {
+    let param = symbol.parameters.first()?;
+    let address = pi_result
+        .eval_parameter_arg_at_call(call_tid, param)?
+        .get_if_absolute_value()?
+        .try_to_bitvec()
+        .ok()?;
+
+    project
+        .runtime_memory_image
+        .read_string_until_null_terminator(&address)
+        .ok()
+        .map(|key| key.to_string())
+}
+
+/// Searches the program for calls to source functions.
+///
+/// The `source_symbols` return sensitive data, while the `buffer_source_symbols`
+/// write it into the buffer given by their first parameter. Calls to
+/// `lookup_symbols` are only included if their key argument is a sensitive
+/// string, i.e., contains one of the `keywords`.
+pub(super) fn get_sources<'a>(
+    analysis_results: &'a AnalysisResults,
+    source_symbols: &HashSet<String>,
+    buffer_source_symbols: &HashSet<String>,
+    lookup_symbols: &HashSet<String>,
+    keywords: &[String],
+) -> Vec<SensitiveSource<'a>> {
+    let project = analysis_results.project;
+    let pi_result = analysis_results
+        .pointer_inference
+        .expect("BUG: No pointer inference results.");
+    let symbols: HashSet<String> = source_symbols
+        .iter()
+        .chain(buffer_source_symbols)
+        .chain(lookup_symbols)
+        .cloned()
+        .collect();
+    let symbol_map = symbol_utils::get_symbol_map_fast(project, &symbols);
+
+    pi_result
+        .get_graph()
+        .edge_references()
+        .filter_map(|edge| {
+            let Edge::ExternCallStub(jmp) = edge.weight() else {
+                return None;
+            };
+            let Jmp::Call { target, .. } = &jmp.term else {
+                return None;
+            };
+            let symbol = *symbol_map.get(target)?;
+            let taints_first_parameter = buffer_source_symbols.contains(&symbol.name);
+            if !taints_first_parameter && !source_symbols.contains(&symbol.name) {
+                let key = get_constant_key_string(pi_result, project, symbol, &jmp.tid)?;
+                if !is_sensitive_key(&key, keywords) {
+                    return None;
+                }
+            }
+
+            Some(SensitiveSource {
+                symbol,
+                return_node: edge.target(),
+                jmp,
+                taints_first_parameter,
+            })
+        })
+        .collect()
+}
+
+/// Computes the flow trace from the source to the given sink call.
+///
+/// The trace is a shortest path in the control flow graph from the return node
+/// of the source call to the node of the sink call that only visits nodes
+/// where the taint analysis computed a state. Returns the TIDs of the basic
+/// blocks on the path.
+pub(super) fn get_flow_trace(
+    computation: &FpComputation,
+    source: NodeIndex,
+    sink_tid: &Tid,
+) -> Vec<Tid> {
+    let graph: &Graph = computation.get_graph();
+    let sink_nodes: HashSet<NodeIndex> = graph
+        .edge_references()
+        .filter_map(|edge| match edge.weight() {
+            Edge::ExternCallStub(jmp) if jmp.tid == *sink_tid => Some(edge.source()),
+            _ => None,
+        })
+        .collect();
+
+    let mut predecessors: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+    let mut visited = HashSet::from([source]);
+    let mut worklist = VecDeque::from([source]);
+    let mut sink_node = None;
+    while let Some(node) = worklist.pop_front() {
+        if sink_nodes.contains(&node) {
+            sink_node = Some(node);
+            break;
+        }
+        for successor in graph.neighbors(node) {
+            if computation.get_node_value(successor).is_some() && visited.insert(successor) {
+                predecessors.insert(successor, node);
+                worklist.push_back(successor);
+            }
+        }
+    }
+
+    let mut trace = Vec::new();
+    let mut current = sink_node;
+    while let Some(node) = current {
+        if let Node::BlkStart(blk, _) = graph[node] {
+            trace.push(blk.tid.clone());
+        }
+        current = predecessors.get(&node).copied();
+    }
+    trace.reverse();
+
+    trace
+}
+
+/// Runs the taint analysis for each source and returns the sink calls that
+/// are reached by its taint together with the flow trace from the source.
+pub(super) fn find_tainted_sink_calls<'a>(
+    analysis_results: &'a AnalysisResults,
+    sources: Vec<SensitiveSource<'a>>,
+    symbols: &Symbols,
+) -> Vec<(SensitiveSource<'a>, SinkCall, Vec<Tid>)> {
+    let project = analysis_results.project;
+    let pi_result = analysis_results
+        .pointer_inference
+        .expect("BUG: No pointer inference results.");
+
+    let mut tainted_sink_calls = Vec::new();
+    for source in sources {
+        let (sink_sender, sink_receiver) = crossbeam_channel::unbounded();
+        let mut computation =
+            TaComputationContext::new(source, project, pi_result, symbols, sink_sender)
+                .into_computation();
+        computation.compute_with_max_steps(100);
+
+        let sink_calls: HashSet<SinkCall> = sink_receiver.try_iter().collect();
+        for sink in sink_calls {
+            let trace = get_flow_trace(&computation, source.return_node, &sink.tid);
+            tainted_sink_calls.push((source, sink, trace));
+        }
+    }
+
+    tainted_sink_calls
+}
+
+/// Generates the CWE warning for a tainted sink call.
+fn generate_cwe_warning(source: &SensitiveSource, sink: &SinkCall, trace: &[Tid]) -> CweWarning {
+    let source_name = source.get_symbol_name();
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Cleartext Transmission of Sensitive Information) Sensitive data returned by {} ({}) is sent unencrypted by the call to {} at {}.",
+            source.jmp.tid.address(),
+            source_name,
+            sink.symbol_name,
+            sink.tid.address()
+        ),
+    )
+    .addresses(vec![
+        sink.tid.address().to_string(),
+        source.jmp.tid.address().to_string(),
+    ])
+    .tids(vec![format!("{}", sink.tid), format!("{}", source.jmp.tid)])
+    .symbols(vec![source_name, sink.symbol_name.clone()])
+    .other(vec![trace
+        .iter()
+        .map(|tid| format!("trace={}", tid.address()))
+        .collect()])
+}
+
+/// CWE-319: Cleartext Transmission of Sensitive Information.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let config: Config =
+        serde_json::from_value(cwe_params.clone()).expect("CWE319: invalid configuration");
+    let project = analysis_results.project;
+
+    let mut sinks = config.sink_symbols.clone();
+    if !symbol_utils::get_symbol_map_fast(project, &config.socket_symbols).is_empty() {
+        sinks.extend(config.socket_write_symbols.iter().cloned());
+    }
+    let symbols = Symbols {
+        sinks,
+        propagation: config.propagation_symbols.clone(),
+        tls: config.tls_symbols.clone(),
+        sanitizers: config.sanitizers.clone(),
+        library_summaries: analysis_results.library_summaries.cloned(),
+    };
+
+    let sources = get_sources(
+        analysis_results,
+        &config.source_symbols,
+        &HashSet::new(),
+        &config.lookup_symbols,
+        &config.keywords,
+    );
+    let mut cwe_warnings: Vec<CweWarning> =
+        find_tainted_sink_calls(analysis_results, sources, &symbols)
+            .iter()
+            .map(|(source, sink, trace)| generate_cwe_warning(source, sink, trace))
+            .collect();
+    cwe_warnings.sort();
+
+    cwe_warnings.deduplicate_addresses()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::State as PiState;
+    use crate::checkers::test_utils::{self, CallSequence};
+    use crate::intermediate_representation::*;
+    use crate::{bitvec, variable};
+
+    use std::collections::BTreeSet;
+
+    /// Runs the check with the given Pointer Inference states at the calls.
+    fn run_check(project: &Project, states: Vec<(&str, PiState)>) -> Vec<CweWarning> {
+        let mut pi_result = PointerInference::mock(project);
+        for (call, state) in states {
+            pi_result
+                .get_mut_states_at_tids()
+                .insert(Tid::new(call), state);
+        }
+        let config = serde_json::json!({
+            "source_symbols": ["getpass"],
+            "lookup_symbols": ["getenv"],
+            "keywords": ["world"],
+            "sink_symbols": ["send"],
+            "socket_write_symbols": ["write"],
+            "socket_symbols": ["socket"],
+            "propagation_symbols": ["strcpy"],
+            "tls_symbols": ["SSL_write"],
+            "sanitizers": {
+                "validate": { "parameter_index": 0 }
+            }
+        });
+
+        test_utils::run_check(project, &pi_result, check_cwe, &config)
+    }
+
+    /// Returns a Pointer Inference state where `RDI` contains the given value.
+    fn state_with_first_argument(value: Option<u64>) -> PiState {
+        let mut state = PiState::new(&variable!("RSP:8"), Tid::new("func"), BTreeSet::new());
+        let value = match value {
+            Some(value) => bitvec!(format!("{value}:8")).into(),
+            // A pointer to the stack frame of the function.
+            None => state.get_register(&variable!("RSP:8")),
+        };
+        state.set_register(&variable!("RDI:8"), value);
+        state
+    }
+
+    #[test]
+    fn sensitive_keys() {
+        let keywords = vec!["passw".to_string(), "token".to_string()];
+
+        assert!(is_sensitive_key("DB_PASSWORD", &keywords));
+        assert!(is_sensitive_key("api_token", &keywords));
+        assert!(!is_sensitive_key("HOME", &keywords));
+    }
+
+    #[test]
+    fn sensitive_data_sent_in_cleartext() {
+        let project = CallSequence::new(&["getpass", "other_function", "send"])
+            .pass_return_value("RDI")
+            .build();
+        let warnings = run_check(&project, Vec::new());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec!["call_send_2".to_string(), "call_getpass_0".to_string()]
+        );
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["getpass".to_string(), "send".to_string()]
+        );
+        assert!(!warnings[0].get_trace().is_empty());
+
+        // Data that is not sensitive is not reported.
+        let project = CallSequence::new(&["getline", "other_function", "send"])
+            .pass_return_value("RDI")
+            .build();
+        assert!(run_check(&project, Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn lookups_with_sensitive_keys() {
+        let project = CallSequence::new(&["getenv", "send"])
+            .pass_return_value("RDI")
+            .build();
+        // The key "Hello World" contains a keyword.
+        let states = vec![("call_getenv_0", state_with_first_argument(Some(0x3002)))];
+        let warnings = run_check(&project, states);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["getenv".to_string(), "send".to_string()]
+        );
+        // The key "/dev/sd%c%d" does not contain a keyword.
+        let states = vec![("call_getenv_0", state_with_first_argument(Some(0x5000)))];
+        assert!(run_check(&project, states).is_empty());
+    }
+
+    #[test]
+    fn tls_functions_stop_the_taint() {
+        let project = CallSequence::new(&["getpass", "SSL_write", "send"])
+            .pass_return_value("RDI")
+            .build();
+        assert!(run_check(&project, Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn writes_are_only_sinks_if_sockets_are_created() {
+        let project = CallSequence::new(&["getpass", "other_function", "write"])
+            .pass_return_value("RDI")
+            .build();
+        assert!(run_check(&project, Vec::new()).is_empty());
+
+        let project = CallSequence::new(&["getpass", "socket", "write"])
+            .pass_return_value("RDI")
+            .build();
+        let warnings = run_check(&project, Vec::new());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["getpass".to_string(), "write".to_string()]
+        );
+    }
+
+    /// Copies the password into a stack buffer that is sent after the call to `function`.
+    ///
+    /// The password itself is not passed to `send`.
+    fn send_copied_password(function: &str) -> Vec<CweWarning> {
+        let mut project = CallSequence::new(&["getpass", "strcpy", function, "send"])
+            .pass_return_value("RSI")
+            .build();
+        for sub in project.program.term.subs.values_mut() {
+            for block in sub.term.blocks.iter_mut() {
+                block
+                    .term
+                    .defs
+                    .retain(|def| def.tid != Tid::new("set_param_3"));
+            }
+        }
+        let sanitizer_call = format!("call_{function}_2");
+        let states = ["call_strcpy_1", &sanitizer_call, "call_send_3"]
+            .into_iter()
+            .map(|call| (call, state_with_first_argument(None)))
+            .collect();
+
+        run_check(&project, states)
+    }
+
+    #[test]
+    fn sanitized_buffers_are_not_reported() {
+        let warnings = send_copied_password("other_function");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["getpass".to_string(), "send".to_string()]
+        );
+
+        assert!(send_copied_password("validate").is_empty());
+    }
+}
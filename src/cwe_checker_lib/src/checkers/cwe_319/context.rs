@@ -0,0 +1,252 @@
+//! Definition of the Taint Analysis for CWE319.
+//!
+//! The same analysis is used by the check for CWE311, where the sinks are
+//! functions that write to files and the TLS functions are replaced by
+//! encryption functions.
+//!
+//! Implementation of the [`TaintAnalysis`] trait for this CWE check. See the
+//! module documentation for more details on the algorithm and its limitations.
+
+use super::{SensitiveSource, SinkCall};
+
+use crate::analysis::fixpoint;
+use crate::analysis::forward_interprocedural_fixpoint::{
+    self, create_computation as fwd_fp_create_computation,
+};
+use crate::analysis::graph::{Graph as Cfg, HasCfg};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::library_summaries::LibrarySummaries;
+use crate::analysis::pointer_inference::{Data as PiData, PointerInference};
+use crate::analysis::taint::sanitizers::Sanitizer;
+use crate::analysis::taint::state::State as TaState;
+use crate::analysis::taint::{Taint, TaintAnalysis, TaintSource};
+use crate::analysis::vsa_results::{HasVsaResult, VsaResult};
+use crate::intermediate_representation::{ByteSize, ExternSymbol, Jmp, Project, Term};
+
+use std::collections::{BTreeMap, HashSet};
+use std::convert::AsRef;
+
+/// Type of the fixpoint computation of the taint analysis.
+pub type FpComputation<'a, 'b> = fixpoint::Computation<
+    forward_interprocedural_fixpoint::GeneralizedContext<'a, TaComputationContext<'a, 'b>>,
+>;
+
+/// The classes of external symbols that are relevant for the taint analysis.
+pub struct Symbols {
+    /// Functions that send their arguments in cleartext.
+    pub sinks: HashSet<String>,
+    /// Functions that copy data into the buffer given by their first argument.
+    pub propagation: HashSet<String>,
+    /// Functions of TLS or encryption libraries.
+    pub tls: HashSet<String>,
+    /// Functions that remove the taint of the data pointed to by one of their
+    /// parameters.
+    pub sanitizers: BTreeMap<String, Sanitizer>,
+    /// The summaries of the functions of the shared libraries imported by the binary.
+    pub library_summaries: Option<LibrarySummaries>,
+}
+
+/// Type that represents the definition of the taint analysis.
+///
+/// Values of this type represent the taint analysis for a particular call to a
+/// source function.
+pub struct TaComputationContext<'a, 'b: 'a> {
+    /// Source function call that is analyzed.
+    source: SensitiveSource<'a>,
+    project: &'a Project,
+    pi_result: &'a PointerInference<'b>,
+    symbols: &'a Symbols,
+    /// Used to send sink calls that are reached by taint to the collector.
+    sink_sender: crossbeam_channel::Sender<SinkCall>,
+}
+
+impl<'a, 'b: 'a> TaComputationContext<'a, 'b> {
+    /// Creates a new taint analysis context for the given call to a source
+    /// function.
+    pub(super) fn new(
+        source: SensitiveSource<'a>,
+        project: &'a Project,
+        pi_result: &'a PointerInference<'b>,
+        symbols: &'a Symbols,
+        sink_sender: crossbeam_channel::Sender<SinkCall>,
+    ) -> Self {
+        Self {
+            source,
+            project,
+            pi_result,
+            symbols,
+            sink_sender,
+        }
+    }
+
+    /// Converts the taint analysis context into a fixpoint computation.
+    ///
+    /// The returned computation can be solved to analyze this particular
+    /// source call.
+    pub fn into_computation(self) -> FpComputation<'a, 'b> {
+        let symbol = self.source.symbol;
+        let vsa_result = self.vsa_result();
+        let return_node = self.source.return_node;
+        let node_value = if self.source.taints_first_parameter {
+            let mut state = TaState::new_empty();
+            self.taint_destination_buffer(&mut state, self.source.jmp, symbol);
+            NodeValue::Value(state)
+        } else {
+            NodeValue::Value(TaState::new_return(
+                symbol,
+                TaintSource::Custom,
+                vsa_result,
+                return_node,
+            ))
+        };
+
+        let mut computation = fwd_fp_create_computation(self, None);
+
+        computation.set_node_value(return_node, node_value);
+
+        computation
+    }
+
+    /// Taints the buffer that the first parameter of the call points to.
+    fn taint_destination_buffer(
+        &self,
+        state: &mut TaState,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) {
+        if let Some(address) = extern_symbol.parameters.first().and_then(|param| {
+            self.vsa_result()
+                .eval_parameter_arg_at_call(&call.tid, param)
+        }) {
+            state.save_taint_to_memory(
+                &address,
+                Taint::new_tainted(ByteSize::new(1), TaintSource::Custom),
+            );
+        }
+    }
+}
+
+impl<'a> HasCfg<'a> for TaComputationContext<'a, '_> {
+    fn get_cfg(&self) -> &Cfg<'a> {
+        self.pi_result.get_graph()
+    }
+}
+
+impl HasVsaResult<PiData> for TaComputationContext<'_, '_> {
+    fn vsa_result(&self) -> &impl VsaResult<ValueDomain = PiData> {
+        self.pi_result
+    }
+}
+
+impl AsRef<Project> for TaComputationContext<'_, '_> {
+    fn as_ref(&self) -> &Project {
+        self.project
+    }
+}
+
+impl<'a> TaintAnalysis<'a> for TaComputationContext<'a, '_> {
+    fn get_sanitizers(&self) -> Option<&BTreeMap<String, Sanitizer>> {
+        Some(&self.symbols.sanitizers)
+    }
+
+    fn get_library_summaries(&self) -> Option<&LibrarySummaries> {
+        self.symbols.library_summaries.as_ref()
+    }
+
+    /// Update taint state on call to extern function.
+    ///
+    /// - Calls to sinks with tainted parameters are reported to the collector.
+    /// - Calls to TLS functions with tainted parameters stop the taint
+    ///   propagation, since we assume that the data is transmitted securely.
+    /// - Calls to propagation functions with tainted source parameters taint
+    ///   the destination buffer.
+    ///
+    /// In all cases the taint is removed from non-callee-saved registers.
+    fn update_extern_call(
+        &self,
+        state: &TaState,
+        call: &Term<Jmp>,
+        project: &Project,
+        extern_symbol: &ExternSymbol,
+    ) -> Option<TaState> {
+        if extern_symbol.no_return {
+            return None;
+        }
+        let vsa_result = self.vsa_result();
+        let name = &extern_symbol.name;
+
+        if self.symbols.tls.contains(name)
+            && state.check_extern_parameters_for_taint::<true>(vsa_result, extern_symbol, &call.tid)
+        {
+            return None;
+        }
+
+        let mut new_state = state.clone();
+
+        if self.symbols.sinks.contains(name)
+            && state.check_extern_parameters_for_taint::<true>(vsa_result, extern_symbol, &call.tid)
+        {
+            self.sink_sender
+                .send(SinkCall {
+                    tid: call.tid.clone(),
+                    symbol_name: name.clone(),
+                })
+                .expect("failed to send sink call");
+        } else if self.symbols.propagation.contains(name) {
+            let source_params = ExternSymbol {
+                parameters: extern_symbol.parameters.iter().skip(1).cloned().collect(),
+                ..extern_symbol.clone()
+            };
+            if state.check_extern_parameters_for_taint::<true>(
+                vsa_result,
+                &source_params,
+                &call.tid,
+            ) {
+                self.taint_destination_buffer(&mut new_state, call, extern_symbol);
+            }
+        }
+
+        new_state.remove_non_callee_saved_taint(project.get_calling_convention(extern_symbol));
+
+        Some(new_state)
+    }
+
+    /// Propagates taint from callee to caller.
+    ///
+    /// Only the taint in return registers and in memory objects that are known
+    /// to the caller is propagated.
+    fn update_return_callee(
+        &self,
+        state: &TaState,
+        call_term: &Term<Jmp>,
+        _return_term: &Term<Jmp>,
+        calling_convention: &Option<String>,
+    ) -> Option<TaState> {
+        let (mut propagated_register_taint, mut propagated_memory_taint) =
+            state.clone().into_mem_reg_taint();
+
+        let renaming_map = self.pi_result.get_call_renaming_map(&call_term.tid);
+        propagated_memory_taint.retain(|aid, _| {
+            renaming_map.is_some_and(|renaming_map| {
+                renaming_map
+                    .get(aid)
+                    .is_some_and(|value| value.referenced_ids().next().is_some())
+            })
+        });
+
+        if let Some(calling_convention) = self
+            .project
+            .get_specific_calling_convention(calling_convention)
+        {
+            let return_registers = calling_convention.get_all_return_register();
+
+            propagated_register_taint
+                .retain(|reg, taint| return_registers.contains(&reg) && taint.is_tainted());
+        }
+
+        Some(TaState::from_mem_reg_taint(
+            propagated_register_taint,
+            propagated_memory_taint,
+        ))
+    }
+}
@@ -0,0 +1,252 @@
+//! CWE-330: Use of Insufficiently Random Values.
+//!
+//! Values returned by non-cryptographic pseudo-random number generators like
+//! `rand` or derived from the current time are predictable. If such values are
+//! used where unpredictability is required, e.g., as cryptographic keys,
+//! session tokens, or names of temporary files, an attacker can guess them.
+//!
+//! See <https://cwe.mitre.org/data/definitions/330.html> for a detailed
+//! description.
+//!
+//! # Algorithm
+//!
+//! We reuse the taint analysis of the [CWE-319 check](super::cwe_319). The
+//! sources are the return values of PRNG and time functions, e.g., `rand` and
+//! `time`, and buffers that are filled with the current time, e.g., the first
+//! parameter of `gettimeofday`. Taint is propagated into buffers by calls to
+//! string and memory copying functions.
+//!
+//! To distinguish security-relevant uses from benign ones (e.g. simulations or
+//! games) only the following sinks are considered:
+//!
+//! - functions that set up cryptographic keys or seed a cryptographic PRNG,
+//!   e.g., `AES_set_encrypt_key` or `RAND_seed`,
+//! - functions that create files or directories, since predictable names of
+//!   temporary files can be abused by an attacker, e.g., in `/tmp`, and
+//! - keyed functions like `setenv` where the (constant) key string contains a
+//!   keyword that indicates a token, e.g., `setenv("SESSION_TOKEN", ...)`.
+//!
+//! A warning containing the flow trace is generated for every sink call with
+//! tainted arguments or arguments that point to tainted memory.
+//!
+//! # Limitations
+//!
+//! ## False Positives
+//!
+//! - Files with time-based names, e.g. log files, in directories that are not
+//!   writable by an attacker are reported.
+//! - The purpose of keyed values is guessed from keywords.
+//!
+//! ## False Negatives
+//!
+//! - Predictable values that are passed to a function of the binary are not
+//!   followed into it, so key setup or file creation inside wrappers like a
+//!   custom `create_session(token)` routine is missed.
+//! - Tokens that are not stored through one of the keyed sinks, e.g. tokens
+//!   that are only sent over the network, are not detected.
+//! - Custom PRNG implementations are not detected as sources.
+//!
+//! # Configuration
+//!
+//! The lists of source, sink, and propagation functions as well as the
+//! keywords that mark keys of tokens can be configured via the `config.json`.
+//! Sanitizers are declared as for the CWE-319 check.
+use super::prelude::*;
+
+use super::cwe_319::context::Symbols;
+use super::cwe_319::{
+    find_tainted_sink_calls, get_constant_key_string, get_sources, is_sensitive_key,
+    SensitiveSource, SinkCall,
+};
+use crate::analysis::taint::sanitizers::Sanitizer;
+use crate::prelude::*;
+use crate::utils::symbol_utils;
+
+use std::collections::{BTreeMap, HashSet};
+
+cwe_module!(
+    "CWE330",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// External symbols whose return values are predictable.
+        source_symbols: HashSet<String>,
+        /// External symbols that write predictable values into the buffer
+        /// given by their first parameter.
+        buffer_source_symbols: HashSet<String>,
+        /// External symbols that require unpredictable arguments, e.g. for key
+        /// generation or the creation of files.
+        sink_symbols: HashSet<String>,
+        /// External symbols that are only treated as sinks if their first
+        /// argument is a constant string containing a keyword.
+        keyed_sink_symbols: HashSet<String>,
+        /// Keywords that mark a key as referencing a value that must be
+        /// unpredictable.
+        keywords: Vec<String>,
+        /// External symbols that copy data from their other parameters into
+        /// the buffer given by their first parameter.
+        propagation_symbols: HashSet<String>,
+        /// Functions that validate the data pointed to by one of their
+        /// parameters.
+        sanitizers: BTreeMap<String, Sanitizer>,
+);
+
+/// Generates the CWE warning for a tainted sink call.
+fn generate_cwe_warning(source: &SensitiveSource, sink: &SinkCall, trace: &[Tid]) -> CweWarning {
+    let source_name = source.get_symbol_name();
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Use of Insufficiently Random Values) Predictable value returned by {} ({}) is used by the call to {} at {}.",
+            source.jmp.tid.address(),
+            source_name,
+            sink.symbol_name,
+            sink.tid.address()
+        ),
+    )
+    .addresses(vec![
+        sink.tid.address().to_string(),
+        source.jmp.tid.address().to_string(),
+    ])
+    .tids(vec![format!("{}", sink.tid), format!("{}", source.jmp.tid)])
+    .symbols(vec![source_name, sink.symbol_name.clone()])
+    .other(vec![trace
+        .iter()
+        .map(|tid| format!("trace={}", tid.address()))
+        .collect()])
+}
+
+/// CWE-330: Use of Insufficiently Random Values.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let config: Config =
+        serde_json::from_value(cwe_params.clone()).expect("CWE330: invalid configuration");
+    let project = analysis_results.project;
+    let pi_result = analysis_results
+        .pointer_inference
+        .expect("CWE330: BUG: No pointer inference results.");
+
+    let symbols = Symbols {
+        sinks: config
+            .sink_symbols
+            .union(&config.keyed_sink_symbols)
+            .cloned()
+            .collect(),
+        propagation: config.propagation_symbols.clone(),
+        tls: HashSet::new(),
+        sanitizers: config.sanitizers.clone(),
+        library_summaries: analysis_results.library_summaries.cloned(),
+    };
+    let keyed_sinks = symbol_utils::get_symbol_map_fast(project, &config.keyed_sink_symbols);
+
+    let sources = get_sources(
+        analysis_results,
+        &config.source_symbols,
+        &config.buffer_source_symbols,
+        &HashSet::new(),
+        &[],
+    );
+    let mut cwe_warnings: Vec<CweWarning> =
+        find_tainted_sink_calls(analysis_results, sources, &symbols)
+            .iter()
+            .filter(|(_, sink, _)| {
+                let Some(symbol) = keyed_sinks
+                    .values()
+                    .find(|symbol| symbol.name == sink.symbol_name)
+                else {
+                    return true;
+                };
+                get_constant_key_string(pi_result, project, symbol, &sink.tid)
+                    .is_some_and(|key| is_sensitive_key(&key, &config.keywords))
+            })
+            .map(|(source, sink, trace)| generate_cwe_warning(source, sink, trace))
+            .collect();
+    cwe_warnings.sort();
+
+    cwe_warnings.deduplicate_addresses()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::{PointerInference, State as PiState};
+    use crate::checkers::test_utils::{self, CallSequence};
+    use crate::intermediate_representation::*;
+    use crate::{bitvec, variable};
+
+    use std::collections::BTreeSet;
+
+    /// Returns a project with a function that calls the given external
+    /// functions in order and passes the return value of the first call as the
+    /// second argument to all following calls.
+    fn mock_project(symbols: &[&str]) -> Project {
+        CallSequence::new(symbols).pass_return_value("RSI").build()
+    }
+
+    /// Runs the check. If a key address is given, it is the value of the first
+    /// argument of the call to `setenv`, which is the second call of the project.
+    fn run_check(project: &Project, key_address: Option<u64>) -> Vec<CweWarning> {
+        let mut pi_result = PointerInference::mock(project);
+        if let Some(address) = key_address {
+            let mut state = PiState::new(&variable!("RSP:8"), Tid::new("func"), BTreeSet::new());
+            state.set_register(&variable!("RDI:8"), bitvec!(format!("{address}:8")).into());
+            pi_result
+                .get_mut_states_at_tids()
+                .insert(Tid::new("call_setenv_1"), state);
+        }
+        let config = serde_json::json!({
+            "source_symbols": ["rand"],
+            "buffer_source_symbols": ["gettimeofday"],
+            "sink_symbols": ["AES_set_encrypt_key", "mkdir"],
+            "keyed_sink_symbols": ["setenv"],
+            "keywords": ["world"],
+            "propagation_symbols": ["strcpy"],
+            "sanitizers": {}
+        });
+
+        test_utils::run_check(project, &pi_result, check_cwe, &config)
+    }
+
+    #[test]
+    fn predictable_value_used_as_key() {
+        let project = mock_project(&["rand", "other_function", "AES_set_encrypt_key"]);
+        let warnings = run_check(&project, None);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "call_AES_set_encrypt_key_2".to_string(),
+                "call_rand_0".to_string()
+            ]
+        );
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["rand".to_string(), "AES_set_encrypt_key".to_string()]
+        );
+
+        // Values from other functions are not reported.
+        let project = mock_project(&["getpid", "other_function", "AES_set_encrypt_key"]);
+        assert!(run_check(&project, None).is_empty());
+    }
+
+    #[test]
+    fn keyed_sinks_require_a_sensitive_key() {
+        let project = mock_project(&["rand", "setenv"]);
+        // The key "Hello World" contains a keyword.
+        let warnings = run_check(&project, Some(0x3002));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["rand".to_string(), "setenv".to_string()]
+        );
+        // The key "/dev/sd%c%d" does not contain a keyword.
+        assert!(run_check(&project, Some(0x5000)).is_empty());
+        // Unknown keys are not reported.
+        assert!(run_check(&project, None).is_empty());
+    }
+}
@@ -26,7 +26,7 @@ use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
 use crate::analysis::pointer_inference::{
     Data as PiData, PointerInference as PointerInferenceComputation,
 };
-use crate::analysis::taint::{state::State as TaState, TaintAnalysis};
+use crate::analysis::taint::{state::State as TaState, TaintAnalysis, TaintSource};
 use crate::analysis::vsa_results::{HasVsaResult, VsaResult};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
@@ -42,6 +42,7 @@ cwe_module!(
     "CWE337",
     "0.1",
     check_cwe,
+    requires: [PointerInference],
     config:
         /// Sources of predictable seeds.
         sources: Vec<String>,
@@ -106,7 +107,12 @@ pub fn check_cwe(
 
         computation.set_node_value(
             return_node,
-            NodeValue::Value(TaState::new_return(symbol, pi_result, return_node)),
+            NodeValue::Value(TaState::new_return(
+                symbol,
+                TaintSource::Custom,
+                pi_result,
+                return_node,
+            )),
         );
     }
 
@@ -0,0 +1,255 @@
+//! This module implements a check for CWE-377: Insecure Temporary File.
+//!
+//! Temporary files with predictable names in shared directories like `/tmp`
+//! can be created or replaced by an attacker before the program opens them,
+//! e.g. as a symbolic link to a sensitive file. Functions like `tmpnam` or
+//! `mktemp` only return a name, so that there is always a race between
+//! choosing the name and creating the file.
+//!
+//! See <https://cwe.mitre.org/data/definitions/377.html> for a detailed
+//! description.
+//!
+//! ## How the check works
+//!
+//! - Every call to one of the configured `insecure_name_symbols`, e.g.
+//!   `tmpnam`, is flagged.
+//! - For calls to the functions in `path_parameters`, e.g. `open`, the path
+//!   argument is evaluated. If it is a constant string or if the [String
+//!   Abstraction](crate::analysis::string_abstraction) can enumerate all
+//!   strings it may represent, and all of them are located in one of the
+//!   configured `temp_directories`, then the call is flagged.
+//!
+//! The warnings suggest `mkstemp` as a replacement.
+//!
+//! ## False Positives
+//!
+//! - Predictable file names in temporary directories are reported even if the
+//!   file is opened with `O_EXCL` or only read.
+//!
+//! ## False Negatives
+//!
+//! - Paths whose parts are unknown to the String Abstraction, e.g. because
+//!   they contain numbers formatted by `sprintf`, are not reported even if
+//!   these parts are predictable. See the check for CWE-330 for such cases.
+use super::prelude::*;
+
+use crate::abstract_domain::{AbstractDomain, BricksDomain, TryToBitvec, TryToConcrete};
+use crate::analysis::graph::Edge;
+use crate::analysis::pointer_inference::{PointerInference, State as PointerInferenceState};
+use crate::analysis::string_abstraction::context::Context;
+use crate::analysis::string_abstraction::state::State;
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::symbol_utils::get_symbol_map;
+
+use petgraph::visit::EdgeRef;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The maximal number of different paths that are enumerated for a parameter.
+const MAX_PATH_COUNT: usize = 16;
+
+cwe_module!(
+    "CWE377",
+    "0.1",
+    check_cwe,
+    requires: [StringAbstraction],
+    config:
+        /// Functions that generate names of temporary files without creating them.
+        insecure_name_symbols: Vec<String>,
+        /// Functions that open or create files, mapped to the index of their path
+        /// parameter.
+        path_parameters: BTreeMap<String, usize>,
+        /// Prefixes of directories that are writable by other users.
+        temp_directories: Vec<String>,
+);
+
+/// Returns the paths represented by the given string domain if they are
+/// predictable and all located in one of the temporary directories.
+fn get_predictable_temp_paths(
+    domain: &BricksDomain,
+    temp_directories: &[String],
+) -> Option<BTreeSet<String>> {
+    let paths = domain.try_to_concrete(MAX_PATH_COUNT).ok()?;
+    let in_temp_directory = |path: &String| {
+        temp_directories
+            .iter()
+            .any(|directory| path.starts_with(directory))
+    };
+
+    (!paths.is_empty() && paths.iter().all(in_temp_directory)).then_some(paths)
+}
+
+/// Computes the string domain of the given path parameter at a call.
+///
+/// Returns *Top* if the parameter cannot be evaluated.
+fn get_path_domain(
+    string_state: &State<BricksDomain>,
+    pi_state: &PointerInferenceState,
+    parameter: &Arg,
+    call_tid: &Tid,
+    pi_result: &PointerInference,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> BricksDomain {
+    let Some(value) = pi_result.eval_parameter_arg_at_call(call_tid, parameter) else {
+        return BricksDomain::Top;
+    };
+    let mut domains = Vec::new();
+    if !value.get_relative_values().is_empty() {
+        domains.push(
+            Context::<BricksDomain>::merge_domains_from_multiple_pointer_targets(
+                string_state,
+                pi_state,
+                value.get_relative_values(),
+            ),
+        );
+    }
+    if let Some(address) = value.get_absolute_value() {
+        match address.try_to_bitvec().ok().and_then(|address| {
+            runtime_memory_image
+                .read_string_until_null_terminator(&address)
+                .ok()
+        }) {
+            Some(string) => domains.push(BricksDomain::from(string.to_string())),
+            None => return BricksDomain::Top,
+        }
+    }
+
+    domains
+        .into_iter()
+        .reduce(|accumulator, domain| accumulator.merge(&domain))
+        .unwrap_or(BricksDomain::Top)
+}
+
+/// Generates the CWE warning for a call to a function that only generates a
+/// name for a temporary file.
+fn generate_insecure_name_warning(jmp: &Term<Jmp>, symbol: &ExternSymbol) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Insecure Temporary File) Call to {} at {} generates a temporary file name that may be taken by an attacker before the file is created. Use mkstemp instead.",
+            symbol.name,
+            jmp.tid.address()
+        ),
+    )
+    .tids(vec![format!("{}", jmp.tid)])
+    .addresses(vec![jmp.tid.address().to_string()])
+    .symbols(vec![symbol.name.clone()])
+}
+
+/// Generates the CWE warning for a call that opens a file with a predictable
+/// path in a temporary directory.
+fn generate_predictable_path_warning(
+    jmp: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    paths: &BTreeSet<String>,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Insecure Temporary File) Call to {} at {} opens a file with a predictable name in a temporary directory ({}). Use mkstemp instead.",
+            symbol.name,
+            jmp.tid.address(),
+            paths
+                .iter()
+                .map(|path| format!("{path:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    )
+    .tids(vec![format!("{}", jmp.tid)])
+    .addresses(vec![jmp.tid.address().to_string()])
+    .symbols(vec![symbol.name.clone()])
+    .other(vec![paths.iter().map(|path| format!("path={path}")).collect()])
+}
+
+/// Execute the CWE check.
+///
+/// Calls to the insecure name generating functions are always flagged. Calls
+/// to file opening functions are flagged if their path argument is predictable
+/// and located in a temporary directory.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let project = analysis_results.project;
+    let pi_result = analysis_results.pointer_inference.unwrap();
+    let string_abstraction = analysis_results.string_abstraction.unwrap();
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    let insecure_name_symbols = get_symbol_map(project, &config.insecure_name_symbols);
+    let path_symbols = get_symbol_map(
+        project,
+        &config.path_parameters.keys().cloned().collect::<Vec<_>>(),
+    );
+    for edge in string_abstraction.get_graph().edge_references() {
+        let Edge::ExternCallStub(jmp) = edge.weight() else {
+            continue;
+        };
+        let Jmp::Call { target, .. } = &jmp.term else {
+            continue;
+        };
+        if let Some(symbol) = insecure_name_symbols.get(target) {
+            cwe_warnings.push(generate_insecure_name_warning(jmp, symbol));
+            continue;
+        }
+        let Some(symbol) = path_symbols.get(target) else {
+            continue;
+        };
+        let (Some(parameter), Some(string_node), Some(pi_state)) = (
+            symbol.parameters.get(config.path_parameters[&symbol.name]),
+            string_abstraction.get_node_value(edge.source()),
+            pi_result.get_state_at_jmp_tid(&jmp.tid),
+        ) else {
+            continue;
+        };
+        let domain = get_path_domain(
+            string_node.unwrap_value(),
+            pi_state,
+            parameter,
+            &jmp.tid,
+            pi_result,
+            &project.runtime_memory_image,
+        );
+        if let Some(paths) = get_predictable_temp_paths(&domain, &config.temp_directories) {
+            cwe_warnings.push(generate_predictable_path_warning(jmp, symbol, &paths));
+        }
+    }
+
+    cwe_warnings.deduplicate_first_address()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::BrickDomain;
+
+    #[test]
+    fn predictable_temp_paths() {
+        let temp_directories = vec!["/tmp/".to_string(), "/var/tmp/".to_string()];
+
+        let domain = BricksDomain::from("/tmp/app.lock".to_string());
+        assert_eq!(
+            get_predictable_temp_paths(&domain, &temp_directories),
+            Some(BTreeSet::from(["/tmp/app.lock".to_string()]))
+        );
+        // Paths outside of temporary directories are not reported.
+        let domain = BricksDomain::from("/etc/app.conf".to_string());
+        assert_eq!(get_predictable_temp_paths(&domain, &temp_directories), None);
+        // Paths with unknown parts are not predictable.
+        let domain = BricksDomain::Value(vec![
+            BrickDomain::new("/tmp/app_".to_string()),
+            BrickDomain::Top,
+        ]);
+        assert_eq!(get_predictable_temp_paths(&domain, &temp_directories), None);
+        assert_eq!(
+            get_predictable_temp_paths(&BricksDomain::Top, &temp_directories),
+            None
+        );
+    }
+}
@@ -0,0 +1,476 @@
+//! This module implements a check for CWE-401: Missing Release of Memory after
+//! Effective Lifetime.
+//!
+//! If the last pointer to a heap object is lost before the object is freed,
+//! the memory of the object can never be released again. Such memory leaks
+//! often hide in error handling code: A function allocates some memory, then
+//! detects an error and returns early without releasing the memory that it
+//! would release or hand over on the regular path. If an attacker can trigger
+//! the error path repeatedly, the program may run out of memory.
+//!
+//! See <https://cwe.mitre.org/data/definitions/401.html> for a detailed
+//! description.
+//!
+//! ## How the check works
+//!
+//! Using the results of the [Pointer Inference
+//! analysis](`crate::analysis::pointer_inference`) the check looks at the heap
+//! objects that were allocated in a function (directly or in a callee that
+//! returned the object) at each return instruction of the function. Such an
+//! object is considered to be leaked at a return instruction if
+//!
+//! - no pointer to it can be reached anymore from the return registers, the
+//!   callee-saved registers, global memory or memory objects of the caller,
+//!   i.e. all pointers to it were overwritten or only stored on the stack frame
+//!   that goes out of scope, and
+//! - it is not released on all paths to the return instruction. An object is
+//!   released by calls to one of the configured `deallocation_symbols` and by
+//!   calls to internal functions that take a pointer to it as a parameter,
+//!   since the callee may free it or store it in some escaping data structure.
+//!
+//! To focus on error paths, a CWE warning is only generated if the function
+//! takes care of the object on some other path, i.e. if the object is released
+//! somewhere in the function or is still reachable at some other return
+//! instruction.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! The `deallocation_symbols` are the names of extern functions that release
+//! memory. The check assumes that the first parameter of such a function is
+//! the memory object to be released.
+//!
+//! ## False Positives
+//!
+//! - Since the Pointer Inference analysis is not path-sensitive, the check may
+//!   report leaks on paths where the object was never allocated, e.g. if the
+//!   allocation failed and the function returns because of it.
+//! - Pointers given to extern functions other than the deallocation symbols
+//!   are assumed to not escape into data structures of the extern function.
+//!
+//! ## False Negatives
+//!
+//! - Objects that are never released or reachable on any path of the function
+//!   are not reported, since the check only flags deviations from the way the
+//!   function handles the object on other paths.
+//! - The Pointer Inference analysis removes unreferenced objects after calls
+//!   to internal functions. Leaks of objects whose last pointer was lost
+//!   before such a call are missed.
+//! - A call to a deallocation symbol releases all objects that its parameter
+//!   may point to, even if the parameter may point to more than one object.
+use super::prelude::*;
+
+use crate::abstract_domain::AbstractIdentifier;
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::{PointerInference, State};
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::symbol_utils::get_symbol_map;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+cwe_module!(
+    "CWE401",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// The names of symbols that release memory (e.g. the "free" function
+        /// of C). The check assumes that the memory object to be released is
+        /// the first parameter of the function.
+        deallocation_symbols: Vec<String>,
+);
+
+/// Return the call in the current function that created the object with the
+/// given ID or returned it from a callee.
+fn get_origin_call(id: &AbstractIdentifier) -> &Tid {
+    id.get_path_hints().last().unwrap_or(id.get_tid())
+}
+
+/// Return the TIDs of the blocks that control flow may reach after the given
+/// block inside the same function.
+fn get_successors(block: &Term<Blk>) -> impl Iterator<Item = &Tid> {
+    block.term.jmps.iter().filter_map(|jmp| match &jmp.term {
+        Jmp::Branch(target) | Jmp::CBranch { target, .. } => Some(target),
+        Jmp::Call { return_, .. }
+        | Jmp::CallInd { return_, .. }
+        | Jmp::CallOther { return_, .. } => return_.as_ref(),
+        Jmp::BranchInd(_) | Jmp::Return(_) => None,
+    })
+}
+
+/// Compute the IDs of the objects that are released at the given call.
+///
+/// These are the targets of the first parameter of calls to deallocation
+/// symbols and the targets of all parameters of calls to internal functions.
+fn get_released_ids(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    deallocation_symbols: &HashMap<Tid, &ExternSymbol>,
+    call: &Term<Jmp>,
+) -> BTreeSet<AbstractIdentifier> {
+    let Jmp::Call { target, .. } = &call.term else {
+        return BTreeSet::new();
+    };
+    if let Some(symbol) = deallocation_symbols.get(target) {
+        symbol
+            .parameters
+            .first()
+            .and_then(|parameter| {
+                pointer_inference.eval_parameter_arg_at_call(&call.tid, parameter)
+            })
+            .map(|value| value.get_relative_values().keys().cloned().collect())
+            .unwrap_or_default()
+    } else if project.program.term.subs.contains_key(target) {
+        pointer_inference
+            .get_id_renaming_map_at_call_tid(&call.tid)
+            .into_iter()
+            .flat_map(|renaming_map| renaming_map.values())
+            .flat_map(|value| value.get_relative_values().keys().cloned())
+            .collect()
+    } else {
+        BTreeSet::new()
+    }
+}
+
+/// Compute for each block of the function the IDs of the objects that are
+/// released on all paths from the function start to the end of the block.
+///
+/// Blocks that are only reachable through indirect jumps are not contained in
+/// the result.
+fn compute_must_released_ids(
+    sub: &Term<Sub>,
+    released_at_calls: &HashMap<Tid, BTreeSet<AbstractIdentifier>>,
+) -> HashMap<Tid, BTreeSet<AbstractIdentifier>> {
+    let mut released_at_block_end = HashMap::new();
+    let Some(entry_block) = sub.term.blocks.first() else {
+        return released_at_block_end;
+    };
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    let mut released_at_block_start = HashMap::from([(&entry_block.tid, BTreeSet::new())]);
+    let mut worklist = vec![entry_block];
+    while let Some(block) = worklist.pop() {
+        let mut released: BTreeSet<AbstractIdentifier> =
+            released_at_block_start[&block.tid].clone();
+        for jmp in block.term.jmps.iter() {
+            if let Some(ids) = released_at_calls.get(&jmp.tid) {
+                released.extend(ids.iter().cloned());
+            }
+        }
+        for successor in get_successors(block) {
+            let Some(successor_block) = blocks.get(successor) else {
+                continue;
+            };
+            let successor_start = match released_at_block_start.get(successor) {
+                Some(old_start) => released.intersection(old_start).cloned().collect(),
+                None => released.clone(),
+            };
+            if released_at_block_start.get(successor) != Some(&successor_start) {
+                released_at_block_start.insert(&successor_block.tid, successor_start);
+                worklist.push(successor_block);
+            }
+        }
+        released_at_block_end.insert(block.tid.clone(), released);
+    }
+    released_at_block_end
+}
+
+/// Return the IDs of all objects that are still reachable after the function
+/// returns.
+///
+/// Pointers to such objects are contained in the return registers, the
+/// callee-saved registers, or in (objects reachable from) memory objects that
+/// are neither the stack frame of the function nor one of the `owned_ids`.
+/// Pointers stored on the stack frame of the function do not survive the
+/// return.
+fn get_reachable_ids(
+    state: &State,
+    calling_convention: Option<&CallingConvention>,
+    owned_ids: &BTreeSet<AbstractIdentifier>,
+) -> BTreeSet<AbstractIdentifier> {
+    let mut worklist: Vec<AbstractIdentifier> = calling_convention
+        .into_iter()
+        .flat_map(|cconv| {
+            cconv
+                .integer_return_register
+                .iter()
+                .chain(cconv.callee_saved_register.iter())
+        })
+        .flat_map(|register| {
+            state
+                .get_register(register)
+                .get_relative_values()
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    worklist.extend(
+        state
+            .memory
+            .get_all_object_ids()
+            .into_iter()
+            .filter(|id| *id != state.stack_id && !owned_ids.contains(id)),
+    );
+    let mut reachable_ids = BTreeSet::new();
+    while let Some(id) = worklist.pop() {
+        if id == state.stack_id || !reachable_ids.insert(id.clone()) {
+            continue;
+        }
+        if let Some(object) = state.memory.get_object(&id) {
+            worklist.extend(
+                object
+                    .get_referenced_ids_overapproximation()
+                    .iter()
+                    .cloned(),
+            );
+        }
+    }
+    reachable_ids
+}
+
+/// Generate the CWE warning for an object that is leaked at the given returns.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    object_id: &AbstractIdentifier,
+    returns: &[&Tid],
+) -> CweWarning {
+    let allocation = get_origin_call(object_id);
+    let return_addresses: Vec<String> = returns
+        .iter()
+        .map(|tid| tid.address().to_string())
+        .collect();
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Memory Leak) Heap object allocated at {} in {} may be leaked when returning at {}",
+            allocation.address(),
+            sub.term.name,
+            return_addresses.join(", ")
+        ),
+    )
+    .tids(
+        std::iter::once(allocation)
+            .chain(returns.iter().copied())
+            .map(|tid| format!("{tid}"))
+            .collect(),
+    )
+    .addresses(
+        std::iter::once(allocation.address().to_string())
+            .chain(return_addresses)
+            .collect(),
+    )
+    .symbols(vec![sub.term.name.clone()])
+    .other(vec![vec![format!("Leaked object ID: {object_id}")]])
+}
+
+/// Check the given function for heap objects that are leaked at some but not
+/// all of its return instructions.
+fn check_function(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    deallocation_symbols: &HashMap<Tid, &ExternSymbol>,
+    sub: &Term<Sub>,
+) -> Vec<CweWarning> {
+    let jmps = || {
+        sub.term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.jmps.iter())
+    };
+    let jmp_tids: HashSet<&Tid> = jmps().map(|jmp| &jmp.tid).collect();
+    let released_at_calls: HashMap<Tid, BTreeSet<AbstractIdentifier>> = jmps()
+        .map(|jmp| {
+            let released_ids =
+                get_released_ids(project, pointer_inference, deallocation_symbols, jmp);
+            (jmp.tid.clone(), released_ids)
+        })
+        .filter(|(_, released_ids)| !released_ids.is_empty())
+        .collect();
+    let must_released_at_block_end = compute_must_released_ids(sub, &released_at_calls);
+    let calling_convention = project.get_specific_calling_convention(&sub.term.calling_convention);
+
+    // Objects that the function takes care of on at least one path.
+    let mut handled_ids: BTreeSet<AbstractIdentifier> =
+        released_at_calls.into_values().flatten().collect();
+    let mut unreachable_ids_at_returns = Vec::new();
+    for block in sub.term.blocks.iter() {
+        let (Some(must_released_ids), Some(return_jmp)) = (
+            must_released_at_block_end.get(&block.tid),
+            block
+                .term
+                .jmps
+                .iter()
+                .find(|jmp| matches!(jmp.term, Jmp::Return(_))),
+        ) else {
+            continue;
+        };
+        let Some(state) = pointer_inference.get_state_at_jmp_tid(&return_jmp.tid) else {
+            continue;
+        };
+        let owned_ids: BTreeSet<AbstractIdentifier> = state
+            .memory
+            .iter()
+            .filter(|(id, object)| {
+                object.get_object_type() == Some(ObjectType::Heap)
+                    && jmp_tids.contains(get_origin_call(id))
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        let reachable_ids = get_reachable_ids(state, calling_convention, &owned_ids);
+        let (reachable_owned_ids, unreachable_owned_ids): (BTreeSet<_>, BTreeSet<_>) = owned_ids
+            .into_iter()
+            .partition(|id| reachable_ids.contains(id));
+        handled_ids.extend(reachable_owned_ids);
+        let leaked_ids: BTreeSet<AbstractIdentifier> = unreachable_owned_ids
+            .difference(must_released_ids)
+            .cloned()
+            .collect();
+        unreachable_ids_at_returns.push((&return_jmp.tid, leaked_ids));
+    }
+
+    let mut leaks: BTreeMap<AbstractIdentifier, Vec<&Tid>> = BTreeMap::new();
+    for (return_tid, leaked_ids) in unreachable_ids_at_returns {
+        for id in leaked_ids.intersection(&handled_ids) {
+            leaks.entry(id.clone()).or_default().push(return_tid);
+        }
+    }
+    leaks
+        .iter()
+        .map(|(id, returns)| generate_cwe_warning(sub, id, returns))
+        .collect()
+}
+
+/// Execute the CWE check.
+///
+/// For each function we check whether heap objects allocated in it are leaked
+/// at some of its return instructions, while they are released or handed over
+/// at others.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference.unwrap();
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let deallocation_symbols = get_symbol_map(project, &config.deallocation_symbols);
+
+    let cwe_warnings: Vec<CweWarning> = project
+        .program
+        .functions()
+        .flat_map(|sub| check_function(project, pointer_inference, &deallocation_symbols, sub))
+        .collect();
+
+    cwe_warnings.deduplicate_first_address()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::{AbstractLocation, IntervalDomain};
+    use crate::analysis::pointer_inference::Data;
+    use crate::variable;
+
+    fn mock_heap_id(call_tid: &str) -> AbstractIdentifier {
+        AbstractIdentifier::new(
+            Tid::new(call_tid),
+            AbstractLocation::Register(variable!("RAX:8")),
+        )
+    }
+
+    #[test]
+    fn must_released_ids() {
+        // entry -> call_blk (free) -> free_return -> exit
+        // entry -> error -> exit
+        let mut entry = Blk::mock_with_tid("entry");
+        entry.term.jmps = vec![
+            Term {
+                tid: Tid::new("cbranch"),
+                term: Jmp::CBranch {
+                    target: Tid::new("error"),
+                    condition: Expression::Var(variable!("ZF:1")),
+                },
+            },
+            Jmp::branch("to_call", "call_blk"),
+        ];
+        let mut call_blk = Blk::mock_with_tid("call_blk");
+        call_blk
+            .term
+            .jmps
+            .push(Jmp::call("free_call", "free", Some("free_return")));
+        let mut free_return = Blk::mock_with_tid("free_return");
+        free_return.term.jmps.push(Jmp::branch("to_exit", "exit"));
+        let mut error = Blk::mock_with_tid("error");
+        error.term.jmps.push(Jmp::branch("error_to_exit", "exit"));
+        let exit = Blk::mock_with_tid("exit");
+        let mut sub = Sub::mock("func");
+        sub.term.blocks = vec![entry, call_blk, free_return, error, exit];
+
+        let released_at_calls = HashMap::from([(
+            Tid::new("free_call"),
+            BTreeSet::from([mock_heap_id("malloc")]),
+        )]);
+        let released = compute_must_released_ids(&sub, &released_at_calls);
+        assert!(released[&Tid::new("call_blk")].contains(&mock_heap_id("malloc")));
+        assert!(released[&Tid::new("free_return")].contains(&mock_heap_id("malloc")));
+        assert!(released[&Tid::new("error")].is_empty());
+        assert!(released[&Tid::new("exit")].is_empty());
+
+        // Without the error path the object is released at the exit block.
+        sub.term.blocks[0].term.jmps.remove(0);
+        let released = compute_must_released_ids(&sub, &released_at_calls);
+        assert!(!released.contains_key(&Tid::new("error")));
+        assert!(released[&Tid::new("exit")].contains(&mock_heap_id("malloc")));
+    }
+
+    #[test]
+    fn reachable_ids() {
+        let cconv = CallingConvention::mock_x64();
+        let mut state = State::new(&variable!("RSP:8"), Tid::new("func"), BTreeSet::new());
+        let zero = || IntervalDomain::from(Bitvector::from_i64(0));
+        for call_tid in ["returned", "stored_in_returned", "stored_on_stack"] {
+            state.memory.add_abstract_object(
+                mock_heap_id(call_tid),
+                ByteSize::new(8),
+                Some(ObjectType::Heap),
+            );
+        }
+        state.set_register(
+            &variable!("RAX:8"),
+            Data::from_target(mock_heap_id("returned"), zero()),
+        );
+        state
+            .memory
+            .set_value(
+                Data::from_target(mock_heap_id("returned"), zero()),
+                Data::from_target(mock_heap_id("stored_in_returned"), zero()),
+            )
+            .unwrap();
+        state
+            .memory
+            .set_value(
+                Data::from_target(
+                    state.stack_id.clone(),
+                    IntervalDomain::from(Bitvector::from_i64(-8)),
+                ),
+                Data::from_target(mock_heap_id("stored_on_stack"), zero()),
+            )
+            .unwrap();
+        let owned_ids = BTreeSet::from([
+            mock_heap_id("returned"),
+            mock_heap_id("stored_in_returned"),
+            mock_heap_id("stored_on_stack"),
+        ]);
+
+        let reachable_ids = get_reachable_ids(&state, Some(&cconv), &owned_ids);
+        assert!(reachable_ids.contains(&mock_heap_id("returned")));
+        assert!(reachable_ids.contains(&mock_heap_id("stored_in_returned")));
+        assert!(!reachable_ids.contains(&mock_heap_id("stored_on_stack")));
+    }
+}
@@ -3,14 +3,17 @@ use super::WarningContext;
 use super::CWE_MODULE;
 use crate::abstract_domain::AbstractDomain;
 use crate::abstract_domain::AbstractIdentifier;
+use crate::abstract_domain::TryToBitvec;
 use crate::analysis::function_signature::FunctionSignature;
 use crate::analysis::graph::Graph;
+use crate::analysis::pointer_inference::Data;
 use crate::analysis::pointer_inference::PointerInference;
 use crate::analysis::vsa_results::VsaResult;
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::CweWarning;
 use crate::utils::log::LogMessage;
+use crate::utils::log::Severity;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
@@ -22,6 +25,11 @@ pub struct Context<'a> {
     /// The names of extern functions that deallocate memory.
     /// These functions will create dangling pointers during the analysis.
     pub deallocation_symbols: BTreeSet<String>,
+    /// The names of extern functions that reallocate memory.
+    /// These functions create dangling pointers unless they return `NULL`.
+    pub reallocation_symbols: BTreeSet<String>,
+    /// Maps the TIDs of calls to reallocation functions to the called extern symbol.
+    reallocation_calls: BTreeMap<Tid, &'a ExternSymbol>,
     /// A pointer to the control flow graph.
     pub graph: &'a Graph<'a>,
     /// A pointer to the results of the pointer inference analysis.
@@ -43,6 +51,7 @@ impl<'a> Context<'a> {
         cwe_warning_collector: crossbeam_channel::Sender<WarningContext>,
         log_collector: crossbeam_channel::Sender<LogMessage>,
         deallocation_symbols: BTreeSet<String>,
+        reallocation_symbols: BTreeSet<String>,
     ) -> Context<'a>
     where
         'a: 'b,
@@ -57,9 +66,28 @@ impl<'a> Context<'a> {
             } else {
                 Vec::new()
             };
+        let extern_symbols = &analysis_results.project.program.term.extern_symbols;
+        let reallocation_calls = analysis_results
+            .project
+            .program
+            .term
+            .subs
+            .values()
+            .flat_map(|sub| sub.term.blocks.iter())
+            .flat_map(|blk| blk.term.jmps.iter())
+            .filter_map(|jmp| match &jmp.term {
+                Jmp::Call { target, .. } => extern_symbols
+                    .get(target)
+                    .filter(|symbol| reallocation_symbols.contains(&symbol.name))
+                    .map(|symbol| (jmp.tid.clone(), symbol)),
+                _ => None,
+            })
+            .collect();
         Context {
             project: analysis_results.project,
             deallocation_symbols,
+            reallocation_symbols,
+            reallocation_calls,
             graph: analysis_results.control_flow_graph,
             pointer_inference: analysis_results.pointer_inference.unwrap(),
             function_signatures: analysis_results.function_signatures.unwrap(),
@@ -168,6 +196,84 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Get the calls to `realloc`-like functions that returned `NULL` if the branch
+    /// given by `condition` and `is_true` is taken, together with the values of their pointer parameters.
+    ///
+    /// Only conditions that compare the return value of the call with zero are recognized.
+    /// If the condition is a variable, then the last assignment to it in `block` is used as the condition.
+    fn get_failed_reallocations(
+        &self,
+        condition: &Expression,
+        block: &Term<Blk>,
+        is_true: bool,
+    ) -> Vec<(Tid, Data)> {
+        let condition = match condition {
+            Expression::Var(var) => block
+                .term
+                .defs
+                .iter()
+                .rev()
+                .find_map(|def| match &def.term {
+                    Def::Assign {
+                        var: assigned_var,
+                        value,
+                    } if assigned_var == var => Some(Some(value)),
+                    Def::Load {
+                        var: assigned_var, ..
+                    } if assigned_var == var => Some(None),
+                    _ => None,
+                })
+                .flatten()
+                .unwrap_or(condition),
+            _ => condition,
+        };
+        let Expression::BinOp { op, lhs, rhs } = condition else {
+            return Vec::new();
+        };
+        let is_null_branch = match op {
+            BinOpType::IntEqual => is_true,
+            BinOpType::IntNotEqual => !is_true,
+            _ => false,
+        };
+        if !is_null_branch {
+            return Vec::new();
+        }
+        let Some(pi_state) = block
+            .term
+            .jmps
+            .first()
+            .and_then(|jmp| self.pointer_inference.get_state_at_jmp_tid(&jmp.tid))
+        else {
+            return Vec::new();
+        };
+        let is_null = |value: &Data| {
+            value
+                .get_if_absolute_value()
+                .and_then(|value| value.try_to_bitvec().ok())
+                .is_some_and(|value| value.is_zero())
+        };
+        let (lhs, rhs) = (pi_state.eval(lhs), pi_state.eval(rhs));
+        let pointer = if is_null(&rhs) {
+            lhs
+        } else if is_null(&lhs) {
+            rhs
+        } else {
+            return Vec::new();
+        };
+
+        pointer
+            .get_relative_values()
+            .keys()
+            .filter_map(|id| {
+                let realloc_symbol = self.reallocation_calls.get(id.get_tid())?;
+                let old_pointer = self
+                    .pointer_inference
+                    .eval_parameter_arg_at_call(id.get_tid(), realloc_symbol.parameters.first()?)?;
+                Some((id.get_tid().clone(), old_pointer))
+            })
+            .collect()
+    }
+
     /// Generate a CWE warning and send it to the warning collector channel.
     fn generate_cwe_warning(
         &self,
@@ -185,6 +291,16 @@ impl<'a> Context<'a> {
             symbols: Vec::new(),
             other: Vec::new(),
             description,
+            related: Vec::new(),
+            source_location: None,
+            demangled_symbols: BTreeMap::new(),
+            mitigations: BTreeSet::new(),
+            severity: Severity::Normal,
+            reproduction_hints: Vec::new(),
+            confirmed: false,
+            explanation: None,
+            function_hash: None,
+            third_party_library: None,
         };
         self.cwe_warning_collector
             .send(WarningContext::new(
@@ -318,7 +434,10 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             _ => None,
         } {
             match extern_symbol.name.as_str() {
-                dealloc_sym if self.deallocation_symbols.contains(dealloc_sym) => {
+                dealloc_sym
+                    if self.deallocation_symbols.contains(dealloc_sym)
+                        || self.reallocation_symbols.contains(dealloc_sym) =>
+                {
                     self.handle_call_to_free(&mut state, &call.tid, extern_symbol)
                 }
                 extern_symbol_name => {
@@ -360,14 +479,22 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         Some(state)
     }
 
-    /// Just returns the unmodified state
+    /// Restore objects freed by calls to `realloc`-like functions
+    /// on branches where the return value of the call is known to be `NULL`.
+    /// In all other cases the state is returned unmodified.
     fn specialize_conditional(
         &self,
         state: &State,
-        _condition: &Expression,
-        _block_before_condition: &Term<Blk>,
-        _is_true: bool,
+        condition: &Expression,
+        block_before_condition: &Term<Blk>,
+        is_true: bool,
     ) -> Option<State> {
-        Some(state.clone())
+        let mut state = state.clone();
+        for (realloc_tid, old_pointer) in
+            self.get_failed_reallocations(condition, block_before_condition, is_true)
+        {
+            state.revert_failed_reallocation(&realloc_tid, &old_pointer);
+        }
+        Some(state)
     }
 }
@@ -25,9 +25,13 @@
 //! - The `deallocation_symbols` are the names of extern functions that
 //!   deallocate memory. The check always assumes that the first parameter of
 //!   such a function is the memory object to be freed. The check also assumes
-//!   that memory is always freed by such a call, which can lead to false
-//!   positive warnings for functions like `realloc`, where the memory object
-//!   may not be freed by the call.
+//!   that memory is always freed by such a call. Calls with a `NULL` parameter
+//!   do not free any memory object.
+//! - The `reallocation_symbols` are the names of extern functions like
+//!   `realloc` that may move a memory object. The first parameter of such a
+//!   function is marked as freed by the call. On branches where the return
+//!   value of the call is checked to be `NULL` the original memory object is
+//!   restored, since it is not freed if reallocation fails.
 //! - The `always_include_full_path_to_free_site` flag controls the amount of
 //!   context information printed in the CWE warnings. If set to `true`, then
 //!   the warning contains the full path in the callgraph from the root function
@@ -45,10 +49,10 @@
 //!   can target may lead to false positive CWE warnings in this check.
 //! - For extern functions that may or may not release memory, the check will
 //!   produce false positives if the original pointer is used after calling the
-//!   function. For example, `realloc` may return NULL, in which case it will
-//!   not free memory and the original pointer remains valid. But the check will
-//!   flag any access to the original pointer as a potential CWE, regardless of
-//!   the return value of `realloc`.
+//!   function. For `realloc`-like functions a failed reallocation is only
+//!   recognized if the return value is directly compared to `NULL` in the same
+//!   function. If `realloc` returns the same pointer that it was given,
+//!   accesses through the original pointer are still flagged.
 //!
 //! ## False Negatives
 //!
@@ -81,11 +85,17 @@ cwe_module!(
     "CWE416",
     "0.3",
     check_cwe,
+    requires: [FunctionSignatures, PointerInference],
     config:
         /// The names of symbols that free memory (e.g. the "free" function of
         /// C). The analysis always assumes that the memory object to be freed
         /// is the first parameter of the function.
         deallocation_symbols: Vec<String>,
+        /// The names of symbols that reallocate memory (e.g. the "realloc"
+        /// function of C). The analysis assumes that the memory object to be
+        /// reallocated is the first parameter of the function and that it is
+        /// only freed if the function does not return `NULL`.
+        reallocation_symbols: Vec<String>,
         /// If this flag is set to `true`, then always include the full path to
         /// the actual `free`-site in the callgraph in the CWE warning context
         /// information.
@@ -110,6 +120,7 @@ pub fn check_cwe(
 ) -> WithLogs<Vec<CweWarning>> {
     let config: Config = serde_json::from_value(config_json.clone()).unwrap();
     let deallocation_symbols = config.deallocation_symbols.iter().cloned().collect();
+    let reallocation_symbols = config.reallocation_symbols.iter().cloned().collect();
     let (cwe_warning_sender, cwe_warning_receiver) = crossbeam_channel::unbounded();
     let (log_sender, log_receiver) = crossbeam_channel::unbounded();
     let context = Context::new(
@@ -117,6 +128,7 @@ pub fn check_cwe(
         cwe_warning_sender,
         log_sender,
         deallocation_symbols,
+        reallocation_symbols,
     );
 
     let mut fixpoint_computation =
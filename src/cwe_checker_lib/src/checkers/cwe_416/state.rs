@@ -185,6 +185,27 @@ impl State {
         }
     }
 
+    /// Revert the effects of a call to a `realloc`-like function that returned `NULL`.
+    ///
+    /// If reallocation fails, then the original memory object is not freed.
+    /// Thus all objects that `old_pointer` may point to and that were marked as freed
+    /// by the call with TID `realloc_tid` are removed from the list of dangling objects.
+    pub fn revert_failed_reallocation(&mut self, realloc_tid: &Tid, old_pointer: &Data) {
+        let free_id_path = [realloc_tid.clone()];
+        for id in old_pointer.get_relative_values().keys() {
+            for dangling_objects in [
+                &mut self.dangling_objects,
+                &mut self.dangling_objects_generated_and_freed_in_same_call,
+            ] {
+                if let Some(ObjectState::Dangling(path)) = dangling_objects.get(id) {
+                    if path[..] == free_id_path {
+                        dangling_objects.remove(id);
+                    }
+                }
+            }
+        }
+    }
+
     /// Add objects that were freed in the callee of a function call to the list of dangling pointers of `self`.
     /// Note that this function does not check for double frees.
     ///
@@ -401,6 +422,32 @@ pub mod tests {
             .is_some());
     }
 
+    #[test]
+    fn test_revert_failed_reallocation() {
+        let mut state = State::new(Tid::new("current_fn"));
+        let param = Data::from_target(
+            AbstractIdentifier::mock("obj_id", "RAX", 8),
+            bitvec!("0:8").into(),
+        );
+        let pi_state = PiState::new(&variable!("RSP:8"), Tid::new("call"), BTreeSet::new());
+        assert!(state
+            .handle_param_of_free_call(&Tid::new("free_call"), &param, &pi_state)
+            .is_none());
+        // Objects freed by other calls are not restored.
+        state.revert_failed_reallocation(&Tid::new("realloc_call"), &param);
+        assert!(state
+            .dangling_objects
+            .contains_key(&AbstractIdentifier::mock("obj_id", "RAX", 8)));
+
+        let mut state = State::new(Tid::new("current_fn"));
+        assert!(state
+            .handle_param_of_free_call(&Tid::new("realloc_call"), &param, &pi_state)
+            .is_none());
+        state.revert_failed_reallocation(&Tid::new("realloc_call"), &param);
+        assert!(state.dangling_objects.is_empty());
+        assert!(state.check_address_for_use_after_free(&param).is_none());
+    }
+
     #[test]
     fn test_collect_freed_objects_from_called_function() {
         let mut state = State::new(Tid::new("current_fn"));
@@ -0,0 +1,237 @@
+//! This module implements a check for CWE-468: Incorrect Pointer Scaling.
+//!
+//! In C pointer arithmetic on typed pointers is implicitly scaled by the size
+//! of the pointed-to type. Adding a byte offset to a typed pointer or
+//! multiplying an index with `sizeof` a second time results in accesses to the
+//! wrong array elements, often outside of the array.
+//!
+//! See <https://cwe.mitre.org/data/definitions/468.html> for a detailed
+//! description.
+//!
+//! ## How the check works
+//!
+//! The element size of a heap array is known if the array was allocated by a
+//! function like `calloc` that takes the element size as a parameter. For each
+//! memory access to such an array with the size of an element, the offsets of
+//! the access computed by the Pointer Inference analysis are converted into a
+//! congruence, i.e. into their known alignment. A warning is generated if the
+//! offsets are not aligned to the element size (missing scaling) or if they
+//! are aligned to the square of the element size (duplicated scaling).
+//!
+//! ### Symbols configurable in config.json
+//!
+//! The `array_allocation_symbols` map the allocation functions to the index of
+//! their element size parameter.
+//!
+//! ## False Positives
+//!
+//! - Loops that only access every n-th element of an array, where n equals
+//!   the element size, are reported as duplicated scaling.
+//!
+//! ## False Negatives
+//!
+//! - The element sizes of stack and global arrays and of arrays allocated by
+//!   `malloc` are unknown, so accesses to them are not checked.
+//! - Only element sizes that are powers of two are checked.
+//! - Accesses with a constant offset or with unknown offsets are not checked.
+use super::prelude::*;
+
+use crate::abstract_domain::{CongruenceDomain, IntervalDomain, TryToBitvec, TryToInterval};
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+
+use std::collections::{BTreeMap, HashMap};
+
+cwe_module!(
+    "CWE468",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// Functions that allocate arrays, mapped to the index of their element
+        /// size parameter.
+        array_allocation_symbols: BTreeMap<String, usize>,
+);
+
+/// The kind of scaling error indicated by the offsets of an array access.
+#[derive(Debug, PartialEq, Eq)]
+enum ScalingError {
+    /// The offsets are not aligned to the element size.
+    Missing,
+    /// The offsets are aligned to the square of the element size.
+    Duplicated,
+}
+
+/// Check the offsets of an access to an array with the given element size for
+/// scaling errors.
+///
+/// Only element sizes that are powers of two and offsets that are neither
+/// constant nor unknown are checked.
+fn check_scaling(offset: &IntervalDomain, element_size: u64) -> Option<ScalingError> {
+    if element_size < 2 || !element_size.is_power_of_two() {
+        return None;
+    }
+    let interval = offset.try_to_interval().ok()?;
+    if interval.start == interval.end {
+        return None;
+    }
+    let congruence = CongruenceDomain::from(&interval);
+    if congruence.modulus_bits() < element_size.trailing_zeros() {
+        Some(ScalingError::Missing)
+    } else if congruence.is_aligned_to(element_size * element_size) {
+        Some(ScalingError::Duplicated)
+    } else {
+        None
+    }
+}
+
+/// Collect the element sizes of all arrays allocated by calls to the configured
+/// array allocation functions, indexed by the TID of the allocation call.
+fn get_array_element_sizes(
+    analysis_results: &AnalysisResults,
+    config: &Config,
+) -> HashMap<Tid, u64> {
+    let project = analysis_results.project;
+    let pi_result = analysis_results.pointer_inference.unwrap();
+    let symbols: Vec<String> = config.array_allocation_symbols.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &symbols);
+
+    let mut element_sizes = HashMap::new();
+    for sub in project.program.functions() {
+        for (_, jmp, symbol) in get_callsites(sub, &symbol_map) {
+            let Some(element_size) = symbol
+                .parameters
+                .get(config.array_allocation_symbols[&symbol.name])
+                .and_then(|param| pi_result.eval_parameter_arg_at_call(&jmp.tid, param))
+                .and_then(|size| size.get_if_absolute_value()?.try_to_bitvec().ok())
+                .and_then(|size| size.try_to_u64().ok())
+            else {
+                continue;
+            };
+            element_sizes.insert(jmp.tid.clone(), element_size);
+        }
+    }
+    element_sizes
+}
+
+/// Generate the CWE warning for an array access with a scaling error.
+fn generate_cwe_warning(
+    def: &Term<Def>,
+    allocation_tid: &Tid,
+    element_size: u64,
+    offset: &IntervalDomain,
+    error: ScalingError,
+) -> CweWarning {
+    let reason = match error {
+        ScalingError::Missing => "are not aligned to the element size",
+        ScalingError::Duplicated => "are scaled by the element size twice",
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Incorrect Pointer Scaling) The offsets {} of the access at {} to the array allocated at {} with element size {} {}",
+            offset,
+            def.tid.address(),
+            allocation_tid.address(),
+            element_size,
+            reason
+        ),
+    )
+    .tids(vec![format!("{}", def.tid), format!("{allocation_tid}")])
+    .addresses(vec![
+        def.tid.address().to_string(),
+        allocation_tid.address().to_string(),
+    ])
+}
+
+/// Execute the CWE check.
+///
+/// For each load or store with the size of an array element we check the
+/// alignment of its offsets into arrays with known element size.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let project = analysis_results.project;
+    let pi_result = analysis_results.pointer_inference.unwrap();
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    let element_sizes = get_array_element_sizes(analysis_results, &config);
+    if element_sizes.is_empty() {
+        return WithLogs::wrap(cwe_warnings);
+    }
+    for sub in project.program.functions() {
+        for blk in &sub.term.blocks {
+            for def in &blk.term.defs {
+                let access_size = match &def.term {
+                    Def::Load { var, .. } => var.size,
+                    Def::Store { value, .. } => value.bytesize(),
+                    Def::Assign { .. } => continue,
+                };
+                let Some(address) = pi_result.eval_address_at_def(&def.tid) else {
+                    continue;
+                };
+                for (id, offset) in address.get_relative_values() {
+                    let Some(element_size) = element_sizes.get(id.get_tid()) else {
+                        continue;
+                    };
+                    if u64::from(access_size) != *element_size {
+                        continue;
+                    }
+                    if let Some(error) = check_scaling(offset, *element_size) {
+                        cwe_warnings.push(generate_cwe_warning(
+                            def,
+                            id.get_tid(),
+                            *element_size,
+                            offset,
+                            error,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    cwe_warnings.deduplicate_first_address()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::Interval;
+
+    #[test]
+    fn scaling_errors() {
+        let offset = |start: i64, end: i64, stride: u64| {
+            IntervalDomain::from(Interval::new(
+                Bitvector::from_i64(start),
+                Bitvector::from_i64(end),
+                stride,
+            ))
+        };
+
+        // Correctly scaled index into an array of 4-byte elements.
+        assert_eq!(check_scaling(&offset(0, 36, 4), 4), None);
+        // Correctly scaled accesses to the second field of 8-byte elements.
+        assert_eq!(check_scaling(&offset(4, 76, 8), 8), None);
+        // Unscaled byte offsets.
+        assert_eq!(
+            check_scaling(&offset(0, 9, 1), 4),
+            Some(ScalingError::Missing)
+        );
+        // Index multiplied by the element size twice.
+        assert_eq!(
+            check_scaling(&offset(0, 144, 16), 4),
+            Some(ScalingError::Duplicated)
+        );
+        // Constant offsets, byte arrays and element sizes that are no powers of two are not checked.
+        assert_eq!(check_scaling(&offset(2, 2, 0), 4), None);
+        assert_eq!(check_scaling(&offset(0, 9, 1), 1), None);
+        assert_eq!(check_scaling(&offset(0, 9, 1), 12), None);
+    }
+}
@@ -11,6 +11,13 @@
 //! Using dataflow analysis we search for an execution path where a memory access using the return value of
 //! a symbol happens before the return value is checked through a conditional jump instruction.
 //!
+//! The analysis tracks the [nullability](crate::abstract_domain::NullabilityDomain)
+//! of the return value. Conditional jumps that compare the return value with zero are recognized
+//! as NULL pointer checks. On the branch where the return value is NULL the analysis continues
+//! and reports memory accesses through the return value, since they are guaranteed NULL pointer
+//! dereferences. All other checks of the return value end the analysis on the checked branches.
+//! Symbols that never return NULL, like `operator.new` or `xmalloc`, are not analyzed.
+//!
 //! ### Symbols configurable in config.json
 //!
 //! The symbols are the functions whose return values are assumed to be potential
@@ -31,9 +38,10 @@
 //! ## False Negatives
 //!
 //! - We do not check whether an access to a potential NULL pointer happens
-//!   regardless of a prior check.
-//! - We do not check whether the conditional jump instruction checks
-//!   specifically for the return value being NULL or something else
+//!   regardless of a prior check, unless the access happens on the branch
+//!   where the return value is known to be NULL.
+//! - Checks that do not compare the return value with zero, e.g. checks of
+//!   values derived from it, are assumed to be correct NULL pointer checks.
 //! - For functions with more than one return value we do not distinguish between
 //!   the return values.
 use super::prelude::*;
@@ -43,6 +51,7 @@ use crate::analysis::forward_interprocedural_fixpoint::Context as _;
 use crate::analysis::graph::{Edge, Node};
 use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
 use crate::analysis::taint::state::State as TaState;
+use crate::analysis::taint::{get_return_value_nullability, TaintSource};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::symbol_utils;
@@ -57,6 +66,7 @@ cwe_module!(
     "CWE476",
     "0.3",
     check_cwe,
+    requires: [PointerInference],
     config:
         /// The names of symbols for which the analysis should check whether the
         /// return values are checked for being a NULL pointer by the analysed
@@ -98,15 +108,18 @@ pub fn check_cwe(
         let Node::BlkStart(.., current_sub) = general_context.get_graph()[return_node] else {
             panic!("Malformed control flow graph.");
         };
+        let nullability = get_return_value_nullability(symbol);
+        if !nullability.may_be_null() {
+            continue;
+        }
 
         let mut context = general_context.clone();
         context.set_taint_source(jmp, current_sub);
 
         let mut computation = create_computation(context, None);
-        computation.set_node_value(
-            return_node,
-            NodeValue::Value(TaState::new_return(symbol, pi_result, return_node)),
-        );
+        let mut state = TaState::new_return(symbol, TaintSource::Custom, pi_result, return_node);
+        state.set_nullability(nullability);
+        computation.set_node_value(return_node, NodeValue::Value(state));
         computation.compute_with_max_steps(100);
     }
 
@@ -8,6 +8,7 @@
 //! [taint analysis module]: crate::analysis::taint
 
 use super::CWE_MODULE;
+use crate::abstract_domain::NullabilityDomain;
 use crate::analysis::graph::{Graph as Cfg, HasCfg, Node as CfgNode};
 use crate::analysis::pointer_inference::{
     Data as PiData, PointerInference as PointerInferenceComputation,
@@ -77,19 +78,24 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
     /// generate a CWE warning and return `None` to suppress the generation of
     /// further warnings. Else just remove all taint contained in
     /// non-callee-saved registers.
+    ///
+    /// Passing a value that is known to be NULL to a function is not reported,
+    /// since functions are usually expected to check their parameters.
     fn update_call_generic(
         &self,
         state: &TaState,
         call_tid: &Tid,
         calling_convention_hint: &Option<String>,
     ) -> Option<TaState> {
-        if state.check_generic_function_params_for_taint::<true>(
-            self.vsa_result(),
-            call_tid,
-            self.project,
-            calling_convention_hint,
-        ) {
-            self.generate_cwe_warning(call_tid);
+        if !is_known_null(state)
+            && state.check_generic_function_params_for_taint::<true>(
+                self.vsa_result(),
+                call_tid,
+                self.project,
+                calling_convention_hint,
+            )
+        {
+            self.generate_cwe_warning(call_tid, false);
 
             // Stop taint propagation to suppress futher warnings.
             None
@@ -106,7 +112,8 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
             Some(new_state)
         }
     }
-    /// Generate a CWE warning if taint may be contained in the function parameters.
+    /// Generate a CWE warning if taint may be contained in the function parameters
+    /// and is not known to be NULL.
     ///
     /// Always returns `None` so that the analysis stays intraprocedural.
     fn update_call(
@@ -116,13 +123,15 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
         _target: &CfgNode,
         calling_convention: &Option<String>,
     ) -> Option<TaState> {
-        if state.check_generic_function_params_for_taint::<true>(
-            self.vsa_result(),
-            &call.tid,
-            self.project,
-            calling_convention,
-        ) {
-            self.generate_cwe_warning(&call.tid);
+        if !is_known_null(state)
+            && state.check_generic_function_params_for_taint::<true>(
+                self.vsa_result(),
+                &call.tid,
+                self.project,
+                calling_convention,
+            )
+        {
+            self.generate_cwe_warning(&call.tid, false);
         }
 
         None
@@ -130,9 +139,10 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
 
     /// Generate a CWE warning if taint may be contained in the function parameters.
     ///
-    /// If taint may be contained in the function parameters, generate a CWE
-    /// warning and return `None` to the suppress the generation of
-    /// further warnings. Else remove taint from non-callee-saved registers.
+    /// If taint may be contained in the function parameters and is not known to
+    /// be NULL, generate a CWE warning and return `None` to the suppress the
+    /// generation of further warnings. Else remove taint from non-callee-saved
+    /// registers.
     fn update_call_stub(&self, state: &TaState, call: &Term<Jmp>) -> Option<TaState> {
         if state.is_empty() {
             return None;
@@ -145,12 +155,14 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
                     .get(target)
                     .expect("Extern symbol not found.");
 
-                if state.check_extern_parameters_for_taint::<true>(
-                    self.vsa_result(),
-                    extern_symbol,
-                    &call.tid,
-                ) {
-                    self.generate_cwe_warning(&call.tid);
+                if !is_known_null(state)
+                    && state.check_extern_parameters_for_taint::<true>(
+                        self.vsa_result(),
+                        extern_symbol,
+                        &call.tid,
+                    )
+                {
+                    self.generate_cwe_warning(&call.tid, false);
 
                     None
                 } else {
@@ -172,11 +184,9 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
     ///
     /// We assume that any check that depends on tainted values is a NULL
     /// pointer check of the return value, and that the program handles both
-    /// outcomes correctly.
-    ///
-    /// A jump can depend on a tainted condition in two ways, either it is
-    /// executed because the condition evaluated to `true`, or because it
-    /// evaluated to `false`, both cases must be handled here.
+    /// outcomes correctly. Only if the check is known to have found the return
+    /// value to be NULL, the propagation continues to find accesses through
+    /// the NULL pointer.
     fn update_jump(
         &self,
         state: &TaState,
@@ -192,20 +202,10 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
         // If this control flow transfer depends on a condition involving
         // a tainted value then we do not propagate any taint information to
         // the destination.
-        match (&jump.term, untaken_conditional) {
-            // Directly depends on a tainted value.
-            (Jmp::CBranch { condition, .. }, _) if state.eval(condition).is_tainted() => None,
-            // Branch is only taken because a condition based on a tainted value
-            // evaluated to false.
-            (
-                _,
-                Some(Term {
-                    tid: _,
-                    term: Jmp::CBranch { condition, .. },
-                }),
-            ) if state.eval(condition).is_tainted() => None,
-            // Does not depend on tainted values.
-            _ => Some(state.clone()),
+        if state.jump_depends_on_taint(jump, untaken_conditional) && !is_known_null(state) {
+            None
+        } else {
+            Some(state.clone())
         }
     }
 
@@ -213,7 +213,8 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
     ///
     /// We assume that returning a tainted value means that the function may
     /// return a NULL pointer. This always generates a warning, even if this may
-    /// be expected by the caller.
+    /// be expected by the caller. Only returning a value that is known to be
+    /// NULL is not reported.
     fn update_return_callee(
         &self,
         state: &TaState,
@@ -221,13 +222,15 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
         return_term: &Term<Jmp>,
         calling_convention: &Option<String>,
     ) -> Option<TaState> {
-        if state.check_return_values_for_taint::<true>(
-            self.vsa_result(),
-            &return_term.tid,
-            self.project,
-            calling_convention,
-        ) {
-            self.generate_cwe_warning(&return_term.tid);
+        if !is_known_null(state)
+            && state.check_return_values_for_taint::<true>(
+                self.vsa_result(),
+                &return_term.tid,
+                self.project,
+                calling_convention,
+            )
+        {
+            self.generate_cwe_warning(&return_term.tid, false);
         }
 
         // Keep analysis intraprocedural but do not force propagation to be
@@ -252,11 +255,11 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
 
         match &def.term {
             Def::Load { var: _, address } if old_state.eval(address).is_tainted() => {
-                self.generate_cwe_warning(&def.tid);
+                self.generate_cwe_warning(&def.tid, is_known_null(old_state));
                 None
             }
             Def::Store { address, .. } if old_state.eval(address).is_tainted() => {
-                self.generate_cwe_warning(&def.tid);
+                self.generate_cwe_warning(&def.tid, is_known_null(old_state));
                 None
             }
             _ => Some(new_state),
@@ -264,6 +267,12 @@ impl<'a> TaintAnalysis<'a> for Context<'a> {
     }
 }
 
+/// Check whether the tainted values are known to be NULL,
+/// i.e. whether the state belongs to the NULL branch of a NULL pointer check.
+fn is_known_null(state: &TaState) -> bool {
+    state.get_nullability() == NullabilityDomain::Null
+}
+
 impl<'a> Context<'a> {
     /// Create a new context object.
     ///
@@ -311,14 +320,28 @@ impl<'a> Context<'a> {
     }
 
     /// Generate a CWE warning for the taint source of the context object.
-    fn generate_cwe_warning(&self, taint_access_location: &Tid) {
+    ///
+    /// If `is_known_null` is true, the access happens after a check found the
+    /// return value to be NULL.
+    fn generate_cwe_warning(&self, taint_access_location: &Tid, is_known_null: bool) {
         let taint_source = self.taint_source.unwrap();
         let taint_source_name = self.taint_source_name.clone().unwrap();
-        let cwe_warning = CweWarning::new(CWE_MODULE.name, CWE_MODULE.version,
+        let description = if is_known_null {
+            format!("(NULL Pointer Dereference) The return value at {} ({}) is accessed at {} although it was checked to be NULL.",
+            taint_source.tid.address(), taint_source_name, taint_access_location.address())
+        } else {
             format!("(NULL Pointer Dereference) There is no check if the return value is NULL at {} ({}).",
-            taint_source.tid.address(), taint_source_name))
-            .addresses(vec![taint_source.tid.address().to_string(), taint_access_location.address().to_string()])
-            .tids(vec![format!("{}", taint_source.tid), format!("{taint_access_location}")])
+            taint_source.tid.address(), taint_source_name)
+        };
+        let cwe_warning = CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+            .addresses(vec![
+                taint_source.tid.address().to_string(),
+                taint_access_location.address().to_string(),
+            ])
+            .tids(vec![
+                format!("{}", taint_source.tid),
+                format!("{taint_access_location}"),
+            ])
             .symbols(vec![taint_source_name]);
         let _ = self.cwe_collector.send(cwe_warning);
     }
@@ -329,7 +352,7 @@ impl<'a> Context<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analysis::taint::Taint as TaTaint;
+    use crate::analysis::taint::{Taint as TaTaint, TaintSource};
     use crate::{expr, variable};
 
     impl<'a> Context<'a> {
@@ -371,7 +394,7 @@ mod tests {
 
         // Test that taint is not propagated through calls that receive tainted
         // arguments.
-        state.set_register_taint(&variable!("RDX:8"), TaTaint::Tainted(ByteSize::new(8)));
+        state.set_register_taint(&variable!("RDX:8"), TaTaint::new_tainted(ByteSize::new(8), TaintSource::Custom));
         assert!(context
             .update_call_generic(&state, &Tid::new("call_tid"), &None)
             .is_none());
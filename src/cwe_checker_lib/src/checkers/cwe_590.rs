@@ -0,0 +1,228 @@
+//! This module implements a check for CWE-590: Free of Memory not on the Heap.
+//!
+//! Calling `free` on a pointer that was not returned by a heap allocation
+//! function corrupts the heap management data structures of the allocator.
+//! This usually results in a crash, but can also be used by an attacker to
+//! overwrite arbitrary memory.
+//!
+//! See <https://cwe.mitre.org/data/definitions/590.html> for a detailed
+//! description.
+//!
+//! ## How the check works
+//!
+//! Using the results of the [Pointer Inference
+//! analysis](`crate::analysis::pointer_inference`) the first parameter of each
+//! call to one of the configured `deallocation_symbols` is evaluated. A CWE
+//! warning is generated if the parameter may point to
+//!
+//! - the stack frame of the calling function,
+//! - global memory, either as a pointer relative to the global memory object
+//!   or as a constant address inside the global memory of the binary, or
+//! - the inside of a heap object, i.e. the offset into the object cannot be
+//!   zero.
+//!
+//! For each offending pointer target the warning contains its provenance
+//! chain: the site where the memory object was created followed by the call
+//! sites through which the pointer was returned to the caller of `free`.
+//!
+//! ## False Positives
+//!
+//! - Since the Pointer Inference analysis is not path-sensitive, a pointer
+//!   may be assumed to target memory objects that it cannot target on any
+//!   feasible path.
+//! - Custom allocators that hand out pointers into global or stack buffers and
+//!   that use `free` as the name of their deallocation function will be
+//!   flagged.
+//!
+//! ## False Negatives
+//!
+//! - Pointers to memory objects that are parameters of the calling function
+//!   are not flagged, since their origin is unknown inside the function.
+//!   In particular, stack or global memory freed in a callee of the function
+//!   owning the memory is missed.
+//! - Heap pointers where the offset into the object is unknown or may be zero
+//!   are not flagged.
+use super::prelude::*;
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec, TryToInterval};
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+
+cwe_module!(
+    "CWE590",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// The names of symbols that free heap memory (e.g. the "free"
+        /// function of C). The check assumes that the memory object to be
+        /// freed is the first parameter of the function.
+        deallocation_symbols: Vec<String>,
+);
+
+/// Describe where the memory object with the given ID was created and through
+/// which call sites the pointer to it was returned to the current function.
+fn describe_provenance(id: &AbstractIdentifier) -> String {
+    let mut provenance = format!("created at {}", id.get_tid());
+    for call_tid in id.get_path_hints().iter().rev() {
+        provenance += &format!(", returned through the call at {call_tid}");
+    }
+    provenance
+}
+
+/// Return a description for each pointer target of `value` that is not the
+/// start of a heap object.
+///
+/// The `state` is the Pointer Inference state at the call site. It is used to
+/// determine the object type of the pointer targets.
+fn get_non_heap_targets(
+    state: &State,
+    value: &Data,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Vec<String> {
+    let mut targets = Vec::new();
+    for (id, offset) in value.get_relative_values() {
+        match state.memory.get_object_type(id) {
+            Ok(Some(ObjectType::Stack)) => targets.push(format!(
+                "Freed pointer may point to the stack frame {id}, {}.",
+                describe_provenance(id)
+            )),
+            Ok(Some(ObjectType::GlobalMem)) => targets.push(format!(
+                "Freed pointer may point to global memory {id} at offset {offset}."
+            )),
+            Ok(Some(ObjectType::Heap)) => {
+                let Ok(interval) = offset.try_to_interval() else {
+                    continue;
+                };
+                if !interval.contains(&Bitvector::zero(interval.bytesize().into())) {
+                    targets.push(format!(
+                        "Freed pointer may point into the middle of the heap object {id} at offset {offset}, {}.",
+                        describe_provenance(id)
+                    ))
+                }
+            }
+            Ok(None) | Err(()) => (),
+        }
+    }
+    if let Some(address) = value
+        .get_absolute_value()
+        .and_then(|absolute| absolute.try_to_bitvec().ok())
+    {
+        if runtime_memory_image.is_global_memory_address(&address) {
+            targets.push(format!(
+                "Freed pointer may point to the global address {:#x}.",
+                address.try_to_u64().unwrap_or_default()
+            ));
+        }
+    }
+    targets
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    jmp: &Term<Jmp>,
+    extern_symbol: &ExternSymbol,
+    non_heap_targets: Vec<String>,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Free of Memory not on the Heap) Call to {} at {} may free memory that was not allocated on the heap",
+            extern_symbol.name,
+            jmp.tid.address()
+        ),
+    )
+    .tids(vec![format!("{}", jmp.tid)])
+    .addresses(vec![jmp.tid.address().to_string()])
+    .symbols(vec![extern_symbol.name.clone()])
+    .other(vec![non_heap_targets])
+}
+
+/// Execute the CWE check.
+///
+/// For each call to one of the configured deallocation symbols we check
+/// whether the freed pointer may point to memory that is not the start of a
+/// heap object.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let project = analysis_results.project;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    let symbol_map = get_symbol_map(project, &config.deallocation_symbols);
+    for sub in project.program.functions() {
+        for (_, jmp, symbol) in get_callsites(sub, &symbol_map) {
+            let (Some(state), Some(parameter)) = (
+                pointer_inference_results.get_state_at_jmp_tid(&jmp.tid),
+                symbol.parameters.first(),
+            ) else {
+                continue;
+            };
+            let Some(value) =
+                pointer_inference_results.eval_parameter_arg_at_call(&jmp.tid, parameter)
+            else {
+                continue;
+            };
+            let non_heap_targets =
+                get_non_heap_targets(state, &value, &project.runtime_memory_image);
+            if !non_heap_targets.is_empty() {
+                cwe_warnings.push(generate_cwe_warning(jmp, symbol, non_heap_targets));
+            }
+        }
+    }
+
+    cwe_warnings.deduplicate_first_address()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::{AbstractDomain, AbstractLocation, IntervalDomain};
+    use crate::variable;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn non_heap_targets() {
+        let heap_id = AbstractIdentifier::new(
+            Tid::new("malloc_call"),
+            AbstractLocation::Register(variable!("RAX:8")),
+        )
+        .with_path_hint(Tid::new("wrapper_call"))
+        .unwrap();
+        let mut state = State::new(&variable!("RSP:8"), Tid::new("func"), BTreeSet::new());
+        state
+            .memory
+            .add_abstract_object(heap_id.clone(), ByteSize::new(8), Some(ObjectType::Heap));
+        let memory_image = RuntimeMemoryImage::mock();
+        let offset = |value: i64| IntervalDomain::from(Bitvector::from_i64(value));
+
+        // Pointers to the start of a heap object are fine.
+        let value = Data::from_target(heap_id.clone(), offset(0));
+        assert!(get_non_heap_targets(&state, &value, &memory_image).is_empty());
+        // Pointers into the middle of a heap object are flagged together with their provenance.
+        let value = Data::from_target(heap_id.clone(), offset(8));
+        assert_eq!(
+            get_non_heap_targets(&state, &value, &memory_image),
+            vec![format!("Freed pointer may point into the middle of the heap object {heap_id} at offset {}, created at malloc_call, returned through the call at wrapper_call.", offset(8))]
+        );
+        // Stack and global pointers are flagged.
+        let value = Data::from_target(state.stack_id.clone(), offset(-16)).merge(
+            &Data::from_target(state.get_global_mem_id(), offset(0x3000)),
+        );
+        assert_eq!(get_non_heap_targets(&state, &value, &memory_image).len(), 2);
+        // Constant addresses are flagged if they point to global memory.
+        let value = Data::from(Bitvector::from_u64(0x3002));
+        assert_eq!(get_non_heap_targets(&state, &value, &memory_image).len(), 1);
+        let value = Data::from(Bitvector::from_u64(0));
+        assert!(get_non_heap_targets(&state, &value, &memory_image).is_empty());
+    }
+}
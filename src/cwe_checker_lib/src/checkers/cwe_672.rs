@@ -0,0 +1,323 @@
+//! This module implements a check for CWE-672: Operation on a Resource after
+//! Expiration or Release and its variant CWE-1341: Multiple Releases of Same
+//! Resource or Handle.
+//!
+//! File descriptors and sockets are released by calls to `close`. Afterwards
+//! the descriptor number may be reused by the next `open` or `socket` call, so
+//! that reading from or writing to the stale descriptor accesses an unrelated
+//! file or connection. Closing the same descriptor twice may close such an
+//! unrelated resource.
+//!
+//! See <https://cwe.mitre.org/data/definitions/672.html> and
+//! <https://cwe.mitre.org/data/definitions/1341.html> for detailed
+//! descriptions.
+//!
+//! ## How the check works
+//!
+//! The [Pointer Inference analysis](crate::analysis::pointer_inference) does
+//! not assign an identity to the integer values returned by extern functions,
+//! so descriptors are tracked with two taint analyses instead:
+//!
+//! 1. For every call to one of the `source_symbols`, e.g. `open` or `socket`,
+//!    the returned descriptor is tainted. The taint is propagated until a call
+//!    to one of the `release_symbols`, e.g. `close`, whose first argument is
+//!    tainted. The state at this call is recorded.
+//! 2. For every recorded call to a release function, a second taint analysis
+//!    starts after the call with the recorded state. A warning is generated
+//!    - for every call to one of the `use_symbols` whose first argument is
+//!      tainted (CWE-672) and
+//!    - for every further call to a release function whose first argument is
+//!      tainted (CWE-1341).
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The `source_symbols` return a new descriptor.
+//! - The `release_symbols` release the descriptor given as their first
+//!   parameter.
+//! - The `use_symbols` operate on the descriptor given as their first
+//!   parameter.
+//!
+//! ## False Positives
+//!
+//! - The analysis is not path-sensitive, so infeasible paths, e.g. through
+//!   checks of a flag that marks the descriptor as closed, lead to false
+//!   positives.
+//! - Descriptors that are reassigned to a newly opened resource in a register
+//!   that is not tracked by the taint analysis may still be considered as
+//!   closed.
+//!
+//! ## False Negatives
+//!
+//! - Descriptors that are passed to a function of the binary are not followed
+//!   into it, so a `close` or `read` inside a helper like `cleanup(fd)` is
+//!   missed. Descriptors opened in a called function are only tracked in the
+//!   caller if they are returned or stored in memory known to the caller.
+//! - Descriptors stored in memory objects that are not known to the caller,
+//!   e.g. in structures on the heap, may lose their taint.
+//! - Descriptors returned by functions other than the configured
+//!   `source_symbols`, e.g. by `pipe`, are not tracked.
+use super::prelude::*;
+
+use crate::analysis::graph::{Edge, NodeIndex};
+use crate::analysis::taint::state::State as TaState;
+use crate::analysis::taint::TaintSource;
+use crate::intermediate_representation::{ExternSymbol, Jmp, Term};
+use crate::pipeline::AnalysisResults;
+use crate::prelude::*;
+use crate::utils::symbol_utils;
+
+use petgraph::visit::EdgeRef;
+
+use std::collections::{HashMap, HashSet};
+
+mod context;
+
+use context::*;
+
+cwe_module!(
+    "CWE672",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// External symbols that return a new file descriptor or socket.
+        source_symbols: HashSet<String>,
+        /// External symbols that release the descriptor given as their first
+        /// parameter.
+        release_symbols: HashSet<String>,
+        /// External symbols that operate on the descriptor given as their first
+        /// parameter.
+        use_symbols: HashSet<String>,
+);
+
+/// Call to an external function that returns or releases a descriptor.
+#[derive(Clone, Copy)]
+struct DescriptorCall<'a> {
+    /// Information about the function that was called.
+    symbol: &'a ExternSymbol,
+    /// CFG node where the call will return to.
+    return_node: NodeIndex,
+    /// IR instruction of the call.
+    jmp: &'a Term<Jmp>,
+}
+
+/// Searches the program for calls to the given functions.
+fn get_calls<'a>(
+    analysis_results: &'a AnalysisResults,
+    symbols: &HashSet<String>,
+) -> Vec<DescriptorCall<'a>> {
+    let symbol_map = symbol_utils::get_symbol_map_fast(analysis_results.project, symbols);
+
+    analysis_results
+        .pointer_inference
+        .expect("CWE672: BUG: No pointer inference results.")
+        .get_graph()
+        .edge_references()
+        .filter_map(|edge| {
+            let Edge::ExternCallStub(jmp) = edge.weight() else {
+                return None;
+            };
+            let Jmp::Call { target, .. } = &jmp.term else {
+                return None;
+            };
+            Some(DescriptorCall {
+                symbol: symbol_map.get(target)?,
+                return_node: edge.target(),
+                jmp,
+            })
+        })
+        .collect()
+}
+
+/// Generates the CWE warning for a descriptor of the given source that is
+/// used or released again by a call to the given symbol after it was released
+/// by the given call.
+fn generate_cwe_warning(
+    sender: &crossbeam_channel::Sender<CweWarning>,
+    source: &DescriptorCall,
+    release: &DescriptorCall,
+    location: &Tid,
+    symbol: &ExternSymbol,
+    is_double_release: bool,
+) {
+    let (name, description) = if is_double_release {
+        (
+            "CWE1341",
+            format!(
+                "(Double Close) Descriptor returned by {} ({}) is released by the call to {} at {} after it was already released at {}.",
+                source.jmp.tid.address(),
+                source.symbol.name,
+                symbol.name,
+                location.address(),
+                release.jmp.tid.address()
+            ),
+        )
+    } else {
+        (
+            CWE_MODULE.name,
+            format!(
+                "(Use of Resource after Close) Descriptor returned by {} ({}) is used by the call to {} at {} after it was released at {}.",
+                source.jmp.tid.address(),
+                source.symbol.name,
+                symbol.name,
+                location.address(),
+                release.jmp.tid.address()
+            ),
+        )
+    };
+    let cwe_warning = CweWarning::new(name, CWE_MODULE.version, description)
+        .addresses(vec![
+            location.address().to_string(),
+            release.jmp.tid.address().to_string(),
+            source.jmp.tid.address().to_string(),
+        ])
+        .tids(vec![
+            format!("{location}"),
+            format!("{}", release.jmp.tid),
+            format!("{}", source.jmp.tid),
+        ])
+        .symbols(vec![
+            source.symbol.name.clone(),
+            release.symbol.name.clone(),
+            symbol.name.clone(),
+        ]);
+    sender
+        .send(cwe_warning)
+        .expect("CWE672: failed to send CWE warning");
+}
+
+/// CWE-672: Operation on a Resource after Expiration or Release.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let config: Config =
+        serde_json::from_value(cwe_params.clone()).expect("CWE672: invalid configuration");
+    let project = analysis_results.project;
+    let pi_result = analysis_results
+        .pointer_inference
+        .expect("CWE672: BUG: No pointer inference results.");
+
+    let releases: HashMap<Tid, DescriptorCall> =
+        get_calls(analysis_results, &config.release_symbols)
+            .into_iter()
+            .map(|release| (release.jmp.tid.clone(), release))
+            .collect();
+    if releases.is_empty() {
+        return WithLogs::wrap(Vec::new());
+    }
+
+    let (release_sender, release_receiver) = crossbeam_channel::unbounded();
+    let (cwe_sender, cwe_receiver) = crossbeam_channel::unbounded();
+    for source in get_calls(analysis_results, &config.source_symbols) {
+        let mut computation = TaComputationContext::new(
+            source,
+            None,
+            project,
+            pi_result,
+            &config,
+            &release_sender,
+            &cwe_sender,
+        )
+        .into_computation(
+            source.return_node,
+            TaState::new_return(
+                source.symbol,
+                TaintSource::Custom,
+                pi_result,
+                source.return_node,
+            ),
+        );
+        computation.compute_with_max_steps(100);
+
+        // The state at a release call grows monotonically during the fixpoint
+        // computation, so the last recorded state is the most complete one.
+        let released_states: HashMap<Tid, TaState> = release_receiver.try_iter().collect();
+        for (release_tid, state) in released_states {
+            let Some(release) = releases.get(&release_tid) else {
+                continue;
+            };
+            let mut computation = TaComputationContext::new(
+                source,
+                Some(*release),
+                project,
+                pi_result,
+                &config,
+                &release_sender,
+                &cwe_sender,
+            )
+            .into_computation(release.return_node, state);
+            computation.compute_with_max_steps(100);
+        }
+    }
+    let mut cwe_warnings: Vec<CweWarning> = cwe_receiver.try_iter().collect();
+    cwe_warnings.sort();
+
+    cwe_warnings.deduplicate_first_address()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::PointerInference;
+    use crate::checkers::test_utils::{self, CallSequence};
+    use crate::intermediate_representation::*;
+
+    /// Returns a project with a function that calls the given external
+    /// functions in order and passes the return value of the first call to all
+    /// following calls.
+    fn mock_project(symbols: &[&str]) -> Project {
+        CallSequence::new(symbols).pass_return_value("RDI").build()
+    }
+
+    fn run_check(project: &Project) -> Vec<CweWarning> {
+        let pi_result = PointerInference::mock(project);
+        let config = serde_json::json!({
+            "source_symbols": ["open", "socket"],
+            "release_symbols": ["close"],
+            "use_symbols": ["read", "write"]
+        });
+
+        test_utils::run_check(project, &pi_result, check_cwe, &config)
+    }
+
+    #[test]
+    fn use_after_close() {
+        let warnings = run_check(&mock_project(&["open", "close", "read"]));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "CWE672");
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "call_read_2".to_string(),
+                "call_close_1".to_string(),
+                "call_open_0".to_string()
+            ]
+        );
+        assert_eq!(
+            warnings[0].symbols,
+            vec!["open".to_string(), "close".to_string(), "read".to_string()]
+        );
+
+        // Uses before the release are not reported.
+        assert!(run_check(&mock_project(&["socket", "write", "close"])).is_empty());
+        // Other values than descriptors are not tracked.
+        assert!(run_check(&mock_project(&["other_function", "close", "read"])).is_empty());
+    }
+
+    #[test]
+    fn double_close() {
+        let warnings = run_check(&mock_project(&["open", "close", "close"]));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "CWE1341");
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "call_close_2".to_string(),
+                "call_close_1".to_string(),
+                "call_open_0".to_string()
+            ]
+        );
+    }
+}
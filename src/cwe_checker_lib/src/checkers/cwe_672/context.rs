@@ -0,0 +1,211 @@
+//! Definition of the Taint Analysis for CWE672.
+//!
+//! Implementation of the [`TaintAnalysis`] trait for this CWE check. See the
+//! module documentation for more details on the algorithm and its limitations.
+
+use super::{Config, DescriptorCall};
+
+use crate::analysis::fixpoint;
+use crate::analysis::forward_interprocedural_fixpoint::{
+    self, create_computation as fwd_fp_create_computation,
+};
+use crate::analysis::graph::{Graph as Cfg, HasCfg, NodeIndex};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data as PiData, PointerInference};
+use crate::analysis::taint::state::State as TaState;
+use crate::analysis::taint::TaintAnalysis;
+use crate::analysis::vsa_results::{HasVsaResult, VsaResult};
+use crate::intermediate_representation::{ExternSymbol, Jmp, Project, Term, Tid};
+use crate::utils::log::CweWarning;
+
+use std::convert::AsRef;
+
+/// Type of the fixpoint computation of the taint analysis.
+pub type FpComputation<'a, 'b> = fixpoint::Computation<
+    forward_interprocedural_fixpoint::GeneralizedContext<'a, TaComputationContext<'a, 'b>>,
+>;
+
+/// Type that represents the definition of the taint analysis.
+///
+/// Values of this type represent the taint analysis for a particular call to a
+/// source function. Before the descriptor is released the analysis records
+/// the states at calls to release functions. After it is released the analysis
+/// generates CWE warnings for further uses and releases of the descriptor.
+pub struct TaComputationContext<'a, 'b: 'a> {
+    /// Source function call that is analyzed.
+    source: DescriptorCall<'a>,
+    /// Call that released the descriptor, if the analysis starts after it.
+    release: Option<DescriptorCall<'a>>,
+    project: &'a Project,
+    pi_result: &'a PointerInference<'b>,
+    config: &'a Config,
+    /// Used to send the states at calls that release the descriptor.
+    release_sender: crossbeam_channel::Sender<(Tid, TaState)>,
+    /// Used to send generated CWE warnings to the collector.
+    cwe_sender: crossbeam_channel::Sender<CweWarning>,
+}
+
+impl<'a, 'b: 'a> TaComputationContext<'a, 'b> {
+    /// Creates a new taint analysis context for the given call to a source
+    /// function.
+    pub(super) fn new(
+        source: DescriptorCall<'a>,
+        release: Option<DescriptorCall<'a>>,
+        project: &'a Project,
+        pi_result: &'a PointerInference<'b>,
+        config: &'a Config,
+        release_sender: &crossbeam_channel::Sender<(Tid, TaState)>,
+        cwe_sender: &crossbeam_channel::Sender<CweWarning>,
+    ) -> Self {
+        Self {
+            source,
+            release,
+            project,
+            pi_result,
+            config,
+            release_sender: release_sender.clone(),
+            cwe_sender: cwe_sender.clone(),
+        }
+    }
+
+    /// Converts the taint analysis context into a fixpoint computation that
+    /// starts at the given node with the given state.
+    pub fn into_computation(self, start_node: NodeIndex, state: TaState) -> FpComputation<'a, 'b> {
+        let mut computation = fwd_fp_create_computation(self, None);
+
+        computation.set_node_value(start_node, NodeValue::Value(state));
+
+        computation
+    }
+
+    /// Returns true if the first parameter of the call is tainted.
+    fn is_descriptor_tainted(
+        &self,
+        state: &TaState,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> bool {
+        let Some(parameter) = extern_symbol.parameters.first() else {
+            return false;
+        };
+        let descriptor_param = ExternSymbol {
+            parameters: vec![parameter.clone()],
+            ..extern_symbol.clone()
+        };
+        state.check_extern_parameters_for_taint::<false>(
+            self.vsa_result(),
+            &descriptor_param,
+            &call.tid,
+        )
+    }
+}
+
+impl<'a> HasCfg<'a> for TaComputationContext<'a, '_> {
+    fn get_cfg(&self) -> &Cfg<'a> {
+        self.pi_result.get_graph()
+    }
+}
+
+impl HasVsaResult<PiData> for TaComputationContext<'_, '_> {
+    fn vsa_result(&self) -> &impl VsaResult<ValueDomain = PiData> {
+        self.pi_result
+    }
+}
+
+impl AsRef<Project> for TaComputationContext<'_, '_> {
+    fn as_ref(&self) -> &Project {
+        self.project
+    }
+}
+
+impl<'a> TaintAnalysis<'a> for TaComputationContext<'a, '_> {
+    /// Update taint state on call to extern function.
+    ///
+    /// Calls to release functions with a tainted descriptor end the analysis.
+    /// Before the descriptor was released their states are recorded,
+    /// afterwards they are reported. Calls to functions that use a released
+    /// descriptor are reported as well. In all other cases the taint is
+    /// removed from non-callee-saved registers.
+    fn update_extern_call(
+        &self,
+        state: &TaState,
+        call: &Term<Jmp>,
+        project: &Project,
+        extern_symbol: &ExternSymbol,
+    ) -> Option<TaState> {
+        if extern_symbol.no_return {
+            return None;
+        }
+        let is_release = self.config.release_symbols.contains(&extern_symbol.name);
+        let is_use = self.config.use_symbols.contains(&extern_symbol.name);
+        if (is_release || is_use) && self.is_descriptor_tainted(state, call, extern_symbol) {
+            match &self.release {
+                Some(release) => super::generate_cwe_warning(
+                    &self.cwe_sender,
+                    &self.source,
+                    release,
+                    &call.tid,
+                    extern_symbol,
+                    is_release,
+                ),
+                None if is_release => {
+                    let mut released_state = state.clone();
+                    released_state.remove_non_callee_saved_taint(
+                        project.get_calling_convention(extern_symbol),
+                    );
+                    self.release_sender
+                        .send((call.tid.clone(), released_state))
+                        .expect("CWE672: failed to send released state");
+                }
+                None => (),
+            }
+            if is_release {
+                return None;
+            }
+        }
+
+        let mut new_state = state.clone();
+        new_state.remove_non_callee_saved_taint(project.get_calling_convention(extern_symbol));
+
+        Some(new_state)
+    }
+
+    /// Propagates taint from callee to caller.
+    ///
+    /// Only the taint in return registers and in memory objects that are known
+    /// to the caller is propagated.
+    fn update_return_callee(
+        &self,
+        state: &TaState,
+        call_term: &Term<Jmp>,
+        _return_term: &Term<Jmp>,
+        calling_convention: &Option<String>,
+    ) -> Option<TaState> {
+        let (mut propagated_register_taint, mut propagated_memory_taint) =
+            state.clone().into_mem_reg_taint();
+
+        let renaming_map = self.pi_result.get_call_renaming_map(&call_term.tid);
+        propagated_memory_taint.retain(|aid, _| {
+            renaming_map.is_some_and(|renaming_map| {
+                renaming_map
+                    .get(aid)
+                    .is_some_and(|value| value.referenced_ids().next().is_some())
+            })
+        });
+
+        if let Some(calling_convention) = self
+            .project
+            .get_specific_calling_convention(calling_convention)
+        {
+            let return_registers = calling_convention.get_all_return_register();
+
+            propagated_register_taint
+                .retain(|reg, taint| return_registers.contains(&reg) && taint.is_tainted());
+        }
+
+        Some(TaState::from_mem_reg_taint(
+            propagated_register_taint,
+            propagated_memory_taint,
+        ))
+    }
+}
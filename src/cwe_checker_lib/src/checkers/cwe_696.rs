@@ -0,0 +1,368 @@
+//! This module implements a check for CWE-696: Incorrect Behavior Order, in
+//! particular for incorrect sequences of calls that drop privileges.
+//!
+//! A program running with root privileges must drop its privileges in the
+//! order `setgroups`, `setgid`, `setuid`. Once the user ID was changed the
+//! program lacks the privileges to change its group IDs, so that calls to
+//! `setgid` or `setgroups` after `setuid` fail and the program keeps the
+//! privileges of the root group. The same is true if the group privileges are
+//! not dropped at all. If the return value of a call that drops privileges is
+//! not checked, the program may continue with elevated privileges after a
+//! failed call.
+//!
+//! See <https://cwe.mitre.org/data/definitions/696.html> for a detailed
+//! description.
+//!
+//! ## How the check works
+//!
+//! For every call to one of the `uid_symbols`, e.g. `setuid`, we check
+//!
+//! - whether a call to one of the `gid_symbols` or `groups_symbols` is
+//!   reachable from it in the same function (wrong order), and
+//! - whether the same function calls any of the `gid_symbols` and
+//!   `groups_symbols` at all (missing calls).
+//!
+//! Likewise calls to one of the `groups_symbols` that are reachable from a call
+//! to one of the `gid_symbols` are reported. Additionally the analysis of the
+//! [CWE-252 check](super::cwe_252) is used to find calls to any of the
+//! configured functions whose return value is not checked.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The `groups_symbols` drop the supplementary groups, e.g. `setgroups`.
+//! - The `gid_symbols` drop the group privileges, e.g. `setgid`.
+//! - The `uid_symbols` drop the user privileges, e.g. `setuid`.
+//!
+//! ## False Positives
+//!
+//! - Programs that only switch between unprivileged users do not need to drop
+//!   group privileges.
+//! - Privileges may be dropped in several helper functions that are called in
+//!   the correct order. Such sequences are reported as missing calls.
+//!
+//! ## False Negatives
+//!
+//! - Calls in the wrong order are only detected if they are in the same
+//!   function.
+//! - The arguments of the calls are not checked, e.g. whether the group ID
+//!   belongs to the same user as the user ID.
+use super::prelude::*;
+
+use super::cwe_252::find_unchecked_calls;
+use crate::analysis::graph::{Edge, Graph, NodeIndex};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::is_sink_call_reachable_from_source_call;
+use crate::utils::symbol_utils;
+
+use petgraph::visit::EdgeRef;
+
+use std::collections::{BTreeMap, HashSet};
+
+cwe_module!(
+    "CWE696",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// External symbols that drop the supplementary groups.
+        groups_symbols: HashSet<String>,
+        /// External symbols that drop the group privileges.
+        gid_symbols: HashSet<String>,
+        /// External symbols that drop the user privileges.
+        uid_symbols: HashSet<String>,
+);
+
+/// The privileges dropped by a function, in the order in which they have to be
+/// dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Privilege {
+    /// Supplementary groups.
+    Groups,
+    /// Group ID.
+    Group,
+    /// User ID.
+    User,
+}
+
+impl Privilege {
+    /// Returns a human readable name of the privilege.
+    fn description(&self) -> &'static str {
+        match self {
+            Privilege::Groups => "the supplementary groups",
+            Privilege::Group => "the group privileges",
+            Privilege::User => "the user privileges",
+        }
+    }
+}
+
+/// Call to an external function that drops privileges.
+struct DropCall<'a> {
+    /// Information about the function that was called.
+    symbol: &'a ExternSymbol,
+    /// The privilege that is dropped by the call.
+    privilege: Privilege,
+    /// The function that contains the call.
+    sub: &'a Term<Sub>,
+    /// CFG node where the call will return to.
+    return_node: NodeIndex,
+    /// IR instruction of the call.
+    jmp: &'a Term<Jmp>,
+}
+
+impl Config {
+    /// Returns the dropped privileges of all configured external functions
+    /// that are called by the program, indexed by the TID of the function.
+    fn get_privilege_map<'a>(
+        &self,
+        project: &'a Project,
+    ) -> BTreeMap<Tid, (&'a ExternSymbol, Privilege)> {
+        let mut privilege_map = BTreeMap::new();
+        for (symbols, privilege) in [
+            (&self.groups_symbols, Privilege::Groups),
+            (&self.gid_symbols, Privilege::Group),
+            (&self.uid_symbols, Privilege::User),
+        ] {
+            for (tid, symbol) in symbol_utils::get_symbol_map_fast(project, symbols) {
+                privilege_map.insert(tid.clone(), (symbol, privilege));
+            }
+        }
+        privilege_map
+    }
+
+    /// Returns the names of all configured external functions.
+    fn get_all_symbols(&self) -> HashSet<String> {
+        self.groups_symbols
+            .iter()
+            .chain(self.gid_symbols.iter())
+            .chain(self.uid_symbols.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Searches the program for calls to functions that drop privileges.
+fn get_drop_calls<'a>(
+    graph: &'a Graph,
+    privilege_map: &BTreeMap<Tid, (&'a ExternSymbol, Privilege)>,
+) -> Vec<DropCall<'a>> {
+    graph
+        .edge_references()
+        .filter_map(|edge| {
+            let Edge::ExternCallStub(jmp) = edge.weight() else {
+                return None;
+            };
+            let Jmp::Call { target, .. } = &jmp.term else {
+                return None;
+            };
+            let (symbol, privilege) = privilege_map.get(target)?;
+            Some(DropCall {
+                symbol,
+                privilege: *privilege,
+                sub: graph[edge.source()].get_sub(),
+                return_node: edge.target(),
+                jmp,
+            })
+        })
+        .collect()
+}
+
+/// Returns true if the given function calls one of the external functions that
+/// drop the given privilege.
+fn drops_privilege(
+    sub: &Term<Sub>,
+    privilege: Privilege,
+    privilege_map: &BTreeMap<Tid, (&ExternSymbol, Privilege)>,
+) -> bool {
+    sub.term
+        .blocks
+        .iter()
+        .flat_map(|blk| blk.term.jmps.iter())
+        .any(|jmp| match &jmp.term {
+            Jmp::Call { target, .. } => privilege_map
+                .get(target)
+                .is_some_and(|(_, callee_privilege)| *callee_privilege == privilege),
+            _ => false,
+        })
+}
+
+/// Generates a CWE warning for the given call that drops privileges.
+fn generate_cwe_warning(call: &DropCall, description: String, other: &[&DropCall]) -> CweWarning {
+    let calls = || std::iter::once(call).chain(other.iter().copied());
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!("(Incorrect Behavior Order) {description}"),
+    )
+    .tids(calls().map(|call| format!("{}", call.jmp.tid)).collect())
+    .addresses(
+        calls()
+            .map(|call| call.jmp.tid.address().to_string())
+            .collect(),
+    )
+    .symbols(calls().map(|call| call.symbol.name.clone()).collect())
+}
+
+/// Execute the CWE check.
+///
+/// For each call that drops privileges we check whether privileges are dropped
+/// after it that should have been dropped before, whether it is the drop of
+/// the user privileges without a drop of the group privileges, and whether its
+/// return value is checked.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let config: Config =
+        serde_json::from_value(cwe_params.clone()).expect("CWE696: invalid configuration");
+    let project = analysis_results.project;
+    let graph = analysis_results
+        .pointer_inference
+        .expect("CWE696: BUG: No pointer inference results.")
+        .get_graph();
+    let mut cwe_warnings = Vec::new();
+
+    let privilege_map = config.get_privilege_map(project);
+    let drop_calls = get_drop_calls(graph, &privilege_map);
+    if drop_calls.is_empty() {
+        return WithLogs::wrap(cwe_warnings);
+    }
+
+    for call in &drop_calls {
+        for (tid, (symbol, privilege)) in &privilege_map {
+            if *privilege >= call.privilege {
+                continue;
+            }
+            let Some(late_call) = is_sink_call_reachable_from_source_call(
+                graph,
+                call.return_node,
+                &call.symbol.tid,
+                tid,
+            )
+            .and_then(|late_tid| drop_calls.iter().find(|other| other.jmp.tid == late_tid)) else {
+                continue;
+            };
+            cwe_warnings.push(generate_cwe_warning(
+                late_call,
+                format!(
+                    "Call to {} at {} drops {} after {} were dropped by the call to {} at {}.",
+                    symbol.name,
+                    late_call.jmp.tid.address(),
+                    privilege.description(),
+                    call.privilege.description(),
+                    call.symbol.name,
+                    call.jmp.tid.address()
+                ),
+                &[call],
+            ));
+        }
+
+        if call.privilege == Privilege::User {
+            let missing: Vec<&str> = [Privilege::Groups, Privilege::Group]
+                .into_iter()
+                .filter(|privilege| !drops_privilege(call.sub, *privilege, &privilege_map))
+                .map(|privilege| privilege.description())
+                .collect();
+            if !missing.is_empty() {
+                cwe_warnings.push(generate_cwe_warning(
+                    call,
+                    format!(
+                        "Function {} drops the user privileges by the call to {} at {} without dropping {}.",
+                        call.sub.term.name,
+                        call.symbol.name,
+                        call.jmp.tid.address(),
+                        missing.join(" and ")
+                    ),
+                    &[],
+                ));
+            }
+        }
+    }
+
+    let unchecked_calls = find_unchecked_calls(analysis_results, &config.get_all_symbols());
+    for call in drop_calls
+        .iter()
+        .filter(|call| unchecked_calls.contains(&format!("{}", call.jmp.tid)))
+    {
+        cwe_warnings.push(generate_cwe_warning(
+            call,
+            format!(
+                "The return value of the call to {} at {} is not checked, so the program may continue without having dropped {} if the call fails.",
+                call.symbol.name,
+                call.jmp.tid.address(),
+                call.privilege.description()
+            ),
+            &[],
+        ));
+    }
+    cwe_warnings.sort();
+
+    WithLogs::wrap(cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::PointerInference;
+    use crate::checkers::test_utils::{self, CallSequence};
+
+    /// Returns a project with a function that calls the given external
+    /// functions in order and returns without a return value. If `checked` is
+    /// true, the return value of each call is checked by a conditional jump to
+    /// an error block.
+    fn mock_project(symbols: &[&str], checked: bool) -> Project {
+        let calls = CallSequence::new(symbols);
+        if checked {
+            calls.check_return_values().build()
+        } else {
+            calls.build()
+        }
+    }
+
+    fn run_check(project: &Project) -> Vec<CweWarning> {
+        let pi_result = PointerInference::mock(project);
+        let config = serde_json::json!({
+            "groups_symbols": ["setgroups"],
+            "gid_symbols": ["setgid", "setregid"],
+            "uid_symbols": ["setuid", "setreuid"]
+        });
+
+        test_utils::run_check(project, &pi_result, check_cwe, &config)
+    }
+
+    #[test]
+    fn correct_privilege_drop() {
+        let project = mock_project(&["setgroups", "setgid", "setuid"], true);
+        assert!(run_check(&project).is_empty());
+    }
+
+    #[test]
+    fn wrong_order_and_missing_calls() {
+        let project = mock_project(&["setuid", "setgid"], true);
+        let warnings = run_check(&project);
+        assert_eq!(warnings.len(), 2);
+        let order_warning = warnings
+            .iter()
+            .find(|warning| warning.symbols == ["setgid", "setuid"])
+            .unwrap();
+        assert_eq!(order_warning.tids, ["call_setgid_1", "call_setuid_0"]);
+        let missing_warning = warnings
+            .iter()
+            .find(|warning| warning.symbols == ["setuid"])
+            .unwrap();
+        assert!(missing_warning
+            .description
+            .ends_with("without dropping the supplementary groups."));
+    }
+
+    #[test]
+    fn unchecked_return_values() {
+        let project = mock_project(&["setgroups", "setgid", "setuid"], false);
+        let warnings = run_check(&project);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings
+            .iter()
+            .all(|warning| warning.description.contains("is not checked")));
+    }
+}
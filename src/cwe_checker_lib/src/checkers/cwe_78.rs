@@ -27,10 +27,34 @@
 //! The `system` symbols considered in this check can be configured in the
 //! `config.json`.
 //!
+//! ### On-demand string abstraction
+//!
+//! Computing string states for the whole program is expensive. If the check
+//! is the only string-based check to be run, the `system` symbols can also be
+//! added to the `on_demand_sinks` field of the string abstraction
+//! configuration. Then string states are only computed for the program
+//! points from which a `system` call is reachable.
+//!
+//! ### Sanitizer functions
+//!
+//! Sanitizer functions can be configured in the `sanitizer_symbols` field of
+//! the string abstraction configuration in the `config.json`. If the command
+//! string was passed through a sanitizer function on all paths to the
+//! `system` call, no warning is generated. Sanitization is not propagated
+//! through string operations, i.e. the command string itself has to be
+//! sanitized.
+//!
+//! ### Shared libraries
+//!
+//! If the shared libraries imported by the binary were analyzed as well,
+//! calls to library functions that pass one of their parameters on to a
+//! `system` symbol are checked like calls to `system` itself.
+//!
 //! ## False Positives
 //!
 //! - The input comes from the user but proper sanitization was not detected by
-//!   the analysis even though it exists.
+//!   the analysis even though it exists, e.g. because the sanitizer function
+//!   is not configured.
 //! - The input comes from the user but the format string's input format could
 //!   not be distinguished as non-string input.
 //!
@@ -39,13 +63,31 @@
 //! - Missing substrings due to lost track of pointer targets.
 //! - Non-tracked function parameters cause incomplete strings that could miss
 //!   possible dangerous inputs.
+//!
+//! ## Warning Context
+//!
+//! If the command string is partially known, the warning contains up to five
+//! examples of the known command prefix before the first unknown part.
+//!
+//! ## Reproduction Hints
+//!
+//! If `reproduction_hints` is enabled in the `config.json`, the input sources
+//! of the string abstraction configuration are tracked to the `system` calls,
+//! see [`input_provenance`](crate::analysis::input_provenance). Warnings at
+//! calls that are reached by an environment variable, a program argument or
+//! stdin get a [`ReproductionHint`] describing the input channel, the known
+//! command prefixes and an example payload that injects a command.
 use super::prelude::*;
 
 use petgraph::visit::EdgeRef;
 
 use crate::abstract_domain::BricksDomain;
 use crate::abstract_domain::TryToBitvec;
+use crate::abstract_domain::TryToConcrete;
 use crate::analysis::graph::Edge;
+use crate::analysis::input_provenance::{get_input_origins_at_sinks, ReproductionHint};
+use crate::analysis::input_sources::InputSources;
+use crate::analysis::library_summaries::LibrarySummaries;
 use crate::analysis::pointer_inference::State as PointerInferenceState;
 use crate::analysis::string_abstraction::context::Context;
 use crate::analysis::string_abstraction::state::State;
@@ -53,19 +95,29 @@ use crate::intermediate_representation::Arg;
 use crate::intermediate_representation::Expression;
 use crate::intermediate_representation::ExternSymbol;
 use crate::intermediate_representation::Jmp;
+use crate::intermediate_representation::Project;
 use crate::intermediate_representation::RuntimeMemoryImage;
 use crate::intermediate_representation::Sub;
 use crate::prelude::*;
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+/// The maximal number of known command prefixes shown in a CWE warning.
+const MAX_COMMAND_EXAMPLES: usize = 5;
+/// The example input of reproduction hints. It terminates the intended command and injects another one.
+const COMMAND_INJECTION_PAYLOAD: &str = ";id #";
 
 cwe_module!(
     "CWE78",
     "0.1",
     check_cwe,
+    requires: [StringAbstraction],
     config:
         /// The names of the `system` symbols.
         system_symbols: Vec<String>,
+        /// Whether to add reproduction hints to warnings reached by user input.
+        reproduction_hints: bool,
 );
 
 /// This check checks the string parameter at system calls given by the string abstraction analysis
@@ -96,37 +148,49 @@ pub fn check_cwe(
         .into_iter()
         .find(|(_, symbol)| config.system_symbols.contains(&symbol.name));
     let string_graph = string_abstraction.get_graph();
+    let project = string_abstraction.get_context().project;
+    let mut sinks: BTreeMap<Tid, Vec<Arg>> = BTreeMap::new();
 
-    if let Some((_, system)) = system_symbol {
-        for edge in string_graph.edge_references() {
-            if let Edge::ExternCallStub(jmp) = edge.weight() {
-                if let Jmp::Call { target, .. } = &jmp.term {
-                    if system.tid == *target {
-                        if let Some(source_node) = string_abstraction.get_node_value(edge.source())
-                        {
-                            if let Some(pi_node) = analysis_results
-                                .pointer_inference
-                                .unwrap()
-                                .get_node_value(edge.source())
-                            {
-                                let pi_state = pi_node.unwrap_value();
-                                let source_state = source_node.unwrap_value();
-                                check_system_call_parameter(
-                                    source_state,
-                                    pi_state,
-                                    &system,
-                                    &jmp.tid,
-                                    &cwe_sender,
-                                    &log_sender,
-                                    &string_abstraction
-                                        .get_context()
-                                        .project
-                                        .runtime_memory_image,
-                                )
-                            }
-                        }
-                    }
-                }
+    for edge in string_graph.edge_references() {
+        let Edge::ExternCallStub(jmp) = edge.weight() else {
+            continue;
+        };
+        let Jmp::Call { target, .. } = &jmp.term else {
+            continue;
+        };
+        let checked_parameters = get_command_parameters(
+            target,
+            system_symbol.as_ref().map(|(_, system)| system),
+            project,
+            analysis_results.library_summaries,
+            &config.system_symbols,
+        );
+        if checked_parameters.is_empty() {
+            continue;
+        }
+        sinks.entry(jmp.tid.clone()).or_default().extend(
+            checked_parameters
+                .iter()
+                .map(|(parameter, _)| (*parameter).clone()),
+        );
+        if let (Some(source_node), Some(pi_node)) = (
+            string_abstraction.get_node_value(edge.source()),
+            analysis_results
+                .pointer_inference
+                .unwrap()
+                .get_node_value(edge.source()),
+        ) {
+            for (parameter, symbol_name) in checked_parameters {
+                check_system_call_parameter(
+                    source_node.unwrap_value(),
+                    pi_node.unwrap_value(),
+                    parameter,
+                    &symbol_name,
+                    &jmp.tid,
+                    &cwe_sender,
+                    &log_sender,
+                    &project.runtime_memory_image,
+                )
             }
         }
     }
@@ -139,29 +203,128 @@ pub fn check_cwe(
         };
     }
 
-    let cwe_warnings = cwe_warnings.into_values().collect();
+    let mut cwe_warnings: Vec<CweWarning> = cwe_warnings.into_values().collect();
+    if config.reproduction_hints {
+        add_reproduction_hints(
+            &mut cwe_warnings,
+            analysis_results,
+            &string_abstraction.get_context().input_sources,
+            &sinks,
+        );
+    }
     let log_messages = log_receiver.try_iter().collect();
 
     WithLogs::new(cwe_warnings, log_messages)
 }
 
+/// Add reproduction hints to the warnings at sink calls that are reached by user input.
+///
+/// The known command prefixes of a warning are used as prefixes of its hints.
+fn add_reproduction_hints(
+    cwe_warnings: &mut [CweWarning],
+    analysis_results: &AnalysisResults,
+    input_sources: &InputSources,
+    sinks: &BTreeMap<Tid, Vec<Arg>>,
+) {
+    let origins = get_input_origins_at_sinks(analysis_results, input_sources, sinks);
+    for (sink, origins) in origins {
+        let address = sink.address().to_string();
+        let Some(warning) = cwe_warnings
+            .iter_mut()
+            .find(|warning| warning.addresses.first() == Some(&address))
+        else {
+            continue;
+        };
+        let prefixes: Vec<String> = warning
+            .other
+            .iter()
+            .flatten()
+            .filter_map(|entry| entry.strip_prefix("command_prefix="))
+            .map(String::from)
+            .collect();
+        warning.reproduction_hints = origins
+            .into_iter()
+            .map(|origin| {
+                ReproductionHint::new(origin, prefixes.clone(), COMMAND_INJECTION_PAYLOAD)
+            })
+            .collect();
+    }
+}
+
+/// Get the parameters of a call to the given target that are executed as commands,
+/// together with the names used for the called function in CWE warnings.
+///
+/// For calls to the `system` symbol this is its first parameter.
+/// For calls to functions of analyzed shared libraries these are the parameters
+/// that the library function passes on to a `system` symbol.
+fn get_command_parameters<'a>(
+    target: &Tid,
+    system_symbol: Option<&'a ExternSymbol>,
+    project: &'a Project,
+    library_summaries: Option<&LibrarySummaries>,
+    system_symbols: &[String],
+) -> Vec<(&'a Arg, String)> {
+    if let Some(system) = system_symbol.filter(|system| system.tid == *target) {
+        return system
+            .parameters
+            .first()
+            .map(|parameter| (parameter, system.name.clone()))
+            .into_iter()
+            .collect();
+    }
+    let (Some(symbol), Some(library_summaries)) = (
+        project.program.term.extern_symbols.get(target),
+        library_summaries,
+    ) else {
+        return Vec::new();
+    };
+    library_summaries
+        .get_sink_parameters(symbol, system_symbols)
+        .into_iter()
+        .map(|(parameter, sinks)| {
+            (
+                parameter,
+                format!(
+                    "{} (which passes it to {} in a shared library)",
+                    symbol.name,
+                    sinks.join(", ")
+                ),
+            )
+        })
+        .collect()
+}
+
 /// Checks the system call parameter given by the Bricks Domain.
+///
+/// The `symbol_name` is the name of the called function used in the CWE warning.
+#[allow(clippy::too_many_arguments)]
 pub fn check_system_call_parameter(
     source_state: &State<BricksDomain>,
     pi_state: &PointerInferenceState,
-    system_symbol: &ExternSymbol,
+    parameter: &Arg,
+    symbol_name: &str,
     jmp_tid: &Tid,
     cwe_collector: &crossbeam_channel::Sender<CweWarning>,
     log_collector: &crossbeam_channel::Sender<LogMessage>,
     runtime_memory_image: &RuntimeMemoryImage,
 ) {
     let sub = source_state.get_current_sub().unwrap();
-    if let Some(Arg::Register {
+    if let Arg::Register {
         expr: Expression::Var(var),
         ..
-    }) = system_symbol.parameters.first()
+    } = parameter
     {
         if let Some(value) = source_state.get_variable_to_pointer_map().get(var) {
+            if source_state
+                .get_string_predicates(value, pi_state)
+                .is_sanitized()
+            {
+                let _ = log_collector.send(LogMessage::new_debug(format!(
+                    "Sanitized parameter for system call at {}",
+                    jmp_tid.address()
+                )));
+                return;
+            }
             let contains_string_constant = value.get_absolute_value().is_some();
             let contains_relative_string_pointer = !value.get_relative_values().is_empty();
             if contains_relative_string_pointer {
@@ -186,14 +349,15 @@ pub fn check_system_call_parameter(
                     parameter_domain,
                     jmp_tid,
                     sub,
-                    system_symbol,
+                    symbol_name,
                     cwe_collector,
                 );
             } else if !contains_string_constant && !contains_relative_string_pointer {
                 let _ = cwe_collector.send(generate_cwe_warning(
                     &sub.term.name,
                     jmp_tid,
-                    &system_symbol.name,
+                    symbol_name,
+                    &BTreeSet::new(),
                 ));
             }
         } else {
@@ -210,7 +374,7 @@ pub fn check_if_string_domain_indicates_vulnerability(
     input_domain: BricksDomain,
     jmp_tid: &Tid,
     sub: &Term<Sub>,
-    system_symbol: &ExternSymbol,
+    symbol_name: &str,
     cwe_collector: &crossbeam_channel::Sender<CweWarning>,
 ) {
     match &input_domain {
@@ -218,30 +382,63 @@ pub fn check_if_string_domain_indicates_vulnerability(
             let _ = cwe_collector.send(generate_cwe_warning(
                 &sub.term.name,
                 jmp_tid,
-                &system_symbol.name,
+                symbol_name,
+                &BTreeSet::new(),
             ));
         }
         BricksDomain::Value(bricks) => {
-            let partially_known = bricks
+            let first_unknown_brick = bricks
                 .iter()
-                .any(|brick| matches!(brick, crate::abstract_domain::BrickDomain::Top));
-            if partially_known {
+                .position(|brick| matches!(brick, crate::abstract_domain::BrickDomain::Top));
+            if let Some(first_unknown_brick) = first_unknown_brick {
+                // The known parts of the command before the first unknown part.
+                let command_prefixes = BricksDomain::Value(bricks[..first_unknown_brick].to_vec())
+                    .try_to_concrete(MAX_COMMAND_EXAMPLES)
+                    .unwrap_or_default();
                 let _ = cwe_collector.send(generate_cwe_warning(
                     &sub.term.name,
                     jmp_tid,
-                    &system_symbol.name,
+                    symbol_name,
+                    &command_prefixes,
                 ));
             }
         }
     }
 }
 
-/// Generates the CWE Warning for the CWE 78 check
-pub fn generate_cwe_warning(sub_name: &str, jmp_tid: &Tid, symbol_name: &str) -> CweWarning {
-    let description: String = format!(
+/// Generates the CWE Warning for the CWE 78 check.
+///
+/// The known prefixes of the command are added to the warning if there are any.
+pub fn generate_cwe_warning(
+    sub_name: &str,
+    jmp_tid: &Tid,
+    symbol_name: &str,
+    command_prefixes: &BTreeSet<String>,
+) -> CweWarning {
+    let mut description: String = format!(
         "(OS Command Injection) Input for call to {} may not be properly sanitized in function {} ({})",
         symbol_name, sub_name, jmp_tid.address(),
     );
+    let mut other = vec![vec![
+        String::from("OS Command Injection"),
+        symbol_name.to_string(),
+    ]];
+    if !command_prefixes.is_empty() {
+        description = format!(
+            "{description}. Known command prefixes: {}",
+            command_prefixes
+                .iter()
+                .map(|prefix| format!("{prefix:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        other.push(
+            command_prefixes
+                .iter()
+                .map(|prefix| format!("command_prefix={prefix}"))
+                .collect(),
+        );
+    }
     CweWarning::new(
         String::from(CWE_MODULE.name),
         String::from(CWE_MODULE.version),
@@ -250,8 +447,5 @@ pub fn generate_cwe_warning(sub_name: &str, jmp_tid: &Tid, symbol_name: &str) ->
     .addresses(vec![jmp_tid.address().to_string()])
     .tids(vec![format!("{jmp_tid}")])
     .symbols(vec![String::from(sub_name)])
-    .other(vec![vec![
-        String::from("OS Command Injection"),
-        symbol_name.to_string(),
-    ]])
+    .other(other)
 }
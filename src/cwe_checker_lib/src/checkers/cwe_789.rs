@@ -15,7 +15,9 @@
 //! exceeds the defined `stack_threshold` defined in config.json, a warning is
 //! generated. For calls like malloc, the provided argument is checked, if its
 //! value exceeds the defined `heap_threshold`. The covered function calls are
-//! defined in config.json. The defined thresholds are provided in bytes.
+//! defined in config.json. Calls to the allocation wrappers configured for the
+//! Pointer Inference analysis are checked in the same way. The defined
+//! thresholds are provided in bytes.
 //!
 //! ## False Positives
 //!
@@ -40,6 +42,7 @@ cwe_module!(
     "CWE789",
     "0.1",
     check_cwe,
+    requires: [PointerInference],
     config:
         /// Size in bytes above which a potential stack memory exhaustion is
         /// reported.
@@ -172,6 +175,17 @@ pub fn check_cwe(
                 )));
             }
         }
+        // Allocation wrapper case
+        for jump in f.term.blocks.iter().flat_map(|blk| blk.term.jmps.iter()) {
+            if let Some(alloc_size_interval) = pir
+                .get_allocation_wrapper_size_param(&jump.tid)
+                .and_then(|size_param| pir.eval_parameter_arg_at_call(&jump.tid, size_param))
+            {
+                if exceeds_threshold_on_call(alloc_size_interval, config.heap_threshold) {
+                    cwe_warnings.push(generate_cwe_warning(&jump.tid, false));
+                }
+            }
+        }
         // Stack allocation case
         for blk in &f.term.blocks {
             let assign_on_sp: Vec<&Term<Def>> = blk
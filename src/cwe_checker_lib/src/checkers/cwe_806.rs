@@ -0,0 +1,342 @@
+//! This module implements a check for CWE-806: Buffer Access Using Size of
+//! Source Buffer and the related off-by-one error of bounded string copies.
+//!
+//! Bounded copy functions like `strncpy`, `memcpy` or `snprintf` take the size
+//! of the destination buffer as a parameter. If the size of the source buffer
+//! is given instead, e.g. `snprintf(dst, sizeof(src), "%s", src)`, the bound
+//! does not protect the destination buffer and a larger source buffer
+//! overflows it. A related mistake is `strncpy(dst, src, sizeof(dst))`:
+//! If the source string is at least as long as the destination buffer, no
+//! terminating NUL byte is written and later string operations on the buffer
+//! read past its end.
+//!
+//! See <https://cwe.mitre.org/data/definitions/806.html> and
+//! <https://cwe.mitre.org/data/definitions/193.html> for detailed descriptions.
+//!
+//! ## How the check works
+//!
+//! For each call to a symbol in the `buffer_size_parameters` map the check
+//! uses the results of the [Pointer Inference
+//! analysis](`crate::analysis::pointer_inference`) to evaluate the destination
+//! and size parameters. If the destination points to a heap object of known
+//! size and the size parameter is a known constant, then
+//!
+//! - a warning is generated if the size is larger than the remaining size of
+//!   the destination buffer and equals the remaining size of another buffer
+//!   passed to the same call, i.e. the size of the source buffer was used, and
+//! - for the `unterminated_copy_symbols` (e.g. `strncpy`) a warning is
+//!   generated if the size equals the remaining size of the destination buffer,
+//!   since then no space is left for a terminating NUL byte.
+//!
+//! The sizes of heap objects are computed from the size parameters of the
+//! calls that allocated them in the same way as in the
+//! [CWE-119 check](`crate::checkers::cwe_119`).
+//!
+//! ## False Positives
+//!
+//! - `strncpy(dst, src, sizeof(dst))` followed by an explicit
+//!   `dst[sizeof(dst) - 1] = 0` is still reported.
+//! - Two buffers of the same size passed to a call may make a correct size
+//!   parameter look like the size of the source buffer, if the size is larger
+//!   than the remaining size of the destination buffer for other reasons.
+//!
+//! ## False Negatives
+//!
+//! - The Pointer Inference analysis cannot distinguish different buffers on the
+//!   same stack frame and does not know the sizes of global buffers. Thus only
+//!   heap buffers allocated in the same function as the call are checked.
+//! - Only buffers passed in parameter registers are considered as possible
+//!   source buffers. Source buffers passed on the stack are missed.
+//! - Size parameters that are not known constants are not checked.
+use super::prelude::*;
+
+use crate::abstract_domain::{AbstractIdentifier, IntervalDomain, TryToBitvec};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::analysis::vsa_results::VsaResult;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use std::collections::{BTreeMap, HashMap};
+
+cwe_module!(
+    "CWE806",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// Maps the names of bounded copy functions to the indices of their
+        /// destination buffer parameter and their size parameter.
+        buffer_size_parameters: BTreeMap<String, (usize, usize)>,
+        /// The names of bounded copy functions that do not NUL-terminate the
+        /// destination buffer if the size limit is reached (e.g. `strncpy`).
+        unterminated_copy_symbols: Vec<String>,
+);
+
+/// A mismatch between the size parameter of a call and the buffers given to it.
+#[derive(Debug, PartialEq, Eq)]
+enum SizeMismatch {
+    /// The size parameter equals the size of another buffer given to the call
+    /// and is larger than the destination buffer.
+    SourceBufferSize {
+        /// The destination buffer.
+        destination: AbstractIdentifier,
+        /// The remaining size of the destination buffer.
+        destination_size: u64,
+        /// The buffer whose size was used as the size parameter.
+        source: AbstractIdentifier,
+        /// The value of the size parameter.
+        size: u64,
+    },
+    /// The size parameter equals the size of the destination buffer, so that
+    /// no space is left for a terminating NUL byte.
+    MissingTerminator {
+        /// The destination buffer.
+        destination: AbstractIdentifier,
+        /// The value of the size parameter.
+        size: u64,
+    },
+}
+
+/// Compute the sizes of heap objects whose allocation size is a known
+/// constant. The sizes are indexed by the TID of the allocating call.
+fn compute_known_object_sizes(analysis_results: &AnalysisResults) -> HashMap<Tid, u64> {
+    super::cwe_119::compute_size_values_of_malloc_calls(analysis_results)
+        .into_iter()
+        .filter_map(|(call_tid, size)| {
+            let size = size.get_if_absolute_value()?.try_to_bitvec().ok()?;
+            Some((call_tid, size.try_to_u64().ok()?))
+        })
+        .collect()
+}
+
+/// Return the target of the pointer and the number of bytes between the
+/// pointer and the end of the target object, if both are exactly known.
+fn get_remaining_buffer_size<'a>(
+    pointer: &'a Data,
+    object_sizes: &HashMap<Tid, u64>,
+) -> Option<(&'a AbstractIdentifier, u64)> {
+    let (id, offset) = pointer.get_if_unique_target()?;
+    if !id.get_path_hints().is_empty() {
+        return None;
+    }
+    let object_size = *object_sizes.get(id.get_tid())?;
+    let offset = offset.try_to_bitvec().ok()?.try_to_u64().ok()?;
+    object_size
+        .checked_sub(offset)
+        .map(|remaining_size| (id, remaining_size))
+}
+
+/// Compare the size parameter of a call with the sizes of the destination
+/// buffer and of the `other_arguments` of the call.
+fn find_size_mismatch(
+    destination: &Data,
+    size: &Data,
+    other_arguments: &[Data],
+    object_sizes: &HashMap<Tid, u64>,
+    may_leave_unterminated: bool,
+) -> Option<SizeMismatch> {
+    let (destination_id, destination_size) = get_remaining_buffer_size(destination, object_sizes)?;
+    let size = size
+        .get_if_absolute_value()
+        .and_then(|size: &IntervalDomain| size.try_to_bitvec().ok())?
+        .try_to_u64()
+        .ok()?;
+    if size > destination_size {
+        other_arguments
+            .iter()
+            .filter_map(|argument| get_remaining_buffer_size(argument, object_sizes))
+            .find(|(source_id, source_size)| *source_id != destination_id && *source_size == size)
+            .map(|(source_id, _)| SizeMismatch::SourceBufferSize {
+                destination: destination_id.clone(),
+                destination_size,
+                source: source_id.clone(),
+                size,
+            })
+    } else if size == destination_size && may_leave_unterminated {
+        Some(SizeMismatch::MissingTerminator {
+            destination: destination_id.clone(),
+            size,
+        })
+    } else {
+        None
+    }
+}
+
+/// Evaluate the values of all integer parameter registers at the given call.
+///
+/// This includes variadic arguments that are not part of the parameter list of
+/// the called symbol.
+fn get_register_arguments(
+    pointer_inference: &PointerInference,
+    calling_convention: &CallingConvention,
+    call_tid: &Tid,
+) -> Vec<Data> {
+    calling_convention
+        .integer_parameter_register
+        .iter()
+        .filter_map(|register| {
+            pointer_inference.eval_at_jmp(call_tid, &Expression::Var(register.clone()))
+        })
+        .collect()
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    jmp: &Term<Jmp>,
+    extern_symbol: &ExternSymbol,
+    mismatch: SizeMismatch,
+) -> CweWarning {
+    let (description, context) = match mismatch {
+        SizeMismatch::SourceBufferSize {
+            destination,
+            destination_size,
+            source,
+            size,
+        } => (
+            format!(
+                "(Buffer Access Using Size of Source Buffer) Size parameter of call to {} at {} is the size of another buffer",
+                extern_symbol.name,
+                jmp.tid.address()
+            ),
+            format!(
+                "Size parameter {size} equals the size of buffer {source}, but the destination buffer {destination} only has {destination_size} bytes."
+            ),
+        ),
+        SizeMismatch::MissingTerminator { destination, size } => (
+            format!(
+                "(Off-by-one Buffer Size) Call to {} at {} may leave the destination buffer without a terminating NUL byte",
+                extern_symbol.name,
+                jmp.tid.address()
+            ),
+            format!(
+                "Size parameter {size} equals the size of the destination buffer {destination}, leaving no space for a terminating NUL byte."
+            ),
+        ),
+    };
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .tids(vec![format!("{}", jmp.tid)])
+        .addresses(vec![jmp.tid.address().to_string()])
+        .symbols(vec![extern_symbol.name.clone()])
+        .other(vec![vec![context]])
+}
+
+/// Execute the CWE check.
+///
+/// For each call to one of the configured bounded copy functions we compare
+/// the size parameter with the sizes of the destination buffer and of the
+/// other buffers given to the call.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference.unwrap();
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let mut cwe_warnings = Vec::new();
+    let object_sizes = compute_known_object_sizes(analysis_results);
+
+    let symbol_names: Vec<String> = config.buffer_size_parameters.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &symbol_names);
+    for sub in project.program.functions() {
+        for (_, jmp, symbol) in get_callsites(sub, &symbol_map) {
+            let (destination_index, size_index) = config.buffer_size_parameters[&symbol.name];
+            let (Some(destination), Some(size)) = (
+                symbol
+                    .parameters
+                    .get(destination_index)
+                    .and_then(|parameter| {
+                        pointer_inference.eval_parameter_arg_at_call(&jmp.tid, parameter)
+                    }),
+                symbol.parameters.get(size_index).and_then(|parameter| {
+                    pointer_inference.eval_parameter_arg_at_call(&jmp.tid, parameter)
+                }),
+            ) else {
+                continue;
+            };
+            let other_arguments = get_register_arguments(
+                pointer_inference,
+                project.get_calling_convention(symbol),
+                &jmp.tid,
+            );
+            if let Some(mismatch) = find_size_mismatch(
+                &destination,
+                &size,
+                &other_arguments,
+                &object_sizes,
+                config.unterminated_copy_symbols.contains(&symbol.name),
+            ) {
+                cwe_warnings.push(generate_cwe_warning(jmp, symbol, mismatch));
+            }
+        }
+    }
+
+    cwe_warnings.deduplicate_first_address()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::AbstractLocation;
+    use crate::variable;
+
+    fn mock_heap_pointer(call_tid: &str, offset: i64) -> Data {
+        Data::from_target(
+            AbstractIdentifier::new(
+                Tid::new(call_tid),
+                AbstractLocation::Register(variable!("RAX:8")),
+            ),
+            Bitvector::from_i64(offset).into(),
+        )
+    }
+
+    #[test]
+    fn size_mismatches() {
+        let object_sizes = HashMap::from([(Tid::new("dst"), 16), (Tid::new("src"), 32)]);
+        let size = |value: u64| Data::from(Bitvector::from_u64(value));
+        let destination = mock_heap_pointer("dst", 0);
+        let source = mock_heap_pointer("src", 0);
+        let destination_id = destination.get_if_unique_target().unwrap().0.clone();
+        let source_id = source.get_if_unique_target().unwrap().0.clone();
+        let arguments = [destination.clone(), source.clone()];
+
+        assert_eq!(
+            find_size_mismatch(&destination, &size(32), &arguments, &object_sizes, false),
+            Some(SizeMismatch::SourceBufferSize {
+                destination: destination_id.clone(),
+                destination_size: 16,
+                source: source_id,
+                size: 32
+            })
+        );
+        // Sizes larger than the destination that do not stem from another buffer are left to the CWE-119 check.
+        assert_eq!(
+            find_size_mismatch(&destination, &size(24), &arguments, &object_sizes, false),
+            None
+        );
+        assert_eq!(
+            find_size_mismatch(&destination, &size(16), &arguments, &object_sizes, true),
+            Some(SizeMismatch::MissingTerminator {
+                destination: destination_id.clone(),
+                size: 16
+            })
+        );
+        assert_eq!(
+            find_size_mismatch(&destination, &size(16), &arguments, &object_sizes, false),
+            None
+        );
+        assert_eq!(
+            find_size_mismatch(&destination, &size(15), &arguments, &object_sizes, true),
+            None
+        );
+        // The remaining size of the destination buffer is used for pointers into the buffer.
+        let destination = mock_heap_pointer("dst", 8);
+        assert_eq!(
+            find_size_mismatch(&destination, &size(8), &arguments, &object_sizes, true),
+            Some(SizeMismatch::MissingTerminator {
+                destination: destination_id,
+                size: 8
+            })
+        );
+    }
+}
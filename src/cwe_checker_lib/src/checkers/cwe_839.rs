@@ -0,0 +1,424 @@
+//! CWE-839: Numeric Range Comparison Without Minimum Check.
+//!
+//! A signed value that is only compared against an upper bound may still be
+//! negative. If it is then used as an array index or as a size, e.g.
+//!
+//! ```c
+//! int len = atoi(input);
+//! if (len < MAX) {
+//!     memcpy(buf, src, len);
+//! }
+//! ```
+//!
+//! a negative value results in an out-of-bounds access or, after the implicit
+//! conversion to an unsigned size, in a huge copy operation.
+//!
+//! See <https://cwe.mitre.org/data/definitions/839.html> for a detailed
+//! description.
+//!
+//! # Algorithm
+//!
+//! We perform a taint analysis for every call to one of the configured
+//! `source_symbols`, e.g. `atoi` or `strtol`, where the return value of the
+//! call is tainted. If `input_sources` are configured, we also perform a
+//! taint analysis for every call to one of their buffer symbols, e.g. `read`,
+//! where the buffer that the call writes to is tainted, since integers read
+//! from files or the network are untrusted as well. If `entry_points` are configured, e.g. the exported
+//! functions of a shared library, we additionally perform a taint analysis
+//! that starts at these functions with all their parameters tainted.
+//! A warning is generated if
+//!
+//! - the size parameter of a call to one of the functions in
+//!   `size_parameters` is tainted, or
+//! - the address of a memory access depends on a tainted value,
+//!
+//! and the value computed by the Pointer Inference analysis for the size or
+//! the offset of the address is bounded from above but not from below. Since
+//! the interval domain of the Pointer Inference analysis refines values by the
+//! conditions of branches, this is the case for values that are only compared
+//! against an upper bound.
+//!
+//! # Limitations
+//!
+//! ## False Positives
+//!
+//! - Checks that are not refined into the interval domain, e.g. checks of a
+//!   copy of the value in another register, are not recognized.
+//!
+//! ## False Negatives
+//!
+//! - Values that are passed to a function of the binary are not followed into
+//!   it. A value that is compared against an upper bound in the caller and
+//!   used as an index or size in the callee is missed.
+//! - 64-bit values that are scaled before being used as an array index can
+//!   overflow in the interval domain, so that their bounds are lost.
+//! - Values from sources other than the configured functions and entry
+//!   points are not checked.
+use super::prelude::*;
+
+use crate::abstract_domain::{IntervalDomain, TryToInterval};
+use crate::analysis::graph::{Edge, NodeIndex};
+use crate::analysis::input_sources::InputSources;
+use crate::analysis::seeding::EntryPointSeeding;
+use crate::intermediate_representation::{ExternSymbol, Jmp, Term};
+use crate::pipeline::AnalysisResults;
+use crate::prelude::*;
+use crate::utils::symbol_utils;
+
+use petgraph::visit::EdgeRef;
+
+use std::collections::{BTreeMap, HashSet};
+
+mod context;
+
+use context::*;
+
+/// Values whose interval extends below the negation of this threshold and
+/// stays below the threshold are considered to be bounded only from above.
+///
+/// The threshold is half of the maximal signed 32-bit integer, so that small
+/// offsets added to or scale factors applied to the value do not matter.
+const BOUND_THRESHOLD: i64 = 1 << 30;
+
+cwe_module!(
+    "CWE839",
+    "0.1",
+    check_cwe,
+    requires: [PointerInference],
+    config:
+        /// External symbols that return signed integers parsed from their
+        /// input.
+        source_symbols: HashSet<String>,
+        /// External symbols mapped to the index of their size parameter.
+        size_parameters: BTreeMap<String, usize>,
+        /// Sources of user input whose buffers contain untrusted signed integers.
+        /// If not set, no buffers are tainted.
+        input_sources: Option<InputSources>,
+        /// Functions whose parameters are untrusted signed integers.
+        /// If not set, only the return values of the source symbols are checked.
+        entry_points: Option<EntryPointSeeding>,
+);
+
+/// Source of untrusted signed integers.
+#[derive(Clone, Copy)]
+enum UntrustedSource<'a> {
+    /// Call to an external function that returns an untrusted signed integer.
+    Call {
+        /// Information about the function that was called.
+        symbol: &'a ExternSymbol,
+        /// CFG node where the call will return to.
+        return_node: NodeIndex,
+        /// IR instruction of the call.
+        jmp: &'a Term<Jmp>,
+    },
+    /// Call to an external function that writes user input into a buffer.
+    Buffer {
+        /// Information about the function that was called.
+        symbol: &'a ExternSymbol,
+        /// CFG node where the call will return to.
+        return_node: NodeIndex,
+        /// IR instruction of the call.
+        jmp: &'a Term<Jmp>,
+        /// The configuration of the buffer written by the call.
+        input_sources: &'a InputSources,
+    },
+    /// Parameters of the functions selected by the seeding strategy.
+    Parameters(&'a EntryPointSeeding),
+}
+
+/// Returns true if the given value may be negative but is bounded from above.
+fn is_only_bounded_from_above(value: &IntervalDomain) -> bool {
+    value
+        .try_to_offset_interval()
+        .is_ok_and(|(start, end)| start <= -BOUND_THRESHOLD && end < BOUND_THRESHOLD)
+}
+
+/// Searches the program for calls to source functions
+/// and to the buffer symbols of the input sources.
+fn get_sources<'a>(
+    analysis_results: &'a AnalysisResults,
+    source_symbols: &HashSet<String>,
+    input_sources: Option<&'a InputSources>,
+) -> Vec<UntrustedSource<'a>> {
+    let mut symbols = source_symbols.clone();
+    if let Some(input_sources) = input_sources {
+        symbols.extend(input_sources.buffer_symbols.keys().cloned());
+    }
+    let symbol_map = symbol_utils::get_symbol_map_fast(analysis_results.project, &symbols);
+
+    analysis_results
+        .pointer_inference
+        .expect("CWE839: BUG: No pointer inference results.")
+        .get_graph()
+        .edge_references()
+        .filter_map(|edge| {
+            let Edge::ExternCallStub(jmp) = edge.weight() else {
+                return None;
+            };
+            let Jmp::Call { target, .. } = &jmp.term else {
+                return None;
+            };
+            let symbol = symbol_map.get(target)?;
+            match input_sources {
+                Some(input_sources) if !source_symbols.contains(&symbol.name) => {
+                    Some(UntrustedSource::Buffer {
+                        symbol,
+                        return_node: edge.target(),
+                        jmp,
+                        input_sources,
+                    })
+                }
+                _ => Some(UntrustedSource::Call {
+                    symbol,
+                    return_node: edge.target(),
+                    jmp,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Generates the CWE warning for a value of the given source that is used
+/// without a minimum check at the given location.
+fn generate_cwe_warning(
+    sender: &crossbeam_channel::Sender<CweWarning>,
+    source: &UntrustedSource,
+    location: &Tid,
+    usage: &str,
+) {
+    let cwe_warning = match source {
+        UntrustedSource::Call { symbol, jmp, .. } => CweWarning::new(
+            CWE_MODULE.name,
+            CWE_MODULE.version,
+            format!(
+                "(Numeric Range Comparison Without Minimum Check) Signed value returned by {} ({}) is only checked against an upper bound before it is used as {} at {}.",
+                jmp.tid.address(),
+                symbol.name,
+                usage,
+                location.address()
+            ),
+        )
+        .addresses(vec![
+            location.address().to_string(),
+            jmp.tid.address().to_string(),
+        ])
+        .tids(vec![format!("{location}"), format!("{}", jmp.tid)])
+        .symbols(vec![symbol.name.clone()]),
+        UntrustedSource::Buffer { symbol, jmp, .. } => CweWarning::new(
+            CWE_MODULE.name,
+            CWE_MODULE.version,
+            format!(
+                "(Numeric Range Comparison Without Minimum Check) Signed value read by {} ({}) is only checked against an upper bound before it is used as {} at {}.",
+                jmp.tid.address(),
+                symbol.name,
+                usage,
+                location.address()
+            ),
+        )
+        .addresses(vec![
+            location.address().to_string(),
+            jmp.tid.address().to_string(),
+        ])
+        .tids(vec![format!("{location}"), format!("{}", jmp.tid)])
+        .symbols(vec![symbol.name.clone()]),
+        UntrustedSource::Parameters(_) => CweWarning::new(
+            CWE_MODULE.name,
+            CWE_MODULE.version,
+            format!(
+                "(Numeric Range Comparison Without Minimum Check) Signed function parameter is only checked against an upper bound before it is used as {} at {}.",
+                usage,
+                location.address()
+            ),
+        )
+        .addresses(vec![location.address().to_string()])
+        .tids(vec![format!("{location}")]),
+    };
+    sender
+        .send(cwe_warning)
+        .expect("CWE839: failed to send CWE warning");
+}
+
+/// CWE-839: Numeric Range Comparison Without Minimum Check.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+    _debug_settings: &debug::Settings,
+) -> WithLogs<Vec<CweWarning>> {
+    let config: Config =
+        serde_json::from_value(cwe_params.clone()).expect("CWE839: invalid configuration");
+    let project = analysis_results.project;
+    let pi_result = analysis_results
+        .pointer_inference
+        .expect("CWE839: BUG: No pointer inference results.");
+
+    let mut sources = get_sources(
+        analysis_results,
+        &config.source_symbols,
+        config.input_sources.as_ref(),
+    );
+    if let Some(seeding) = &config.entry_points {
+        sources.push(UntrustedSource::Parameters(seeding));
+    }
+
+    let mut logs = Vec::new();
+    let (cwe_sender, cwe_receiver) = crossbeam_channel::unbounded();
+    for source in sources {
+        match TaComputationContext::new(
+            source,
+            project,
+            pi_result,
+            &config.size_parameters,
+            &cwe_sender,
+        )
+        .into_computation()
+        {
+            Ok(mut computation) => computation.compute_with_max_steps(100),
+            Err(err) => logs.push(
+                LogMessage::new_error(format!("Invalid entry point configuration: {err}"))
+                    .source(CWE_MODULE.name),
+            ),
+        }
+    }
+    let mut cwe_warnings: Vec<CweWarning> = cwe_receiver.try_iter().collect();
+    cwe_warnings.sort();
+
+    let mut cwe_warnings = cwe_warnings.deduplicate_first_address();
+    cwe_warnings.add_logs(logs);
+
+    cwe_warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::SizedDomain;
+    use crate::analysis::graph::get_entry_nodes_of_subs;
+    use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+    use crate::analysis::pointer_inference::{PointerInference, State as PiState};
+    use crate::intermediate_representation::*;
+    use crate::variable;
+
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn only_bounded_from_above() {
+        let interval = |start: i64, end: i64| {
+            IntervalDomain::new(Bitvector::from_i64(start), Bitvector::from_i64(end))
+        };
+
+        // A signed 32-bit value after the check `len < 100`.
+        let value = interval(i64::from(i32::MIN), 99);
+        assert!(is_only_bounded_from_above(&value));
+        // The same value used as an array index into 4-byte elements on the stack.
+        let value = interval(i64::from(i32::MIN) * 4 - 0x40, 99 * 4 - 0x40);
+        assert!(is_only_bounded_from_above(&value));
+        // Values that are also checked against a lower bound.
+        assert!(!is_only_bounded_from_above(&interval(0, 99)));
+        // Unchecked values.
+        assert!(!is_only_bounded_from_above(&interval(
+            i64::from(i32::MIN),
+            i64::from(i32::MAX)
+        )));
+        assert!(!is_only_bounded_from_above(&IntervalDomain::new_top(
+            ByteSize::new(8)
+        )));
+    }
+
+    #[test]
+    fn entry_point_parameters_are_tainted() {
+        let mut project = Project::mock_x64();
+        let mut sub = Sub::mock("func");
+        sub.term.blocks.push(Blk::mock_with_tid("func_blk"));
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        let pi_result = PointerInference::mock(&project);
+        let size_parameters = BTreeMap::new();
+        let (cwe_sender, _) = crossbeam_channel::unbounded();
+        let entry_node = get_entry_nodes_of_subs(pi_result.get_graph())[&Tid::new("func")];
+
+        let seeding = EntryPointSeeding::AllFunctions;
+        let computation = TaComputationContext::new(
+            UntrustedSource::Parameters(&seeding),
+            &project,
+            &pi_result,
+            &size_parameters,
+            &cwe_sender,
+        )
+        .into_computation()
+        .unwrap();
+        let Some(NodeValue::Value(state)) = computation.get_node_value(entry_node) else {
+            panic!("No start state at the entry point.");
+        };
+        assert!(state.get_register_taint(&variable!("RDI:8")).is_tainted());
+        assert!(state.get_register_taint(&variable!("R9:8")).is_tainted());
+        assert!(!state.get_register_taint(&variable!("RAX:8")).is_tainted());
+
+        let seeding = EntryPointSeeding::SymbolRegex("(".to_string());
+        assert!(TaComputationContext::new(
+            UntrustedSource::Parameters(&seeding),
+            &project,
+            &pi_result,
+            &size_parameters,
+            &cwe_sender,
+        )
+        .into_computation()
+        .is_err());
+    }
+
+    #[test]
+    fn input_buffers_are_tainted() {
+        let mut project = Project::mock_x64();
+        let mut read = ExternSymbol::mock_x64("read");
+        read.parameters = ["RDI", "RSI", "RDX"]
+            .into_iter()
+            .map(|name| Arg::mock_register(name, 8))
+            .collect();
+        project
+            .program
+            .term
+            .extern_symbols
+            .insert(read.tid.clone(), read);
+        let mut sub = Sub::mock("func");
+        let mut call_blk = Blk::mock_with_tid("call_blk");
+        call_blk
+            .term
+            .jmps
+            .push(Jmp::call("call_read", "read", Some("return_blk")));
+        sub.term.blocks.push(call_blk);
+        sub.term.blocks.push(Blk::mock_with_tid("return_blk"));
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        let mut pi_result = PointerInference::mock(&project);
+        let mut pi_state = PiState::new(&variable!("RSP:8"), Tid::new("func"), BTreeSet::new());
+        pi_state.set_register(
+            &variable!("RSI:8"),
+            pi_state
+                .get_register(&variable!("RSP:8"))
+                .add_offset(&Bitvector::from_i64(-16).into()),
+        );
+        pi_result
+            .get_mut_states_at_tids()
+            .insert(Tid::new("call_read"), pi_state);
+        let analysis_results =
+            AnalysisResults::mock_from_project(&project).with_pointer_inference(Some(&pi_result));
+        let size_parameters = BTreeMap::new();
+        let (cwe_sender, _) = crossbeam_channel::unbounded();
+
+        // Without input sources the call to read is no source.
+        let source_symbols = HashSet::from(["atoi".to_string()]);
+        assert!(get_sources(&analysis_results, &source_symbols, None).is_empty());
+
+        let input_sources = InputSources::default();
+        let sources = get_sources(&analysis_results, &source_symbols, Some(&input_sources));
+        let [source @ UntrustedSource::Buffer { return_node, .. }] = sources[..] else {
+            panic!("The call to read is not a buffer source.");
+        };
+        let computation =
+            TaComputationContext::new(source, &project, &pi_result, &size_parameters, &cwe_sender)
+                .into_computation()
+                .unwrap();
+        let Some(NodeValue::Value(state)) = computation.get_node_value(return_node) else {
+            panic!("No start state after the call.");
+        };
+        assert!(state.has_memory_taint());
+        assert!(!state.get_register_taint(&variable!("RAX:8")).is_tainted());
+    }
+}
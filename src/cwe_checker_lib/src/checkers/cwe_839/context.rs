@@ -0,0 +1,264 @@
+//! Definition of the Taint Analysis for CWE839.
+//!
+//! Implementation of the [`TaintAnalysis`] trait for this CWE check. See the
+//! module documentation for more details on the algorithm and its limitations.
+
+use super::{is_only_bounded_from_above, UntrustedSource};
+
+use crate::analysis::fixpoint;
+use crate::analysis::forward_interprocedural_fixpoint::{
+    self, create_computation as fwd_fp_create_computation,
+};
+use crate::analysis::graph::{Graph as Cfg, HasCfg};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data as PiData, PointerInference};
+use crate::analysis::taint::state::State as TaState;
+use crate::analysis::taint::{self, TaintAnalysis, TaintSource};
+use crate::analysis::vsa_results::{HasVsaResult, VsaResult};
+use crate::intermediate_representation::{Def, ExternSymbol, Jmp, Project, Term, Tid};
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+
+use std::collections::BTreeMap;
+use std::convert::AsRef;
+
+/// Type of the fixpoint computation of the taint analysis.
+pub type FpComputation<'a, 'b> = fixpoint::Computation<
+    forward_interprocedural_fixpoint::GeneralizedContext<'a, TaComputationContext<'a, 'b>>,
+>;
+
+/// Type that represents the definition of the taint analysis.
+///
+/// Values of this type represent the taint analysis for a particular call to a
+/// source function.
+pub struct TaComputationContext<'a, 'b: 'a> {
+    /// Source function call that is analyzed.
+    source: UntrustedSource<'a>,
+    project: &'a Project,
+    pi_result: &'a PointerInference<'b>,
+    /// External functions mapped to the index of their size parameter.
+    size_parameters: &'a BTreeMap<String, usize>,
+    /// Used to send generated CWE warnings to the collector.
+    cwe_sender: crossbeam_channel::Sender<CweWarning>,
+}
+
+impl<'a, 'b: 'a> TaComputationContext<'a, 'b> {
+    /// Creates a new taint analysis context for the given call to a source
+    /// function.
+    pub(super) fn new(
+        source: UntrustedSource<'a>,
+        project: &'a Project,
+        pi_result: &'a PointerInference<'b>,
+        size_parameters: &'a BTreeMap<String, usize>,
+        cwe_sender: &crossbeam_channel::Sender<CweWarning>,
+    ) -> Self {
+        Self {
+            source,
+            project,
+            pi_result,
+            size_parameters,
+            cwe_sender: cwe_sender.clone(),
+        }
+    }
+
+    /// Converts the taint analysis context into a fixpoint computation.
+    ///
+    /// The returned computation can be solved to analyze this particular
+    /// source. Returns an error if the seeding strategy of entry point
+    /// parameters is invalid.
+    pub fn into_computation(self) -> Result<FpComputation<'a, 'b>, Error> {
+        match self.source {
+            UntrustedSource::Call {
+                symbol,
+                return_node,
+                ..
+            } => {
+                let node_value = NodeValue::Value(TaState::new_return(
+                    symbol,
+                    TaintSource::Custom,
+                    self.vsa_result(),
+                    return_node,
+                ));
+                let mut computation = fwd_fp_create_computation(self, None);
+                computation.set_node_value(return_node, node_value);
+
+                Ok(computation)
+            }
+            UntrustedSource::Buffer {
+                symbol,
+                return_node,
+                jmp,
+                input_sources,
+            } => {
+                let mut state = TaState::new_empty();
+                input_sources.taint_input_buffer(&mut state, self.vsa_result(), &jmp.tid, symbol);
+                let mut computation = fwd_fp_create_computation(self, None);
+                computation.set_node_value(return_node, NodeValue::Value(state));
+
+                Ok(computation)
+            }
+            UntrustedSource::Parameters(seeding) => {
+                let mut computation = fwd_fp_create_computation(self, None);
+                taint::seed_entry_points(&mut computation, seeding)?;
+
+                Ok(computation)
+            }
+        }
+    }
+
+    fn generate_cwe_warning(&self, location: &Tid, usage: &str) {
+        super::generate_cwe_warning(&self.cwe_sender, &self.source, location, usage)
+    }
+
+    /// Generates a CWE warning if the size parameter of the call is tainted and
+    /// only bounded from above.
+    fn check_size_parameter(
+        &self,
+        state: &TaState,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        index: usize,
+    ) {
+        let Some(parameter) = extern_symbol.parameters.get(index) else {
+            return;
+        };
+        let size_param = ExternSymbol {
+            parameters: vec![parameter.clone()],
+            ..extern_symbol.clone()
+        };
+        if !state.check_extern_parameters_for_taint::<false>(
+            self.vsa_result(),
+            &size_param,
+            &call.tid,
+        ) {
+            return;
+        }
+        if self
+            .vsa_result()
+            .eval_parameter_arg_at_call(&call.tid, parameter)
+            .and_then(|size| size.get_absolute_value().cloned())
+            .is_some_and(|size| is_only_bounded_from_above(&size))
+        {
+            self.generate_cwe_warning(
+                &call.tid,
+                &format!("the size parameter of {}", extern_symbol.name),
+            );
+        }
+    }
+}
+
+impl<'a> HasCfg<'a> for TaComputationContext<'a, '_> {
+    fn get_cfg(&self) -> &Cfg<'a> {
+        self.pi_result.get_graph()
+    }
+}
+
+impl HasVsaResult<PiData> for TaComputationContext<'_, '_> {
+    fn vsa_result(&self) -> &impl VsaResult<ValueDomain = PiData> {
+        self.pi_result
+    }
+}
+
+impl AsRef<Project> for TaComputationContext<'_, '_> {
+    fn as_ref(&self) -> &Project {
+        self.project
+    }
+}
+
+impl<'a> TaintAnalysis<'a> for TaComputationContext<'a, '_> {
+    /// Update taint state on call to extern function.
+    ///
+    /// Calls to functions with a tainted size parameter are checked. In all
+    /// cases the taint is removed from non-callee-saved registers.
+    fn update_extern_call(
+        &self,
+        state: &TaState,
+        call: &Term<Jmp>,
+        project: &Project,
+        extern_symbol: &ExternSymbol,
+    ) -> Option<TaState> {
+        if extern_symbol.no_return {
+            return None;
+        }
+        if let Some(index) = self.size_parameters.get(&extern_symbol.name) {
+            self.check_size_parameter(state, call, extern_symbol, *index);
+        }
+
+        let mut new_state = state.clone();
+        new_state.remove_non_callee_saved_taint(project.get_calling_convention(extern_symbol));
+
+        Some(new_state)
+    }
+
+    /// Checks memory accesses whose address depends on a tainted value.
+    ///
+    /// A CWE warning is generated if the offset of the address into one of
+    /// its target memory objects is only bounded from above.
+    fn update_def_post(
+        &self,
+        old_state: &TaState,
+        new_state: TaState,
+        def: &Term<Def>,
+    ) -> Option<TaState> {
+        if let Def::Load { address, .. } | Def::Store { address, .. } = &def.term {
+            if old_state.eval(address).is_tainted()
+                && self
+                    .vsa_result()
+                    .eval_address_at_def(&def.tid)
+                    .is_some_and(|address| {
+                        address
+                            .get_relative_values()
+                            .values()
+                            .any(is_only_bounded_from_above)
+                    })
+            {
+                self.generate_cwe_warning(&def.tid, "an array index");
+            }
+        }
+
+        if new_state.is_empty() {
+            self.handle_empty_state_out(&def.tid)
+        } else {
+            Some(new_state)
+        }
+    }
+
+    /// Propagates taint from callee to caller.
+    ///
+    /// Only the taint in return registers and in memory objects that are known
+    /// to the caller is propagated.
+    fn update_return_callee(
+        &self,
+        state: &TaState,
+        call_term: &Term<Jmp>,
+        _return_term: &Term<Jmp>,
+        calling_convention: &Option<String>,
+    ) -> Option<TaState> {
+        let (mut propagated_register_taint, mut propagated_memory_taint) =
+            state.clone().into_mem_reg_taint();
+
+        let renaming_map = self.pi_result.get_call_renaming_map(&call_term.tid);
+        propagated_memory_taint.retain(|aid, _| {
+            renaming_map.is_some_and(|renaming_map| {
+                renaming_map
+                    .get(aid)
+                    .is_some_and(|value| value.referenced_ids().next().is_some())
+            })
+        });
+
+        if let Some(calling_convention) = self
+            .project
+            .get_specific_calling_convention(calling_convention)
+        {
+            let return_registers = calling_convention.get_all_return_register();
+
+            propagated_register_taint
+                .retain(|reg, taint| return_registers.contains(&reg) && taint.is_tainted());
+        }
+
+        Some(TaState::from_mem_reg_taint(
+            propagated_register_taint,
+            propagated_memory_taint,
+        ))
+    }
+}
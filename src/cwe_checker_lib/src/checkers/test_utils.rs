@@ -0,0 +1,144 @@
+//! Mock projects for testing checks on sequences of calls to external functions.
+
+use super::prelude::*;
+use crate::analysis::pointer_inference::PointerInference;
+use crate::intermediate_representation::*;
+use crate::pipeline::AnalysisResults;
+use crate::{expr, variable};
+
+/// Builder for a project with a function `func` that calls external functions in order.
+///
+/// The call to the symbol with index `i` is the `Jmp` term `call_{name}_{i}` in the block `blk_{i}`.
+/// After the last call the function sets `RAX` to zero and returns.
+pub struct CallSequence {
+    /// The names of the called external functions.
+    symbols: Vec<String>,
+    /// The parameter register that the return value of the first call is passed in to all following calls.
+    value_register: Option<String>,
+    /// Whether the return value of each call is checked by a conditional jump to an error block.
+    checked: bool,
+}
+
+impl CallSequence {
+    /// Create a builder for a function that calls the given external functions in order.
+    pub fn new(symbols: &[&str]) -> CallSequence {
+        CallSequence {
+            symbols: symbols.iter().map(|name| name.to_string()).collect(),
+            value_register: None,
+            checked: false,
+        }
+    }
+
+    /// Pass the return value of the first call in the given parameter register to all following calls.
+    ///
+    /// The value is kept in the callee-saved register `RBX` between the calls.
+    /// The register is added to the parameters of the external functions.
+    pub fn pass_return_value(mut self, register: &str) -> CallSequence {
+        self.value_register = Some(register.to_string());
+        self
+    }
+
+    /// Check the return value of each call by a conditional jump to the block `error`.
+    ///
+    /// The check of the call with index `i` is in the block `check_{i}`.
+    pub fn check_return_values(mut self) -> CallSequence {
+        self.checked = true;
+        self
+    }
+
+    /// Build the project.
+    pub fn build(self) -> Project {
+        let mut project = Project::mock_x64();
+        let mut sub = Sub::mock("func");
+        for (index, name) in self.symbols.iter().enumerate() {
+            let mut symbol = ExternSymbol::mock_x64(name);
+            if let Some(register) = &self.value_register {
+                let parameter = Arg::mock_register(register, 8);
+                if !symbol.parameters.contains(&parameter) {
+                    symbol.parameters.push(parameter);
+                }
+            }
+            project
+                .program
+                .term
+                .extern_symbols
+                .insert(symbol.tid.clone(), symbol);
+
+            let mut blk = Blk::mock_with_tid(&format!("blk_{index}"));
+            if let Some(register) = &self.value_register {
+                if index == 1 {
+                    blk.term.defs.push(Def::assign(
+                        "save_value",
+                        variable!("RBX:8"),
+                        expr!("RAX:8"),
+                    ));
+                }
+                if index > 0 {
+                    blk.term.defs.push(Def::assign(
+                        &format!("set_param_{index}"),
+                        variable!(format!("{register}:8")),
+                        expr!("RBX:8"),
+                    ));
+                }
+            }
+            let next_blk = format!("blk_{}", index + 1);
+            let return_blk = if self.checked {
+                format!("check_{index}")
+            } else {
+                next_blk.clone()
+            };
+            blk.term.jmps.push(Jmp::call(
+                &format!("call_{name}_{index}"),
+                name,
+                Some(return_blk.as_str()),
+            ));
+            sub.term.blocks.push(blk);
+
+            if self.checked {
+                let mut check_blk = Blk::mock_with_tid(&return_blk);
+                check_blk.term.jmps = vec![
+                    Term {
+                        tid: Tid::new(format!("cbranch_{index}")),
+                        term: Jmp::CBranch {
+                            target: Tid::new("error"),
+                            condition: Expression::Var(variable!("RAX:8"))
+                                .subpiece(ByteSize::new(0), ByteSize::new(1)),
+                        },
+                    },
+                    Jmp::branch(&format!("branch_{index}"), &next_blk),
+                ];
+                sub.term.blocks.push(check_blk);
+            }
+        }
+        let mut exit_blk = Blk::mock_with_tid(&format!("blk_{}", self.symbols.len()));
+        exit_blk.term.defs.push(Def::assign(
+            "clear_return",
+            variable!("RAX:8"),
+            expr!("0:8"),
+        ));
+        exit_blk.term.jmps.push(Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(expr!("RSP:8")),
+        });
+        sub.term.blocks.push(exit_blk);
+        if self.checked {
+            sub.term.blocks.push(Blk::mock_with_tid("error"));
+        }
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+
+        project
+    }
+}
+
+/// Run the check on the project with the given Pointer Inference results and configuration.
+pub fn run_check<'a>(
+    project: &'a Project,
+    pointer_inference: &'a PointerInference<'a>,
+    check_cwe: CweModuleFn,
+    config: &serde_json::Value,
+) -> Vec<CweWarning> {
+    let analysis_results =
+        AnalysisResults::mock_from_project(project).with_pointer_inference(Some(pointer_inference));
+
+    check_cwe(&analysis_results, config, &debug::Settings::default()).into_object()
+}
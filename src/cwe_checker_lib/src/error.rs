@@ -0,0 +1,123 @@
+//! The error type of the public API of the library.
+//!
+//! Functions of the public API return a [`CweCheckerError`],
+//! so that embedders of the library can distinguish the reasons of a failure,
+//! e.g. whether the input binary could not be loaded or whether the configuration is invalid.
+//! The underlying error is kept as the [source](std::error::Error::source) of the error.
+
+use crate::intermediate_representation::IrDiagnostic;
+use crate::prelude::*;
+use std::fmt;
+
+/// An error of the public API of the library.
+#[derive(Debug)]
+pub enum CweCheckerError {
+    /// The input binary could not be read or parsed,
+    /// e.g. because the file does not exist or the binary format is not supported.
+    Loader(Error),
+    /// The binary could not be lifted to the intermediate representation,
+    /// e.g. because the execution of Ghidra failed.
+    Lifting(Error),
+    /// The intermediate representation violates invariants that the analyses assume.
+    MalformedIr {
+        /// The step of the pipeline after which the invariants were checked.
+        stage: String,
+        /// The violated invariants together with the TIDs of the offending terms.
+        diagnostics: Vec<IrDiagnostic>,
+    },
+    /// An analysis failed.
+    Analysis {
+        /// The TID of the term (usually a function) whose analysis failed, if known.
+        tid: Option<Tid>,
+        /// The reason of the failure.
+        source: Error,
+    },
+    /// A configuration file is missing or invalid.
+    Config(Error),
+}
+
+impl CweCheckerError {
+    /// Get the TIDs of the terms that caused the error.
+    pub fn tids(&self) -> Vec<&Tid> {
+        match self {
+            CweCheckerError::MalformedIr { diagnostics, .. } => diagnostics
+                .iter()
+                .map(|diagnostic| &diagnostic.location)
+                .collect(),
+            CweCheckerError::Analysis { tid: Some(tid), .. } => vec![tid],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for CweCheckerError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CweCheckerError::Loader(_) => write!(formatter, "Loading of the binary failed"),
+            CweCheckerError::Lifting(_) => write!(formatter, "Lifting of the binary failed"),
+            CweCheckerError::MalformedIr { stage, diagnostics } => {
+                write!(formatter, "The IR is malformed after {stage}:")?;
+                for diagnostic in diagnostics {
+                    write!(formatter, "\n  {diagnostic}")?;
+                }
+                Ok(())
+            }
+            CweCheckerError::Analysis { tid: Some(tid), .. } => {
+                write!(formatter, "Analysis of {tid} failed")
+            }
+            CweCheckerError::Analysis { tid: None, .. } => write!(formatter, "Analysis failed"),
+            CweCheckerError::Config(_) => write!(formatter, "Invalid configuration"),
+        }
+    }
+}
+
+impl std::error::Error for CweCheckerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CweCheckerError::Loader(source)
+            | CweCheckerError::Lifting(source)
+            | CweCheckerError::Analysis { source, .. }
+            | CweCheckerError::Config(source) => Some(source.as_ref()),
+            CweCheckerError::MalformedIr { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::IrDiagnosticKind;
+
+    #[test]
+    fn error_messages() {
+        let error = CweCheckerError::Config(anyhow!("Unable to find configuration file"));
+        assert_eq!(format!("{error}"), "Invalid configuration");
+        // The underlying error is part of the error chain.
+        let error = Error::from(error);
+        assert_eq!(
+            format!("{error:#}"),
+            "Invalid configuration: Unable to find configuration file"
+        );
+        assert!(error.downcast_ref::<CweCheckerError>().is_some());
+
+        let error = CweCheckerError::MalformedIr {
+            stage: "lifting".to_string(),
+            diagnostics: vec![IrDiagnostic {
+                location: Tid::new("sub"),
+                kind: IrDiagnosticKind::NonexistingEntryPoint,
+            }],
+        };
+        assert_eq!(error.tids(), vec![&Tid::new("sub")]);
+        assert!(format!("{error}").starts_with("The IR is malformed after lifting:\n  "));
+
+        let error = CweCheckerError::Analysis {
+            tid: Some(Tid::new("func")),
+            source: anyhow!("Fixpoint computation panicked"),
+        };
+        assert_eq!(error.tids(), vec![&Tid::new("func")]);
+        assert_eq!(
+            format!("{:#}", Error::from(error)),
+            "Analysis of func failed: Fixpoint computation panicked"
+        );
+    }
+}
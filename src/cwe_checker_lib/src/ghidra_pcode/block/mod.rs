@@ -1,5 +1,5 @@
 use super::JmpOpcode::*;
-use crate::ghidra_pcode::{Instruction, PcodeOpcode, Term};
+use crate::ghidra_pcode::{Instruction, Term};
 use crate::intermediate_representation::{Blk as IrBlock, Jmp as IrJmp, Term as IrTerm, Tid};
 
 use std::collections::HashSet;
@@ -125,8 +125,7 @@ impl<'a> OpIterator<'a> {
 
     /// Return `true` if the next P-Code term is a jump.
     fn peek_for_jmp_term(&mut self) -> bool {
-        self.peek_next_term()
-            .is_some_and(|term| matches!(term.opcode(), PcodeOpcode::Jump(_)))
+        self.peek_next_term().is_some_and(|term| term.is_jump())
     }
 
     /// Advance the iterator until one of the following occurs:
@@ -175,7 +174,10 @@ impl<'a> OpIterator<'a> {
         block: &'b mut IrTerm<IrBlock>,
     ) -> &'b mut IrTerm<IrBlock> {
         while let Some(term) = self.next_def_term(&block.tid) {
-            block.term.defs.append(&mut term.to_ir_def_terms());
+            block
+                .term
+                .defs
+                .append(&mut term.to_ir_def_terms(self.current_instr.unwrap()));
         }
 
         block
@@ -1,5 +1,6 @@
 use crate::ghidra_pcode::{PcodeProject, Varnode};
-use crate::intermediate_representation::{ExternSymbol as IrExternSymbol, Tid};
+use crate::intermediate_representation::{Arg as IrArg, ExternSymbol as IrExternSymbol, Tid};
+use crate::utils::symbol_utils::{get_symbol_alias, normalize_symbol_name};
 
 use serde::{Deserialize, Serialize};
 
@@ -18,12 +19,18 @@ pub struct ExternFunction {
 }
 
 impl ExternFunction {
+    /// Translates the extern function to an IR extern symbol.
+    ///
+    /// The name of the symbol is normalized, see
+    /// [`symbol_utils`](crate::utils::symbol_utils) for details.
+    /// The TID is still derived from the original name,
+    /// since several extern functions may have the same normalized name.
     pub fn to_ir_extern_symbol(&self, pcode_project: &PcodeProject) -> IrExternSymbol {
         let ir_expr_sp = pcode_project.stack_pointer_register.to_ir_expr();
         let mut ir_extern_symbol = IrExternSymbol {
             tid: Tid::new_external_function(&self.name),
             addresses: self.thunks.to_owned(),
-            name: self.name.clone(),
+            name: normalize_symbol_name(&self.name).to_string(),
             calling_convention: Some(self.calling_convention.clone()),
             parameters: self
                 .parameters
@@ -37,10 +44,52 @@ impl ExternFunction {
                 .unwrap_or_default(),
             no_return: self.has_no_return,
             has_var_args: self.has_var_args,
+            alias: None,
         };
 
+        resolve_alias(&mut ir_extern_symbol, pcode_project);
         apply_domain_knowledge_to(&mut ir_extern_symbol, pcode_project);
 
         ir_extern_symbol
     }
 }
+
+/// If the extern symbol is an alias of a libc function, e.g. `__printf_chk`,
+/// rename it to the aliased function and remove the additional parameters of the alias.
+///
+/// If the parameters of the alias are unknown, they are derived from the calling convention.
+/// If this is not possible, e.g. because the parameters are passed on the stack,
+/// the symbol is left unchanged.
+fn resolve_alias(ir_extern_symbol: &mut IrExternSymbol, pcode_project: &PcodeProject) {
+    let Some(alias) = get_symbol_alias(&ir_extern_symbol.name) else {
+        return;
+    };
+    if !alias.additional_params.is_empty() && ir_extern_symbol.parameters.len() < alias.num_params {
+        let ir_expr_sp = pcode_project.stack_pointer_register.to_ir_expr();
+        let Some(cconv) = ir_extern_symbol
+            .calling_convention
+            .as_ref()
+            .and_then(|cconv_name| pcode_project.calling_conventions.get(cconv_name))
+        else {
+            return;
+        };
+        let parameters: Option<Vec<IrArg>> = (0..alias.num_params)
+            .map(|index| {
+                cconv
+                    .get_integer_parameter_register(index)
+                    .map(|register| register.to_ir_arg(&ir_expr_sp))
+            })
+            .collect();
+        let Some(parameters) = parameters else {
+            return;
+        };
+        ir_extern_symbol.parameters = parameters;
+    }
+    for index in alias.additional_params.iter().rev() {
+        ir_extern_symbol.parameters.remove(*index);
+    }
+    ir_extern_symbol.alias = Some(std::mem::replace(
+        &mut ir_extern_symbol.name,
+        alias.name.to_string(),
+    ));
+}
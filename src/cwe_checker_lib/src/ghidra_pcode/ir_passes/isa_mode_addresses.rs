@@ -0,0 +1,349 @@
+use super::prelude::*;
+use crate::intermediate_representation::{Jmp, Tid};
+
+use std::collections::HashMap;
+
+/// Normalizes call targets with the ISA mode bit set.
+///
+/// On ARM and MIPS, the lowest bit of a code address selects the instruction
+/// set that is used after a control flow transfer to it. Thus calls into Thumb,
+/// MIPS16e or microMIPS code may refer to the function at `address + 1`. This
+/// pass replaces such call targets with the TID of the function at the even
+/// address.
+///
+/// - Rewrites targets of direct calls.
+/// - Rewrites potential targets of indirect calls.
+/// - Rewrites entry points.
+/// - Rewrites the stub addresses of external functions.
+///
+/// Targets are only rewritten if there is no function at the odd address but
+/// there is one at the even address. The pass does nothing for other
+/// architectures.
+///
+/// # Guarantees
+///
+/// - Preserves: All CFT targets exist.
+///
+/// # Postconditions
+///
+/// - No call targets a nonexistent function at an odd address if there is a
+///   function at the corresponding even address.
+///
+/// # Run After
+pub struct IsaModeAddressesPass {
+    has_isa_mode_bit: bool,
+}
+
+impl IsaModeAddressesPass {
+    /// Returns true iff the lowest bit of code addresses selects the ISA mode
+    /// on the given architecture.
+    fn has_isa_mode_bit(cpu_arch: &str) -> bool {
+        cpu_arch.starts_with("ARM") || cpu_arch.starts_with("MIPS")
+    }
+
+    /// Returns the function at the even address if `target` is an address of
+    /// it with the ISA mode bit set.
+    fn get_normalized_target<'a>(
+        program: &Program,
+        fn_address_to_tid_map: &'a HashMap<u64, Tid>,
+        target: &Tid,
+    ) -> Option<&'a Tid> {
+        let address = u64::try_from(target.address()).ok()?;
+
+        if address % 2 == 0 || program.subs.contains_key(target) {
+            return None;
+        }
+
+        fn_address_to_tid_map.get(&(address - 1))
+    }
+
+    /// Clears the ISA mode bit of the given hexadecimal address string.
+    ///
+    /// Returns `None` if the bit is not set. The formatting of the address
+    /// is preserved.
+    fn clear_isa_mode_bit(address: &str) -> Option<String> {
+        let mut chars: Vec<char> = address.chars().collect();
+        let last_digit = chars.last()?.to_digit(16)?;
+
+        if last_digit % 2 == 0 {
+            return None;
+        }
+        let cleared_digit = char::from_digit(last_digit - 1, 16)?;
+        *chars.last_mut().unwrap() = if chars.last().unwrap().is_ascii_uppercase() {
+            cleared_digit.to_ascii_uppercase()
+        } else {
+            cleared_digit
+        };
+
+        Some(chars.into_iter().collect())
+    }
+}
+
+impl IrPass for IsaModeAddressesPass {
+    const NAME: &'static str = "IsaModeAddressesPass";
+    const DBG_IR_FORM: debug::IrForm = debug::IrForm::IsaModeAddressesNormalized;
+
+    type Input = Program;
+    type ConstructionInput = String;
+
+    fn new(cpu_arch: &Self::ConstructionInput) -> Self {
+        Self {
+            has_isa_mode_bit: Self::has_isa_mode_bit(cpu_arch),
+        }
+    }
+
+    fn run(&mut self, program: &mut Self::Input) -> Vec<LogMessage> {
+        let mut logs = Vec::new();
+
+        if !self.has_isa_mode_bit {
+            return logs;
+        }
+
+        let fn_address_to_tid_map: HashMap<u64, Tid> = program
+            .subs
+            .keys()
+            .filter_map(|fn_tid| Some((u64::try_from(fn_tid.address()).ok()?, fn_tid.clone())))
+            .collect();
+        let mut replacements: HashMap<Tid, Tid> = HashMap::new();
+
+        for fn_tid in program
+            .jmps()
+            .filter_map(|j| match &j.term {
+                Jmp::Call { target, .. } => Some(target),
+                _ => None,
+            })
+            .chain(program.blocks().flat_map(|b| {
+                b.ind_call_targets()
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+            }))
+            .chain(program.entry_points.iter())
+        {
+            if let Some(normalized_tid) =
+                Self::get_normalized_target(program, &fn_address_to_tid_map, fn_tid)
+            {
+                replacements.insert(fn_tid.clone(), normalized_tid.clone());
+            }
+        }
+
+        // Rewrite direct calls.
+        for j in program.jmps_mut() {
+            let Jmp::Call { target, .. } = &mut j.term else {
+                continue;
+            };
+            let Some(normalized_tid) = replacements.get(target) else {
+                continue;
+            };
+
+            logs.push(LogMessage::new_info(format!(
+                "{}: Replaced ISA mode call target {} @ {} with {}.",
+                Self::NAME,
+                target,
+                j.tid,
+                normalized_tid
+            )));
+
+            *target = normalized_tid.clone();
+        }
+
+        // Rewrite potential targets of indirect calls.
+        for b in program.blocks_mut() {
+            let Some(ind_call_targets) = b.ind_call_targets_targets_mut() else {
+                continue;
+            };
+            for target in ind_call_targets {
+                if let Some(normalized_tid) = replacements.get(target) {
+                    *target = normalized_tid.clone();
+                }
+            }
+        }
+
+        // Rewrite entry points.
+        program.entry_points = std::mem::take(&mut program.entry_points)
+            .into_iter()
+            .map(|fn_tid| replacements.get(&fn_tid).cloned().unwrap_or(fn_tid))
+            .collect();
+
+        // Rewrite stub addresses of external functions.
+        for ext_fn in program.extern_symbols.values_mut() {
+            for address in ext_fn.addresses.iter_mut() {
+                let Some(normalized_address) = Self::clear_isa_mode_bit(address) else {
+                    continue;
+                };
+                if program.subs.contains_key(&Tid::new_function(&*address))
+                    || !program
+                        .subs
+                        .contains_key(&Tid::new_function(&normalized_address))
+                {
+                    continue;
+                }
+
+                logs.push(LogMessage::new_info(format!(
+                    "{}: Replaced ISA mode stub address {} of {} with {}.",
+                    Self::NAME,
+                    address,
+                    ext_fn.name,
+                    normalized_address
+                )));
+
+                *address = normalized_address;
+            }
+        }
+
+        logs
+    }
+
+    fn assert_postconditions(cpu_arch: &Self::ConstructionInput, program: &Self::Input) {
+        if !Self::has_isa_mode_bit(cpu_arch) {
+            return;
+        }
+        let fn_address_to_tid_map: HashMap<u64, Tid> = program
+            .subs
+            .keys()
+            .filter_map(|fn_tid| Some((u64::try_from(fn_tid.address()).ok()?, fn_tid.clone())))
+            .collect();
+
+        for j in program.jmps() {
+            if let Jmp::Call { target, .. } = &j.term {
+                assert!(
+                    Self::get_normalized_target(program, &fn_address_to_tid_map, target).is_none()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::{Blk, Sub, Term};
+
+    fn mock_sub(address: &str, jmps: Vec<Term<Jmp>>) -> Term<Sub> {
+        let mut blk = Blk::new();
+        blk.add_jumps(jmps);
+
+        Term::new(
+            Tid::new_function(address),
+            Sub::new::<_, str>(
+                &format!("sub_{address}"),
+                vec![Term::new(Tid::new_block(address, 0), blk)],
+                None,
+            ),
+        )
+    }
+
+    fn mock_call(index: u64, target: &str) -> Term<Jmp> {
+        Term::new(
+            Tid::new_instr("00040000", index),
+            Jmp::Call {
+                target: Tid::new_function(target),
+                return_: None,
+            },
+        )
+    }
+
+    /// Returns a program with a function at an even and a function at an odd
+    /// address. Both are called and the first one is an entry point with the
+    /// ISA mode bit set. The stub of `malloc` is also referenced with the ISA
+    /// mode bit set.
+    fn mock_program() -> Program {
+        let mut program = Program::mock_x64();
+        for sub in [
+            mock_sub("00010000", Vec::new()),
+            mock_sub("00020001", Vec::new()),
+            mock_sub("00030000", Vec::new()),
+            mock_sub(
+                "00040000",
+                vec![mock_call(0, "00010001"), mock_call(1, "00020001")],
+            ),
+        ] {
+            program.subs.insert(sub.tid.clone(), sub);
+        }
+        program.entry_points.insert(Tid::new_function("00010001"));
+        program
+            .extern_symbols
+            .get_mut(&Tid::new("malloc"))
+            .unwrap()
+            .addresses = vec!["00030001".to_string()];
+
+        program
+    }
+
+    fn get_call_targets(program: &Program) -> Vec<Tid> {
+        program
+            .jmps()
+            .filter_map(|j| match &j.term {
+                Jmp::Call { target, .. } => Some(target.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clear_isa_mode_bit() {
+        assert_eq!(
+            IsaModeAddressesPass::clear_isa_mode_bit("0001000f"),
+            Some("0001000e".to_string())
+        );
+        assert_eq!(
+            IsaModeAddressesPass::clear_isa_mode_bit("0001000F"),
+            Some("0001000E".to_string())
+        );
+        assert_eq!(IsaModeAddressesPass::clear_isa_mode_bit("00010000"), None);
+    }
+
+    #[test]
+    fn thumb_call_targets() {
+        let mut program = mock_program();
+        let cpu_arch = "ARM:LE:32:v8T".to_string();
+
+        let logs = IsaModeAddressesPass::new(&cpu_arch).run(&mut program);
+
+        assert_eq!(logs.len(), 2);
+        assert_eq!(
+            get_call_targets(&program),
+            vec![Tid::new_function("00010000"), Tid::new_function("00020001")]
+        );
+        assert!(program
+            .entry_points
+            .contains(&Tid::new_function("00010000")));
+        assert!(!program
+            .entry_points
+            .contains(&Tid::new_function("00010001")));
+        assert_eq!(
+            program.extern_symbols[&Tid::new("malloc")].addresses,
+            vec!["00030000".to_string()]
+        );
+        IsaModeAddressesPass::assert_postconditions(&cpu_arch, &program);
+    }
+
+    #[test]
+    fn mips16_call_targets() {
+        let mut program = mock_program();
+        let cpu_arch = "MIPS:BE:32:default".to_string();
+
+        let logs = IsaModeAddressesPass::new(&cpu_arch).run(&mut program);
+
+        assert_eq!(logs.len(), 2);
+        assert_eq!(
+            get_call_targets(&program),
+            vec![Tid::new_function("00010000"), Tid::new_function("00020001")]
+        );
+        assert_eq!(
+            program.extern_symbols[&Tid::new("malloc")].addresses,
+            vec!["00030000".to_string()]
+        );
+        IsaModeAddressesPass::assert_postconditions(&cpu_arch, &program);
+    }
+
+    #[test]
+    fn no_isa_mode_bit() {
+        let mut program = mock_program();
+
+        let logs = IsaModeAddressesPass::new(&"x86:LE:64:default".to_string()).run(&mut program);
+
+        assert!(logs.is_empty());
+        assert_eq!(program, mock_program());
+    }
+}
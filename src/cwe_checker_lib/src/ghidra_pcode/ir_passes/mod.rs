@@ -3,6 +3,9 @@
 use crate::utils::debug;
 use crate::utils::log::LogMessage;
 
+mod isa_mode_addresses;
+pub use isa_mode_addresses::*;
+
 mod single_target_indirect_calls;
 pub use single_target_indirect_calls::*;
 
@@ -115,6 +115,13 @@ impl PcodeProject {
         };
         debug_settings.print(&ir_program, debug::Stage::Ir(debug::IrForm::Early));
 
+        run_ir_pass![
+            ir_program,
+            self.cpu_arch,
+            IsaModeAddressesPass,
+            logs,
+            debug_settings
+        ];
         run_ir_pass![
             ir_program,
             (),
@@ -137,6 +144,7 @@ impl PcodeProject {
         run_ir_pass!(ir_program, PatchCfPass, logs, debug_settings);
         run_ir_pass![ir_program, (), EntryPointsPass, logs, debug_settings];
 
+        debug_assert_postconditions![ir_program, self.cpu_arch, IsaModeAddressesPass];
         debug_assert_postconditions![ir_program, (), SingleTargetIndirectCallsPass];
         debug_assert_postconditions![ir_program, (), ReorderFnBlocksPass];
         debug_assert_postconditions!(ir_program, ReplaceCallsToExtFnsPass);
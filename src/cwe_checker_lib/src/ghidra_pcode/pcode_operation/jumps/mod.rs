@@ -20,8 +20,11 @@ impl PcodeOperation {
     }
 
     /// Returns true iff this is a jump operation.
+    ///
+    /// User-defined operations that compute a value are not jumps, see
+    /// [`PcodeOperation::is_user_defined_expression`].
     pub fn is_jump(&self) -> bool {
-        matches!(self.pcode_mnemonic, PcodeOpcode::Jump(_))
+        matches!(self.pcode_mnemonic, PcodeOpcode::Jump(_)) && !self.is_user_defined_expression()
     }
 
     /// Returns true iff this is a direct jump operation.
@@ -87,6 +87,16 @@ impl PcodeOperation {
         )
     }
 
+    /// Returns true iff this is a `CALLOTHER` operation with an output.
+    ///
+    /// Ghidra uses such user-defined operations for instructions whose
+    /// semantics are not described by P-Code, e.g. many SIMD instructions.
+    /// They compute a value and continue with the next operation, so they are
+    /// translated into assignments of unknown values instead of jumps.
+    pub fn is_user_defined_expression(&self) -> bool {
+        matches!(self.opcode(), PcodeOpcode::Jump(JmpOpcode::CALLOTHER)) && self.output.is_some()
+    }
+
     /// Returns the first input varnode to this operation.
     pub fn input0(&self) -> Option<&Varnode> {
         self.input0.as_ref()
@@ -351,6 +361,26 @@ impl PcodeOperation {
         self.wrap_in_ir_def_assign_or_store(ir_expr)
     }
 
+    /// Translates a user-defined operation with an output into an [`IrDef`]
+    /// containing an [`IrExpression::Unknown`] value of the size of the output.
+    ///
+    /// The description of the unknown value is given by the mnemonic of the
+    /// corresponding assembly instruction.
+    pub fn to_ir_def_user_defined_op(&self, description: &str) -> Option<IrDef> {
+        assert!(self.is_user_defined_expression());
+
+        let ir_expr = IrExpression::Unknown {
+            description: description.to_string(),
+            size: self
+                .output()
+                .expect("No output for user-defined operation.")
+                .size()
+                .into(),
+        };
+
+        self.wrap_in_ir_def_assign_or_store(ir_expr)
+    }
+
     /// Creates an [`IrDef::Assign`] or [`IrDef::Store`] operation depending on
     /// whether an implicit memory write is performed.
     ///
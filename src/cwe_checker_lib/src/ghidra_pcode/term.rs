@@ -255,7 +255,8 @@ impl Term {
     /// performs implicit loads from ram.
     ///
     /// Panics if the operation is not an expression operation.
-    pub fn to_ir_def_terms(&self) -> Vec<IrTerm<IrDef>> {
+    /// `instr` must be the instruction that contains this term.
+    pub fn to_ir_def_terms(&self, instr: &Instruction) -> Vec<IrTerm<IrDef>> {
         assert!(!self.operation.uses_stack_varnode());
 
         let mut ir_terms = vec![];
@@ -267,34 +268,38 @@ impl Term {
                 term_to_convert.make_inputs_explicitly_loaded_and_return_ir_def_load_terms();
             ir_terms.append(&mut explicit_loads);
 
-            if let Some(ir_term) = term_to_convert.to_ir_def_term_no_implicit_load() {
+            if let Some(ir_term) = term_to_convert.to_ir_def_term_no_implicit_load(instr) {
                 ir_terms.push(ir_term);
             }
         } else if self.is_ghidra_unimplemented() {
             // We silently ignore instructions that are not implemented in
             // Ghidra. Happens only for exotic arches, e.g., m68k.
-        } else if let Some(ir_term) = self.to_ir_def_term_no_implicit_load() {
+        } else if let Some(ir_term) = self.to_ir_def_term_no_implicit_load(instr) {
             ir_terms.push(ir_term);
         }
 
         ir_terms
     }
 
-    fn to_ir_def_term_no_implicit_load(&self) -> Option<IrTerm<IrDef>> {
+    fn to_ir_def_term_no_implicit_load(&self, instr: &Instruction) -> Option<IrTerm<IrDef>> {
         assert!(!self.has_implicit_load());
 
-        let ir_def = match self.unwrap_expr_opcode() {
-            ExpressionOpcode::LOAD => self.to_ir_def_load(),
-            ExpressionOpcode::STORE => self.to_ir_def_store(),
-            ExpressionOpcode::COPY => self.to_ir_def_assign(),
-            ExpressionOpcode::SUBPIECE => self.to_ir_def_subpiece(),
-            expr_type if expr_type.is_ir_unop() => self.to_ir_def_unop(),
-            expr_type if expr_type.is_ir_biop() => self.to_ir_def_biop(),
-            expr_type if expr_type.is_ir_cast() => self.to_ir_def_castop(),
-            _ => panic!(
-                "Unexpected pcode expression operator while translating: {}",
-                self
-            ),
+        let ir_def = if self.is_user_defined_expression() {
+            self.to_ir_def_user_defined_op(instr.mnemonic())
+        } else {
+            match self.unwrap_expr_opcode() {
+                ExpressionOpcode::LOAD => self.to_ir_def_load(),
+                ExpressionOpcode::STORE => self.to_ir_def_store(),
+                ExpressionOpcode::COPY => self.to_ir_def_assign(),
+                ExpressionOpcode::SUBPIECE => self.to_ir_def_subpiece(),
+                expr_type if expr_type.is_ir_unop() => self.to_ir_def_unop(),
+                expr_type if expr_type.is_ir_biop() => self.to_ir_def_biop(),
+                expr_type if expr_type.is_ir_cast() => self.to_ir_def_castop(),
+                _ => panic!(
+                    "Unexpected pcode expression operator while translating: {}",
+                    self
+                ),
+            }
         };
 
         ir_def.map(|ir_def| IrTerm {
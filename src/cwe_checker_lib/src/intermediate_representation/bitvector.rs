@@ -282,7 +282,10 @@ impl BitvectorExtended for Bitvector {
             ))
         } else {
             let result = self.clone().into_checked_mul(rhs).unwrap();
-            if result.clone().into_checked_sdiv(self).unwrap() != *rhs {
+            // The division does not detect the overflow of `-1 * MIN`, since `MIN / -1` overflows, too.
+            if result.clone().into_checked_sdiv(self).unwrap() != *rhs
+                || (self.is_all_set() && *rhs == Bitvector::signed_min_value(rhs.width()))
+            {
                 Ok((result, true))
             } else {
                 Ok((result, false))
@@ -345,4 +348,25 @@ mod tests {
             Some(Bitvector::zero(ByteSize::new(8).into()))
         );
     }
+
+    #[test]
+    fn overflow_checked_mult() {
+        let min = Bitvector::signed_min_value(ByteSize::new(4).into());
+        let minus_one = Bitvector::from_i32(-1);
+
+        assert_eq!(
+            minus_one.signed_mult_with_overflow_flag(&min).unwrap(),
+            (min.clone(), true)
+        );
+        assert_eq!(
+            min.signed_mult_with_overflow_flag(&minus_one).unwrap(),
+            (min.clone(), true)
+        );
+        assert_eq!(
+            minus_one
+                .signed_mult_with_overflow_flag(&Bitvector::from_i32(3))
+                .unwrap(),
+            (Bitvector::from_i32(-3), false)
+        );
+    }
 }
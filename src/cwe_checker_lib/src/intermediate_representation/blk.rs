@@ -44,6 +44,11 @@ pub struct Blk {
     /// contain targets that are infeasible at runtime and miss targets that may
     /// be observed.
     indirect_control_flow_targets: Option<Box<IndirectCfTargets>>,
+    /// Iff the basic block ends in a call that may throw an exception,
+    /// this field contains the block of the exception handler (landing pad)
+    /// that is executed in that case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exception_handler: Option<Tid>,
 }
 
 /// Possible targets of an indirect control flow transfer.
@@ -213,6 +218,7 @@ impl Blk {
             defs: vec![],
             jmps: vec![],
             indirect_control_flow_targets: None,
+            exception_handler: None,
         }
     }
 
@@ -309,6 +315,19 @@ impl Blk {
         self
     }
 
+    /// Sets the exception handler (landing pad) for the call at the end of this block.
+    pub fn set_exception_handler(&mut self, handler: Tid) -> &mut Self {
+        self.exception_handler = Some(handler);
+
+        self
+    }
+
+    /// Returns the exception handler (landing pad) for the call at the end of
+    /// this block.
+    pub fn exception_handler(&self) -> Option<&Tid> {
+        self.exception_handler.as_ref()
+    }
+
     /// Returns the possible targets of the indirect control flow transfer at
     /// the end of this block.
     pub fn ind_control_flow_targets(&self) -> Option<&IndirectCfTargets> {
@@ -511,6 +530,7 @@ impl Term<Blk> {
                 defs: Vec::with_capacity(0),
                 jmps,
                 indirect_control_flow_targets: None,
+                exception_handler: None,
             },
         }
     }
@@ -533,6 +553,7 @@ impl Term<Blk> {
                 defs: Vec::with_capacity(0),
                 jmps,
                 indirect_control_flow_targets: None,
+                exception_handler: None,
             },
         }
     }
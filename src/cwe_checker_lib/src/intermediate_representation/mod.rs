@@ -32,6 +32,8 @@ mod project;
 pub use project::*;
 mod runtime_memory_image;
 pub use runtime_memory_image::*;
+mod pseudo_assembly;
+pub use pseudo_assembly::*;
 #[cfg(test)]
 #[macro_use]
 mod macros;
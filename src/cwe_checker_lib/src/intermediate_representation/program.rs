@@ -2,7 +2,7 @@
 
 use super::{Blk, Def, ExternSymbol, Jmp, Sub, Variable};
 use crate::prelude::*;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 
 /// The `Program` structure represents a disassembled binary.
@@ -175,6 +175,30 @@ impl Program {
         None
     }
 
+    /// Returns a map from the addresses of all blocks and instructions of the program
+    /// to the functions containing them.
+    ///
+    /// An address can be contained in more than one function if functions share code.
+    pub fn get_functions_at_addresses(&self) -> HashMap<u64, BTreeSet<Tid>> {
+        let mut functions_at_addresses: HashMap<u64, BTreeSet<Tid>> = HashMap::new();
+        for sub in self.subs.values() {
+            for block in sub.term.blocks.iter() {
+                let tids = std::iter::once(&block.tid)
+                    .chain(block.term.defs.iter().map(|def| &def.tid))
+                    .chain(block.term.jmps.iter().map(|jmp| &jmp.tid));
+                for tid in tids {
+                    if let Ok(address) = u64::try_from(tid.address()) {
+                        functions_at_addresses
+                            .entry(address)
+                            .or_default()
+                            .insert(sub.tid.clone());
+                    }
+                }
+            }
+        }
+        functions_at_addresses
+    }
+
     /// Asserts some invariants that are not covered elsewhere.
     pub fn debug_assert_invariants(&self) {
         // Check that the mapping from function TIDs to function terms is
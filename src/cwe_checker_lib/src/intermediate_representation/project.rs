@@ -8,6 +8,27 @@ use std::collections::{BTreeMap, BTreeSet};
 pub mod ir_passes;
 use ir_passes::*;
 
+mod serialization;
+pub use serialization::*;
+
+mod validation;
+pub use validation::*;
+
+mod calling_convention_resolver;
+pub use calling_convention_resolver::*;
+
+/// The configuration of the optional normalization passes for the IR.
+///
+/// The normalization passes are run after the IR optimization
+/// and are configured in the `Normalization` section of the configuration file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct NormalizationConfig {
+    /// Propagate block-local temporary registers into their uses,
+    /// fold constant expressions and remove dead temporaries.
+    #[serde(default)]
+    pub temporary_propagation: bool,
+}
+
 /// The `Project` struct is the main data structure representing a binary.
 ///
 /// It contains information about the disassembled binary
@@ -101,6 +122,7 @@ impl WithLogs<Project> {
             logs,
             debug_settings,
         ];
+        self.debug_assert_valid(IntraproceduralDeadBlockElimPass::NAME);
         run_ir_pass![
             self.program.term,
             (),
@@ -108,6 +130,7 @@ impl WithLogs<Project> {
             logs,
             debug_settings,
         ];
+        self.debug_assert_valid(InputExpressionPropagationPass::NAME);
         run_ir_pass![
             self.program.term,
             (),
@@ -115,6 +138,7 @@ impl WithLogs<Project> {
             logs,
             debug_settings,
         ];
+        self.debug_assert_valid(TrivialExpressionSubstitutionPass::NAME);
         run_ir_pass![
             self.program.term,
             self.register_set,
@@ -122,6 +146,7 @@ impl WithLogs<Project> {
             logs,
             debug_settings,
         ];
+        self.debug_assert_valid(DeadVariableElimPass::NAME);
         run_ir_pass![
             self.program.term,
             (),
@@ -129,6 +154,7 @@ impl WithLogs<Project> {
             logs,
             debug_settings,
         ];
+        self.debug_assert_valid(ControlFlowPropagationPass::NAME);
         run_ir_pass![
             self.program.term,
             self,
@@ -136,6 +162,7 @@ impl WithLogs<Project> {
             logs,
             debug_settings,
         ];
+        self.debug_assert_valid(StackPointerAlignmentSubstitutionPass::NAME);
 
         debug_assert_postconditions![self.program.term, (), IntraproceduralDeadBlockElimPass];
         debug_assert_postconditions![self.program.term, (), InputExpressionPropagationPass];
@@ -150,6 +177,41 @@ impl WithLogs<Project> {
 
         self.add_logs(logs)
     }
+
+    /// Assert that the IR satisfies the invariants checked by [`Project::validate`]
+    /// after the given pass.
+    ///
+    /// Only active if debug assertions are enabled.
+    fn debug_assert_valid(&self, pass_name: &str) {
+        if cfg!(debug_assertions) {
+            let diagnostics = self.validate();
+            assert!(
+                diagnostics.is_empty(),
+                "The IR is malformed after {pass_name}: {diagnostics:?}"
+            );
+        }
+    }
+
+    /// Run the normalization passes that are enabled in the given configuration.
+    ///
+    /// The normalization should happen before the control flow graph of the program is generated.
+    pub fn normalize(&mut self, config: &NormalizationConfig, debug_settings: &debug::Settings) {
+        let mut logs = Vec::new();
+
+        if config.temporary_propagation {
+            run_ir_pass![
+                self.program.term,
+                (),
+                TemporaryPropagationPass,
+                logs,
+                debug_settings,
+            ];
+            self.debug_assert_valid(TemporaryPropagationPass::NAME);
+            debug_assert_postconditions![self.program.term, (), TemporaryPropagationPass];
+        }
+
+        self.add_logs(logs)
+    }
 }
 
 // TODO: Fix tests.
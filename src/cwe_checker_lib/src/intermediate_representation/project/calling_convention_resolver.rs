@@ -0,0 +1,537 @@
+//! Architecture-specific knowledge about argument and return value locations.
+//!
+//! The calling conventions provided by Ghidra only list the parameter and
+//! return registers. Details like the return address pushed by x86 calls or
+//! the stack space reserved for register arguments depend on the CPU
+//! architecture and are resolved by the [`CallingConventionResolver`].
+
+use super::*;
+
+/// The CPU architecture families with differing calling convention details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CpuArchitecture {
+    /// Intel x86, both 32 and 64 bit.
+    X86,
+    /// 32-bit ARM.
+    Arm,
+    /// 64-bit ARM.
+    Aarch64,
+    /// MIPS, both 32 and 64 bit.
+    Mips,
+    /// PowerPC, both 32 and 64 bit.
+    PowerPc,
+    /// RISC-V, both 32 and 64 bit.
+    RiscV,
+    /// Any other architecture.
+    Other,
+}
+
+impl CpuArchitecture {
+    /// Determines the architecture family from the architecture name of a
+    /// project, e.g. from the Ghidra language ID `x86:LE:64:default`.
+    pub fn from_name(cpu_architecture: &str) -> CpuArchitecture {
+        let name = cpu_architecture.to_ascii_lowercase();
+        if name.starts_with("x86") {
+            CpuArchitecture::X86
+        } else if name.starts_with("aarch64") {
+            CpuArchitecture::Aarch64
+        } else if name.starts_with("arm") {
+            CpuArchitecture::Arm
+        } else if name.starts_with("mips") {
+            CpuArchitecture::Mips
+        } else if name.starts_with("powerpc") || name.starts_with("ppc") {
+            CpuArchitecture::PowerPc
+        } else if name.starts_with("riscv") {
+            CpuArchitecture::RiscV
+        } else {
+            CpuArchitecture::Other
+        }
+    }
+}
+
+/// The families of calling conventions that handle arguments and return
+/// values differently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum CallingConventionFamily {
+    /// The stack-based x86-32 conventions `__cdecl`, `__stdcall` and
+    /// `__thiscall`.
+    Cdecl,
+    /// Microsoft `__fastcall`. On x86-64 this is the Microsoft x64 calling
+    /// convention, which reserves 32 bytes of shadow space for the register
+    /// arguments on the stack.
+    Fastcall,
+    /// The System V AMD64 ABI.
+    SystemV,
+    /// The 32-bit ARM AAPCS.
+    Aapcs,
+    /// The 64-bit ARM AAPCS64.
+    Aapcs64,
+    /// The 32-bit MIPS o32 ABI, which reserves 16 bytes on the stack for the
+    /// register arguments.
+    MipsO32,
+    /// The 64-bit MIPS n64 ABI.
+    MipsN64,
+    /// Any other calling convention.
+    Other,
+}
+
+/// Resolves the locations of arguments and return values for a calling
+/// convention of a project.
+#[derive(Clone, Copy, Debug)]
+pub struct CallingConventionResolver<'a> {
+    family: CallingConventionFamily,
+    calling_convention: &'a CallingConvention,
+    stack_pointer_register: &'a Variable,
+    cpu_architecture: CpuArchitecture,
+}
+
+impl<'a> CallingConventionResolver<'a> {
+    /// Creates a resolver for the given calling convention of the given
+    /// project.
+    pub fn new(project: &'a Project, calling_convention: &'a CallingConvention) -> Self {
+        let cpu_architecture = project.get_cpu_architecture();
+        let is_64_bit = project.get_pointer_bytesize() == ByteSize::new(8);
+        let family = match cpu_architecture {
+            CpuArchitecture::X86 if calling_convention.name == "__fastcall" => {
+                CallingConventionFamily::Fastcall
+            }
+            // Ghidra names the Microsoft x64 calling convention of member
+            // functions `__thiscall`.
+            CpuArchitecture::X86 if is_64_bit && calling_convention.name == "__thiscall" => {
+                CallingConventionFamily::Fastcall
+            }
+            CpuArchitecture::X86 if is_64_bit => CallingConventionFamily::SystemV,
+            CpuArchitecture::X86 => CallingConventionFamily::Cdecl,
+            CpuArchitecture::Arm => CallingConventionFamily::Aapcs,
+            CpuArchitecture::Aarch64 => CallingConventionFamily::Aapcs64,
+            CpuArchitecture::Mips if is_64_bit => CallingConventionFamily::MipsN64,
+            CpuArchitecture::Mips => CallingConventionFamily::MipsO32,
+            _ => CallingConventionFamily::Other,
+        };
+        CallingConventionResolver {
+            family,
+            calling_convention,
+            stack_pointer_register: &project.stack_pointer_register,
+            cpu_architecture,
+        }
+    }
+
+    /// Returns the calling convention that is resolved.
+    pub fn get_calling_convention(&self) -> &'a CallingConvention {
+        self.calling_convention
+    }
+
+    /// Returns the size of the return address that the call pushes onto the
+    /// stack, i.e. the offset of the caller's stack frame relative to the
+    /// stack pointer at the start of the callee.
+    pub fn get_return_address_size(&self) -> ByteSize {
+        get_return_address_size(self.cpu_architecture, self.stack_pointer_register)
+    }
+
+    /// Returns the offset of the first stack argument relative to the stack
+    /// pointer at the start of the callee.
+    ///
+    /// Some calling conventions reserve stack space for the register
+    /// arguments before the first stack argument.
+    fn get_stack_parameter_offset(&self) -> i64 {
+        let reserved_space = match self.family {
+            CallingConventionFamily::Fastcall
+                if self.stack_pointer_register.size == ByteSize::new(8) =>
+            {
+                32
+            }
+            CallingConventionFamily::MipsO32 => 16,
+            _ => 0,
+        };
+        u64::from(self.get_return_address_size()) as i64 + reserved_space
+    }
+
+    /// Returns true if variadic floating point arguments are passed in integer
+    /// registers instead of float registers.
+    pub fn passes_variadic_floats_in_integer_registers(&self) -> bool {
+        self.cpu_architecture == CpuArchitecture::RiscV
+    }
+
+    /// Returns the location of the integer or pointer argument with the given
+    /// index if all previous arguments are integers or pointers.
+    pub fn get_integer_parameter_arg(&self, index: usize) -> Arg {
+        let registers = &self.calling_convention.integer_parameter_register;
+        match registers.get(index) {
+            Some(register) => Arg::from_var(register.clone(), None),
+            None => {
+                let pointer_size = self.stack_pointer_register.size;
+                let offset = self.get_stack_parameter_offset()
+                    + ((index - registers.len()) as u64 * u64::from(pointer_size)) as i64;
+                create_stack_arg(pointer_size, offset, None, self.stack_pointer_register)
+            }
+        }
+    }
+
+    /// Returns the locations of variadic parameters with the given data types
+    /// and sizes that follow the given fixed parameters.
+    ///
+    /// Panics for data types that are not supported for variadic parameters.
+    pub fn get_variadic_parameter_locations(
+        &self,
+        fixed_parameters: &[Arg],
+        variadic_parameters: &[(Datatype, ByteSize)],
+    ) -> Vec<Arg> {
+        let calling_convention = self.calling_convention;
+        let mut var_args: Vec<Arg> = Vec::new();
+        let mut float_arg_register_count = calling_convention.float_parameter_register.len();
+        let mut stack_offset = self.get_stack_parameter_offset();
+        let floats_in_integer_register = self.passes_variadic_floats_in_integer_registers();
+        let mut integer_arg_register_count = if calling_convention.integer_parameter_register.len()
+            >= fixed_parameters.len()
+        {
+            calling_convention.integer_parameter_register.len() - fixed_parameters.len()
+        } else {
+            for param in fixed_parameters.iter() {
+                if let Ok(offset) = param.eval_stack_offset() {
+                    let offset_after = offset.try_to_u64().unwrap() + u64::from(param.bytesize());
+                    stack_offset = std::cmp::max(stack_offset, offset_after as i64);
+                }
+            }
+            0
+        };
+
+        for (data_type, size) in variadic_parameters.iter() {
+            match data_type {
+                Datatype::Double if !floats_in_integer_register => {
+                    if float_arg_register_count > 0 {
+                        let expr = calling_convention.float_parameter_register[calling_convention
+                            .float_parameter_register
+                            .len()
+                            - float_arg_register_count]
+                            .clone();
+
+                        var_args.push(Arg::Register {
+                            expr,
+                            data_type: Some(data_type.clone()),
+                        });
+
+                        float_arg_register_count -= 1;
+                    } else {
+                        var_args.push(create_stack_arg(
+                            *size,
+                            stack_offset,
+                            Some(data_type.clone()),
+                            self.stack_pointer_register,
+                        ));
+                        stack_offset += u64::from(*size) as i64
+                    }
+                }
+                Datatype::Integer | Datatype::Pointer | Datatype::Char | Datatype::Double => {
+                    if integer_arg_register_count > 0 {
+                        let register = calling_convention.integer_parameter_register
+                            [calling_convention.integer_parameter_register.len()
+                                - integer_arg_register_count]
+                            .clone();
+
+                        var_args.push(Arg::from_var(register, Some(data_type.clone())));
+
+                        integer_arg_register_count -= 1;
+                    } else {
+                        var_args.push(create_stack_arg(
+                            *size,
+                            stack_offset,
+                            Some(data_type.clone()),
+                            self.stack_pointer_register,
+                        ));
+                        stack_offset += u64::from(*size) as i64
+                    }
+                }
+                _ => panic!("Invalid data type specifier from format string."),
+            }
+        }
+
+        var_args
+    }
+}
+
+impl Project {
+    /// Returns the CPU architecture family of the project.
+    pub fn get_cpu_architecture(&self) -> CpuArchitecture {
+        CpuArchitecture::from_name(&self.cpu_architecture)
+    }
+
+    /// Returns the size of the return address that calls push onto the stack.
+    ///
+    /// See [`CallingConventionResolver::get_return_address_size`].
+    pub fn get_return_address_size(&self) -> ByteSize {
+        get_return_address_size(self.get_cpu_architecture(), &self.stack_pointer_register)
+    }
+
+    /// Returns a resolver for the calling convention with the given name.
+    ///
+    /// Falls back to the standard calling convention like
+    /// [`Project::get_specific_calling_convention`].
+    pub fn get_calling_convention_resolver(
+        &self,
+        cconv_name: &Option<String>,
+    ) -> Option<CallingConventionResolver<'_>> {
+        self.get_specific_calling_convention(cconv_name)
+            .map(|calling_convention| CallingConventionResolver::new(self, calling_convention))
+    }
+
+    /// Returns a resolver for the calling convention of the given extern
+    /// symbol.
+    ///
+    /// This function panics if no suitable calling convention is found.
+    pub fn get_extern_symbol_resolver(
+        &self,
+        extern_symbol: &ExternSymbol,
+    ) -> CallingConventionResolver<'_> {
+        CallingConventionResolver::new(self, self.get_calling_convention(extern_symbol))
+    }
+}
+
+/// Returns the size of the return address pushed onto the stack by calls.
+fn get_return_address_size(
+    cpu_architecture: CpuArchitecture,
+    stack_pointer_register: &Variable,
+) -> ByteSize {
+    match cpu_architecture {
+        CpuArchitecture::X86 => stack_pointer_register.size,
+        _ => ByteSize::new(0),
+    }
+}
+
+/// Creates a stack argument at the given offset to the stack pointer.
+fn create_stack_arg(
+    size: ByteSize,
+    stack_offset: i64,
+    data_type: Option<Datatype>,
+    stack_pointer_register: &Variable,
+) -> Arg {
+    Arg::Stack {
+        address: Expression::Var(stack_pointer_register.clone()).plus_const(stack_offset),
+        size,
+        data_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{expr, variable};
+
+    fn mock_project(
+        cpu_architecture: &str,
+        stack_pointer: &str,
+        cconv: CallingConvention,
+    ) -> Project {
+        let mut project = Project::mock_x64();
+        project.cpu_architecture = cpu_architecture.to_string();
+        project.stack_pointer_register = variable!(stack_pointer);
+        project.calling_conventions = BTreeMap::from([(cconv.name.clone(), cconv)]);
+        project
+    }
+
+    fn mock_calling_convention(
+        name: &str,
+        parameters: &[&str],
+        returns: &[&str],
+    ) -> CallingConvention {
+        CallingConvention {
+            name: name.to_string(),
+            integer_parameter_register: parameters.iter().map(|reg| variable!(reg)).collect(),
+            float_parameter_register: Vec::new(),
+            integer_return_register: returns.iter().map(|reg| variable!(reg)).collect(),
+            float_return_register: Vec::new(),
+            callee_saved_register: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn stack_arg() {
+        assert_eq!(
+            Arg::Stack {
+                address: expr!("RSP:8 + 8:8"),
+                size: ByteSize::new(8),
+                data_type: Some(Datatype::Pointer),
+            },
+            create_stack_arg(
+                ByteSize::new(8),
+                8,
+                Some(Datatype::Pointer),
+                &variable!("RSP:8")
+            ),
+        )
+    }
+
+    #[test]
+    fn cpu_architectures() {
+        assert_eq!(
+            CpuArchitecture::from_name("x86:LE:64:default"),
+            CpuArchitecture::X86
+        );
+        assert_eq!(CpuArchitecture::from_name("x86_32"), CpuArchitecture::X86);
+        assert_eq!(
+            CpuArchitecture::from_name("AARCH64:LE:64:v8A"),
+            CpuArchitecture::Aarch64
+        );
+        assert_eq!(
+            CpuArchitecture::from_name("ARM:LE:32:v8"),
+            CpuArchitecture::Arm
+        );
+        assert_eq!(
+            CpuArchitecture::from_name("MIPS:BE:32:default"),
+            CpuArchitecture::Mips
+        );
+        assert_eq!(
+            CpuArchitecture::from_name("PowerPC:BE:64:default"),
+            CpuArchitecture::PowerPc
+        );
+        assert_eq!(
+            CpuArchitecture::from_name("RISCV:LE:64:RV64GC"),
+            CpuArchitecture::RiscV
+        );
+        assert_eq!(CpuArchitecture::from_name("sparc"), CpuArchitecture::Other);
+    }
+
+    #[test]
+    fn x86_calling_conventions() {
+        // System V: arguments in registers, the return address is on the stack.
+        let project = Project::mock_x64();
+        let resolver = project.get_calling_convention_resolver(&None).unwrap();
+        assert_eq!(resolver.family, CallingConventionFamily::SystemV);
+        assert_eq!(resolver.get_return_address_size(), ByteSize::new(8));
+        assert_eq!(
+            resolver.get_integer_parameter_arg(0),
+            Arg::mock_register("RDI", 8)
+        );
+        assert_eq!(
+            resolver
+                .get_integer_parameter_arg(7)
+                .eval_stack_offset()
+                .unwrap()
+                .try_to_i64()
+                .unwrap(),
+            16
+        );
+
+        // Microsoft x64: 32 bytes of shadow space before the stack arguments.
+        let project = mock_project(
+            "x86:LE:64:default",
+            "RSP:8",
+            mock_calling_convention(
+                "__fastcall",
+                &["RCX:8", "RDX:8", "R8:8", "R9:8"],
+                &["RAX:8"],
+            ),
+        );
+        let resolver = project
+            .get_calling_convention_resolver(&Some("__fastcall".to_string()))
+            .unwrap();
+        assert_eq!(resolver.family, CallingConventionFamily::Fastcall);
+        assert_eq!(
+            resolver
+                .get_integer_parameter_arg(4)
+                .eval_stack_offset()
+                .unwrap()
+                .try_to_i64()
+                .unwrap(),
+            40
+        );
+
+        // cdecl: all arguments on the stack.
+        let project = mock_project(
+            "x86:LE:32:default",
+            "ESP:4",
+            mock_calling_convention("__cdecl", &[], &["EAX:4"]),
+        );
+        let resolver = project.get_calling_convention_resolver(&None).unwrap();
+        assert_eq!(resolver.family, CallingConventionFamily::Cdecl);
+        assert_eq!(
+            resolver.get_integer_parameter_arg(0),
+            Arg::Stack {
+                address: expr!("ESP:4 + 4:4"),
+                size: ByteSize::new(4),
+                data_type: None,
+            }
+        );
+        assert_eq!(
+            resolver.get_variadic_parameter_locations(
+                &[Arg::Stack {
+                    address: expr!("ESP:4 + 4:4"),
+                    size: ByteSize::new(4),
+                    data_type: None,
+                }],
+                &[(Datatype::Integer, ByteSize::new(4))]
+            ),
+            vec![Arg::Stack {
+                address: expr!("ESP:4 + 8:4"),
+                size: ByteSize::new(4),
+                data_type: Some(Datatype::Integer),
+            }]
+        );
+    }
+
+    #[test]
+    fn arm_and_mips_calling_conventions() {
+        // AAPCS: no return address on the stack.
+        let project = Project::mock_arm32();
+        let resolver = project.get_calling_convention_resolver(&None).unwrap();
+        assert_eq!(resolver.family, CallingConventionFamily::Aapcs);
+        assert_eq!(resolver.get_return_address_size(), ByteSize::new(0));
+        assert_eq!(
+            resolver.get_integer_parameter_arg(0),
+            Arg::mock_register("r0", 4)
+        );
+
+        // AAPCS64: stack arguments directly at the stack pointer.
+        let project = mock_project(
+            "AARCH64:LE:64:v8A",
+            "sp:8",
+            mock_calling_convention("__cdecl", &["x0:8", "x1:8"], &["x0:8"]),
+        );
+        let resolver = project.get_calling_convention_resolver(&None).unwrap();
+        assert_eq!(resolver.family, CallingConventionFamily::Aapcs64);
+        assert_eq!(
+            resolver
+                .get_integer_parameter_arg(2)
+                .eval_stack_offset()
+                .unwrap()
+                .try_to_i64()
+                .unwrap(),
+            0
+        );
+
+        // MIPS o32: 16 bytes are reserved for the register arguments.
+        let project = mock_project(
+            "MIPS:BE:32:default",
+            "sp:4",
+            mock_calling_convention("__stdcall", &["a0:4", "a1:4", "a2:4", "a3:4"], &["v0:4"]),
+        );
+        let resolver = project.get_calling_convention_resolver(&None).unwrap();
+        assert_eq!(resolver.family, CallingConventionFamily::MipsO32);
+        assert_eq!(
+            resolver
+                .get_integer_parameter_arg(4)
+                .eval_stack_offset()
+                .unwrap()
+                .try_to_i64()
+                .unwrap(),
+            16
+        );
+
+        // MIPS n64: stack arguments directly at the stack pointer.
+        let project = mock_project(
+            "MIPS:BE:64:64-32addr",
+            "sp:8",
+            mock_calling_convention("__stdcall", &["a0:8"], &["v0:8"]),
+        );
+        let resolver = project.get_calling_convention_resolver(&None).unwrap();
+        assert_eq!(resolver.family, CallingConventionFamily::MipsN64);
+        assert_eq!(
+            resolver
+                .get_integer_parameter_arg(1)
+                .eval_stack_offset()
+                .unwrap()
+                .try_to_i64()
+                .unwrap(),
+            0
+        );
+    }
+}
@@ -18,5 +18,8 @@ pub use trivial_expression_substitution::*;
 mod input_expression_propagation;
 pub use input_expression_propagation::*;
 
+mod temporary_propagation;
+pub use temporary_propagation::*;
+
 pub use crate::ghidra_pcode::ir_passes::prelude;
 pub use crate::ghidra_pcode::ir_passes::{debug_assert_postconditions, run_ir_pass, IrPass};
@@ -149,6 +149,7 @@ pub fn substitute_and_on_stackpointer(
         "x86_32" => 16,
         "x86_64" => 16,
         "arm32" => 4,
+        arch if arch.starts_with("RISCV") => 16,
         _ => 0,
     };
 
@@ -0,0 +1,516 @@
+use super::prelude::*;
+
+use crate::intermediate_representation::*;
+
+use std::collections::{HashMap, HashSet};
+
+/// Propagates temporary registers into their uses and removes them afterwards.
+///
+/// The P-Code translation of a single assembly instruction often produces long
+/// chains of assignments to temporary registers. This pass performs the
+/// following simplifications inside each basic block:
+///
+/// - **Copy propagation**: An assignment of a register or a constant to a
+///   temporary register is substituted into all uses of the temporary.
+///   Assignments of other expressions are only substituted if the temporary
+///   has exactly one use.
+/// - **Constant folding**: Operations whose inputs are all constants are
+///   replaced by their result.
+/// - **Dead temporary elimination**: Assignments and loads to temporary
+///   registers that are not used afterwards are removed.
+///
+/// Only temporary registers that are not accessed outside of their block are
+/// considered. Substitutions are only done if no input register of the
+/// substituted expression is overwritten between the assignment and the use.
+///
+/// The pass is optional and only run if it is enabled in the `Normalization`
+/// section of the configuration file.
+pub struct TemporaryPropagationPass;
+
+impl IrPass for TemporaryPropagationPass {
+    const NAME: &'static str = "TemporaryPropagationPass";
+    const DBG_IR_FORM: debug::IrForm = debug::IrForm::TemporariesPropagated;
+
+    type Input = Program;
+    type ConstructionInput = ();
+
+    fn new(_construction_input: &Self::ConstructionInput) -> Self {
+        Self
+    }
+
+    fn run(&mut self, program: &mut Self::Input) -> Vec<LogMessage> {
+        let block_local_temporaries = get_block_local_temporaries(program);
+        let mut num_removed_defs = 0;
+        for block in program.blocks_mut() {
+            let Some(temporaries) = block_local_temporaries.get(&block.tid) else {
+                continue;
+            };
+            let num_defs = block.term.defs.len();
+            fold_constants(block);
+            propagate_temporaries(block, temporaries);
+            fold_constants(block);
+            remove_dead_temporaries(block, temporaries);
+            num_removed_defs += num_defs - block.term.defs.len();
+        }
+
+        vec![LogMessage::new_debug(format!(
+            "Removed {num_removed_defs} assignments to temporary registers."
+        ))
+        .source(Self::NAME)]
+    }
+
+    fn assert_postconditions(
+        _construction_input: &Self::ConstructionInput,
+        _program: &Self::Input,
+    ) {
+    }
+}
+
+/// Return a map from block TIDs to the temporary registers
+/// that are only accessed inside the corresponding block.
+fn get_block_local_temporaries(program: &Program) -> HashMap<Tid, HashSet<Variable>> {
+    let mut blocks_of_temporaries: HashMap<&Variable, HashSet<&Tid>> = HashMap::new();
+    for block in program.blocks() {
+        for var in get_accessed_vars(block) {
+            if var.is_temp {
+                blocks_of_temporaries
+                    .entry(var)
+                    .or_default()
+                    .insert(&block.tid);
+            }
+        }
+    }
+    let mut block_local_temporaries: HashMap<Tid, HashSet<Variable>> = HashMap::new();
+    for (var, blocks) in blocks_of_temporaries {
+        if let [block_tid] = Vec::from_iter(blocks)[..] {
+            block_local_temporaries
+                .entry(block_tid.clone())
+                .or_default()
+                .insert(var.clone());
+        }
+    }
+
+    block_local_temporaries
+}
+
+/// Return all registers that are read or written in the block.
+fn get_accessed_vars(block: &Term<Blk>) -> Vec<&Variable> {
+    let mut vars = Vec::new();
+    for def in block.term.defs.iter() {
+        match &def.term {
+            Def::Assign { var, value } => {
+                vars.push(var);
+                vars.extend(value.input_vars());
+            }
+            Def::Load { var, address } => {
+                vars.push(var);
+                vars.extend(address.input_vars());
+            }
+            Def::Store { address, value } => {
+                vars.extend(address.input_vars());
+                vars.extend(value.input_vars());
+            }
+        }
+    }
+    for jmp in block.term.jmps.iter() {
+        if let Some(expression) = get_jmp_expression(&jmp.term) {
+            vars.extend(expression.input_vars());
+        }
+    }
+
+    vars
+}
+
+/// Return the expression evaluated by the jump, if there is one.
+fn get_jmp_expression(jmp: &Jmp) -> Option<&Expression> {
+    match jmp {
+        Jmp::BranchInd(expression)
+        | Jmp::CBranch {
+            condition: expression,
+            ..
+        }
+        | Jmp::CallInd {
+            target: expression, ..
+        }
+        | Jmp::Return(expression) => Some(expression),
+        Jmp::Branch(_) | Jmp::Call { .. } | Jmp::CallOther { .. } => None,
+    }
+}
+
+/// Mutable version of [`get_jmp_expression`].
+fn get_jmp_expression_mut(jmp: &mut Jmp) -> Option<&mut Expression> {
+    match jmp {
+        Jmp::BranchInd(expression)
+        | Jmp::CBranch {
+            condition: expression,
+            ..
+        }
+        | Jmp::CallInd {
+            target: expression, ..
+        }
+        | Jmp::Return(expression) => Some(expression),
+        Jmp::Branch(_) | Jmp::Call { .. } | Jmp::CallOther { .. } => None,
+    }
+}
+
+/// Return the register written by the `Def`, if there is one.
+fn get_assigned_var(def: &Def) -> Option<&Variable> {
+    match def {
+        Def::Assign { var, .. } | Def::Load { var, .. } => Some(var),
+        Def::Store { .. } => None,
+    }
+}
+
+/// Return the number of occurrences of the register in the input expressions of the `Def`.
+fn count_uses_in_def(def: &Def, var: &Variable) -> usize {
+    let inputs = match def {
+        Def::Assign { value, .. } => value.input_vars(),
+        Def::Load { address, .. } => address.input_vars(),
+        Def::Store { address, value } => {
+            let mut inputs = address.input_vars();
+            inputs.extend(value.input_vars());
+            inputs
+        }
+    };
+    inputs.into_iter().filter(|input| *input == var).count()
+}
+
+/// Substitute assignments to block-local temporaries into their uses
+/// and remove the assignments afterwards.
+fn propagate_temporaries(block: &mut Term<Blk>, temporaries: &HashSet<Variable>) {
+    let mut index = 0;
+    while index < block.term.defs.len() {
+        let Def::Assign { var, value } = &block.term.defs[index].term else {
+            index += 1;
+            continue;
+        };
+        if !temporaries.contains(var) {
+            index += 1;
+            continue;
+        }
+        let (var, value) = (var.clone(), value.clone());
+        match get_substitutable_uses(block, index, &var, &value) {
+            Some(use_indices) => {
+                for use_index in use_indices {
+                    match block.term.defs.get_mut(use_index) {
+                        Some(def) => def.substitute_input_var(&var, &value),
+                        None => {
+                            for jmp in block.term.jmps.iter_mut() {
+                                if let Some(expression) = get_jmp_expression_mut(&mut jmp.term) {
+                                    expression.substitute_input_var(&var, &value);
+                                }
+                            }
+                        }
+                    }
+                }
+                block.term.defs.remove(index);
+            }
+            None => index += 1,
+        }
+    }
+}
+
+/// Return the indices of all `Def` terms using the value assigned to `var` by the `Def` at `def_index`
+/// if the value can be substituted into all of them.
+/// The index `block.term.defs.len()` denotes uses in the jumps of the block.
+///
+/// Returns `None` if the value cannot be substituted into all uses.
+fn get_substitutable_uses(
+    block: &Term<Blk>,
+    def_index: usize,
+    var: &Variable,
+    value: &Expression,
+) -> Option<Vec<usize>> {
+    let value_inputs = value.input_vars();
+    let is_copy = matches!(value, Expression::Var(_) | Expression::Const(_));
+    let mut use_indices = Vec::new();
+    let mut num_uses = 0;
+    let mut inputs_overwritten = false;
+    for (index, def) in block.term.defs.iter().enumerate().skip(def_index + 1) {
+        let uses = count_uses_in_def(&def.term, var);
+        if uses > 0 {
+            if inputs_overwritten {
+                return None;
+            }
+            num_uses += uses;
+            use_indices.push(index);
+        }
+        let assigned_var = get_assigned_var(&def.term);
+        if assigned_var == Some(var) {
+            return (is_copy || num_uses <= 1).then_some(use_indices);
+        }
+        if assigned_var.is_some_and(|assigned_var| value_inputs.contains(&assigned_var)) {
+            inputs_overwritten = true;
+        }
+    }
+    let jmp_uses: usize = block
+        .term
+        .jmps
+        .iter()
+        .filter_map(|jmp| get_jmp_expression(&jmp.term))
+        .map(|expression| {
+            expression
+                .input_vars()
+                .into_iter()
+                .filter(|input| *input == var)
+                .count()
+        })
+        .sum();
+    if jmp_uses > 0 {
+        if inputs_overwritten {
+            return None;
+        }
+        num_uses += jmp_uses;
+        use_indices.push(block.term.defs.len());
+    }
+
+    (is_copy || num_uses <= 1).then_some(use_indices)
+}
+
+/// Replace all operations in the block whose inputs are constants by their result.
+fn fold_constants(block: &mut Term<Blk>) {
+    for def in block.term.defs.iter_mut() {
+        match &mut def.term {
+            Def::Assign {
+                value: expression, ..
+            }
+            | Def::Load {
+                address: expression,
+                ..
+            } => fold_constants_in_expression(expression),
+            Def::Store { address, value } => {
+                fold_constants_in_expression(address);
+                fold_constants_in_expression(value);
+            }
+        }
+    }
+    for jmp in block.term.jmps.iter_mut() {
+        if let Some(expression) = get_jmp_expression_mut(&mut jmp.term) {
+            fold_constants_in_expression(expression);
+        }
+    }
+}
+
+/// Replace all subexpressions whose inputs are constants by their result.
+///
+/// Operations whose result cannot be computed are left unchanged.
+fn fold_constants_in_expression(expression: &mut Expression) {
+    use Expression::*;
+    let result = match expression {
+        Var(_) | Const(_) | Unknown { .. } => None,
+        BinOp { op, lhs, rhs } => {
+            fold_constants_in_expression(lhs);
+            fold_constants_in_expression(rhs);
+            match (&**lhs, &**rhs) {
+                (Const(lhs), Const(rhs))
+                    if lhs.bytesize() == rhs.bytesize() || *op == BinOpType::Piece =>
+                {
+                    lhs.bin_op(*op, rhs).ok()
+                }
+                _ => None,
+            }
+        }
+        UnOp { op, arg } => {
+            fold_constants_in_expression(arg);
+            match &**arg {
+                Const(arg) => arg.un_op(*op).ok(),
+                _ => None,
+            }
+        }
+        Cast { op, size, arg } => {
+            fold_constants_in_expression(arg);
+            match &**arg {
+                Const(arg) => arg.cast(*op, *size).ok(),
+                _ => None,
+            }
+        }
+        Subpiece {
+            low_byte,
+            size,
+            arg,
+        } => {
+            fold_constants_in_expression(arg);
+            match &**arg {
+                Const(arg) if *low_byte + *size <= arg.bytesize() => {
+                    Some(arg.subpiece(*low_byte, *size))
+                }
+                _ => None,
+            }
+        }
+    };
+    if let Some(result) = result {
+        *expression = Const(result);
+    }
+}
+
+/// Remove assignments and loads to block-local temporaries whose values are never used.
+fn remove_dead_temporaries(block: &mut Term<Blk>, temporaries: &HashSet<Variable>) {
+    let mut live_vars: HashSet<Variable> = HashSet::new();
+    for jmp in block.term.jmps.iter() {
+        if let Some(expression) = get_jmp_expression(&jmp.term) {
+            live_vars.extend(expression.input_vars().into_iter().cloned());
+        }
+    }
+    let mut live_defs = Vec::with_capacity(block.term.defs.len());
+    for def in block.term.defs.drain(..).rev() {
+        if let Some(var) = get_assigned_var(&def.term) {
+            if temporaries.contains(var) && !live_vars.contains(var) {
+                continue;
+            }
+            live_vars.remove(var);
+        }
+        let inputs = match &def.term {
+            Def::Assign { value, .. } => value.input_vars(),
+            Def::Load { address, .. } => address.input_vars(),
+            Def::Store { address, value } => {
+                let mut inputs = address.input_vars();
+                inputs.extend(value.input_vars());
+                inputs
+            }
+        };
+        live_vars.extend(inputs.into_iter().cloned());
+        live_defs.push(def);
+    }
+    live_defs.reverse();
+    block.term.defs = live_defs;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{defs, expr};
+
+    /// Create a program with a single block.
+    /// Registers whose names start with `U` are turned into temporary registers.
+    fn mock_program(mut defs: Vec<Term<Def>>, mut jmps: Vec<Term<Jmp>>) -> Program {
+        let mut vars: Vec<Variable> = Vec::new();
+        for def in defs.iter() {
+            if let Some(var) = get_assigned_var(&def.term) {
+                vars.push(var.clone());
+            }
+        }
+        for jmp in jmps.iter() {
+            vars.extend(
+                get_jmp_expression(&jmp.term)
+                    .into_iter()
+                    .flat_map(|expression| expression.input_vars().into_iter().cloned()),
+            );
+        }
+        for var in vars.into_iter().filter(|var| var.name.starts_with('U')) {
+            let temp = Expression::Var(Variable {
+                name: format!("$U_{}", &var.name[1..]),
+                is_temp: true,
+                ..var.clone()
+            });
+            for def in defs.iter_mut() {
+                def.substitute_input_var(&var, &temp);
+                match &mut def.term {
+                    Def::Assign {
+                        var: assigned_var, ..
+                    }
+                    | Def::Load {
+                        var: assigned_var, ..
+                    } if *assigned_var == var => {
+                        *assigned_var = temp.input_vars()[0].clone();
+                    }
+                    _ => (),
+                }
+            }
+            for jmp in jmps.iter_mut() {
+                if let Some(expression) = get_jmp_expression_mut(&mut jmp.term) {
+                    expression.substitute_input_var(&var, &temp);
+                }
+            }
+        }
+        let mut block = Blk::mock_with_tid("block");
+        block.term.defs = defs;
+        block.term.jmps = jmps;
+        let sub = Term {
+            tid: Tid::new("sub"),
+            term: Sub::new::<_, &str>("sub", vec![block], None),
+        };
+        let mut program = Program::mock_x64();
+        program.subs.insert(sub.tid.clone(), sub);
+        program
+    }
+
+    fn run_pass(program: &mut Program) -> &Vec<Term<Def>> {
+        TemporaryPropagationPass::new(&()).run(program);
+        &program.blocks().next().unwrap().term.defs
+    }
+
+    fn expected_defs(defs: Vec<Term<Def>>) -> Vec<Term<Def>> {
+        let program = mock_program(defs, vec![]);
+        let block = program.blocks().next().unwrap();
+        block.term.defs.clone()
+    }
+
+    #[test]
+    fn copy_propagation_and_constant_folding() {
+        let mut program = mock_program(
+            defs![
+                "def_0: U1:8 = RDI:8",
+                "def_1: U2:8 = 0x10:8 + 0x8:8",
+                "def_2: RAX:8 = U1:8 + U2:8",
+                "def_3: Store at U1:8 := U2:8"
+            ],
+            vec![],
+        );
+        assert_eq!(
+            run_pass(&mut program),
+            &expected_defs(defs![
+                "def_2: RAX:8 = RDI:8 + 0x18:8",
+                "def_3: Store at RDI:8 := 0x18:8"
+            ])
+        );
+    }
+
+    #[test]
+    fn single_use_expressions() {
+        let mut program = mock_program(
+            defs![
+                "def_0: U1:8 = RDI:8 + 0x8:8",
+                "def_1: U2:8 = RSI:8 - 0x2:8",
+                "def_2: RAX:8 := Load from U1:8",
+                "def_3: RBX:8 = U2:8 + U2:8"
+            ],
+            vec![Term {
+                tid: Tid::new("jmp"),
+                term: Jmp::CBranch {
+                    target: Tid::new("block"),
+                    condition: expr!("U3:1"),
+                },
+            }],
+        );
+        // `U2` is used twice, `U3` is an input of the block.
+        assert_eq!(
+            run_pass(&mut program),
+            &expected_defs(defs![
+                "def_1: U2:8 = RSI:8 - 0x2:8",
+                "def_2: RAX:8 := Load from RDI:8 + 0x8:8",
+                "def_3: RBX:8 = U2:8 + U2:8"
+            ])
+        );
+    }
+
+    #[test]
+    fn overwritten_inputs_and_dead_temporaries() {
+        let mut program = mock_program(
+            defs![
+                "def_0: U1:8 = RDI:8",
+                "def_1: RDI:8 = 0x0:8",
+                "def_2: RAX:8 = U1:8",
+                "def_3: U2:8 := Load from RSP:8"
+            ],
+            vec![],
+        );
+        assert_eq!(
+            run_pass(&mut program),
+            &expected_defs(defs![
+                "def_0: U1:8 = RDI:8",
+                "def_1: RDI:8 = 0x0:8",
+                "def_2: RAX:8 = U1:8"
+            ])
+        );
+    }
+}
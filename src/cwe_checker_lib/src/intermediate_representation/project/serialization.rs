@@ -0,0 +1,232 @@
+//! Saving and loading of projects to and from a binary on-disk format.
+//!
+//! Lifting a binary through Ghidra usually dominates the runtime of the cwe_checker.
+//! A saved project can be reanalyzed with different configurations without lifting the binary again.
+//!
+//! The format consists of a short header followed by the project encoded as MessagePack.
+//! The header contains a format version that is incremented on incompatible changes to the IR,
+//! the version of the cwe_checker that saved the project
+//! and hashes of the binary and the bare metal configuration that the project was generated from.
+//! Saved projects are only loaded if all of them match,
+//! since the IR generated by different cwe_checker versions or for other inputs may differ.
+
+use super::*;
+use crate::error::CweCheckerError;
+use crate::utils::binary::BareMetalConfig;
+
+use std::hash::Hasher;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Magic bytes at the start of a saved project.
+const MAGIC: &[u8; 8] = b"CWEIR\0\0\0";
+
+/// The version of the on-disk format.
+///
+/// Saved projects with a different version are rejected when loading them.
+pub const IR_FORMAT_VERSION: u32 = 2;
+
+/// The MessagePack-encoded header of a saved project that precedes the project itself.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct SavedProjectHeader {
+    /// The version of the cwe_checker that generated the project.
+    cwe_checker_version: String,
+    /// The hash of the binary that the project was generated from.
+    binary_hash: u64,
+    /// The hash of the bare metal configuration used for lifting the binary, if one was used.
+    bare_metal_config_hash: Option<u64>,
+}
+
+impl SavedProjectHeader {
+    /// Generate the header for a project generated by this cwe_checker version from the given inputs.
+    fn new(binary: &[u8], bare_metal_config: Option<&BareMetalConfig>) -> SavedProjectHeader {
+        SavedProjectHeader {
+            cwe_checker_version: env!("CARGO_PKG_VERSION").to_string(),
+            binary_hash: hash_bytes(binary),
+            bare_metal_config_hash: bare_metal_config.map(|config| {
+                hash_bytes(
+                    serde_json::to_string(config)
+                        .expect("Serialization of the bare metal configuration failed")
+                        .as_bytes(),
+                )
+            }),
+        }
+    }
+}
+
+impl Project {
+    /// Save the project to the given file.
+    ///
+    /// The `binary` is the binary file that the project was generated from
+    /// and `bare_metal_config` the bare metal configuration used for lifting it.
+    /// Their hashes are stored alongside the project,
+    /// so that [`Project::load`] can detect saved projects of other inputs.
+    pub fn save(
+        &self,
+        path: &Path,
+        binary: &[u8],
+        bare_metal_config: Option<&BareMetalConfig>,
+    ) -> Result<(), Error> {
+        let header = SavedProjectHeader::new(binary, bare_metal_config);
+        let mut file = std::io::BufWriter::new(
+            std::fs::File::create(path).context("Could not create the IR cache file")?,
+        );
+        file.write_all(MAGIC)?;
+        file.write_all(&IR_FORMAT_VERSION.to_le_bytes())?;
+        rmp_serde::encode::write_named(&mut file, &header)
+            .context("Serialization of the IR cache header failed")?;
+        rmp_serde::encode::write_named(&mut file, self)
+            .context("Serialization of the project failed")?;
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Load a project saved with [`Project::save`] from the given file.
+    ///
+    /// Returns an error if the file is not a saved project,
+    /// if it was saved with a different format version or by a different version of the cwe_checker
+    /// or if it was generated from a binary or bare metal configuration different from the given ones.
+    pub fn load(
+        path: &Path,
+        binary: &[u8],
+        bare_metal_config: Option<&BareMetalConfig>,
+    ) -> Result<Project, CweCheckerError> {
+        Project::load_saved_project(path, binary, bare_metal_config)
+            .map_err(CweCheckerError::Loader)
+    }
+
+    /// Read a saved project from the given file and check that it matches the given inputs.
+    fn load_saved_project(
+        path: &Path,
+        binary: &[u8],
+        bare_metal_config: Option<&BareMetalConfig>,
+    ) -> Result<Project, Error> {
+        let mut file = std::io::BufReader::new(
+            std::fs::File::open(path).context("Could not open the IR cache file")?,
+        );
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)
+            .context("The IR cache file is truncated")?;
+        if &magic != MAGIC {
+            return Err(anyhow!("The file is not an IR cache file"));
+        }
+        let mut version = [0u8; 4];
+        file.read_exact(&mut version)
+            .context("The IR cache file is truncated")?;
+        let version = u32::from_le_bytes(version);
+        if version != IR_FORMAT_VERSION {
+            return Err(anyhow!(
+                "The IR cache file has format version {version}, expected version {IR_FORMAT_VERSION}"
+            ));
+        }
+        let mut deserializer = rmp_serde::Deserializer::new(file);
+        let header = SavedProjectHeader::deserialize(&mut deserializer)
+            .context("Deserialization of the IR cache header failed")?;
+        let expected_header = SavedProjectHeader::new(binary, bare_metal_config);
+        if header.cwe_checker_version != expected_header.cwe_checker_version {
+            return Err(anyhow!(
+                "The IR cache file was generated by cwe_checker version {}",
+                header.cwe_checker_version
+            ));
+        }
+        if header.binary_hash != expected_header.binary_hash {
+            return Err(anyhow!(
+                "The IR cache file was generated from a different binary"
+            ));
+        }
+        if header.bare_metal_config_hash != expected_header.bare_metal_config_hash {
+            return Err(anyhow!(
+                "The IR cache file was generated with a different bare metal configuration"
+            ));
+        }
+
+        Project::deserialize(&mut deserializer).context("Deserialization of the project failed")
+    }
+}
+
+/// Compute a hash of the bytes that is stable across runs and platforms.
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = fnv::FnvHasher::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defs;
+
+    #[test]
+    fn save_and_load() {
+        let mut project = Project::mock_x64();
+        let mut block = Blk::mock_with_tid("block");
+        block.term.defs = defs!["def: RAX:8 = RDI:8 + 0x1:8"];
+        block.term.jmps = vec![Jmp::branch("jmp", "block")];
+        block.term.set_exception_handler(Tid::new("block"));
+        let sub = Term {
+            tid: Tid::new("sub"),
+            term: Sub::new::<_, &str>("sub", vec![block], None),
+        };
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+
+        let path = std::env::temp_dir().join(format!(
+            "cwe_checker_ir_cache_test_{}.bin",
+            std::process::id()
+        ));
+        let binary = b"binary";
+        project.save(&path, binary, None).unwrap();
+        assert_eq!(Project::load(&path, binary, None).unwrap(), project);
+        assert!(Project::load(&path, b"other binary", None).is_err());
+
+        std::fs::write(&path, b"not an IR cache").unwrap();
+        assert!(Project::load(&path, binary, None).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reject_other_inputs_and_versions() {
+        let project = Project::mock_x64();
+        let path = std::env::temp_dir().join(format!(
+            "cwe_checker_ir_cache_inputs_test_{}.bin",
+            std::process::id()
+        ));
+        let binary = b"binary";
+        let mut bare_metal_config: BareMetalConfig = serde_json::from_str(
+            r#"
+            {
+                "processor_id": "ARM:LE:32:v7",
+                "flash_base_address": "0x08000000",
+                "ram_base_address": "0x20000000",
+                "ram_size": "0x10000"
+            }
+            "#,
+        )
+        .unwrap();
+        project
+            .save(&path, binary, Some(&bare_metal_config))
+            .unwrap();
+        assert_eq!(
+            Project::load(&path, binary, Some(&bare_metal_config)).unwrap(),
+            project
+        );
+        assert!(Project::load(&path, binary, None).is_err());
+        bare_metal_config.flash_base_address = "0x0".to_string();
+        assert!(Project::load(&path, binary, Some(&bare_metal_config)).is_err());
+
+        // Projects saved by other versions of the cwe_checker are rejected.
+        let mut header = SavedProjectHeader::new(binary, None);
+        header.cwe_checker_version = "0.1".to_string();
+        let mut file = MAGIC.to_vec();
+        file.extend(IR_FORMAT_VERSION.to_le_bytes());
+        rmp_serde::encode::write_named(&mut file, &header).unwrap();
+        rmp_serde::encode::write_named(&mut file, &project).unwrap();
+        std::fs::write(&path, &file).unwrap();
+        let error = Project::load(&path, binary, None).unwrap_err();
+        assert_eq!(
+            format!("{:#}", Error::from(error)),
+            "Loading of the binary failed: The IR cache file was generated by cwe_checker version 0.1"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
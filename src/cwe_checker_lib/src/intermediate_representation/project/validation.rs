@@ -0,0 +1,346 @@
+//! Validation of the invariants of the intermediate representation.
+//!
+//! The analyses assume that the IR satisfies some basic invariants,
+//! e.g. that all jump targets exist.
+//! Malformed input from the frontend that violates these invariants
+//! usually leads to panics deep inside of an analysis.
+//! The [`Project::validate`] method checks these invariants
+//! so that such input can be rejected early.
+
+use super::*;
+use crate::utils::log::LogMessage;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A violation of an invariant of the IR.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IrDiagnostic {
+    /// The TID of the term that violates the invariant.
+    pub location: Tid,
+    /// The violated invariant.
+    pub kind: IrDiagnosticKind,
+}
+
+/// The invariants of the IR that are checked by [`Project::validate`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IrDiagnosticKind {
+    /// The function is stored under a different TID in the map of functions.
+    InconsistentFunctionMapping {
+        /// The TID under which the function is stored.
+        key: Tid,
+    },
+    /// The entry point does not exist in the program.
+    NonexistingEntryPoint,
+    /// The jump target does not exist in the function containing the jump.
+    NonexistingJumpTarget {
+        /// The TID of the target block.
+        target: Tid,
+    },
+    /// The call target is neither a function nor an extern symbol.
+    NonexistingCallTarget {
+        /// The TID of the target function.
+        target: Tid,
+    },
+    /// The size of an assigned value differs from the size of the assigned variable.
+    AssignmentSizeMismatch {
+        /// The size of the assigned variable.
+        var_size: ByteSize,
+        /// The size of the assigned value.
+        value_size: ByteSize,
+    },
+    /// A register is accessed with a size different from the size of the register.
+    RegisterSizeMismatch {
+        /// The name of the register.
+        register: String,
+        /// The size of the register.
+        expected: ByteSize,
+        /// The size of the access.
+        found: ByteSize,
+    },
+}
+
+impl fmt::Display for IrDiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IrDiagnosticKind::*;
+        match self {
+            InconsistentFunctionMapping { key } => {
+                write!(f, "Function is stored under the TID {key}")
+            }
+            NonexistingEntryPoint => write!(f, "Entry point does not exist"),
+            NonexistingJumpTarget { target } => {
+                write!(f, "Jump target {target} does not exist in the function")
+            }
+            NonexistingCallTarget { target } => write!(f, "Call target {target} does not exist"),
+            AssignmentSizeMismatch {
+                var_size,
+                value_size,
+            } => write!(
+                f,
+                "Value of size {value_size} is assigned to variable of size {var_size}"
+            ),
+            RegisterSizeMismatch {
+                register,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Register {register} of size {expected} is accessed with size {found}"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for IrDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+impl From<&IrDiagnostic> for LogMessage {
+    fn from(diagnostic: &IrDiagnostic) -> LogMessage {
+        LogMessage::new_error(diagnostic.kind.to_string())
+            .location(diagnostic.location.clone())
+            .source("IR validation")
+    }
+}
+
+impl Project {
+    /// Check the invariants of the IR and return a diagnostic for each violation.
+    ///
+    /// The following invariants are checked:
+    /// - All functions are stored under their own TID.
+    /// - All entry points exist.
+    /// - All intraprocedural jump targets (including return targets of calls)
+    ///   exist in the function containing the jump.
+    /// - All call targets exist as functions or extern symbols.
+    /// - Assigned values have the same size as the assigned variable.
+    /// - Registers of the register set are always accessed with their full size.
+    pub fn validate(&self) -> Vec<IrDiagnostic> {
+        let program = &self.program.term;
+        let mut diagnostics = Vec::new();
+        for (key, sub) in program.subs.iter() {
+            if *key != sub.tid {
+                diagnostics.push(IrDiagnostic {
+                    location: sub.tid.clone(),
+                    kind: IrDiagnosticKind::InconsistentFunctionMapping { key: key.clone() },
+                });
+            }
+        }
+        for entry_point in program.entry_points.iter() {
+            if !program.subs.contains_key(entry_point) {
+                diagnostics.push(IrDiagnostic {
+                    location: entry_point.clone(),
+                    kind: IrDiagnosticKind::NonexistingEntryPoint,
+                });
+            }
+        }
+        let block_tids: HashSet<&Tid> = program.blocks().map(|block| &block.tid).collect();
+        let register_sizes: HashMap<&str, ByteSize> = self
+            .register_set
+            .iter()
+            .map(|register| (register.name.as_str(), register.size))
+            .collect();
+        for sub in program.subs.values() {
+            let sub_block_tids: HashSet<&Tid> = sub.term.blocks().map(|block| &block.tid).collect();
+            for block in sub.term.blocks() {
+                self.validate_control_flow(block, &sub_block_tids, &mut diagnostics);
+                if let Some(handler) = block.term.exception_handler() {
+                    if !block_tids.contains(handler) {
+                        diagnostics.push(IrDiagnostic {
+                            location: block.tid.clone(),
+                            kind: IrDiagnosticKind::NonexistingJumpTarget {
+                                target: handler.clone(),
+                            },
+                        });
+                    }
+                }
+                for def in block.term.defs() {
+                    validate_def(def, &register_sizes, &mut diagnostics);
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Check that all control flow targets of the block exist.
+    fn validate_control_flow(
+        &self,
+        block: &Term<Blk>,
+        sub_block_tids: &HashSet<&Tid>,
+        diagnostics: &mut Vec<IrDiagnostic>,
+    ) {
+        let program = &self.program.term;
+        let call_target_exists = |target: &Tid| {
+            program.subs.contains_key(target) || program.extern_symbols.contains_key(target)
+        };
+        let mut jump_targets: Vec<(&Tid, &Tid)> = Vec::new();
+        let mut call_targets: Vec<(&Tid, &Tid)> = Vec::new();
+        for jmp in block.term.jmps() {
+            match &jmp.term {
+                Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                    jump_targets.push((&jmp.tid, target))
+                }
+                Jmp::Call { target, return_ } => {
+                    call_targets.push((&jmp.tid, target));
+                    jump_targets.extend(return_.iter().map(|return_| (&jmp.tid, return_)));
+                }
+                Jmp::CallInd { return_, .. } | Jmp::CallOther { return_, .. } => {
+                    jump_targets.extend(return_.iter().map(|return_| (&jmp.tid, return_)))
+                }
+                Jmp::BranchInd(_) | Jmp::Return(_) => (),
+            }
+        }
+        jump_targets.extend(
+            block
+                .term
+                .ind_jump_targets()
+                .into_iter()
+                .flatten()
+                .map(|target| (&block.tid, target)),
+        );
+        call_targets.extend(
+            block
+                .term
+                .ind_call_targets()
+                .into_iter()
+                .flatten()
+                .map(|target| (&block.tid, target)),
+        );
+        for (location, target) in jump_targets {
+            if !sub_block_tids.contains(target) {
+                diagnostics.push(IrDiagnostic {
+                    location: location.clone(),
+                    kind: IrDiagnosticKind::NonexistingJumpTarget {
+                        target: target.clone(),
+                    },
+                });
+            }
+        }
+        for (location, target) in call_targets {
+            if !call_target_exists(target) {
+                diagnostics.push(IrDiagnostic {
+                    location: location.clone(),
+                    kind: IrDiagnosticKind::NonexistingCallTarget {
+                        target: target.clone(),
+                    },
+                });
+            }
+        }
+    }
+}
+
+/// Check the sizes of the variables and values of the `Def`.
+fn validate_def(
+    def: &Term<Def>,
+    register_sizes: &HashMap<&str, ByteSize>,
+    diagnostics: &mut Vec<IrDiagnostic>,
+) {
+    let mut vars: Vec<&Variable> = Vec::new();
+    match &def.term {
+        Def::Assign { var, value } => {
+            if var.size != value.bytesize() {
+                diagnostics.push(IrDiagnostic {
+                    location: def.tid.clone(),
+                    kind: IrDiagnosticKind::AssignmentSizeMismatch {
+                        var_size: var.size,
+                        value_size: value.bytesize(),
+                    },
+                });
+            }
+            vars.push(var);
+            vars.extend(value.input_vars());
+        }
+        Def::Load { var, address } => {
+            vars.push(var);
+            vars.extend(address.input_vars());
+        }
+        Def::Store { address, value } => {
+            vars.extend(address.input_vars());
+            vars.extend(value.input_vars());
+        }
+    }
+    for var in vars.into_iter().filter(|var| !var.is_temp) {
+        match register_sizes.get(var.name.as_str()) {
+            Some(size) if *size != var.size => diagnostics.push(IrDiagnostic {
+                location: def.tid.clone(),
+                kind: IrDiagnosticKind::RegisterSizeMismatch {
+                    register: var.name.clone(),
+                    expected: *size,
+                    found: var.size,
+                },
+            }),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defs;
+
+    #[test]
+    fn valid_project() {
+        let mut project = Project::mock_x64();
+        let mut block = Blk::mock_with_tid("block");
+        block.term.defs = defs!["def: RAX:8 = RDI:8 + 0x1:8"];
+        block.term.jmps = vec![Jmp::branch("jmp", "block")];
+        let sub = Term {
+            tid: Tid::new("sub"),
+            term: Sub::new::<_, &str>("sub", vec![block], None),
+        };
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        assert_eq!(project.validate(), Vec::new());
+    }
+
+    #[test]
+    fn invalid_project() {
+        let mut project = Project::mock_x64();
+        let mut block = Blk::mock_with_tid("block");
+        block.term.defs = defs!["def: RAX:4 = RDI:8"];
+        block.term.jmps = vec![Jmp::call(
+            "call",
+            "nonexisting_sub",
+            Some("nonexisting_block"),
+        )];
+        let sub = Term {
+            tid: Tid::new("sub"),
+            term: Sub::new::<_, &str>("sub", vec![block], None),
+        };
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project
+            .program
+            .term
+            .entry_points
+            .insert(Tid::new("nonexisting_entry_point"));
+
+        let kinds: Vec<IrDiagnosticKind> = project
+            .validate()
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                IrDiagnosticKind::NonexistingEntryPoint,
+                IrDiagnosticKind::NonexistingJumpTarget {
+                    target: Tid::new("nonexisting_block")
+                },
+                IrDiagnosticKind::NonexistingCallTarget {
+                    target: Tid::new("nonexisting_sub")
+                },
+                IrDiagnosticKind::AssignmentSizeMismatch {
+                    var_size: ByteSize::new(4),
+                    value_size: ByteSize::new(8)
+                },
+                IrDiagnosticKind::RegisterSizeMismatch {
+                    register: "RAX".to_string(),
+                    expected: ByteSize::new(8),
+                    found: ByteSize::new(4)
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! A readable textual representation of the IR in the style of assembly code.
+//!
+//! The format is meant for humans, e.g. for checking why a check did or did not
+//! generate a warning at some address. It is stable in the sense that printing
+//! the same program twice generates the same output.
+
+use super::*;
+use std::fmt::Write;
+
+/// Print the functions of the program as pseudo-assembly.
+///
+/// If `function_names` is not empty, only functions with one of the given names are printed.
+/// Extern symbols are listed at the end of the output.
+pub fn dump_pseudo_assembly(program: &Program, function_names: &[String]) -> String {
+    let mut output = String::new();
+    let functions = program
+        .subs
+        .values()
+        .filter(|sub| function_names.is_empty() || function_names.contains(&sub.term.name));
+    for sub in functions {
+        write_function(&mut output, program, sub);
+        output.push('\n');
+    }
+    if function_names.is_empty() {
+        let mut extern_symbols: Vec<&ExternSymbol> = program.extern_symbols.values().collect();
+        extern_symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        for symbol in extern_symbols {
+            let _ = write!(output, "extern {} ({})", symbol.name, symbol.tid);
+            if symbol.no_return {
+                output.push_str(" [non-returning]");
+            }
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+/// Format the address of the TID as a hexadecimal number.
+fn format_address(tid: &Tid) -> String {
+    match u64::try_from(tid.address()) {
+        Ok(address) => format!("{address:08x}"),
+        Err(_) => "????????".to_string(),
+    }
+}
+
+/// Return the name of the function or extern symbol with the given TID.
+///
+/// Returns the TID itself for unknown call targets.
+fn get_callee_name(program: &Program, tid: &Tid) -> String {
+    if let Some(sub) = program.subs.get(tid) {
+        sub.term.name.clone()
+    } else if let Some(symbol) = program.extern_symbols.get(tid) {
+        symbol.name.clone()
+    } else {
+        tid.to_string()
+    }
+}
+
+/// Append the pseudo-assembly of a function to the output.
+fn write_function(output: &mut String, program: &Program, sub: &Term<Sub>) {
+    let _ = write!(
+        output,
+        "function {} ({}) @ {}",
+        sub.term.name,
+        sub.tid,
+        format_address(&sub.tid)
+    );
+    if program.entry_points.contains(&sub.tid) {
+        output.push_str(" [entry point]");
+    }
+    if sub.term.is_non_returning() {
+        output.push_str(" [non-returning]");
+    }
+    output.push('\n');
+    for block in sub.term.blocks() {
+        let _ = writeln!(output, "  {}:", block.tid);
+        for def in block.term.defs() {
+            let _ = writeln!(output, "    {}  {}", format_address(&def.tid), def.term);
+        }
+        for jmp in block.term.jmps() {
+            let _ = writeln!(
+                output,
+                "    {}  {}",
+                format_address(&jmp.tid),
+                format_jmp(program, &jmp.term)
+            );
+        }
+        if let Some(targets) = block.term.ind_jump_targets() {
+            let targets: Vec<String> = targets.map(|target| target.to_string()).collect();
+            let _ = writeln!(
+                output,
+                "    ; indirect jump targets: {}",
+                targets.join(", ")
+            );
+        }
+        if let Some(targets) = block.term.ind_call_targets() {
+            let targets: Vec<String> = targets
+                .map(|target| get_callee_name(program, target))
+                .collect();
+            let _ = writeln!(
+                output,
+                "    ; indirect call targets: {}",
+                targets.join(", ")
+            );
+        }
+        if let Some(handler) = block.term.exception_handler() {
+            let _ = writeln!(output, "    ; exception handler: {handler}");
+        }
+    }
+}
+
+/// Format a jump as pseudo-assembly.
+fn format_jmp(program: &Program, jmp: &Jmp) -> String {
+    let format_return = |return_: &Option<Tid>| match return_ {
+        Some(return_) => format!(" -> {return_}"),
+        None => " -> (no return)".to_string(),
+    };
+    match jmp {
+        Jmp::Branch(target) => format!("goto {target}"),
+        Jmp::BranchInd(target) => format!("goto [{target}]"),
+        Jmp::CBranch { target, condition } => format!("if {condition} goto {target}"),
+        Jmp::Call { target, return_ } => format!(
+            "call {}{}",
+            get_callee_name(program, target),
+            format_return(return_)
+        ),
+        Jmp::CallInd { target, return_ } => {
+            format!("call [{target}]{}", format_return(return_))
+        }
+        Jmp::CallOther {
+            description,
+            return_,
+        } => format!("callother {description}{}", format_return(return_)),
+        Jmp::Return(target) => format!("return [{target}]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{defs, expr};
+
+    #[test]
+    fn dump_function() {
+        let mut program = Program::mock_x64();
+        let mut block = Blk::mock_with_tid("blk_00401000");
+        block.term.defs = defs!["RAX:8 = RDI:8 + 0x1:8"];
+        block.term.defs[0].tid = Tid::mock("instr_00401000_0");
+        block.term.jmps = vec![
+            Term {
+                tid: Tid::mock("instr_00401004_0"),
+                term: Jmp::Call {
+                    target: Tid::new("malloc"),
+                    return_: Some(Tid::new("blk_00401008")),
+                },
+            },
+            Term {
+                tid: Tid::mock("instr_00401008_0"),
+                term: Jmp::Return(expr!("RSP:8")),
+            },
+        ];
+        let sub = Term {
+            tid: Tid::mock("FUN_00401000"),
+            term: Sub::new::<_, &str>("main", vec![block], None),
+        };
+        program.entry_points.insert(sub.tid.clone());
+        program.subs.insert(sub.tid.clone(), sub);
+
+        let dump = dump_pseudo_assembly(&program, &["main".to_string()]);
+        let expected_lines = [
+            "function main (FUN_00401000) @ 00401000 [entry point]",
+            "  blk_00401000:",
+            "    00401000  RAX:8 = (RDI:8 + 0x1:8)",
+            "    00401004  call malloc -> blk_00401008",
+            "    00401008  return [RSP:8]",
+            "",
+        ];
+        assert_eq!(dump.lines().collect::<Vec<_>>(), expected_lines);
+        assert!(dump_pseudo_assembly(&program, &[]).contains("extern malloc"));
+        assert!(dump_pseudo_assembly(&program, &["other".to_string()]).is_empty());
+    }
+}
@@ -1,6 +1,10 @@
 use super::*;
+use crate::error::CweCheckerError;
 use crate::utils::binary::{parse_hex_string_to_u64, BareMetalConfig, MemorySegment};
-use goblin::{elf, Object};
+use goblin::{elf, mach, Object};
+use std::collections::BTreeMap;
+
+mod mach_o;
 
 /// A representation of the runtime image of a binary after being loaded into memory by the loader.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
@@ -11,6 +15,16 @@ pub struct RuntimeMemoryImage {
     pub is_little_endian: bool,
     /// True iff we are analyzing a Linux loadable kernel module.
     pub is_lkm: bool,
+    /// Pointer values that the loader stores into the relocation read-only
+    /// (RELRO) part of writeable segments, e.g. into the global offset table.
+    /// Maps the address of the pointer to its value.
+    #[serde(default)]
+    pub relocated_pointers: BTreeMap<u64, Bitvector>,
+    /// Lengths of string literals that are not NUL-terminated,
+    /// e.g. the string literals of Go binaries.
+    /// Maps the address of the string literal to its length in bytes.
+    #[serde(default)]
+    pub string_lengths: BTreeMap<u64, u64>,
 }
 
 impl RuntimeMemoryImage {
@@ -21,13 +35,20 @@ impl RuntimeMemoryImage {
             memory_segments: Vec::new(),
             is_little_endian,
             is_lkm: false,
+            relocated_pointers: BTreeMap::new(),
+            string_lengths: BTreeMap::new(),
         }
     }
 
     /// Generate a runtime memory image for a given binary.
     ///
-    /// The function can parse ELF and PE files as input.
-    pub fn new(binary: &[u8]) -> Result<Self, Error> {
+    /// The function can parse ELF, PE and Mach-O files as input.
+    pub fn new(binary: &[u8]) -> Result<Self, CweCheckerError> {
+        Self::from_binary(binary).map_err(CweCheckerError::Loader)
+    }
+
+    /// Generate a runtime memory image for an ELF, PE or Mach-O file.
+    fn from_binary(binary: &[u8]) -> Result<Self, Error> {
         match Object::parse(binary)? {
             Object::Elf(elf_file) => match elf_file.header.e_type {
                 elf::header::ET_REL => Self::from_elf_sections(binary, elf_file),
@@ -51,10 +72,16 @@ impl RuntimeMemoryImage {
                     memory_segments,
                     is_little_endian: true,
                     is_lkm: false,
+                    relocated_pointers: BTreeMap::new(),
+                    string_lengths: BTreeMap::new(),
                 };
                 memory_image.add_global_memory_offset(pe_file.image_base as u64);
                 Ok(memory_image)
             }
+            Object::Mach(mach::Mach::Binary(mach_o)) => Self::from_mach_o(binary, &mach_o),
+            Object::Mach(mach::Mach::Fat(_)) => Err(anyhow!(
+                "Universal Mach-O binaries are not supported. Extract the binary for one architecture first."
+            )),
             _ => Err(anyhow!("Object type not supported.")),
         }
     }
@@ -73,11 +100,175 @@ impl RuntimeMemoryImage {
             return Err(anyhow!("No loadable segments found"));
         }
 
-        Ok(Self {
+        let mut memory_image = Self {
             memory_segments,
             is_little_endian: elf_file.header.endianness().unwrap().is_little(),
             is_lkm: false,
-        })
+            relocated_pointers: BTreeMap::new(),
+            string_lengths: BTreeMap::new(),
+        };
+        memory_image.add_relro_pointer_relocations(&elf_file);
+        memory_image.add_mips_got_pointers(&elf_file);
+
+        Ok(memory_image)
+    }
+
+    /// Add the pointers that the loader writes into the relocation read-only
+    /// (RELRO) memory range of the ELF file to the `relocated_pointers`.
+    ///
+    /// Position-independent code accesses (string) constants through pointers
+    /// in the global offset table or in `.data.rel.ro`. Although these pointers
+    /// are located in writeable segments, they are read-only after the loader
+    /// has processed the relocations. Only relocations that store an address
+    /// inside the binary itself are considered, i.e. relative relocations and
+    /// `GLOB_DAT` relocations of symbols defined in the binary.
+    ///
+    /// Supported architectures are x86, x86-64, ARM, AArch64 and RISC-V.
+    fn add_relro_pointer_relocations(&mut self, elf_file: &elf::Elf) {
+        let relro_ranges: Vec<_> = elf_file
+            .program_headers
+            .iter()
+            .filter(|header| header.p_type == elf::program_header::PT_GNU_RELRO)
+            .map(|header| header.vm_range())
+            .collect();
+        let pointer_size = ByteSize::new(if elf_file.is_64 { 8 } else { 4 });
+        for reloc in elf_file.dynrelas.iter().chain(elf_file.dynrels.iter()) {
+            if !relro_ranges
+                .iter()
+                .any(|range| range.contains(&(reloc.r_offset as usize)))
+            {
+                continue;
+            }
+            let value = match get_pointer_relocation_kind(elf_file.header.e_machine, reloc.r_type) {
+                Some(PointerRelocationKind::Relative) => match reloc.r_addend {
+                    Some(addend) => addend as u64,
+                    // Without explicit addend the relative address is stored at the relocated address.
+                    None => match self.read_raw(reloc.r_offset, pointer_size) {
+                        Some(value) => value,
+                        None => continue,
+                    },
+                },
+                Some(PointerRelocationKind::GlobalData) => {
+                    match elf_file.dynsyms.get(reloc.r_sym) {
+                        Some(symbol) if symbol.st_shndx != 0 && symbol.st_value != 0 => symbol
+                            .st_value
+                            .wrapping_add(reloc.r_addend.unwrap_or(0) as u64),
+                        _ => continue,
+                    }
+                }
+                None => continue,
+            };
+            self.relocated_pointers.insert(
+                reloc.r_offset,
+                Bitvector::from_u64(value).into_resize_unsigned(pointer_size),
+            );
+        }
+    }
+
+    /// Add the pointers in the global offset table (GOT) of MIPS ELF files to
+    /// the `relocated_pointers`.
+    ///
+    /// MIPS code accesses global data through GOT entries that are addressed
+    /// relative to the global pointer register `gp`. In contrast to other
+    /// architectures the loader fills the MIPS GOT without relocations: Local
+    /// entries contain link-time addresses that are only shifted by the load
+    /// bias and global entries contain the addresses of the dynamic symbols
+    /// starting at the index given by the `DT_MIPS_GOTSYM` tag.
+    fn add_mips_got_pointers(&mut self, elf_file: &elf::Elf) {
+        if elf_file.header.e_machine != elf::header::EM_MIPS {
+            return;
+        }
+        let Some(dynamic) = &elf_file.dynamic else {
+            return;
+        };
+        let get_dynamic_value = |tag| {
+            dynamic
+                .dyns
+                .iter()
+                .find(|entry| entry.d_tag == tag)
+                .map(|entry| entry.d_val)
+        };
+        let (Some(got_address), Some(local_gotno), Some(gotsym), Some(symtabno)) = (
+            get_dynamic_value(elf::dynamic::DT_PLTGOT),
+            get_dynamic_value(DT_MIPS_LOCAL_GOTNO),
+            get_dynamic_value(DT_MIPS_GOTSYM),
+            get_dynamic_value(DT_MIPS_SYMTABNO),
+        ) else {
+            return;
+        };
+        let global_entry_values: Vec<Option<u64>> = (gotsym..symtabno)
+            .map(|index| match elf_file.dynsyms.get(index as usize) {
+                Some(symbol) if symbol.st_shndx != 0 && symbol.st_value != 0 => {
+                    Some(symbol.st_value)
+                }
+                _ => None,
+            })
+            .collect();
+        let pointer_size = ByteSize::new(if elf_file.is_64 { 8 } else { 4 });
+
+        self.add_mips_got_entries(got_address, local_gotno, &global_entry_values, pointer_size);
+    }
+
+    /// Add the entries of a MIPS GOT at the given address to the `relocated_pointers`.
+    ///
+    /// The values of the first `local_gotno` entries are read from the memory image.
+    /// The values of the following global entries are given by `global_entry_values`,
+    /// where `None` marks symbols that are not defined in the binary itself.
+    fn add_mips_got_entries(
+        &mut self,
+        got_address: u64,
+        local_gotno: u64,
+        global_entry_values: &[Option<u64>],
+        pointer_size: ByteSize,
+    ) {
+        let entry_address = |index: u64| got_address + index * u64::from(pointer_size);
+        let most_significant_bit = 1u64 << (u64::from(pointer_size) * 8 - 1);
+        // The first entry is reserved for the lazy resolver.
+        for index in 1..local_gotno {
+            let Some(value) = self.read_raw(entry_address(index), pointer_size) else {
+                continue;
+            };
+            // The GNU toolchain reserves the second entry for the module pointer
+            // and marks it by setting the most significant bit.
+            if value == 0 || (index == 1 && value & most_significant_bit != 0) {
+                continue;
+            }
+            self.relocated_pointers.insert(
+                entry_address(index),
+                Bitvector::from_u64(value).into_resize_unsigned(pointer_size),
+            );
+        }
+        for (index, value) in (local_gotno..).zip(global_entry_values) {
+            if let Some(value) = value {
+                self.relocated_pointers.insert(
+                    entry_address(index),
+                    Bitvector::from_u64(*value).into_resize_unsigned(pointer_size),
+                );
+            }
+        }
+    }
+
+    /// Read the unsigned integer of the given size stored at the given address,
+    /// regardless of whether the containing segment is writeable.
+    fn read_raw(&self, address: u64, size: ByteSize) -> Option<u64> {
+        let segment = self.memory_segments.iter().find(|segment| {
+            address >= segment.base_address
+                && address + u64::from(size) <= segment.base_address + segment.bytes.len() as u64
+        })?;
+        let index = (address - segment.base_address) as usize;
+        let bytes = &segment.bytes[index..index + u64::from(size) as usize];
+        let value = if self.is_little_endian {
+            bytes
+                .iter()
+                .rev()
+                .fold(0, |accum, byte| (accum << 8) | *byte as u64)
+        } else {
+            bytes
+                .iter()
+                .fold(0, |accum, byte| (accum << 8) | *byte as u64)
+        };
+
+        Some(value)
     }
 
     /// Generate a runtime memory image for a relocatable object file.
@@ -115,6 +306,8 @@ impl RuntimeMemoryImage {
             is_little_endian: elf_file.header.endianness().unwrap().is_little(),
             is_lkm: get_section(".modinfo", &elf_file).is_some()
                 && get_section(".gnu.linkonce.this_module", &elf_file).is_some(),
+            relocated_pointers: BTreeMap::new(),
+            string_lengths: BTreeMap::new(),
         })
     }
 
@@ -123,42 +316,60 @@ impl RuntimeMemoryImage {
     /// The generated runtime memory image contains:
     /// * one memory region corresponding to non-volatile memory
     /// * one memory region corresponding to volatile memory (RAM)
+    /// * the additional memory regions given in the configuration
     ///
     /// See [`BareMetalConfig`] for more information about the assumed memory layout for bare metal binaries.
     pub fn new_from_bare_metal(
         binary: &[u8],
         bare_metal_config: &BareMetalConfig,
-    ) -> Result<Self, Error> {
+    ) -> Result<Self, CweCheckerError> {
         let processor_id_parts: Vec<&str> = bare_metal_config.processor_id.split(':').collect();
         if processor_id_parts.len() < 3 {
-            return Err(anyhow!("Could not parse processor ID."));
+            return Err(CweCheckerError::Config(anyhow!(
+                "Could not parse processor ID."
+            )));
         }
         let is_little_endian = match processor_id_parts[1] {
             "LE" => true,
             "BE" => false,
-            _ => return Err(anyhow!("Could not parse endianness of the processor ID.")),
+            _ => {
+                return Err(CweCheckerError::Config(anyhow!(
+                    "Could not parse endianness of the processor ID."
+                )))
+            }
         };
         let flash_base_address = parse_hex_string_to_u64(&bare_metal_config.flash_base_address)?;
         let ram_base_address = parse_hex_string_to_u64(&bare_metal_config.ram_base_address)?;
         let ram_size = parse_hex_string_to_u64(&bare_metal_config.ram_size)?;
         // Check that the whole binary is contained in addressable space.
-        let address_bit_length = processor_id_parts[2].parse::<u64>()?;
-        match flash_base_address.checked_add(binary.len() as u64) {
-            Some(max_address) => {
-                if (max_address >> address_bit_length) != 0 {
-                    return Err(anyhow!("Binary too large for given base address"));
-                }
-            }
-            None => return Err(anyhow!("Binary too large for given base address")),
+        let address_bit_length = processor_id_parts[2]
+            .parse::<u64>()
+            .map_err(|err| CweCheckerError::Config(err.into()))?;
+        if flash_base_address
+            .checked_add(binary.len() as u64)
+            .is_none_or(|max_address| (max_address >> address_bit_length) != 0)
+        {
+            return Err(CweCheckerError::Config(anyhow!(
+                "Binary too large for given base address"
+            )));
+        }
+
+        let mut memory_segments = vec![
+            MemorySegment::from_bare_metal_file(binary, flash_base_address),
+            MemorySegment::new_bare_metal_ram_segment(ram_base_address, ram_size),
+        ];
+        for region in bare_metal_config.memory_regions.iter() {
+            memory_segments.push(MemorySegment::from_bare_metal_memory_region(
+                binary, region,
+            )?);
         }
 
         Ok(RuntimeMemoryImage {
-            memory_segments: vec![
-                MemorySegment::from_bare_metal_file(binary, flash_base_address),
-                MemorySegment::new_bare_metal_ram_segment(ram_base_address, ram_size),
-            ],
+            memory_segments,
             is_little_endian,
             is_lkm: false,
+            relocated_pointers: BTreeMap::new(),
+            string_lengths: BTreeMap::new(),
         })
     }
 
@@ -187,6 +398,7 @@ impl RuntimeMemoryImage {
                 }
                 ty => Err(anyhow!("Unsupported ELF type: e_type {}", ty)),
             },
+            Object::Mach(mach::Mach::Binary(mach_o)) => mach_o::get_mach_o_image_base(&mach_o),
             _ => Err(anyhow!("Binary type not yet supported")),
         }
     }
@@ -204,6 +416,16 @@ impl RuntimeMemoryImage {
         for segment in self.memory_segments.iter_mut() {
             segment.base_address += offset;
         }
+        self.relocated_pointers = std::mem::take(&mut self.relocated_pointers)
+            .into_iter()
+            .map(|(address, pointer)| {
+                let value = pointer.try_to_u64().unwrap().wrapping_add(offset);
+                (
+                    address + offset,
+                    Bitvector::from_u64(value).into_resize_unsigned(pointer.bytesize()),
+                )
+            })
+            .collect();
     }
 
     /// Read the contents of the memory image at the given address
@@ -213,6 +435,8 @@ impl RuntimeMemoryImage {
     /// i.e. values are interpreted with the endianness of the CPU architecture.
     /// If the address points to a writeable segment, the returned value is a `Ok(None)` value,
     /// since the data may change during program execution.
+    /// The only exception are pointers written by the loader into relocation read-only memory,
+    /// see [`RuntimeMemoryImage::relocated_pointers`].
     ///
     /// Returns an error if the address is not contained in the global data address range.
     pub fn read(&self, address: &Bitvector, size: ByteSize) -> Result<Option<Bitvector>, Error> {
@@ -223,8 +447,11 @@ impl RuntimeMemoryImage {
                 && address <= segment.base_address + segment.bytes.len() as u64 - u64::from(size)
             {
                 if segment.write_flag {
-                    // The segment is writeable, thus we do not know the content at runtime.
-                    return Ok(None);
+                    return Ok(self
+                        .relocated_pointers
+                        .get(&address)
+                        .filter(|pointer| pointer.bytesize() == size)
+                        .cloned());
                 }
                 let index = (address - segment.base_address) as usize;
                 let mut bytes = segment.bytes[index..index + u64::from(size) as usize].to_vec();
@@ -244,19 +471,64 @@ impl RuntimeMemoryImage {
         Err(anyhow!("Address is not a valid global memory address."))
     }
 
+    /// Read the string literal at the given address and check whether it is a valid UTF8 string.
+    ///
+    /// If the length of the string literal is known (see [`RuntimeMemoryImage::string_lengths`]),
+    /// then exactly that many bytes are read.
+    /// Otherwise the string is read until the first null byte.
+    pub fn read_string_literal(&self, address: &Bitvector) -> Result<&str, Error> {
+        match self.string_lengths.get(&address.try_to_u64()?) {
+            Some(length) => self.read_string_with_length(address, *length),
+            None => self.read_string_until_null_terminator(address),
+        }
+    }
+
+    /// Read `length` bytes of memory from the given address onwards and check whether the
+    /// content is a valid UTF8 string.
+    ///
+    /// Returns an error if the bytes are not contained in a non-writeable memory segment.
+    pub fn read_string_with_length(&self, address: &Bitvector, length: u64) -> Result<&str, Error> {
+        let address = address.try_to_u64()?;
+        for segment in self.memory_segments.iter() {
+            let segment_end = segment.base_address + segment.bytes.len() as u64;
+            if address >= segment.base_address
+                && address
+                    .checked_add(length)
+                    .is_some_and(|end| end <= segment_end)
+            {
+                if segment.write_flag {
+                    return Err(anyhow!("String is located in writeable memory."));
+                }
+                let start_index = (address - segment.base_address) as usize;
+                let bytes = &segment.bytes[start_index..start_index + length as usize];
+                return Ok(std::str::from_utf8(bytes)?);
+            }
+        }
+
+        Err(anyhow!("Address is not a valid global memory address."))
+    }
+
     /// Read the contents of memory from a given address onwards until a null byte is reached and checks whether the
     /// content is a valid UTF8 string.
     pub fn read_string_until_null_terminator(&self, address: &Bitvector) -> Result<&str, Error> {
-        let address = address.try_to_u64().unwrap();
+        self.read_c_string(address, u64::MAX)
+    }
+
+    /// Read the null-terminated string at the given address and check whether it is a valid UTF8 string.
+    ///
+    /// Returns an error if no null byte is found within the first `max_length` bytes of the string
+    /// (not counting the null byte itself).
+    pub fn read_c_string(&self, address: &Bitvector, max_length: u64) -> Result<&str, Error> {
+        let address = address.try_to_u64()?;
         for segment in self.memory_segments.iter() {
             if address >= segment.base_address
                 && address <= segment.base_address + segment.bytes.len() as u64
             {
                 let start_index = (address - segment.base_address) as usize;
-                if let Some(end_index) = segment.bytes[start_index..].iter().position(|&b| b == 0) {
-                    let c_str = std::ffi::CStr::from_bytes_with_nul(
-                        &segment.bytes[start_index..start_index + end_index + 1],
-                    )?;
+                let bytes = &segment.bytes[start_index..];
+                let bytes = &bytes[..bytes.len().min(max_length.saturating_add(1) as usize)];
+                if let Some(end_index) = bytes.iter().position(|&b| b == 0) {
+                    let c_str = std::ffi::CStr::from_bytes_with_nul(&bytes[..end_index + 1])?;
                     return Ok(c_str.to_str()?);
                 } else {
                     return Err(anyhow!("Not a valid string in memory."));
@@ -267,6 +539,95 @@ impl RuntimeMemoryImage {
         Err(anyhow!("Address is not a valid global memory address."))
     }
 
+    /// Read the null-terminated wide string at the given address.
+    ///
+    /// Characters of size 2 are decoded as UTF-16 and characters of size 4 as UTF-32,
+    /// using the endianness of the CPU architecture.
+    /// Returns an error if no null character is found within the first `max_length` characters of the string
+    /// or if the string is not correctly encoded.
+    pub fn read_wide_string(
+        &self,
+        address: &Bitvector,
+        char_size: ByteSize,
+        max_length: u64,
+    ) -> Result<String, Error> {
+        if char_size != ByteSize::new(2) && char_size != ByteSize::new(4) {
+            return Err(anyhow!("Unsupported wide character size {char_size}."));
+        }
+        let mut code_units: Vec<u32> = Vec::new();
+        let mut char_address = address.clone();
+        let char_size_bitvec =
+            Bitvector::from_u64(u64::from(char_size)).into_resize_unsigned(address.bytesize());
+        loop {
+            let code_unit = self
+                .read(&char_address, char_size)?
+                .ok_or_else(|| anyhow!("String is located in writeable memory."))?
+                .try_to_u32()?;
+            if code_unit == 0 {
+                break;
+            }
+            if code_units.len() as u64 >= max_length {
+                return Err(anyhow!("Not a valid string in memory."));
+            }
+            code_units.push(code_unit);
+            char_address = char_address.bin_op(BinOpType::IntAdd, &char_size_bitvec)?;
+        }
+        if char_size == ByteSize::new(2) {
+            let code_units: Vec<u16> = code_units.into_iter().map(|unit| unit as u16).collect();
+            Ok(String::from_utf16(&code_units)?)
+        } else {
+            code_units
+                .into_iter()
+                .map(|unit| {
+                    char::from_u32(unit).ok_or_else(|| anyhow!("Invalid UTF-32 character."))
+                })
+                .collect()
+        }
+    }
+
+    /// Read the pointer of the given size at the given address.
+    ///
+    /// Like [`RuntimeMemoryImage::read`], the pointer is only known (i.e. not `Ok(None)`)
+    /// if it is located in read-only memory or was written by the loader into relocation read-only memory.
+    pub fn read_pointer(&self, address: u64, pointer_size: ByteSize) -> Result<Option<u64>, Error> {
+        let address = Bitvector::from_u64(address).into_resize_unsigned(pointer_size);
+        match self.read(&address, pointer_size)? {
+            Some(pointer) => Ok(Some(pointer.try_to_u64()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Read an array of `count` elements of the given size starting at the given address.
+    ///
+    /// Each element is read with [`RuntimeMemoryImage::read`],
+    /// i.e. elements in writeable memory are `None`.
+    /// Returns an error if the array is not completely contained in global memory.
+    pub fn read_array(
+        &self,
+        address: &Bitvector,
+        element_size: ByteSize,
+        count: u64,
+    ) -> Result<Vec<Option<Bitvector>>, Error> {
+        let start = address.try_to_u64()?;
+        (0..count)
+            .map(|index| {
+                let element_address = Bitvector::from_u64(start + index * u64::from(element_size))
+                    .into_resize_unsigned(address.bytesize());
+                self.read(&element_address, element_size)
+            })
+            .collect()
+    }
+
+    /// Iterate over the pointers that the loader writes into relocation read-only memory,
+    /// see [`RuntimeMemoryImage::relocated_pointers`].
+    ///
+    /// Yields pairs of the address of the pointer and its value.
+    pub fn relocations(&self) -> impl Iterator<Item = (u64, &Bitvector)> {
+        self.relocated_pointers
+            .iter()
+            .map(|(address, pointer)| (*address, pointer))
+    }
+
     /// Checks whether the constant is a global memory address.
     pub fn is_global_memory_address(&self, constant: &Bitvector) -> bool {
         if self.read(constant, constant.bytesize()).is_ok() {
@@ -370,6 +731,39 @@ fn get_section<'a>(name: &str, elf_file: &'a elf::Elf<'a>) -> Option<&'a elf::Se
     })
 }
 
+/// Dynamic tag for the number of local entries of the MIPS GOT.
+const DT_MIPS_LOCAL_GOTNO: u64 = 0x7000000a;
+/// Dynamic tag for the number of entries in the dynamic symbol table of MIPS ELF files.
+const DT_MIPS_SYMTABNO: u64 = 0x70000011;
+/// Dynamic tag for the index of the first dynamic symbol with an entry in the MIPS GOT.
+const DT_MIPS_GOTSYM: u64 = 0x70000013;
+
+/// Kinds of relocations that store a pointer into the binary itself.
+enum PointerRelocationKind {
+    /// The pointer is the image-relative address given by the addend.
+    Relative,
+    /// The pointer is the address of the symbol of the relocation.
+    GlobalData,
+}
+
+/// Return the kind of the relocation type if it stores a pointer into the binary itself.
+fn get_pointer_relocation_kind(machine: u16, r_type: u32) -> Option<PointerRelocationKind> {
+    use elf::header::*;
+    use elf::reloc::*;
+    match (machine, r_type) {
+        (EM_X86_64, R_X86_64_RELATIVE)
+        | (EM_386, R_386_RELATIVE)
+        | (EM_ARM, R_ARM_RELATIVE)
+        | (EM_AARCH64, R_AARCH64_RELATIVE)
+        | (EM_RISCV, R_RISCV_RELATIVE) => Some(PointerRelocationKind::Relative),
+        (EM_X86_64, R_X86_64_GLOB_DAT)
+        | (EM_386, R_386_GLOB_DAT)
+        | (EM_ARM, R_ARM_GLOB_DAT)
+        | (EM_AARCH64, R_AARCH64_GLOB_DAT) => Some(PointerRelocationKind::GlobalData),
+        _ => None,
+    }
+}
+
 /// Returns true iff the section header will be loaded into memory by Ghidra.
 #[inline]
 fn is_loaded(section_header: &elf::SectionHeader) -> bool {
@@ -380,6 +774,7 @@ fn is_loaded(section_header: &elf::SectionHeader) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use crate::utils::binary::{BareMetalConfig, MemorySegment};
     use crate::{bitvec, intermediate_representation::*};
 
     #[test]
@@ -397,6 +792,107 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bare_metal_memory_regions() {
+        let config: BareMetalConfig = serde_json::from_str(
+            r#"
+            {
+                "processor_id": "ARM:LE:32:v8",
+                "flash_base_address": "0x08000000",
+                "ram_base_address": "0x20000000",
+                "ram_size": "0x100",
+                "memory_regions": [
+                    { "base_address": "0x0", "size": "0x4", "file_offset": "0x4" },
+                    { "base_address": "0x40000000", "size": "0x10", "is_writeable": true }
+                ],
+                "entry_points": ["0x08000001"]
+            }
+            "#,
+        )
+        .unwrap();
+        let binary = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let mem_image = RuntimeMemoryImage::new_from_bare_metal(&binary, &config).unwrap();
+        assert_eq!(mem_image.memory_segments.len(), 4);
+        assert_eq!(
+            mem_image.read(&bitvec!("0x0:4"), ByteSize::new(4)).unwrap(),
+            bitvec!("0x08070605:4").into()
+        );
+        assert!(!mem_image.is_address_writeable(&bitvec!("0x0:4")).unwrap());
+        assert!(mem_image
+            .is_address_writeable(&bitvec!("0x40000008:4"))
+            .unwrap());
+
+        let mut config = config;
+        config.memory_regions[0].file_offset = Some("0x6".to_string());
+        assert!(RuntimeMemoryImage::new_from_bare_metal(&binary, &config).is_err());
+    }
+
+    #[test]
+    fn mips_got_entries() {
+        let mut mem_image = RuntimeMemoryImage::mock();
+        mem_image.memory_segments[1].bytes =
+            [0x02, 0x30, 0x00, 0x80, 0x02, 0x30, 0x00, 0x00].to_vec();
+        mem_image.add_mips_got_entries(0x1ffc, 3, &[None, Some(0x3002)], ByteSize::new(4));
+        // The module pointer entry is skipped.
+        assert_eq!(mem_image.relocated_pointers.get(&0x2000), None);
+        assert_eq!(
+            mem_image.relocated_pointers.get(&0x2004),
+            Some(&bitvec!("0x3002:4"))
+        );
+        assert_eq!(mem_image.relocated_pointers.get(&0x2008), None);
+        assert_eq!(
+            mem_image.relocated_pointers.get(&0x200c),
+            Some(&bitvec!("0x3002:4"))
+        );
+        assert_eq!(
+            mem_image
+                .read(&bitvec!("0x2004:4"), ByteSize::new(4))
+                .unwrap(),
+            Some(bitvec!("0x3002:4"))
+        );
+    }
+
+    #[test]
+    fn read_relocated_pointer() {
+        let mut mem_image = RuntimeMemoryImage::mock();
+        mem_image
+            .relocated_pointers
+            .insert(0x2000, bitvec!("0x3002:4"));
+        // Only reads of the relocated pointer itself are constant.
+        assert_eq!(
+            mem_image
+                .read(&bitvec!("0x2000:4"), ByteSize::new(4))
+                .unwrap(),
+            Some(bitvec!("0x3002:4"))
+        );
+        assert_eq!(
+            mem_image
+                .read(&bitvec!("0x2004:4"), ByteSize::new(4))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            mem_image
+                .read(&bitvec!("0x2000:4"), ByteSize::new(2))
+                .unwrap(),
+            None
+        );
+        // Relocated pointers are shifted together with the memory segments.
+        mem_image.add_global_memory_offset(0x10000);
+        assert_eq!(
+            mem_image
+                .read(&bitvec!("0x12000:4"), ByteSize::new(4))
+                .unwrap(),
+            Some(bitvec!("0x13002:4"))
+        );
+        assert_eq!(
+            mem_image
+                .read_string_until_null_terminator(&bitvec!("0x13002:4"))
+                .unwrap(),
+            "Hello World"
+        );
+    }
+
     #[test]
     fn ro_data_pointer() {
         let mem_image = RuntimeMemoryImage::mock();
@@ -420,4 +916,64 @@ mod tests {
                 .unwrap(),
         );
     }
+
+    #[test]
+    fn read_typed_data() {
+        let mut mem_image = RuntimeMemoryImage::mock();
+        assert_eq!(
+            mem_image.read_pointer(0x4000, ByteSize::new(4)).unwrap(),
+            Some(0x3002)
+        );
+        assert_eq!(
+            mem_image.read_c_string(&bitvec!("0x3002:4"), 11).unwrap(),
+            "Hello World"
+        );
+        assert!(mem_image.read_c_string(&bitvec!("0x3002:4"), 5).is_err());
+        assert_eq!(
+            mem_image
+                .read_array(&bitvec!("0x4000:4"), ByteSize::new(2), 2)
+                .unwrap(),
+            vec![Some(bitvec!("0x3002:2")), Some(bitvec!("0x0:2"))]
+        );
+        assert!(mem_image
+            .read_array(&bitvec!("0x4000:4"), ByteSize::new(4), 3)
+            .is_err());
+
+        // Contains the wide string "Hi" with 2-byte characters
+        // and the wide string "H" with 4-byte characters.
+        mem_image.memory_segments.push(MemorySegment {
+            bytes: [
+                0x48, 0x00, 0x69, 0x00, 0x00, 0x00, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ]
+            .to_vec(),
+            base_address: 0x8000,
+            read_flag: true,
+            write_flag: false,
+            execute_flag: false,
+        });
+        let address = bitvec!("0x8000:4");
+        assert_eq!(
+            mem_image
+                .read_wide_string(&address, ByteSize::new(2), 10)
+                .unwrap(),
+            "Hi"
+        );
+        assert!(mem_image
+            .read_wide_string(&address, ByteSize::new(2), 1)
+            .is_err());
+        assert_eq!(
+            mem_image
+                .read_wide_string(&bitvec!("0x8006:4"), ByteSize::new(4), 10)
+                .unwrap(),
+            "H"
+        );
+
+        mem_image
+            .relocated_pointers
+            .insert(0x2000, bitvec!("0x3002:4"));
+        assert_eq!(
+            mem_image.relocations().collect::<Vec<_>>(),
+            vec![(0x2000, &bitvec!("0x3002:4"))]
+        );
+    }
 }
@@ -0,0 +1,290 @@
+//! Generation of runtime memory images for Mach-O executables and dynamic libraries.
+//!
+//! Newer Mach-O files do not contain the pointer values of the data segments
+//! directly. Instead, each pointer encodes either the target of a rebase or an
+//! import together with the offset to the next pointer to fix up. These chains
+//! are described in the `LC_DYLD_CHAINED_FIXUPS` load command. We decode the
+//! chains and write the resulting pointer values into the memory image, just
+//! like the loader does.
+
+use super::*;
+use crate::utils::binary::MemorySegment;
+use goblin::mach;
+
+/// Marks pages without fixups in the page start table.
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xFFFF;
+/// Pointer format of arm64e binaries where rebase targets are virtual addresses.
+const DYLD_CHAINED_PTR_ARM64E: u16 = 1;
+/// Pointer format where rebase targets are virtual addresses.
+const DYLD_CHAINED_PTR_64: u16 = 2;
+/// Pointer format where rebase targets are offsets to the image base.
+const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+/// Pointer format of arm64e userland binaries where rebase targets are offsets to the image base.
+const DYLD_CHAINED_PTR_ARM64E_USERLAND: u16 = 9;
+/// Like [`DYLD_CHAINED_PTR_ARM64E_USERLAND`] but with 24-bit import ordinals.
+const DYLD_CHAINED_PTR_ARM64E_USERLAND24: u16 = 12;
+
+impl RuntimeMemoryImage {
+    /// Generate a runtime memory image for a Mach-O executable or dynamic library.
+    pub(super) fn from_mach_o(binary: &[u8], mach_o: &mach::MachO) -> Result<Self, Error> {
+        let memory_segments: Vec<MemorySegment> = mach_o
+            .segments
+            .iter()
+            .filter(|segment| segment.vmsize != 0)
+            .map(MemorySegment::from_mach_o_segment)
+            // The `__PAGEZERO` segment is not accessible.
+            .filter(|segment| segment.read_flag)
+            .collect();
+        if memory_segments.is_empty() {
+            return Err(anyhow!("No loadable segments found"));
+        }
+        let mut memory_image = RuntimeMemoryImage {
+            memory_segments,
+            is_little_endian: mach_o.little_endian,
+            is_lkm: false,
+            relocated_pointers: BTreeMap::new(),
+            string_lengths: BTreeMap::new(),
+        };
+
+        let image_base = get_mach_o_image_base(mach_o)?;
+        for load_command in mach_o.load_commands.iter() {
+            if let mach::load_command::CommandVariant::DyldChainedFixups(command) =
+                &load_command.command
+            {
+                let fixups = binary
+                    .get(command.dataoff as usize..(command.dataoff + command.datasize) as usize)
+                    .ok_or_else(|| anyhow!("Chained fixups out of bounds"))?;
+                memory_image.apply_chained_fixups(fixups, image_base)?;
+            }
+        }
+
+        Ok(memory_image)
+    }
+
+    /// Decode the chained fixups given by the `dyld_chained_fixups_header`
+    /// at the start of `fixups` and write the resulting pointers into the
+    /// memory image.
+    ///
+    /// Rebases are replaced by the target address. Since the addresses of
+    /// imported symbols are unknown, pointers to them are set to zero.
+    fn apply_chained_fixups(&mut self, fixups: &[u8], image_base: u64) -> Result<(), Error> {
+        let starts_offset = read_u32(fixups, 4)? as usize;
+        let segment_count = read_u32(fixups, starts_offset)? as usize;
+        for segment_index in 0..segment_count {
+            let segment_info_offset = read_u32(fixups, starts_offset + 4 + 4 * segment_index)?;
+            if segment_info_offset == 0 {
+                // The segment does not contain fixups.
+                continue;
+            }
+            let segment_starts = starts_offset + segment_info_offset as usize;
+            let page_size = read_u16(fixups, segment_starts + 4)? as u64;
+            let pointer_format = read_u16(fixups, segment_starts + 6)?;
+            let segment_offset = read_u64(fixups, segment_starts + 8)?;
+            let page_count = read_u16(fixups, segment_starts + 20)? as usize;
+            for page_index in 0..page_count {
+                let page_start = read_u16(fixups, segment_starts + 22 + 2 * page_index)?;
+                if page_start == DYLD_CHAINED_PTR_START_NONE {
+                    continue;
+                }
+                let mut address =
+                    image_base + segment_offset + page_index as u64 * page_size + page_start as u64;
+                loop {
+                    let raw_pointer = self
+                        .read_raw(address, ByteSize::new(8))
+                        .ok_or_else(|| anyhow!("Chained fixup at 0x{address:x} out of bounds"))?;
+                    let (target, next, stride) =
+                        decode_chained_pointer(raw_pointer, pointer_format, image_base)?;
+                    self.write_raw(address, target.unwrap_or(0), ByteSize::new(8))?;
+                    if next == 0 {
+                        break;
+                    }
+                    address += next * stride;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the unsigned integer of the given size to the given address.
+    fn write_raw(&mut self, address: u64, value: u64, size: ByteSize) -> Result<(), Error> {
+        let is_little_endian = self.is_little_endian;
+        let segment = self
+            .memory_segments
+            .iter_mut()
+            .find(|segment| {
+                address >= segment.base_address
+                    && address + u64::from(size)
+                        <= segment.base_address + segment.bytes.len() as u64
+            })
+            .ok_or_else(|| anyhow!("Address not contained in runtime memory image"))?;
+        let index = (address - segment.base_address) as usize;
+        let size = u64::from(size) as usize;
+        let bytes = if is_little_endian {
+            value.to_le_bytes()[..size].to_vec()
+        } else {
+            value.to_be_bytes()[8 - size..].to_vec()
+        };
+        segment.bytes[index..index + size].copy_from_slice(&bytes);
+
+        Ok(())
+    }
+}
+
+/// Get the address of the `__TEXT` segment, which is the base address of the image.
+pub(super) fn get_mach_o_image_base(mach_o: &mach::MachO) -> Result<u64, Error> {
+    mach_o
+        .segments
+        .iter()
+        .find(|segment| matches!(segment.name(), Ok(name) if name == mach::constants::SEG_TEXT))
+        .map(|segment| segment.vmaddr)
+        .context("No __TEXT segment found.")
+}
+
+/// Decode a pointer of a fixup chain in the given pointer format.
+///
+/// Returns the target address for rebases (`None` for imports),
+/// the distance to the next pointer of the chain (zero for the end of the chain)
+/// and the stride in bytes in which the distance is given.
+fn decode_chained_pointer(
+    raw_pointer: u64,
+    pointer_format: u16,
+    image_base: u64,
+) -> Result<(Option<u64>, u64, u64), Error> {
+    match pointer_format {
+        DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => {
+            let is_bind = (raw_pointer >> 63) == 1;
+            let next = (raw_pointer >> 51) & 0xFFF;
+            let target = (!is_bind).then(|| {
+                let high8 = (raw_pointer >> 36) & 0xFF;
+                let mut target = raw_pointer & 0xF_FFFF_FFFF;
+                if pointer_format == DYLD_CHAINED_PTR_64_OFFSET {
+                    target += image_base;
+                }
+                (high8 << 56) | target
+            });
+            Ok((target, next, 4))
+        }
+        DYLD_CHAINED_PTR_ARM64E
+        | DYLD_CHAINED_PTR_ARM64E_USERLAND
+        | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => {
+            let is_auth = (raw_pointer >> 63) == 1;
+            let is_bind = (raw_pointer >> 62) & 1 == 1;
+            let next = (raw_pointer >> 51) & 0x7FF;
+            let target = match (is_bind, is_auth) {
+                (true, _) => None,
+                // Authenticated rebases always store the offset to the image base.
+                (false, true) => Some(image_base + (raw_pointer & 0xFFFF_FFFF)),
+                (false, false) => {
+                    let high8 = (raw_pointer >> 43) & 0xFF;
+                    let mut target = raw_pointer & 0x7FF_FFFF_FFFF;
+                    if pointer_format != DYLD_CHAINED_PTR_ARM64E {
+                        target += image_base;
+                    }
+                    Some((high8 << 56) | target)
+                }
+            };
+            Ok((target, next, 8))
+        }
+        _ => Err(anyhow!(
+            "Unsupported chained fixup pointer format {pointer_format}"
+        )),
+    }
+}
+
+/// Read a little-endian `u16` at the given offset.
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Error> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("Chained fixups truncated"))
+}
+
+/// Read a little-endian `u32` at the given offset.
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("Chained fixups truncated"))
+}
+
+/// Read a little-endian `u64` at the given offset.
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, Error> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("Chained fixups truncated"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a `dyld_chained_fixups_header` with a single segment whose
+    /// first page contains a chain starting at `page_start`.
+    fn mock_fixups(pointer_format: u16, segment_offset: u64, page_start: u16) -> Vec<u8> {
+        let mut fixups = Vec::new();
+        // fixups_version, starts_offset
+        fixups.extend(0u32.to_le_bytes());
+        fixups.extend(8u32.to_le_bytes());
+        // dyld_chained_starts_in_image: seg_count, seg_info_offset[0]
+        fixups.extend(1u32.to_le_bytes());
+        fixups.extend(8u32.to_le_bytes());
+        // dyld_chained_starts_in_segment
+        fixups.extend(24u32.to_le_bytes());
+        fixups.extend(0x1000u16.to_le_bytes());
+        fixups.extend(pointer_format.to_le_bytes());
+        fixups.extend(segment_offset.to_le_bytes());
+        fixups.extend(0u32.to_le_bytes());
+        fixups.extend(1u16.to_le_bytes());
+        fixups.extend(page_start.to_le_bytes());
+        fixups
+    }
+
+    #[test]
+    fn chained_fixups() {
+        let mut mem_image = RuntimeMemoryImage::mock();
+        // A rebase to the image offset 0x2002 followed by an import 8 bytes later.
+        let rebase: u64 = (2 << 51) | 0x2002;
+        let bind: u64 = 1 << 63;
+        mem_image.memory_segments[1].bytes = [rebase.to_le_bytes(), bind.to_le_bytes()].concat();
+        mem_image
+            .apply_chained_fixups(&mock_fixups(DYLD_CHAINED_PTR_64_OFFSET, 0x1000, 0), 0x1000)
+            .unwrap();
+        assert_eq!(mem_image.read_raw(0x2000, ByteSize::new(8)), Some(0x3002));
+        assert_eq!(mem_image.read_raw(0x2008, ByteSize::new(8)), Some(0));
+        // Pages without fixups are skipped.
+        mem_image
+            .apply_chained_fixups(
+                &mock_fixups(DYLD_CHAINED_PTR_64, 0x1000, DYLD_CHAINED_PTR_START_NONE),
+                0x1000,
+            )
+            .unwrap();
+        assert_eq!(mem_image.read_raw(0x2000, ByteSize::new(8)), Some(0x3002));
+        // Chains in unsupported pointer formats are rejected.
+        assert!(mem_image
+            .apply_chained_fixups(&mock_fixups(3, 0x1000, 0), 0x1000)
+            .is_err());
+    }
+
+    #[test]
+    fn chained_pointer_formats() {
+        // Rebase to a virtual address with high bits.
+        assert_eq!(
+            decode_chained_pointer((0xAB << 36) | 0x3002, DYLD_CHAINED_PTR_64, 0x1000).unwrap(),
+            (Some(0xAB00_0000_0000_3002), 0, 4)
+        );
+        // Authenticated arm64e rebase.
+        assert_eq!(
+            decode_chained_pointer(
+                (1 << 63) | (2 << 51) | 0x2002,
+                DYLD_CHAINED_PTR_ARM64E,
+                0x1000
+            )
+            .unwrap(),
+            (Some(0x3002), 2, 8)
+        );
+        // arm64e import.
+        assert_eq!(
+            decode_chained_pointer(1 << 62, DYLD_CHAINED_PTR_ARM64E_USERLAND, 0x1000).unwrap(),
+            (None, 0, 8)
+        );
+    }
+}
@@ -283,6 +283,10 @@ pub struct ExternSymbol {
     /// If the function has a variable number of parameters, this flag is set to
     /// `true`.
     pub has_var_args: bool,
+    /// The name of the imported alias if calls to the alias are modeled as
+    /// calls to the function given by `name`, e.g. `__memcpy_chk` for `memcpy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
 }
 
 impl ExternSymbol {
@@ -312,6 +316,12 @@ impl ExternSymbol {
         }
     }
 
+    /// Returns the name under which the binary imports the symbol,
+    /// i.e. the alias if the symbol is imported through an alias.
+    pub fn get_imported_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
     /// Get the calling convention corresponding to the extern symbol.
     pub fn get_calling_convention<'a>(&self, project: &'a Project) -> &'a CallingConvention {
         project.get_calling_convention(self)
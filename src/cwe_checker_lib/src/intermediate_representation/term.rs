@@ -226,6 +226,16 @@ impl Tid {
         self.id.ends_with(suffix)
     }
 
+    /// Remove the provided suffix from the ID string and return the new `Tid`.
+    ///
+    /// Returns `None` if the ID string does not end with the suffix.
+    pub fn without_id_suffix(&self, suffix: &str) -> Option<Self> {
+        Some(Tid {
+            id: self.id.strip_suffix(suffix)?.to_string(),
+            address: self.address,
+        })
+    }
+
     /// Returns the ID of the artificial sink function.
     pub fn artificial_sink_fn() -> Self {
         Self {
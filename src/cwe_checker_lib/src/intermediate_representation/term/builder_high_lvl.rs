@@ -88,6 +88,8 @@ impl RuntimeMemoryImage {
             ],
             is_little_endian: true,
             is_lkm: false,
+            relocated_pointers: BTreeMap::new(),
+            string_lengths: BTreeMap::new(),
         }
     }
 }
@@ -158,6 +160,34 @@ impl Program {
             (other_function.tid.clone(), other_function),
         ])
     }
+
+    /// Returns Program with malloc, free and other_function
+    pub fn mock_riscv64() -> Program {
+        let malloc = ExternSymbol::create_extern_symbol(
+            "malloc",
+            CallingConvention::mock_riscv64(),
+            Some(Datatype::Integer),
+            Some(Datatype::Pointer),
+        );
+        let free = ExternSymbol::create_extern_symbol(
+            "free",
+            CallingConvention::mock_riscv64(),
+            Some(Datatype::Pointer),
+            None,
+        );
+        let other_function = ExternSymbol::create_extern_symbol(
+            "other_function",
+            CallingConvention::mock_riscv64(),
+            None,
+            None,
+        );
+
+        Program::add_extern_symbols_to_program(vec![
+            (malloc.tid.clone(), malloc),
+            (free.tid.clone(), free),
+            (other_function.tid.clone(), other_function),
+        ])
+    }
 }
 
 #[cfg(test)]
@@ -228,4 +258,35 @@ impl Project {
             runtime_memory_image: RuntimeMemoryImage::mock(),
         }
     }
+
+    /// Returns project with RISC-V (rv64) calling convention and mocked program.
+    pub fn mock_riscv64() -> Project {
+        let none_cconv_register: Vec<Variable> =
+            vec!["ra", "t0", "t1", "t2", "t3", "t4", "t5", "t6"]
+                .into_iter()
+                .map(|name| variable!(format!("{name}:8")))
+                .collect();
+
+        let integer_register = CallingConvention::mock_riscv64()
+            .integer_parameter_register
+            .into_iter()
+            .chain(none_cconv_register)
+            .chain(CallingConvention::mock_riscv64().callee_saved_register);
+
+        Project {
+            program: Term {
+                tid: Tid::new("program_tid"),
+                term: Program::mock_riscv64(),
+            },
+            cpu_architecture: "RISCV:LE:64:RV64GC".to_string(),
+            stack_pointer_register: variable!("sp:8"),
+            calling_conventions: BTreeMap::from([(
+                "__stdcall".to_string(),
+                CallingConvention::mock_riscv64(),
+            )]),
+            register_set: integer_register.collect(),
+            datatype_properties: DatatypeProperties::mock_riscv64(),
+            runtime_memory_image: RuntimeMemoryImage::mock(),
+        }
+    }
 }
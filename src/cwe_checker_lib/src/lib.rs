@@ -41,6 +41,9 @@ The cwe_checker offers experimental support for analyzing bare-metal binaries.
 For that, one needs to provide a bare metal configuration file via the `--bare-metal-config` command line option.
 An example for such a configuration file can be found at `bare_metal/stm32f407vg.json`
 (which was created and tested for an STM32F407VG MCU).
+Besides the flash memory and RAM of the chip, the configuration file may contain
+additional memory regions (e.g. memory-mapped peripherals)
+and the addresses of the entry points of the firmware.
 
 For more information on the necessary fields of the configuration file
 and the assumed memory model when analyzing bare metal binaries
@@ -68,6 +71,7 @@ by reading the check-specific module documentation in the [`checkers`] module.
 pub mod abstract_domain;
 pub mod analysis;
 pub mod checkers;
+pub mod error;
 pub mod ghidra_pcode;
 pub mod intermediate_representation;
 pub mod pipeline;
@@ -0,0 +1,382 @@
+//! Aggregation of the results of many binaries of one firmware image or project into a single report.
+//!
+//! The input are the JSON result files of the individual binaries,
+//! e.g. the result files written by a batch run.
+//! The [`ProjectReport`] contains statistics over all binaries
+//! and a matrix with the number of warnings of each check in each binary.
+//!
+//! Binaries of one project often share code, e.g. functions of statically linked libraries.
+//! Bugs in such code are reported once for each binary containing it.
+//! If the result files contain [function hashes](crate::analysis::function_hashing)
+//! or the source code locations of the warnings,
+//! warnings of the same check in the same [`SharedCode`] are grouped across binaries,
+//! so that each shared bug has to be assessed only once.
+
+use crate::error::CweCheckerError;
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The aggregated results of all binaries of a project.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ProjectReport {
+    /// The names of the binaries.
+    pub binaries: Vec<String>,
+    /// The total number of warnings.
+    pub num_warnings: usize,
+    /// The number of warnings if each group of duplicate warnings is counted only once.
+    pub num_unique_warnings: usize,
+    /// Maps the name of each check to the number of its warnings.
+    pub warnings_by_check: BTreeMap<String, usize>,
+    /// Maps the name of each binary to the number of its warnings.
+    pub warnings_by_binary: BTreeMap<String, usize>,
+    /// Maps the name of each check to the number of binaries with warnings of the check.
+    pub binaries_by_check: BTreeMap<String, usize>,
+    /// Maps the name of each check and the name of each binary to the number of warnings of the check in the binary.
+    ///
+    /// Binaries without warnings of a check are omitted.
+    pub matrix: BTreeMap<String, BTreeMap<String, usize>>,
+    /// Warnings of the same check in the same shared code in more than one binary.
+    pub duplicate_groups: Vec<DuplicateGroup>,
+}
+
+/// Warnings of the same check in the same shared code.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DuplicateGroup {
+    /// The name of the check, e.g. `CWE476`.
+    pub check: String,
+    /// The code containing the warnings.
+    pub shared_code: SharedCode,
+    /// The warnings of the group.
+    pub occurrences: Vec<Occurrence>,
+}
+
+/// Identifies code that is contained in more than one binary.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, PartialOrd, Ord)]
+pub enum SharedCode {
+    /// The source code location of the warnings, e.g. `zlib/inflate.c:1250`.
+    ///
+    /// Only known for binaries with debug information.
+    SourceLocation(String),
+    /// The position-independent hash of the functions containing the warnings.
+    ///
+    /// Only known if function hashes were requested for the analysis of the binaries.
+    FunctionHash(String),
+}
+
+impl SharedCode {
+    /// Get the shared code containing the warning, if it can be identified.
+    ///
+    /// Function hashes take precedence over source code locations,
+    /// since they also identify shared code in binaries without debug information.
+    fn of_warning(warning: &CweWarning) -> Option<SharedCode> {
+        if let Some(function_hash) = &warning.function_hash {
+            return Some(SharedCode::FunctionHash(function_hash.clone()));
+        }
+        warning
+            .source_location
+            .as_ref()
+            .map(|location| SharedCode::SourceLocation(location.to_string()))
+    }
+}
+
+impl fmt::Display for SharedCode {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharedCode::SourceLocation(location) => write!(formatter, "at {location}"),
+            SharedCode::FunctionHash(hash) => write!(formatter, "with function hash {hash}"),
+        }
+    }
+}
+
+/// A warning in a [`DuplicateGroup`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, PartialOrd, Ord)]
+pub struct Occurrence {
+    /// The name of the binary.
+    pub binary: String,
+    /// The first address of the warning.
+    pub address: Option<String>,
+    /// The description of the warning.
+    pub description: String,
+}
+
+impl ProjectReport {
+    /// Aggregate the warnings of the given binaries.
+    pub fn new(results: &BTreeMap<String, Vec<CweWarning>>) -> ProjectReport {
+        let mut report = ProjectReport {
+            binaries: results.keys().cloned().collect(),
+            ..ProjectReport::default()
+        };
+        let mut groups: BTreeMap<(&str, SharedCode), Vec<Occurrence>> = BTreeMap::new();
+        for (binary, warnings) in results {
+            report
+                .warnings_by_binary
+                .insert(binary.clone(), warnings.len());
+            for warning in warnings {
+                report.num_warnings += 1;
+                *report
+                    .warnings_by_check
+                    .entry(warning.name.clone())
+                    .or_default() += 1;
+                *report
+                    .matrix
+                    .entry(warning.name.clone())
+                    .or_default()
+                    .entry(binary.clone())
+                    .or_default() += 1;
+                if let Some(shared_code) = SharedCode::of_warning(warning) {
+                    groups
+                        .entry((&warning.name, shared_code))
+                        .or_default()
+                        .push(Occurrence {
+                            binary: binary.clone(),
+                            address: warning.addresses.first().cloned(),
+                            description: warning.description.clone(),
+                        });
+                }
+            }
+        }
+        for (check, binaries) in report.matrix.iter() {
+            report
+                .binaries_by_check
+                .insert(check.clone(), binaries.len());
+        }
+        report.duplicate_groups = groups
+            .into_iter()
+            .filter(|(_, occurrences)| {
+                let binaries: BTreeSet<&String> = occurrences
+                    .iter()
+                    .map(|occurrence| &occurrence.binary)
+                    .collect();
+                binaries.len() > 1
+            })
+            .map(|((check, shared_code), occurrences)| DuplicateGroup {
+                check: check.to_string(),
+                shared_code,
+                occurrences,
+            })
+            .collect();
+        report.num_unique_warnings = report.num_warnings
+            - report
+                .duplicate_groups
+                .iter()
+                .map(|group| group.occurrences.len() - 1)
+                .sum::<usize>();
+        report
+    }
+
+    /// Read the JSON result files and aggregate their warnings.
+    ///
+    /// Directories are searched recursively for files with the extension `.json`,
+    /// except for `firmware_report.json` files of batch runs.
+    /// The name of a binary is the path of its result file without the extension,
+    /// relative to the directory containing the result file if it was found in a directory.
+    pub fn from_result_files(paths: &[PathBuf]) -> Result<ProjectReport, CweCheckerError> {
+        let mut results = BTreeMap::new();
+        for path in paths {
+            if path.is_dir() {
+                let mut files = Vec::new();
+                collect_result_files(path, &mut files).map_err(CweCheckerError::Loader)?;
+                for file in files {
+                    let name = file.strip_prefix(path).unwrap_or(&file).with_extension("");
+                    let warnings = read_result_file(&file).map_err(CweCheckerError::Loader)?;
+                    results.insert(name.display().to_string(), warnings);
+                }
+            } else {
+                let name = path.with_extension("").display().to_string();
+                let warnings = read_result_file(path).map_err(CweCheckerError::Loader)?;
+                results.insert(name, warnings);
+            }
+        }
+        Ok(ProjectReport::new(&results))
+    }
+}
+
+/// Add all JSON result files in the directory and its subdirectories to `files`.
+fn collect_result_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in std::fs::read_dir(dir).context(format!("Could not read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_result_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+            && path
+                .file_name()
+                .is_some_and(|name| name != "firmware_report.json")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Read the CWE warnings from a JSON result file.
+fn read_result_file(path: &Path) -> Result<Vec<CweWarning>, Error> {
+    let file = std::fs::File::open(path).context(format!("Could not open {}", path.display()))?;
+    serde_json::from_reader(std::io::BufReader::new(file)).context(format!(
+        "Parsing of the result file {} failed",
+        path.display()
+    ))
+}
+
+impl fmt::Display for ProjectReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            formatter,
+            "{} warnings ({} unique) in {} binaries",
+            self.num_warnings,
+            self.num_unique_warnings,
+            self.binaries.len()
+        )?;
+        let name_width = self
+            .binaries
+            .iter()
+            .map(String::len)
+            .chain(["Binary".len(), "Total".len()])
+            .max()
+            .unwrap_or_default();
+        write!(formatter, "\n{:<name_width$}", "Binary")?;
+        for check in self.matrix.keys() {
+            write!(formatter, "  {check}")?;
+        }
+        writeln!(formatter, "  Total")?;
+        for binary in self.binaries.iter() {
+            write!(formatter, "{binary:<name_width$}")?;
+            for (check, binaries) in self.matrix.iter() {
+                let count = binaries.get(binary).copied().unwrap_or_default();
+                write!(formatter, "  {count:>width$}", width = check.len())?;
+            }
+            writeln!(formatter, "  {:>5}", self.warnings_by_binary[binary])?;
+        }
+        write!(formatter, "{:<name_width$}", "Total")?;
+        for (check, count) in self.warnings_by_check.iter() {
+            write!(formatter, "  {count:>width$}", width = check.len())?;
+        }
+        writeln!(formatter, "  {:>5}", self.num_warnings)?;
+        for group in self.duplicate_groups.iter() {
+            let binaries: BTreeSet<&String> = group
+                .occurrences
+                .iter()
+                .map(|occurrence| &occurrence.binary)
+                .collect();
+            writeln!(
+                formatter,
+                "\n[{}] {} warnings in {} binaries {}:",
+                group.check,
+                group.occurrences.len(),
+                binaries.len(),
+                group.shared_code
+            )?;
+            for occurrence in group.occurrences.iter() {
+                writeln!(
+                    formatter,
+                    "  {}: {}",
+                    occurrence.binary, occurrence.description
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::debug_info::SourceLocation;
+
+    fn warning(name: &str, address: &str, source_file: Option<&str>) -> CweWarning {
+        let mut warning = CweWarning::new(name, "0.1", format!("{name} at {address}"))
+            .addresses(vec![address.to_string()]);
+        warning.source_location = source_file.map(|file| SourceLocation {
+            file: file.to_string(),
+            line: 42,
+            variables: Vec::new(),
+        });
+        warning
+    }
+
+    #[test]
+    fn aggregate_results() {
+        let results = BTreeMap::from([
+            (
+                "bin/busybox".to_string(),
+                vec![
+                    warning("CWE476", "4096", Some("zlib/inflate.c")),
+                    warning("CWE476", "4112", Some("zlib/inflate.c")),
+                    warning("CWE134", "8192", None),
+                ],
+            ),
+            (
+                "usr/sbin/httpd".to_string(),
+                vec![
+                    warning("CWE476", "12288", Some("zlib/inflate.c")),
+                    warning("CWE134", "16384", Some("httpd.c")),
+                ],
+            ),
+            ("usr/sbin/telnetd".to_string(), Vec::new()),
+        ]);
+        let report = ProjectReport::new(&results);
+        assert_eq!(report.binaries.len(), 3);
+        assert_eq!(report.num_warnings, 5);
+        assert_eq!(report.num_unique_warnings, 3);
+        assert_eq!(report.warnings_by_check["CWE476"], 3);
+        assert_eq!(report.warnings_by_binary["usr/sbin/telnetd"], 0);
+        assert_eq!(report.binaries_by_check["CWE134"], 2);
+        assert_eq!(report.matrix["CWE476"]["bin/busybox"], 2);
+        assert!(!report.matrix["CWE476"].contains_key("usr/sbin/telnetd"));
+        // Warnings at the same source location in only one binary are not grouped.
+        assert_eq!(report.duplicate_groups.len(), 1);
+        assert_eq!(report.duplicate_groups[0].check, "CWE476");
+        assert_eq!(report.duplicate_groups[0].occurrences.len(), 3);
+
+        let text = report.to_string();
+        assert!(text.starts_with("5 warnings (3 unique) in 3 binaries\n"));
+        assert!(text.contains("\nbin/busybox            1       2      3\n"));
+        assert!(text.contains("\nTotal                  2       3      5\n"));
+        assert!(text.contains("\n[CWE476] 3 warnings in 2 binaries at zlib/inflate.c:42:\n"));
+    }
+
+    #[test]
+    fn group_by_function_hash() {
+        let mut hashed_warning = warning("CWE476", "4096", None);
+        hashed_warning.function_hash = Some("00000000000000aa".to_string());
+        // The function hash takes precedence over the source location.
+        let mut hashed_warning_with_location = warning("CWE476", "8192", Some("inflate.c"));
+        hashed_warning_with_location.function_hash = Some("00000000000000aa".to_string());
+        let results = BTreeMap::from([
+            ("busybox".to_string(), vec![hashed_warning]),
+            ("httpd".to_string(), vec![hashed_warning_with_location]),
+        ]);
+        let report = ProjectReport::new(&results);
+        assert_eq!(report.num_unique_warnings, 1);
+        assert_eq!(
+            report.duplicate_groups[0].shared_code,
+            SharedCode::FunctionHash("00000000000000aa".to_string())
+        );
+        assert!(report.to_string().contains(
+            "\n[CWE476] 2 warnings in 2 binaries with function hash 00000000000000aa:\n"
+        ));
+    }
+
+    #[test]
+    fn read_result_files() {
+        let dir = std::env::temp_dir().join(format!("cwe_checker_results_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("usr/sbin")).unwrap();
+        let write = |path: &str, warnings: &[CweWarning]| {
+            std::fs::write(dir.join(path), serde_json::to_string(warnings).unwrap()).unwrap()
+        };
+        write("usr/sbin/httpd.json", &[warning("CWE476", "4096", None)]);
+        write("ping.json", &[]);
+        std::fs::write(dir.join("firmware_report.json"), "{}").unwrap();
+        std::fs::write(dir.join("ping.error"), "Lifting failed").unwrap();
+        let report = ProjectReport::from_result_files(std::slice::from_ref(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let report = report.unwrap();
+        assert_eq!(report.binaries, vec!["ping", "usr/sbin/httpd"]);
+        assert_eq!(report.matrix["CWE476"]["usr/sbin/httpd"], 1);
+    }
+}
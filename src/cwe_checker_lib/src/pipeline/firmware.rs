@@ -0,0 +1,259 @@
+//! Input adapters for analyzing many binaries in one run and the aggregation of their findings.
+//!
+//! Firmware images are usually unpacked by external tools before the contained binaries can be analyzed.
+//! An [`InputAdapter`] provides the extracted binaries together with metadata about them,
+//! e.g. their path and permissions in the original filesystem of the firmware.
+//! Unpackers can either extract into a directory ([`DirectoryInput`])
+//! or additionally write a manifest describing the extracted files ([`ManifestInput`]).
+//!
+//! The [`FirmwareReport`] aggregates the CWE warnings of all binaries,
+//! e.g. to find out which binaries of a firmware call `system` with a tainted argument.
+//! It also contains the [hardening report](HardeningReport) of each binary.
+
+use crate::prelude::*;
+use crate::utils::hardening::HardeningReport;
+use crate::utils::log::CweWarning;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// A binary to be analyzed together with metadata about it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct InputBinary {
+    /// The location of the (extracted) binary on disk.
+    pub path: PathBuf,
+    /// The path of the binary in the original filesystem, e.g. `/usr/sbin/httpd`.
+    pub original_path: String,
+    /// The Unix permission bits of the binary in the original filesystem, if known.
+    #[serde(default)]
+    pub permissions: Option<u32>,
+    /// Further metadata provided by the unpacker, e.g. the filesystem image containing the binary.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl InputBinary {
+    /// Returns `true` if the setuid or setgid bit of the binary is set.
+    pub fn is_setuid(&self) -> bool {
+        self.permissions
+            .is_some_and(|permissions| permissions & 0o6000 != 0)
+    }
+}
+
+/// A source of binaries for a single analysis run.
+pub trait InputAdapter {
+    /// Get the binaries to analyze.
+    fn get_binaries(&self) -> Result<Vec<InputBinary>, Error>;
+}
+
+/// All regular files in a directory and its subdirectories.
+///
+/// The directory is treated as the root of the original filesystem
+/// and the permissions are read from the extracted files.
+/// Symbolic links are not followed.
+pub struct DirectoryInput {
+    /// The root directory.
+    pub root: PathBuf,
+}
+
+impl InputAdapter for DirectoryInput {
+    fn get_binaries(&self) -> Result<Vec<InputBinary>, Error> {
+        let mut binaries = Vec::new();
+        self.collect_files(&self.root, &mut binaries)?;
+        binaries.sort_by(|a, b| a.original_path.cmp(&b.original_path));
+        Ok(binaries)
+    }
+}
+
+impl DirectoryInput {
+    /// Add all regular files in the directory and its subdirectories to `binaries`.
+    fn collect_files(&self, dir: &Path, binaries: &mut Vec<InputBinary>) -> Result<(), Error> {
+        use std::os::unix::fs::PermissionsExt;
+        for entry in std::fs::read_dir(dir).context(format!("Could not read {}", dir.display()))? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                self.collect_files(&entry.path(), binaries)?;
+            } else if file_type.is_file() {
+                let path = entry.path();
+                let relative_path = path.strip_prefix(&self.root).unwrap_or(&path);
+                binaries.push(InputBinary {
+                    original_path: format!("/{}", relative_path.display()),
+                    permissions: Some(entry.metadata()?.permissions().mode() & 0o7777),
+                    metadata: BTreeMap::new(),
+                    path,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The binaries listed in a JSON manifest written by an unpacker.
+///
+/// The manifest is a JSON array of [`InputBinary`] objects.
+/// Relative paths of the extracted binaries are resolved relative to the directory of the manifest.
+pub struct ManifestInput {
+    /// The path to the manifest file.
+    pub manifest: PathBuf,
+}
+
+impl InputAdapter for ManifestInput {
+    fn get_binaries(&self) -> Result<Vec<InputBinary>, Error> {
+        let file = std::fs::File::open(&self.manifest).context(format!(
+            "Could not open manifest {}",
+            self.manifest.display()
+        ))?;
+        let mut binaries: Vec<InputBinary> = serde_json::from_reader(std::io::BufReader::new(file))
+            .context("Parsing of the manifest failed")?;
+        let base_dir = self.manifest.parent().unwrap_or(Path::new(""));
+        for binary in binaries.iter_mut() {
+            binary.path = base_dir.join(&binary.path);
+        }
+        Ok(binaries)
+    }
+}
+
+/// The result of the analysis of one binary in a [`FirmwareReport`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct BinaryResult {
+    /// The analyzed binary.
+    #[serde(flatten)]
+    pub binary: InputBinary,
+    /// The number of CWE warnings of each check.
+    pub warning_counts: BTreeMap<String, usize>,
+    /// The compile-time exploit mitigations of the binary, if its format is supported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardening: Option<HardeningReport>,
+    /// The error message if the analysis of the binary failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The aggregated findings of all binaries analyzed in one run.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct FirmwareReport {
+    /// The results of the individual binaries.
+    pub binaries: Vec<BinaryResult>,
+    /// Maps the name of each check to the original paths of the binaries with warnings of the check.
+    pub binaries_by_check: BTreeMap<String, BTreeSet<String>>,
+    /// Maps each symbol mentioned in warnings, e.g. `system`, and the name of the check
+    /// to the original paths of the binaries with such warnings.
+    pub binaries_by_symbol: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl FirmwareReport {
+    /// Add the CWE warnings and the hardening report of a successfully analyzed binary to the report.
+    pub fn add_warnings(
+        &mut self,
+        binary: InputBinary,
+        warnings: &[CweWarning],
+        hardening: Option<HardeningReport>,
+    ) {
+        let mut warning_counts: BTreeMap<String, usize> = BTreeMap::new();
+        for warning in warnings {
+            *warning_counts.entry(warning.name.clone()).or_default() += 1;
+            self.binaries_by_check
+                .entry(warning.name.clone())
+                .or_default()
+                .insert(binary.original_path.clone());
+            for symbol in warning.symbols.iter() {
+                let symbol = warning.demangled_symbols.get(symbol).unwrap_or(symbol);
+                self.binaries_by_symbol
+                    .entry(symbol.clone())
+                    .or_default()
+                    .entry(warning.name.clone())
+                    .or_default()
+                    .insert(binary.original_path.clone());
+            }
+        }
+        self.binaries.push(BinaryResult {
+            binary,
+            warning_counts,
+            hardening,
+            error: None,
+        });
+    }
+
+    /// Add a binary whose analysis failed to the report.
+    pub fn add_error(&mut self, binary: InputBinary, error: String) {
+        self.binaries.push(BinaryResult {
+            binary,
+            warning_counts: BTreeMap::new(),
+            hardening: None,
+            error: Some(error),
+        });
+    }
+
+    /// Sort the results of the binaries by their original paths.
+    pub fn sort(&mut self) {
+        self.binaries
+            .sort_by(|a, b| a.binary.original_path.cmp(&b.binary.original_path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary(original_path: &str, permissions: Option<u32>) -> InputBinary {
+        InputBinary {
+            path: PathBuf::from(original_path.trim_start_matches('/')),
+            original_path: original_path.to_string(),
+            permissions,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn aggregate_findings() {
+        let system_warning = CweWarning::new("CWE78", "0.1", "(OS Command Injection)")
+            .symbols(vec!["system".to_string()]);
+        let mut report = FirmwareReport::default();
+        report.add_warnings(
+            binary("/usr/sbin/httpd", Some(0o755)),
+            &[
+                system_warning.clone(),
+                system_warning.clone(),
+                CweWarning::new("CWE476", "0.3", "(NULL Pointer Dereference)"),
+            ],
+            None,
+        );
+        report.add_warnings(binary("/bin/ping", Some(0o4755)), &[system_warning], None);
+        report.add_error(binary("/bin/busybox", None), "Lifting failed".to_string());
+        report.sort();
+
+        assert_eq!(report.binaries[0].binary.original_path, "/bin/busybox");
+        assert_eq!(report.binaries[0].error, Some("Lifting failed".to_string()));
+        assert!(report.binaries[1].binary.is_setuid());
+        assert_eq!(report.binaries[2].warning_counts["CWE78"], 2);
+        assert_eq!(
+            report.binaries_by_symbol["system"]["CWE78"],
+            BTreeSet::from(["/bin/ping".to_string(), "/usr/sbin/httpd".to_string()])
+        );
+        assert_eq!(
+            report.binaries_by_check["CWE476"],
+            BTreeSet::from(["/usr/sbin/httpd".to_string()])
+        );
+    }
+
+    #[test]
+    fn manifest_input() {
+        let dir = std::env::temp_dir().join(format!("cwe_checker_manifest_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest = dir.join("manifest.json");
+        std::fs::write(
+            &manifest,
+            r#"[{"path": "extracted/httpd", "original_path": "/usr/sbin/httpd", "permissions": 2541, "metadata": {"image": "rootfs.squashfs"}},
+                {"path": "/tmp/ping", "original_path": "/bin/ping"}]"#,
+        )
+        .unwrap();
+        let binaries = ManifestInput { manifest }.get_binaries().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(binaries[0].path, dir.join("extracted/httpd"));
+        assert!(binaries[0].is_setuid());
+        assert_eq!(binaries[0].metadata["image"], "rootfs.squashfs");
+        assert_eq!(binaries[1].path, PathBuf::from("/tmp/ping"));
+        assert_eq!(binaries[1].permissions, None);
+    }
+}
@@ -1,16 +1,38 @@
 //! This module contains functions and structs helpful for building a complete analysis pipeline
 //! starting from the binary file path.
 
+mod aggregation;
+pub use aggregation::{DuplicateGroup, Occurrence, ProjectReport, SharedCode};
+mod firmware;
+pub use firmware::{
+    BinaryResult, DirectoryInput, FirmwareReport, InputAdapter, InputBinary, ManifestInput,
+};
+mod precision_report;
+pub use precision_report::{PrecisionReport, PrecisionSink, TopEvent};
 mod results;
 pub use results::AnalysisResults;
+mod statistics;
+pub use statistics::RunStatistics;
 
+use crate::analysis::vtables;
+use crate::error::CweCheckerError;
 use crate::intermediate_representation::{Project, RuntimeMemoryImage};
 use crate::prelude::*;
 use crate::utils::debug;
-use crate::utils::log::WithLogs;
-use crate::utils::{binary::BareMetalConfig, ghidra::get_project_from_ghidra};
+use crate::utils::log::{LogMessage, WithLogs};
+use crate::utils::{
+    binary::BareMetalConfig,
+    exception_handling,
+    ghidra::{
+        get_pcode_projects_from_ghidra, get_project_from_ghidra, parse_pcode_project_to_ir_project,
+    },
+    golang,
+};
 use std::path::Path;
 
+/// A binary file (as a byte vector) together with the project lifted from it.
+pub type LiftedBinary = (Vec<u8>, WithLogs<Project>);
+
 /// Disassemble the given binary and parse it to a [`Project`] struct.
 ///
 /// If successful, returns the binary file (as a byte vector), the parsed project struct,
@@ -19,15 +41,64 @@ pub fn disassemble_binary(
     binary_file_path: &Path,
     bare_metal_config_opt: Option<BareMetalConfig>,
     debug_settings: &debug::Settings,
-) -> Result<(Vec<u8>, WithLogs<Project>), Error> {
-    let binary: Vec<u8> =
-        std::fs::read(binary_file_path).context("Could not read from binary file path {}")?;
-    let mut project = get_project_from_ghidra(
+) -> Result<(Vec<u8>, WithLogs<Project>), CweCheckerError> {
+    let binary = read_binary(binary_file_path)?;
+    let project = get_project_from_ghidra(
         binary_file_path,
         &binary[..],
         bare_metal_config_opt.clone(),
         debug_settings,
     )?;
+    let project = process_lifted_project(
+        project,
+        &binary,
+        bare_metal_config_opt.as_ref(),
+        debug_settings,
+    )?;
+
+    Ok((binary, project))
+}
+
+/// Disassemble the given binaries like [`disassemble_binary`], but all in a single Ghidra instance.
+///
+/// The file names of the binaries must be distinct.
+/// Returns the results in the order of the file paths.
+/// If a binary cannot be disassembled, only the result of this binary is an error.
+pub fn disassemble_binaries(
+    binary_file_paths: &[&Path],
+    debug_settings: &debug::Settings,
+) -> Result<Vec<Result<LiftedBinary, CweCheckerError>>, CweCheckerError> {
+    let pcode_projects = get_pcode_projects_from_ghidra(binary_file_paths, debug_settings)?;
+
+    Ok(binary_file_paths
+        .iter()
+        .zip(pcode_projects)
+        .map(|(binary_file_path, pcode_project)| {
+            let binary = read_binary(binary_file_path)?;
+            let pcode_project = pcode_project?;
+            debug_settings.print(
+                &pcode_project,
+                debug::Stage::Pcode(debug::PcodeForm::Parsed),
+            );
+            let project =
+                parse_pcode_project_to_ir_project(pcode_project, &binary, &None, debug_settings)?;
+            let project = process_lifted_project(project, &binary, None, debug_settings)?;
+
+            Ok((binary, project))
+        })
+        .collect())
+}
+
+/// Optimize the project lifted from the binary by Ghidra
+/// and add the information that Ghidra does not provide to it.
+fn process_lifted_project(
+    mut project: WithLogs<Project>,
+    binary: &[u8],
+    bare_metal_config_opt: Option<&BareMetalConfig>,
+    debug_settings: &debug::Settings,
+) -> Result<WithLogs<Project>, CweCheckerError> {
+    // Reject malformed input from the frontend before any analysis runs on it.
+    validate_project(&project, "lifting")?;
 
     // Normalize the project and gather log messages generated from it.
     debug_settings.print(&project.program.term, debug::Stage::Ir(debug::IrForm::Raw));
@@ -40,11 +111,10 @@ pub fn disassemble_binary(
     );
 
     // Generate the representation of the runtime memory image of the binary
-    let mut runtime_memory_image = if let Some(bare_metal_config) = bare_metal_config_opt.as_ref() {
-        RuntimeMemoryImage::new_from_bare_metal(&binary, bare_metal_config)
-            .context("Error while generating runtime memory image.")?
+    let mut runtime_memory_image = if let Some(bare_metal_config) = bare_metal_config_opt {
+        RuntimeMemoryImage::new_from_bare_metal(binary, bare_metal_config)?
     } else {
-        RuntimeMemoryImage::new(&binary).context("Error while generating runtime memory image.")?
+        RuntimeMemoryImage::new(binary)?
     };
     if project.program.term.address_base_offset != 0 {
         // We adjust the memory addresses once globally
@@ -53,5 +123,106 @@ pub fn disassemble_binary(
     }
     project.runtime_memory_image = runtime_memory_image;
 
+    // Go binaries contain a function table and non-NUL-terminated string literals.
+    if let Ok(go_functions) = golang::parse_pclntab(binary) {
+        let num_renamed = golang::add_go_function_names(&mut project.program, &go_functions);
+        project.runtime_memory_image.string_lengths = golang::recover_go_string_literals(&project);
+        project.add_log_msg(LogMessage::new_info(format!(
+            "Go binary detected. Recovered {} function names and {} string literals.",
+            num_renamed,
+            project.runtime_memory_image.string_lengths.len()
+        )));
+    }
+
+    // Add the exception handlers of C++ binaries to the blocks of calls that may throw exceptions.
+    if let Ok(call_sites) = exception_handling::parse_call_sites(binary) {
+        let num_handlers =
+            exception_handling::add_exception_handlers(&mut project.program, &call_sites);
+        if num_handlers > 0 {
+            project.add_log_msg(LogMessage::new_info(format!(
+                "Added exception handlers to {num_handlers} calls."
+            )));
+        }
+    }
+
+    // Resolve virtual calls of C++ binaries.
+    let vtable_logs = vtables::resolve_virtual_calls(&mut project);
+    project.add_logs(vtable_logs);
+
+    validate_project(&project, "the IR transformations")?;
+
+    Ok(project)
+}
+
+/// Disassemble the given binary like [`disassemble_binary`],
+/// but reuse the project saved in the given IR cache file if possible.
+///
+/// If the cache file does not exist or was generated from a different binary or bare metal configuration
+/// or by a different version of the cwe_checker,
+/// then the binary is disassembled and the resulting project is saved to the cache file.
+pub fn disassemble_binary_with_ir_cache(
+    binary_file_path: &Path,
+    bare_metal_config_opt: Option<BareMetalConfig>,
+    debug_settings: &debug::Settings,
+    ir_cache_path: &Path,
+) -> Result<(Vec<u8>, WithLogs<Project>), CweCheckerError> {
+    let mut logs = Vec::new();
+    if ir_cache_path.exists() {
+        let binary = read_binary(binary_file_path)?;
+        match Project::load(ir_cache_path, &binary, bare_metal_config_opt.as_ref()) {
+            Ok(project) => {
+                validate_project(&project, "loading the IR cache")?;
+                let msg = LogMessage::new_info(format!(
+                    "Loaded the IR from the cache file {}.",
+                    ir_cache_path.display()
+                ));
+                return Ok((binary, WithLogs::new(project, vec![msg])));
+            }
+            Err(err) => logs.push(LogMessage::new_info(format!(
+                "Could not use the IR cache file: {:#}",
+                Error::from(err)
+            ))),
+        }
+    }
+    let (binary, mut project) = disassemble_binary(
+        binary_file_path,
+        bare_metal_config_opt.clone(),
+        debug_settings,
+    )?;
+    project
+        .save(ir_cache_path, &binary, bare_metal_config_opt.as_ref())
+        .map_err(CweCheckerError::Loader)?;
+    logs.push(LogMessage::new_info(format!(
+        "Saved the IR to the cache file {}.",
+        ir_cache_path.display()
+    )));
+    project.add_logs(logs);
+
     Ok((binary, project))
 }
+
+/// Check the invariants of the IR of the project.
+///
+/// Returns an error listing all violations if the IR is malformed.
+fn validate_project(project: &Project, stage: &str) -> Result<(), CweCheckerError> {
+    let diagnostics = project.validate();
+    if diagnostics.is_empty() {
+        return Ok(());
+    }
+    Err(CweCheckerError::MalformedIr {
+        stage: stage.to_string(),
+        diagnostics,
+    })
+}
+
+/// Read the binary file at the given path.
+fn read_binary(binary_file_path: &Path) -> Result<Vec<u8>, CweCheckerError> {
+    std::fs::read(binary_file_path)
+        .with_context(|| {
+            format!(
+                "Could not read from binary file path {}",
+                binary_file_path.display()
+            )
+        })
+        .map_err(CweCheckerError::Loader)
+}
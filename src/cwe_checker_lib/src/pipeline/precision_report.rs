@@ -0,0 +1,189 @@
+//! Reports of the program points at which the Pointer Inference loses all information about register values.
+//!
+//! If the Pointer Inference records precision losses
+//! (see the `record_precision_losses` option of its [configuration](crate::analysis::pointer_inference::Config)),
+//! the report lists for each register and program point the first precision loss that made the register value `Top`
+//! together with its [cause](PrecisionLossCause).
+//! The precision losses are grouped by their origin, i.e. by the merge location or by the called function,
+//! and the groups are ranked by the number of register values that became `Top` through them.
+//!
+//! The top-ranked groups are the biggest precision sinks of the analysis.
+//! E.g. adding a summary for an extern function that makes many values `Top` (see the `--library` option)
+//! or adding it to the configuration of the Pointer Inference can improve the precision of the analysis considerably.
+
+use crate::analysis::fixpoint::PrecisionLossCause;
+use crate::analysis::pointer_inference::PointerInference;
+use crate::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+/// The program points at which register values became `Top`, grouped and ranked by their origin.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct PrecisionReport {
+    /// The origins of values becoming `Top`, ranked by the number of values that became `Top` through them.
+    pub sinks: Vec<PrecisionSink>,
+    /// The program points at which register values became `Top`.
+    pub top_events: Vec<TopEvent>,
+}
+
+/// A register value that became `Top` at a program point.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct TopEvent {
+    /// The TID of the block at whose start the value became `Top`.
+    pub tid: String,
+    /// The address of the block.
+    pub address: String,
+    /// The name of the function containing the block.
+    pub function: String,
+    /// The name of the register.
+    pub register: String,
+    /// The cause of the value becoming `Top`.
+    pub cause: PrecisionLossCause,
+    /// A description of the lost precision.
+    pub description: String,
+}
+
+/// An origin of register values becoming `Top`.
+///
+/// Merges are grouped by their location, calls are grouped by the called function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct PrecisionSink {
+    /// The cause of the values becoming `Top`.
+    pub cause: PrecisionLossCause,
+    /// The TID of the block at which the values were merged.
+    /// `None` for calls to unknown functions.
+    pub tid: Option<String>,
+    /// The number of register values that became `Top`.
+    pub num_values: usize,
+    /// The names of the registers that became `Top`.
+    pub registers: BTreeSet<String>,
+    /// The names of the functions in which the values became `Top`.
+    pub functions: BTreeSet<String>,
+}
+
+impl PrecisionReport {
+    /// Generate the report from the precision losses recorded by the Pointer Inference.
+    ///
+    /// Returns `None` if the Pointer Inference did not record precision losses.
+    pub fn new(pointer_inference: &PointerInference) -> Option<PrecisionReport> {
+        let graph = pointer_inference.get_graph();
+        let top_events = pointer_inference
+            .get_precision_losses()?
+            .iter()
+            .flat_map(|(node, losses)| {
+                let block = graph[*node].get_block();
+                let function = &graph[*node].get_sub().term.name;
+                losses
+                    .iter()
+                    .filter(|loss| loss.top)
+                    .map(move |loss| TopEvent {
+                        tid: block.tid.to_string(),
+                        address: block.tid.address().to_string(),
+                        function: function.clone(),
+                        register: loss.location.clone(),
+                        cause: loss.cause.clone(),
+                        description: loss.description.clone(),
+                    })
+            })
+            .collect();
+        Some(PrecisionReport::from_top_events(top_events))
+    }
+
+    /// Group and rank the given events by their origin.
+    pub fn from_top_events(mut top_events: Vec<TopEvent>) -> PrecisionReport {
+        top_events.sort();
+        top_events.dedup();
+        let mut sinks: BTreeMap<(PrecisionLossCause, Option<String>), PrecisionSink> =
+            BTreeMap::new();
+        for event in top_events.iter() {
+            let tid = match event.cause {
+                PrecisionLossCause::UnknownCallee(_) => None,
+                PrecisionLossCause::Merge | PrecisionLossCause::Widening => Some(event.tid.clone()),
+            };
+            let sink = sinks
+                .entry((event.cause.clone(), tid.clone()))
+                .or_insert_with(|| PrecisionSink {
+                    cause: event.cause.clone(),
+                    tid,
+                    num_values: 0,
+                    registers: BTreeSet::new(),
+                    functions: BTreeSet::new(),
+                });
+            sink.num_values += 1;
+            sink.registers.insert(event.register.clone());
+            sink.functions.insert(event.function.clone());
+        }
+        let mut sinks: Vec<PrecisionSink> = sinks.into_values().collect();
+        // The sort is stable, so sinks with the same number of values keep their order.
+        sinks.sort_by_key(|sink| std::cmp::Reverse(sink.num_values));
+        PrecisionReport { sinks, top_events }
+    }
+
+    /// Write the report as JSON to the given file.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let file =
+            std::fs::File::create(path).context("Could not create the precision report file")?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for PrecisionSink {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{} values became Top by {}",
+            self.num_values, self.cause
+        )?;
+        if let Some(tid) = &self.tid {
+            write!(formatter, " at {tid}")?;
+        }
+        let registers: Vec<&str> = self.registers.iter().map(String::as_str).collect();
+        let functions: Vec<&str> = self.functions.iter().map(String::as_str).collect();
+        write!(
+            formatter,
+            " (registers: {}; functions: {})",
+            registers.join(", "),
+            functions.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn top_event(tid: &str, register: &str, cause: PrecisionLossCause) -> TopEvent {
+        TopEvent {
+            tid: tid.to_string(),
+            address: "4096".to_string(),
+            function: "main".to_string(),
+            register: register.to_string(),
+            cause,
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn rank_precision_sinks() {
+        let callee = PrecisionLossCause::UnknownCallee(Some("getline".to_string()));
+        let report = PrecisionReport::from_top_events(vec![
+            top_event("blk_1", "RAX", PrecisionLossCause::Widening),
+            top_event("blk_2", "RAX", callee.clone()),
+            top_event("blk_3", "RAX", callee.clone()),
+            top_event("blk_3", "RCX", callee.clone()),
+            top_event("blk_3", "RCX", callee.clone()),
+        ]);
+        assert_eq!(report.top_events.len(), 4);
+        assert_eq!(report.sinks.len(), 2);
+        assert_eq!(report.sinks[0].cause, callee);
+        assert_eq!(report.sinks[0].tid, None);
+        assert_eq!(report.sinks[0].num_values, 3);
+        assert_eq!(report.sinks[1].tid, Some("blk_1".to_string()));
+        assert_eq!(
+            report.sinks[0].to_string(),
+            "3 values became Top by call to unknown function getline (registers: RAX, RCX; functions: main)"
+        );
+    }
+}
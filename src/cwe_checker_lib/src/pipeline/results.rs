@@ -1,8 +1,9 @@
 use crate::abstract_domain::BricksDomain;
 use crate::analysis::function_signature::FunctionSignature;
 use crate::analysis::graph::Graph;
-use crate::analysis::pointer_inference::PointerInference;
-use crate::analysis::string_abstraction::StringAbstraction;
+use crate::analysis::library_summaries::LibrarySummaries;
+use crate::analysis::pointer_inference::{self, PointerInference};
+use crate::analysis::string_abstraction::{self, StringAbstraction};
 use crate::intermediate_representation::Project;
 use crate::prelude::*;
 use crate::utils::log::WithLogs;
@@ -24,6 +25,8 @@ pub struct AnalysisResults<'a> {
     pub pointer_inference: Option<&'a PointerInference<'a>>,
     /// The result of the string abstraction if already computed.
     pub string_abstraction: Option<&'a StringAbstraction<'a, BricksDomain>>,
+    /// The summaries of the functions of the shared libraries that the binary imports, if these were analyzed.
+    pub library_summaries: Option<&'a LibrarySummaries>,
 }
 
 impl<'a> AnalysisResults<'a> {
@@ -40,11 +43,13 @@ impl<'a> AnalysisResults<'a> {
             function_signatures: None,
             pointer_inference: None,
             string_abstraction: None,
+            library_summaries: None,
         }
     }
 
     /// Compute the function signatures for internal functions.
     pub fn compute_function_signatures(&self) -> WithLogs<BTreeMap<Tid, FunctionSignature>> {
+        let _span = tracing::info_span!("analysis", name = "Function Signatures").entered();
         crate::analysis::function_signature::compute_function_signatures(
             self.project,
             self.control_flow_graph,
@@ -64,19 +69,44 @@ impl<'a> AnalysisResults<'a> {
 
     /// Compute the pointer inference analysis.
     /// The result gets returned, but not saved to the `AnalysisResults` struct itself.
+    ///
+    /// The configuration is extended by the library summaries,
+    /// see [`get_pointer_inference_config`](Self::get_pointer_inference_config).
     pub fn compute_pointer_inference(
         &'a self,
         config: &serde_json::Value,
         print_stats: bool,
     ) -> PointerInference<'a> {
-        crate::analysis::pointer_inference::run(
+        let _span = tracing::info_span!("analysis", name = "Pointer Inference").entered();
+        pointer_inference::run(
             self,
-            serde_json::from_value(config.clone()).unwrap(),
+            self.get_pointer_inference_config(config),
             false,
             print_stats,
         )
     }
 
+    /// Get the configuration of the pointer inference analysis from the given JSON value.
+    ///
+    /// Library functions wrapping allocation functions according to the library summaries
+    /// are added to the allocation wrappers unless the configuration already contains them.
+    /// Library functions returning string literals are handled like input string symbols.
+    pub fn get_pointer_inference_config(
+        &self,
+        config: &serde_json::Value,
+    ) -> pointer_inference::Config {
+        let mut config: pointer_inference::Config = serde_json::from_value(config.clone()).unwrap();
+        if let Some(library_summaries) = self.library_summaries {
+            for (name, index) in library_summaries.get_allocation_wrappers() {
+                config.allocation_wrappers.entry(name).or_insert(index);
+            }
+            config
+                .input_string_symbols
+                .extend(library_summaries.get_returned_strings().into_keys());
+        }
+        config
+    }
+
     /// Create a new `AnalysisResults` struct containing the given pointer inference analysis results.
     pub fn with_pointer_inference<'b: 'a>(
         self,
@@ -92,16 +122,30 @@ impl<'a> AnalysisResults<'a> {
     /// As the string abstraction depends on the pointer inference, the
     /// pointer inference is also computed and put into the `AnalysisResults` struct.
     /// The result gets returned, but not saved to the `AnalysisResults` struct itself.
+    ///
+    /// The string literals returned by library functions according to the library summaries
+    /// are added to the configuration.
+    /// Returns an error if the configuration of the analysis is invalid.
     pub fn compute_string_abstraction(
         &'a self,
         config: &serde_json::Value,
         pi_results: Option<&'a PointerInference<'a>>,
-    ) -> StringAbstraction<BricksDomain> {
-        crate::analysis::string_abstraction::run(
+    ) -> Result<StringAbstraction<'a, BricksDomain>, Error> {
+        let _span = tracing::info_span!("analysis", name = "String Abstraction").entered();
+        let mut config: string_abstraction::Config = serde_json::from_value(config.clone())?;
+        if let Some(library_summaries) = self.library_summaries {
+            for (name, strings) in library_summaries.get_returned_strings() {
+                config
+                    .returned_string_symbols
+                    .entry(name)
+                    .or_insert(strings);
+            }
+        }
+        string_abstraction::run(
             self.project,
             self.control_flow_graph,
             pi_results.unwrap(),
-            serde_json::from_value(config.clone()).unwrap(),
+            config,
         )
     }
 
@@ -115,14 +159,25 @@ impl<'a> AnalysisResults<'a> {
             ..self
         }
     }
+
+    /// Create a new `AnalysisResults` struct containing the given summaries of shared library functions.
+    pub fn with_library_summaries(
+        self,
+        library_summaries: Option<&'a LibrarySummaries>,
+    ) -> AnalysisResults<'a> {
+        AnalysisResults {
+            library_summaries,
+            ..self
+        }
+    }
 }
 
-// TODO: Fix tests.
-/*
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::analysis::graph::get_program_cfg;
+    use crate::analysis::library_summaries::FunctionSummary;
+    use std::collections::BTreeSet;
 
     impl<'a> AnalysisResults<'a> {
         /// Mocks the `AnalysisResults` struct with a given project.
@@ -132,11 +187,59 @@ mod tests {
             let graph: &'a Graph = Box::leak(graph);
             let binary: &'a Vec<u8> = Box::leak(Box::new(Vec::new()));
             let analysis_results = AnalysisResults::new(binary, graph, project);
-            let (fn_sigs, _) = analysis_results.compute_function_signatures();
+            let fn_sigs = analysis_results.compute_function_signatures().into_object();
             let fn_sigs: &'a BTreeMap<_, _> = Box::leak(Box::new(fn_sigs));
-            let analysis_results = analysis_results.with_function_signatures(Some(fn_sigs));
-            analysis_results
+            analysis_results.with_function_signatures(Some(fn_sigs))
         }
     }
+
+    #[test]
+    fn pointer_inference_config_with_library_summaries() {
+        let project = Project::mock_x64();
+        let analysis_results = AnalysisResults::mock_from_project(&project);
+        let mut library_summaries = LibrarySummaries::default();
+        library_summaries.functions.insert(
+            "xmalloc".to_string(),
+            FunctionSummary {
+                allocation_size_parameter: Some(0),
+                ..Default::default()
+            },
+        );
+        library_summaries.functions.insert(
+            "my_calloc".to_string(),
+            FunctionSummary {
+                allocation_size_parameter: Some(1),
+                ..Default::default()
+            },
+        );
+        library_summaries.functions.insert(
+            "version".to_string(),
+            FunctionSummary {
+                returned_strings: BTreeSet::from(["1.0".to_string()]),
+                ..Default::default()
+            },
+        );
+        let config_json = serde_json::json!({
+            "allocation_symbols": ["malloc"],
+            "reallocation_symbols": [],
+            "allocation_wrappers": {"my_calloc": 0},
+            "input_string_symbols": []
+        });
+
+        let config = analysis_results.get_pointer_inference_config(&config_json);
+        assert_eq!(
+            config.allocation_wrappers,
+            BTreeMap::from([("my_calloc".to_string(), 0)])
+        );
+        assert!(config.input_string_symbols.is_empty());
+
+        let analysis_results = analysis_results.with_library_summaries(Some(&library_summaries));
+        let config = analysis_results.get_pointer_inference_config(&config_json);
+        // Configured wrappers take precedence over the library summaries.
+        assert_eq!(
+            config.allocation_wrappers,
+            BTreeMap::from([("my_calloc".to_string(), 0), ("xmalloc".to_string(), 0)])
+        );
+        assert_eq!(config.input_string_symbols, vec!["version".to_string()]);
+    }
 }
-*/
@@ -0,0 +1,150 @@
+//! Machine-readable metadata and statistics about a run of the cwe_checker.
+//!
+//! The statistics are meant for tracking the performance of the analyses across versions,
+//! e.g. by comparing the statistics files of runs on the same set of binaries.
+
+use crate::analysis::graph::get_entry_nodes_of_subs;
+use crate::analysis::pointer_inference::PointerInference;
+use crate::intermediate_representation::Project;
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::time::Instant;
+
+/// Metadata and statistics about a run of the cwe_checker.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct RunStatistics {
+    /// The version of the cwe_checker.
+    pub cwe_checker_version: String,
+    /// The path to the analyzed binary.
+    pub binary: String,
+    /// The runtime in seconds of each analysis stage and each checker.
+    pub runtimes: BTreeMap<String, f64>,
+    /// The number of node updates of the fixpoint computation of each fixpoint analysis.
+    pub fixpoint_steps: BTreeMap<String, u64>,
+    /// The peak resident memory of the process in bytes, if known.
+    pub memory_high_water_mark: Option<u64>,
+    /// The number of functions in the program.
+    pub functions: usize,
+    /// The number of functions reached by the pointer inference analysis.
+    pub functions_analyzed: Option<usize>,
+    /// The number of functions not reached by the pointer inference analysis.
+    pub functions_skipped: Option<usize>,
+    /// The number of functions containing nodes on which the pointer inference did not stabilize.
+    pub functions_not_stabilized: Option<usize>,
+    /// The number of CWE warnings generated by each checker.
+    pub warning_counts: BTreeMap<String, usize>,
+}
+
+impl RunStatistics {
+    /// Create an empty statistics object for a run on the given binary.
+    pub fn new(binary: &Path) -> RunStatistics {
+        RunStatistics {
+            cwe_checker_version: env!("CARGO_PKG_VERSION").to_string(),
+            binary: binary.display().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Run the given stage of the analysis and record its runtime.
+    ///
+    /// The runtimes of stages with the same name are added up.
+    pub fn time<T>(&mut self, stage: &str, stage_fn: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = stage_fn();
+        *self.runtimes.entry(stage.to_string()).or_default() += start.elapsed().as_secs_f64();
+        result
+    }
+
+    /// Record the number of fixpoint steps of an analysis.
+    pub fn add_fixpoint_steps(&mut self, analysis: &str, steps: u64) {
+        self.fixpoint_steps.insert(analysis.to_string(), steps);
+    }
+
+    /// Record the number of CWE warnings generated by a checker.
+    pub fn add_warning_count(&mut self, checker: &str, count: usize) {
+        *self.warning_counts.entry(checker.to_string()).or_default() += count;
+    }
+
+    /// Record the number of functions of the program.
+    pub fn add_project_statistics(&mut self, project: &Project) {
+        self.functions = project.program.term.subs.len();
+    }
+
+    /// Record the fixpoint steps and the function coverage of the pointer inference analysis.
+    pub fn add_pointer_inference_statistics(&mut self, pointer_inference: &PointerInference) {
+        self.add_fixpoint_steps("Pointer Inference", pointer_inference.get_fixpoint_steps());
+        let graph = pointer_inference.get_graph();
+        let analyzed = get_entry_nodes_of_subs(graph)
+            .into_values()
+            .filter(|node| pointer_inference.get_node_value(*node).is_some())
+            .count();
+        let not_stabilized: HashSet<&Tid> = pointer_inference
+            .get_non_stabilized_nodes()
+            .into_iter()
+            .map(|node| &graph[node].get_sub().tid)
+            .collect();
+        self.functions_analyzed = Some(analyzed);
+        self.functions_skipped = Some(self.functions.saturating_sub(analyzed));
+        self.functions_not_stabilized = Some(not_stabilized.len());
+    }
+
+    /// Record the peak memory usage of the process up to this point.
+    ///
+    /// The peak memory usage is only known on Linux.
+    pub fn add_memory_statistics(&mut self) {
+        self.memory_high_water_mark = std::fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|status| parse_memory_high_water_mark(&status));
+    }
+
+    /// Write the statistics as JSON to the given file.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let file = std::fs::File::create(path).context("Could not create the statistics file")?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+/// Parse the peak resident memory in bytes from the contents of `/proc/self/status`.
+fn parse_memory_high_water_mark(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes: u64 = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_high_water_mark() {
+        let status =
+            "Name:\tcwe_checker\nVmPeak:\t  20000 kB\nVmHWM:\t   1234 kB\nVmRSS:\t   1000 kB\n";
+        assert_eq!(parse_memory_high_water_mark(status), Some(1234 * 1024));
+        assert_eq!(parse_memory_high_water_mark("Name:\tcwe_checker\n"), None);
+    }
+
+    #[test]
+    fn record_statistics() {
+        let mut stats = RunStatistics::new(Path::new("binary"));
+        assert_eq!(stats.time("stage", || 42), 42);
+        stats.time("stage", || ());
+        stats.add_warning_count("CWE476", 2);
+        stats.add_warning_count("CWE476", 1);
+        stats.add_project_statistics(&Project::mock_x64());
+
+        assert_eq!(stats.runtimes.keys().collect::<Vec<_>>(), vec!["stage"]);
+        assert_eq!(stats.warning_counts["CWE476"], 3);
+        assert_eq!(stats.functions, 0);
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["binary"], "binary");
+        assert_eq!(json["warning_counts"]["CWE476"], 3);
+    }
+}
@@ -0,0 +1,333 @@
+//! Suppression of CWE warnings in functions that developers verified to be safe.
+//!
+//! Functions can be marked as safe in two ways:
+//! - By a suffix of the function name: Functions whose names end with `_cwe_safe_`
+//!   are safe with respect to all checks, e.g. `copy_string_cwe_safe_`.
+//!   The suffix may be followed by CWE numbers separated by underscores to restrict it to these CWEs,
+//!   e.g. `copy_string_cwe_safe_134_416` is only safe with respect to CWE-134 and CWE-416.
+//!   For C++ and Rust functions the suffix is matched on the demangled name without parameters.
+//! - By an annotation file: A JSON object that maps the entry addresses of functions
+//!   to the CWEs that the functions were verified to be safe for, e.g.
+//!   `{"0x401000": ["CWE-134"], "0x401200": ["CWE416", "CWE476"]}`.
+//!   The addresses are the addresses reported by the cwe_checker,
+//!   i.e. they contain the address base offset of the program
+//!   (see [`Program::address_base_offset`](crate::intermediate_representation::Program::address_base_offset)).
+//!
+//! A CWE warning is suppressed if the warning and its flow trace pass exclusively through functions
+//! that are safe with respect to the check that generated the warning,
+//! i.e. if all of its addresses, the addresses of its trace (`trace=<address>` entries of its `other` field)
+//! and the terms of its TIDs are contained in such functions.
+//! Warnings without addresses are never suppressed.
+
+use crate::error::CweCheckerError;
+use crate::intermediate_representation::{Project, Tid};
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage, WithLogs};
+use crate::utils::symbol_utils::demangle_symbol;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+/// The suffix of function names that marks functions as safe.
+pub const SAFE_FUNCTION_MARKER: &str = "_cwe_safe_";
+
+/// The CWEs for which a function was verified to be safe.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum SafeFor {
+    /// The function is safe with respect to all checks.
+    AllCwes,
+    /// The function is safe with respect to the checks with the given names, e.g. `CWE134`.
+    Cwes(BTreeSet<String>),
+}
+
+impl SafeFor {
+    /// Returns `true` if warnings of the check with the given name are suppressed.
+    fn contains(&self, check_name: &str) -> bool {
+        match self {
+            SafeFor::AllCwes => true,
+            SafeFor::Cwes(cwes) => cwes.contains(check_name),
+        }
+    }
+
+    /// Add the CWEs of `other`.
+    fn extend(&mut self, other: SafeFor) {
+        match (self, other) {
+            (SafeFor::AllCwes, _) => (),
+            (this, SafeFor::AllCwes) => *this = SafeFor::AllCwes,
+            (SafeFor::Cwes(cwes), SafeFor::Cwes(other_cwes)) => cwes.extend(other_cwes),
+        }
+    }
+}
+
+/// The functions of a program that were verified to be safe.
+#[derive(Debug, Clone)]
+pub struct Allowlist {
+    /// Maps the addresses of all instructions of the program to the functions containing them.
+    functions_at_addresses: HashMap<u64, BTreeSet<Tid>>,
+    /// Maps the IDs of all blocks, `Def` and `Jmp` terms of the program to the functions containing them.
+    functions_of_terms: HashMap<String, BTreeSet<Tid>>,
+    /// Maps the entry addresses of the functions of the program to their TIDs.
+    function_entries: HashMap<u64, Tid>,
+    /// The functions that are safe with respect to some checks.
+    safe_functions: HashMap<Tid, SafeFor>,
+}
+
+impl Allowlist {
+    /// Create an allowlist containing the functions of the project whose names are marked as safe.
+    pub fn new(project: &Project) -> Allowlist {
+        let mut allowlist = Allowlist {
+            functions_at_addresses: project.program.term.get_functions_at_addresses(),
+            functions_of_terms: HashMap::new(),
+            function_entries: HashMap::new(),
+            safe_functions: HashMap::new(),
+        };
+        for sub in project.program.term.subs.values() {
+            for block in sub.term.blocks.iter() {
+                let term_tids = std::iter::once(&block.tid)
+                    .chain(block.term.defs.iter().map(|def| &def.tid))
+                    .chain(block.term.jmps.iter().map(|jmp| &jmp.tid));
+                for tid in term_tids {
+                    allowlist
+                        .functions_of_terms
+                        .entry(tid.to_string())
+                        .or_default()
+                        .insert(sub.tid.clone());
+                }
+            }
+            if let Ok(address) = u64::try_from(sub.tid.address()) {
+                allowlist.function_entries.insert(address, sub.tid.clone());
+            }
+            if let Some(safe_for) = parse_safe_function_name(&sub.term.name) {
+                allowlist.add_safe_function(&sub.tid, safe_for);
+            }
+        }
+        allowlist
+    }
+
+    /// Mark the functions in the given annotation file as safe.
+    ///
+    /// Annotations for addresses that are not entry addresses of functions are ignored
+    /// and reported as log messages.
+    pub fn add_annotation_file(&mut self, path: &Path) -> Result<Vec<LogMessage>, CweCheckerError> {
+        let file = std::io::BufReader::new(
+            std::fs::File::open(path)
+                .context("Could not open the annotation file")
+                .map_err(CweCheckerError::Config)?,
+        );
+        let annotations: BTreeMap<String, Vec<String>> = serde_json::from_reader(file)
+            .context("Parsing of the annotation file failed")
+            .map_err(CweCheckerError::Config)?;
+        self.add_annotations(&annotations)
+    }
+
+    /// Mark the functions with the given entry addresses as safe with respect to the given CWEs.
+    ///
+    /// The addresses are hexadecimal numbers, the CWEs are formatted like `CWE-134` or `CWE134`.
+    pub fn add_annotations(
+        &mut self,
+        annotations: &BTreeMap<String, Vec<String>>,
+    ) -> Result<Vec<LogMessage>, CweCheckerError> {
+        let mut logs = Vec::new();
+        for (address_string, cwes) in annotations {
+            let address = u64::from_str_radix(address_string.trim_start_matches("0x"), 16)
+                .context(format!("Invalid function address {address_string}"))
+                .map_err(CweCheckerError::Config)?;
+            let Some(function) = self.function_entries.get(&address).cloned() else {
+                logs.push(LogMessage::new_info(format!(
+                    "No function starts at the annotated address {address_string}"
+                )));
+                continue;
+            };
+            let cwes = cwes
+                .iter()
+                .map(|cwe| {
+                    format!(
+                        "CWE{}",
+                        cwe.trim_start_matches("CWE").trim_start_matches('-')
+                    )
+                })
+                .collect();
+            self.add_safe_function(&function, SafeFor::Cwes(cwes));
+        }
+        Ok(logs)
+    }
+
+    /// Mark the function as safe with respect to the given CWEs in addition to its other CWEs.
+    fn add_safe_function(&mut self, function: &Tid, safe_for: SafeFor) {
+        match self.safe_functions.get_mut(function) {
+            Some(existing) => existing.extend(safe_for),
+            None => {
+                self.safe_functions.insert(function.clone(), safe_for);
+            }
+        }
+    }
+
+    /// Returns `true` if all addresses of the warning and of its flow trace
+    /// and all terms of its TIDs are contained in functions
+    /// that are safe with respect to the check that generated the warning.
+    ///
+    /// TIDs that do not belong to a block, `Def` or `Jmp` term of the program are ignored.
+    pub fn suppresses(&self, warning: &CweWarning) -> bool {
+        let are_safe = |functions: &BTreeSet<Tid>| {
+            functions.iter().all(|function| {
+                self.safe_functions
+                    .get(function)
+                    .is_some_and(|safe_for| safe_for.contains(&warning.name))
+            })
+        };
+        !warning.addresses.is_empty()
+            && warning
+                .addresses
+                .iter()
+                .map(String::as_str)
+                .chain(warning.get_trace())
+                .all(|address| {
+                    // Addresses of CWE warnings are formatted as decimal numbers.
+                    address
+                        .parse::<u64>()
+                        .ok()
+                        .and_then(|address| self.functions_at_addresses.get(&address))
+                        .is_some_and(are_safe)
+                })
+            && warning
+                .tids
+                .iter()
+                .all(|tid| self.functions_of_terms.get(tid).is_none_or(are_safe))
+    }
+
+    /// Remove the warnings that are suppressed by the allowlist.
+    ///
+    /// A debug log message is generated for each removed warning.
+    pub fn filter_cwe_warnings(&self, warnings: Vec<CweWarning>) -> WithLogs<Vec<CweWarning>> {
+        let mut logs = Vec::new();
+        let warnings = warnings
+            .into_iter()
+            .filter(|warning| {
+                if self.suppresses(warning) {
+                    logs.push(LogMessage::new_debug(format!(
+                        "Suppressed CWE warning in functions verified to be safe: {}",
+                        warning.description
+                    )));
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect();
+        WithLogs::new(warnings, logs)
+    }
+}
+
+/// Get the CWEs for which a function is safe from the suffix of its name.
+///
+/// Returns `None` if the name is not marked as safe.
+fn parse_safe_function_name(name: &str) -> Option<SafeFor> {
+    let demangled = demangle_symbol(name);
+    let name = match &demangled {
+        Some(demangled) => demangled.split('(').next().unwrap_or(demangled),
+        None => name,
+    };
+    let (_, suffix) = name.rsplit_once(SAFE_FUNCTION_MARKER)?;
+    if suffix.is_empty() {
+        return Some(SafeFor::AllCwes);
+    }
+    let cwes: Option<BTreeSet<String>> = suffix
+        .split('_')
+        .map(|number| {
+            (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+                .then(|| format!("CWE{number}"))
+        })
+        .collect();
+    cwes.map(SafeFor::Cwes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::{Blk, Sub, Term};
+
+    #[test]
+    fn safe_function_names() {
+        assert_eq!(
+            parse_safe_function_name("copy_cwe_safe_"),
+            Some(SafeFor::AllCwes)
+        );
+        assert_eq!(
+            parse_safe_function_name("copy_cwe_safe_134_416"),
+            Some(SafeFor::Cwes(BTreeSet::from([
+                "CWE134".to_string(),
+                "CWE416".to_string()
+            ])))
+        );
+        assert_eq!(parse_safe_function_name("copy_cwe_safe_x"), None);
+        assert_eq!(parse_safe_function_name("copy"), None);
+    }
+
+    #[test]
+    fn suppress_warnings() {
+        let mut project = Project::mock_x64();
+        let sub = Term::new(
+            Tid::new_function("0x1000"),
+            Sub::new::<_, &str>(
+                &"format_cwe_safe_134",
+                vec![Term::new(Tid::new_block("0x1000", 0), Blk::new())],
+                None,
+            ),
+        );
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        let mut allowlist = Allowlist::new(&project);
+
+        let warning = |name: &str| {
+            CweWarning::new(name, "0.1", "description").addresses(vec!["4096".to_string()])
+        };
+        assert!(allowlist.suppresses(&warning("CWE134")));
+        assert!(!allowlist.suppresses(&warning("CWE416")));
+        assert!(!allowlist.suppresses(&CweWarning::new("CWE134", "0.1", "description")));
+
+        let annotations = BTreeMap::from([("0x1000".to_string(), vec!["CWE-416".to_string()])]);
+        assert!(allowlist.add_annotations(&annotations).unwrap().is_empty());
+        assert!(allowlist.suppresses(&warning("CWE416")));
+        let annotations = BTreeMap::from([("0x2000".to_string(), vec!["CWE-416".to_string()])]);
+        assert_eq!(allowlist.add_annotations(&annotations).unwrap().len(), 1);
+
+        let warnings = allowlist.filter_cwe_warnings(vec![warning("CWE416"), warning("CWE476")]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings.logs().len(), 1);
+    }
+
+    #[test]
+    fn trace_leaving_safe_function() {
+        let mut project = Project::mock_x64();
+        for (address, name) in [("0x1000", "copy_cwe_safe_"), ("0x2000", "main")] {
+            let sub = Term::new(
+                Tid::new_function(address),
+                Sub::new::<_, &str>(
+                    &name,
+                    vec![Term::new(Tid::new_block(address, 0), Blk::new())],
+                    None,
+                ),
+            );
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        let allowlist = Allowlist::new(&project);
+        let warning = CweWarning::new("CWE134", "0.1", "description")
+            .addresses(vec!["4096".to_string()])
+            .tids(vec![Tid::new_block("0x1000", 0).to_string()]);
+        assert!(allowlist.suppresses(&warning));
+
+        // The trace starts in the unsafe caller of the safe function.
+        let trace = vec![vec!["trace=8192".to_string(), "trace=4096".to_string()]];
+        assert!(!allowlist.suppresses(&warning.clone().other(trace)));
+        let trace = vec![vec!["trace=4096".to_string()]];
+        assert!(allowlist.suppresses(&warning.clone().other(trace)));
+
+        // A TID of the warning is contained in the unsafe caller.
+        let tids = vec![
+            Tid::new_block("0x1000", 0).to_string(),
+            Tid::new_block("0x2000", 0).to_string(),
+        ];
+        assert!(!allowlist.suppresses(&warning.clone().tids(tids)));
+        // TIDs of terms outside of the program are ignored.
+        let tids = vec!["instr_unknown".to_string()];
+        assert!(allowlist.suppresses(&warning.tids(tids)));
+    }
+}
@@ -0,0 +1,133 @@
+//! Export of CWE warnings as annotations for Binary Ninja and radare2.
+//!
+//! Ghidra loads position-independent binaries at a different base address than Binary Ninja and radare2.
+//! Therefore the addresses of the warnings are translated by subtracting the address base offset of the program,
+//! see [`Program::address_base_offset`](crate::intermediate_representation::Program::address_base_offset).
+//!
+//! For Binary Ninja the annotations are a JSON file with one tag per warning,
+//! which can be imported with the script `binja_plugin/cwe_checker_binja_plugin.py`.
+//! For radare2 the annotations are a script with a comment (`CC`) and a flag (`f`) per warning,
+//! which can be loaded with `r2 -i SCRIPT BINARY`.
+
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use std::collections::BTreeSet;
+
+/// The tools for which annotations can be generated.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AnnotationTarget {
+    /// A JSON file with tags for Binary Ninja.
+    BinaryNinja,
+    /// A radare2 script with comments and flags.
+    Radare2,
+}
+
+/// A tag for a CWE warning in Binary Ninja.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BinaryNinjaTag {
+    /// The address of the tag.
+    pub address: u64,
+    /// The name of the tag type, i.e. the name of the check.
+    pub tag_type: String,
+    /// The description of the warning.
+    pub data: String,
+}
+
+/// The tags for the CWE warnings in Binary Ninja.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct BinaryNinjaTags {
+    /// The names of the tag types that the tags use.
+    pub tag_types: BTreeSet<String>,
+    /// The tags for the warnings.
+    pub tags: Vec<BinaryNinjaTag>,
+}
+
+/// Convert the CWE warnings to annotations for the given tool.
+///
+/// Related warnings get annotations of their own.
+/// Warnings without address are skipped, since annotations are attached to addresses.
+pub fn format_annotations(
+    cwes: &[&CweWarning],
+    target: AnnotationTarget,
+    address_base_offset: u64,
+) -> String {
+    let annotations: Vec<(u64, &CweWarning)> = cwes
+        .iter()
+        .flat_map(|cwe| std::iter::once(*cwe).chain(cwe.related.iter()))
+        .filter_map(|cwe| {
+            let address = cwe.get_address()?;
+            Some((address.wrapping_sub(address_base_offset), cwe))
+        })
+        .collect();
+    match target {
+        AnnotationTarget::BinaryNinja => format_binary_ninja_tags(&annotations),
+        AnnotationTarget::Radare2 => format_radare2_script(&annotations),
+    }
+}
+
+/// Format the warnings as a JSON object of Binary Ninja tags.
+fn format_binary_ninja_tags(annotations: &[(u64, &CweWarning)]) -> String {
+    let mut tags = BinaryNinjaTags::default();
+    for (address, cwe) in annotations {
+        tags.tag_types.insert(cwe.name.clone());
+        tags.tags.push(BinaryNinjaTag {
+            address: *address,
+            tag_type: cwe.name.clone(),
+            data: cwe.description.clone(),
+        });
+    }
+    serde_json::to_string_pretty(&tags).unwrap()
+}
+
+/// Format the warnings as a radare2 script.
+///
+/// Each warning gets a comment and a flag named `cwe.<check>.<index>` in the `cwe_checker` flag space.
+/// The comments are quoted, so that special characters in the descriptions are not interpreted by radare2.
+fn format_radare2_script(annotations: &[(u64, &CweWarning)]) -> String {
+    let mut output = String::from("fs cwe_checker\n");
+    for (index, (address, cwe)) in annotations.iter().enumerate() {
+        let comment = format!("[{}] {}", cwe.name, cwe.description).replace(['"', '\n'], " ");
+        output += &format!("\"CC {comment}\" @ {address:#x}\n");
+        output += &format!("f cwe.{}.{index} 1 @ {address:#x}\n", cwe.name);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotations() {
+        let mut warning =
+            CweWarning::new("CWE476", "0.3", "(NULL Pointer Dereference) at \"f\"; ls")
+                .addresses(vec!["1052672".to_string()]);
+        warning
+            .related
+            .push(CweWarning::new("CWE690", "0.1", "(Unchecked Return Value)"));
+        let cwes = [&warning];
+
+        let tags: BinaryNinjaTags = serde_json::from_str(&format_annotations(
+            &cwes,
+            AnnotationTarget::BinaryNinja,
+            0x100000,
+        ))
+        .unwrap();
+        assert_eq!(tags.tag_types, BTreeSet::from(["CWE476".to_string()]));
+        assert_eq!(
+            tags.tags,
+            vec![BinaryNinjaTag {
+                address: 0x1000,
+                tag_type: "CWE476".to_string(),
+                data: "(NULL Pointer Dereference) at \"f\"; ls".to_string(),
+            }]
+        );
+
+        assert_eq!(
+            format_annotations(&cwes, AnnotationTarget::Radare2, 0x100000),
+            "fs cwe_checker\n\
+            \"CC [CWE476] (NULL Pointer Dereference) at  f ; ls\" @ 0x1000\n\
+            f cwe.CWE476.0 1 @ 0x1000\n"
+        );
+    }
+}
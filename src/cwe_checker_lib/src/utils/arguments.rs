@@ -143,105 +143,17 @@ pub fn get_variable_parameters(
 
 /// Calculates the register and stack positions of format string parameters.
 /// The parameters are then returned as an argument vector for later tainting.
+///
+/// The locations are resolved by the [`CallingConventionResolver`](crate::intermediate_representation::CallingConventionResolver)
+/// of the calling convention of the symbol.
 pub fn calculate_parameter_locations(
     variadic_parameters: Vec<(Datatype, ByteSize)>,
     extern_symbol: &ExternSymbol,
     project: &Project,
 ) -> Vec<Arg> {
-    let calling_convention = project.get_calling_convention(extern_symbol);
-    let mut var_args: Vec<Arg> = Vec::new();
-    let mut float_arg_register_count = calling_convention.float_parameter_register.len();
-    let mut stack_offset: i64 = match project.cpu_architecture.as_str() {
-        "x86" | "x86_32" | "x86_64" => u64::from(project.stack_pointer_register.size) as i64,
-        _ => 0,
-    };
-    let mut integer_arg_register_count =
-        if calling_convention.integer_parameter_register.len() >= extern_symbol.parameters.len() {
-            calling_convention.integer_parameter_register.len() - extern_symbol.parameters.len()
-        } else {
-            for param in extern_symbol.parameters.iter() {
-                if let Ok(offset) = param.eval_stack_offset() {
-                    let offset_after = offset.try_to_u64().unwrap() + u64::from(param.bytesize());
-                    stack_offset = std::cmp::max(stack_offset, offset_after as i64);
-                }
-            }
-            0
-        };
-
-    for (data_type, size) in variadic_parameters.iter() {
-        match data_type {
-            Datatype::Integer | Datatype::Pointer | Datatype::Char => {
-                if integer_arg_register_count > 0 {
-                    let register = calling_convention.integer_parameter_register[calling_convention
-                        .integer_parameter_register
-                        .len()
-                        - integer_arg_register_count]
-                        .clone();
-
-                    var_args.push(create_register_arg(
-                        Expression::Var(register),
-                        data_type.clone(),
-                    ));
-
-                    integer_arg_register_count -= 1;
-                } else {
-                    var_args.push(create_stack_arg(
-                        *size,
-                        stack_offset,
-                        data_type.clone(),
-                        &project.stack_pointer_register,
-                    ));
-                    stack_offset += u64::from(*size) as i64
-                }
-            }
-            Datatype::Double => {
-                if float_arg_register_count > 0 {
-                    let expr = calling_convention.float_parameter_register[calling_convention
-                        .float_parameter_register
-                        .len()
-                        - float_arg_register_count]
-                        .clone();
-
-                    var_args.push(create_register_arg(expr, data_type.clone()));
-
-                    float_arg_register_count -= 1;
-                } else {
-                    var_args.push(create_stack_arg(
-                        *size,
-                        stack_offset,
-                        data_type.clone(),
-                        &project.stack_pointer_register,
-                    ));
-                    stack_offset += u64::from(*size) as i64
-                }
-            }
-            _ => panic!("Invalid data type specifier from format string."),
-        }
-    }
-
-    var_args
-}
-
-/// Creates a stack parameter given a size, stack offset and data type.
-fn create_stack_arg(
-    size: ByteSize,
-    stack_offset: i64,
-    data_type: Datatype,
-    stack_register: &Variable,
-) -> Arg {
-    Arg::Stack {
-        address: Expression::Var(stack_register.clone()).plus_const(stack_offset),
-        size,
-        data_type: Some(data_type),
-    }
-}
-
-/// Creates a register parameter given a size, register name and data type.
-fn create_register_arg(expr: Expression, data_type: Datatype) -> Arg {
-    Arg::Register {
-        expr,
-        data_type: Some(data_type),
-    }
+    project
+        .get_extern_symbol_resolver(extern_symbol)
+        .get_variadic_parameter_locations(&extern_symbol.parameters, &variadic_parameters)
 }
 
 #[cfg(test)]
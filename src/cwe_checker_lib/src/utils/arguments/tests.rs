@@ -228,13 +228,35 @@ fn test_calculate_parameter_locations() {
 }
 
 #[test]
-fn test_create_stack_arg() {
-    assert_eq!(
-        Arg::Stack {
-            address: expr!("RSP:8 + 8:8"),
-            size: ByteSize::new(8),
+/// Tests that variadic float parameters are passed in integer registers on RISC-V
+fn test_calculate_parameter_locations_riscv64() {
+    let project = Project::mock_riscv64();
+    let mut extern_symbol = ExternSymbol::mock_riscv64("sprintf");
+    extern_symbol.parameters.push(Arg::mock_register("a1", 8));
+    extern_symbol.has_var_args = true;
+    let parameters: Vec<(Datatype, ByteSize)> = vec![
+        ("d".to_string().into(), ByteSize::new(8)),
+        ("f".to_string().into(), ByteSize::new(8)),
+        ("s".to_string().into(), ByteSize::new(8)),
+    ];
+
+    let expected_args = vec![
+        Arg::Register {
+            expr: expr!("a2:8"),
+            data_type: Some(Datatype::Integer),
+        },
+        Arg::Register {
+            expr: expr!("a3:8"),
+            data_type: Some(Datatype::Double),
+        },
+        Arg::Register {
+            expr: expr!("a4:8"),
             data_type: Some(Datatype::Pointer),
         },
-        create_stack_arg(ByteSize::new(8), 8, Datatype::Pointer, &variable!("RSP:8")),
-    )
+    ];
+
+    assert_eq!(
+        expected_args,
+        calculate_parameter_locations(parameters, &extern_symbol, &project)
+    );
 }
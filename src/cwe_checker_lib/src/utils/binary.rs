@@ -1,7 +1,9 @@
 //! Utility structs and functions which directly parse the binary file.
 
+use crate::error::CweCheckerError;
 use crate::prelude::*;
 use goblin::elf;
+use goblin::mach;
 use goblin::pe;
 
 /// Contains all information parsed out of the bare metal configuration JSON file.
@@ -14,6 +16,8 @@ use goblin::pe;
 /// consisting of exactly one region of non-volatile (flash) memory
 /// and exactly one region of volatile memory (RAM).
 /// Furthermore, we assume that the binary itself is just a dump of the non-volatile memory region.
+/// Further memory regions, e.g. memory-mapped peripherals or aliases of parts of the flash memory,
+/// can be added to this memory map via the `memory_regions` field.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct BareMetalConfig {
     /// The CPU type.
@@ -36,6 +40,32 @@ pub struct BareMetalConfig {
     ///
     /// If the exact size is unknown, then one can try to use an upper approximation instead.
     pub ram_size: String,
+    /// Additional memory regions of the chip.
+    #[serde(default)]
+    pub memory_regions: Vec<BareMetalMemoryRegion>,
+    /// The addresses of the entry points of the firmware (e.g. the reset handler) as hexadecimal numbers.
+    ///
+    /// Since bare metal binaries do not contain a file header,
+    /// Ghidra cannot find the code of the binary on its own without them.
+    #[serde(default)]
+    pub entry_points: Vec<String>,
+}
+
+/// A memory region of a bare metal chip in addition to its flash memory and RAM.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BareMetalMemoryRegion {
+    /// The base address of the memory region as a hexadecimal number.
+    pub base_address: String,
+    /// The size of the memory region as a hexadecimal number.
+    pub size: String,
+    /// The offset in the input binary (as a hexadecimal number) of the contents of the memory region.
+    ///
+    /// If not set, then the memory region is filled with zeroes.
+    #[serde(default)]
+    pub file_offset: Option<String>,
+    /// Whether the memory region is writeable.
+    #[serde(default)]
+    pub is_writeable: bool,
 }
 
 impl BareMetalConfig {
@@ -47,11 +77,11 @@ impl BareMetalConfig {
 }
 
 /// A helper function to parse a hex string to an integer.
-pub fn parse_hex_string_to_u64(mut string: &str) -> Result<u64, Error> {
+pub fn parse_hex_string_to_u64(mut string: &str) -> Result<u64, CweCheckerError> {
     if string.starts_with("0x") {
         string = &string[2..]
     }
-    Ok(u64::from_str_radix(string, 16)?)
+    u64::from_str_radix(string, 16).map_err(|err| CweCheckerError::Config(err.into()))
 }
 
 /// A continuous segment in the memory image.
@@ -132,6 +162,29 @@ impl MemorySegment {
         }
     }
 
+    /// Generate a segment from a segment load command of a Mach-O file.
+    ///
+    /// Segments flagged as `SG_READ_ONLY` are made read-only by the loader
+    /// after applying the fixups and are thus not writeable.
+    pub fn from_mach_o_segment(segment: &mach::segment::Segment) -> MemorySegment {
+        /// Flag of segments that are made read-only after applying the fixups.
+        const SG_READ_ONLY: u32 = 0x10;
+
+        let mut bytes: Vec<u8> = segment.data.to_vec();
+        if segment.vmsize > segment.filesize {
+            // The additional memory space must be filled with null bytes.
+            bytes.resize(segment.vmsize as usize, 0u8);
+        }
+        MemorySegment {
+            bytes,
+            base_address: segment.vmaddr,
+            read_flag: (segment.initprot & mach::constants::VM_PROT_READ) != 0,
+            write_flag: (segment.initprot & mach::constants::VM_PROT_WRITE) != 0
+                && (segment.flags & SG_READ_ONLY) == 0,
+            execute_flag: (segment.initprot & mach::constants::VM_PROT_EXECUTE) != 0,
+        }
+    }
+
     /// Generate a segment with the given `base_address` and content given by `binary`.
     /// The segment is readable, writeable and executable, its size equals the size of `binary`.
     pub fn from_bare_metal_file(binary: &[u8], base_address: u64) -> MemorySegment {
@@ -144,6 +197,39 @@ impl MemorySegment {
         }
     }
 
+    /// Generate a segment for an additional memory region of a bare metal binary.
+    ///
+    /// Returns an error if the values of the region cannot be parsed
+    /// or if its contents are not contained in `binary`.
+    pub fn from_bare_metal_memory_region(
+        binary: &[u8],
+        region: &BareMetalMemoryRegion,
+    ) -> Result<MemorySegment, CweCheckerError> {
+        let base_address = parse_hex_string_to_u64(&region.base_address)?;
+        let size = parse_hex_string_to_u64(&region.size)? as usize;
+        let bytes = match &region.file_offset {
+            Some(file_offset) => {
+                let file_offset = parse_hex_string_to_u64(file_offset)? as usize;
+                binary
+                    .get(file_offset..file_offset.saturating_add(size))
+                    .ok_or_else(|| {
+                        CweCheckerError::Config(anyhow!(
+                            "Memory region not contained in the binary"
+                        ))
+                    })?
+                    .to_vec()
+            }
+            None => vec![0; size],
+        };
+        Ok(MemorySegment {
+            bytes,
+            base_address,
+            read_flag: true,
+            write_flag: region.is_writeable,
+            execute_flag: region.file_offset.is_some(),
+        })
+    }
+
     /// Generate a segment with the given base address and size.
     /// The segment is readable and writeable, but not executable.
     /// The content is set to a vector of zeroes.
@@ -32,6 +32,9 @@ pub enum Stage {
 pub enum IrForm {
     /// The very first IR representation of the program.
     Early,
+    /// After call targets with the ISA mode bit set (ARM/Thumb interworking,
+    /// MIPS16e and microMIPS) have been normalized.
+    IsaModeAddressesNormalized,
     /// Indirect calls with a single target have been replaced by direct calls
     /// to this target.
     SingleTargetIndirectCallsReplaced,
@@ -71,6 +74,9 @@ pub enum IrForm {
     StackPointerAlignmentSubstituted,
     /// The final IR.
     Optimized,
+    /// After temporary registers have been propagated into their uses.
+    /// Only generated if the normalization is enabled in the configuration.
+    TemporariesPropagated,
 }
 
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
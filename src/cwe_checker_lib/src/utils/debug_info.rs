@@ -0,0 +1,318 @@
+//! Mapping of addresses to source code locations through DWARF debug information.
+//!
+//! If an ELF binary contains DWARF debug information,
+//! the [`DebugInfo`] struct maps addresses in the binary to the corresponding source file and line.
+//! It also knows the names of the local variables of functions
+//! that are stored in registers or at fixed offsets in the stack frame.
+//! This information is added to CWE warnings to make them easier to understand.
+
+use crate::error::CweCheckerError;
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use gimli::{constants, AttributeValue, EndianSlice, Operation, Reader, RunTimeEndian};
+use goblin::elf::header::{EM_386, EM_AARCH64, EM_ARM, EM_MIPS, EM_PPC64, EM_RISCV, EM_X86_64};
+use std::ops::Range;
+
+/// The source code location corresponding to an address in the binary.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct SourceLocation {
+    /// The path to the source file.
+    pub file: String,
+    /// The line in the source file.
+    pub line: u64,
+    /// The local variables of the function containing the address
+    /// whose storage location is known.
+    pub variables: Vec<SourceVariable>,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}:{}", self.file, self.line)
+    }
+}
+
+/// A local variable or parameter of a function in the source code.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct SourceVariable {
+    /// The name of the variable in the source code.
+    pub name: String,
+    /// The storage location of the variable,
+    /// i.e. a register name or an offset relative to the stack frame base.
+    pub location: String,
+}
+
+/// A function described by the debug information.
+#[derive(Debug, Clone)]
+struct FunctionInfo {
+    /// The address ranges of the function.
+    ranges: Vec<Range<u64>>,
+    /// The variables of the function with a known storage location.
+    variables: Vec<SourceVariable>,
+}
+
+/// The source code information extracted from the DWARF debug information of a binary.
+#[derive(Debug, Clone, Default)]
+pub struct DebugInfo {
+    /// The paths of all source files referenced by the line tables.
+    files: Vec<String>,
+    /// The rows of the line tables sorted by address.
+    ///
+    /// Each row contains the index of the source file and the line.
+    /// The row is `None` for the end of a sequence of instructions.
+    line_rows: Vec<(u64, Option<(usize, u64)>)>,
+    /// The functions described by the debug information.
+    functions: Vec<FunctionInfo>,
+}
+
+impl DebugInfo {
+    /// Parse the DWARF debug information of the given binary.
+    ///
+    /// Returns `None` if the binary is not an ELF file or does not contain DWARF debug information.
+    pub fn parse(binary: &[u8]) -> Result<Option<DebugInfo>, CweCheckerError> {
+        let elf = match goblin::Object::parse(binary) {
+            Ok(goblin::Object::Elf(elf)) => elf,
+            _ => return Ok(None),
+        };
+        let get_section = |name: &str| -> &[u8] {
+            elf.section_headers
+                .iter()
+                .find(|header| elf.shdr_strtab.get_at(header.sh_name) == Some(name))
+                .and_then(|header| binary.get(header.file_range()?))
+                .unwrap_or(&[])
+        };
+        if get_section(".debug_info").is_empty() {
+            return Ok(None);
+        }
+        let endian = if elf.little_endian {
+            RunTimeEndian::Little
+        } else {
+            RunTimeEndian::Big
+        };
+        let dwarf = gimli::Dwarf::load(|section| -> Result<_, gimli::Error> {
+            Ok(EndianSlice::new(get_section(section.name()), endian))
+        })
+        .map_err(|err| CweCheckerError::Loader(err.into()))?;
+
+        DebugInfo::from_dwarf(&dwarf, elf.header.e_machine)
+            .map(Some)
+            .map_err(CweCheckerError::Loader)
+    }
+
+    /// Collect the line tables and functions of all compilation units of the DWARF information.
+    fn from_dwarf<R: Reader>(dwarf: &gimli::Dwarf<R>, machine: u16) -> Result<DebugInfo, Error> {
+        let mut debug_info = DebugInfo::default();
+        let mut units = dwarf.units();
+        while let Some(header) = units.next()? {
+            let unit = dwarf.unit(header)?;
+            debug_info.add_line_rows(dwarf, &unit)?;
+            debug_info.add_functions(dwarf, &unit, machine)?;
+        }
+        debug_info.line_rows.sort_by_key(|(address, _)| *address);
+
+        Ok(debug_info)
+    }
+
+    /// Add the rows of the line table of the compilation unit.
+    fn add_line_rows<R: Reader>(
+        &mut self,
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+    ) -> Result<(), Error> {
+        let Some(program) = unit.line_program.clone() else {
+            return Ok(());
+        };
+        let mut file_indices = std::collections::HashMap::new();
+        let mut rows = program.rows();
+        while let Some((header, row)) = rows.next_row()? {
+            if row.end_sequence() {
+                self.line_rows.push((row.address(), None));
+                continue;
+            }
+            let (Some(file), Some(line)) = (row.file(header), row.line()) else {
+                continue;
+            };
+            let file_index = match file_indices.get(&row.file_index()) {
+                Some(index) => *index,
+                None => {
+                    let mut path = String::new();
+                    if let Some(directory) = file.directory(header) {
+                        path = dwarf
+                            .attr_string(unit, directory)?
+                            .to_string_lossy()?
+                            .into();
+                    }
+                    let name = dwarf.attr_string(unit, file.path_name())?;
+                    let name = name.to_string_lossy()?;
+                    if path.is_empty() || name.starts_with('/') {
+                        path = name.into();
+                    } else {
+                        path = format!("{path}/{name}");
+                    }
+                    self.files.push(path);
+                    file_indices.insert(row.file_index(), self.files.len() - 1);
+                    self.files.len() - 1
+                }
+            };
+            self.line_rows
+                .push((row.address(), Some((file_index, line.get()))));
+        }
+        Ok(())
+    }
+
+    /// Add the functions of the compilation unit together with their variables.
+    fn add_functions<R: Reader>(
+        &mut self,
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        machine: u16,
+    ) -> Result<(), Error> {
+        // The indices of the enclosing functions in `self.functions` together with their depth.
+        let mut function_stack: Vec<(isize, usize)> = Vec::new();
+        let mut depth = 0;
+        let mut entries = unit.entries();
+        while let Some((delta_depth, entry)) = entries.next_dfs()? {
+            depth += delta_depth;
+            while matches!(function_stack.last(), Some((function_depth, _)) if *function_depth >= depth)
+            {
+                function_stack.pop();
+            }
+            match entry.tag() {
+                constants::DW_TAG_subprogram => {
+                    let mut ranges = Vec::new();
+                    let mut range_iter = dwarf.die_ranges(unit, entry)?;
+                    while let Some(range) = range_iter.next()? {
+                        ranges.push(range.begin..range.end);
+                    }
+                    self.functions.push(FunctionInfo {
+                        ranges,
+                        variables: Vec::new(),
+                    });
+                    function_stack.push((depth, self.functions.len() - 1));
+                }
+                constants::DW_TAG_variable | constants::DW_TAG_formal_parameter => {
+                    let Some((_, function_index)) = function_stack.last() else {
+                        continue;
+                    };
+                    let (Some(name), Some(AttributeValue::Exprloc(expression))) = (
+                        entry.attr_value(constants::DW_AT_name)?,
+                        entry.attr_value(constants::DW_AT_location)?,
+                    ) else {
+                        continue;
+                    };
+                    let mut bytecode = expression.0;
+                    let location = match Operation::parse(&mut bytecode, unit.encoding()) {
+                        Ok(Operation::Register { register }) => {
+                            get_register_name(machine, register)
+                        }
+                        Ok(Operation::FrameOffset { offset }) => {
+                            format!("frame base {offset:+}")
+                        }
+                        _ => continue,
+                    };
+                    let name = dwarf.attr_string(unit, name)?.to_string_lossy()?.into();
+                    self.functions[*function_index]
+                        .variables
+                        .push(SourceVariable { name, location });
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the source code location corresponding to the given address.
+    pub fn get_source_location(&self, address: u64) -> Option<SourceLocation> {
+        let row_index = self
+            .line_rows
+            .partition_point(|(row_address, _)| *row_address <= address)
+            .checked_sub(1)?;
+        let (file_index, line) = self.line_rows[row_index].1?;
+        let variables = self
+            .functions
+            .iter()
+            .filter(|function| function.ranges.iter().any(|range| range.contains(&address)))
+            .flat_map(|function| function.variables.iter().cloned())
+            .collect();
+        Some(SourceLocation {
+            file: self.files[file_index].clone(),
+            line,
+            variables,
+        })
+    }
+
+    /// Add the source code location of the first address of the CWE warning to the warning.
+    ///
+    /// The `address_base_offset` is the offset that was added to all addresses of the binary
+    /// when it was loaded, see [`Program::address_base_offset`](crate::intermediate_representation::Program::address_base_offset).
+    pub fn add_source_location(&self, warning: &mut CweWarning, address_base_offset: u64) {
+        if let Some(address) = warning.get_address() {
+            warning.source_location =
+                self.get_source_location(address.wrapping_sub(address_base_offset));
+        }
+    }
+}
+
+/// Get the name of the DWARF register for the given ELF machine type.
+///
+/// The names are converted to uppercase to match the register names of the IR.
+fn get_register_name(machine: u16, register: gimli::Register) -> String {
+    let name = match machine {
+        EM_X86_64 => gimli::X86_64::register_name(register),
+        EM_386 => gimli::X86::register_name(register),
+        EM_ARM => gimli::Arm::register_name(register),
+        EM_AARCH64 => gimli::AArch64::register_name(register),
+        EM_MIPS => gimli::MIPS::register_name(register),
+        EM_PPC64 => gimli::PowerPc64::register_name(register),
+        EM_RISCV => gimli::RiscV::register_name(register),
+        _ => None,
+    };
+    match name {
+        Some(name) => name.to_uppercase(),
+        None => format!("DWARF register {}", register.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_location() {
+        let debug_info = DebugInfo {
+            files: vec!["src/main.c".to_string(), "src/util.c".to_string()],
+            line_rows: vec![
+                (0x1000, Some((0, 10))),
+                (0x1008, Some((0, 12))),
+                (0x1010, None),
+                (0x2000, Some((1, 3))),
+            ],
+            functions: vec![FunctionInfo {
+                ranges: vec![0x1000..0x1008, 0x1008..0x1010],
+                variables: vec![SourceVariable {
+                    name: "buffer".to_string(),
+                    location: "frame base -24".to_string(),
+                }],
+            }],
+        };
+        let location = debug_info.get_source_location(0x100c).unwrap();
+        assert_eq!(location.to_string(), "src/main.c:12");
+        assert_eq!(location.variables[0].name, "buffer");
+        assert_eq!(debug_info.get_source_location(0x1010), None);
+        assert_eq!(debug_info.get_source_location(0x800), None);
+        let location = debug_info.get_source_location(0x2004).unwrap();
+        assert_eq!(location.to_string(), "src/util.c:3");
+        assert!(location.variables.is_empty());
+
+        let mut warning =
+            CweWarning::new("CWE676", "0.1", "description").addresses(vec!["4356".to_string()]);
+        debug_info.add_source_location(&mut warning, 0x100);
+        assert_eq!(warning.source_location.unwrap().line, 10);
+    }
+
+    #[test]
+    fn register_names() {
+        assert_eq!(get_register_name(EM_X86_64, gimli::Register(5)), "RDI");
+        assert_eq!(get_register_name(0, gimli::Register(5)), "DWARF register 5");
+        assert!(DebugInfo::parse(b"not a binary").unwrap().is_none());
+    }
+}
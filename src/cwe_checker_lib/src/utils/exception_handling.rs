@@ -0,0 +1,460 @@
+//! Parsing of the exception handling information of ELF binaries.
+//!
+//! C++ binaries compiled with GCC or Clang describe their exception handlers (landing pads)
+//! in the language-specific data areas (LSDA) in the `.gcc_except_table` section.
+//! The LSDA of a function is referenced by its frame description entry (FDE) in the `.eh_frame` section.
+//! Each LSDA contains a call site table that maps address ranges of call instructions
+//! to the landing pads that are executed if the call throws an exception.
+//!
+//! Since no instruction jumps to a landing pad, the code of exception handlers
+//! would be unreachable in the control flow graph.
+//! Thus we add the landing pads as exception handlers to the blocks of the corresponding calls
+//! (see [`Blk::exception_handler`](crate::intermediate_representation::Blk::exception_handler)).
+
+use crate::error::CweCheckerError;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use goblin::elf;
+use std::collections::HashMap;
+
+/// Pointer encoding value signaling that the pointer is omitted.
+const DW_EH_PE_OMIT: u8 = 0xff;
+
+/// A call site range together with its landing pad.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CallSite {
+    /// The start address of the call site range.
+    pub start: u64,
+    /// The end address (exclusive) of the call site range.
+    pub end: u64,
+    /// The address of the landing pad for calls in the range.
+    pub landing_pad: u64,
+}
+
+/// Parse the call sites with landing pads from the exception handling information of an ELF binary.
+///
+/// Returns an error if the binary is not an ELF file or has no `.eh_frame` section.
+/// FDEs and LSDAs that cannot be parsed are ignored.
+pub fn parse_call_sites(binary: &[u8]) -> Result<Vec<CallSite>, CweCheckerError> {
+    parse_elf_call_sites(binary).map_err(CweCheckerError::Loader)
+}
+
+/// Parse the call sites with landing pads of an ELF binary.
+fn parse_elf_call_sites(binary: &[u8]) -> Result<Vec<CallSite>, Error> {
+    let elf_file = elf::Elf::parse(binary)?;
+    let sections: Vec<(u64, &[u8])> = elf_file
+        .section_headers
+        .iter()
+        .filter(|header| header.sh_type != elf::section_header::SHT_NOBITS && header.sh_addr != 0)
+        .filter_map(|header| Some((header.sh_addr, binary.get(header.file_range()?)?)))
+        .collect();
+    let eh_frame = elf_file
+        .section_headers
+        .iter()
+        .find(|header| elf_file.shdr_strtab.get_at(header.sh_name) == Some(".eh_frame"))
+        .ok_or_else(|| anyhow!("No .eh_frame section found"))?;
+    let eh_frame = Reader {
+        bytes: binary
+            .get(eh_frame.file_range().unwrap_or_default())
+            .ok_or_else(|| anyhow!("Invalid .eh_frame section"))?,
+        address: eh_frame.sh_addr,
+        is_little_endian: elf_file.little_endian,
+        pointer_size: if elf_file.is_64 { 8 } else { 4 },
+    };
+
+    let mut call_sites = Vec::new();
+    for (function_start, lsda_address) in eh_frame.parse_lsda_pointers() {
+        let Some(lsda) = sections.iter().find_map(|(address, bytes)| {
+            let offset = lsda_address.checked_sub(*address)? as usize;
+            (offset < bytes.len()).then(|| Reader {
+                bytes: &bytes[offset..],
+                address: lsda_address,
+                ..eh_frame
+            })
+        }) else {
+            continue;
+        };
+        if let Some(lsda_call_sites) = lsda.parse_lsda(function_start) {
+            call_sites.extend(lsda_call_sites);
+        }
+    }
+
+    Ok(call_sites)
+}
+
+/// Helper struct for reading the contents of exception handling sections.
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    /// The bytes to read.
+    bytes: &'a [u8],
+    /// The virtual address of the first byte.
+    address: u64,
+    is_little_endian: bool,
+    pointer_size: usize,
+}
+
+impl Reader<'_> {
+    /// Read an unsigned integer of the given size at the given offset.
+    fn read_uint(&self, offset: &mut usize, size: usize) -> Option<u64> {
+        let bytes = self.bytes.get(*offset..offset.checked_add(size)?)?;
+        *offset += size;
+        let fold = |value: u64, byte: &u8| (value << 8) | u64::from(*byte);
+        if self.is_little_endian {
+            Some(bytes.iter().rev().fold(0, fold))
+        } else {
+            Some(bytes.iter().fold(0, fold))
+        }
+    }
+
+    /// Read a signed integer of the given size at the given offset.
+    fn read_sint(&self, offset: &mut usize, size: usize) -> Option<u64> {
+        let value = self.read_uint(offset, size)?;
+        let shift = 64 - 8 * size as u32;
+        Some((((value << shift) as i64) >> shift) as u64)
+    }
+
+    /// Read an unsigned LEB128 value at the given offset.
+    fn read_uleb128(&self, offset: &mut usize) -> Option<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = *self.bytes.get(*offset)?;
+            *offset += 1;
+            if shift < 64 {
+                value |= u64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+    }
+
+    /// Read a signed LEB128 value at the given offset.
+    fn read_sleb128(&self, offset: &mut usize) -> Option<u64> {
+        let start = *offset;
+        let value = self.read_uleb128(offset)?;
+        let shift = 7 * (*offset - start) as u32;
+        if shift < 64 && self.bytes[*offset - 1] & 0x40 != 0 {
+            Some(value | (u64::MAX << shift))
+        } else {
+            Some(value)
+        }
+    }
+
+    /// Read a byte at the given offset.
+    fn read_u8(&self, offset: &mut usize) -> Option<u8> {
+        self.read_uint(offset, 1).map(|value| value as u8)
+    }
+
+    /// Read a pointer with the given DWARF exception header pointer encoding.
+    ///
+    /// Only absolute and PC-relative pointers are supported.
+    fn read_encoded_pointer(&self, offset: &mut usize, encoding: u8) -> Option<u64> {
+        let field_address = self.address + *offset as u64;
+        let value = match encoding & 0x0f {
+            0x00 => self.read_uint(offset, self.pointer_size)?,
+            0x01 => self.read_uleb128(offset)?,
+            0x02 => self.read_uint(offset, 2)?,
+            0x03 => self.read_uint(offset, 4)?,
+            0x04 => self.read_uint(offset, 8)?,
+            0x09 => self.read_sleb128(offset)?,
+            0x0a => self.read_sint(offset, 2)?,
+            0x0b => self.read_sint(offset, 4)?,
+            0x0c => self.read_sint(offset, 8)?,
+            _ => return None,
+        };
+        match encoding & 0x70 {
+            0x00 => Some(value),
+            0x10 => Some(field_address.wrapping_add(value)),
+            _ => None,
+        }
+    }
+
+    /// Parse the `.eh_frame` section and return the pairs of function start addresses
+    /// and LSDA addresses of all FDEs with an LSDA.
+    fn parse_lsda_pointers(&self) -> Vec<(u64, u64)> {
+        // Maps the offset of a CIE to its FDE pointer encoding and LSDA encoding.
+        let mut cies: HashMap<usize, (u8, Option<u8>)> = HashMap::new();
+        let mut lsda_pointers = Vec::new();
+        let mut offset = 0;
+        while let Some(length) = self.read_uint(&mut offset, 4) {
+            let entry_start = offset;
+            let length = match length {
+                0 => break,
+                0xffffffff => match self.read_uint(&mut offset, 8) {
+                    Some(length) => length,
+                    None => break,
+                },
+                length => length,
+            };
+            let Some(entry_end) = (offset as u64)
+                .checked_add(length)
+                .and_then(|end| usize::try_from(end).ok())
+            else {
+                break;
+            };
+            let id_offset = offset;
+            match self.read_uint(&mut offset, 4) {
+                Some(0) => {
+                    if let Some(cie) = self.parse_cie(&mut offset) {
+                        cies.insert(entry_start - 4, cie);
+                    }
+                }
+                Some(cie_pointer) => {
+                    let cie = id_offset
+                        .checked_sub(cie_pointer as usize)
+                        .and_then(|cie_offset| cies.get(&cie_offset));
+                    if let Some((fde_encoding, Some(lsda_encoding))) = cie {
+                        if let Some(pointers) =
+                            self.parse_fde(&mut offset, *fde_encoding, *lsda_encoding)
+                        {
+                            lsda_pointers.push(pointers);
+                        }
+                    }
+                }
+                None => break,
+            }
+            offset = entry_end;
+        }
+
+        lsda_pointers
+    }
+
+    /// Parse a CIE and return its FDE pointer encoding and LSDA encoding (if present).
+    fn parse_cie(&self, offset: &mut usize) -> Option<(u8, Option<u8>)> {
+        let version = self.read_u8(offset)?;
+        let augmentation_length = self.bytes.get(*offset..)?.iter().position(|&b| b == 0)?;
+        let augmentation = self.bytes[*offset..*offset + augmentation_length].to_vec();
+        *offset += augmentation_length + 1;
+        if augmentation.first() != Some(&b'z') {
+            return None;
+        }
+        // code alignment factor, data alignment factor and return address register
+        self.read_uleb128(offset)?;
+        self.read_sleb128(offset)?;
+        if version == 1 {
+            self.read_u8(offset)?;
+        } else {
+            self.read_uleb128(offset)?;
+        }
+        self.read_uleb128(offset)?;
+        let mut fde_encoding = 0;
+        let mut lsda_encoding = None;
+        for character in augmentation[1..].iter() {
+            match character {
+                b'L' => lsda_encoding = Some(self.read_u8(offset)?),
+                b'R' => fde_encoding = self.read_u8(offset)?,
+                b'P' => {
+                    let encoding = self.read_u8(offset)?;
+                    // The personality routine pointer may be indirect or data-relative,
+                    // so we only skip its bytes.
+                    self.read_encoded_pointer(offset, encoding & 0x0f)?;
+                }
+                b'S' | b'B' => (),
+                _ => return None,
+            }
+        }
+
+        Some((fde_encoding, lsda_encoding))
+    }
+
+    /// Parse an FDE and return the start address of the function and the address of its LSDA.
+    fn parse_fde(
+        &self,
+        offset: &mut usize,
+        fde_encoding: u8,
+        lsda_encoding: u8,
+    ) -> Option<(u64, u64)> {
+        let function_start = self.read_encoded_pointer(offset, fde_encoding)?;
+        // The address range of the function is not relative to anything.
+        self.read_encoded_pointer(offset, fde_encoding & 0x0f)?;
+        self.read_uleb128(offset)?;
+        if lsda_encoding == DW_EH_PE_OMIT {
+            return None;
+        }
+        let lsda = self.read_encoded_pointer(offset, lsda_encoding)?;
+        (lsda != 0).then_some((function_start, lsda))
+    }
+
+    /// Parse the call site table of an LSDA.
+    ///
+    /// Call sites without a landing pad are omitted.
+    fn parse_lsda(&self, function_start: u64) -> Option<Vec<CallSite>> {
+        let mut offset = 0;
+        let landing_pad_start_encoding = self.read_u8(&mut offset)?;
+        let landing_pad_start = if landing_pad_start_encoding == DW_EH_PE_OMIT {
+            function_start
+        } else {
+            self.read_encoded_pointer(&mut offset, landing_pad_start_encoding)?
+        };
+        if self.read_u8(&mut offset)? != DW_EH_PE_OMIT {
+            // Offset of the type table
+            self.read_uleb128(&mut offset)?;
+        }
+        let call_site_encoding = self.read_u8(&mut offset)?;
+        let call_site_table_length = self.read_uleb128(&mut offset)?;
+        let call_site_table_end = offset.checked_add(call_site_table_length as usize)?;
+        let mut call_sites = Vec::new();
+        while offset < call_site_table_end {
+            let start = self.read_encoded_pointer(&mut offset, call_site_encoding)?;
+            let length = self.read_encoded_pointer(&mut offset, call_site_encoding)?;
+            let landing_pad = self.read_encoded_pointer(&mut offset, call_site_encoding)?;
+            // Action table entry
+            self.read_uleb128(&mut offset)?;
+            if landing_pad != 0 {
+                call_sites.push(CallSite {
+                    start: function_start.wrapping_add(start),
+                    end: function_start.wrapping_add(start).wrapping_add(length),
+                    landing_pad: landing_pad_start.wrapping_add(landing_pad),
+                });
+            }
+        }
+
+        Some(call_sites)
+    }
+}
+
+/// Add the landing pads of the given call sites as exception handlers
+/// to the blocks of the program that end with a call inside a call site range.
+///
+/// Returns the number of blocks for which an exception handler was added.
+pub fn add_exception_handlers(program: &mut Term<Program>, call_sites: &[CallSite]) -> usize {
+    let address_base_offset = program.term.address_base_offset;
+    let mut num_handlers = 0;
+    for sub in program.term.subs.values_mut() {
+        let block_addresses: HashMap<u64, Tid> = sub
+            .term
+            .blocks
+            .iter()
+            .rev()
+            .filter_map(|block| Some((u64::try_from(block.tid.address()).ok()?, block.tid.clone())))
+            .collect();
+        for block in sub.term.blocks.iter_mut() {
+            let Some(call_address) = block
+                .term
+                .jmps
+                .iter()
+                .find(|jmp| matches!(jmp.term, Jmp::Call { .. } | Jmp::CallInd { .. }))
+                .and_then(|call| u64::try_from(call.tid.address()).ok())
+            else {
+                continue;
+            };
+            let call_address = call_address.wrapping_sub(address_base_offset);
+            let handler = call_sites
+                .iter()
+                .find(|call_site| call_site.start <= call_address && call_address < call_site.end)
+                .and_then(|call_site| {
+                    block_addresses.get(&call_site.landing_pad.wrapping_add(address_base_offset))
+                });
+            if let Some(handler) = handler {
+                block.term.set_exception_handler(handler.clone());
+                num_handlers += 1;
+            }
+        }
+    }
+
+    num_handlers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_reader(bytes: &[u8], address: u64) -> Reader<'_> {
+        Reader {
+            bytes,
+            address,
+            is_little_endian: true,
+            pointer_size: 8,
+        }
+    }
+
+    #[test]
+    fn leb128_and_encoded_pointers() {
+        let reader = mock_reader(&[0xe5, 0x8e, 0x26, 0x7f, 0xfc, 0xff, 0xff, 0xff], 0x1000);
+        let mut offset = 0;
+        assert_eq!(reader.read_uleb128(&mut offset), Some(624485));
+        assert_eq!(reader.read_sleb128(&mut offset), Some(-1i64 as u64));
+        // PC-relative signed 4-byte value
+        assert_eq!(
+            reader.read_encoded_pointer(&mut offset, 0x1b),
+            Some(0x1000 + 4 - 4)
+        );
+        assert_eq!(reader.read_encoded_pointer(&mut offset, 0x1b), None);
+    }
+
+    #[test]
+    fn eh_frame_and_lsda() {
+        let mut eh_frame = Vec::new();
+        // CIE with augmentation "zLR": LSDA and FDE pointers are PC-relative sdata4 values.
+        let cie = [
+            0, 0, 0, 0, 1, b'z', b'L', b'R', 0, 1, 0x78, 0x10, 2, 0x1b, 0x1b, 0, 0, 0,
+        ];
+        eh_frame.extend((cie.len() as u32).to_le_bytes());
+        eh_frame.extend(cie);
+        // FDE for the function at 0x2000 with the LSDA at 0x3000.
+        let fde_start = eh_frame.len();
+        eh_frame.extend(24u32.to_le_bytes());
+        eh_frame.extend(((fde_start + 4) as u32).to_le_bytes());
+        let pc_begin = 0x2000i64 - (0x1000 + eh_frame.len() as i64);
+        eh_frame.extend((pc_begin as i32).to_le_bytes());
+        eh_frame.extend(0x100u32.to_le_bytes());
+        eh_frame.push(4);
+        let lsda = 0x3000i64 - (0x1000 + eh_frame.len() as i64);
+        eh_frame.extend((lsda as i32).to_le_bytes());
+        eh_frame.extend([0u8; 7]);
+        let reader = mock_reader(&eh_frame, 0x1000);
+        assert_eq!(reader.parse_lsda_pointers(), vec![(0x2000, 0x3000)]);
+
+        // LSDA with two call sites (uleb128 encoded), only the first has a landing pad.
+        let lsda = [0xff, 0xff, 0x01, 8, 0x10, 0x08, 0x40, 0, 0x20, 0x08, 0, 0];
+        let reader = mock_reader(&lsda, 0x3000);
+        assert_eq!(
+            reader.parse_lsda(0x2000),
+            Some(vec![CallSite {
+                start: 0x2010,
+                end: 0x2018,
+                landing_pad: 0x2040,
+            }])
+        );
+    }
+
+    #[test]
+    fn exception_handlers() {
+        let mut program = Project::mock_x64().program;
+        program.term.address_base_offset = 0;
+        let mut call_block = Term {
+            tid: Tid::mock("blk_0x2010"),
+            term: Blk::new(),
+        };
+        call_block.term.jmps = vec![Term {
+            tid: Tid::mock("instr_0x2014_0"),
+            term: Jmp::Call {
+                target: Tid::new("callee"),
+                return_: None,
+            },
+        }];
+        let landing_pad = Term {
+            tid: Tid::mock("blk_0x2040"),
+            term: Blk::new(),
+        };
+        let sub = Term {
+            tid: Tid::mock("FUN_0x2000"),
+            term: Sub::new::<_, &str>("sub", vec![call_block, landing_pad], None),
+        };
+        program.term.subs.insert(sub.tid.clone(), sub);
+        let call_sites = vec![CallSite {
+            start: 0x2010,
+            end: 0x2018,
+            landing_pad: 0x2040,
+        }];
+
+        assert_eq!(add_exception_handlers(&mut program, &call_sites), 1);
+        let call_block = &program.term.subs[&Tid::mock("FUN_0x2000")].term.blocks[0];
+        assert_eq!(
+            call_block.term.exception_handler(),
+            Some(&Tid::mock("blk_0x2040"))
+        );
+    }
+}
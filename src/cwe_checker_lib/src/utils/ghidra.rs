@@ -1,5 +1,6 @@
 //! Utility functions for executing Ghidra and extracting P-Code from the output.
 
+use crate::error::CweCheckerError;
 use crate::ghidra_pcode::PcodeProject;
 use crate::intermediate_representation::{Project, RuntimeMemoryImage};
 use crate::prelude::*;
@@ -9,13 +10,20 @@ use crate::utils::log::{LogMessage, WithLogs};
 use crate::utils::{get_ghidra_plugin_path, read_config_file};
 
 use directories::ProjectDirs;
-use nix::{sys::stat, unistd};
+use nix::{fcntl::OFlag, sys::stat, unistd};
 
+use std::collections::HashSet;
 use std::io::Read;
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use std::thread;
 
+/// Counts the Ghidra instances started by this process to generate unique names for temporary files.
+static GHIDRA_INSTANCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
 /// Execute the `p_code_extractor` plugin in Ghidra and parse its output into the `Project` data structure.
 ///
 /// Return an error if the creation of the project failed.
@@ -24,7 +32,7 @@ pub fn get_project_from_ghidra(
     binary: &[u8],
     bare_metal_config_opt: Option<BareMetalConfig>,
     debug_settings: &debug::Settings,
-) -> Result<WithLogs<Project>, Error> {
+) -> Result<WithLogs<Project>, CweCheckerError> {
     let pcode_project = if let Some(saved_pcode_raw) = debug_settings.get_saved_pcode_raw() {
         let mut file = std::fs::File::open(saved_pcode_raw)
             .expect("Failed to open saved output of Pcode Extractor plugin.");
@@ -32,28 +40,22 @@ pub fn get_project_from_ghidra(
         file.read_to_string(&mut saved_pcode_raw)
             .expect("Failed to read saved Pcode Extractor plugin output");
         debug_settings.print(&saved_pcode_raw, debug::Stage::Pcode(debug::PcodeForm::Raw));
-        serde_json::from_str(&saved_pcode_raw)?
+        serde_json::from_str(&saved_pcode_raw)
+            .context("Failed to parse saved Pcode Extractor plugin output.")
+            .map_err(CweCheckerError::Lifting)?
     } else {
-        let tmp_folder = get_tmp_folder()?;
-        // We add a timestamp suffix to file names
-        // so that if two instances of the cwe_checker are running in parallel on the same file
-        // they do not interfere with each other.
-        let timestamp_suffix = format!(
-            "{:?}",
-            std::time::SystemTime::now()
-                .duration_since(std::time::SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis()
-        );
+        let tmp_folder = get_tmp_folder().map_err(CweCheckerError::Lifting)?;
+        let timestamp_suffix = get_unique_suffix();
         // Create a unique name for the pipe
         let fifo_path = tmp_folder.join(format!("pcode_{timestamp_suffix}.pipe"));
         let ghidra_command = generate_ghidra_call_command(
-            file_path,
+            &[file_path],
             &fifo_path,
             &timestamp_suffix,
             &bare_metal_config_opt,
         )?;
-        execute_ghidra(ghidra_command, &fifo_path, debug_settings)?
+        execute_ghidra(ghidra_command, &fifo_path, debug_settings)
+            .map_err(CweCheckerError::Lifting)?
     };
     debug_settings.print(
         &pcode_project,
@@ -68,6 +70,69 @@ pub fn get_project_from_ghidra(
     )
 }
 
+/// Execute the `p_code_extractor` plugin in a single Ghidra instance for all given binaries
+/// and return their P-Code projects in the order of the file paths.
+///
+/// Starting Ghidra takes a lot of time,
+/// so lifting many binaries in one Ghidra instance is much faster than starting an instance per binary.
+/// The file names of the binaries must be distinct, since Ghidra names the imported programs after them.
+/// If the plugin fails for a binary, only the result of this binary is an error.
+pub fn get_pcode_projects_from_ghidra(
+    file_paths: &[&Path],
+    debug_settings: &debug::Settings,
+) -> Result<Vec<Result<PcodeProject, CweCheckerError>>, CweCheckerError> {
+    let mut file_names = HashSet::new();
+    for file_path in file_paths {
+        let file_name = file_path
+            .file_name()
+            .ok_or_else(|| CweCheckerError::Loader(anyhow!("Invalid file name")))?;
+        if !file_names.insert(file_name) {
+            return Err(CweCheckerError::Loader(anyhow!(
+                "The file name {} is not unique",
+                file_name.to_string_lossy()
+            )));
+        }
+    }
+    let tmp_folder = get_tmp_folder().map_err(CweCheckerError::Lifting)?;
+    let timestamp_suffix = get_unique_suffix();
+    // The plugin writes the P-Code of each program to a file of its own in the output folder.
+    let output_folder = tmp_folder.join(format!("pcode_{timestamp_suffix}"));
+    std::fs::create_dir(&output_folder)
+        .context("Unable to create the output folder of the Ghidra plugin")
+        .map_err(CweCheckerError::Lifting)?;
+    let mut ghidra_command =
+        generate_ghidra_call_command(file_paths, &output_folder, &timestamp_suffix, &None)?;
+    let ghidra_result = match ghidra_command.output() {
+        Ok(output) => check_ghidra_output(output, debug_settings.verbose()),
+        Err(err) => Err(anyhow!("Ghidra could not be executed: {err}")),
+    };
+
+    let pcode_projects = file_paths
+        .iter()
+        .map(|file_path| {
+            let file_name = file_path.file_name().unwrap().to_string_lossy();
+            let output_path = output_folder.join(format!("{file_name}.json"));
+            let Ok(pcode_raw) = std::fs::read_to_string(output_path) else {
+                // Ghidra continues with the next binary if the plugin fails for a binary.
+                let error = match &ghidra_result {
+                    Err(err) => anyhow!("{err:#}"),
+                    Ok(()) => anyhow!("Execution of Ghidra plugin failed."),
+                };
+                return Err(CweCheckerError::Lifting(error));
+            };
+            debug_settings.print(&pcode_raw, debug::Stage::Pcode(debug::PcodeForm::Raw));
+            serde_json::from_str(&pcode_raw)
+                .context("Failed to parse plugin output.")
+                .map_err(CweCheckerError::Lifting)
+        })
+        .collect();
+    std::fs::remove_dir_all(&output_folder)
+        .context("Could not clean up the output folder of the Ghidra plugin")
+        .map_err(CweCheckerError::Lifting)?;
+
+    Ok(pcode_projects)
+}
+
 /// Normalize the given P-Code project and then parse it into a project struct
 /// of the internally used intermediate representation.
 pub fn parse_pcode_project_to_ir_project(
@@ -75,7 +140,7 @@ pub fn parse_pcode_project_to_ir_project(
     binary: &[u8],
     bare_metal_config_opt: &Option<BareMetalConfig>,
     debug_settings: &debug::Settings,
-) -> Result<WithLogs<Project>, Error> {
+) -> Result<WithLogs<Project>, CweCheckerError> {
     let bare_metal_base_address_opt = bare_metal_config_opt
         .as_ref()
         .map(|config| config.parse_binary_base_address());
@@ -110,7 +175,7 @@ pub fn parse_pcode_project_to_ir_project(
 
 /// Execute Ghidra with the P-Code plugin and return the parsed P-Code project.
 ///
-/// Note that this function will abort the program if the Ghidra execution does not succeed.
+/// Returns an error if the Ghidra execution does not succeed.
 fn execute_ghidra(
     mut ghidra_command: Command,
     fifo_path: &PathBuf,
@@ -122,32 +187,19 @@ fn execute_ghidra(
         .context("Error creating FIFO pipe")?;
     // Execute Ghidra in a new thread and return a Join Handle, so that the thread is only joined
     // after the output has been read into the cwe_checker
-    let ghidra_subprocess = thread::spawn(move || {
+    let thread_fifo_path = fifo_path.clone();
+    let reading_finished = Arc::new(AtomicBool::new(false));
+    let thread_reading_finished = reading_finished.clone();
+    let ghidra_subprocess = thread::spawn(move || -> Result<(), Error> {
         // Execute the command and catch its output.
-        let output = match ghidra_command.output() {
-            Ok(output) => output,
-            Err(err) => {
-                eprintln!("Ghidra could not be executed: {err}");
-                std::process::exit(101);
-            }
+        let result = match ghidra_command.output() {
+            Ok(output) => check_ghidra_output(output, should_print_ghidra_error),
+            Err(err) => Err(anyhow!("Ghidra could not be executed: {err}")),
         };
-
-        if let Ok(stdout) = String::from_utf8(output.stdout.clone()) {
-            if stdout.contains("Pcode was successfully extracted!") && output.status.success() {
-                return;
-            }
+        if result.is_err() {
+            unblock_fifo_reader(&thread_fifo_path, &thread_reading_finished);
         }
-        if should_print_ghidra_error {
-            eprintln!("{}", String::from_utf8(output.stdout).unwrap());
-            eprintln!("{}", String::from_utf8(output.stderr).unwrap());
-            if let Some(code) = output.status.code() {
-                eprintln!("Ghidra plugin failed with exit code {code}");
-            }
-            eprintln!("Execution of Ghidra plugin failed.");
-        } else {
-            eprintln!("Execution of Ghidra plugin failed. Use the --verbose flag to print Ghidra output for troubleshooting.");
-        }
-        std::process::exit(101)
+        result
     });
 
     // Open the FIFO
@@ -155,45 +207,102 @@ fn execute_ghidra(
     let mut buf = String::new();
     file.read_to_string(&mut buf)
         .expect("Error while reading from FIFO.");
-    debug_settings.print(&buf, debug::Stage::Pcode(debug::PcodeForm::Raw));
-    let pcode_parsing_result = serde_json::from_str(&buf).expect("Failed to parse plugin output.");
-
-    ghidra_subprocess
+    reading_finished.store(true, Ordering::Relaxed);
+    let ghidra_result = ghidra_subprocess
         .join()
         .expect("The Ghidra thread to be joined has panicked!");
-    // Clean up the FIFO pipe and propagate errors from the JSON parsing.
+    // Clean up the FIFO pipe and propagate errors from the Ghidra execution and the JSON parsing.
     std::fs::remove_file(fifo_path).context("Could not clean up FIFO pipe")?;
+    ghidra_result?;
+    debug_settings.print(&buf, debug::Stage::Pcode(debug::PcodeForm::Raw));
+    let pcode_parsing_result =
+        serde_json::from_str(&buf).context("Failed to parse plugin output.")?;
+
     Ok(pcode_parsing_result)
 }
 
+/// Open and close the write end of the FIFO,
+/// so that the reader does not wait forever for the output of a failed plugin execution.
+///
+/// The FIFO is opened without blocking, since the reader may already have finished reading.
+/// Until the reader has opened the FIFO, opening it fails and is retried.
+fn unblock_fifo_reader(fifo_path: &Path, reading_finished: &AtomicBool) {
+    while !reading_finished.load(Ordering::Relaxed) {
+        let fifo = std::fs::OpenOptions::new()
+            .write(true)
+            .custom_flags(OFlag::O_NONBLOCK.bits())
+            .open(fifo_path);
+        if fifo.is_ok() {
+            return;
+        }
+        thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
+/// Check whether the P-Code plugin was executed successfully by Ghidra.
+///
+/// If `verbose` is set, the error contains the output of Ghidra for troubleshooting.
+fn check_ghidra_output(output: std::process::Output, verbose: bool) -> Result<(), Error> {
+    if let Ok(stdout) = String::from_utf8(output.stdout.clone()) {
+        if stdout.contains("Pcode was successfully extracted!") && output.status.success() {
+            return Ok(());
+        }
+    }
+    if verbose {
+        let mut message = String::new();
+        for ghidra_output in [&output.stdout, &output.stderr] {
+            let ghidra_output = String::from_utf8_lossy(ghidra_output);
+            if !ghidra_output.trim().is_empty() {
+                message += &format!("{}\n", ghidra_output.trim_end());
+            }
+        }
+        if let Some(code) = output.status.code() {
+            message += &format!("Ghidra plugin failed with exit code {code}\n");
+        }
+        Err(anyhow!("{message}Execution of Ghidra plugin failed."))
+    } else {
+        Err(anyhow!("Execution of Ghidra plugin failed. Use the --verbose flag to print Ghidra output for troubleshooting."))
+    }
+}
+
 /// Generate the command that is used to call Ghidra and execute the P-Code-Extractor plugin in it.
+///
+/// The plugin writes the P-Code of a single binary to the file or named pipe at `output_path`.
+/// If `output_path` is a directory, the P-Code of each binary is written to the file `<file name>.json` in it.
 fn generate_ghidra_call_command(
-    file_path: &Path,
-    fifo_path: &Path,
+    file_paths: &[&Path],
+    output_path: &Path,
     timestamp_suffix: &str,
     bare_metal_config_opt: &Option<BareMetalConfig>,
-) -> Result<Command, Error> {
+) -> Result<Command, CweCheckerError> {
     let ghidra_path: std::path::PathBuf =
         serde_json::from_value(read_config_file("ghidra.json")?["ghidra_path"].clone())
-            .context("Path to Ghidra not configured.")?;
+            .context("Path to Ghidra not configured.")
+            .map_err(CweCheckerError::Config)?;
     let headless_path = ghidra_path.join("support/analyzeHeadless");
-    let tmp_folder = get_tmp_folder()?;
-    let filename = file_path
-        .file_name()
-        .ok_or_else(|| anyhow!("Invalid file name"))?
+    let tmp_folder = get_tmp_folder().map_err(CweCheckerError::Lifting)?;
+    let filename = file_paths
+        .first()
+        .and_then(|file_path| file_path.file_name())
+        .ok_or_else(|| CweCheckerError::Loader(anyhow!("Invalid file name")))?
         .to_string_lossy()
         .to_string();
     let ghidra_plugin_path = get_ghidra_plugin_path("p_code_extractor")?;
+    // Entry points are passed as additional arguments to the PcodeExtractor.java script.
+    let entry_points: &[String] = bare_metal_config_opt
+        .as_ref()
+        .map_or(&[], |config| &config.entry_points);
 
     let mut ghidra_command = Command::new(headless_path);
     ghidra_command
         .arg(&tmp_folder) // The folder where temporary files should be stored
         .arg(format!("PcodeExtractor_{filename}_{timestamp_suffix}")) // The name of the temporary Ghidra Project.
-        .arg("-import") // Import a file into the Ghidra project
-        .arg(file_path) // File import path
+        .arg("-import") // Import files into the Ghidra project
+        .args(file_paths) // File import paths
         .arg("-postScript") // Execute a script after standard analysis by Ghidra finished
         .arg(ghidra_plugin_path.join("PcodeExtractor.java")) // Path to the PcodeExtractor.java
-        .arg(fifo_path) // The path to the named pipe (fifo)
+        .arg(output_path) // The path to the named pipe (fifo) or the output folder
+        .args(entry_points) // Entry points at which functions are created (for bare metal binaries)
         .arg("-scriptPath") // Add a folder containing additional script files to the Ghidra script file search paths
         .arg(ghidra_plugin_path) // Path to the folder containing the PcodeExtractor.java (so that the other java files can be found.)
         .arg("-deleteProject") // Delete the temporary project after the script finished
@@ -216,6 +325,24 @@ fn generate_ghidra_call_command(
     Ok(ghidra_command)
 }
 
+/// Get a suffix for the names of temporary files.
+///
+/// We add a timestamp suffix to file names
+/// so that if several instances of Ghidra are running in parallel on the same file
+/// they do not interfere with each other.
+/// The process ID and a counter distinguish instances started at the same time.
+fn get_unique_suffix() -> String {
+    format!(
+        "{:?}_{}_{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis(),
+        std::process::id(),
+        GHIDRA_INSTANCE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
 /// Get the folder where temporary files should be stored for the program.
 fn get_tmp_folder() -> Result<PathBuf, Error> {
     let project_dirs = ProjectDirs::from("", "", "cwe_checker")
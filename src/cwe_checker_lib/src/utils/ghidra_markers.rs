@@ -0,0 +1,179 @@
+//! Export of the analysis results as markers that can be imported into Ghidra.
+//!
+//! The markers consist of bookmarks and comments at the addresses of the CWE warnings
+//! and of comments with the recovered string arguments at calls to string functions.
+//! The script `ghidra_plugin/cwe_checker_ghidra_plugin.py` imports them into a Ghidra project,
+//! so that analysts can jump from the CWE warnings to the corresponding disassembly.
+
+use crate::analysis::string_abstraction::report::CallSiteStrings;
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+
+/// The category of bookmarks for CWE warnings.
+pub const BOOKMARK_CATEGORY: &str = "[cwe_checker]";
+
+/// The kinds of comments in Ghidra.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CommentKind {
+    /// An end-of-line comment.
+    Eol,
+    /// A comment displayed before the instruction.
+    Pre,
+}
+
+/// A bookmark in Ghidra.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Bookmark {
+    /// The (hexadecimal) address of the bookmark
+    /// or `None` if the bookmark belongs to the program as a whole.
+    pub address: Option<String>,
+    /// The category of the bookmark.
+    pub category: String,
+    /// The text of the bookmark.
+    pub text: String,
+}
+
+/// A comment in Ghidra.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Comment {
+    /// The (hexadecimal) address of the comment
+    /// or `None` if the comment belongs to the program as a whole.
+    pub address: Option<String>,
+    /// The kind of the comment.
+    pub kind: CommentKind,
+    /// The text of the comment.
+    pub text: String,
+}
+
+/// Markers for the analysis results that can be imported into Ghidra.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct GhidraMarkers {
+    /// Bookmarks at the addresses of the CWE warnings.
+    pub bookmarks: Vec<Bookmark>,
+    /// Comments for the CWE warnings and the recovered strings.
+    pub comments: Vec<Comment>,
+}
+
+impl GhidraMarkers {
+    /// Add a bookmark and an end-of-line comment at the first address of each CWE warning.
+    ///
+    /// The addresses are converted to the hexadecimal format expected by Ghidra.
+    ///
+    /// Related warnings get markers of their own.
+    /// Warnings without address are attached to the program as a whole.
+    pub fn add_cwe_warnings(&mut self, cwes: &[&CweWarning]) {
+        let all_cwes = cwes
+            .iter()
+            .flat_map(|cwe| std::iter::once(*cwe).chain(cwe.related.iter()));
+        for cwe in all_cwes {
+            let address = cwe.get_address().map(|address| format!("{address:x}"));
+            let text = match address {
+                Some(_) => cwe.description.clone(),
+                None => format!("[{}] {}", cwe.name, cwe.description),
+            };
+            self.bookmarks.push(Bookmark {
+                address: address.clone(),
+                category: BOOKMARK_CATEGORY.to_string(),
+                text: text.clone(),
+            });
+            self.comments.push(Comment {
+                kind: if address.is_some() {
+                    CommentKind::Eol
+                } else {
+                    CommentKind::Pre
+                },
+                address,
+                text,
+            });
+        }
+    }
+
+    /// Add a pre-comment with the recovered string arguments at each call site.
+    ///
+    /// Call sites at unknown addresses or without string arguments are skipped.
+    pub fn add_string_annotations(&mut self, call_sites: &[CallSiteStrings]) {
+        for call_site in call_sites {
+            let Ok(address) = u64::try_from(call_site.call.address()) else {
+                continue;
+            };
+            if call_site.arguments.is_empty() {
+                continue;
+            }
+            let arguments: Vec<String> = call_site
+                .arguments
+                .iter()
+                .map(|argument| format!("{}: {}", argument.register, argument.value))
+                .collect();
+            self.comments.push(Comment {
+                address: Some(format!("{address:x}")),
+                kind: CommentKind::Pre,
+                text: format!(
+                    "{BOOKMARK_CATEGORY} {}({})",
+                    call_site.callee,
+                    arguments.join(", ")
+                ),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::BricksDomain;
+    use crate::analysis::string_abstraction::report::StringArgument;
+    use crate::intermediate_representation::TidAddress;
+
+    #[test]
+    fn markers() {
+        let mut warning = CweWarning::new("CWE476", "0.3", "(NULL Pointer Dereference) at 4096")
+            .addresses(vec!["4096".to_string()]);
+        warning.related.push(CweWarning::new(
+            "CWE690",
+            "0.1",
+            "(Unchecked Return Value) at 4096",
+        ));
+        let mut markers = GhidraMarkers::default();
+        markers.add_cwe_warnings(&[&warning]);
+        assert_eq!(markers.bookmarks.len(), 2);
+        assert_eq!(markers.bookmarks[0].address, Some("1000".to_string()));
+        assert_eq!(
+            markers.comments[0].text,
+            "(NULL Pointer Dereference) at 4096"
+        );
+        assert_eq!(markers.comments[0].kind, CommentKind::Eol);
+        assert_eq!(markers.bookmarks[1].address, None);
+        assert_eq!(
+            markers.comments[1].text,
+            "[CWE690] (Unchecked Return Value) at 4096"
+        );
+        assert_eq!(markers.comments[1].kind, CommentKind::Pre);
+
+        let call_site = |address: Option<u64>, arguments: Vec<StringArgument>| {
+            let mut call = Tid::new("call");
+            call.set_address(TidAddress::new(address));
+            CallSiteStrings {
+                call,
+                caller: "main".to_string(),
+                callee: "system".to_string(),
+                arguments,
+            }
+        };
+        let argument = StringArgument::new("RDI", &BricksDomain::from("ls".to_string()));
+        let mut markers = GhidraMarkers::default();
+        markers.add_string_annotations(&[
+            call_site(Some(0x2a), vec![argument.clone()]),
+            call_site(None, vec![argument]),
+            call_site(Some(0x30), Vec::new()),
+        ]);
+        assert!(markers.bookmarks.is_empty());
+        assert_eq!(
+            markers.comments,
+            vec![Comment {
+                address: Some("2a".to_string()),
+                kind: CommentKind::Pre,
+                text: "[cwe_checker] system(RDI: Bricks: {\"ls\"}^(1,1) )".to_string(),
+            }]
+        );
+    }
+}
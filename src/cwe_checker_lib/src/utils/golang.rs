@@ -0,0 +1,455 @@
+//! Support for binaries generated by the Go compiler.
+//!
+//! Go binaries contain a function table (the *pclntab*) with the entry addresses and names
+//! of all functions of the binary, even if the binary is stripped.
+//! We use it to recover the names of functions that Ghidra could not name.
+//!
+//! Furthermore, Go strings are not NUL-terminated.
+//! The string literals of a Go binary are stored back-to-back in read-only memory
+//! and are referenced by (pointer, length) pairs.
+//! Since Go passes function arguments in registers,
+//! such pairs can be recovered from the constants that are assigned to consecutive
+//! argument registers before a call.
+//! The recovered lengths are stored in [`RuntimeMemoryImage::string_lengths`]
+//! so that the string abstraction reads string literals with their correct length.
+
+use crate::error::CweCheckerError;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use goblin::{mach, Object};
+use std::collections::{BTreeMap, HashMap};
+
+/// Magic number of the pclntab of Go 1.2 to Go 1.15.
+const PCLNTAB_MAGIC_GO_1_2: u32 = 0xfffffffb;
+/// Magic number of the pclntab of Go 1.16 and Go 1.17.
+const PCLNTAB_MAGIC_GO_1_16: u32 = 0xfffffffa;
+/// Magic number of the pclntab of Go 1.18 and Go 1.19.
+const PCLNTAB_MAGIC_GO_1_18: u32 = 0xfffffff0;
+/// Magic number of the pclntab of Go 1.20 and later.
+const PCLNTAB_MAGIC_GO_1_20: u32 = 0xfffffff1;
+
+/// String literals longer than this are not recovered,
+/// since such constants are more likely to be something else than a string length.
+const MAX_STRING_LITERAL_LENGTH: u64 = 0x1000;
+
+/// A function entry of the Go function table.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GoFunction {
+    /// The entry address of the function.
+    pub entry: u64,
+    /// The (fully qualified) name of the function, e.g. `main.main`.
+    pub name: String,
+}
+
+/// Parse the function table (pclntab) of a Go binary.
+///
+/// Returns an error if the binary contains no pclntab or if the pclntab could not be parsed.
+/// The pclntab is found via its section name in ELF and Mach-O files.
+pub fn parse_pclntab(binary: &[u8]) -> Result<Vec<GoFunction>, CweCheckerError> {
+    find_pclntab(binary)
+        .and_then(parse_pclntab_contents)
+        .map_err(CweCheckerError::Loader)
+}
+
+/// Find the pclntab section of an ELF or Mach-O binary.
+fn find_pclntab(binary: &[u8]) -> Result<&[u8], Error> {
+    let pclntab = match Object::parse(binary)? {
+        Object::Elf(elf_file) => elf_file
+            .section_headers
+            .iter()
+            .find(|header| elf_file.shdr_strtab.get_at(header.sh_name) == Some(".gopclntab"))
+            .and_then(|header| header.file_range())
+            .and_then(|range| binary.get(range)),
+        Object::Mach(mach::Mach::Binary(mach_o)) => {
+            let mut pclntab = None;
+            for segment in mach_o.segments.iter() {
+                for (section, data) in segment.sections()? {
+                    if section.name()? == "__gopclntab" {
+                        pclntab = Some(data);
+                    }
+                }
+            }
+            pclntab
+        }
+        _ => None,
+    };
+
+    pclntab.ok_or_else(|| anyhow!("No Go function table found"))
+}
+
+/// Parse the contents of a pclntab.
+///
+/// The byte order is determined from the magic number at the start of the table.
+fn parse_pclntab_contents(pclntab: &[u8]) -> Result<Vec<GoFunction>, Error> {
+    let magic_bytes: [u8; 4] = pclntab
+        .get(0..4)
+        .ok_or_else(|| anyhow!("Go function table too small"))?
+        .try_into()?;
+    let (magic, is_little_endian) = if is_pclntab_magic(u32::from_le_bytes(magic_bytes)) {
+        (u32::from_le_bytes(magic_bytes), true)
+    } else if is_pclntab_magic(u32::from_be_bytes(magic_bytes)) {
+        (u32::from_be_bytes(magic_bytes), false)
+    } else {
+        return Err(anyhow!("Unknown Go function table version"));
+    };
+    let pointer_size = *pclntab
+        .get(7)
+        .ok_or_else(|| anyhow!("Go function table too small"))? as usize;
+    if pointer_size != 4 && pointer_size != 8 {
+        return Err(anyhow!("Invalid pointer size in Go function table"));
+    }
+    let table = PclntabReader {
+        bytes: pclntab,
+        is_little_endian,
+    };
+    let read_header_word = |index: usize| table.read_uint(8 + index * pointer_size, pointer_size);
+    let num_functions = read_header_word(0)? as usize;
+
+    let mut functions = Vec::new();
+    match magic {
+        PCLNTAB_MAGIC_GO_1_2 => {
+            let functab = 8 + pointer_size;
+            for index in 0..num_functions {
+                let entry_offset = functab + 2 * index * pointer_size;
+                let entry = table.read_uint(entry_offset, pointer_size)?;
+                let func = table.read_uint(entry_offset + pointer_size, pointer_size)? as usize;
+                let name_offset = table.read_uint(func + pointer_size, 4)? as usize;
+                let name = table.read_name(name_offset)?;
+                functions.push(GoFunction { entry, name });
+            }
+        }
+        PCLNTAB_MAGIC_GO_1_16 => {
+            let funcname_table = read_header_word(2)? as usize;
+            let functab = read_header_word(6)? as usize;
+            for index in 0..num_functions {
+                let entry_offset = functab + 2 * index * pointer_size;
+                let entry = table.read_uint(entry_offset, pointer_size)?;
+                let func = table.read_uint(entry_offset + pointer_size, pointer_size)? as usize;
+                let name_offset = table.read_uint(functab + func + pointer_size, 4)? as usize;
+                let name = table.read_name(funcname_table + name_offset)?;
+                functions.push(GoFunction { entry, name });
+            }
+        }
+        _ => {
+            let text_start = read_header_word(2)?;
+            let funcname_table = read_header_word(3)? as usize;
+            let functab = read_header_word(7)? as usize;
+            for index in 0..num_functions {
+                let entry_offset = functab + 8 * index;
+                let entry = text_start + table.read_uint(entry_offset, 4)?;
+                let func = table.read_uint(entry_offset + 4, 4)? as usize;
+                let name_offset = table.read_uint(functab + func + 4, 4)? as usize;
+                let name = table.read_name(funcname_table + name_offset)?;
+                functions.push(GoFunction { entry, name });
+            }
+        }
+    }
+
+    Ok(functions)
+}
+
+/// Returns true if the given value is the magic number of a supported pclntab version.
+fn is_pclntab_magic(value: u32) -> bool {
+    matches!(
+        value,
+        PCLNTAB_MAGIC_GO_1_2
+            | PCLNTAB_MAGIC_GO_1_16
+            | PCLNTAB_MAGIC_GO_1_18
+            | PCLNTAB_MAGIC_GO_1_20
+    )
+}
+
+/// Helper struct for reading values from a pclntab.
+struct PclntabReader<'a> {
+    bytes: &'a [u8],
+    is_little_endian: bool,
+}
+
+impl PclntabReader<'_> {
+    /// Read an unsigned integer of the given size (in bytes) at the given offset.
+    fn read_uint(&self, offset: usize, size: usize) -> Result<u64, Error> {
+        let bytes = self
+            .bytes
+            .get(offset..offset.saturating_add(size))
+            .ok_or_else(|| anyhow!("Offset out of bounds of the Go function table"))?;
+        let fold = |value: u64, byte: &u8| (value << 8) | u64::from(*byte);
+        if self.is_little_endian {
+            Ok(bytes.iter().rev().fold(0, fold))
+        } else {
+            Ok(bytes.iter().fold(0, fold))
+        }
+    }
+
+    /// Read the NUL-terminated function name at the given offset.
+    fn read_name(&self, offset: usize) -> Result<String, Error> {
+        let bytes = self
+            .bytes
+            .get(offset..)
+            .ok_or_else(|| anyhow!("Offset out of bounds of the Go function table"))?;
+        let end = bytes
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or_else(|| anyhow!("Unterminated function name in the Go function table"))?;
+        Ok(String::from_utf8_lossy(&bytes[..end]).to_string())
+    }
+}
+
+/// Rename the functions of the program that have no name in the binary (i.e. Ghidra named them `FUN_*`)
+/// to the names given in the Go function table.
+///
+/// Returns the number of renamed functions.
+pub fn add_go_function_names(program: &mut Term<Program>, functions: &[GoFunction]) -> usize {
+    let address_base_offset = program.term.address_base_offset;
+    let names: HashMap<u64, &String> = functions
+        .iter()
+        .map(|function| {
+            (
+                function.entry.wrapping_add(address_base_offset),
+                &function.name,
+            )
+        })
+        .collect();
+    let mut num_renamed = 0;
+    for sub in program.term.subs.values_mut() {
+        if !sub.term.name.starts_with("FUN_") {
+            continue;
+        }
+        let Ok(address) = u64::try_from(sub.tid.address()) else {
+            continue;
+        };
+        if let Some(name) = names.get(&address) {
+            sub.term.name = name.to_string();
+            num_renamed += 1;
+        }
+    }
+
+    num_renamed
+}
+
+/// Return the integer argument registers of the Go register-based calling convention
+/// of the CPU architecture of the project in the order in which they are used for arguments.
+///
+/// Returns an empty list for CPU architectures where Go passes arguments on the stack.
+fn get_go_argument_registers(project: &Project) -> Vec<String> {
+    if project.get_pointer_bytesize() != ByteSize::new(8) {
+        return Vec::new();
+    }
+    match project.get_cpu_architecture() {
+        CpuArchitecture::X86 => ["RAX", "RBX", "RCX", "RDI", "RSI", "R8", "R9", "R10", "R11"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        CpuArchitecture::Aarch64 => (0..16).map(|index| format!("x{index}")).collect(),
+        CpuArchitecture::RiscV => [
+            "a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7", "s0", "s1", "s2", "s3", "s4", "s5",
+            "s6", "s7",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Return the value of the expression if it is a (zero-extended) constant.
+fn get_constant_value(expression: &Expression) -> Option<u64> {
+    match expression {
+        Expression::Const(constant) => constant.try_to_u64().ok(),
+        Expression::Cast {
+            op: CastOpType::IntZExt,
+            arg,
+            ..
+        } => get_constant_value(arg),
+        _ => None,
+    }
+}
+
+/// Recover the (pointer, length) pairs of Go string literals in the project.
+///
+/// For each call we check which argument registers are assigned constant values
+/// in the basic block of the call.
+/// If a register contains a pointer to read-only memory and the next argument register
+/// contains a length such that the referenced bytes are a valid UTF-8 string,
+/// then the pair is assumed to be a string literal.
+///
+/// Returns a map from the addresses of the recovered string literals to their lengths.
+pub fn recover_go_string_literals(project: &Project) -> BTreeMap<u64, u64> {
+    let argument_registers = get_go_argument_registers(project);
+    let memory_image = &project.runtime_memory_image;
+    let mut string_lengths = BTreeMap::new();
+    for sub in project.program.term.subs.values() {
+        for block in sub.term.blocks.iter() {
+            if !block
+                .term
+                .jmps
+                .iter()
+                .any(|jmp| matches!(jmp.term, Jmp::Call { .. } | Jmp::CallInd { .. }))
+            {
+                continue;
+            }
+            let mut constants: HashMap<&str, u64> = HashMap::new();
+            for def in block.term.defs.iter() {
+                match &def.term {
+                    Def::Assign { var, value } => match get_constant_value(value) {
+                        Some(constant) => {
+                            constants.insert(&var.name, constant);
+                        }
+                        None => {
+                            constants.remove(var.name.as_str());
+                        }
+                    },
+                    Def::Load { var, .. } => {
+                        constants.remove(var.name.as_str());
+                    }
+                    Def::Store { .. } => (),
+                }
+            }
+            for registers in argument_registers.windows(2) {
+                let (Some(&pointer), Some(&length)) = (
+                    constants.get(registers[0].as_str()),
+                    constants.get(registers[1].as_str()),
+                ) else {
+                    continue;
+                };
+                if length == 0 || length > MAX_STRING_LITERAL_LENGTH {
+                    continue;
+                }
+                let pointer_value = Bitvector::from_u64(pointer);
+                if memory_image
+                    .read_string_with_length(&pointer_value, length)
+                    .is_ok()
+                {
+                    string_lengths.insert(pointer, length);
+                }
+            }
+        }
+    }
+
+    string_lengths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defs;
+    use crate::intermediate_representation::parsing;
+
+    /// Create a pclntab in the format of Go 1.18 with the functions `main.main` at 0x401000
+    /// and `main.foo` at 0x401100.
+    fn mock_pclntab_go_1_18() -> Vec<u8> {
+        let mut pclntab = vec![0xf0, 0xff, 0xff, 0xff, 0, 0, 1, 8];
+        // nfunc, nfiles, textStart, funcnameOffset, cuOffset, filetabOffset, pctabOffset, pclnOffset
+        for word in [2u64, 0, 0x401000, 0x48, 0, 0, 0, 0x5b] {
+            pclntab.extend(word.to_le_bytes());
+        }
+        pclntab.extend(b"main.main\0main.foo\0");
+        // functab at 0x5b: (entryoff, funcoff) pairs
+        for word in [0u32, 0x18, 0x100, 0x20] {
+            pclntab.extend(word.to_le_bytes());
+        }
+        pclntab.extend([0u8; 8]);
+        // _func structs: (entryoff, nameoff)
+        for word in [0u32, 0, 0x100, 10] {
+            pclntab.extend(word.to_le_bytes());
+        }
+        pclntab
+    }
+
+    #[test]
+    fn parse_go_1_18_pclntab() {
+        let functions = parse_pclntab_contents(&mock_pclntab_go_1_18()).unwrap();
+        assert_eq!(
+            functions,
+            vec![
+                GoFunction {
+                    entry: 0x401000,
+                    name: "main.main".to_string()
+                },
+                GoFunction {
+                    entry: 0x401100,
+                    name: "main.foo".to_string()
+                }
+            ]
+        );
+        let mut truncated = mock_pclntab_go_1_18();
+        truncated.truncate(0x60);
+        assert!(parse_pclntab_contents(&truncated).is_err());
+        assert!(parse_pclntab_contents(&[0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn parse_go_1_2_pclntab() {
+        let mut pclntab = vec![0xff, 0xff, 0xff, 0xfb, 0, 0, 1, 4];
+        // nfunc, then the functab with (entry, funcoff) pairs
+        for word in [1u32, 0x1000, 0x1c, 0x1010, 0] {
+            pclntab.extend(word.to_be_bytes());
+        }
+        // _func struct at 0x1c: (entry, nameoff)
+        for word in [0x1000u32, 0x24] {
+            pclntab.extend(word.to_be_bytes());
+        }
+        pclntab.extend(b"main.main\0");
+        let functions = parse_pclntab_contents(&pclntab).unwrap();
+        assert_eq!(
+            functions,
+            vec![GoFunction {
+                entry: 0x1000,
+                name: "main.main".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn function_names() {
+        let mut program = Project::mock_x64().program;
+        // The mock program has an address base offset of 0x1000.
+        let sub = Term {
+            tid: Tid::mock("FUN_0x402100"),
+            term: Sub::new::<_, &str>("FUN_00402100", Vec::new(), None),
+        };
+        program.term.subs.insert(sub.tid.clone(), sub);
+        let functions = parse_pclntab_contents(&mock_pclntab_go_1_18()).unwrap();
+        assert_eq!(add_go_function_names(&mut program, &functions), 1);
+        assert_eq!(
+            program.term.subs[&Tid::mock("FUN_0x402100")].term.name,
+            "main.foo"
+        );
+    }
+
+    #[test]
+    fn string_literals() {
+        let mut project = Project::mock_x64();
+        let mut block = Blk::mock();
+        block.term.defs = defs![
+            "RAX:8 = 0x3002:8",
+            "RBX:8 = 0x5:8",
+            "RCX:8 = 0x5000:8",
+            "RDI:8 = 0x1000:8"
+        ];
+        block.term.jmps = vec![Term {
+            tid: Tid::new("call"),
+            term: Jmp::Call {
+                target: Tid::new("target"),
+                return_: None,
+            },
+        }];
+        let sub = Term {
+            tid: Tid::new("sub"),
+            term: Sub::new::<_, &str>("sub", vec![block], None),
+        };
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+
+        let string_lengths = recover_go_string_literals(&project);
+        // The pair (0x5000, 0x1000) is not contained in memory and (0x5, 0x5000) is too long.
+        assert_eq!(string_lengths, BTreeMap::from([(0x3002, 5)]));
+
+        project.runtime_memory_image.string_lengths = string_lengths;
+        assert_eq!(
+            project
+                .runtime_memory_image
+                .read_string_literal(&Bitvector::from_u64(0x3002))
+                .unwrap(),
+            "Hello"
+        );
+    }
+}
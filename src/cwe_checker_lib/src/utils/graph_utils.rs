@@ -47,7 +47,8 @@ pub fn is_sink_call_reachable_from_source_call(
                 | Edge::CallCombine(_)
                 | Edge::ReturnCombine(_)
                 | Edge::Jump(_, _)
-                | Edge::ExternCallStub(_) => {
+                | Edge::ExternCallStub(_)
+                | Edge::ExceptionHandler(_) => {
                     if !visited_nodes.contains(&edge.target()) {
                         visited_nodes.insert(edge.target());
                         worklist.push(edge.target())
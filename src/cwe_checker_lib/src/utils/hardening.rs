@@ -0,0 +1,363 @@
+//! Report of the compile-time hardening of a binary.
+//!
+//! The [`HardeningReport`] lists the exploit mitigations of a binary
+//! similar to tools like `checksec`.
+//! It is computed from the headers of the binary alone, i.e. without lifting it:
+//!
+//! - For ELF binaries the RELRO and NX status and position independence are
+//!   read from the program headers and the dynamic section.
+//!   Stack canaries and `_FORTIFY_SOURCE` are recognized by the imported symbols,
+//!   see [`Mitigation::from_symbol_name`].
+//!   Control flow integrity features like Intel CET or ARM BTI are read from the
+//!   GNU property notes.
+//! - For PE binaries the DLL characteristics of the optional header are used.
+//!
+//! Other binary formats are not supported.
+
+use crate::error::CweCheckerError;
+use crate::prelude::*;
+use crate::utils::mitigations::Mitigation;
+use goblin::elf::dynamic::{DF_1_NOW, DF_1_PIE, DF_BIND_NOW, DT_BIND_NOW, DT_FLAGS, DT_FLAGS_1};
+use goblin::elf::header::{EM_386, EM_AARCH64, EM_X86_64, ET_DYN};
+use goblin::elf::note::NT_GNU_PROPERTY_TYPE_0;
+use goblin::elf::program_header::{PF_X, PT_GNU_RELRO, PT_GNU_STACK};
+use std::collections::BTreeSet;
+
+/// The GNU property containing the x86 features that all object files of the binary support.
+const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc000_0002;
+/// The GNU property containing the AArch64 features that all object files of the binary support.
+const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc000_0000;
+/// The PE DLL characteristic indicating that the image can be relocated at load time.
+const IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE: u16 = 0x0040;
+/// The PE DLL characteristic indicating that the image is compatible with data execution prevention.
+const IMAGE_DLLCHARACTERISTICS_NX_COMPAT: u16 = 0x0100;
+/// The PE DLL characteristic indicating that the image supports Control Flow Guard.
+const IMAGE_DLLCHARACTERISTICS_GUARD_CF: u16 = 0x4000;
+/// Symbols of the runtime support of the Clang control flow integrity sanitizer.
+const CLANG_CFI_SYMBOLS: [&str; 3] = ["__cfi_check", "__cfi_slowpath", "__cfi_slowpath_diag"];
+
+/// The relocation read-only (RELRO) status of an ELF binary.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum Relro {
+    /// The relocated data, e.g. the global offset table, stays writable.
+    No,
+    /// Only relocated data that is not needed for lazy binding is read-only.
+    Partial,
+    /// All relocations are resolved at load time and all relocated data is read-only.
+    Full,
+}
+
+impl std::fmt::Display for Relro {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relro::No => write!(formatter, "no"),
+            Relro::Partial => write!(formatter, "partial"),
+            Relro::Full => write!(formatter, "full"),
+        }
+    }
+}
+
+/// A control flow integrity feature of a binary.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum ControlFlowIntegrity {
+    /// Intel CET indirect branch tracking.
+    IndirectBranchTracking,
+    /// Intel CET shadow stack.
+    ShadowStack,
+    /// ARM branch target identification.
+    BranchTargetIdentification,
+    /// ARM pointer authentication of return addresses.
+    PointerAuthentication,
+    /// The control flow integrity sanitizer of Clang.
+    ClangCfi,
+    /// Control Flow Guard of Windows binaries.
+    ControlFlowGuard,
+}
+
+impl std::fmt::Display for ControlFlowIntegrity {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlFlowIntegrity::IndirectBranchTracking => write!(formatter, "IBT"),
+            ControlFlowIntegrity::ShadowStack => write!(formatter, "SHSTK"),
+            ControlFlowIntegrity::BranchTargetIdentification => write!(formatter, "BTI"),
+            ControlFlowIntegrity::PointerAuthentication => write!(formatter, "PAC"),
+            ControlFlowIntegrity::ClangCfi => write!(formatter, "Clang CFI"),
+            ControlFlowIntegrity::ControlFlowGuard => write!(formatter, "CFG"),
+        }
+    }
+}
+
+/// The compile-time exploit mitigations of a binary.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct HardeningReport {
+    /// The RELRO status. Always [`Relro::No`] for PE binaries.
+    pub relro: Relro,
+    /// Whether the stack and the data of the binary are not executable.
+    pub nx: bool,
+    /// Whether the binary can be loaded at a random base address,
+    /// i.e. whether it is position independent or a shared library.
+    pub pie: bool,
+    /// Whether the binary uses stack canaries.
+    pub canary: bool,
+    /// Whether the binary calls checked variants of libc functions.
+    pub fortify_source: bool,
+    /// The control flow integrity features of the binary.
+    pub cfi: BTreeSet<ControlFlowIntegrity>,
+}
+
+impl HardeningReport {
+    /// Compute the hardening report of the given binary.
+    ///
+    /// Returns an error if the binary is neither an ELF nor a PE file.
+    pub fn parse(binary: &[u8]) -> Result<HardeningReport, CweCheckerError> {
+        match goblin::Object::parse(binary).map_err(|err| CweCheckerError::Loader(err.into()))? {
+            goblin::Object::Elf(elf) => Ok(HardeningReport::from_elf(&elf, binary)),
+            goblin::Object::PE(pe) => Ok(HardeningReport::from_pe(&pe)),
+            _ => Err(CweCheckerError::Loader(anyhow!(
+                "Hardening reports are only supported for ELF and PE binaries."
+            ))),
+        }
+    }
+
+    /// Compute the hardening report of an ELF binary.
+    fn from_elf(elf: &goblin::elf::Elf, binary: &[u8]) -> HardeningReport {
+        let dynamic_entries = elf
+            .dynamic
+            .as_ref()
+            .map(|dynamic| dynamic.dyns.as_slice())
+            .unwrap_or_default();
+        let get_dynamic_flags = |tag: u64| -> u64 {
+            dynamic_entries
+                .iter()
+                .filter(|entry| entry.d_tag == tag)
+                .fold(0, |flags, entry| flags | entry.d_val)
+        };
+        let bind_now = dynamic_entries
+            .iter()
+            .any(|entry| entry.d_tag == DT_BIND_NOW)
+            || get_dynamic_flags(DT_FLAGS) & DF_BIND_NOW != 0
+            || get_dynamic_flags(DT_FLAGS_1) & DF_1_NOW != 0;
+        let relro = match (
+            elf.program_headers
+                .iter()
+                .any(|header| header.p_type == PT_GNU_RELRO),
+            bind_now,
+        ) {
+            (false, _) => Relro::No,
+            (true, false) => Relro::Partial,
+            (true, true) => Relro::Full,
+        };
+        // Without a `PT_GNU_STACK` header the stack is executable.
+        let nx = elf
+            .program_headers
+            .iter()
+            .any(|header| header.p_type == PT_GNU_STACK && header.p_flags & PF_X == 0);
+        let pie = elf.header.e_type == ET_DYN || get_dynamic_flags(DT_FLAGS_1) & DF_1_PIE != 0;
+
+        let symbol_names: BTreeSet<&str> = elf
+            .dynsyms
+            .iter()
+            .filter_map(|symbol| elf.dynstrtab.get_at(symbol.st_name))
+            .chain(
+                elf.syms
+                    .iter()
+                    .filter_map(|symbol| elf.strtab.get_at(symbol.st_name)),
+            )
+            .collect();
+        let mitigations: BTreeSet<Mitigation> = symbol_names
+            .iter()
+            .filter_map(|name| Mitigation::from_symbol_name(name))
+            .collect();
+
+        let mut cfi = BTreeSet::new();
+        for note in elf
+            .iter_note_headers(binary)
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            if note.n_type == NT_GNU_PROPERTY_TYPE_0 && note.name == "GNU" {
+                cfi.append(&mut parse_gnu_properties(
+                    note.desc,
+                    elf.header.e_machine,
+                    elf.is_64,
+                    elf.little_endian,
+                ));
+            }
+        }
+        if CLANG_CFI_SYMBOLS
+            .iter()
+            .any(|name| symbol_names.contains(name))
+        {
+            cfi.insert(ControlFlowIntegrity::ClangCfi);
+        }
+
+        HardeningReport {
+            relro,
+            nx,
+            pie,
+            canary: mitigations.contains(&Mitigation::StackCanary),
+            fortify_source: mitigations.contains(&Mitigation::FortifySource),
+            cfi,
+        }
+    }
+
+    /// Compute the hardening report of a PE binary.
+    ///
+    /// Stack canaries are only recognized if the binary imports the cookie check function,
+    /// which is usually linked statically.
+    fn from_pe(pe: &goblin::pe::PE) -> HardeningReport {
+        let dll_characteristics = pe
+            .header
+            .optional_header
+            .map(|header| header.windows_fields.dll_characteristics)
+            .unwrap_or_default();
+        let mitigations: BTreeSet<Mitigation> = pe
+            .imports
+            .iter()
+            .filter_map(|import| Mitigation::from_symbol_name(&import.name))
+            .collect();
+        let mut cfi = BTreeSet::new();
+        if dll_characteristics & IMAGE_DLLCHARACTERISTICS_GUARD_CF != 0 {
+            cfi.insert(ControlFlowIntegrity::ControlFlowGuard);
+        }
+
+        HardeningReport {
+            relro: Relro::No,
+            nx: dll_characteristics & IMAGE_DLLCHARACTERISTICS_NX_COMPAT != 0,
+            pie: dll_characteristics & IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0,
+            canary: mitigations.contains(&Mitigation::StackCanary),
+            fortify_source: mitigations.contains(&Mitigation::FortifySource),
+            cfi,
+        }
+    }
+}
+
+impl std::fmt::Display for HardeningReport {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        writeln!(formatter, "RELRO:          {}", self.relro)?;
+        writeln!(formatter, "NX:             {}", yes_no(self.nx))?;
+        writeln!(formatter, "PIE:            {}", yes_no(self.pie))?;
+        writeln!(formatter, "Stack canary:   {}", yes_no(self.canary))?;
+        writeln!(formatter, "Fortify source: {}", yes_no(self.fortify_source))?;
+        if self.cfi.is_empty() {
+            writeln!(formatter, "CFI:            no")
+        } else {
+            let cfi: Vec<String> = self.cfi.iter().map(|cfi| cfi.to_string()).collect();
+            writeln!(formatter, "CFI:            {}", cfi.join(", "))
+        }
+    }
+}
+
+/// Get the control flow integrity features from the descriptor of a GNU property note.
+///
+/// The descriptor is an array of properties, each consisting of its type, the size of its data
+/// and the data itself, padded to the word size of the binary.
+fn parse_gnu_properties(
+    desc: &[u8],
+    machine: u16,
+    is_64: bool,
+    little_endian: bool,
+) -> BTreeSet<ControlFlowIntegrity> {
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = desc.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    let alignment = if is_64 { 8 } else { 4 };
+    let mut cfi = BTreeSet::new();
+    let mut offset = 0;
+    while let (Some(property_type), Some(data_size)) = (read_u32(offset), read_u32(offset + 4)) {
+        if data_size >= 4 {
+            let features = read_u32(offset + 8).unwrap_or(0);
+            match (machine, property_type) {
+                (EM_X86_64 | EM_386, GNU_PROPERTY_X86_FEATURE_1_AND) => {
+                    if features & 1 != 0 {
+                        cfi.insert(ControlFlowIntegrity::IndirectBranchTracking);
+                    }
+                    if features & 2 != 0 {
+                        cfi.insert(ControlFlowIntegrity::ShadowStack);
+                    }
+                }
+                (EM_AARCH64, GNU_PROPERTY_AARCH64_FEATURE_1_AND) => {
+                    if features & 1 != 0 {
+                        cfi.insert(ControlFlowIntegrity::BranchTargetIdentification);
+                    }
+                    if features & 2 != 0 {
+                        cfi.insert(ControlFlowIntegrity::PointerAuthentication);
+                    }
+                }
+                _ => (),
+            }
+        }
+        let data_size = (data_size as usize).next_multiple_of(alignment);
+        offset += 8 + data_size;
+    }
+    cfi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gnu_properties() {
+        let mut desc = Vec::new();
+        // A property unrelated to control flow integrity.
+        desc.extend_from_slice(&0xc000_8002u32.to_le_bytes());
+        desc.extend_from_slice(&4u32.to_le_bytes());
+        desc.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        desc.extend_from_slice(&GNU_PROPERTY_X86_FEATURE_1_AND.to_le_bytes());
+        desc.extend_from_slice(&4u32.to_le_bytes());
+        desc.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(
+            parse_gnu_properties(&desc, EM_X86_64, true, true),
+            BTreeSet::from([
+                ControlFlowIntegrity::IndirectBranchTracking,
+                ControlFlowIntegrity::ShadowStack
+            ])
+        );
+        assert!(parse_gnu_properties(&desc, EM_AARCH64, true, true).is_empty());
+        assert!(parse_gnu_properties(&desc[..10], EM_X86_64, true, true).is_empty());
+
+        let mut desc = Vec::new();
+        desc.extend_from_slice(&GNU_PROPERTY_AARCH64_FEATURE_1_AND.to_be_bytes());
+        desc.extend_from_slice(&4u32.to_be_bytes());
+        desc.extend_from_slice(&1u32.to_be_bytes());
+        assert_eq!(
+            parse_gnu_properties(&desc, EM_AARCH64, false, false),
+            BTreeSet::from([ControlFlowIntegrity::BranchTargetIdentification])
+        );
+    }
+
+    #[test]
+    fn display_report() {
+        let report = HardeningReport {
+            relro: Relro::Partial,
+            nx: true,
+            pie: false,
+            canary: true,
+            fortify_source: false,
+            cfi: BTreeSet::from([ControlFlowIntegrity::IndirectBranchTracking]),
+        };
+        assert_eq!(
+            report.to_string(),
+            "RELRO:          partial\n\
+             NX:             yes\n\
+             PIE:            no\n\
+             Stack canary:   yes\n\
+             Fortify source: no\n\
+             CFI:            IBT\n"
+        );
+    }
+
+    #[test]
+    fn unsupported_format() {
+        assert!(HardeningReport::parse(b"not a binary").is_err());
+    }
+}
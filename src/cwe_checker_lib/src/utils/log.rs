@@ -1,10 +1,16 @@
 //! Structs and functions for generating log messages and CWE warnings.
 
+use crate::analysis::explanation::Explanation;
+use crate::analysis::input_provenance::ReproductionHint;
 use crate::prelude::*;
+use crate::utils::debug_info::SourceLocation;
+use crate::utils::mitigations::Mitigation;
+use crate::utils::symbol_utils::demangle_symbol;
+use regex::Regex;
 
 use std::ops::{Deref, DerefMut};
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     thread::JoinHandle,
 };
 
@@ -35,6 +41,80 @@ pub struct CweWarning {
     /// warning, including the address in the binary for which the warning was
     /// generated.
     pub description: String,
+    /// Warnings of other checks that were generated at the same address.
+    ///
+    /// Only filled by [`group_cwe_warnings`]. The related warnings usually
+    /// describe the same root cause as this warning.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<CweWarning>,
+    /// The source code location of the first address of the warning.
+    ///
+    /// Only known if the binary contains debug information,
+    /// see [`DebugInfo`](crate::utils::debug_info::DebugInfo).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_location: Option<SourceLocation>,
+    /// Maps the mangled C++ or Rust symbol names of the warning to their demangled names.
+    ///
+    /// Only filled by [`CweWarning::demangle_symbols`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub demangled_symbols: BTreeMap<String, String>,
+    /// Exploit mitigations that protect the code location of the warning.
+    ///
+    /// Only filled by checks that can rule out parts of the impact of a bug,
+    /// see [`CweWarning::mitigations`].
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub mitigations: BTreeSet<Mitigation>,
+    /// The severity of the warning.
+    #[serde(default, skip_serializing_if = "Severity::is_normal")]
+    pub severity: Severity,
+    /// Descriptions of user input that may trigger the warning.
+    ///
+    /// Only filled by checks for injection bugs if reproduction hints are
+    /// enabled in their configuration, see [`CweWarning::reproduction_hints`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reproduction_hints: Vec<ReproductionHint>,
+    /// Set if a path to the warning was found by symbolic execution,
+    /// see [`confirm_cwe_warnings`](crate::analysis::symbolic_execution::confirm_cwe_warnings).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub confirmed: bool,
+    /// The abstract facts that triggered the warning.
+    ///
+    /// Only filled by checks that support explanations if explanations are requested,
+    /// see [`Explanation`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Explanation>,
+    /// The position-independent hash of the function containing the first address of the warning.
+    ///
+    /// Only filled if function hashes are requested,
+    /// see [`add_function_hashes`](crate::analysis::function_hashing::add_function_hashes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_hash: Option<String>,
+    /// The name of the statically linked third-party library containing the warning, e.g. `zlib 1.3.1`.
+    ///
+    /// Only filled for warnings in functions contained in a hash database,
+    /// see [`label_third_party_code`](crate::analysis::function_hashing::label_third_party_code).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub third_party_library: Option<String>,
+}
+
+/// The severity of a CWE warning.
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Default,
+)]
+pub enum Severity {
+    /// The bug is probably not exploitable, e.g. because it is detected by an
+    /// exploit mitigation at runtime.
+    Low,
+    /// The default severity of warnings.
+    #[default]
+    Normal,
+}
+
+impl Severity {
+    /// Returns true if this is the default severity.
+    pub fn is_normal(&self) -> bool {
+        *self == Severity::Normal
+    }
 }
 
 /// Methods to deduplicate CWE warnings.
@@ -94,6 +174,49 @@ pub trait DeduplicateCweWarnings:
 
 impl DeduplicateCweWarnings for Vec<CweWarning> {}
 
+/// Checks that only search for syntactic patterns, e.g. calls to certain
+/// functions.
+///
+/// Their warnings are only chosen as primary warnings of a group if no other
+/// check generated a warning at the same address.
+const SYNTACTIC_CHECKS: [&str; 2] = ["CWE676", "CWE782"];
+
+/// Group CWE warnings that were generated at the same address.
+///
+/// Different checks often report the same root cause at the same address,
+/// e.g. a call to `strcpy` may be reported both as a use of a dangerous
+/// function and as a buffer overflow. For each address one warning is chosen
+/// as the primary warning of the group. The other warnings of the group are
+/// moved into the `related` field of the primary warning.
+///
+/// The primary warning is the first warning in iteration order that was not
+/// generated by a purely syntactic check. The returned groups are sorted by
+/// their address. Only the first address of a warning is considered for the
+/// grouping.
+pub fn group_cwe_warnings(warnings: Vec<CweWarning>) -> Vec<CweWarning> {
+    let mut groups: BTreeMap<String, Vec<CweWarning>> = BTreeMap::new();
+    let mut ungrouped = Vec::new();
+    for warning in warnings {
+        match warning.addresses.first() {
+            Some(address) => groups.entry(address.clone()).or_default().push(warning),
+            None => ungrouped.push(warning),
+        }
+    }
+    groups
+        .into_values()
+        .map(|mut group| {
+            let primary_index = group
+                .iter()
+                .position(|w| !SYNTACTIC_CHECKS.contains(&w.name.as_str()))
+                .unwrap_or(0);
+            let mut primary = group.remove(primary_index);
+            primary.related.extend(group);
+            primary
+        })
+        .chain(ungrouped)
+        .collect()
+}
+
 impl CweWarning {
     /// Creates a new `CweWarning` by only setting name, version and
     /// description.
@@ -110,6 +233,16 @@ impl CweWarning {
             symbols: Vec::new(),
             other: Vec::new(),
             description: description.to_string(),
+            related: Vec::new(),
+            source_location: None,
+            demangled_symbols: BTreeMap::new(),
+            mitigations: BTreeSet::new(),
+            severity: Severity::Normal,
+            reproduction_hints: Vec::new(),
+            confirmed: false,
+            explanation: None,
+            function_hash: None,
+            third_party_library: None,
         }
     }
 
@@ -136,6 +269,77 @@ impl CweWarning {
         self.other = other;
         self
     }
+
+    /// Sets the mitigations field of the CweWarning.
+    ///
+    /// Warnings with at least one mitigation are downgraded to a low severity.
+    pub fn mitigations(mut self, mitigations: BTreeSet<Mitigation>) -> CweWarning {
+        if !mitigations.is_empty() {
+            self.severity = Severity::Low;
+        }
+        self.mitigations = mitigations;
+        self
+    }
+
+    /// Sets the reproduction hints field of the CweWarning.
+    pub fn reproduction_hints(mut self, hints: Vec<ReproductionHint>) -> CweWarning {
+        self.reproduction_hints = hints;
+        self
+    }
+
+    /// Sets the explanation field of the CweWarning.
+    pub fn explanation(mut self, explanation: Option<Explanation>) -> CweWarning {
+        self.explanation = explanation;
+        self
+    }
+
+    /// Get the first address of the warning.
+    ///
+    /// Returns `None` if the warning has no address.
+    pub fn get_address(&self) -> Option<u64> {
+        // Addresses of CWE warnings are formatted as decimal numbers.
+        self.addresses
+            .first()
+            .and_then(|address| address.parse::<u64>().ok())
+    }
+
+    /// Get the addresses of the blocks on the flow trace of the warning,
+    /// i.e. of the `trace=<address>` entries in its `other` field.
+    pub fn get_trace(&self) -> Vec<&str> {
+        self.other
+            .iter()
+            .flatten()
+            .filter_map(|entry| entry.strip_prefix("trace="))
+            .collect()
+    }
+
+    /// Demangle the C++ and Rust symbol names of the warning and its related warnings.
+    ///
+    /// Mangled names in the description are replaced by their demangled names.
+    /// The `symbols` field keeps the mangled names,
+    /// the `demangled_symbols` field maps them to their demangled names.
+    pub fn demangle_symbols(&mut self) {
+        let mangled_name_regex = Regex::new(r"\b_(Z|R)[A-Za-z0-9_$.]+").unwrap();
+        let names = self.symbols.iter().map(String::as_str).chain(
+            mangled_name_regex
+                .find_iter(&self.description)
+                .map(|name| name.as_str()),
+        );
+        for name in names {
+            if let Some(demangled) = demangle_symbol(name) {
+                self.demangled_symbols.insert(name.to_string(), demangled);
+            }
+        }
+        // Replace longer names first in case that one name is a prefix of another name.
+        let mut replacements: Vec<(&String, &String)> = self.demangled_symbols.iter().collect();
+        replacements.sort_by_key(|(mangled, _)| std::cmp::Reverse(mangled.len()));
+        for (mangled, demangled) in replacements {
+            self.description = self.description.replace(mangled.as_str(), demangled);
+        }
+        for related in self.related.iter_mut() {
+            related.demangle_symbols();
+        }
+    }
 }
 
 impl std::fmt::Display for CweWarning {
@@ -144,7 +348,30 @@ impl std::fmt::Display for CweWarning {
             formatter,
             "[{}] ({}) {}",
             self.name, self.version, self.description
-        )
+        )?;
+        if let Some(source_location) = &self.source_location {
+            write!(formatter, " ({source_location})")?;
+        }
+        if !self.mitigations.is_empty() {
+            let mitigations: Vec<String> = self.mitigations.iter().map(|m| m.to_string()).collect();
+            write!(formatter, " (mitigated by {})", mitigations.join(", "))?;
+        }
+        if self.confirmed {
+            write!(formatter, " (confirmed)")?;
+        }
+        if let Some(library) = &self.third_party_library {
+            write!(
+                formatter,
+                " (in statically linked third-party code: {library})"
+            )?;
+        }
+        if let Some(explanation) = &self.explanation {
+            write!(formatter, "{explanation}")?;
+        }
+        for related in self.related.iter() {
+            write!(formatter, "\n    related: {related}")?;
+        }
+        Ok(())
     }
 }
 
@@ -321,32 +548,62 @@ impl std::fmt::Display for LogMessage {
     }
 }
 
+/// The output formats for CWE warnings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum OutputFormat {
+    /// One line of human-readable text per warning.
+    #[default]
+    Text,
+    /// A JSON array of the warnings.
+    Json,
+    /// Comma-separated values with one row per warning.
+    ///
+    /// The column names are the field names of the JSON output.
+    Csv,
+    /// A JUnit XML test report with one failed test case per warning.
+    ///
+    /// Each check corresponds to a test suite.
+    Junit,
+}
+
+/// The columns of the CSV output.
+///
+/// List-valued fields are joined with semicolons.
+/// The `other` field is embedded as a JSON array.
+/// The source location is formatted as `file:line`.
+const CSV_COLUMNS: [&str; 8] = [
+    "name",
+    "version",
+    "addresses",
+    "tids",
+    "symbols",
+    "other",
+    "description",
+    "source_location",
+];
+
 /// Print all provided log- and CWE-messages.
 ///
 /// Log-messages will always be printed to `stdout`.
 /// CWE-warnings will either be printed to `stdout` or to the file path provided
-/// in `out_path`.
-///
-/// If `emit_json` is set, the CWE-warnings will be converted to json for the
-/// output.
+/// in `out_path`. They are converted to the given output `format`.
 pub fn print_all_messages(
     logs: Vec<&LogMessage>,
     cwes: Vec<&CweWarning>,
     out_path: Option<&str>,
-    emit_json: bool,
+    format: OutputFormat,
 ) {
+    print_logs_and_output(logs, &format_cwe_warnings(&cwes, format), out_path);
+}
+
+/// Print all provided log-messages and the already formatted output of the CWE warnings.
+///
+/// Log-messages will always be printed to `stdout`.
+/// The output will either be printed to `stdout` or to the file path provided in `out_path`.
+pub fn print_logs_and_output(logs: Vec<&LogMessage>, output: &str, out_path: Option<&str>) {
     for log in logs {
         println!("{log}");
     }
-    let output: String = if emit_json {
-        serde_json::to_string_pretty(&cwes).unwrap()
-    } else {
-        cwes.iter()
-            .map(|cwe| format!("{cwe}"))
-            .collect::<Vec<String>>()
-            .join("\n")
-            + "\n"
-    };
     if let Some(file_path) = out_path {
         std::fs::write(file_path, output)
             .unwrap_or_else(|error| panic!("Writing to output path {file_path} failed: {error}"));
@@ -355,6 +612,130 @@ pub fn print_all_messages(
     }
 }
 
+/// Convert the CWE warnings to the given output format.
+///
+/// Formats without support for nested warnings (CSV and JUnit XML) list
+/// related warnings like other warnings.
+pub fn format_cwe_warnings(cwes: &[&CweWarning], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => {
+            cwes.iter()
+                .map(|cwe| format!("{cwe}"))
+                .collect::<Vec<String>>()
+                .join("\n")
+                + "\n"
+        }
+        OutputFormat::Json => serde_json::to_string_pretty(&cwes).unwrap(),
+        OutputFormat::Csv => format_csv(&flatten_related_warnings(cwes)),
+        OutputFormat::Junit => format_junit(&flatten_related_warnings(cwes)),
+    }
+}
+
+/// Return the warnings together with their related warnings.
+fn flatten_related_warnings<'a>(cwes: &[&'a CweWarning]) -> Vec<&'a CweWarning> {
+    cwes.iter()
+        .flat_map(|cwe| std::iter::once(*cwe).chain(cwe.related.iter()))
+        .collect()
+}
+
+/// Format the warnings as comma-separated values with a header row.
+fn format_csv(cwes: &[&CweWarning]) -> String {
+    let mut output = CSV_COLUMNS.join(",") + "\n";
+    for cwe in cwes {
+        let fields = [
+            cwe.name.clone(),
+            cwe.version.clone(),
+            cwe.addresses.join(";"),
+            cwe.tids.join(";"),
+            cwe.symbols.join(";"),
+            serde_json::to_string(&cwe.other).unwrap(),
+            cwe.description.clone(),
+            cwe.source_location
+                .as_ref()
+                .map(|location| location.to_string())
+                .unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| escape_csv(field)).collect();
+        output += &fields.join(",");
+        output.push('\n');
+    }
+    output
+}
+
+/// Quote a CSV field if it contains special characters.
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Format the warnings as a JUnit XML test report.
+///
+/// Each check is a test suite and each warning is a failed test case
+/// named after the address of the warning.
+fn format_junit(cwes: &[&CweWarning]) -> String {
+    let mut suites: BTreeMap<&str, Vec<&CweWarning>> = BTreeMap::new();
+    for cwe in cwes {
+        suites.entry(cwe.name.as_str()).or_default().push(cwe);
+    }
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output += &format!(
+        "<testsuites name=\"cwe_checker\" tests=\"{0}\" failures=\"{0}\">\n",
+        cwes.len()
+    );
+    for (name, suite) in suites {
+        output += &format!(
+            "  <testsuite name=\"{0}\" tests=\"{1}\" failures=\"{1}\">\n",
+            escape_xml(name),
+            suite.len()
+        );
+        for cwe in suite {
+            let address = cwe.addresses.first().map(String::as_str).unwrap_or("");
+            output += &format!(
+                "    <testcase name=\"{}\" classname=\"{}\">\n",
+                escape_xml(address),
+                escape_xml(&cwe.name)
+            );
+            output += &format!(
+                "      <failure message=\"{}\" type=\"{}\">",
+                escape_xml(&cwe.description),
+                escape_xml(&cwe.name)
+            );
+            for (field, value) in [
+                ("version", cwe.version.clone()),
+                ("addresses", cwe.addresses.join(";")),
+                ("tids", cwe.tids.join(";")),
+                ("symbols", cwe.symbols.join(";")),
+                ("other", serde_json::to_string(&cwe.other).unwrap()),
+                (
+                    "source_location",
+                    cwe.source_location
+                        .as_ref()
+                        .map(|location| location.to_string())
+                        .unwrap_or_default(),
+                ),
+            ] {
+                output += &format!("\n{field}: {}", escape_xml(&value));
+            }
+            output += "</failure>\n    </testcase>\n";
+        }
+        output += "  </testsuite>\n";
+    }
+    output += "</testsuites>\n";
+    output
+}
+
+/// Escape the special characters of XML.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 /// For each analysis count the number of debug log messages in `all_logs`
 /// and add a (INFO level) log message with the resulting number to `all_logs`.
 /// Also count and log the number of general debug log messages.
@@ -396,7 +777,7 @@ pub enum LogThreadMsg {
     /// A normal log message.
     Log(LogMessage),
     /// A CWE warning
-    Cwe(CweWarning),
+    Cwe(Box<CweWarning>),
     /// If the log collector thread receives this signal, it should stop
     /// receiving new messages and instead terminate and return the collected
     /// messages prior to receiving the termination signal.
@@ -411,7 +792,7 @@ impl From<LogMessage> for LogThreadMsg {
 
 impl From<CweWarning> for LogThreadMsg {
     fn from(warning: CweWarning) -> Self {
-        Self::Cwe(warning)
+        Self::Cwe(Box::new(warning))
     }
 }
 
@@ -533,7 +914,7 @@ impl LogThread {
                 LogThreadMsg::Cwe(cwe_warning) => match &cwe_warning.addresses[..] {
                     [] => panic!("Unexpected CWE warning without origin address"),
                     [address, ..] => {
-                        collected_cwes.insert(address.clone(), cwe_warning);
+                        collected_cwes.insert(address.clone(), *cwe_warning);
                     }
                 },
                 LogThreadMsg::Terminate => break,
@@ -548,3 +929,121 @@ impl LogThread {
         (logs, cwes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_warning(name: &str, address: &str) -> CweWarning {
+        CweWarning::new(name, "0.1", format!("{name} @ {address}"))
+            .addresses(vec![address.to_string()])
+    }
+
+    #[test]
+    fn group_warnings() {
+        let warnings = vec![
+            mock_warning("CWE676", "00401000"),
+            mock_warning("CWE119", "00401000"),
+            mock_warning("CWE476", "00402000"),
+            mock_warning("CWE134", "00401000"),
+            mock_warning("CWE782", "00400000"),
+        ];
+        let groups = group_cwe_warnings(warnings);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].name, "CWE782");
+        assert!(groups[0].related.is_empty());
+        assert_eq!(groups[1].name, "CWE119");
+        let related: Vec<&str> = groups[1].related.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(related, vec!["CWE676", "CWE134"]);
+        assert_eq!(groups[2].name, "CWE476");
+        assert_eq!(
+            groups[1].to_string(),
+            "[CWE119] (0.1) CWE119 @ 00401000\n    related: [CWE676] (0.1) CWE676 @ 00401000\n    related: [CWE134] (0.1) CWE134 @ 00401000"
+        );
+    }
+
+    #[test]
+    fn demangle_symbols() {
+        let mut warning = CweWarning::new(
+            "CWE676",
+            "0.1",
+            "(Use of Potentially Dangerous Function) _ZN3foo3barEv (401000) -> strcpy",
+        )
+        .symbols(vec![
+            "_ZN3foo3barEv".to_string(),
+            "_ZN4core3fmt5write17h0123456789abcdefE".to_string(),
+            "strcpy".to_string(),
+        ]);
+        warning.demangle_symbols();
+        assert_eq!(
+            warning.description,
+            "(Use of Potentially Dangerous Function) foo::bar() (401000) -> strcpy"
+        );
+        assert_eq!(warning.symbols[0], "_ZN3foo3barEv");
+        assert_eq!(warning.demangled_symbols.len(), 2);
+        assert_eq!(
+            warning.demangled_symbols["_ZN4core3fmt5write17h0123456789abcdefE"],
+            "core::fmt::write"
+        );
+        let json = serde_json::to_value(&warning).unwrap();
+        assert_eq!(json["demangled_symbols"]["_ZN3foo3barEv"], "foo::bar()");
+        assert_eq!(
+            demangle_symbol("_ZN3fooERKSs").as_deref(),
+            Some("foo(std::string const&)")
+        );
+        assert_eq!(demangle_symbol("strcpy"), None);
+    }
+
+    #[test]
+    fn mitigated_warning() {
+        let warning = mock_warning("CWE787", "00401000").mitigations(BTreeSet::new());
+        assert_eq!(warning.severity, Severity::Normal);
+        let json = serde_json::to_value(&warning).unwrap();
+        assert!(json.get("mitigations").is_none());
+        assert!(json.get("severity").is_none());
+
+        let warning = mock_warning("CWE787", "00401000")
+            .mitigations(BTreeSet::from([Mitigation::StackCanary]));
+        assert_eq!(warning.severity, Severity::Low);
+        assert_eq!(
+            warning.to_string(),
+            "[CWE787] (0.1) CWE787 @ 00401000 (mitigated by stack canary)"
+        );
+        let json = serde_json::to_value(&warning).unwrap();
+        assert_eq!(json["mitigations"][0], "StackCanary");
+        assert_eq!(json["severity"], "Low");
+    }
+
+    #[test]
+    fn csv_output() {
+        let mut warning = mock_warning("CWE676", "00401000")
+            .symbols(vec!["strcpy".to_string(), "main".to_string()])
+            .other(vec![vec![
+                "dangerous_function".to_string(),
+                "strcpy".to_string(),
+            ]]);
+        warning.description = "Call to \"strcpy\", be careful".to_string();
+        let output = format_cwe_warnings(&[&warning], OutputFormat::Csv);
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            vec![
+                "name,version,addresses,tids,symbols,other,description,source_location",
+                r#"CWE676,0.1,00401000,,strcpy;main,"[[""dangerous_function"",""strcpy""]]","Call to ""strcpy"", be careful","#,
+            ]
+        );
+    }
+
+    #[test]
+    fn junit_output() {
+        let mut primary = mock_warning("CWE119", "00401000");
+        primary.related = vec![mock_warning("CWE676", "00401000")];
+        let other = mock_warning("CWE119", "00402000");
+        let output = format_cwe_warnings(&[&primary, &other], OutputFormat::Junit);
+        assert!(output.contains(r#"<testsuites name="cwe_checker" tests="3" failures="3">"#));
+        assert!(output.contains(r#"<testsuite name="CWE119" tests="2" failures="2">"#));
+        assert!(output.contains(r#"<testsuite name="CWE676" tests="1" failures="1">"#));
+        assert!(output.contains(r#"<testcase name="00402000" classname="CWE119">"#));
+        assert!(output.contains(r#"<failure message="CWE676 @ 00401000" type="CWE676">"#));
+        assert!(output.ends_with("</testsuites>\n"));
+    }
+}
@@ -0,0 +1,187 @@
+//! A [`tracing`] layer that records events as [`LogMessage`]s.
+//!
+//! The analyses and checkers are instrumented with `tracing` spans:
+//! - An `analysis` span for each analysis, e.g. the Pointer Inference.
+//! - A `checker` span for each executed check.
+//! - A `function` span on the `TRACE` level for each transition function evaluation
+//!   of the interprocedural fixpoint computations.
+//!
+//! The `analysis` and `checker` spans have a `name` field.
+//! The [`LogMessageLayer`] turns the events emitted inside of these spans into log messages
+//! whose source is the name of the innermost named span.
+//! When a named span on the `INFO` level or below is closed,
+//! a log message with the runtime of the span is recorded.
+//! Thus the events can be printed together with the other log messages of the cwe_checker.
+
+use super::log::{LogLevel, LogMessage};
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// A layer for `tracing` subscribers that records events as log messages.
+///
+/// Clones of the layer share the recorded log messages,
+/// so that they can be retrieved after the layer was added to a subscriber.
+#[derive(Clone, Default)]
+pub struct LogMessageLayer {
+    messages: Arc<Mutex<Vec<LogMessage>>>,
+}
+
+impl LogMessageLayer {
+    /// Create a new layer without recorded log messages.
+    pub fn new() -> LogMessageLayer {
+        LogMessageLayer::default()
+    }
+
+    /// Remove and return the recorded log messages.
+    pub fn take_messages(&self) -> Vec<LogMessage> {
+        std::mem::take(&mut *self.messages.lock().unwrap())
+    }
+
+    /// Record a log message.
+    fn push(&self, message: LogMessage) {
+        self.messages.lock().unwrap().push(message);
+    }
+}
+
+/// The data of a span that is stored in the extensions of the span.
+struct SpanData {
+    /// The value of the `name` field of the span.
+    name: Option<String>,
+    /// The time at which the span was created.
+    start: Instant,
+}
+
+/// Collects the fields of a span or event.
+#[derive(Default)]
+struct FieldVisitor {
+    /// The value of the `message` field.
+    message: String,
+    /// The value of the `name` field.
+    name: Option<String>,
+    /// All other fields in the form ` key=value`.
+    fields: String,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            "name" => self.name = Some(value.to_string()),
+            _ => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{value:?}"),
+            "name" => self.name = Some(format!("{value:?}")),
+            name => {
+                let _ = write!(self.fields, " {name}={value:?}");
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for LogMessageLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanData {
+                name: visitor.name,
+                start: Instant::now(),
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let level = match *event.metadata().level() {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN | Level::INFO => LogLevel::Info,
+            Level::DEBUG | Level::TRACE => LogLevel::Debug,
+        };
+        let mut message = LogMessage {
+            text: visitor.message + &visitor.fields,
+            level,
+            location: None,
+            source: None,
+        };
+        let source = ctx.event_scope(event).and_then(|scope| {
+            scope.into_iter().find_map(|span| {
+                span.extensions()
+                    .get::<SpanData>()
+                    .and_then(|data| data.name.clone())
+            })
+        });
+        if let Some(source) = source {
+            message = message.source(source);
+        }
+        self.push(message);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        if *span.metadata().level() > Level::INFO {
+            return;
+        }
+        let extensions = span.extensions();
+        if let Some(SpanData {
+            name: Some(name),
+            start,
+        }) = extensions.get::<SpanData>()
+        {
+            self.push(
+                LogMessage::new_info(format!(
+                    "Finished {} after {:.3}s",
+                    span.name(),
+                    start.elapsed().as_secs_f64()
+                ))
+                .source(name.clone()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn recording_events() {
+        let layer = LogMessageLayer::new();
+        let subscriber = tracing_subscriber::registry().with(layer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!("Outside of spans");
+            let _span = tracing::info_span!("analysis", name = "Pointer Inference").entered();
+            {
+                let _span = tracing::trace_span!("function", function = "main").entered();
+                tracing::error!(steps = 42, "Fixpoint did not stabilize");
+            }
+        });
+        let messages = layer.take_messages();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0], LogMessage::new_info("Outside of spans"));
+        assert_eq!(
+            messages[1],
+            LogMessage::new_error("Fixpoint did not stabilize steps=42")
+                .source("Pointer Inference")
+        );
+        assert!(messages[2].text.starts_with("Finished analysis after "));
+        assert_eq!(messages[2].source.as_deref(), Some("Pointer Inference"));
+        assert!(layer.take_messages().is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+//! Recognition of exploit mitigations inserted by the compiler.
+//!
+//! Compilers can protect functions against the exploitation of buffer
+//! overflows:
+//!
+//! - Functions protected by a stack canary check a random value stored between
+//!   the local variables and the return address before returning and call
+//!   `__stack_chk_fail` if it was overwritten.
+//! - Functions compiled with `_FORTIFY_SOURCE` call checked variants of libc
+//!   functions, e.g. `__memcpy_chk`, which abort the program if the size of the
+//!   destination buffer is exceeded.
+//!
+//! Checks can use the mitigations of a function to downgrade the
+//! [severity](crate::utils::log::Severity) of their warnings with
+//! [`CweWarning::mitigations`](crate::utils::log::CweWarning::mitigations).
+
+use crate::intermediate_representation::{Jmp, Project, Tid};
+use crate::prelude::*;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Extern functions that are called if a stack canary was overwritten.
+const STACK_CANARY_SYMBOLS: [&str; 3] = [
+    "__stack_chk_fail",
+    "__stack_chk_fail_local",
+    "__security_check_cookie",
+];
+
+/// An exploit mitigation that protects a function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum Mitigation {
+    /// The function is protected by a stack canary.
+    StackCanary,
+    /// The function calls checked variants of libc functions, i.e. it was
+    /// compiled with `_FORTIFY_SOURCE`.
+    FortifySource,
+}
+
+impl std::fmt::Display for Mitigation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mitigation::StackCanary => write!(formatter, "stack canary"),
+            Mitigation::FortifySource => write!(formatter, "fortify source"),
+        }
+    }
+}
+
+impl Mitigation {
+    /// Returns the mitigation indicated by a call to the extern function with
+    /// the given name.
+    pub fn from_symbol_name(name: &str) -> Option<Mitigation> {
+        if STACK_CANARY_SYMBOLS.contains(&name) {
+            Some(Mitigation::StackCanary)
+        } else if name.starts_with("__") && name.ends_with("_chk") {
+            Some(Mitigation::FortifySource)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the exploit mitigations of all functions of the program that are
+/// protected by at least one mitigation, indexed by the TID of the function.
+pub fn get_function_mitigations(project: &Project) -> HashMap<Tid, BTreeSet<Mitigation>> {
+    let mitigation_symbols: HashMap<&Tid, Mitigation> = project
+        .program
+        .term
+        .extern_symbols
+        .iter()
+        .filter_map(|(tid, symbol)| {
+            Some((
+                tid,
+                Mitigation::from_symbol_name(symbol.get_imported_name())?,
+            ))
+        })
+        .collect();
+    if mitigation_symbols.is_empty() {
+        return HashMap::new();
+    }
+
+    project
+        .program
+        .term
+        .subs
+        .iter()
+        .filter_map(|(sub_tid, sub)| {
+            let mitigations: BTreeSet<Mitigation> = sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|blk| blk.term.jmps.iter())
+                .filter_map(|jmp| match &jmp.term {
+                    Jmp::Call { target, .. } => mitigation_symbols.get(target).copied(),
+                    _ => None,
+                })
+                .collect();
+            (!mitigations.is_empty()).then(|| (sub_tid.clone(), mitigations))
+        })
+        .collect()
+}
+
+/// Returns the TIDs of all functions of the program that are protected by the
+/// given mitigation.
+pub fn get_functions_with_mitigation(project: &Project, mitigation: Mitigation) -> HashSet<Tid> {
+    get_function_mitigations(project)
+        .into_iter()
+        .filter_map(|(tid, mitigations)| mitigations.contains(&mitigation).then_some(tid))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::{Blk, ExternSymbol, Sub, Term};
+
+    #[test]
+    fn mitigations_from_symbol_names() {
+        assert_eq!(
+            Mitigation::from_symbol_name("__stack_chk_fail"),
+            Some(Mitigation::StackCanary)
+        );
+        assert_eq!(
+            Mitigation::from_symbol_name("__memcpy_chk"),
+            Some(Mitigation::FortifySource)
+        );
+        assert_eq!(Mitigation::from_symbol_name("memcpy"), None);
+    }
+
+    #[test]
+    fn function_mitigations() {
+        let mut project = Project::mock_x64();
+        let mut symbol = ExternSymbol::mock_x64("__stack_chk_fail");
+        symbol.tid = Tid::new("__stack_chk_fail");
+        project
+            .program
+            .term
+            .extern_symbols
+            .insert(symbol.tid.clone(), symbol);
+        let mut blk = Blk::mock();
+        blk.term.jmps.push(Term {
+            tid: Tid::new("call_stack_chk_fail"),
+            term: Jmp::Call {
+                target: Tid::new("__stack_chk_fail"),
+                return_: None,
+            },
+        });
+        let mut protected_sub = Sub::mock("protected");
+        protected_sub.term.blocks.push(blk);
+        project
+            .program
+            .term
+            .subs
+            .insert(protected_sub.tid.clone(), protected_sub);
+        project
+            .program
+            .term
+            .subs
+            .insert(Tid::new("unprotected"), Sub::mock("unprotected"));
+
+        let mitigations = get_function_mitigations(&project);
+        assert_eq!(
+            mitigations,
+            HashMap::from([(
+                Tid::new("protected"),
+                BTreeSet::from([Mitigation::StackCanary])
+            )])
+        );
+        assert!(get_functions_with_mitigation(&project, Mitigation::FortifySource).is_empty());
+    }
+}
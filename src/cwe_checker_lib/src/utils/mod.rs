@@ -1,19 +1,28 @@
 //! This module contains various utility modules and helper functions.
 
+pub mod allowlist;
+pub mod annotations;
 pub mod arguments;
 pub mod binary;
 pub mod debug;
+pub mod debug_info;
+pub mod exception_handling;
 pub mod ghidra;
+pub mod ghidra_markers;
+pub mod golang;
 pub mod graph_utils;
+pub mod hardening;
 pub mod log;
+pub mod log_layer;
+pub mod mitigations;
+pub mod state_diff;
 pub mod symbol_utils;
 
+use crate::error::CweCheckerError;
 use crate::prelude::*;
 
 use std::{env, fs, path};
 
-use anyhow::bail;
-
 const ENV_CWE_CHECKER_CONFIGS_PATH: &str = "CWE_CHECKER_CONFIGS_PATH";
 const ENV_CWE_CHECKER_GHIDRA_PLUGINS_PATH: &str = "CWE_CHECKER_GHIDRA_PLUGINS_PATH";
 
@@ -21,17 +30,23 @@ const ENV_CWE_CHECKER_GHIDRA_PLUGINS_PATH: &str = "CWE_CHECKER_GHIDRA_PLUGINS_PA
 ///
 /// We first search the file in our config directory. Then, we fall back to
 /// the CWE_CHECKER_CONFIG environment variable.
-pub fn read_config_file(filename: &str) -> Result<serde_json::Value, Error> {
+pub fn read_config_file(filename: &str) -> Result<serde_json::Value, CweCheckerError> {
     let config_path = if let Some(config_path) = get_config_path_from_project_dir(filename) {
         config_path
     } else if let Some(config_path) = get_path_from_env(ENV_CWE_CHECKER_CONFIGS_PATH, filename) {
         config_path
     } else {
-        bail!("Unable to find configuration file: {}.", filename)
+        return Err(CweCheckerError::Config(anyhow!(
+            "Unable to find configuration file: {}.",
+            filename
+        )));
     };
     let config_file = fs::read_to_string(config_path)
-        .context(format!("Could not read configuration file: {}", filename))?;
-    Ok(serde_json::from_str(&config_file)?)
+        .context(format!("Could not read configuration file: {}", filename))
+        .map_err(CweCheckerError::Config)?;
+    serde_json::from_str(&config_file)
+        .context(format!("Could not parse configuration file: {}", filename))
+        .map_err(CweCheckerError::Config)
 }
 
 fn get_config_path_from_project_dir(filename: &str) -> Option<path::PathBuf> {
@@ -50,7 +65,7 @@ fn get_config_path_from_project_dir(filename: &str) -> Option<path::PathBuf> {
 ///
 /// We first search the plugin in our data directory, then we fall back to
 /// the CWE_CHECKER_GHIDRA_PLUGIN_PATH environment variable.
-pub fn get_ghidra_plugin_path(plugin_name: &str) -> Result<path::PathBuf, Error> {
+pub fn get_ghidra_plugin_path(plugin_name: &str) -> Result<path::PathBuf, CweCheckerError> {
     if let Some(ghidra_plugin_path) = get_ghidra_plugin_path_from_project_dirs(plugin_name) {
         Ok(ghidra_plugin_path)
     } else if let Some(ghidra_plugin_path) =
@@ -58,7 +73,10 @@ pub fn get_ghidra_plugin_path(plugin_name: &str) -> Result<path::PathBuf, Error>
     {
         Ok(ghidra_plugin_path)
     } else {
-        bail!("Unable to find Ghidra plugin: {}", plugin_name)
+        Err(CweCheckerError::Config(anyhow!(
+            "Unable to find Ghidra plugin: {}",
+            plugin_name
+        )))
     }
 }
 
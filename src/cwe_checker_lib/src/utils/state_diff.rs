@@ -0,0 +1,121 @@
+//! Differences between abstract states of an analysis at two program points.
+//!
+//! The states are compared in their compact JSON representation,
+//! e.g. as returned by [`State::to_json_compact`](crate::analysis::pointer_inference::State::to_json_compact),
+//! so that the states of all analyses can be compared in the same way.
+//! Nested objects are compared entry by entry,
+//! so that a change is reported for each register, memory object or memory cell that changed.
+//! All other values are compared as a whole.
+
+use crate::prelude::*;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A change of an entry of a state between two program points.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StateChange {
+    /// The path of the entry in the state, e.g. `register/RAX`.
+    pub path: String,
+    /// The value of the entry at the first program point or `None` if the entry did not exist.
+    pub before: Option<Value>,
+    /// The value of the entry at the second program point or `None` if the entry does not exist anymore.
+    pub after: Option<Value>,
+}
+
+impl fmt::Display for StateChange {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.before, &self.after) {
+            (None, Some(after)) => write!(formatter, "+ {}: {after}", self.path),
+            (Some(before), None) => write!(formatter, "- {}: {before}", self.path),
+            (Some(before), Some(after)) => {
+                write!(formatter, "~ {}: {before} -> {after}", self.path)
+            }
+            (None, None) => write!(formatter, "  {}", self.path),
+        }
+    }
+}
+
+/// Compute the changes between two states given in their compact JSON representation.
+///
+/// The changes are sorted by their paths.
+pub fn diff_states(before: &Value, after: &Value) -> Vec<StateChange> {
+    let mut changes = Vec::new();
+    diff_values("", before, after, &mut changes);
+    changes
+}
+
+/// Add the changes between the two values at the given path to `changes`.
+fn diff_values(path: &str, before: &Value, after: &Value, changes: &mut Vec<StateChange>) {
+    let (Value::Object(before_map), Value::Object(after_map)) = (before, after) else {
+        if before != after {
+            changes.push(StateChange {
+                path: path.to_string(),
+                before: Some(before.clone()),
+                after: Some(after.clone()),
+            });
+        }
+        return;
+    };
+    let keys: BTreeSet<&String> = before_map.keys().chain(after_map.keys()).collect();
+    for key in keys {
+        let entry_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}/{key}")
+        };
+        match (before_map.get(key), after_map.get(key)) {
+            (Some(before), Some(after)) => diff_values(&entry_path, before, after, changes),
+            (before, after) => changes.push(StateChange {
+                path: entry_path,
+                before: before.cloned(),
+                after: after.cloned(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diffing_states() {
+        let before = json!({
+            "register": {"RAX": "Value: 0x1:i64", "RDI": {"Pointer": {"main @ RSP": "0x8:i64"}}},
+            "memory": {"main @ RSP": {"is_unique": "true", "memory": {"-8": "Top:i64"}}},
+        });
+        let after = json!({
+            "register": {"RAX": "Value: 0x2:i64", "RSI": "Top:i64"},
+            "memory": {"main @ RSP": {"is_unique": "true", "memory": {"-8": "Top:i64"}}},
+        });
+        let changes = diff_states(&before, &after);
+        assert_eq!(
+            changes,
+            vec![
+                StateChange {
+                    path: "register/RAX".to_string(),
+                    before: Some(json!("Value: 0x1:i64")),
+                    after: Some(json!("Value: 0x2:i64")),
+                },
+                StateChange {
+                    path: "register/RDI".to_string(),
+                    before: Some(json!({"Pointer": {"main @ RSP": "0x8:i64"}})),
+                    after: None,
+                },
+                StateChange {
+                    path: "register/RSI".to_string(),
+                    before: None,
+                    after: Some(json!("Top:i64")),
+                },
+            ]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "~ register/RAX: \"Value: 0x1:i64\" -> \"Value: 0x2:i64\""
+        );
+        assert_eq!(changes[2].to_string(), "+ register/RSI: \"Top:i64\"");
+        assert!(diff_states(&before, &before).is_empty());
+    }
+}
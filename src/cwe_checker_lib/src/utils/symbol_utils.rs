@@ -1,10 +1,143 @@
 //! Helper functions for common tasks utilizing extern symbols,
 //! e.g. searching for calls to a specific extern symbol.
+//!
+//! The names of extern symbols are normalized when the IR is generated,
+//! so that checks and function summaries can match symbols by their plain name:
+//!
+//! - Symbol versions are removed, e.g. `memcpy@GLIBC_2.14` is named `memcpy`.
+//! - The names of IFUNC resolvers and of the CPU-specific implementations they select
+//!   are replaced by the name of the implemented function,
+//!   e.g. `__memcpy_avx_unaligned_erms` and `memcpy_ifunc` are named `memcpy`.
+//! - Calls to the [aliases](SymbolAlias) of libc functions, e.g. `__printf_chk`,
+//!   are modeled as calls to the aliased function, e.g. `printf`.
+//!   The original name is kept in [`ExternSymbol::alias`].
+//!
+//! Several extern symbols may have the same name after the normalization,
+//! e.g. if a binary imports both `memcpy` and `__memcpy_chk`.
 
 use std::collections::{HashMap, HashSet};
 
 use crate::intermediate_representation::*;
 
+/// An alias of a libc function, e.g. a fortified variant of it or a variant for a specific standard.
+///
+/// Calls to the alias have the same semantics as calls to the aliased function,
+/// except for additional parameters, e.g. the size of the destination buffer for fortified variants.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SymbolAlias {
+    /// The name of the alias, e.g. `__printf_chk`.
+    pub alias: &'static str,
+    /// The name of the aliased function, e.g. `printf`.
+    pub name: &'static str,
+    /// The number of (non-variadic) parameters of the alias.
+    pub num_params: usize,
+    /// The indices of the parameters of the alias that the aliased function does not have,
+    /// e.g. the flag parameter of `__printf_chk`.
+    pub additional_params: &'static [usize],
+}
+
+/// Shorthand for the entries of [`SYMBOL_ALIASES`].
+const fn alias(
+    alias: &'static str,
+    name: &'static str,
+    num_params: usize,
+    additional_params: &'static [usize],
+) -> SymbolAlias {
+    SymbolAlias {
+        alias,
+        name,
+        num_params,
+        additional_params,
+    }
+}
+
+/// The known aliases of libc functions.
+const SYMBOL_ALIASES: [SymbolAlias; 38] = [
+    alias("__printf_chk", "printf", 2, &[0]),
+    alias("__fprintf_chk", "fprintf", 3, &[1]),
+    alias("__dprintf_chk", "dprintf", 3, &[1]),
+    alias("__sprintf_chk", "sprintf", 4, &[1, 2]),
+    alias("__snprintf_chk", "snprintf", 5, &[2, 3]),
+    alias("__vprintf_chk", "vprintf", 3, &[0]),
+    alias("__vfprintf_chk", "vfprintf", 4, &[1]),
+    alias("__vdprintf_chk", "vdprintf", 4, &[1]),
+    alias("__vsprintf_chk", "vsprintf", 5, &[1, 2]),
+    alias("__vsnprintf_chk", "vsnprintf", 6, &[2, 3]),
+    alias("__syslog_chk", "syslog", 3, &[1]),
+    alias("__memcpy_chk", "memcpy", 4, &[3]),
+    alias("__memmove_chk", "memmove", 4, &[3]),
+    alias("__mempcpy_chk", "mempcpy", 4, &[3]),
+    alias("__memset_chk", "memset", 4, &[3]),
+    alias("__strcpy_chk", "strcpy", 3, &[2]),
+    alias("__stpcpy_chk", "stpcpy", 3, &[2]),
+    alias("__strcat_chk", "strcat", 3, &[2]),
+    alias("__strncpy_chk", "strncpy", 4, &[3]),
+    alias("__stpncpy_chk", "stpncpy", 4, &[3]),
+    alias("__strncat_chk", "strncat", 4, &[3]),
+    alias("__read_chk", "read", 4, &[3]),
+    alias("__pread_chk", "pread", 5, &[4]),
+    alias("__recv_chk", "recv", 5, &[3]),
+    alias("__recvfrom_chk", "recvfrom", 7, &[3]),
+    alias("__fgets_chk", "fgets", 4, &[1]),
+    alias("__fread_chk", "fread", 5, &[1]),
+    alias("__gets_chk", "gets", 2, &[1]),
+    alias("__realpath_chk", "realpath", 3, &[2]),
+    alias("__getcwd_chk", "getcwd", 3, &[2]),
+    alias("__isoc99_scanf", "scanf", 1, &[]),
+    alias("__isoc99_sscanf", "sscanf", 2, &[]),
+    alias("__isoc99_fscanf", "fscanf", 2, &[]),
+    alias("__isoc23_scanf", "scanf", 1, &[]),
+    alias("__isoc23_sscanf", "sscanf", 2, &[]),
+    alias("__isoc23_fscanf", "fscanf", 2, &[]),
+    alias("__isoc23_strtol", "strtol", 3, &[]),
+    alias("__isoc23_strtoul", "strtoul", 3, &[]),
+];
+
+/// Name suffixes of the CPU-specific implementations of libc functions that IFUNC resolvers select.
+const IFUNC_IMPLEMENTATION_SUFFIXES: [&str; 9] = [
+    "_sse2", "_ssse3", "_sse4_1", "_sse4_2", "_avx", "_avx2", "_avx512", "_evex", "_erms",
+];
+
+/// Get the alias with the given name.
+pub fn get_symbol_alias(name: &str) -> Option<&'static SymbolAlias> {
+    SYMBOL_ALIASES.iter().find(|alias| alias.alias == name)
+}
+
+/// Remove the version from a versioned symbol name, e.g. `memcpy@GLIBC_2.14` or `memcpy@@GLIBC_2.14`.
+pub fn strip_symbol_version(name: &str) -> &str {
+    match name.find('@') {
+        Some(0) | None => name,
+        Some(index) => &name[..index],
+    }
+}
+
+/// Get the name of the implemented function for the name of an IFUNC resolver
+/// or of a CPU-specific implementation selected by it, e.g. `memcpy` for `memcpy_ifunc`
+/// or `__memmove_avx_unaligned_erms`.
+///
+/// Returns `None` if the name does not belong to an IFUNC resolver or implementation.
+pub fn strip_ifunc_suffix(name: &str) -> Option<&str> {
+    if let Some(function_name) = name.strip_suffix("_ifunc") {
+        return Some(function_name.trim_start_matches("__libc_"));
+    }
+    let function_name = name.strip_prefix("__")?;
+    let suffix_start = IFUNC_IMPLEMENTATION_SUFFIXES
+        .iter()
+        .filter_map(|suffix| function_name.find(suffix))
+        .min()?;
+    (suffix_start > 0).then(|| &function_name[..suffix_start])
+}
+
+/// Get the normalized name of an imported extern symbol,
+/// i.e. the name without symbol version and IFUNC suffixes.
+///
+/// Aliases are not replaced by the aliased function,
+/// since this requires adjusting the parameters of the symbol.
+pub fn normalize_symbol_name(name: &str) -> &str {
+    let name = strip_symbol_version(name);
+    strip_ifunc_suffix(name).unwrap_or(name)
+}
+
 /// Find the extern symbol object for a symbol name and return the symbol tid and name.
 pub fn find_symbol<'a>(prog: &'a Term<Program>, name: &str) -> Option<(&'a Tid, &'a str)> {
     let mut symbol: Option<(&'a Tid, &'a str)> = None;
@@ -20,6 +153,21 @@ pub fn find_symbol<'a>(prog: &'a Term<Program>, name: &str) -> Option<(&'a Tid,
     symbol
 }
 
+/// Demangle a C++ or Rust symbol name.
+///
+/// Returns `None` if the name is not a mangled name.
+/// Hashes of legacy Rust symbol names are omitted.
+pub fn demangle_symbol(name: &str) -> Option<String> {
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        return Some(format!("{demangled:#}"));
+    }
+    if !name.starts_with("_Z") {
+        return None;
+    }
+    let symbol = cpp_demangle::Symbol::new(name).ok()?;
+    symbol.demangle(&Default::default()).ok()
+}
+
 /// Match direct calls' target tids in the program's subroutines
 /// with the tids of the external symbols given to the function.
 /// When a match was found, add a triple of (caller name, callsite tid, callee name)
@@ -54,21 +202,15 @@ pub fn get_symbol_map<'a>(
 ) -> HashMap<Tid, &'a ExternSymbol> {
     let mut tid_map = HashMap::new();
     for symbol_name in symbols_to_find {
-        if let Some((tid, symbol)) =
-            project
-                .program
-                .term
-                .extern_symbols
-                .iter()
-                .find_map(|(_tid, symbol)| {
-                    if symbol.name == *symbol_name {
-                        Some((symbol.tid.clone(), symbol))
-                    } else {
-                        None
-                    }
-                })
+        // Several symbols may have the same name, e.g. `memcpy` and its alias `__memcpy_chk`.
+        for symbol in project
+            .program
+            .term
+            .extern_symbols
+            .values()
+            .filter(|symbol| symbol.name == *symbol_name)
         {
-            tid_map.insert(tid, symbol);
+            tid_map.insert(symbol.tid.clone(), symbol);
         }
     }
     tid_map
@@ -120,3 +262,33 @@ pub fn get_callsites<'a>(
     }
     callsites
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_symbol_names() {
+        assert_eq!(normalize_symbol_name("memcpy@GLIBC_2.14"), "memcpy");
+        assert_eq!(normalize_symbol_name("memcpy@@GLIBC_2.2.5"), "memcpy");
+        assert_eq!(
+            normalize_symbol_name("__memmove_avx_unaligned_erms"),
+            "memmove"
+        );
+        assert_eq!(normalize_symbol_name("__strlen_sse2"), "strlen");
+        assert_eq!(normalize_symbol_name("memcpy_ifunc"), "memcpy");
+        assert_eq!(normalize_symbol_name("__libc_strstr_ifunc"), "strstr");
+        assert_eq!(normalize_symbol_name("__printf_chk"), "__printf_chk");
+        assert_eq!(normalize_symbol_name("__sse2"), "__sse2");
+        assert_eq!(normalize_symbol_name("@plt"), "@plt");
+    }
+
+    #[test]
+    fn symbol_aliases() {
+        let alias = get_symbol_alias("__snprintf_chk").unwrap();
+        assert_eq!(alias.name, "snprintf");
+        assert_eq!(alias.additional_params, &[2, 3]);
+        assert_eq!(get_symbol_alias("__isoc99_sscanf").unwrap().name, "sscanf");
+        assert!(get_symbol_alias("printf").is_none());
+    }
+}
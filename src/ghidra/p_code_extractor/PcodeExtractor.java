@@ -3,6 +3,7 @@ import java.util.Arrays;
 import java.util.List;
 import java.util.HashMap;
 import java.util.LinkedList;
+import java.io.File;
 import java.io.FileWriter;
 import java.io.IOException;
 import ghidra.app.script.GhidraScript;
@@ -33,6 +34,32 @@ import com.google.gson.*;
 
 public class PcodeExtractor extends GhidraScript {
 
+	/**
+	 * Create functions at the entry points given as additional script arguments
+	 * (hexadecimal addresses) and mark them as entry points of the program.
+	 * This is needed for bare metal binaries, since they contain no header
+	 * from which Ghidra could learn where the code of the binary starts.
+	 */
+	private void addEntryPointsFromScriptArgs() {
+		String[] args = getScriptArgs();
+		if (args.length <= 1) {
+			return;
+		}
+		for (String entryPointString : Arrays.copyOfRange(args, 1, args.length)) {
+			Address entryPoint = toAddr(entryPointString);
+			if (entryPoint == null) {
+				println(String.format("Invalid entry point address: %s", entryPointString));
+				continue;
+			}
+			disassemble(entryPoint);
+			if (getFunctionAt(entryPoint) == null) {
+				createFunction(entryPoint, null);
+			}
+			addEntryPoint(entryPoint);
+		}
+		analyzeChanges(currentProgram);
+	}
+
 	/**
 	 * Main routine for extracting pcode, register properties, CPU architecture
 	 * details, stack pointer, datatype properties,
@@ -45,6 +72,7 @@ public class PcodeExtractor extends GhidraScript {
 	@Override
 	protected void run() throws Exception {
 		TaskMonitor monitor = getMonitor();
+		addEntryPointsFromScriptArgs();
 		ghidra.program.model.listing.Program ghidraProgram = currentProgram;
 		FunctionManager funcMan = ghidraProgram.getFunctionManager();
 		VarnodeContext context = new VarnodeContext(ghidraProgram, ghidraProgram.getProgramContext(),
@@ -158,11 +186,17 @@ public class PcodeExtractor extends GhidraScript {
 		Gson gson = new GsonBuilder().setPrettyPrinting().serializeNulls().create();
 		FileWriter writer = null;
 		String jsonPath = getScriptArgs()[0];
+		// If several programs are analyzed by this Ghidra instance,
+		// the output of each program is written to a file of its own in the given directory.
+		File outputDirectory = new File(jsonPath);
+		if (outputDirectory.isDirectory()) {
+			jsonPath = new File(outputDirectory, ghidraProgram.getName() + ".json").getPath();
+		}
 
 		try {
 			writer = new FileWriter(jsonPath);
 		} catch (IOException e) {
-			System.out.printf("%s is a directory, could not be created or can not be opened", getScriptArgs()[0]);
+			System.out.printf("%s is a directory, could not be created or can not be opened", jsonPath);
 			e.printStackTrace();
 			System.exit(1);
 		}
@@ -171,7 +205,7 @@ public class PcodeExtractor extends GhidraScript {
 			writer.flush();
 			writer.close();
 		} catch (Exception e) {
-			System.out.printf("Could not write to %s", getScriptArgs()[0]);
+			System.out.printf("Could not write to %s", jsonPath);
 			e.printStackTrace();
 			System.exit(1);
 		}
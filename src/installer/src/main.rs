@@ -45,6 +45,10 @@ fn copy_config_json(location: &Path) -> Result<()> {
         repo_dir.join("src/lkm_config.json"),
         location.join("lkm_config.json"),
     )?;
+    std::fs::copy(
+        repo_dir.join("src/function_hashes.json"),
+        location.join("function_hashes.json"),
+    )?;
     Ok(())
 }
 
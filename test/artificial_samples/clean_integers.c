@@ -0,0 +1,25 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+#define MAX_COUNT 1024
+
+// The size of the allocation cannot overflow, since the count is bounded.
+long* allocate_array(int count){
+  if (count <= 0 || count > MAX_COUNT){
+    return NULL;
+  }
+  return malloc(count * sizeof(long));
+}
+
+int main(int argc, char** argv) {
+  long* array = allocate_array(argc);
+  if (array == NULL){
+    return 1;
+  }
+  for (int i = 0; i < argc; i++){
+    array[i] = i;
+  }
+  printf("%s: %ld\n", argv[0], array[argc - 1]);
+  free(array);
+  return 0;
+}
@@ -0,0 +1,35 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+#define COUNT 16
+
+// All allocations are checked, accessed within their bounds and freed exactly once.
+int sum_of_squares(){
+  int* values = malloc(COUNT * sizeof(int));
+  if (values == NULL){
+    return -1;
+  }
+  for (int i = 0; i < COUNT; i++){
+    values[i] = i * i;
+  }
+  int* larger_values = realloc(values, 2 * COUNT * sizeof(int));
+  if (larger_values == NULL){
+    free(values);
+    return -1;
+  }
+  values = larger_values;
+  for (int i = COUNT; i < 2 * COUNT; i++){
+    values[i] = 0;
+  }
+  int sum = 0;
+  for (int i = 0; i < 2 * COUNT; i++){
+    sum += values[i];
+  }
+  free(values);
+  return sum;
+}
+
+int main() {
+  printf("%d\n", sum_of_squares());
+  return 0;
+}
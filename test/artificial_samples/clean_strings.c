@@ -0,0 +1,24 @@
+#include <stdio.h>
+
+#define SIZE 32
+
+// Copies at most size - 1 characters and always terminates the destination.
+void copy_string(char* destination, const char* source, int size){
+  int i = 0;
+  while (i < size - 1 && source[i] != '\0'){
+    destination[i] = source[i];
+    i++;
+  }
+  destination[i] = '\0';
+}
+
+int main() {
+  char input[SIZE];
+  char copy[SIZE];
+  if (fgets(input, sizeof(input), stdin) == NULL){
+    return 1;
+  }
+  copy_string(copy, input, sizeof(copy));
+  printf("%s", copy);
+  return 0;
+}
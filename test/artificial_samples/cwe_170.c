@@ -0,0 +1,33 @@
+#include <stdio.h>
+#include <string.h>
+
+void print_unterminated(char *input){
+    char buffer[16];
+    strncpy(buffer, input, sizeof(buffer));
+    printf("%s\n", buffer);
+}
+
+void print_terminated(char *input){
+    char buffer[16];
+    strncpy(buffer, input, sizeof(buffer));
+    buffer[sizeof(buffer) - 1] = '\0';
+    printf("%s\n", buffer);
+}
+
+size_t length_of_copy(char *input, size_t length){
+    char buffer[16];
+    if (length > sizeof(buffer)) {
+        length = sizeof(buffer);
+    }
+    memcpy(buffer, input, length);
+    return strlen(buffer);
+}
+
+int main(int argc, char *argv[]){
+    if (argc < 2) {
+        return 1;
+    }
+    print_unterminated(argv[1]);
+    print_terminated(argv[1]);
+    return (int) length_of_copy(argv[1], strlen(argv[1]));
+}
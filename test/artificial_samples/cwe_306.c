@@ -0,0 +1,32 @@
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/socket.h>
+
+int authenticate(int sock){
+    char password[64];
+    recv(sock, password, sizeof(password) - 1, 0);
+    return strcmp(password, "secret") == 0;
+}
+
+void serve(int sock){
+    char command[64];
+    while (recv(sock, command, sizeof(command), 0) > 0) {
+        switch (command[0]) {
+        case 'r':
+            system("reboot");
+            break;
+        case 'u':
+            if (authenticate(sock)) {
+                system("update");
+            }
+            break;
+        }
+    }
+}
+
+int main(int argc, char *argv[]){
+    int sock = socket(AF_INET, SOCK_STREAM, 0);
+    serve(sock);
+    return 0;
+}
@@ -0,0 +1,31 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <fcntl.h>
+
+void store_password(FILE *file){
+    char *password = getpass("Password: ");
+    fwrite(password, 1, strlen(password), file);
+}
+
+void store_copied_token(int fd){
+    char buffer[64];
+    char *token = getenv("API_TOKEN");
+    strcpy(buffer, token);
+    write(fd, buffer, strlen(buffer));
+}
+
+void store_home(FILE *file){
+    char *home = getenv("HOME");
+    fwrite(home, 1, strlen(home), file);
+}
+
+int main(int argc, char *argv[]){
+    FILE *file = fopen("credentials.txt", "w");
+    int fd = open("token.txt", O_WRONLY | O_CREAT, 0600);
+    store_password(file);
+    store_copied_token(fd);
+    store_home(file);
+    return 0;
+}
@@ -0,0 +1,29 @@
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/socket.h>
+
+void send_password(int sock){
+    char *password = getenv("APP_PASSWORD");
+    send(sock, password, strlen(password), 0);
+}
+
+void send_copied_password(int sock){
+    char buffer[64];
+    char *password = getpass("Password: ");
+    strcpy(buffer, password);
+    write(sock, buffer, strlen(buffer));
+}
+
+void send_home(int sock){
+    char *home = getenv("HOME");
+    send(sock, home, strlen(home), 0);
+}
+
+int main(int argc, char *argv[]){
+    int sock = socket(AF_INET, SOCK_STREAM, 0);
+    send_password(sock);
+    send_copied_password(sock);
+    send_home(sock);
+    return 0;
+}
@@ -0,0 +1,39 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <time.h>
+#include <fcntl.h>
+#include <sys/stat.h>
+
+void create_temp_file(){
+    char path[64];
+    sprintf(path, "/tmp/app_%d", rand());
+    open(path, O_WRONLY | O_CREAT, 0600);
+}
+
+void create_session_token(){
+    char token[32];
+    sprintf(token, "%lx", (long) time(NULL));
+    setenv("SESSION_TOKEN", token, 1);
+}
+
+void create_lock_dir(){
+    char path[64];
+    sprintf(path, "/tmp/lock_%d", rand());
+    mkdir(path, 0700);
+}
+
+void roll_dice(){
+    char result[32];
+    sprintf(result, "%d", rand() % 6 + 1);
+    setenv("LAST_DICE_ROLL", result, 1);
+    puts(result);
+}
+
+int main(int argc, char *argv[]){
+    srand(time(NULL));
+    create_temp_file();
+    create_session_token();
+    create_lock_dir();
+    roll_dice();
+    return 0;
+}
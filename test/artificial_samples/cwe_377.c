@@ -0,0 +1,39 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <fcntl.h>
+
+void use_tmpnam(){
+    char name[L_tmpnam];
+    tmpnam(name);
+    FILE *file = fopen(name, "w");
+    fclose(file);
+}
+
+void open_fixed_path(){
+    int fd = open("/tmp/cwe_377.lock", O_WRONLY | O_CREAT, 0600);
+    close(fd);
+}
+
+void open_concatenated_path(){
+    char path[64];
+    strcpy(path, "/tmp/");
+    strcat(path, "cwe_377.log");
+    FILE *file = fopen(path, "a");
+    fclose(file);
+}
+
+void use_mkstemp(){
+    char name[] = "/tmp/cwe_377_XXXXXX";
+    int fd = mkstemp(name);
+    close(fd);
+}
+
+int main(int argc, char *argv[]){
+    use_tmpnam();
+    open_fixed_path();
+    open_concatenated_path();
+    use_mkstemp();
+    return 0;
+}
@@ -0,0 +1,21 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define BUFSIZE 512
+
+int read_config(const char *path) {
+        char *buf = (char *) malloc(BUFSIZE);
+        FILE *file = fopen(path, "r");
+        if (file == NULL) {
+                return -1;
+        }
+        fread(buf, 1, BUFSIZE, file);
+        fclose(file);
+        free(buf);
+        return 0;
+}
+
+int main(int argc, char **argv) {
+        return read_config(argv[0]);
+}
@@ -0,0 +1,32 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+#define COUNT 10
+
+void fill_unscaled(int *array){
+    for (int i = 0; i < COUNT; i++) {
+        *(int *)((char *) array + i) = i;
+    }
+}
+
+void fill_scaled_twice(int *array){
+    for (int i = 0; i < COUNT / 4; i++) {
+        *(array + i * sizeof(int)) = i;
+    }
+}
+
+void fill_correctly(int *array){
+    for (int i = 0; i < COUNT; i++) {
+        array[i] = i;
+    }
+}
+
+int main(int argc, char *argv[]){
+    int *array = calloc(COUNT, sizeof(int));
+    fill_unscaled(array);
+    fill_scaled_twice(array);
+    fill_correctly(array);
+    printf("%d\n", array[argc]);
+    free(array);
+    return 0;
+}
@@ -0,0 +1,17 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define BUFSIZE 512
+
+char global_buf[BUFSIZE];
+
+int main(int argc, char **argv) {
+        char stack_buf[BUFSIZE];
+        char *heap_buf;
+        memset(stack_buf, 0x42, BUFSIZE);
+        heap_buf = (char *) malloc(BUFSIZE);
+        free(stack_buf);
+        free(global_buf);
+        free(heap_buf + 8);
+}
@@ -0,0 +1,43 @@
+#include <fcntl.h>
+#include <stdio.h>
+#include <unistd.h>
+
+void use_after_close(char *path){
+    char buf[64];
+    int fd = open(path, O_RDONLY);
+    if (fd < 0) {
+        return;
+    }
+    close(fd);
+    read(fd, buf, sizeof(buf));
+}
+
+void double_close(char *path){
+    int fd = open(path, O_WRONLY);
+    if (fd < 0) {
+        return;
+    }
+    write(fd, "data", 4);
+    close(fd);
+    close(fd);
+}
+
+void correct_usage(char *path){
+    char buf[64];
+    int fd = open(path, O_RDONLY);
+    if (fd < 0) {
+        return;
+    }
+    read(fd, buf, sizeof(buf));
+    close(fd);
+}
+
+int main(int argc, char *argv[]){
+    if (argc < 2) {
+        return 1;
+    }
+    use_after_close(argv[1]);
+    double_close(argv[1]);
+    correct_usage(argv[1]);
+    return 0;
+}
@@ -0,0 +1,67 @@
+#include <grp.h>
+#include <stdlib.h>
+#include <sys/types.h>
+#include <unistd.h>
+
+void drop_wrong_order(uid_t uid, gid_t gid){
+    if (setgroups(0, NULL) != 0) {
+        exit(1);
+    }
+    if (setuid(uid) != 0) {
+        exit(1);
+    }
+    if (setgid(gid) != 0) {
+        exit(1);
+    }
+}
+
+void drop_missing_groups(uid_t uid, gid_t gid){
+    if (setgid(gid) != 0) {
+        exit(1);
+    }
+    if (setuid(uid) != 0) {
+        exit(1);
+    }
+}
+
+void drop_unchecked(uid_t uid, gid_t gid){
+    if (setgroups(0, NULL) != 0) {
+        exit(1);
+    }
+    if (setgid(gid) != 0) {
+        exit(1);
+    }
+    setuid(uid);
+}
+
+void drop_correct(uid_t uid, gid_t gid){
+    if (setgroups(0, NULL) != 0) {
+        exit(1);
+    }
+    if (setgid(gid) != 0) {
+        exit(1);
+    }
+    if (setuid(uid) != 0) {
+        exit(1);
+    }
+}
+
+int main(int argc, char *argv[]){
+    if (argc < 2) {
+        return 1;
+    }
+    switch (atoi(argv[1])) {
+    case 0:
+        drop_wrong_order(1000, 1000);
+        break;
+    case 1:
+        drop_missing_groups(1000, 1000);
+        break;
+    case 2:
+        drop_unchecked(1000, 1000);
+        break;
+    default:
+        drop_correct(1000, 1000);
+    }
+    return 0;
+}
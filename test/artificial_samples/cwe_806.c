@@ -0,0 +1,17 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int main(int argc, char **argv) {
+        char *src = (char *) malloc(64);
+        char *dst = (char *) malloc(16);
+        char *name = (char *) malloc(16);
+        fgets(src, 64, stdin);
+        snprintf(dst, 64, "%s", src);
+        strncpy(name, argv[0], 16);
+        puts(dst);
+        puts(name);
+        free(src);
+        free(dst);
+        free(name);
+}
@@ -0,0 +1,40 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define MAX_LEN 64
+
+char buffer[MAX_LEN];
+int table[16];
+
+void copy_input(char *source, char *length){
+    int len = atoi(length);
+    if (len < MAX_LEN) {
+        memcpy(buffer, source, len);
+    }
+}
+
+int lookup(char *index){
+    int i = atoi(index);
+    if (i < 16) {
+        return table[i];
+    }
+    return 0;
+}
+
+int lookup_checked(char *index){
+    int i = atoi(index);
+    if (i >= 0 && i < 16) {
+        return table[i];
+    }
+    return 0;
+}
+
+int main(int argc, char *argv[]){
+    if (argc < 3) {
+        return 1;
+    }
+    copy_input(argv[1], argv[2]);
+    printf("%d %d\n", lookup(argv[2]), lookup_checked(argv[2]));
+    return 0;
+}
@@ -36,6 +36,9 @@ sudo apt install -y clang clang-10
 sudo ln -s /usr/include/asm-generic /usr/include/asm
 sudo ln -s /usr/riscv64-linux-gnu/include/gnu/stubs-lp64d.h /usr/riscv64-linux-gnu/include/gnu/stubs-lp64.h
 
+echo "Installing tools for building the libraries of the hash database"
+sudo apt install -y wget make perl
+
 echo "Cleaning package index."
 sudo apt-get clean && sudo rm -rf /var/cache/apt/archives /var/lib/apt/lists/*
 
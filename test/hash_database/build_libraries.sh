@@ -0,0 +1,105 @@
+#!/bin/bash
+# Build statically linked binaries containing the functions of libc, zlib and libcrypto (OpenSSL)
+# for all architectures of the acceptance test samples whose cross compiler is installed.
+# zlib and OpenSSL are built from source if the toolchain does not provide the static libraries.
+set -e
+
+ZLIB_VERSION=${ZLIB_VERSION:-1.3.1}
+OPENSSL_VERSION=${OPENSSL_VERSION:-3.0.15}
+BUILD_DIR=$(pwd)/build
+
+declare -A compilers=(
+    [x64]="gcc" [x86]="gcc -m32" [arm]="arm-linux-gnueabi-gcc" [aarch64]="aarch64-linux-gnu-gcc"
+    [mips]="mips-linux-gnu-gcc" [mipsel]="mipsel-linux-gnu-gcc"
+    [mips64]="mips64-linux-gnuabi64-gcc" [mips64el]="mips64el-linux-gnuabi64-gcc"
+    [ppc]="powerpc-linux-gnu-gcc" [ppc64]="powerpc64-linux-gnu-gcc" [ppc64le]="powerpc64le-linux-gnu-gcc"
+    [riscv64]="riscv64-linux-gnu-gcc"
+)
+declare -A openssl_targets=(
+    [x64]=linux-x86_64 [x86]=linux-x86 [arm]=linux-armv4 [aarch64]=linux-aarch64
+    [mips]=linux-mips32 [mipsel]=linux-mips32 [mips64]=linux64-mips64 [mips64el]=linux64-mips64
+    [ppc]=linux-ppc [ppc64]=linux-ppc64 [ppc64le]=linux-ppc64le [riscv64]=linux64-riscv64
+)
+
+# Print the path of a static library of the toolchain, or nothing if the toolchain does not provide it.
+find_library() {
+    local path
+    path=$($CC -print-file-name="$1")
+    if [ -f "$path" ]; then
+        echo "$path"
+    fi
+}
+
+# Print the value of a preprocessor macro defined in the given header.
+macro_value() {
+    printf '#include <%s>\n%s\n' "$1" "$2" | $CC $INCLUDES -E -P - | tail -n 1 | tr -d ' "'
+}
+
+# Download and unpack a source archive once.
+fetch() {
+    if [ ! -d "$BUILD_DIR/src/$2" ]; then
+        mkdir -p "$BUILD_DIR/src"
+        wget -q -O - "$1" | tar -xz -C "$BUILD_DIR/src"
+    fi
+}
+
+build_zlib() {
+    fetch "https://zlib.net/fossils/zlib-$ZLIB_VERSION.tar.gz" "zlib-$ZLIB_VERSION"
+    rm -rf "$PREFIX/zlib-build" && cp -r "$BUILD_DIR/src/zlib-$ZLIB_VERSION" "$PREFIX/zlib-build"
+    (cd "$PREFIX/zlib-build" && CC="$CC" ./configure --static --prefix="$PREFIX" && make install) > /dev/null
+}
+
+build_openssl() {
+    fetch "https://www.openssl.org/source/openssl-$OPENSSL_VERSION.tar.gz" "openssl-$OPENSSL_VERSION"
+    rm -rf "$PREFIX/openssl-build" && cp -r "$BUILD_DIR/src/openssl-$OPENSSL_VERSION" "$PREFIX/openssl-build"
+    (cd "$PREFIX/openssl-build" \
+        && CC="$CC" ./Configure "${openssl_targets[$ARCH]}" no-shared no-asm no-tests --prefix="$PREFIX" --libdir=lib \
+        && make -j"$(nproc)" && make install_sw) > /dev/null
+}
+
+# Link all global functions of the static library into a binary
+# and write the name and version of the library next to it.
+link_library() {
+    local name=$1 archive=$2 output="$BUILD_DIR/$3_$ARCH.out"
+    shift 3
+    local symbols
+    symbols=$($NM --defined-only -g "$archive" 2> /dev/null \
+        | awk 'NF == 3 && ($2 == "T" || $2 == "W") { print "-Wl,-u," $3 }' | sort -u)
+    # Linking all functions of libc triggers a linker warning for each dangerous or obsolete function.
+    # shellcheck disable=SC2086
+    $CC -static -o "$output" stub.c $symbols "$archive" "$@" 2> >(grep -v -e "warning" -e "in function" >&2)
+    echo "$name" > "${output%.out}.name"
+    echo "Built $output ($name)"
+}
+
+for ARCH in x64 x86 arm aarch64 mips mipsel mips64 mips64el ppc ppc64 ppc64le riscv64; do
+    CC=${compilers[$ARCH]}
+    if ! $CC -static -o /dev/null stub.c 2> /dev/null; then
+        echo "No working static toolchain $CC for $ARCH found, skipping."
+        continue
+    fi
+    NM=nm
+    if command -v "${CC%-gcc}-nm" > /dev/null; then
+        NM="${CC%-gcc}-nm"
+    fi
+    PREFIX="$BUILD_DIR/$ARCH"
+    INCLUDES="-I$PREFIX/include"
+    mkdir -p "$PREFIX"
+
+    link_library "glibc $(macro_value features.h '__GLIBC__.__GLIBC_MINOR__')" "$(find_library libc.a)" libc
+
+    zlib=$(find_library libz.a)
+    if [ -z "$zlib" ]; then
+        build_zlib
+        zlib="$PREFIX/lib/libz.a"
+    fi
+    link_library "zlib $(macro_value zlib.h ZLIB_VERSION)" "$zlib" zlib
+
+    crypto=$(find_library libcrypto.a)
+    if [ -z "$crypto" ]; then
+        build_openssl
+        crypto="$PREFIX/lib/libcrypto.a"
+    fi
+    link_library "OpenSSL $(macro_value openssl/opensslv.h OPENSSL_VERSION_STR)" \
+        "$crypto" libcrypto "$zlib" -lpthread -ldl
+done
@@ -0,0 +1,13 @@
+#!/bin/bash
+# Add the function hashes of the binaries built by build_libraries.sh to the hash database bundled with the cwe_checker.
+# The cwe_checker has to be reinstalled afterwards to install the updated database.
+set -e
+
+CWE_CHECKER=${CWE_CHECKER:-cwe_checker}
+DATABASE=${DATABASE:-../../src/function_hashes.json}
+
+for binary in build/*.out; do
+    name=$(cat "${binary%.out}.name")
+    echo "Hashing $name in $binary"
+    $CWE_CHECKER hash-library "$binary" --name "$name" --database "$DATABASE"
+done
@@ -0,0 +1,4 @@
+// The library functions are linked into the binary by undefined symbol references on the command line.
+int main(void) {
+    return 0;
+}
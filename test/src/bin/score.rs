@@ -0,0 +1,30 @@
+//! Score the checks of the cwe_checker against the annotated ground truth of the test binaries.
+//!
+//! Prints precision and recall figures for each check.
+//! With `--json` the score table is printed as JSON, e.g. for comparisons between runs.
+
+use acceptance_tests_ghidra::scoring::{score_all_binaries, GroundTruth};
+
+fn main() {
+    let json = std::env::args().any(|arg| arg == "--json");
+    let ground_truth = match GroundTruth::read() {
+        Ok(ground_truth) => ground_truth,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    };
+    let (table, error_log) = score_all_binaries(&ground_truth);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&table).unwrap());
+    } else {
+        println!("{table}");
+    }
+    // Errors are printed to stderr, so that they do not mix with JSON output.
+    for (filepath, error) in error_log.iter() {
+        eprintln!("+++ Error for {filepath} +++\n{error}");
+    }
+    if !error_log.is_empty() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,146 @@
+//! Acceptance test of the hash database of library functions bundled with the cwe_checker.
+//!
+//! The binaries in `hash_database/build` link all functions of a library build
+//! and are generated by `hash_database/build_libraries.sh`.
+//! Hashing such a binary again must yield hashes contained in the bundled database,
+//! so that warnings in statically linked copies of the library are labelled as third-party code.
+
+use super::*;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// The libraries of which the bundled database has to contain at least one build.
+pub const HASHED_LIBRARIES: &[&str] = &["glibc", "zlib", "OpenSSL"];
+
+/// The parts of a hash database relevant for the test.
+#[derive(Deserialize)]
+struct HashDatabase {
+    libraries: Vec<LibraryHashes>,
+}
+
+/// The hashes of the functions of a library build.
+#[derive(Deserialize)]
+struct LibraryHashes {
+    name: String,
+    cpu_architecture: String,
+    function_hashes: BTreeSet<String>,
+}
+
+impl HashDatabase {
+    fn read(path: &Path) -> Result<HashDatabase, String> {
+        let file = std::fs::read_to_string(path)
+            .map_err(|err| format!("Reading the hash database {} failed: {err}", path.display()))?;
+        serde_json::from_str(&file).map_err(|err| {
+            format!(
+                "Parsing of the hash database {} failed: {err}",
+                path.display()
+            )
+        })
+    }
+}
+
+/// Hash the given binary with the `hash-library` subcommand into a new database at `database`.
+fn hash_library(binary: &Path, name: &str, database: &Path) -> Result<HashDatabase, String> {
+    let _ = std::fs::remove_file(database);
+    let output = if cfg!(feature = "docker") {
+        let build_dir = binary.parent().unwrap();
+        Command::new("docker")
+            .args(["run", "--rm", "-v"])
+            .arg(format!("{}:/a/build", build_dir.display()))
+            .args(["cwe_checker", "hash-library", "--name", name, "--database"])
+            .arg(format!(
+                "/a/build/{}",
+                database.file_name().unwrap().to_string_lossy()
+            ))
+            .arg(format!(
+                "/a/build/{}",
+                binary.file_name().unwrap().to_string_lossy()
+            ))
+            .output()
+    } else {
+        Command::new("cwe_checker")
+            .arg("hash-library")
+            .arg(binary)
+            .args(["--name", name, "--database"])
+            .arg(database)
+            .output()
+    }
+    .map_err(|err| format!("Running the cwe_checker failed: {err}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    HashDatabase::read(database)
+}
+
+/// Check that the bundled hash database contains builds of all [`HASHED_LIBRARIES`]
+/// and the function hashes of all library binaries in `hash_database/build`.
+///
+/// Returns the errors for each failed library binary.
+pub fn check_bundled_hash_database() -> Result<(), Vec<(String, String)>> {
+    let bundled_path = Path::new(&helpers::cwd()).join("../src/function_hashes.json");
+    let bundled = HashDatabase::read(&bundled_path)
+        .map_err(|error| vec![(bundled_path.display().to_string(), error)])?;
+    let mut error_log = Vec::new();
+    for library in HASHED_LIBRARIES {
+        if !bundled.libraries.iter().any(|hashes| {
+            hashes.name.starts_with(&format!("{library} ")) && !hashes.function_hashes.is_empty()
+        }) {
+            error_log.push((
+                bundled_path.display().to_string(),
+                format!("No hashes of {library} in the bundled database"),
+            ));
+        }
+    }
+
+    let build_dir = Path::new(&helpers::cwd()).join("hash_database/build");
+    let mut binaries: Vec<PathBuf> = std::fs::read_dir(&build_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "out"))
+        .collect();
+    binaries.sort();
+    for binary in binaries {
+        let filepath = binary.display().to_string();
+        let name = match std::fs::read_to_string(binary.with_extension("name")) {
+            Ok(name) => name.trim().to_string(),
+            Err(err) => {
+                error_log.push((filepath, format!("Reading the library name failed: {err}")));
+                continue;
+            }
+        };
+        let hashed = match hash_library(&binary, &name, &binary.with_extension("hashes.json")) {
+            Ok(hashed) => hashed,
+            Err(error) => {
+                println!("{} \t {}", filepath, "[FAILED]".red());
+                error_log.push((filepath, error));
+                continue;
+            }
+        };
+        let matching = hashed.libraries.iter().all(|hashes| {
+            bundled.libraries.iter().any(|bundled_hashes| {
+                bundled_hashes.name == hashes.name
+                    && bundled_hashes.cpu_architecture == hashes.cpu_architecture
+                    && !bundled_hashes
+                        .function_hashes
+                        .is_disjoint(&hashes.function_hashes)
+            })
+        });
+        if matching && !hashed.libraries.is_empty() {
+            println!("{} \t {}", filepath, "[OK]".green());
+        } else {
+            println!("{} \t {}", filepath, "[FAILED]".red());
+            error_log.push((
+                filepath,
+                format!("No function of {name} matches a function in the bundled database"),
+            ));
+        }
+    }
+
+    if error_log.is_empty() {
+        Ok(())
+    } else {
+        Err(error_log)
+    }
+}
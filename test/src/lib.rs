@@ -1,13 +1,19 @@
 //! This crate contains acceptance tests for the cwe_checker.
 
 use colored::*;
-use std::process::Command;
+use std::process::{Command, Output};
+
+pub mod hash_database;
+pub mod scoring;
+pub mod snapshots;
 
 /// CPU architectures contained in the test samples.
 pub const ARCHITECTURES: &[&str] = &[
     "aarch64", "arm", "mips64", "mips64el", "mips", "mipsel", "ppc64", "ppc64le", "ppc", "x64",
     "x86", "riscv64",
 ];
+/// MIPS variants contained in the test samples (big- and little-endian).
+pub const MIPS_ARCHITECTURES: &[&str] = &["mips64", "mips64el", "mips", "mipsel"];
 /// Compilers contained in the test samples
 pub const COMPILERS: &[&str] = &["gcc", "clang"];
 /// CPU architectures for the Windows-based test samples
@@ -70,19 +76,10 @@ impl CweTestCase {
         }
     }
 
-    /// Run the test case and print to the shell, whether the test case succeeded or not.
-    /// Returns stdout + stderr of the test execution on failure.
-    pub fn run_test(
-        &self,
-        search_string: &str,
-        num_expected_occurences: usize,
-    ) -> Result<(), String> {
+    /// Run the cwe_checker with the given arguments on the test binary and return its output.
+    fn execute_cwe_checker(&self, arguments: &[&str]) -> Output {
         let filepath = self.get_filepath();
-        if self.skipped {
-            println!("{} \t {}", filepath, "[SKIPPED]".yellow());
-            return Ok(());
-        }
-        let output = if cfg!(feature = "docker") {
+        if cfg!(feature = "docker") {
             const DOCKER_MEMORY_GIB: u64 = 8;
             const DOCKER_CPUS: u64 = 1;
 
@@ -97,9 +94,7 @@ impl CweTestCase {
             cmd.arg(format!("{}:/a/target", filepath));
 
             cmd.arg("cwe_checker");
-            cmd.arg("--partial");
-            cmd.arg(self.check_name);
-            cmd.arg("--quiet");
+            cmd.args(arguments);
             // Placing target at `/` makes Ghidra crash on PE files...
             cmd.arg("/a/target");
 
@@ -107,12 +102,25 @@ impl CweTestCase {
         } else {
             Command::new("cwe_checker")
                 .arg(&filepath)
-                .arg("--partial")
-                .arg(self.check_name)
-                .arg("--quiet")
+                .args(arguments)
                 .output()
                 .unwrap()
-        };
+        }
+    }
+
+    /// Run the test case and print to the shell, whether the test case succeeded or not.
+    /// Returns stdout + stderr of the test execution on failure.
+    pub fn run_test(
+        &self,
+        search_string: &str,
+        num_expected_occurences: usize,
+    ) -> Result<(), String> {
+        let filepath = self.get_filepath();
+        if self.skipped {
+            println!("{} \t {}", filepath, "[SKIPPED]".yellow());
+            return Ok(());
+        }
+        let output = self.execute_cwe_checker(&["--partial", self.check_name, "--quiet"]);
         if output.status.success() {
             let num_cwes = String::from_utf8(output.stdout)
                 .unwrap()
@@ -187,6 +195,11 @@ pub fn linux_test_cases(cwe: &'static str, check_name: &'static str) -> Vec<CweT
         .collect()
 }
 
+/// Return a list with all possible MIPS test cases for the given CWE.
+pub fn mips_test_cases(cwe: &'static str, check_name: &'static str) -> Vec<CweTestCase> {
+    new_test_cases(cwe, MIPS_ARCHITECTURES, COMPILERS, check_name, false)
+}
+
 /// Return a list with all possible Windows test cases for the given CWE
 pub fn windows_test_cases(cwe: &'static str, check_name: &'static str) -> Vec<CweTestCase> {
     new_test_cases(
@@ -376,6 +389,34 @@ mod tests {
         }
     }
 
+    /// Compare the warnings of all checks on the test binaries with the checked-in snapshots.
+    #[test]
+    #[ignore]
+    fn snapshots() {
+        let mut error_log = Vec::new();
+
+        for test_case in snapshots::snapshot_test_cases() {
+            if let Err(error) = test_case.run_snapshot_test() {
+                error_log.push((test_case.get_filepath(), error));
+            }
+        }
+
+        if !error_log.is_empty() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
+    /// Check that the bundled hash database matches the library binaries in `hash_database/build`.
+    #[test]
+    #[ignore]
+    fn bundled_hash_database() {
+        if let Err(error_log) = hash_database::check_bundled_hash_database() {
+            print_errors(error_log);
+            panic!();
+        }
+    }
+
     #[test]
     #[ignore]
     fn cwe_78() {
@@ -397,6 +438,20 @@ mod tests {
         run_tests!(tests, 1, "[CWE78]");
     }
 
+    /// String constants of MIPS binaries are accessed through the global offset
+    /// table relative to the global pointer register `gp`.
+    #[test]
+    #[ignore]
+    fn mips_string_constants() {
+        let tests = mips_test_cases("cwe_134", "CWE134");
+
+        run_tests!(tests, 1, "[CWE134]");
+
+        let tests = mips_test_cases("cwe_78", "CWE78");
+
+        run_tests!(tests, 1, "[CWE78]");
+    }
+
     #[test]
     #[ignore]
     fn cwe_119() {
@@ -445,6 +500,14 @@ mod tests {
         run_tests!(tests, 1, "[CWE134]");
     }
 
+    #[test]
+    #[ignore]
+    fn cwe_170() {
+        let tests = all_test_cases("cwe_170", "CWE170");
+
+        run_tests!(tests, 2, "[CWE170]");
+    }
+
     #[test]
     #[ignore]
     fn cwe_190() {
@@ -514,6 +577,54 @@ mod tests {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn cwe_306() {
+        let mut tests = linux_test_cases("cwe_306", "CWE306");
+
+        // Functions called via unrecognized thunk.
+        mark_architecture_skipped(&mut tests, "ppc64");
+        mark_skipped(&mut tests, "ppc64le", "gcc");
+
+        run_tests!(tests, 1, "[CWE306]");
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_311() {
+        let mut tests = linux_test_cases("cwe_311", "CWE311");
+
+        // Functions called via unrecognized thunk.
+        mark_architecture_skipped(&mut tests, "ppc64");
+        mark_skipped(&mut tests, "ppc64le", "gcc");
+
+        run_tests!(tests, 2, "[CWE311]");
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_319() {
+        let mut tests = linux_test_cases("cwe_319", "CWE319");
+
+        // Functions called via unrecognized thunk.
+        mark_architecture_skipped(&mut tests, "ppc64");
+        mark_skipped(&mut tests, "ppc64le", "gcc");
+
+        run_tests!(tests, 2, "[CWE319]");
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_330() {
+        let mut tests = linux_test_cases("cwe_330", "CWE330");
+
+        // Functions called via unrecognized thunk.
+        mark_architecture_skipped(&mut tests, "ppc64");
+        mark_skipped(&mut tests, "ppc64le", "gcc");
+
+        run_tests!(tests, 3, "[CWE330]");
+    }
+
     #[test]
     #[ignore]
     fn cwe_332() {
@@ -553,6 +664,28 @@ mod tests {
         run_tests!(tests, 1, "[CWE367]");
     }
 
+    #[test]
+    #[ignore]
+    fn cwe_377() {
+        let mut tests = linux_test_cases("cwe_377", "CWE377");
+
+        // Functions called via unrecognized thunk.
+        mark_architecture_skipped(&mut tests, "ppc64");
+        mark_skipped(&mut tests, "ppc64le", "gcc");
+
+        run_tests!(tests, 3, "[CWE377]");
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_401() {
+        let mut tests = all_test_cases("cwe_401", "CWE401");
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc");
+
+        run_tests!(tests, 1, "[CWE401]");
+    }
+
     #[test]
     #[ignore]
     fn cwe_415() {
@@ -630,6 +763,16 @@ mod tests {
         run_tests!(tests, 2, "[CWE467]");
     }
 
+    #[test]
+    #[ignore]
+    fn cwe_468() {
+        let mut tests = all_test_cases("cwe_468", "CWE468");
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc");
+
+        run_tests!(tests, 2, "[CWE468]");
+    }
+
     #[test]
     #[ignore]
     fn cwe_476() {
@@ -662,6 +805,28 @@ mod tests {
         run_tests!(tests, 1, "[CWE560]");
     }
 
+    #[test]
+    #[ignore]
+    fn cwe_590() {
+        let mut tests = all_test_cases("cwe_590", "CWE590");
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc");
+
+        run_tests!(tests, 3, "[CWE590]");
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_672() {
+        let mut tests = linux_test_cases("cwe_672", "CWE672");
+
+        // Functions called via unrecognized thunk.
+        mark_architecture_skipped(&mut tests, "ppc64");
+        mark_skipped(&mut tests, "ppc64le", "gcc");
+
+        run_tests!(tests, 1, "[CWE672]");
+    }
+
     #[test]
     #[ignore]
     fn cwe_676() {
@@ -673,6 +838,18 @@ mod tests {
         run_tests!(tests, 1, "[CWE676]", ("aarch64", "clang", 2));
     }
 
+    #[test]
+    #[ignore]
+    fn cwe_696() {
+        let mut tests = linux_test_cases("cwe_696", "CWE696");
+
+        // Functions called via unrecognized thunk.
+        mark_architecture_skipped(&mut tests, "ppc64");
+        mark_skipped(&mut tests, "ppc64le", "gcc");
+
+        run_tests!(tests, 3, "[CWE696]");
+    }
+
     #[test]
     #[ignore]
     fn cwe_782() {
@@ -719,4 +896,36 @@ mod tests {
 
         run_tests!(tests, 2, "[CWE789]");
     }
+
+    #[test]
+    #[ignore]
+    fn cwe_806() {
+        let mut tests = all_test_cases("cwe_806", "CWE806");
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc");
+
+        run_tests!(tests, 2, "[CWE806]");
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_839() {
+        let mut tests = all_test_cases("cwe_839", "CWE839");
+
+        mark_compiler_skipped(&mut tests, "mingw32-gcc");
+
+        run_tests!(tests, 2, "[CWE839]");
+    }
+
+    #[test]
+    #[ignore]
+    fn cwe_1341() {
+        let mut tests = linux_test_cases("cwe_672", "CWE672");
+
+        // Functions called via unrecognized thunk.
+        mark_architecture_skipped(&mut tests, "ppc64");
+        mark_skipped(&mut tests, "ppc64le", "gcc");
+
+        run_tests!(tests, 1, "[CWE1341]");
+    }
 }
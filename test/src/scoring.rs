@@ -0,0 +1,368 @@
+//! Scoring of the checks of the cwe_checker against annotated ground truth.
+//!
+//! The ground truth in `artificial_samples/ground_truth.json` annotates test files
+//! with the true warnings of some checks and the source lines at which they are reported.
+//! Test files marked as `clean` (the *known clean* corpus) contain no weaknesses at all,
+//! so that every warning of any check on them is a false positive.
+//! Warnings of checks that are not annotated for a test file are not scored.
+//!
+//! For each test binary and annotated check, the warnings are matched against the expected findings:
+//! A warning with a known source line is only matched to a finding annotated with this line
+//! or to a finding without line annotations.
+//! Warnings without a source line, e.g. on binaries without DWARF debug information,
+//! are matched to any finding.
+//! Matched warnings are true positives, unmatched warnings are false positives
+//! and unmatched findings are false negatives.
+//! The scores of all binaries are summed up per check and yield precision and recall figures,
+//! so that the effects of precision-affecting changes of the analyses can be quantified.
+
+use super::*;
+use crate::snapshots::{Snapshot, SnapshotWarning};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// The annotated ground truth for the test files.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct GroundTruth {
+    /// Checks that are never scored, e.g. because they report properties of the build process
+    /// that all test binaries share.
+    #[serde(default)]
+    pub ignored_checks: BTreeSet<String>,
+    /// The annotations of the test files, indexed by the name of the test file.
+    pub samples: BTreeMap<String, SampleAnnotation>,
+}
+
+/// The ground truth for a test file.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct SampleAnnotation {
+    /// If set, the test file contains no weaknesses detected by any check.
+    #[serde(default)]
+    pub clean: bool,
+    /// The true warnings of each annotated check.
+    #[serde(default)]
+    pub expected: BTreeMap<String, Vec<ExpectedFinding>>,
+}
+
+/// A true warning of a check on a test file.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ExpectedFinding {
+    /// The source lines at which the warning may be reported.
+    /// If empty, the warning may be reported at any line.
+    #[serde(default)]
+    pub lines: Vec<u64>,
+}
+
+impl ExpectedFinding {
+    /// Returns whether a warning reported at the given source line matches the finding.
+    fn matches(&self, line: Option<u64>) -> bool {
+        match line {
+            Some(line) => self.lines.is_empty() || self.lines.contains(&line),
+            None => true,
+        }
+    }
+}
+
+/// The numbers of true positive, false positive and false negative warnings of a check.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Score {
+    /// The number of true positives.
+    pub true_positives: usize,
+    /// The number of false positives.
+    pub false_positives: usize,
+    /// The number of false negatives.
+    pub false_negatives: usize,
+}
+
+impl Score {
+    /// Score the warnings of a check against the expected findings.
+    ///
+    /// Warnings with a source line are matched first and prefer findings annotated with their line,
+    /// so that neither warnings without a source line nor findings without line annotations
+    /// take away a matching finding from a warning with a known line.
+    pub fn from_matches(warnings: &[&SnapshotWarning], findings: &[ExpectedFinding]) -> Score {
+        let mut matched = vec![false; findings.len()];
+        let mut true_positives = 0;
+        let mut sorted_warnings = warnings.to_vec();
+        sorted_warnings.sort_by_key(|warning| warning.line.is_none());
+        for warning in sorted_warnings {
+            let candidates = || {
+                findings
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, finding)| !matched[*index] && finding.matches(warning.line))
+            };
+            let best_candidate = candidates()
+                .find(|(_, finding)| !finding.lines.is_empty())
+                .or_else(|| candidates().next())
+                .map(|(index, _)| index);
+            if let Some(index) = best_candidate {
+                matched[index] = true;
+                true_positives += 1;
+            }
+        }
+        Score {
+            true_positives,
+            false_positives: warnings.len() - true_positives,
+            false_negatives: findings.len() - true_positives,
+        }
+    }
+
+    /// Score the number of found warnings against the expected number of warnings.
+    pub fn new(found: usize, expected: usize) -> Score {
+        let true_positives = found.min(expected);
+        Score {
+            true_positives,
+            false_positives: found - true_positives,
+            false_negatives: expected - true_positives,
+        }
+    }
+
+    /// The fraction of warnings that are true positives.
+    /// Returns `None` if there are no warnings.
+    pub fn precision(&self) -> Option<f64> {
+        let warnings = self.true_positives + self.false_positives;
+        (warnings > 0).then(|| self.true_positives as f64 / warnings as f64)
+    }
+
+    /// The fraction of true warnings that were found.
+    /// Returns `None` if there are no true warnings.
+    pub fn recall(&self) -> Option<f64> {
+        let true_warnings = self.true_positives + self.false_negatives;
+        (true_warnings > 0).then(|| self.true_positives as f64 / true_warnings as f64)
+    }
+}
+
+impl std::ops::AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        self.true_positives += other.true_positives;
+        self.false_positives += other.false_positives;
+        self.false_negatives += other.false_negatives;
+    }
+}
+
+/// The scores of all checks over the scored test binaries.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ScoreTable {
+    /// The number of scored test binaries.
+    pub binaries: usize,
+    /// The summed up scores of each check.
+    pub scores: BTreeMap<String, Score>,
+}
+
+impl GroundTruth {
+    /// Read the ground truth from `artificial_samples/ground_truth.json`.
+    pub fn read() -> Result<GroundTruth, String> {
+        let path = format!("{}/artificial_samples/ground_truth.json", helpers::cwd());
+        let file = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Reading the ground truth {path} failed: {err}"))?;
+        serde_json::from_str(&file)
+            .map_err(|err| format!("Parsing of the ground truth {path} failed: {err}"))
+    }
+
+    /// Score the warnings generated for a binary of the given test file
+    /// and add the scores to the score table.
+    pub fn add_scores(&self, sample: &str, warnings: &Snapshot, table: &mut ScoreTable) {
+        let Some(annotation) = self.samples.get(sample) else {
+            return;
+        };
+        let mut found: BTreeMap<&str, Vec<&SnapshotWarning>> = BTreeMap::new();
+        for warning in warnings.warnings.iter() {
+            found.entry(&warning.name).or_default().push(warning);
+        }
+        let mut scored_checks: BTreeSet<&str> =
+            annotation.expected.keys().map(String::as_str).collect();
+        if annotation.clean {
+            scored_checks.extend(found.keys());
+        }
+        for check in scored_checks {
+            if self.ignored_checks.contains(check) {
+                continue;
+            }
+            let score = Score::from_matches(
+                found.get(check).map(Vec::as_slice).unwrap_or_default(),
+                annotation
+                    .expected
+                    .get(check)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+            );
+            *table.scores.entry(check.to_string()).or_default() += score;
+        }
+        table.binaries += 1;
+    }
+}
+
+impl std::fmt::Display for ScoreTable {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let percentage = |fraction: Option<f64>| match fraction {
+            Some(fraction) => format!("{:.1}%", fraction * 100.0),
+            None => "-".to_string(),
+        };
+        writeln!(
+            formatter,
+            "{:<10} {:>6} {:>6} {:>6} {:>10} {:>10}",
+            "Check", "TP", "FP", "FN", "Precision", "Recall"
+        )?;
+        let mut total = Score::default();
+        for (check, score) in self.scores.iter() {
+            total += *score;
+            writeln!(
+                formatter,
+                "{:<10} {:>6} {:>6} {:>6} {:>10} {:>10}",
+                check,
+                score.true_positives,
+                score.false_positives,
+                score.false_negatives,
+                percentage(score.precision()),
+                percentage(score.recall())
+            )?;
+        }
+        writeln!(
+            formatter,
+            "{:<10} {:>6} {:>6} {:>6} {:>10} {:>10}",
+            "Total",
+            total.true_positives,
+            total.false_positives,
+            total.false_negatives,
+            percentage(total.precision()),
+            percentage(total.recall())
+        )?;
+        write!(formatter, "Scored binaries: {}", self.binaries)
+    }
+}
+
+/// Run all checks on the binaries of all annotated test files and score the warnings.
+///
+/// Binaries that were not built are not scored.
+/// Returns the score table and the errors of failed cwe_checker runs.
+pub fn score_all_binaries(ground_truth: &GroundTruth) -> (ScoreTable, Vec<(String, String)>) {
+    let mut table = ScoreTable::default();
+    let mut error_log = Vec::new();
+    for sample in ground_truth.samples.keys() {
+        // The test cases need static strings.
+        let sample: &'static str = Box::leak(sample.clone().into_boxed_str());
+        let mut test_cases = linux_test_cases(sample, "");
+        test_cases.append(&mut windows_test_cases(sample, ""));
+        for test_case in test_cases {
+            if !Path::new(&test_case.get_filepath()).exists() {
+                continue;
+            }
+            match test_case.run_all_checks() {
+                Ok(warnings) => ground_truth.add_scores(sample, &warnings, &mut table),
+                Err(error) => error_log.push((test_case.get_filepath(), error)),
+            }
+        }
+    }
+    (table, error_log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(name: &str, address: &str, line: Option<u64>) -> SnapshotWarning {
+        SnapshotWarning {
+            name: name.to_string(),
+            address: Some(address.to_string()),
+            symbols: Vec::new(),
+            line,
+        }
+    }
+
+    fn finding(lines: &[u64]) -> ExpectedFinding {
+        ExpectedFinding {
+            lines: lines.to_vec(),
+        }
+    }
+
+    #[test]
+    fn scoring() {
+        let ground_truth: GroundTruth = serde_json::from_str(
+            r#"{
+                "ignored_checks": ["CWE215"],
+                "samples": {
+                    "cwe_476": {"expected": {"CWE476": [{"lines": [13]}, {}]}},
+                    "clean": {"clean": true}
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut table = ScoreTable::default();
+        let warnings = Snapshot {
+            warnings: [
+                warning("CWE476", "0x10", Some(13)),
+                warning("CWE215", "0x20", None),
+                warning("CWE676", "0x30", Some(8)),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        ground_truth.add_scores("cwe_476", &warnings, &mut table);
+        // Warnings of checks without annotations are not scored.
+        assert_eq!(table.scores.len(), 1);
+        assert_eq!(table.scores["CWE476"], Score::new(1, 2));
+        // All warnings on clean binaries are false positives.
+        ground_truth.add_scores("clean", &warnings, &mut table);
+        assert_eq!(table.binaries, 2);
+        assert_eq!(
+            table.scores["CWE476"],
+            Score {
+                true_positives: 1,
+                false_positives: 1,
+                false_negatives: 1,
+            }
+        );
+        assert_eq!(table.scores["CWE676"], Score::new(1, 0));
+        assert!(!table.scores.contains_key("CWE215"));
+
+        let score = table.scores["CWE476"];
+        assert_eq!(score.precision(), Some(0.5));
+        assert_eq!(score.recall(), Some(0.5));
+        assert_eq!(Score::default().precision(), None);
+    }
+
+    #[test]
+    fn matching_of_source_lines() {
+        let at_line = warning("CWE476", "0x10", Some(13));
+        let at_other_line = warning("CWE476", "0x20", Some(20));
+        let without_line = warning("CWE476", "0x30", None);
+
+        // Warnings at other lines than the annotated ones are false positives.
+        assert_eq!(
+            Score::from_matches(&[&at_other_line], &[finding(&[13])]),
+            Score {
+                true_positives: 0,
+                false_positives: 1,
+                false_negatives: 1,
+            }
+        );
+        assert_eq!(
+            Score::from_matches(&[&at_line], &[finding(&[12, 13])]),
+            Score::new(1, 1)
+        );
+        // Findings without line annotations match warnings at any line.
+        assert_eq!(
+            Score::from_matches(&[&at_other_line], &[finding(&[])]),
+            Score::new(1, 1)
+        );
+        // Warnings without a source line match any finding,
+        // but do not take away the finding at the line of another warning.
+        assert_eq!(
+            Score::from_matches(
+                &[&without_line, &at_line],
+                &[finding(&[13]), finding(&[20])]
+            ),
+            Score::new(2, 2)
+        );
+        // Warnings at an annotated line prefer the finding with this line.
+        assert_eq!(
+            Score::from_matches(&[&at_line, &at_other_line], &[finding(&[]), finding(&[13])]),
+            Score::new(2, 2)
+        );
+        assert_eq!(
+            Score::from_matches(&[&at_line, &at_line], &[finding(&[13])]),
+            Score::new(2, 1)
+        );
+    }
+}
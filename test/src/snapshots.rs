@@ -0,0 +1,306 @@
+//! Snapshot tests that run all checks of the cwe_checker on the test binaries
+//! and compare the generated warnings with expected warnings checked in to the `snapshots` directory.
+//!
+//! In contrast to the per-CWE acceptance tests, which only count the warnings of one check,
+//! the snapshot tests catch changes of any warning of any check,
+//! e.g. a warning that silently moves to another address or disappears.
+//!
+//! If the environment variable `UPDATE_SNAPSHOTS` is set, the snapshots are overwritten
+//! with the generated warnings instead.
+//! The changes of the snapshots should then be reviewed before committing them.
+//! Otherwise the generated warnings of binaries without snapshot or with changed warnings
+//! are written next to the snapshot with the suffix `.new`,
+//! so that the snapshots can also be created from the artifacts of a CI run.
+
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// The names of the test files whose binaries are part of the snapshot test corpus.
+pub const SNAPSHOT_CORPUS: &[&str] = &[
+    "clean_integers",
+    "clean_memory",
+    "clean_strings",
+    "cwe_243_clean",
+    "cwe_78",
+    "cwe_119",
+    "cwe_134",
+    "cwe_170",
+    "cwe_190",
+    "cwe_243",
+    "cwe_252",
+    "cwe_306",
+    "cwe_311",
+    "cwe_319",
+    "cwe_330",
+    "cwe_332",
+    "cwe_337",
+    "cwe_367",
+    "cwe_377",
+    "cwe_401",
+    "cwe_415",
+    "cwe_416",
+    "cwe_426",
+    "cwe_467",
+    "cwe_468",
+    "cwe_476",
+    "cwe_560",
+    "cwe_590",
+    "cwe_672",
+    "cwe_676",
+    "cwe_696",
+    "cwe_782",
+    "cwe_789",
+    "cwe_806",
+    "cwe_839",
+];
+
+/// The environment variable that enables the update mode of the snapshot tests.
+const UPDATE_SNAPSHOTS_VARIABLE: &str = "UPDATE_SNAPSHOTS";
+
+/// A warning in a snapshot.
+///
+/// Only the parts of a warning that identify it are compared,
+/// so that e.g. changes of the wording of warning descriptions do not invalidate snapshots.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct SnapshotWarning {
+    /// The name of the check that generated the warning, e.g. `CWE476`.
+    pub name: String,
+    /// The address where the warning was generated.
+    pub address: Option<String>,
+    /// The symbols associated with the warning.
+    pub symbols: Vec<String>,
+    /// The line in the source file corresponding to the address,
+    /// if the binary contains debug information.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+}
+
+/// The warnings of all checks for one test binary.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct Snapshot {
+    /// The generated warnings.
+    pub warnings: BTreeSet<SnapshotWarning>,
+}
+
+/// The parts of a warning in the JSON output of the cwe_checker that are part of snapshots.
+#[derive(Deserialize)]
+struct ReportedWarning {
+    name: String,
+    addresses: Vec<String>,
+    symbols: Vec<String>,
+    #[serde(default)]
+    source_location: Option<ReportedSourceLocation>,
+}
+
+/// The parts of the source code location of a warning in the JSON output of the cwe_checker
+/// that are part of snapshots.
+#[derive(Deserialize)]
+struct ReportedSourceLocation {
+    line: u64,
+}
+
+impl Snapshot {
+    /// Parse the JSON output of the cwe_checker.
+    pub fn from_json_output(output: &str) -> Result<Snapshot, String> {
+        let reported_warnings: Vec<ReportedWarning> = serde_json::from_str(output)
+            .map_err(|err| format!("Parsing of the cwe_checker output failed: {err}"))?;
+        let warnings = reported_warnings
+            .into_iter()
+            .map(|warning| SnapshotWarning {
+                name: warning.name,
+                address: warning.addresses.into_iter().next(),
+                symbols: warning.symbols,
+                line: warning.source_location.map(|location| location.line),
+            })
+            .collect();
+        Ok(Snapshot { warnings })
+    }
+
+    /// Compare the generated warnings with the expected warnings of the snapshot.
+    /// Returns a description of the differences if they are not equal.
+    pub fn compare(&self, expected: &Snapshot) -> Result<(), String> {
+        let missing: Vec<_> = expected.warnings.difference(&self.warnings).collect();
+        let unexpected: Vec<_> = self.warnings.difference(&expected.warnings).collect();
+        if missing.is_empty() && unexpected.is_empty() {
+            return Ok(());
+        }
+        let mut message = String::new();
+        for (title, warnings) in [
+            ("Missing warnings", missing),
+            ("Unexpected warnings", unexpected),
+        ] {
+            if !warnings.is_empty() {
+                message += &format!("{title}:\n");
+                for warning in warnings {
+                    message += &format!("\t{warning}\n");
+                }
+            }
+        }
+        Err(message)
+    }
+
+    /// Read the snapshot from the given file.
+    fn read(path: &Path) -> Result<Snapshot, String> {
+        let file = std::fs::read_to_string(path).map_err(|_| {
+            format!(
+                "No snapshot found at {}. Run the test with {UPDATE_SNAPSHOTS_VARIABLE}=1 to create it.",
+                path.display()
+            )
+        })?;
+        serde_json::from_str(&file)
+            .map_err(|err| format!("Parsing of snapshot {} failed: {err}", path.display()))
+    }
+
+    /// Write the snapshot to the given file.
+    fn write(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).unwrap() + "\n";
+        std::fs::write(path, json)
+            .map_err(|err| format!("Writing snapshot {} failed: {err}", path.display()))
+    }
+}
+
+impl std::fmt::Display for SnapshotWarning {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "[{}] at {} ({})",
+            self.name,
+            self.address.as_deref().unwrap_or("unknown address"),
+            self.symbols.join(", ")
+        )?;
+        if let Some(line) = self.line {
+            write!(formatter, " in line {line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl CweTestCase {
+    /// Get the full path of the snapshot of the test binary.
+    fn get_snapshot_filepath(&self) -> String {
+        let binary_filepath = self.get_filepath();
+        let binary_filename = Path::new(&binary_filepath).file_name().unwrap();
+        format!(
+            "{}/snapshots/{}.json",
+            helpers::cwd(),
+            binary_filename.to_str().unwrap()
+        )
+    }
+
+    /// Run all checks on the test binary and return the generated warnings.
+    /// Returns stdout + stderr of the execution on failure.
+    pub(crate) fn run_all_checks(&self) -> Result<Snapshot, String> {
+        let output = self.execute_cwe_checker(&["--json", "--quiet"]);
+        if !output.status.success() {
+            return Err(String::from_utf8(output.stdout).unwrap()
+                + &String::from_utf8(output.stderr).unwrap());
+        }
+        Snapshot::from_json_output(&String::from_utf8(output.stdout).unwrap())
+    }
+
+    /// Run all checks on the test binary and compare the warnings with the snapshot of the binary.
+    /// Prints to the shell whether the test case succeeded or not.
+    ///
+    /// In update mode the snapshot is overwritten with the generated warnings.
+    /// Otherwise the generated warnings are written to `<snapshot>.new` if they differ from the snapshot.
+    /// Test binaries that do not exist are skipped unless a snapshot exists for them.
+    pub fn run_snapshot_test(&self) -> Result<(), String> {
+        let filepath = self.get_filepath();
+        let snapshot_filepath = self.get_snapshot_filepath();
+        let snapshot_path = Path::new(&snapshot_filepath);
+        if !Path::new(&filepath).exists() {
+            if snapshot_path.exists() {
+                println!("{} \t {}", filepath, "[FAILED]".red());
+                return Err(format!(
+                    "Test binary for snapshot {snapshot_filepath} not found"
+                ));
+            }
+            println!("{} \t {}", filepath, "[SKIPPED]".yellow());
+            return Ok(());
+        }
+        let snapshot = match self.run_all_checks() {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                println!("{} \t {}", filepath, "[FAILED]".red());
+                return Err(error);
+            }
+        };
+        if std::env::var_os(UPDATE_SNAPSHOTS_VARIABLE).is_some() {
+            if Snapshot::read(snapshot_path).ok().as_ref() == Some(&snapshot) {
+                println!("{} \t {}", filepath, "[OK]".green());
+            } else {
+                snapshot.write(snapshot_path)?;
+                println!("{} \t {}", filepath, "[UPDATED]".yellow());
+            }
+            return Ok(());
+        }
+        let new_snapshot_path = Path::new(&snapshot_filepath).with_extension("json.new");
+        let _ = std::fs::remove_file(&new_snapshot_path);
+        match Snapshot::read(snapshot_path).and_then(|expected| snapshot.compare(&expected)) {
+            Ok(()) => {
+                println!("{} \t {}", filepath, "[OK]".green());
+                Ok(())
+            }
+            Err(error) => {
+                println!("{} \t {}", filepath, "[FAILED]".red());
+                match snapshot.write(&new_snapshot_path) {
+                    Ok(()) => Err(format!(
+                        "{}\nGenerated warnings written to {}",
+                        error.trim_end(),
+                        new_snapshot_path.display()
+                    )),
+                    Err(write_error) => Err(format!("{}\n{write_error}", error.trim_end())),
+                }
+            }
+        }
+    }
+}
+
+/// Return the test cases of all binaries in the snapshot test corpus.
+///
+/// The check name of the test cases is empty, since snapshot tests run all checks.
+pub fn snapshot_test_cases() -> Vec<CweTestCase> {
+    SNAPSHOT_CORPUS
+        .iter()
+        .flat_map(|cwe| all_test_cases(cwe, ""))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comparing_snapshots() {
+        let output = r#"[
+            {"name": "CWE476", "version": "0.3", "addresses": ["0x1234"], "tids": [],
+             "symbols": ["malloc"], "other": [], "description": "NULL Pointer Dereference"},
+            {"name": "CWE676", "version": "0.1", "addresses": ["0x1250", "0x1240"], "tids": [],
+             "symbols": ["strcpy"], "other": [], "description": "Use of dangerous function",
+             "source_location": {"file": "cwe_676.c", "line": 8, "variables": []}}
+        ]"#;
+        let snapshot = Snapshot::from_json_output(output).unwrap();
+        assert_eq!(snapshot.warnings.len(), 2);
+        assert!(snapshot.compare(&snapshot.clone()).is_ok());
+
+        let mut expected = snapshot.clone();
+        expected.warnings.insert(SnapshotWarning {
+            name: "CWE476".to_string(),
+            address: Some("0x1300".to_string()),
+            symbols: vec!["malloc".to_string()],
+            line: None,
+        });
+        expected.warnings.retain(|warning| warning.name != "CWE676");
+        assert_eq!(
+            snapshot.compare(&expected).unwrap_err(),
+            "Missing warnings:\n\t[CWE476] at 0x1300 (malloc)\n\
+             Unexpected warnings:\n\t[CWE676] at 0x1250 (strcpy) in line 8\n"
+        );
+
+        // Snapshots are stable under serialization.
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+    }
+}